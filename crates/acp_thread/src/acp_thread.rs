@@ -51,6 +51,12 @@ pub struct Checkpoint {
     pub show: bool,
 }
 
+impl Checkpoint {
+    pub fn git_checkpoint(&self) -> &GitStoreCheckpoint {
+        &self.git_checkpoint
+    }
+}
+
 impl UserMessage {
     fn to_markdown(&self, cx: &App) -> String {
         let mut markdown = String::new();
@@ -815,6 +821,7 @@ pub struct AcpThread {
     token_usage: Option<TokenUsage>,
     prompt_capabilities: acp::PromptCapabilities,
     _observe_prompt_capabilities: Task<anyhow::Result<()>>,
+    available_commands: Vec<acp::AvailableCommand>,
     terminals: HashMap<acp::TerminalId, Entity<Terminal>>,
     pending_terminal_output: HashMap<acp::TerminalId, Vec<Vec<u8>>>,
     pending_terminal_exit: HashMap<acp::TerminalId, acp::TerminalExitStatus>,
@@ -1045,6 +1052,7 @@ impl AcpThread {
             token_usage: None,
             prompt_capabilities,
             _observe_prompt_capabilities: task,
+            available_commands: Vec::new(),
             terminals: HashMap::default(),
             pending_terminal_output: HashMap::default(),
             pending_terminal_exit: HashMap::default(),
@@ -1055,6 +1063,20 @@ impl AcpThread {
         self.prompt_capabilities.clone()
     }
 
+    /// The slash commands most recently advertised by the agent.
+    pub fn available_commands(&self) -> &[acp::AvailableCommand] {
+        &self.available_commands
+    }
+
+    pub fn update_available_commands(
+        &mut self,
+        available_commands: Vec<acp::AvailableCommand>,
+        cx: &mut Context<Self>,
+    ) {
+        self.available_commands = available_commands.clone();
+        cx.emit(AcpThreadEvent::AvailableCommandsUpdated(available_commands));
+    }
+
     pub fn connection(&self) -> &Rc<dyn AgentConnection> {
         &self.connection
     }
@@ -1149,7 +1171,7 @@ impl AcpThread {
             acp::SessionUpdate::AvailableCommandsUpdate(acp::AvailableCommandsUpdate {
                 available_commands,
                 ..
-            }) => cx.emit(AcpThreadEvent::AvailableCommandsUpdated(available_commands)),
+            }) => self.update_available_commands(available_commands, cx),
             acp::SessionUpdate::CurrentModeUpdate(acp::CurrentModeUpdate {
                 current_mode_id,
                 ..
@@ -46,6 +46,11 @@ pub enum MentionUri {
     Fetch {
         url: Url,
     },
+    McpResource {
+        server_id: String,
+        uri: Url,
+        name: String,
+    },
 }
 
 impl MentionUri {
@@ -159,6 +164,21 @@ impl MentionUri {
                     Ok(Self::Directory {
                         abs_path: path.into(),
                     })
+                } else if let Some(server_id) = path.strip_prefix("/agent/mcp-resource/") {
+                    let mut resource_uri = None;
+                    let mut name = None;
+                    for (key, value) in url.query_pairs() {
+                        match key.as_ref() {
+                            "uri" => resource_uri = Some(Url::parse(&value)?),
+                            "name" => name = Some(value.into_owned()),
+                            _ => bail!("invalid query parameter"),
+                        }
+                    }
+                    Ok(Self::McpResource {
+                        server_id: server_id.to_string(),
+                        uri: resource_uri.context("Missing uri for MCP resource")?,
+                        name: name.context("Missing name for MCP resource")?,
+                    })
                 } else if path.starts_with("/agent/selection") {
                     let fragment = url.fragment().context("Missing fragment for selection")?;
                     let line_range = parse_line_range(fragment)?;
@@ -195,6 +215,7 @@ impl MentionUri {
                 ..
             } => selection_name(path.as_deref(), line_range),
             MentionUri::Fetch { url } => url.to_string(),
+            MentionUri::McpResource { name, .. } => name.clone(),
         }
     }
 
@@ -212,6 +233,7 @@ impl MentionUri {
             MentionUri::Rule { .. } => IconName::Reader.path().into(),
             MentionUri::Selection { .. } => IconName::Reader.path().into(),
             MentionUri::Fetch { .. } => IconName::ToolWeb.path().into(),
+            MentionUri::McpResource { .. } => IconName::Server.path().into(),
         }
     }
 
@@ -289,6 +311,18 @@ impl MentionUri {
                 url
             }
             MentionUri::Fetch { url } => url.clone(),
+            MentionUri::McpResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                let mut url = Url::parse("zed:///").unwrap();
+                url.set_path(&format!("/agent/mcp-resource/{server_id}"));
+                url.query_pairs_mut()
+                    .append_pair("uri", uri.as_str())
+                    .append_pair("name", name);
+                url
+            }
         }
     }
 }
@@ -480,6 +514,26 @@ mod tests {
         assert_eq!(parsed.to_uri().to_string(), https_uri);
     }
 
+    #[test]
+    fn test_parse_mcp_resource_uri() {
+        let mcp_uri =
+            "zed:///agent/mcp-resource/github?uri=repo%3A%2F%2Fzed%2FREADME.md&name=README";
+        let parsed = MentionUri::parse(mcp_uri, PathStyle::local()).unwrap();
+        match &parsed {
+            MentionUri::McpResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                assert_eq!(server_id, "github");
+                assert_eq!(uri.as_str(), "repo://zed/README.md");
+                assert_eq!(name, "README");
+            }
+            _ => panic!("Expected McpResource variant"),
+        }
+        assert_eq!(parsed.to_uri().to_string(), mcp_uri);
+    }
+
     #[test]
     fn test_invalid_scheme() {
         assert!(MentionUri::parse("ftp://example.com", PathStyle::local()).is_err());
@@ -5,9 +5,11 @@ mod history_store;
 mod legacy_thread;
 mod native_agent_server;
 pub mod outline;
+mod spend;
 mod templates;
 mod thread;
 mod tools;
+pub mod worktree;
 
 #[cfg(test)]
 mod tests;
@@ -16,6 +18,7 @@ pub use agent_activity::{ActivityStatusChanged, AgentActivityStatus, AgentActivi
 pub use db::*;
 pub use history_store::*;
 pub use native_agent_server::NativeAgentServer;
+pub use spend::SpendTracker;
 pub use templates::*;
 pub use thread::*;
 pub use tools::*;
@@ -277,6 +280,12 @@ impl NativeAgent {
             if let Some(prompt_store) = prompt_store.as_ref() {
                 subscriptions.push(cx.subscribe(prompt_store, Self::handle_prompts_updated_event))
             }
+            let context_server_registry = cx
+                .new(|cx| ContextServerRegistry::new(project.read(cx).context_server_store(), cx));
+            subscriptions.push(cx.subscribe(
+                &context_server_registry,
+                Self::handle_context_server_registry_event,
+            ));
 
             let (project_context_needs_refresh_tx, project_context_needs_refresh_rx) =
                 watch::channel(());
@@ -288,9 +297,7 @@ impl NativeAgent {
                 _maintain_project_context: cx.spawn(async move |this, cx| {
                     Self::maintain_project_context(this, project_context_needs_refresh_rx, cx).await
                 }),
-                context_server_registry: cx.new(|cx| {
-                    ContextServerRegistry::new(project.read(cx).context_server_store(), cx)
-                }),
+                context_server_registry,
                 templates,
                 models: LanguageModels::new(cx),
                 project,
@@ -327,6 +334,11 @@ impl NativeAgent {
             )
         });
 
+        let available_commands = self.available_commands(cx);
+        acp_thread.update(cx, |acp_thread, cx| {
+            acp_thread.update_available_commands(available_commands, cx)
+        });
+
         let registry = LanguageModelRegistry::read_global(cx);
         let summarization_model = registry.thread_summary_model().map(|c| c.model);
 
@@ -608,6 +620,96 @@ impl NativeAgent {
         self.project_context_needs_refresh.send(()).ok();
     }
 
+    fn handle_context_server_registry_event(
+        &mut self,
+        _registry: Entity<ContextServerRegistry>,
+        event: &ContextServerRegistryEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            ContextServerRegistryEvent::PromptsChanged => {
+                let available_commands = self.available_commands(cx);
+                for session in self.sessions.values() {
+                    session
+                        .acp_thread
+                        .update(cx, |acp_thread, cx| {
+                            acp_thread.update_available_commands(available_commands.clone(), cx)
+                        })
+                        .ok();
+                }
+            }
+        }
+    }
+
+    /// Prompts provided by context servers are offered as slash commands.
+    fn available_commands(&self, cx: &App) -> Vec<acp::AvailableCommand> {
+        self.context_server_registry
+            .read(cx)
+            .prompts()
+            .map(|prompt| {
+                let arguments = prompt.prompt.arguments.as_deref().unwrap_or_default();
+                acp::AvailableCommand {
+                    name: prompt.prompt.name.clone(),
+                    description: prompt.prompt.description.clone().unwrap_or_else(|| {
+                        format!("Run '{}' from {}", prompt.prompt.name, prompt.server_id)
+                    }),
+                    input: (!arguments.is_empty()).then(|| {
+                        acp::AvailableCommandInput::Unstructured {
+                            hint: arguments
+                                .iter()
+                                .map(|argument| {
+                                    if argument.required == Some(true) {
+                                        format!("<{}>", argument.name)
+                                    } else {
+                                        format!("[{}]", argument.name)
+                                    }
+                                })
+                                .collect::<Vec<_>>()
+                                .join(" "),
+                        }
+                    }),
+                    meta: None,
+                }
+            })
+            .collect()
+    }
+
+    /// Replaces a leading slash command naming a context server prompt with
+    /// the prompt's messages.
+    fn expand_slash_command(
+        &self,
+        mut content: Vec<UserMessageContent>,
+        cx: &App,
+    ) -> Task<Result<Vec<UserMessageContent>>> {
+        let Some(UserMessageContent::Text(text)) = content.first() else {
+            return Task::ready(Ok(content));
+        };
+        let Some(command) = text.trim_start().strip_prefix('/') else {
+            return Task::ready(Ok(content));
+        };
+        let (name, input) = command
+            .split_once(char::is_whitespace)
+            .unwrap_or((command, ""));
+        let registry = self.context_server_registry.read(cx);
+        let Some(prompt) = registry.find_prompt(name) else {
+            return Task::ready(Ok(content));
+        };
+
+        let messages = registry.load_prompt(prompt, input, cx);
+        cx.background_spawn(async move {
+            let messages = messages.await?;
+            content.splice(
+                ..1,
+                messages.into_iter().map(|mut message| {
+                    // Servers might return CR characters.
+                    text::LineEnding::normalize(&mut message);
+                    UserMessageContent::Text(message)
+                }),
+            );
+            Ok(content)
+        })
+    }
+
     fn handle_models_updated_event(
         &mut self,
         _registry: Entity<LanguageModelRegistry>,
@@ -716,6 +818,46 @@ impl NativeAgent {
         })
     }
 
+    /// Merges or discards the changes of a thread running in a git worktree,
+    /// then removes the worktree and detaches the thread from it. Returns the
+    /// paths that the merge left with conflict markers.
+    ///
+    /// The worktree is kept if merging fails, so that it can be merged again.
+    /// A merge with conflicts has already applied the changes, so the
+    /// worktree is removed as usual.
+    pub fn resolve_worktree(
+        &mut self,
+        session_id: &acp::SessionId,
+        resolution: WorktreeResolution,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<String>>> {
+        let Some(session) = self.sessions.get(session_id) else {
+            return Task::ready(Err(anyhow!("Session not found")));
+        };
+        let thread = session.thread.clone();
+        let Some(thread_worktree) = thread.read(cx).worktree().cloned() else {
+            return Task::ready(Err(anyhow!("Thread is not running in a worktree")));
+        };
+
+        let fs = self.fs.clone();
+        let merge = match resolution {
+            WorktreeResolution::Merge => {
+                Some(worktree::merge_worktree(&thread_worktree, fs.clone(), cx))
+            }
+            WorktreeResolution::Discard => None,
+        };
+        cx.spawn(async move |_, cx| {
+            let conflicts = match merge {
+                Some(merge) => merge.await?,
+                None => Vec::new(),
+            };
+            cx.update(|cx| worktree::remove_worktree(&thread_worktree, fs, cx))?
+                .await?;
+            thread.update(cx, |thread, cx| thread.set_worktree(None, cx))?;
+            Ok(conflicts)
+        })
+    }
+
     fn save_thread(&mut self, thread: Entity<Thread>, cx: &mut Context<Self>) {
         if thread.read(cx).is_empty() {
             return;
@@ -739,6 +881,15 @@ impl NativeAgent {
     }
 }
 
+/// What to do with the changes of a thread that runs in its own git worktree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorktreeResolution {
+    /// Apply the worktree's changes to the original checkout.
+    Merge,
+    /// Throw the worktree's changes away.
+    Discard,
+}
+
 /// Wrapper struct that implements the AgentConnection trait
 #[derive(Clone)]
 pub struct NativeAgentConnection(pub Entity<NativeAgent>);
@@ -756,6 +907,17 @@ impl NativeAgentConnection {
         self.0.update(cx, |this, cx| this.load_thread(id, cx))
     }
 
+    pub fn resolve_worktree(
+        &self,
+        session_id: &acp::SessionId,
+        resolution: WorktreeResolution,
+        cx: &mut App,
+    ) -> Task<Result<Vec<String>>> {
+        self.0.update(cx, |this, cx| {
+            this.resolve_worktree(session_id, resolution, cx)
+        })
+    }
+
     /// Returns a reference to the activity tracker for this native agent.
     /// This can be used to subscribe to agent activity changes for collaborative features.
     pub fn activity_tracker(&self, cx: &App) -> Entity<AgentActivityTracker> {
@@ -1081,17 +1243,26 @@ impl acp_thread::AgentConnection for NativeAgentConnection {
         log::debug!("Prompt blocks count: {}", params.prompt.len());
         let path_style = self.0.read(cx).project.read(cx).path_style(cx);
 
-        self.run_turn(session_id, cx, move |thread, cx| {
-            let content: Vec<UserMessageContent> = params
-                .prompt
-                .into_iter()
-                .map(|block| UserMessageContent::from_content_block(block, path_style))
-                .collect::<Vec<_>>();
+        let content: Vec<UserMessageContent> = params
+            .prompt
+            .into_iter()
+            .map(|block| UserMessageContent::from_content_block(block, path_style))
+            .collect::<Vec<_>>();
+        let content = self.0.read(cx).expand_slash_command(content, cx);
+
+        let this = self.clone();
+        cx.spawn(async move |cx| {
+            let content = content.await?;
             log::debug!("Converted prompt to message: {} chars", content.len());
             log::debug!("Message id: {:?}", id);
             log::debug!("Message content: {:?}", content);
 
-            thread.update(cx, |thread, cx| thread.send(id, content, cx))
+            cx.update(|cx| {
+                this.run_turn(session_id, cx, move |thread, cx| {
+                    thread.update(cx, |thread, cx| thread.send(id, content, cx))
+                })
+            })?
+            .await
         })
     }
 
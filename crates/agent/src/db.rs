@@ -2,12 +2,14 @@ use crate::{AgentMessage, AgentMessageContent, UserMessage, UserMessageContent};
 use acp_thread::UserMessageId;
 use agent_client_protocol as acp;
 use agent_settings::{AgentProfileId, CompletionMode};
-use anyhow::{Result, anyhow};
+use anyhow::{Context as _, Result, anyhow};
 use chrono::{DateTime, Utc};
-use collections::{HashMap, IndexMap};
+use collections::{BTreeSet, HashMap, IndexMap};
 use futures::{FutureExt, future::Shared};
 use gpui::{BackgroundExecutor, Global, Task};
-use indoc::indoc;
+use indoc::{formatdoc, indoc};
+use itertools::Itertools as _;
+use language_model::LanguageModelToolUseId;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use sqlez::{
@@ -15,7 +17,7 @@ use sqlez::{
     connection::Connection,
     statement::Statement,
 };
-use std::sync::Arc;
+use std::{fmt::Write as _, path::PathBuf, sync::Arc};
 use ui::{App, SharedString};
 use zed_env_vars::ZED_STATELESS;
 
@@ -31,6 +33,148 @@ pub struct DbThreadMetadata {
     pub updated_at: DateTime<Utc>,
 }
 
+/// A portable representation of a thread, used to hand off a thread to
+/// another Zed installation.
+///
+/// The embedded thread carries its own [`DbThread::VERSION`], so older
+/// exports are upgraded the same way as threads stored in the database.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedThread {
+    pub format: String,
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub thread: serde_json::Value,
+    /// Added in format version 2.
+    #[serde(default)]
+    pub checkpoints: Vec<ExportedCheckpoint>,
+    /// Added in format version 2.
+    #[serde(default)]
+    pub diffs: Vec<ExportedDiff>,
+}
+
+/// The git checkpoint taken before a user message was sent.
+///
+/// Checkpoints reference commits that only exist in the exporting machine's
+/// repositories, so they are kept as metadata and aren't restored on import.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedCheckpoint {
+    pub user_message_id: UserMessageId,
+    pub repositories: Vec<ExportedRepositoryCheckpoint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedRepositoryCheckpoint {
+    pub work_directory: PathBuf,
+    pub commit_sha: String,
+}
+
+/// The changes a tool call made to a file, as a unified diff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedDiff {
+    pub tool_use_id: LanguageModelToolUseId,
+    pub path: PathBuf,
+    pub diff: String,
+}
+
+impl ExportedThread {
+    pub const FORMAT: &'static str = "zed-agent-thread";
+    pub const FORMAT_VERSION: u32 = 2;
+    pub const FILE_EXTENSION: &'static str = "zed-thread.json";
+
+    pub fn new(
+        thread: &DbThread,
+        checkpoints: Vec<ExportedCheckpoint>,
+        diffs: Vec<ExportedDiff>,
+    ) -> Result<Self> {
+        Ok(Self {
+            format: Self::FORMAT.to_string(),
+            format_version: Self::FORMAT_VERSION,
+            exported_at: Utc::now(),
+            thread: serde_json::to_value(SerializedDbThread::new(thread))?,
+            checkpoints,
+            diffs,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &[u8]) -> Result<Self> {
+        let exported =
+            serde_json::from_slice::<Self>(json).context("file is not an exported agent thread")?;
+        anyhow::ensure!(
+            exported.format == Self::FORMAT,
+            "unknown thread export format {:?}",
+            exported.format
+        );
+        anyhow::ensure!(
+            exported.format_version <= Self::FORMAT_VERSION,
+            "thread was exported by a newer version of Zed (format version {})",
+            exported.format_version
+        );
+        Ok(exported)
+    }
+
+    pub fn db_thread(&self) -> Result<DbThread> {
+        DbThread::from_json(&serde_json::to_vec(&self.thread)?)
+    }
+}
+
+#[derive(Serialize)]
+struct SerializedDbThread<'a> {
+    #[serde(flatten)]
+    thread: &'a DbThread,
+    version: &'static str,
+}
+
+impl<'a> SerializedDbThread<'a> {
+    fn new(thread: &'a DbThread) -> Self {
+        Self {
+            thread,
+            version: DbThread::VERSION,
+        }
+    }
+}
+
+/// Restricts which threads are returned by [`ThreadsDatabase::search_threads`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadSearchFilter {
+    pub updated_after: Option<DateTime<Utc>>,
+    /// Formatted as `provider/model`, see [`DbThread::search_model_id`].
+    pub model: Option<String>,
+    pub profile: Option<String>,
+    /// Absolute path of one of the worktrees the thread was started in.
+    pub project: Option<String>,
+}
+
+impl ThreadSearchFilter {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ThreadSearchQuery {
+    pub text: String,
+    pub filter: ThreadSearchFilter,
+}
+
+#[derive(Debug, Clone)]
+pub struct ThreadSearchMatch {
+    pub metadata: DbThreadMetadata,
+    /// A short excerpt of the content that matched the query, if any.
+    pub snippet: Option<SharedString>,
+}
+
+/// The distinct values that threads can be filtered by.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadSearchFacets {
+    pub models: Vec<String>,
+    pub profiles: Vec<String>,
+    pub projects: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DbThread {
     pub title: SharedString,
@@ -44,12 +188,59 @@ pub struct DbThread {
     pub cumulative_token_usage: language_model::TokenUsage,
     #[serde(default)]
     pub request_token_usage: HashMap<acp_thread::UserMessageId, language_model::TokenUsage>,
+    /// What the thread has spent on completion requests, in US dollars.
+    #[serde(default)]
+    pub cost: f64,
     #[serde(default)]
     pub model: Option<DbLanguageModel>,
     #[serde(default)]
     pub completion_mode: Option<CompletionMode>,
     #[serde(default)]
     pub profile: Option<AgentProfileId>,
+    #[serde(default)]
+    pub forked_from: Option<ThreadForkOrigin>,
+    #[serde(default)]
+    pub worktree: Option<ThreadWorktree>,
+    #[serde(default)]
+    pub model_fallbacks: Vec<ModelFallback>,
+}
+
+/// The thread a forked thread was created from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadForkOrigin {
+    pub thread_id: acp::SessionId,
+    pub thread_title: SharedString,
+}
+
+/// A switch to one of the profile's fallback models, made when the thread's
+/// model couldn't complete a request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelFallback {
+    /// The user message whose turn the switch happened in.
+    pub user_message_id: UserMessageId,
+    pub from: DbLanguageModel,
+    pub to: DbLanguageModel,
+    pub reason: ModelFallbackReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelFallbackReason {
+    /// The model kept failing after being retried.
+    Error,
+    /// The conversation didn't fit in the model's context window.
+    ContextOverflow,
+}
+
+/// A git worktree that a thread's changes are isolated in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadWorktree {
+    /// The working directory of the repository the worktree was created from.
+    pub repository_path: PathBuf,
+    pub worktree_path: PathBuf,
+    pub branch: String,
+    /// The commit the worktree's branch was created from.
+    pub base_commit: String,
 }
 
 impl DbThread {
@@ -70,6 +261,68 @@ impl DbThread {
         }
     }
 
+    pub fn search_model_id(&self) -> Option<String> {
+        self.model
+            .as_ref()
+            .map(|model| format!("{}/{}", model.provider, model.model))
+    }
+
+    fn search_document(&self) -> ThreadSearchDocument {
+        let mut document = ThreadSearchDocument::default();
+
+        for message in &self.messages {
+            match message {
+                crate::Message::User(message) => {
+                    for content in &message.content {
+                        match content {
+                            UserMessageContent::Text(text) => {
+                                writeln!(document.messages, "{text}").ok();
+                            }
+                            UserMessageContent::Mention { uri, .. } => {
+                                writeln!(document.messages, "{}", uri.name()).ok();
+                                if let Some(path) = mention_path(uri) {
+                                    document.push_path(path);
+                                }
+                            }
+                            UserMessageContent::Image(_) => {}
+                        }
+                    }
+                }
+                crate::Message::Agent(message) => {
+                    for content in &message.content {
+                        match content {
+                            AgentMessageContent::Text(text) => {
+                                writeln!(document.messages, "{text}").ok();
+                            }
+                            AgentMessageContent::ToolUse(tool_use) => {
+                                writeln!(
+                                    document.tool_calls,
+                                    "{} {}",
+                                    tool_use.name, tool_use.raw_input
+                                )
+                                .ok();
+                                collect_input_paths(&tool_use.input, &mut document);
+                            }
+                            AgentMessageContent::Thinking { .. }
+                            | AgentMessageContent::RedactedThinking(_) => {}
+                        }
+                    }
+                }
+                crate::Message::Resume => {}
+            }
+        }
+
+        if let Some(snapshot) = &self.initial_project_snapshot {
+            document.projects = snapshot
+                .worktree_snapshots
+                .iter()
+                .map(|worktree| worktree.worktree_path.as_str())
+                .join("\n");
+        }
+
+        document
+    }
+
     fn upgrade_from_agent_1(thread: crate::legacy_thread::SerializedThread) -> Result<Self> {
         let mut messages = Vec::new();
         let mut request_token_usage = HashMap::default();
@@ -206,13 +459,90 @@ impl DbThread {
             initial_project_snapshot: thread.initial_project_snapshot,
             cumulative_token_usage: thread.cumulative_token_usage,
             request_token_usage,
+            cost: 0.,
             model: thread.model,
             completion_mode: thread.completion_mode,
             profile: thread.profile,
+            forked_from: None,
+            worktree: None,
+            model_fallbacks: Vec::new(),
         })
     }
 }
 
+/// The text of a thread that gets written to the full-text search index.
+#[derive(Debug, Default)]
+struct ThreadSearchDocument {
+    messages: String,
+    tool_calls: String,
+    paths: String,
+    /// Newline-separated worktree paths.
+    projects: String,
+}
+
+impl ThreadSearchDocument {
+    fn push_path(&mut self, path: &str) {
+        if !self.paths.lines().any(|existing| existing == path) {
+            writeln!(self.paths, "{path}").ok();
+        }
+    }
+}
+
+fn mention_path(uri: &acp_thread::MentionUri) -> Option<&str> {
+    use acp_thread::MentionUri;
+
+    match uri {
+        MentionUri::File { abs_path }
+        | MentionUri::Directory { abs_path }
+        | MentionUri::Symbol { abs_path, .. }
+        | MentionUri::Selection {
+            abs_path: Some(abs_path),
+            ..
+        } => abs_path.to_str(),
+        _ => None,
+    }
+}
+
+/// Tools that operate on files take them as `path`, `source_path`,
+/// `destination_path`, etc.
+fn collect_input_paths(input: &serde_json::Value, document: &mut ThreadSearchDocument) {
+    match input {
+        serde_json::Value::Object(object) => {
+            for (key, value) in object {
+                match value {
+                    serde_json::Value::String(path) if key.ends_with("path") => {
+                        document.push_path(path);
+                    }
+                    serde_json::Value::Object(_) | serde_json::Value::Array(_) => {
+                        collect_input_paths(value, document);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for value in values {
+                collect_input_paths(value, document);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Turns free-form user input into an FTS5 query where every word is
+/// matched as a prefix, and all words must be present.
+fn fts_query(text: &str) -> Option<String> {
+    let terms = text
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DataType {
     #[serde(rename = "json")]
@@ -306,14 +636,96 @@ impl ThreadsDatabase {
         "})?()
         .map_err(|e| anyhow!("Failed to create threads table: {}", e))?;
 
+        connection.exec(indoc! {"
+            CREATE VIRTUAL TABLE IF NOT EXISTS threads_search USING fts5(
+                id UNINDEXED,
+                title,
+                messages,
+                tool_calls,
+                paths,
+                model UNINDEXED,
+                profile UNINDEXED,
+                projects UNINDEXED,
+                tokenize = 'unicode61'
+            )
+        "})?()
+        .map_err(|e| anyhow!("Failed to create threads search table: {}", e))?;
+
+        let connection = Arc::new(Mutex::new(connection));
+        Self::index_unindexed_threads(&connection)?;
+
         let db = Self {
             executor,
-            connection: Arc::new(Mutex::new(connection)),
+            connection,
         };
 
         Ok(db)
     }
 
+    /// Threads saved before the search index existed are indexed on startup.
+    fn index_unindexed_threads(connection: &Arc<Mutex<Connection>>) -> Result<()> {
+        let connection = connection.lock();
+        let mut select = connection.select::<(Arc<str>, DataType, Vec<u8>)>(indoc! {"
+            SELECT id, data_type, data FROM threads
+            WHERE id NOT IN (SELECT id FROM threads_search)
+        "})?;
+
+        for (id, data_type, data) in select()? {
+            match Self::decode_thread(data_type, data) {
+                Ok(thread) => Self::index_thread_sync(&connection, &id, &thread)?,
+                Err(error) => log::error!("Failed to index thread {id}: {error:#}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn decode_thread(data_type: DataType, data: Vec<u8>) -> Result<DbThread> {
+        let json_data = match data_type {
+            DataType::Zstd => {
+                let decompressed = zstd::decode_all(&data[..])?;
+                String::from_utf8(decompressed)?
+            }
+            DataType::Json => String::from_utf8(data)?,
+        };
+        DbThread::from_json(json_data.as_bytes())
+    }
+
+    fn index_thread_sync(connection: &Connection, id: &Arc<str>, thread: &DbThread) -> Result<()> {
+        let document = thread.search_document();
+
+        let mut delete = connection.exec_bound::<Arc<str>>(indoc! {"
+            DELETE FROM threads_search WHERE id = ?
+        "})?;
+        delete(id.clone())?;
+
+        let mut insert = connection.exec_bound::<(
+            Arc<str>,
+            String,
+            String,
+            String,
+            String,
+            Option<String>,
+            Option<String>,
+            String,
+        )>(indoc! {"
+            INSERT INTO threads_search (id, title, messages, tool_calls, paths, model, profile, projects)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "})?;
+        insert((
+            id.clone(),
+            thread.title.to_string(),
+            document.messages,
+            document.tool_calls,
+            document.paths,
+            thread.search_model_id(),
+            thread.profile.as_ref().map(|profile| profile.to_string()),
+            document.projects,
+        ))?;
+
+        Ok(())
+    }
+
     fn save_thread_sync(
         connection: &Arc<Mutex<Connection>>,
         id: acp::SessionId,
@@ -321,19 +733,9 @@ impl ThreadsDatabase {
     ) -> Result<()> {
         const COMPRESSION_LEVEL: i32 = 3;
 
-        #[derive(Serialize)]
-        struct SerializedThread {
-            #[serde(flatten)]
-            thread: DbThread,
-            version: &'static str,
-        }
-
         let title = thread.title.to_string();
         let updated_at = thread.updated_at.to_rfc3339();
-        let json_data = serde_json::to_string(&SerializedThread {
-            thread,
-            version: DbThread::VERSION,
-        })?;
+        let json_data = serde_json::to_string(&SerializedDbThread::new(&thread))?;
 
         let connection = connection.lock();
 
@@ -341,13 +743,15 @@ impl ThreadsDatabase {
         let data_type = DataType::Zstd;
         let data = compressed;
 
-        let mut insert = connection.exec_bound::<(Arc<str>, String, String, DataType, Vec<u8>)>(indoc! {"
-            INSERT OR REPLACE INTO threads (id, summary, updated_at, data_type, data) VALUES (?, ?, ?, ?, ?)
-        "})?;
+        connection.with_savepoint("save_thread", || {
+            let mut insert = connection.exec_bound::<(Arc<str>, String, String, DataType, Vec<u8>)>(indoc! {"
+                INSERT OR REPLACE INTO threads (id, summary, updated_at, data_type, data) VALUES (?, ?, ?, ?, ?)
+            "})?;
 
-        insert((id.0, title, updated_at, data_type, data))?;
+            insert((id.0.clone(), title, updated_at, data_type, data))?;
 
-        Ok(())
+            Self::index_thread_sync(&connection, &id.0, &thread)
+        })
     }
 
     pub fn list_threads(&self) -> Task<Result<Vec<DbThreadMetadata>>> {
@@ -387,21 +791,128 @@ impl ThreadsDatabase {
 
             let rows = select(id.0)?;
             if let Some((data_type, data)) = rows.into_iter().next() {
-                let json_data = match data_type {
-                    DataType::Zstd => {
-                        let decompressed = zstd::decode_all(&data[..])?;
-                        String::from_utf8(decompressed)?
-                    }
-                    DataType::Json => String::from_utf8(data)?,
-                };
-                let thread = DbThread::from_json(json_data.as_bytes())?;
-                Ok(Some(thread))
+                Ok(Some(Self::decode_thread(data_type, data)?))
             } else {
                 Ok(None)
             }
         })
     }
 
+    /// Searches the title, messages, tool calls and touched paths of all
+    /// threads. Results are ranked by relevance, or by recency when the query
+    /// text is empty.
+    pub fn search_threads(
+        &self,
+        query: ThreadSearchQuery,
+        limit: usize,
+    ) -> Task<Result<Vec<ThreadSearchMatch>>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+            let ThreadSearchFilter {
+                updated_after,
+                model,
+                profile,
+                project,
+            } = query.filter;
+            let updated_after = updated_after.map(|updated_after| updated_after.to_rfc3339());
+
+            // The filter's numbered parameters are shared by both queries below.
+            const FILTER: &str = indoc! {"
+                (?1 IS NULL OR julianday(threads.updated_at) >= julianday(?1))
+                AND (?2 IS NULL OR threads_search.model = ?2)
+                AND (?3 IS NULL OR threads_search.profile = ?3)
+                AND (?4 IS NULL OR instr(
+                    char(10) || threads_search.projects || char(10),
+                    char(10) || ?4 || char(10)
+                ) > 0)
+            "};
+            let filter = (updated_after, model, profile, project, limit);
+
+            let rows = if let Some(match_expression) = fts_query(&query.text) {
+                let mut select = connection.select_bound::<_, (Arc<str>, String, String, String)>(
+                    &formatdoc! {"
+                        SELECT threads.id, threads.summary, threads.updated_at,
+                            snippet(threads_search, -1, '', '', '…', 16)
+                        FROM threads_search JOIN threads ON threads.id = threads_search.id
+                        WHERE threads_search MATCH ?6 AND {FILTER}
+                        ORDER BY bm25(threads_search, 0.0, 10.0, 4.0, 2.0, 2.0)
+                        LIMIT ?5
+                    "},
+                )?;
+                select((filter, match_expression))?
+                    .into_iter()
+                    .map(|(id, summary, updated_at, snippet)| {
+                        (id, summary, updated_at, Some(snippet))
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                let mut select =
+                    connection.select_bound::<_, (Arc<str>, String, String)>(&formatdoc! {"
+                        SELECT threads.id, threads.summary, threads.updated_at
+                        FROM threads LEFT JOIN threads_search ON threads.id = threads_search.id
+                        WHERE {FILTER}
+                        ORDER BY threads.updated_at DESC
+                        LIMIT ?5
+                    "})?;
+                select(filter)?
+                    .into_iter()
+                    .map(|(id, summary, updated_at)| (id, summary, updated_at, None))
+                    .collect::<Vec<_>>()
+            };
+
+            let mut matches = Vec::with_capacity(rows.len());
+            for (id, summary, updated_at, snippet) in rows {
+                matches.push(ThreadSearchMatch {
+                    metadata: DbThreadMetadata {
+                        id: acp::SessionId(id),
+                        title: summary.into(),
+                        updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
+                    },
+                    snippet: snippet
+                        .map(|snippet| snippet.split_whitespace().join(" "))
+                        .filter(|snippet| !snippet.is_empty())
+                        .map(SharedString::from),
+                });
+            }
+
+            Ok(matches)
+        })
+    }
+
+    pub fn search_facets(&self) -> Task<Result<ThreadSearchFacets>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+            let mut select =
+                connection.select::<(Option<String>, Option<String>, String)>(indoc! {"
+                    SELECT model, profile, projects FROM threads_search
+                "})?;
+
+            let mut models = BTreeSet::default();
+            let mut profiles = BTreeSet::default();
+            let mut projects = BTreeSet::default();
+            for (model, profile, thread_projects) in select()? {
+                models.extend(model);
+                profiles.extend(profile);
+                projects.extend(
+                    thread_projects
+                        .lines()
+                        .filter(|project| !project.is_empty())
+                        .map(ToOwned::to_owned),
+                );
+            }
+
+            Ok(ThreadSearchFacets {
+                models: models.into_iter().collect(),
+                profiles: profiles.into_iter().collect(),
+                projects: projects.into_iter().collect(),
+            })
+        })
+    }
+
     pub fn save_thread(&self, id: acp::SessionId, thread: DbThread) -> Task<Result<()>> {
         let connection = self.connection.clone();
 
@@ -415,13 +926,254 @@ impl ThreadsDatabase {
         self.executor.spawn(async move {
             let connection = connection.lock();
 
-            let mut delete = connection.exec_bound::<Arc<str>>(indoc! {"
-                DELETE FROM threads WHERE id = ?
-            "})?;
+            connection.with_savepoint("delete_thread", || {
+                let mut delete = connection.exec_bound::<Arc<str>>(indoc! {"
+                    DELETE FROM threads WHERE id = ?
+                "})?;
+                delete(id.0.clone())?;
 
-            delete(id.0)?;
+                let mut delete_search = connection.exec_bound::<Arc<str>>(indoc! {"
+                    DELETE FROM threads_search WHERE id = ?
+                "})?;
+                delete_search(id.0)?;
 
-            Ok(())
+                Ok(())
+            })
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone as _;
+    use gpui::TestAppContext;
+    use language_model::LanguageModelToolUse;
+    use serde_json::json;
+
+    fn thread(
+        title: &str,
+        user_text: &str,
+        tool_input: serde_json::Value,
+        updated_at: DateTime<Utc>,
+    ) -> DbThread {
+        DbThread {
+            title: title.to_string().into(),
+            messages: vec![
+                crate::Message::User(UserMessage {
+                    id: UserMessageId::new(),
+                    content: vec![UserMessageContent::Text(user_text.into())],
+                }),
+                crate::Message::Agent(AgentMessage {
+                    content: vec![AgentMessageContent::ToolUse(LanguageModelToolUse {
+                        id: "tool-1".into(),
+                        name: "edit_file".into(),
+                        raw_input: tool_input.to_string(),
+                        input: tool_input,
+                        is_input_complete: true,
+                        thought_signature: None,
+                    })],
+                    tool_results: IndexMap::default(),
+                    reasoning_details: None,
+                }),
+            ],
+            updated_at,
+            detailed_summary: None,
+            initial_project_snapshot: None,
+            cumulative_token_usage: Default::default(),
+            request_token_usage: HashMap::default(),
+            cost: 0.,
+            model: Some(DbLanguageModel {
+                provider: "anthropic".into(),
+                model: "claude-sonnet-4".into(),
+            }),
+            completion_mode: None,
+            profile: Some(AgentProfileId("write".into())),
+            forked_from: None,
+            worktree: None,
+            model_fallbacks: Vec::new(),
+        }
+    }
+
+    #[gpui::test]
+    async fn test_search_threads(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(cx.executor()).unwrap();
+
+        let old = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let new = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+        database
+            .save_thread(
+                acp::SessionId("websocket".into()),
+                thread(
+                    "Networking fixes",
+                    "The websocket keeps dropping, please fix the reconnect logic",
+                    json!({ "path": "src/net/socket.rs", "mode": "edit" }),
+                    old,
+                ),
+            )
+            .await
+            .unwrap();
+        database
+            .save_thread(
+                acp::SessionId("parser".into()),
+                thread(
+                    "Parser cleanup",
+                    "Refactor the tokenizer",
+                    json!({ "path": "src/parser/lexer.rs", "mode": "edit" }),
+                    new,
+                ),
+            )
+            .await
+            .unwrap();
+
+        let search = |text: &str, filter: ThreadSearchFilter| {
+            let task = database.search_threads(
+                ThreadSearchQuery {
+                    text: text.into(),
+                    filter,
+                },
+                10,
+            );
+            async move {
+                task.await
+                    .unwrap()
+                    .into_iter()
+                    .map(|search_match| search_match.metadata.id.0.to_string())
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // Message content, prefixes of words and touched paths are all searchable.
+        assert_eq!(
+            search("websock reconn", Default::default()).await,
+            ["websocket"]
+        );
+        assert_eq!(search("lexer", Default::default()).await, ["parser"]);
+        assert_eq!(
+            search("\"unbalanced", Default::default()).await,
+            Vec::<String>::new()
+        );
+
+        // Without a query, filtered threads are listed by recency.
+        assert_eq!(
+            search("", Default::default()).await,
+            ["parser", "websocket"]
+        );
+        assert_eq!(
+            search(
+                "",
+                ThreadSearchFilter {
+                    updated_after: Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()),
+                    ..Default::default()
+                }
+            )
+            .await,
+            ["parser"]
+        );
+        assert_eq!(
+            search(
+                "websocket",
+                ThreadSearchFilter {
+                    model: Some("anthropic/claude-sonnet-4".into()),
+                    profile: Some("write".into()),
+                    ..Default::default()
+                }
+            )
+            .await,
+            ["websocket"]
+        );
+        assert_eq!(
+            search(
+                "websocket",
+                ThreadSearchFilter {
+                    profile: Some("ask".into()),
+                    ..Default::default()
+                }
+            )
+            .await,
+            Vec::<String>::new()
+        );
+
+        // Deleted threads are removed from the index.
+        database
+            .delete_thread(acp::SessionId("websocket".into()))
+            .await
+            .unwrap();
+        assert_eq!(
+            search("websocket", Default::default()).await,
+            Vec::<String>::new()
+        );
+
+        assert_eq!(
+            database.search_facets().await.unwrap(),
+            ThreadSearchFacets {
+                models: vec!["anthropic/claude-sonnet-4".into()],
+                profiles: vec!["write".into()],
+                projects: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_thread_export_round_trip() {
+        let updated_at = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+        let original = thread(
+            "Networking fixes",
+            "Fix the reconnect logic",
+            json!({ "path": "src/net/socket.rs", "mode": "edit" }),
+            updated_at,
+        );
+        let Some(crate::Message::User(user_message)) = original.messages.first() else {
+            panic!("expected a user message");
+        };
+        let checkpoints = vec![ExportedCheckpoint {
+            user_message_id: user_message.id.clone(),
+            repositories: vec![ExportedRepositoryCheckpoint {
+                work_directory: PathBuf::from("/code/net"),
+                commit_sha: "4b825dc642cb6eb9a060e54bf8d69288fbee4904".into(),
+            }],
+        }];
+        let diffs = vec![ExportedDiff {
+            tool_use_id: "tool-1".into(),
+            path: PathBuf::from("src/net/socket.rs"),
+            diff: "@@ -1 +1 @@\n-connect()\n+reconnect()\n".into(),
+        }];
+
+        let exported = ExportedThread::new(&original, checkpoints.clone(), diffs.clone())
+            .unwrap()
+            .to_json()
+            .unwrap();
+        let imported = ExportedThread::from_json(exported.as_bytes()).unwrap();
+        assert_eq!(imported.checkpoints, checkpoints);
+        assert_eq!(imported.diffs, diffs);
+        let imported_thread = imported.db_thread().unwrap();
+        assert_eq!(imported_thread.title, original.title);
+        assert_eq!(imported_thread.updated_at, original.updated_at);
+        assert_eq!(
+            imported_thread.search_model_id(),
+            original.search_model_id()
+        );
+        assert_eq!(imported_thread.profile, original.profile);
+        assert_eq!(imported_thread.messages, original.messages);
+
+        // Version 1 exports had no checkpoints or diffs.
+        let mut version_1_export = serde_json::from_str::<serde_json::Value>(&exported).unwrap();
+        version_1_export["format_version"] = json!(1);
+        let version_1_export = version_1_export.as_object_mut().unwrap();
+        version_1_export.remove("checkpoints");
+        version_1_export.remove("diffs");
+        let imported =
+            ExportedThread::from_json(serde_json::to_vec(&version_1_export).unwrap().as_slice())
+                .unwrap();
+        assert!(imported.checkpoints.is_empty());
+        assert!(imported.diffs.is_empty());
+        assert_eq!(imported.db_thread().unwrap().messages, original.messages);
+
+        let mut newer_export = serde_json::from_str::<serde_json::Value>(&exported).unwrap();
+        newer_export["format_version"] = json!(ExportedThread::FORMAT_VERSION + 1);
+        let error = ExportedThread::from_json(newer_export.to_string().as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("newer version of Zed"));
+
+        assert!(ExportedThread::from_json(b"{\"title\": \"not an export\"}").is_err());
+    }
+}
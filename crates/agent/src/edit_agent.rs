@@ -730,6 +730,8 @@ impl EditAgent {
             stop: Vec::new(),
             temperature: None,
             thinking_allowed: true,
+            response_format: None,
+            max_tokens: None,
         };
 
         Ok(self.model.stream_completion_text(request, cx).await?.stream)
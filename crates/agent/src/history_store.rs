@@ -1,4 +1,7 @@
-use crate::{DbThread, DbThreadMetadata, ThreadsDatabase};
+use crate::{
+    DbThread, DbThreadMetadata, ExportedThread, ThreadSearchFacets, ThreadSearchMatch,
+    ThreadSearchQuery, ThreadsDatabase,
+};
 use acp_thread::MentionUri;
 use agent_client_protocol as acp;
 use anyhow::{Context as _, Result, anyhow};
@@ -15,6 +18,7 @@ use ui::ElementId;
 use util::ResultExt as _;
 
 const MAX_RECENTLY_OPENED_ENTRIES: usize = 6;
+const MAX_SEARCH_RESULTS: usize = 100;
 const RECENTLY_OPENED_THREADS_KEY: &str = "recent-agent-threads";
 const SAVE_RECENTLY_OPENED_ENTRIES_DEBOUNCE: Duration = Duration::from_millis(50);
 
@@ -175,6 +179,66 @@ impl HistoryStore {
         })
     }
 
+    /// Stores an exported thread as a new thread, so that it can be resumed
+    /// like any other thread in the history.
+    pub fn import_thread(
+        &mut self,
+        json: Vec<u8>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<DbThreadMetadata>> {
+        cx.spawn(async move |this, cx| {
+            let mut thread = cx
+                .background_spawn(async move { ExportedThread::from_json(&json)?.db_thread() })
+                .await?;
+            thread.updated_at = Utc::now();
+            this.update(cx, |this, cx| this.save_new_thread(thread, cx))?
+                .await
+        })
+    }
+
+    /// Saves a thread under a new id, so that it never overwrites an existing
+    /// thread (e.g. when importing the same file twice).
+    pub fn save_new_thread(
+        &mut self,
+        thread: DbThread,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<DbThreadMetadata>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.spawn(async move |this, cx| {
+            let metadata = DbThreadMetadata {
+                id: acp::SessionId(uuid::Uuid::new_v4().to_string().into()),
+                title: thread.title.clone(),
+                updated_at: thread.updated_at,
+            };
+
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.save_thread(metadata.id.clone(), thread).await?;
+            this.update(cx, |this, cx| this.reload(cx))?;
+
+            Ok(metadata)
+        })
+    }
+
+    pub fn search_threads(
+        &self,
+        query: ThreadSearchQuery,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<ThreadSearchMatch>>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.search_threads(query, MAX_SEARCH_RESULTS).await
+        })
+    }
+
+    pub fn search_facets(&self, cx: &mut Context<Self>) -> Task<Result<ThreadSearchFacets>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.search_facets().await
+        })
+    }
+
     pub fn delete_thread(
         &mut self,
         id: acp::SessionId,
@@ -0,0 +1,98 @@
+use chrono::{Local, NaiveDate};
+use db::kvp::KEY_VALUE_STORE;
+use gpui::{App, Global, Task};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use util::ResultExt as _;
+
+const DAILY_SPEND_KEY: &str = "agent-daily-spend";
+const SAVE_DAILY_SPEND_DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+struct DailySpend {
+    date: Option<NaiveDate>,
+    /// In US dollars.
+    amount: f64,
+}
+
+/// Tracks how much has been spent on language model requests today, across
+/// agent threads and inline assists.
+///
+/// Spend is only persisted once [`SpendTracker::init`] has been called;
+/// otherwise it's kept in memory for the lifetime of the app.
+pub struct SpendTracker {
+    spend: DailySpend,
+    persist: bool,
+    _save_task: Option<Task<()>>,
+}
+
+impl Global for SpendTracker {}
+
+impl SpendTracker {
+    /// Loads today's spend from the database and saves it back as it changes.
+    pub fn init(cx: &mut App) {
+        cx.set_global(Self {
+            spend: Self::load().unwrap_or_default(),
+            persist: true,
+            _save_task: None,
+        });
+    }
+
+    fn global(cx: &mut App) -> &mut Self {
+        if !cx.has_global::<Self>() {
+            cx.set_global(Self {
+                spend: DailySpend::default(),
+                persist: false,
+                _save_task: None,
+            });
+        }
+        cx.global_mut::<Self>()
+    }
+
+    /// What has been spent today, in US dollars.
+    pub fn today(cx: &mut App) -> f64 {
+        let spend = Self::global(cx).spend;
+        if spend.date == Some(Local::now().date_naive()) {
+            spend.amount
+        } else {
+            0.
+        }
+    }
+
+    /// Adds the given cost, in US dollars, to today's spend.
+    pub fn record(cost: f64, cx: &mut App) {
+        if cost <= 0. {
+            return;
+        }
+
+        let today = Local::now().date_naive();
+        let executor = cx.background_executor().clone();
+        let this = Self::global(cx);
+        if this.spend.date != Some(today) {
+            this.spend = DailySpend {
+                date: Some(today),
+                amount: 0.,
+            };
+        }
+        this.spend.amount += cost;
+        if !this.persist {
+            return;
+        }
+
+        let spend = this.spend;
+        this._save_task = Some(executor.spawn(async move {
+            executor.timer(SAVE_DAILY_SPEND_DEBOUNCE).await;
+            if let Some(content) = serde_json::to_string(&spend).log_err() {
+                KEY_VALUE_STORE
+                    .write_kvp(DAILY_SPEND_KEY.to_owned(), content)
+                    .await
+                    .log_err();
+            }
+        }));
+    }
+
+    fn load() -> Option<DailySpend> {
+        let json = KEY_VALUE_STORE.read_kvp(DAILY_SPEND_KEY).log_err()??;
+        serde_json::from_str(&json).log_err()
+    }
+}
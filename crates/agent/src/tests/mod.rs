@@ -23,7 +23,9 @@ use language_model::{
     LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent, LanguageModelId,
     LanguageModelProviderName, LanguageModelRegistry, LanguageModelRequest,
     LanguageModelRequestMessage, LanguageModelToolResult, LanguageModelToolSchemaFormat,
-    LanguageModelToolUse, MessageContent, Role, StopReason, fake_provider::FakeLanguageModel,
+    LanguageModelToolUse, MessageContent, Role, StopReason,
+    fake_provider::FakeLanguageModel,
+    replay_provider::{Cassette, RecordingLanguageModel, ReplayLanguageModel, RequestMatching},
 };
 use pretty_assertions::assert_eq;
 use project::{
@@ -71,6 +73,78 @@ async fn test_echo(cx: &mut TestAppContext) {
     assert_eq!(stop_events(events), vec![acp::StopReason::EndTurn]);
 }
 
+#[gpui::test]
+async fn test_replayed_completion(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model,
+        thread,
+        project_context,
+        context_server_store,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    let cassette_dir = tempfile::tempdir().unwrap();
+    let recorder = Arc::new(RecordingLanguageModel::new(
+        model.clone(),
+        cassette_dir.path().join("fake.json"),
+        RequestMatching::default(),
+    ));
+    thread.update(cx, |thread, cx| thread.set_model(recorder, cx));
+
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Testing: Reply with 'Hello'"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Hello");
+    fake_model
+        .send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(StopReason::EndTurn));
+    fake_model.end_last_completion_stream();
+    events.collect::<Vec<_>>().await;
+
+    let cassette = Cassette::load(&cassette_dir.path().join("fake.json")).unwrap();
+    assert_eq!(cassette.interactions.len(), 1);
+
+    // A fresh thread sending the same message is served from the cassette,
+    // even though its thread ID differs from the recorded one.
+    let replay = Arc::new(ReplayLanguageModel::new(
+        cassette,
+        RequestMatching::default(),
+    ));
+    let project = thread.read_with(cx, |thread, _| thread.project().clone());
+    let context_server_registry =
+        cx.new(|cx| ContextServerRegistry::new(context_server_store.clone(), cx));
+    let replayed_thread = cx.new(|cx| {
+        Thread::new(
+            project,
+            project_context,
+            context_server_registry,
+            Templates::new(),
+            Some(replay as Arc<dyn LanguageModel>),
+            cx,
+        )
+    });
+    let events = replayed_thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Testing: Reply with 'Hello'"], cx)
+        })
+        .unwrap()
+        .collect()
+        .await;
+    replayed_thread.update(cx, |thread, _cx| {
+        assert_eq!(
+            thread.last_message().unwrap().to_markdown(),
+            indoc! {"
+                ## Assistant
+
+                Hello
+            "}
+        )
+    });
+    assert_eq!(stop_events(events), vec![acp::StopReason::EndTurn]);
+}
+
 #[gpui::test]
 async fn test_thinking(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
@@ -1759,6 +1833,195 @@ async fn test_truncate_second_message(cx: &mut TestAppContext) {
     assert_first_message_state(cx);
 }
 
+#[gpui::test]
+async fn test_thread_budget(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    fake_model.set_pricing(Some(language_model::LanguageModelPricing::new(
+        1., 1., 0., 0.,
+    )));
+    cx.update(|cx| {
+        agent_settings::AgentSettings::override_global(
+            agent_settings::AgentSettings {
+                thread_budget: Some(0.05),
+                ..agent_settings::AgentSettings::get_global(cx).clone()
+            },
+            cx,
+        );
+    });
+
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Hello"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    // Usage is reported cumulatively, so only new tokens add to the cost.
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 20_000,
+            output_tokens: 1_000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        },
+    ));
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 20_000,
+            output_tokens: 10_000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    events.collect::<Vec<_>>().await;
+    thread.read_with(cx, |thread, _| {
+        assert!((thread.cost() - 0.03).abs() < 1e-9);
+    });
+
+    // The next request would cost about as much as the last one, which would
+    // exceed the budget.
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Again"], cx)
+        })
+        .unwrap();
+    let last_event = events.collect::<Vec<_>>().await.pop().unwrap();
+    let error = last_event.unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<BudgetExceededError>(),
+        Some(&BudgetExceededError {
+            kind: BudgetKind::Thread,
+            limit: 0.05,
+            spent: thread.read_with(cx, |thread, _| thread.cost()),
+        })
+    );
+    assert_eq!(fake_model.completion_count(), 0);
+
+    thread.update(cx, |thread, _| thread.allow_budget_exceeded());
+    let _events = thread.update(cx, |thread, cx| thread.resume(cx)).unwrap();
+    cx.run_until_parked();
+    assert_eq!(fake_model.completion_count(), 1);
+}
+
+#[gpui::test]
+async fn test_model_pricing_setting(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    fake_model.set_pricing(Some(language_model::LanguageModelPricing::new(
+        1., 1., 0., 0.,
+    )));
+    cx.update(|cx| {
+        agent_settings::AgentSettings::override_global(
+            agent_settings::AgentSettings {
+                model_pricing: vec![settings::LanguageModelPricingSetting {
+                    provider: model.provider_id().0.to_string().into(),
+                    model: model.id().0,
+                    input: 2.,
+                    output: 10.,
+                    cache_read: None,
+                    cache_write: None,
+                }],
+                ..agent_settings::AgentSettings::get_global(cx).clone()
+            },
+            cx,
+        );
+    });
+
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Hello"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 10_000,
+            output_tokens: 1_000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 5_000,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    events.collect::<Vec<_>>().await;
+
+    // The setting takes precedence over the model's pricing, and cache reads
+    // default to the input price.
+    thread.read_with(cx, |thread, _| {
+        assert!((thread.cost() - 0.04).abs() < 1e-9);
+    });
+}
+
+#[gpui::test]
+async fn test_fork_thread(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Message 1"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Message 1 response");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let second_message_id = UserMessageId::new();
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(second_message_id.clone(), ["Message 2"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Message 2 response");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let original_markdown = thread.read_with(cx, |thread, _| thread.to_markdown());
+    let fork = thread
+        .read_with(cx, |thread, cx| thread.fork(&second_message_id, cx))
+        .await
+        .unwrap();
+
+    // The fork ends right before the message it was forked from...
+    assert_eq!(fork.messages.len(), 2);
+    assert_eq!(
+        fork.messages
+            .iter()
+            .map(|message| message.to_markdown())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        indoc! {"
+            ## User
+
+            Message 1
+
+            ## Assistant
+
+            Message 1 response
+        "}
+    );
+    assert_eq!(
+        fork.forked_from,
+        Some(ThreadForkOrigin {
+            thread_id: thread.read_with(cx, |thread, _| thread.id().clone()),
+            thread_title: thread.read_with(cx, |thread, _| thread.title()),
+        })
+    );
+
+    // ...and the original thread is left intact.
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(thread.to_markdown(), original_markdown);
+    });
+
+    let missing_message = thread
+        .read_with(cx, |thread, cx| thread.fork(&UserMessageId::new(), cx))
+        .await;
+    assert!(missing_message.is_err());
+}
+
 #[gpui::test]
 async fn test_title_generation(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
@@ -2374,6 +2637,78 @@ async fn test_send_max_retries_exceeded(cx: &mut TestAppContext) {
     ));
 }
 
+#[gpui::test]
+async fn test_model_fallback(cx: &mut TestAppContext) {
+    let ThreadTest { thread, model, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    cx.run_until_parked();
+
+    let fallback_provider = language_model::fake_provider::FakeLanguageModelProvider::new(
+        language_model::LanguageModelProviderId::new("fallback"),
+        LanguageModelProviderName::new("Fallback"),
+    );
+    let fallback_model = Arc::new(fallback_provider.test_model());
+    let fallback_provider = fallback_provider.with_model(fallback_model.clone());
+    cx.update(|cx| {
+        LanguageModelRegistry::test(cx);
+        LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
+            registry.register_provider(Arc::new(fallback_provider), cx)
+        });
+        let mut settings = agent_settings::AgentSettings::get_global(cx).clone();
+        settings
+            .profiles
+            .get_mut(&AgentProfileId("test-profile".into()))
+            .unwrap()
+            .fallback_models = vec![settings::LanguageModelSelection {
+            provider: settings::LanguageModelProviderSetting("fallback".into()),
+            model: "fake".into(),
+        }];
+        agent_settings::AgentSettings::override_global(settings, cx);
+    });
+
+    let message_id = UserMessageId::new();
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.send(message_id.clone(), ["Hello!"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    fake_model.send_last_completion_stream_error(LanguageModelCompletionError::NoApiKey {
+        provider: LanguageModelProviderName::new("Fake"),
+    });
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    assert_eq!(fake_model.completion_count(), 0);
+    fallback_model.send_last_completion_stream_text_chunk("Hi from the fallback");
+    fallback_model.end_last_completion_stream();
+    let events = events.collect::<Vec<_>>().await;
+    assert_eq!(stop_events(events), vec![acp::StopReason::EndTurn]);
+
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(
+            thread.model().unwrap().provider_id(),
+            language_model::LanguageModelProviderId::new("fallback")
+        );
+        assert_eq!(
+            thread.model_fallbacks(),
+            &[ModelFallback {
+                user_message_id: message_id,
+                from: DbLanguageModel {
+                    provider: "fake".into(),
+                    model: "Fake".into(),
+                },
+                to: DbLanguageModel {
+                    provider: "fallback".into(),
+                    model: "Fake".into(),
+                },
+                reason: ModelFallbackReason::Error,
+            }]
+        );
+    });
+}
+
 /// Filters out the stop events for asserting against in tests
 fn stop_events(result_events: Vec<Result<ThreadEvent>>) -> Vec<acp::StopReason> {
     result_events
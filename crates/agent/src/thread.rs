@@ -1,8 +1,10 @@
 use crate::{
     ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DbLanguageModel, DbThread,
-    DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindPathTool, GrepTool,
-    ListDirectoryTool, MovePathTool, NowTool, OpenTool, ProjectSnapshot, ReadFileTool,
-    SystemPromptTemplate, Template, Templates, TerminalTool, ThinkingTool, WebSearchTool,
+    DeletePathTool, DiagnosticsTool, EditFileTool, EditFileToolOutput, ExportedCheckpoint,
+    ExportedThread, FetchTool, FindPathTool, GrepTool, ListDirectoryTool, ModelFallback,
+    ModelFallbackReason, MovePathTool, NowTool, OpenTool, ProjectSnapshot, ReadFileTool,
+    SpendTracker, SystemPromptTemplate, Template, Templates, TerminalTool, ThinkingTool,
+    ThreadForkOrigin, ThreadWorktree, WebSearchTool,
 };
 use acp_thread::{MentionUri, UserMessageId};
 use action_log::ActionLog;
@@ -191,6 +193,7 @@ impl UserMessage {
         const OPEN_SELECTIONS_TAG: &str = "<selections>";
         const OPEN_THREADS_TAG: &str = "<threads>";
         const OPEN_FETCH_TAG: &str = "<fetched_urls>";
+        const OPEN_RESOURCES_TAG: &str = "<mcp_resources>";
         const OPEN_RULES_TAG: &str =
             "<rules>\nThe user has specified the following rules that should be applied:\n";
 
@@ -200,6 +203,7 @@ impl UserMessage {
         let mut selection_context = OPEN_SELECTIONS_TAG.to_string();
         let mut thread_context = OPEN_THREADS_TAG.to_string();
         let mut fetch_context = OPEN_FETCH_TAG.to_string();
+        let mut resource_context = OPEN_RESOURCES_TAG.to_string();
         let mut rules_context = OPEN_RULES_TAG.to_string();
 
         for chunk in &self.content {
@@ -282,6 +286,14 @@ impl UserMessage {
                         MentionUri::Fetch { url } => {
                             write!(&mut fetch_context, "\nFetch: {}\n\n{}", url, content).ok();
                         }
+                        MentionUri::McpResource { server_id, uri, .. } => {
+                            write!(
+                                &mut resource_context,
+                                "\nResource: {} (from {})\n\n{}",
+                                uri, server_id, content
+                            )
+                            .ok();
+                        }
                     }
 
                     language_model::MessageContent::Text(uri.as_link().to_string())
@@ -335,6 +347,13 @@ impl UserMessage {
                 .push(language_model::MessageContent::Text(fetch_context));
         }
 
+        if resource_context.len() > OPEN_RESOURCES_TAG.len() {
+            resource_context.push_str("</mcp_resources>\n");
+            message
+                .content
+                .push(language_model::MessageContent::Text(resource_context));
+        }
+
         if rules_context.len() > OPEN_RULES_TAG.len() {
             rules_context.push_str("</user_rules>\n");
             message
@@ -585,6 +604,33 @@ enum CompletionError {
     Other(#[from] anyhow::Error),
 }
 
+/// Returned when a thread is paused because its next request would exceed
+/// one of the budgets configured in the agent settings.
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+#[error("{kind} budget of ${limit:.2} reached")]
+pub struct BudgetExceededError {
+    pub kind: BudgetKind,
+    /// In US dollars.
+    pub limit: f64,
+    /// In US dollars.
+    pub spent: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetKind {
+    Thread,
+    Daily,
+}
+
+impl std::fmt::Display for BudgetKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetKind::Thread => write!(f, "Thread"),
+            BudgetKind::Daily => write!(f, "Daily"),
+        }
+    }
+}
+
 pub struct Thread {
     id: acp::SessionId,
     prompt_id: PromptId,
@@ -604,6 +650,13 @@ pub struct Thread {
     tools: BTreeMap<SharedString, Arc<dyn AnyAgentTool>>,
     tool_use_limit_reached: bool,
     request_token_usage: HashMap<UserMessageId, language_model::TokenUsage>,
+    /// Usage reported so far for the completion request in flight.
+    completion_usage: TokenUsage,
+    /// What this thread has spent on completion requests, in US dollars.
+    cost: f64,
+    /// Whether the user chose to keep going after a budget was reached. Reset
+    /// whenever a new message is sent.
+    budget_exceeded_allowed: bool,
     #[allow(unused)]
     cumulative_token_usage: TokenUsage,
     #[allow(unused)]
@@ -620,6 +673,9 @@ pub struct Thread {
     pub(crate) action_log: Entity<ActionLog>,
     /// Tracks the last time files were read by the agent, to detect external modifications
     pub(crate) file_read_times: HashMap<PathBuf, fs::MTime>,
+    forked_from: Option<ThreadForkOrigin>,
+    worktree: Option<ThreadWorktree>,
+    model_fallbacks: Vec<ModelFallback>,
 }
 
 impl Thread {
@@ -661,6 +717,9 @@ impl Thread {
             tools: BTreeMap::default(),
             tool_use_limit_reached: false,
             request_token_usage: HashMap::default(),
+            completion_usage: TokenUsage::default(),
+            cost: 0.,
+            budget_exceeded_allowed: false,
             cumulative_token_usage: TokenUsage::default(),
             initial_project_snapshot: {
                 let project_snapshot = Self::project_snapshot(project.clone(), cx);
@@ -679,6 +738,9 @@ impl Thread {
             project,
             action_log,
             file_read_times: HashMap::default(),
+            forked_from: None,
+            worktree: None,
+            model_fallbacks: Vec::new(),
         }
     }
 
@@ -861,6 +923,9 @@ impl Thread {
             tools: BTreeMap::default(),
             tool_use_limit_reached: false,
             request_token_usage: db_thread.request_token_usage.clone(),
+            completion_usage: TokenUsage::default(),
+            cost: db_thread.cost,
+            budget_exceeded_allowed: false,
             cumulative_token_usage: db_thread.cumulative_token_usage,
             initial_project_snapshot: Task::ready(db_thread.initial_project_snapshot).shared(),
             context_server_registry,
@@ -875,6 +940,9 @@ impl Thread {
             prompt_capabilities_tx,
             prompt_capabilities_rx,
             file_read_times: HashMap::default(),
+            forked_from: db_thread.forked_from,
+            worktree: db_thread.worktree,
+            model_fallbacks: db_thread.model_fallbacks,
         }
     }
 
@@ -888,12 +956,16 @@ impl Thread {
             initial_project_snapshot: None,
             cumulative_token_usage: self.cumulative_token_usage,
             request_token_usage: self.request_token_usage.clone(),
+            cost: self.cost,
             model: self.model.as_ref().map(|model| DbLanguageModel {
                 provider: model.provider_id().to_string(),
                 model: model.name().0.to_string(),
             }),
             completion_mode: Some(self.completion_mode),
             profile: Some(self.profile_id.clone()),
+            forked_from: self.forked_from.clone(),
+            worktree: self.worktree.clone(),
+            model_fallbacks: self.model_fallbacks.clone(),
         };
 
         cx.background_spawn(async move {
@@ -903,6 +975,93 @@ impl Thread {
         })
     }
 
+    /// Serializes this thread, including turns that haven't been saved to the
+    /// database yet, into the portable [`ExportedThread`] format.
+    pub fn to_export(
+        &self,
+        checkpoints: Vec<ExportedCheckpoint>,
+        cx: &App,
+    ) -> Task<Result<ExportedThread>> {
+        let diffs = self
+            .messages
+            .iter()
+            .filter_map(|message| match message {
+                Message::Agent(message) => Some(message),
+                _ => None,
+            })
+            .flat_map(|message| message.tool_results.values())
+            .filter_map(EditFileToolOutput::exported_diff)
+            .collect();
+        let db_thread = self.to_db(cx);
+        cx.background_spawn(
+            async move { ExportedThread::new(&db_thread.await, checkpoints, diffs) },
+        )
+    }
+
+    /// Creates a copy of this thread that ends right before the given user
+    /// message, leaving this thread untouched.
+    pub fn fork(&self, message_id: &UserMessageId, cx: &App) -> Task<Result<DbThread>> {
+        let Some(position) = self.messages.iter().position(
+            |msg| matches!(msg, Message::User(UserMessage { id, .. }) if id == message_id),
+        ) else {
+            return Task::ready(Err(anyhow!("Message not found")));
+        };
+
+        let forked_from = ThreadForkOrigin {
+            thread_id: self.id.clone(),
+            thread_title: self.title(),
+        };
+        let db_thread = self.to_db(cx);
+        cx.background_spawn(async move {
+            let mut thread = db_thread.await;
+            for message in thread.messages.drain(position..) {
+                match message {
+                    Message::User(message) => {
+                        thread.request_token_usage.remove(&message.id);
+                        thread
+                            .model_fallbacks
+                            .retain(|fallback| fallback.user_message_id != message.id);
+                    }
+                    Message::Agent(_) | Message::Resume => {}
+                }
+            }
+            thread.title = format!("{} (Fork)", forked_from.thread_title).into();
+            thread.detailed_summary = None;
+            thread.updated_at = Utc::now();
+            thread.forked_from = Some(forked_from);
+            thread.worktree = None;
+            thread.cost = 0.;
+            Ok(thread)
+        })
+    }
+
+    pub fn forked_from(&self) -> Option<&ThreadForkOrigin> {
+        self.forked_from.as_ref()
+    }
+
+    pub fn worktree(&self) -> Option<&ThreadWorktree> {
+        self.worktree.as_ref()
+    }
+
+    /// The switches to fallback models made so far, oldest first.
+    pub fn model_fallbacks(&self) -> &[ModelFallback] {
+        &self.model_fallbacks
+    }
+
+    /// The last switch to a fallback model, if it happened during the latest turn.
+    pub fn latest_model_fallback(&self) -> Option<&ModelFallback> {
+        let fallback = self.model_fallbacks.last()?;
+        (Some(&fallback.user_message_id) == self.last_user_message().map(|message| &message.id))
+            .then_some(fallback)
+    }
+
+    pub fn set_worktree(&mut self, worktree: Option<ThreadWorktree>, cx: &mut Context<Self>) {
+        if self.worktree != worktree {
+            self.worktree = worktree;
+            cx.notify();
+        }
+    }
+
     /// Create a snapshot of the current project state including git information and unsaved buffers.
     fn project_snapshot(
         project: Entity<Project>,
@@ -1056,6 +1215,20 @@ impl Thread {
     }
 
     fn update_token_usage(&mut self, update: language_model::TokenUsage, cx: &mut Context<Self>) {
+        // Providers report the usage of a request so far, so only the tokens
+        // we haven't seen yet add to the cost.
+        let new_usage = update.saturating_sub(self.completion_usage);
+        self.completion_usage = update;
+        if let Some(pricing) = self
+            .model
+            .as_ref()
+            .and_then(|model| AgentSettings::pricing_for_model(model, cx))
+        {
+            let cost = pricing.cost(&new_usage);
+            self.cost += cost;
+            SpendTracker::record(cost, cx);
+        }
+
         let Some(last_user_message) = self.last_user_message() else {
             return;
         };
@@ -1078,6 +1251,8 @@ impl Thread {
             match message {
                 Message::User(message) => {
                     self.request_token_usage.remove(&message.id);
+                    self.model_fallbacks
+                        .retain(|fallback| fallback.user_message_id != message.id);
                 }
                 Message::Agent(_) | Message::Resume => {}
             }
@@ -1087,6 +1262,53 @@ impl Thread {
         Ok(())
     }
 
+    /// What this thread has spent on completion requests, in US dollars.
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    /// Lets the thread keep going after reaching a budget, until the next
+    /// message is sent.
+    pub fn allow_budget_exceeded(&mut self) {
+        self.budget_exceeded_allowed = true;
+    }
+
+    /// Checks whether the next completion request would exceed the thread or
+    /// daily budget, assuming it costs about as much as the last one.
+    fn check_budget(&mut self, cx: &mut App) -> Result<(), BudgetExceededError> {
+        if self.budget_exceeded_allowed {
+            return Ok(());
+        }
+        let settings = AgentSettings::get_global(cx);
+        let (thread_budget, daily_budget) = (settings.thread_budget, settings.daily_budget);
+        let estimate = self
+            .model
+            .as_ref()
+            .and_then(|model| AgentSettings::pricing_for_model(model, cx))
+            .map_or(0., |pricing| pricing.cost(&self.completion_usage));
+
+        if let Some(limit) = thread_budget
+            && self.cost + estimate > limit
+        {
+            return Err(BudgetExceededError {
+                kind: BudgetKind::Thread,
+                limit,
+                spent: self.cost,
+            });
+        }
+        if let Some(limit) = daily_budget {
+            let spent = SpendTracker::today(cx);
+            if spent + estimate > limit {
+                return Err(BudgetExceededError {
+                    kind: BudgetKind::Daily,
+                    limit,
+                    spent,
+                });
+            }
+        }
+        Ok(())
+    }
+
     pub fn latest_request_token_usage(&self) -> Option<language_model::TokenUsage> {
         let last_user_message = self.last_user_message()?;
         let tokens = self.request_token_usage.get(&last_user_message.id)?;
@@ -1164,6 +1386,7 @@ impl Thread {
 
         self.messages
             .push(Message::User(UserMessage { id, content }));
+        self.budget_exceeded_allowed = false;
         cx.notify();
 
         log::debug!("Total messages in thread: {}", self.messages.len());
@@ -1240,15 +1463,19 @@ impl Thread {
 
     async fn run_turn_internal(
         this: &WeakEntity<Self>,
-        model: Arc<dyn LanguageModel>,
+        mut model: Arc<dyn LanguageModel>,
         event_stream: &ThreadEventStream,
         cx: &mut AsyncApp,
     ) -> Result<()> {
         let mut attempt = 0;
         let mut intent = CompletionIntent::UserPrompt;
+        let mut tried_models = vec![(model.provider_id(), model.id())];
         loop {
-            let request =
-                this.update(cx, |this, cx| this.build_completion_request(intent, cx))??;
+            let request = this.update(cx, |this, cx| {
+                this.check_budget(cx)?;
+                this.completion_usage = TokenUsage::default();
+                this.build_completion_request(intent, cx)
+            })??;
 
             telemetry::event!(
                 "Agent Thread Completion",
@@ -1316,10 +1543,25 @@ impl Thread {
                 let retry = this.update(cx, |this, cx| {
                     let user_store = this.user_store.read(cx);
                     this.handle_completion_error(error, attempt, user_store.plan())
-                })??;
-                let timer = cx.background_executor().timer(retry.duration);
-                event_stream.send_retry(retry);
-                timer.await;
+                })?;
+                match retry {
+                    Ok(retry) => {
+                        let timer = cx.background_executor().timer(retry.duration);
+                        event_stream.send_retry(retry);
+                        timer.await;
+                    }
+                    Err(error) => {
+                        let fallback = this.update(cx, |this, cx| {
+                            this.fall_back(&model, &error, &tried_models, cx)
+                        })?;
+                        let Some(fallback) = fallback else {
+                            return Err(error);
+                        };
+                        tried_models.push((fallback.provider_id(), fallback.id()));
+                        model = fallback;
+                        attempt = 0;
+                    }
+                }
                 this.update(cx, |this, _cx| {
                     if let Some(Message::Agent(message)) = this.messages.last() {
                         if message.tool_results.is_empty() {
@@ -1339,6 +1581,67 @@ impl Thread {
         }
     }
 
+    /// Switches the thread to the first of its profile's fallback models that
+    /// can take over from `model` after it failed with `error`, if any.
+    fn fall_back(
+        &mut self,
+        model: &Arc<dyn LanguageModel>,
+        error: &anyhow::Error,
+        tried_models: &[(LanguageModelProviderId, LanguageModelId)],
+        cx: &mut Context<Self>,
+    ) -> Option<Arc<dyn LanguageModel>> {
+        use LanguageModelCompletionError::*;
+
+        let reason = match error.downcast_ref::<LanguageModelCompletionError>()? {
+            PromptTooLarge { .. }
+            | HttpResponseError {
+                status_code: http_client::StatusCode::PAYLOAD_TOO_LARGE,
+                ..
+            } => ModelFallbackReason::ContextOverflow,
+            _ => ModelFallbackReason::Error,
+        };
+        let selections = AgentSettings::get_global(cx)
+            .profiles
+            .get(&self.profile_id)?
+            .fallback_models
+            .clone();
+        let fallback = selections
+            .iter()
+            .filter_map(|selection| Self::resolve_model_from_selection(selection, cx))
+            .find(|candidate| {
+                !tried_models.contains(&(candidate.provider_id(), candidate.id()))
+                    && (candidate.supports_tools() || !model.supports_tools())
+                    && match reason {
+                        ModelFallbackReason::Error => true,
+                        ModelFallbackReason::ContextOverflow => {
+                            candidate.max_token_count() > model.max_token_count()
+                        }
+                    }
+            })?;
+
+        log::info!(
+            "Falling back from {} to {} ({reason:?}): {error:#}",
+            model.id().0,
+            fallback.id().0
+        );
+        if let Some(user_message) = self.last_user_message() {
+            self.model_fallbacks.push(ModelFallback {
+                user_message_id: user_message.id.clone(),
+                from: DbLanguageModel {
+                    provider: model.provider_id().to_string(),
+                    model: model.name().0.to_string(),
+                },
+                to: DbLanguageModel {
+                    provider: fallback.provider_id().to_string(),
+                    model: fallback.name().0.to_string(),
+                },
+                reason,
+            });
+        }
+        self.set_model(fallback.clone(), cx);
+        Some(fallback)
+    }
+
     fn handle_completion_error(
         &mut self,
         error: LanguageModelCompletionError,
@@ -1916,6 +2219,8 @@ impl Thread {
             stop: Vec::new(),
             temperature: AgentSettings::temperature_for_model(model, cx),
             thinking_allowed: true,
+            response_format: None,
+            max_tokens: None,
         };
 
         log::debug!("Completion request built successfully");
@@ -3,7 +3,7 @@ use agent_client_protocol::ToolKind;
 use anyhow::{Result, anyhow, bail};
 use collections::{BTreeMap, HashMap};
 use context_server::ContextServerId;
-use gpui::{App, Context, Entity, SharedString, Task};
+use gpui::{App, Context, Entity, EventEmitter, SharedString, Task};
 use project::context_server_store::{ContextServerStatus, ContextServerStore};
 use std::sync::Arc;
 use util::ResultExt;
@@ -16,9 +16,35 @@ pub struct ContextServerRegistry {
 
 struct RegisteredContextServer {
     tools: BTreeMap<SharedString, Arc<dyn AnyAgentTool>>,
+    prompts: BTreeMap<SharedString, ContextServerPrompt>,
     load_tools: Task<Result<()>>,
+    load_prompts: Task<Result<()>>,
 }
 
+impl RegisteredContextServer {
+    fn new() -> Self {
+        Self {
+            tools: BTreeMap::default(),
+            prompts: BTreeMap::default(),
+            load_tools: Task::ready(Ok(())),
+            load_prompts: Task::ready(Ok(())),
+        }
+    }
+}
+
+/// A prompt exposed by a running context server.
+#[derive(Clone)]
+pub struct ContextServerPrompt {
+    pub server_id: ContextServerId,
+    pub prompt: Arc<context_server::types::Prompt>,
+}
+
+pub enum ContextServerRegistryEvent {
+    PromptsChanged,
+}
+
+impl EventEmitter<ContextServerRegistryEvent> for ContextServerRegistry {}
+
 impl ContextServerRegistry {
     pub fn new(server_store: Entity<ContextServerStore>, cx: &mut Context<Self>) -> Self {
         let mut this = Self {
@@ -28,6 +54,7 @@ impl ContextServerRegistry {
         };
         for server in server_store.read(cx).running_servers() {
             this.reload_tools_for_server(server.id(), cx);
+            this.reload_prompts_for_server(server.id(), cx);
         }
         this
     }
@@ -56,6 +83,64 @@ impl ContextServerRegistry {
             .map(|(id, server)| (id, &server.tools))
     }
 
+    pub fn prompts(&self) -> impl Iterator<Item = &ContextServerPrompt> {
+        self.registered_servers
+            .values()
+            .flat_map(|server| server.prompts.values())
+    }
+
+    pub fn find_prompt(&self, name: &str) -> Option<&ContextServerPrompt> {
+        self.prompts().find(|prompt| prompt.prompt.name == name)
+    }
+
+    /// Fetches the messages of a prompt, filling in its arguments from the
+    /// text that followed the slash command.
+    pub fn load_prompt(
+        &self,
+        prompt: &ContextServerPrompt,
+        input: &str,
+        cx: &App,
+    ) -> Task<Result<Vec<String>>> {
+        let Some(server) = self
+            .server_store
+            .read(cx)
+            .get_running_server(&prompt.server_id)
+        else {
+            return Task::ready(Err(anyhow!("Context server not found")));
+        };
+        let arguments = match prompt_arguments(&prompt.prompt, input) {
+            Ok(arguments) => arguments,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let name = prompt.prompt.name.clone();
+        cx.spawn(async move |_cx| {
+            let Some(protocol) = server.client() else {
+                bail!("Context server not initialized");
+            };
+            let response = protocol
+                .request::<context_server::types::requests::PromptsGet>(
+                    context_server::types::PromptsGetParams {
+                        name,
+                        arguments: Some(arguments),
+                        meta: None,
+                    },
+                )
+                .await?;
+
+            let mut messages = Vec::new();
+            for message in response.messages {
+                if !matches!(message.role, context_server::types::Role::User) {
+                    bail!("Prompt contains non-user roles, which is not supported");
+                }
+                match message.content {
+                    context_server::types::MessageContent::Text { text, .. } => messages.push(text),
+                    _ => log::warn!("Ignoring non-text content from prompt"),
+                }
+            }
+            Ok(messages)
+        })
+    }
+
     fn reload_tools_for_server(&mut self, server_id: ContextServerId, cx: &mut Context<Self>) {
         let Some(server) = self.server_store.read(cx).get_running_server(&server_id) else {
             return;
@@ -67,13 +152,10 @@ impl ContextServerRegistry {
             return;
         }
 
-        let registered_server =
-            self.registered_servers
-                .entry(server_id.clone())
-                .or_insert(RegisteredContextServer {
-                    tools: BTreeMap::default(),
-                    load_tools: Task::ready(Ok(())),
-                });
+        let registered_server = self
+            .registered_servers
+            .entry(server_id.clone())
+            .or_insert_with(RegisteredContextServer::new);
         registered_server.load_tools = cx.spawn(async move |this, cx| {
             let response = client
                 .request::<context_server::types::requests::ListTools>(())
@@ -100,6 +182,49 @@ impl ContextServerRegistry {
         });
     }
 
+    fn reload_prompts_for_server(&mut self, server_id: ContextServerId, cx: &mut Context<Self>) {
+        let Some(server) = self.server_store.read(cx).get_running_server(&server_id) else {
+            return;
+        };
+        let Some(client) = server.client() else {
+            return;
+        };
+        if !client.capable(context_server::protocol::ServerCapability::Prompts) {
+            return;
+        }
+
+        let registered_server = self
+            .registered_servers
+            .entry(server_id.clone())
+            .or_insert_with(RegisteredContextServer::new);
+        registered_server.load_prompts = cx.spawn(async move |this, cx| {
+            let response = client
+                .request::<context_server::types::requests::PromptsList>(())
+                .await;
+
+            this.update(cx, |this, cx| {
+                let Some(registered_server) = this.registered_servers.get_mut(&server_id) else {
+                    return;
+                };
+
+                registered_server.prompts.clear();
+                if let Some(response) = response.log_err() {
+                    for prompt in response.prompts {
+                        registered_server.prompts.insert(
+                            prompt.name.clone().into(),
+                            ContextServerPrompt {
+                                server_id: server_id.clone(),
+                                prompt: Arc::new(prompt),
+                            },
+                        );
+                    }
+                    cx.emit(ContextServerRegistryEvent::PromptsChanged);
+                    cx.notify();
+                }
+            })
+        });
+    }
+
     fn handle_context_server_store_event(
         &mut self,
         _: Entity<ContextServerStore>,
@@ -112,15 +237,63 @@ impl ContextServerRegistry {
                     ContextServerStatus::Starting => {}
                     ContextServerStatus::Running => {
                         self.reload_tools_for_server(server_id.clone(), cx);
+                        self.reload_prompts_for_server(server_id.clone(), cx);
                     }
-                    ContextServerStatus::Stopped | ContextServerStatus::Error(_) => {
-                        self.registered_servers.remove(server_id);
+                    ContextServerStatus::Stopped
+                    | ContextServerStatus::AuthenticationRequired
+                    | ContextServerStatus::Error(_) => {
+                        if let Some(server) = self.registered_servers.remove(server_id)
+                            && !server.prompts.is_empty()
+                        {
+                            cx.emit(ContextServerRegistryEvent::PromptsChanged);
+                        }
                         cx.notify();
                     }
                 }
             }
+            project::context_server_store::Event::ResourceUpdated { .. } => {}
+        }
+    }
+}
+
+/// Maps the text following a prompt's slash command to its arguments. Words
+/// are assigned to the arguments in order, with the last argument receiving
+/// the rest of the text.
+fn prompt_arguments(
+    prompt: &context_server::types::Prompt,
+    input: &str,
+) -> Result<HashMap<String, String>> {
+    let arguments = prompt.arguments.as_deref().unwrap_or_default();
+    let mut values = HashMap::default();
+    let mut rest = input.trim();
+    for (ix, argument) in arguments.iter().enumerate() {
+        if rest.is_empty() {
+            break;
         }
+        let value = if ix + 1 == arguments.len() {
+            std::mem::take(&mut rest)
+        } else {
+            let (value, remaining) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            rest = remaining.trim_start();
+            value
+        };
+        values.insert(argument.name.clone(), value.to_string());
+    }
+
+    if !rest.is_empty() {
+        bail!("Prompt `{}` does not take arguments", prompt.name);
     }
+    if let Some(missing) = arguments
+        .iter()
+        .find(|argument| argument.required == Some(true) && !values.contains_key(&argument.name))
+    {
+        bail!(
+            "Prompt `{}` requires the `{}` argument",
+            prompt.name,
+            missing.name
+        );
+    }
+    Ok(values)
 }
 
 struct ContextServerTool {
@@ -251,3 +424,48 @@ impl AnyAgentTool for ContextServerTool {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use context_server::types::{Prompt, PromptArgument};
+
+    fn prompt(arguments: &[(&str, bool)]) -> Prompt {
+        Prompt {
+            name: "review".into(),
+            description: None,
+            arguments: Some(
+                arguments
+                    .iter()
+                    .map(|(name, required)| PromptArgument {
+                        name: name.to_string(),
+                        description: None,
+                        required: Some(*required),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_prompt_arguments() {
+        let arguments = prompt_arguments(
+            &prompt(&[("file", true), ("focus", false)]),
+            " main.rs  error handling ",
+        )
+        .unwrap();
+        assert_eq!(arguments.get("file").map(String::as_str), Some("main.rs"));
+        assert_eq!(
+            arguments.get("focus").map(String::as_str),
+            Some("error handling")
+        );
+
+        let arguments =
+            prompt_arguments(&prompt(&[("file", true), ("focus", false)]), "main.rs").unwrap();
+        assert_eq!(arguments.len(), 1);
+
+        assert!(prompt_arguments(&prompt(&[("file", true)]), "").is_err());
+        assert!(prompt_arguments(&prompt(&[]), "unexpected").is_err());
+        assert!(prompt_arguments(&prompt(&[]), "").unwrap().is_empty());
+    }
+}
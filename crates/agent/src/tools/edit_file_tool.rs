@@ -1,5 +1,5 @@
 use crate::{
-    AgentTool, Templates, Thread, ToolCallEventStream,
+    AgentTool, ExportedDiff, Templates, Thread, ToolCallEventStream,
     edit_agent::{EditAgent, EditAgentOutput, EditAgentOutputEvent, EditFormat},
 };
 use acp_thread::Diff;
@@ -11,7 +11,7 @@ use gpui::{App, AppContext, AsyncApp, Entity, Task, WeakEntity};
 use indoc::formatdoc;
 use language::language_settings::{self, FormatOnSave};
 use language::{LanguageRegistry, ToPoint};
-use language_model::LanguageModelToolResultContent;
+use language_model::{LanguageModelToolResult, LanguageModelToolResultContent};
 use paths;
 use project::lsp_store::{FormatTrigger, LspFormatTarget};
 use project::{Project, ProjectPath};
@@ -106,6 +106,25 @@ pub struct EditFileToolOutput {
     edit_agent_output: EditAgentOutput,
 }
 
+impl EditFileToolOutput {
+    /// Returns the diff recorded in an `edit_file` tool result, if it made any
+    /// edits.
+    pub(crate) fn exported_diff(result: &LanguageModelToolResult) -> Option<ExportedDiff> {
+        if result.tool_name.as_ref() != EditFileTool::name() {
+            return None;
+        }
+        let output = serde_json::from_value::<Self>(result.output.clone()?).ok()?;
+        if output.diff.is_empty() {
+            return None;
+        }
+        Some(ExportedDiff {
+            tool_use_id: result.tool_use_id.clone(),
+            path: output.input_path,
+            diff: output.diff,
+        })
+    }
+}
+
 impl From<EditFileToolOutput> for LanguageModelToolResultContent {
     fn from(output: EditFileToolOutput) -> Self {
         if output.diff.is_empty() {
@@ -0,0 +1,459 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use chrono::Utc;
+use collections::HashMap;
+use fs::Fs;
+use git::repository::{GitRepository, GitRepositoryCheckpoint};
+use gpui::{App, AppContext as _, Entity, Task};
+use project::{Project, git_store::Repository};
+
+use crate::{DbThread, DbThreadMetadata, HistoryStore, ThreadWorktree};
+
+/// Creates a worktree from the project's active repository along with an empty
+/// thread that will run in it once the worktree is opened as a project.
+pub fn create_worktree_thread(
+    project: &Entity<Project>,
+    history: &Entity<HistoryStore>,
+    fs: Arc<dyn Fs>,
+    cx: &mut App,
+) -> Task<Result<(ThreadWorktree, DbThreadMetadata)>> {
+    if !project.read(cx).is_local() {
+        return Task::ready(Err(anyhow::anyhow!(
+            "worktree threads are only supported in local projects"
+        )));
+    }
+    let Some(repository) = project.read(cx).active_repository(cx) else {
+        return Task::ready(Err(anyhow::anyhow!("project has no git repository")));
+    };
+    let worktree = create_worktree(&repository, None, fs, cx);
+    let history = history.clone();
+    cx.spawn(async move |cx| {
+        let worktree = worktree.await?;
+        let thread = DbThread {
+            title: Default::default(),
+            messages: Vec::new(),
+            updated_at: Utc::now(),
+            detailed_summary: None,
+            initial_project_snapshot: None,
+            cumulative_token_usage: Default::default(),
+            request_token_usage: HashMap::default(),
+            cost: 0.,
+            model: None,
+            completion_mode: None,
+            profile: None,
+            forked_from: None,
+            worktree: Some(worktree.clone()),
+            model_fallbacks: Vec::new(),
+        };
+        let metadata = history
+            .update(cx, |history, cx| history.save_new_thread(thread, cx))?
+            .await?;
+        Ok((worktree, metadata))
+    })
+}
+
+/// Creates a new git worktree for an agent thread, checked out on a fresh
+/// branch starting at `commit` (or the repository's `HEAD` when not given).
+pub fn create_worktree(
+    repository: &Entity<Repository>,
+    commit: Option<String>,
+    fs: Arc<dyn Fs>,
+    cx: &mut App,
+) -> Task<Result<ThreadWorktree>> {
+    let repository_path = repository.read(cx).work_directory_abs_path.clone();
+    let Some(repository_name) = repository_path.file_name() else {
+        return Task::ready(Err(anyhow::anyhow!(
+            "repository at {repository_path:?} has no directory name"
+        )));
+    };
+    let directory = paths::agent_worktrees_dir().join(repository_name);
+    create_worktree_in(repository, commit, directory, fs, cx)
+}
+
+fn create_worktree_in(
+    repository: &Entity<Repository>,
+    commit: Option<String>,
+    directory: PathBuf,
+    fs: Arc<dyn Fs>,
+    cx: &mut App,
+) -> Task<Result<ThreadWorktree>> {
+    let repository_snapshot = repository.read(cx);
+    let repository_path = repository_snapshot.work_directory_abs_path.to_path_buf();
+    let Some(base_commit) = commit.or_else(|| {
+        repository_snapshot
+            .head_commit
+            .as_ref()
+            .map(|commit| commit.sha.to_string())
+    }) else {
+        return Task::ready(Err(anyhow::anyhow!(
+            "repository at {repository_path:?} has no commits"
+        )));
+    };
+    let branch = format!(
+        "zed-agent-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S%3f")
+    );
+    let worktree_path = directory.join(&branch);
+
+    let receiver = repository.update(cx, |repository, _| {
+        repository.create_worktree(branch.clone(), directory.clone(), Some(base_commit.clone()))
+    });
+    cx.background_spawn(async move {
+        fs.create_dir(&directory).await?;
+        receiver.await??;
+        Ok(ThreadWorktree {
+            repository_path,
+            worktree_path,
+            branch,
+            base_commit,
+        })
+    })
+}
+
+/// Computes a unified diff from the current state of the original checkout to
+/// the current state of the worktree, including uncommitted changes.
+pub fn diff_worktree(
+    worktree: &ThreadWorktree,
+    fs: Arc<dyn Fs>,
+    cx: &mut App,
+) -> Task<Result<String>> {
+    let worktree = worktree.clone();
+    cx.background_spawn(async move {
+        let (repository, worktree_repository) = open_repositories(&worktree, fs.as_ref())?;
+
+        // Worktrees share their object database with the main repository, so
+        // both checkpoints can be compared from the original checkout.
+        let base = repository.checkpoint().await?;
+        let target = worktree_repository.checkpoint().await?;
+        repository.diff_checkpoints(base, target).await
+    })
+}
+
+/// Applies everything that changed in the worktree since it was created to the
+/// original checkout, and returns the paths that were left with conflict
+/// markers.
+pub fn merge_worktree(
+    worktree: &ThreadWorktree,
+    fs: Arc<dyn Fs>,
+    cx: &mut App,
+) -> Task<Result<Vec<String>>> {
+    let worktree = worktree.clone();
+    cx.background_spawn(async move {
+        let (repository, worktree_repository) = open_repositories(&worktree, fs.as_ref())?;
+
+        let base = GitRepositoryCheckpoint {
+            commit_sha: worktree.base_commit.parse()?,
+        };
+        let target = worktree_repository.checkpoint().await?;
+        let mut diff = repository.diff_checkpoints(base, target).await?;
+        if diff.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        // The diff comes back without its trailing newline, which `git apply`
+        // rejects as a corrupt patch.
+        diff.push('\n');
+        repository.apply_diff(diff).await
+    })
+}
+
+/// Deletes the worktree and its branch, discarding any changes made in it.
+pub fn remove_worktree(
+    worktree: &ThreadWorktree,
+    fs: Arc<dyn Fs>,
+    cx: &mut App,
+) -> Task<Result<()>> {
+    let worktree = worktree.clone();
+    cx.background_spawn(async move {
+        let (repository, _) = open_repositories(&worktree, fs.as_ref())?;
+        repository
+            .remove_worktree(worktree.worktree_path.clone(), true)
+            .await?;
+        repository.delete_branch(worktree.branch.clone()).await
+    })
+}
+
+fn open_repositories(
+    worktree: &ThreadWorktree,
+    fs: &dyn Fs,
+) -> Result<(Arc<dyn GitRepository>, Arc<dyn GitRepository>)> {
+    let repository = fs
+        .open_repo(&worktree.repository_path.join(".git"), None)
+        .with_context(|| format!("failed to open repository {:?}", worktree.repository_path))?;
+    let worktree_repository = fs
+        .open_repo(&worktree.worktree_path.join(".git"), None)
+        .with_context(|| format!("failed to open worktree {:?}", worktree.worktree_path))?;
+    Ok((repository, worktree_repository))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NativeAgent, NativeAgentConnection, Templates, WorktreeResolution};
+    use acp_thread::AgentConnection as _;
+    use fs::RealFs;
+    use gpui::{AppContext as _, TestAppContext};
+    use language_model::LanguageModelRegistry;
+    use settings::SettingsStore;
+    use std::{path::Path, process::Command, rc::Rc};
+
+    #[gpui::test]
+    async fn test_worktree_diff_merge_and_remove(cx: &mut TestAppContext) {
+        init_test(cx);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let repository_path = init_repository(&root);
+        let fs: Arc<dyn Fs> = Arc::new(RealFs::new(Some("git".into()), cx.executor()));
+        let project = Project::test(fs.clone(), [repository_path.as_path()], cx).await;
+        let repository = active_repository(&project, cx).await;
+
+        let worktree = cx
+            .update(|cx| {
+                create_worktree_in(&repository, None, root.join("worktrees"), fs.clone(), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            worktree.base_commit,
+            git(&repository_path, &["rev-parse", "HEAD"])
+        );
+        assert_eq!(read(&worktree.worktree_path.join("a.txt")), "one\n");
+
+        std::fs::write(worktree.worktree_path.join("a.txt"), "two\n").unwrap();
+        std::fs::write(worktree.worktree_path.join("b.txt"), "new\n").unwrap();
+        let diff = cx
+            .update(|cx| diff_worktree(&worktree, fs.clone(), cx))
+            .await
+            .unwrap();
+        assert!(diff.contains("-one\n+two\n"), "unexpected diff: {diff}");
+        assert!(diff.contains("+++ b/b.txt"), "unexpected diff: {diff}");
+
+        let conflicts = cx
+            .update(|cx| merge_worktree(&worktree, fs.clone(), cx))
+            .await
+            .unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(read(&repository_path.join("a.txt")), "two\n");
+        assert_eq!(read(&repository_path.join("b.txt")), "new\n");
+
+        cx.update(|cx| remove_worktree(&worktree, fs.clone(), cx))
+            .await
+            .unwrap();
+        assert!(!worktree.worktree_path.exists());
+        assert_eq!(
+            git(&repository_path, &["branch", "--list", &worktree.branch]),
+            ""
+        );
+    }
+
+    #[gpui::test]
+    async fn test_merge_worktree_with_conflicts(cx: &mut TestAppContext) {
+        init_test(cx);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let repository_path = init_repository(&root);
+        let fs: Arc<dyn Fs> = Arc::new(RealFs::new(Some("git".into()), cx.executor()));
+        let project = Project::test(fs.clone(), [repository_path.as_path()], cx).await;
+        let repository = active_repository(&project, cx).await;
+
+        let worktree = cx
+            .update(|cx| {
+                create_worktree_in(&repository, None, root.join("worktrees"), fs.clone(), cx)
+            })
+            .await
+            .unwrap();
+
+        // The original checkout moves on while the thread edits the same line.
+        std::fs::write(repository_path.join("a.txt"), "three\n").unwrap();
+        commit(&repository_path, "Change a.txt");
+        std::fs::write(worktree.worktree_path.join("a.txt"), "two\n").unwrap();
+
+        // Merging needs a three-way merge, which isn't attempted while the
+        // checkout has uncommitted changes, so nothing is applied.
+        std::fs::write(repository_path.join("a.txt"), "three\nfour\n").unwrap();
+        let result = cx
+            .update(|cx| merge_worktree(&worktree, fs.clone(), cx))
+            .await;
+        assert!(result.is_err(), "merging into a dirty checkout should fail");
+        assert_eq!(read(&repository_path.join("a.txt")), "three\nfour\n");
+        assert_eq!(read(&worktree.worktree_path.join("a.txt")), "two\n");
+
+        // Once the checkout is clean, merging again leaves conflict markers
+        // in the working copy without staging anything.
+        git(&repository_path, &["checkout", "--", "a.txt"]);
+        let conflicts = cx
+            .update(|cx| merge_worktree(&worktree, fs.clone(), cx))
+            .await
+            .unwrap();
+        assert_eq!(conflicts, ["a.txt"]);
+        let merged = read(&repository_path.join("a.txt"));
+        assert!(
+            merged.contains("<<<<<<<"),
+            "expected conflict markers: {merged}"
+        );
+        assert!(merged.contains("three\n") && merged.contains("two\n"));
+        assert_eq!(
+            git(&repository_path, &["diff", "--cached", "--name-only"]),
+            ""
+        );
+    }
+
+    #[gpui::test]
+    async fn test_resolve_worktree(cx: &mut TestAppContext) {
+        init_test(cx);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let repository_path = init_repository(&root);
+        let fs: Arc<dyn Fs> = Arc::new(RealFs::new(Some("git".into()), cx.executor()));
+        let project = Project::test(fs.clone(), [repository_path.as_path()], cx).await;
+        let repository = active_repository(&project, cx).await;
+
+        let text_thread_store =
+            cx.new(|cx| assistant_text_thread::TextThreadStore::fake(project.clone(), cx));
+        let history_store = cx.new(|cx| HistoryStore::new(text_thread_store, cx));
+        let agent = NativeAgent::new(
+            project.clone(),
+            history_store,
+            Templates::new(),
+            None,
+            fs.clone(),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        let connection = Rc::new(NativeAgentConnection(agent.clone()));
+        let acp_thread = cx
+            .update(|cx| connection.new_thread(project.clone(), &repository_path, cx))
+            .await
+            .unwrap();
+        let session_id = cx.update(|cx| acp_thread.read(cx).session_id().clone());
+        let thread = agent.read_with(cx, |agent, _| agent.sessions[&session_id].thread.clone());
+
+        let merged_worktree = cx
+            .update(|cx| {
+                create_worktree_in(&repository, None, root.join("worktrees"), fs.clone(), cx)
+            })
+            .await
+            .unwrap();
+        std::fs::write(merged_worktree.worktree_path.join("a.txt"), "merged\n").unwrap();
+        thread.update(cx, |thread, cx| {
+            thread.set_worktree(Some(merged_worktree.clone()), cx)
+        });
+        agent
+            .update(cx, |agent, cx| {
+                agent.resolve_worktree(&session_id, WorktreeResolution::Merge, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(read(&repository_path.join("a.txt")), "merged\n");
+        assert!(!merged_worktree.worktree_path.exists());
+        thread.read_with(cx, |thread, _| assert_eq!(thread.worktree(), None));
+
+        let discarded_worktree = cx
+            .update(|cx| {
+                create_worktree_in(&repository, None, root.join("worktrees"), fs.clone(), cx)
+            })
+            .await
+            .unwrap();
+        std::fs::write(
+            discarded_worktree.worktree_path.join("a.txt"),
+            "discarded\n",
+        )
+        .unwrap();
+        thread.update(cx, |thread, cx| {
+            thread.set_worktree(Some(discarded_worktree.clone()), cx)
+        });
+        agent
+            .update(cx, |agent, cx| {
+                agent.resolve_worktree(&session_id, WorktreeResolution::Discard, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(read(&repository_path.join("a.txt")), "merged\n");
+        assert!(!discarded_worktree.worktree_path.exists());
+        thread.read_with(cx, |thread, _| assert_eq!(thread.worktree(), None));
+
+        let error = agent
+            .update(cx, |agent, cx| {
+                agent.resolve_worktree(&session_id, WorktreeResolution::Merge, cx)
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Thread is not running in a worktree");
+    }
+
+    async fn active_repository(
+        project: &Entity<Project>,
+        cx: &mut TestAppContext,
+    ) -> Entity<Repository> {
+        let worktree = project.read_with(cx, |project, cx| project.worktrees(cx).next().unwrap());
+        worktree
+            .read_with(cx, |worktree, _| {
+                worktree.as_local().unwrap().scan_complete()
+            })
+            .await;
+        cx.run_until_parked();
+        let repository =
+            project.read_with(cx, |project, cx| project.active_repository(cx).unwrap());
+        repository
+            .update(cx, |repository, _| repository.barrier())
+            .await
+            .unwrap();
+        repository
+    }
+
+    fn init_repository(root: &Path) -> PathBuf {
+        let path = root.join("project");
+        std::fs::create_dir(&path).unwrap();
+        std::fs::write(path.join("a.txt"), "one\n").unwrap();
+        git(&path, &["init", "--initial-branch", "main"]);
+        commit(&path, "Initial commit");
+        path
+    }
+
+    fn commit(path: &Path, message: &str) {
+        git(path, &["add", "--all"]);
+        git(
+            path,
+            &[
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@zed.dev",
+                "commit",
+                "--message",
+                message,
+            ],
+        );
+    }
+
+    #[track_caller]
+    fn git(path: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .current_dir(path)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    #[track_caller]
+    fn read(path: &Path) -> String {
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        env_logger::try_init().ok();
+        cx.executor().allow_parking();
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            LanguageModelRegistry::test(cx);
+        });
+    }
+}
@@ -0,0 +1,749 @@
+mod output;
+
+use acp_thread::UserMessageId;
+use agent::{ContextServerRegistry, ThreadEvent};
+use agent_client_protocol as acp;
+use agent_settings::{AgentProfileId, AgentSettings};
+use anyhow::{Context as _, Result, anyhow};
+use clap::{Parser, ValueEnum};
+use collections::HashMap;
+use futures::StreamExt as _;
+use futures::future::{self, Shared};
+use gpui::{App, AppContext as _, Application, AsyncApp, Entity, Task};
+use headless::HeadlessAppState;
+use language_model::{LanguageModel, LanguageModelRegistry, SelectedModel};
+use output::{EventWriter, OutputEvent};
+use project::Project;
+use prompt_store::{ProjectContext, PromptBuilder, WorktreeContext};
+use reqwest_client::ReqwestClient;
+use settings::{Settings as _, SettingsStore};
+use std::io::Read as _;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr as _;
+use std::sync::Arc;
+use std::time::Duration;
+use util::command::new_smol_command;
+
+/// Runs the agent on a project without opening a window, streaming its
+/// events to stdout as JSON lines.
+#[derive(Parser, Debug)]
+#[command(name = "zed-agent", disable_version_flag = true)]
+struct Args {
+    /// The prompt to send to the agent. Read from stdin when omitted.
+    #[arg(value_name = "PROMPT", conflicts_with = "prompt_file")]
+    prompt: Option<String>,
+    /// Reads the prompt from this file.
+    #[arg(long, value_name = "PATH")]
+    prompt_file: Option<PathBuf>,
+    /// The directory to open as the project.
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    project: PathBuf,
+    /// provider/model to use. Defaults to the model configured in settings.
+    #[arg(long)]
+    model: Option<String>,
+    /// Agent profile to run with. Defaults to the profile configured in settings.
+    #[arg(long)]
+    profile: Option<String>,
+    /// How to answer tool calls that ask for confirmation.
+    #[arg(long, value_enum, default_value_t = ToolPermissions::Deny)]
+    tool_permissions: ToolPermissions,
+    /// Tools that are allowed to run when `--tool-permissions` is `deny`.
+    #[arg(long = "allow-tool", value_name = "TOOL")]
+    allowed_tools: Vec<String>,
+    /// Writes the diff of the project's changes against HEAD to this file.
+    #[arg(long, value_name = "PATH")]
+    diff: Option<PathBuf>,
+    /// Settings file to load instead of the user's settings.
+    #[arg(long, value_name = "PATH")]
+    settings: Option<PathBuf>,
+    /// Cancels the run after this many seconds.
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+    /// Output current environment variables as JSON to stdout
+    #[arg(long, hide = true)]
+    printenv: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ToolPermissions {
+    /// Allow every tool call.
+    Allow,
+    /// Deny tool calls that ask for confirmation, unless allowed with `--allow-tool`.
+    Deny,
+}
+
+/// How the run ended, reported as the process' exit status.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    Completed,
+    Failed,
+    Refused,
+    LimitReached,
+    Cancelled,
+    PermissionDenied,
+}
+
+impl Outcome {
+    fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Completed => 0,
+            Outcome::Failed => 1,
+            Outcome::Refused => 2,
+            Outcome::LimitReached => 3,
+            Outcome::Cancelled => 4,
+            Outcome::PermissionDenied => 5,
+        }
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    // This prevents errors showing up in the logs, because
+    // project::environment::load_shell_environment() calls
+    // std::env::current_exe().unwrap() --printenv
+    if args.printenv {
+        util::shell_env::print_env();
+        return;
+    }
+
+    zlog::init();
+    zlog::init_output_stderr();
+
+    let http_client = Arc::new(ReqwestClient::new());
+    let app = Application::headless().with_http_client(http_client);
+
+    app.run(move |cx| {
+        let app_state = Arc::new(init(cx));
+        let settings = load_settings(args.settings.as_deref(), cx);
+
+        cx.spawn(async move |cx| {
+            let mut events = EventWriter::new(std::io::stdout());
+            let outcome = match settings {
+                Ok(()) => run(args, app_state, &mut events, cx).await,
+                Err(error) => Err(error),
+            };
+            std::process::exit(report(outcome, &mut events));
+        })
+        .detach();
+    });
+}
+
+fn init(cx: &mut App) -> HeadlessAppState {
+    let app_state = headless::init("Zed Agent CLI", env!("ZED_PKG_VERSION"), cx);
+    let stdout_is_a_pty = false;
+    let prompt_builder = PromptBuilder::load(app_state.fs.clone(), stdout_is_a_pty, cx);
+    agent_ui::init(
+        app_state.fs.clone(),
+        app_state.client.clone(),
+        prompt_builder,
+        app_state.languages.clone(),
+        false,
+        cx,
+    );
+    app_state
+}
+
+/// Writes the final event of the run and returns the process' exit code.
+fn report<W: std::io::Write>(
+    outcome: Result<(Outcome, Option<PathBuf>, Option<u64>)>,
+    events: &mut EventWriter<W>,
+) -> i32 {
+    match outcome {
+        Ok((outcome, diff, used_tokens)) => {
+            events.emit(OutputEvent::Done {
+                exit_code: outcome.exit_code(),
+                diff,
+                used_tokens,
+            });
+            outcome.exit_code()
+        }
+        Err(error) => {
+            events.emit(OutputEvent::Error {
+                message: format!("{error:#}"),
+            });
+            Outcome::Failed.exit_code()
+        }
+    }
+}
+
+fn load_settings(path: Option<&Path>, cx: &mut App) -> Result<()> {
+    let contents = match path {
+        Some(path) => {
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?
+        }
+        None => match std::fs::read_to_string(paths::settings_file()) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => "{}".to_string(),
+            Err(error) => return Err(error).context("reading user settings"),
+        },
+    };
+    SettingsStore::update_global(cx, |store, cx| {
+        // Confirmations are answered by `--tool-permissions`, so they must not
+        // be skipped because of the user's settings.
+        let contents = store.new_text_for_update(contents, |settings| {
+            settings
+                .agent
+                .get_or_insert_default()
+                .set_always_allow_tool_actions(false);
+        });
+        store.set_user_settings(&contents, cx)
+    })
+    .result()
+    .context("parsing settings")?;
+    Ok(())
+}
+
+async fn run<W: std::io::Write>(
+    args: Args,
+    app_state: Arc<HeadlessAppState>,
+    events: &mut EventWriter<W>,
+    cx: &mut AsyncApp,
+) -> Result<(Outcome, Option<PathBuf>, Option<u64>)> {
+    let prompt = read_prompt(&args)?;
+    let project_path = app_state
+        .fs
+        .canonicalize(&args.project)
+        .await
+        .with_context(|| format!("opening {}", args.project.display()))?;
+
+    let tasks = cx.update(|cx| {
+        LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
+            registry
+                .providers()
+                .iter()
+                .map(|provider| provider.authenticate(cx))
+                .collect::<Vec<_>>()
+        })
+    })?;
+    future::join_all(tasks).await;
+
+    let (model, profile_id) = cx.update(|cx| {
+        let registry = LanguageModelRegistry::read_global(cx);
+        let model = match &args.model {
+            Some(model_name) => find_model(model_name, registry, cx)?,
+            None => {
+                registry
+                    .default_model()
+                    .context("No model is configured. Pass one with `--model provider/model`")?
+                    .model
+            }
+        };
+
+        let agent_settings = AgentSettings::get_global(cx);
+        let profile_id = match &args.profile {
+            Some(profile) => AgentProfileId(profile.as_str().into()),
+            None => agent_settings.default_profile.clone(),
+        };
+        anyhow::ensure!(
+            agent_settings.profiles.contains_key(&profile_id),
+            "No agent profile with ID {}",
+            profile_id.as_str()
+        );
+        anyhow::Ok((model, profile_id))
+    })??;
+
+    let project = cx.update(|cx| {
+        Project::local(
+            app_state.client.clone(),
+            app_state.node_runtime.clone(),
+            app_state.user_store.clone(),
+            app_state.languages.clone(),
+            app_state.fs.clone(),
+            None,
+            cx,
+        )
+    })?;
+    let worktree = project
+        .update(cx, |project, cx| {
+            project.create_worktree(&project_path, true, cx)
+        })?
+        .await?;
+    worktree
+        .update(cx, |worktree, _cx| {
+            worktree.as_local().map(|worktree| worktree.scan_complete())
+        })?
+        .context("project is not a local directory")?
+        .await;
+
+    let thread = cx.update(|cx| {
+        let worktrees = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| WorktreeContext {
+                root_name: worktree.read(cx).root_name_str().into(),
+                abs_path: worktree.read(cx).abs_path(),
+                rules_file: None,
+            })
+            .collect::<Vec<_>>();
+        let project_context = cx.new(|_cx| ProjectContext::new(worktrees, vec![]));
+        let context_server_registry =
+            cx.new(|cx| ContextServerRegistry::new(project.read(cx).context_server_store(), cx));
+        let thread = cx.new(|cx| {
+            agent::Thread::new(
+                project.clone(),
+                project_context,
+                context_server_registry,
+                agent::Templates::new(),
+                None,
+                cx,
+            )
+        });
+        thread.update(cx, |thread, cx| {
+            thread.add_default_tools(
+                Rc::new(HeadlessThreadEnvironment {
+                    project: project.clone(),
+                }),
+                cx,
+            );
+            thread.set_profile(profile_id, cx);
+            thread.set_model(model, cx);
+        });
+        thread
+    })?;
+
+    let mut event_stream = thread.update(cx, |thread, cx| {
+        thread.send(UserMessageId::new(), [prompt.as_str()], cx)
+    })??;
+
+    let _timeout = args.timeout.map(|seconds| {
+        let thread = thread.clone();
+        cx.spawn(async move |cx| {
+            cx.background_executor()
+                .timer(Duration::from_secs(seconds))
+                .await;
+            thread.update(cx, |thread, cx| thread.cancel(cx)).ok();
+        })
+    });
+
+    let mut tool_calls = HashMap::<acp::ToolCallId, ToolCallState>::default();
+    let mut permission_denied = false;
+    let mut outcome = Outcome::Failed;
+    while let Some(event) = event_stream.next().await {
+        let event = match event {
+            Ok(event) => event,
+            Err(error) => {
+                events.emit(OutputEvent::Error {
+                    message: format!("{error:#}"),
+                });
+                outcome = Outcome::Failed;
+                continue;
+            }
+        };
+
+        match event {
+            ThreadEvent::UserMessage(_) | ThreadEvent::ActivityChanged { .. } => {}
+            ThreadEvent::AgentText(text) => events.push_text(&text),
+            ThreadEvent::AgentThinking(text) => events.push_thinking(&text),
+            ThreadEvent::ToolCall(tool_call) => {
+                let tool = tool_call
+                    .meta
+                    .as_ref()
+                    .and_then(|meta| meta.get("tool_name"))
+                    .and_then(|name| name.as_str())
+                    .map(ToString::to_string);
+                tool_calls.insert(
+                    tool_call.id.clone(),
+                    ToolCallState {
+                        tool: tool.clone(),
+                        input: tool_call.raw_input.clone(),
+                    },
+                );
+                events.emit(OutputEvent::ToolCall {
+                    id: tool_call.id.0.to_string(),
+                    tool,
+                    title: tool_call.title,
+                    input: tool_call.raw_input,
+                });
+            }
+            ThreadEvent::ToolCallUpdate(acp_thread::ToolCallUpdate::UpdateFields(update)) => {
+                let state = tool_calls.entry(update.id.clone()).or_default();
+                if let Some(input) = update.fields.raw_input {
+                    state.input = Some(input);
+                }
+                // Input is streamed in many small updates, so only changes of
+                // status and results are reported.
+                if update.fields.status.is_some() || update.fields.raw_output.is_some() {
+                    events.emit(OutputEvent::ToolCallUpdate {
+                        id: update.id.0.to_string(),
+                        tool: state.tool.clone(),
+                        status: update.fields.status,
+                        input: state.input.clone(),
+                        output: update.fields.raw_output,
+                    });
+                }
+            }
+            ThreadEvent::ToolCallUpdate(_) => {}
+            ThreadEvent::ToolCallAuthorization(authorization) => {
+                let tool = tool_calls
+                    .get(&authorization.tool_call.id)
+                    .and_then(|state| state.tool.clone());
+                let allowed = match args.tool_permissions {
+                    ToolPermissions::Allow => true,
+                    ToolPermissions::Deny => tool
+                        .as_ref()
+                        .is_some_and(|tool| args.allowed_tools.contains(tool)),
+                };
+                permission_denied |= !allowed;
+                events.emit(OutputEvent::ToolPermission {
+                    id: authorization.tool_call.id.0.to_string(),
+                    tool,
+                    title: authorization.tool_call.fields.title,
+                    allowed,
+                });
+                // Dropping the response without choosing an option denies
+                // the tool call.
+                if let Some(option_id) = permission_option(&authorization.options, allowed) {
+                    authorization.response.send(option_id).ok();
+                }
+            }
+            ThreadEvent::Retry(status) => events.emit(OutputEvent::Retry {
+                attempt: status.attempt,
+                max_attempts: status.max_attempts,
+                error: status.last_error.to_string(),
+            }),
+            ThreadEvent::Stop(reason) => {
+                outcome = match reason {
+                    acp::StopReason::EndTurn if permission_denied => Outcome::PermissionDenied,
+                    acp::StopReason::EndTurn => Outcome::Completed,
+                    acp::StopReason::MaxTokens | acp::StopReason::MaxTurnRequests => {
+                        Outcome::LimitReached
+                    }
+                    acp::StopReason::Refusal => Outcome::Refused,
+                    acp::StopReason::Cancelled => Outcome::Cancelled,
+                };
+                events.emit(OutputEvent::Stop { reason });
+                break;
+            }
+        }
+    }
+    events.flush_text();
+
+    let diff = if let Some(diff_path) = args.diff {
+        let diff = repository_diff(&project_path).await?;
+        std::fs::write(&diff_path, diff)
+            .with_context(|| format!("writing diff to {}", diff_path.display()))?;
+        Some(diff_path)
+    } else {
+        None
+    };
+    let used_tokens = thread.read_with(cx, |thread, _cx| {
+        thread.latest_token_usage().map(|usage| usage.used_tokens)
+    })?;
+
+    Ok((outcome, diff, used_tokens))
+}
+
+#[derive(Default)]
+struct ToolCallState {
+    tool: Option<String>,
+    input: Option<serde_json::Value>,
+}
+
+fn read_prompt(args: &Args) -> Result<String> {
+    let prompt = if let Some(prompt) = &args.prompt {
+        prompt.clone()
+    } else if let Some(path) = &args.prompt_file {
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?
+    } else {
+        let mut prompt = String::new();
+        std::io::stdin()
+            .read_to_string(&mut prompt)
+            .context("reading prompt from stdin")?;
+        prompt
+    };
+    anyhow::ensure!(!prompt.trim().is_empty(), "The prompt is empty");
+    Ok(prompt)
+}
+
+fn find_model(
+    model_name: &str,
+    model_registry: &LanguageModelRegistry,
+    cx: &App,
+) -> Result<Arc<dyn LanguageModel>> {
+    let selected = SelectedModel::from_str(model_name).map_err(|e| anyhow!(e))?;
+    model_registry
+        .available_models(cx)
+        .find(|model| model.id() == selected.model && model.provider_id() == selected.provider)
+        .ok_or_else(|| {
+            anyhow!(
+                "No language model with ID {}/{} was available. Available models: {}",
+                selected.provider.0,
+                selected.model.0,
+                model_registry
+                    .available_models(cx)
+                    .map(|model| format!("{}/{}", model.provider_id().0, model.id().0))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+}
+
+/// Picks the option that answers a confirmation. "Always allow" is never
+/// chosen, as it would write to the user's settings.
+fn permission_option(
+    options: &[acp::PermissionOption],
+    allowed: bool,
+) -> Option<acp::PermissionOptionId> {
+    options
+        .iter()
+        .find(|option| match option.kind {
+            acp::PermissionOptionKind::AllowOnce => allowed,
+            acp::PermissionOptionKind::RejectOnce | acp::PermissionOptionKind::RejectAlways => {
+                !allowed
+            }
+            acp::PermissionOptionKind::AllowAlways => false,
+        })
+        .map(|option| option.id.clone())
+}
+
+/// Diffs the working tree against HEAD, including untracked files.
+async fn repository_diff(repo_path: &Path) -> Result<String> {
+    let mut diff = run_git(repo_path, &["diff", "--no-color", "HEAD"], true).await?;
+    let untracked = run_git(
+        repo_path,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+        true,
+    )
+    .await?;
+    for path in untracked.split('\0').filter(|path| !path.is_empty()) {
+        // `git diff --no-index` exits with 1 when the files differ.
+        diff.push_str(
+            &run_git(
+                repo_path,
+                &["diff", "--no-color", "--no-index", "--", "/dev/null", path],
+                false,
+            )
+            .await?,
+        );
+    }
+    Ok(diff)
+}
+
+async fn run_git(repo_path: &Path, args: &[&str], check_status: bool) -> Result<String> {
+    let output = new_smol_command("git")
+        .current_dir(repo_path)
+        .args(args)
+        .output()
+        .await?;
+
+    anyhow::ensure!(
+        !check_status || output.status.success(),
+        "`git {}` within `{}` failed with status: {}\nstderr:\n{}",
+        args.join(" "),
+        repo_path.display(),
+        output.status,
+        String::from_utf8_lossy(&output.stderr),
+    );
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+struct HeadlessThreadEnvironment {
+    project: Entity<Project>,
+}
+
+struct HeadlessTerminalHandle {
+    terminal: Entity<acp_thread::Terminal>,
+}
+
+impl agent::TerminalHandle for HeadlessTerminalHandle {
+    fn id(&self, cx: &AsyncApp) -> Result<acp::TerminalId> {
+        self.terminal.read_with(cx, |term, _cx| term.id().clone())
+    }
+
+    fn wait_for_exit(&self, cx: &AsyncApp) -> Result<Shared<Task<acp::TerminalExitStatus>>> {
+        self.terminal
+            .read_with(cx, |term, _cx| term.wait_for_exit())
+    }
+
+    fn current_output(&self, cx: &AsyncApp) -> Result<acp::TerminalOutputResponse> {
+        self.terminal
+            .read_with(cx, |term, cx| term.current_output(cx))
+    }
+}
+
+impl agent::ThreadEnvironment for HeadlessThreadEnvironment {
+    fn create_terminal(
+        &self,
+        command: String,
+        cwd: Option<PathBuf>,
+        output_byte_limit: Option<u64>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<Rc<dyn agent::TerminalHandle>>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let language_registry =
+                project.read_with(cx, |project, _cx| project.languages().clone())?;
+            let id = acp::TerminalId(uuid::Uuid::new_v4().to_string().into());
+            let terminal =
+                acp_thread::create_terminal_entity(command, &[], vec![], cwd.clone(), &project, cx)
+                    .await?;
+            let terminal = cx.new(|cx| {
+                acp_thread::Terminal::new(
+                    id,
+                    "",
+                    cwd,
+                    output_byte_limit.map(|limit| limit as usize),
+                    terminal,
+                    language_registry,
+                    cx,
+                )
+            })?;
+            Ok(Rc::new(HeadlessTerminalHandle { terminal }) as Rc<dyn agent::TerminalHandle>)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::UserStore;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use language::LanguageRegistry;
+    use language_model::fake_provider::{FakeLanguageModel, FakeLanguageModelProvider};
+    use language_model::{LanguageModelProviderId, LanguageModelProviderName};
+    use node_runtime::NodeRuntime;
+    use serde_json::json;
+    use util::path;
+
+    fn option(id: &str, kind: acp::PermissionOptionKind) -> acp::PermissionOption {
+        acp::PermissionOption {
+            id: acp::PermissionOptionId(id.into()),
+            name: id.into(),
+            kind,
+            meta: None,
+        }
+    }
+
+    #[test]
+    fn test_permission_option() {
+        let options = vec![
+            option("always_allow", acp::PermissionOptionKind::AllowAlways),
+            option("allow", acp::PermissionOptionKind::AllowOnce),
+            option("deny", acp::PermissionOptionKind::RejectOnce),
+        ];
+        assert_eq!(
+            permission_option(&options, true),
+            Some(acp::PermissionOptionId("allow".into()))
+        );
+        assert_eq!(
+            permission_option(&options, false),
+            Some(acp::PermissionOptionId("deny".into()))
+        );
+        assert_eq!(permission_option(&options[..1], true), None);
+    }
+
+    #[gpui::test]
+    async fn test_run(cx: &mut TestAppContext) {
+        let (app_state, model) = init_test(cx).await;
+        let args = Args::try_parse_from([
+            "zed-agent",
+            "--project",
+            path!("/project"),
+            "--model",
+            "test/fake",
+            "Say hello",
+        ])
+        .unwrap();
+
+        let task = cx.spawn(|mut cx| async move {
+            let mut events = EventWriter::new(Vec::new());
+            let outcome = run(args, app_state, &mut events, &mut cx).await;
+            let exit_code = report(outcome, &mut events);
+            (exit_code, events.into_inner())
+        });
+        cx.run_until_parked();
+
+        model.send_last_completion_stream_text_chunk("Hello");
+        model.send_last_completion_stream_text_chunk(" there!");
+        model.end_last_completion_stream();
+        let (exit_code, output) = task.await;
+
+        assert_eq!(exit_code, 0);
+        let lines = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                json!({"type": "text", "text": "Hello there!"}),
+                json!({"type": "stop", "reason": "end_turn"}),
+                json!({"type": "done", "exit_code": 0, "diff": null, "used_tokens": null}),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_run_with_unknown_model(cx: &mut TestAppContext) {
+        let (app_state, _model) = init_test(cx).await;
+        let args = Args::try_parse_from([
+            "zed-agent",
+            "--project",
+            path!("/project"),
+            "--model",
+            "test/missing",
+            "Say hello",
+        ])
+        .unwrap();
+
+        let (exit_code, output) = cx
+            .spawn(|mut cx| async move {
+                let mut events = EventWriter::new(Vec::new());
+                let outcome = run(args, app_state, &mut events, &mut cx).await;
+                let exit_code = report(outcome, &mut events);
+                (exit_code, events.into_inner())
+            })
+            .await;
+
+        assert_eq!(exit_code, Outcome::Failed.exit_code());
+        let output = String::from_utf8(output).unwrap();
+        let event = serde_json::from_str::<serde_json::Value>(output.trim()).unwrap();
+        assert_eq!(event["type"], "error");
+        assert!(
+            event["message"]
+                .as_str()
+                .unwrap()
+                .starts_with("No language model with ID test/missing"),
+            "{event}"
+        );
+    }
+
+    async fn init_test(cx: &mut TestAppContext) -> (Arc<HeadlessAppState>, Arc<FakeLanguageModel>) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/project"), json!({"README.md": "# Project"}))
+            .await;
+
+        let provider = FakeLanguageModelProvider::new(
+            LanguageModelProviderId::new("test"),
+            LanguageModelProviderName::new("Test"),
+        );
+        let model = Arc::new(provider.test_model());
+        let provider = provider.with_model(model.clone());
+        let app_state = cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            release_channel::init(semver::Version::new(0, 0, 0), cx);
+            LanguageModelRegistry::test(cx);
+            LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
+                registry.register_provider(Arc::new(provider), cx)
+            });
+
+            let clock = Arc::new(clock::FakeSystemClock::new());
+            let http_client = http_client::FakeHttpClient::with_404_response();
+            let client = client::Client::new(clock, http_client, cx);
+            let user_store = cx.new(|cx| UserStore::new(client.clone(), cx));
+            Arc::new(HeadlessAppState {
+                languages: Arc::new(LanguageRegistry::test(cx.background_executor().clone())),
+                client,
+                user_store,
+                fs,
+                node_runtime: NodeRuntime::unavailable(),
+            })
+        });
+        (app_state, model)
+    }
+}
@@ -0,0 +1,153 @@
+use agent_client_protocol as acp;
+use serde::Serialize;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+/// A line of the JSON event stream written to stdout.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputEvent {
+    Text {
+        text: String,
+    },
+    Thinking {
+        text: String,
+    },
+    ToolCall {
+        id: String,
+        tool: Option<String>,
+        title: String,
+        input: Option<serde_json::Value>,
+    },
+    ToolCallUpdate {
+        id: String,
+        tool: Option<String>,
+        status: Option<acp::ToolCallStatus>,
+        input: Option<serde_json::Value>,
+        output: Option<serde_json::Value>,
+    },
+    ToolPermission {
+        id: String,
+        tool: Option<String>,
+        title: Option<String>,
+        allowed: bool,
+    },
+    Retry {
+        attempt: usize,
+        max_attempts: usize,
+        error: String,
+    },
+    Stop {
+        reason: acp::StopReason,
+    },
+    Error {
+        message: String,
+    },
+    Done {
+        exit_code: i32,
+        diff: Option<PathBuf>,
+        used_tokens: Option<u64>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TextKind {
+    Text,
+    Thinking,
+}
+
+/// Writes events as JSON lines, coalescing streamed text chunks so that each
+/// run of agent text or thinking becomes a single event.
+pub struct EventWriter<W: std::io::Write> {
+    writer: W,
+    pending_text: Option<(TextKind, String)>,
+}
+
+impl<W: std::io::Write> EventWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            pending_text: None,
+        }
+    }
+
+    pub fn push_text(&mut self, text: &str) {
+        self.push_chunk(TextKind::Text, text);
+    }
+
+    pub fn push_thinking(&mut self, text: &str) {
+        self.push_chunk(TextKind::Thinking, text);
+    }
+
+    fn push_chunk(&mut self, kind: TextKind, chunk: &str) {
+        if let Some((pending_kind, pending)) = &mut self.pending_text
+            && *pending_kind == kind
+        {
+            pending.push_str(chunk);
+            return;
+        }
+        self.flush_text();
+        self.pending_text = Some((kind, chunk.to_string()));
+    }
+
+    pub fn emit(&mut self, event: OutputEvent) {
+        self.flush_text();
+        self.write(&event);
+    }
+
+    pub fn flush_text(&mut self) {
+        let Some((kind, text)) = self.pending_text.take() else {
+            return;
+        };
+        let event = match kind {
+            TextKind::Text => OutputEvent::Text { text },
+            TextKind::Thinking => OutputEvent::Thinking { text },
+        };
+        self.write(&event);
+    }
+
+    fn write(&mut self, event: &OutputEvent) {
+        // Events are written one per line so that consumers can act on them
+        // while the agent is still running.
+        if let Ok(line) = serde_json::to_string(event) {
+            writeln!(self.writer, "{line}").ok();
+            self.writer.flush().ok();
+        }
+    }
+
+    #[cfg(test)]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_chunks_are_coalesced() {
+        let mut writer = EventWriter::new(Vec::new());
+        writer.push_thinking("Let me ");
+        writer.push_thinking("look.");
+        writer.push_text("Done");
+        writer.push_text(".");
+        writer.emit(OutputEvent::Stop {
+            reason: acp::StopReason::EndTurn,
+        });
+
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let lines = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                serde_json::json!({"type": "thinking", "text": "Let me look."}),
+                serde_json::json!({"type": "text", "text": "Done."}),
+                serde_json::json!({"type": "stop", "reason": "end_turn"}),
+            ]
+        );
+    }
+}
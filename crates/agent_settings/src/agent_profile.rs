@@ -70,6 +70,10 @@ impl AgentProfile {
         let default_model = base_profile
             .as_ref()
             .and_then(|profile| profile.default_model.clone());
+        let fallback_models = base_profile
+            .as_ref()
+            .map(|profile| profile.fallback_models.clone())
+            .unwrap_or_default();
 
         let profile_settings = AgentProfileSettings {
             name: name.into(),
@@ -77,6 +81,7 @@ impl AgentProfile {
             enable_all_context_servers,
             context_servers,
             default_model,
+            fallback_models,
         };
 
         update_settings_file(fs, cx, {
@@ -109,6 +114,8 @@ pub struct AgentProfileSettings {
     pub context_servers: IndexMap<Arc<str>, ContextServerPreset>,
     /// Default language model to apply when this profile becomes active.
     pub default_model: Option<LanguageModelSelection>,
+    /// Models to switch to, in order, when a thread's model can't complete a request.
+    pub fallback_models: Vec<LanguageModelSelection>,
 }
 
 impl AgentProfileSettings {
@@ -158,6 +165,8 @@ impl AgentProfileSettings {
                     })
                     .collect(),
                 default_model: self.default_model.clone(),
+                fallback_models: (!self.fallback_models.is_empty())
+                    .then(|| self.fallback_models.clone()),
             },
         );
 
@@ -173,6 +182,7 @@ impl From<AgentProfileContent> for AgentProfileSettings {
             enable_all_context_servers,
             context_servers,
             default_model,
+            fallback_models,
         } = content;
 
         Self {
@@ -184,6 +194,7 @@ impl From<AgentProfileContent> for AgentProfileSettings {
                 .map(|(server_id, preset)| (server_id, preset.into()))
                 .collect(),
             default_model,
+            fallback_models: fallback_models.unwrap_or_default(),
         }
     }
 }
@@ -4,13 +4,13 @@ use std::sync::Arc;
 
 use collections::IndexMap;
 use gpui::{App, Pixels, px};
-use language_model::LanguageModel;
+use language_model::{LanguageModel, LanguageModelPricing};
 use project::DisableAiSettings;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{
-    DefaultAgentView, DockPosition, LanguageModelParameters, LanguageModelSelection,
-    NotifyWhenAgentWaiting, RegisterSetting, Settings,
+    DefaultAgentView, DockPosition, LanguageModelParameters, LanguageModelPricingSetting,
+    LanguageModelSelection, NotifyWhenAgentWaiting, RegisterSetting, Settings,
 };
 
 pub use crate::agent_profile::*;
@@ -45,6 +45,9 @@ pub struct AgentSettings {
     pub expand_terminal_card: bool,
     pub use_modifier_to_send: bool,
     pub message_editor_min_lines: usize,
+    pub thread_budget: Option<f64>,
+    pub daily_budget: Option<f64>,
+    pub model_pricing: Vec<LanguageModelPricingSetting>,
 }
 
 impl AgentSettings {
@@ -70,6 +73,31 @@ impl AgentSettings {
         return None;
     }
 
+    /// The pricing to use for `model`, preferring the `model_pricing` setting
+    /// over the model's own pricing.
+    pub fn pricing_for_model(
+        model: &Arc<dyn LanguageModel>,
+        cx: &App,
+    ) -> Option<LanguageModelPricing> {
+        let settings = Self::get_global(cx);
+        settings
+            .model_pricing
+            .iter()
+            .rev()
+            .find(|setting| {
+                setting.provider.0 == model.provider_id().0 && setting.model == model.id().0
+            })
+            .map(|setting| {
+                LanguageModelPricing::new(
+                    setting.input,
+                    setting.output,
+                    setting.cache_read.unwrap_or(setting.input),
+                    setting.cache_write.unwrap_or(setting.input),
+                )
+            })
+            .or_else(|| model.pricing())
+    }
+
     pub fn set_inline_assistant_model(&mut self, provider: String, model: String) {
         self.inline_assistant_model = Some(LanguageModelSelection {
             provider: provider.into(),
@@ -177,6 +205,9 @@ impl Settings for AgentSettings {
             expand_terminal_card: agent.expand_terminal_card.unwrap(),
             use_modifier_to_send: agent.use_modifier_to_send.unwrap(),
             message_editor_min_lines: agent.message_editor_min_lines.unwrap(),
+            thread_budget: agent.thread_budget,
+            daily_budget: agent.daily_budget,
+            model_pricing: agent.model_pricing,
         }
     }
 }
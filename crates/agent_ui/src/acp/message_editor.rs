@@ -72,6 +72,7 @@ impl PromptCompletionProviderDelegate for Entity<MessageEditor> {
                 PromptContextType::Thread,
                 PromptContextType::Fetch,
                 PromptContextType::Rules,
+                PromptContextType::McpResource,
             ]);
         }
         supported
@@ -195,6 +196,18 @@ impl MessageEditor {
                 }
             }
         }));
+        subscriptions.push(cx.subscribe(
+            &project.read(cx).context_server_store(),
+            |this, _, event, cx| {
+                if let project::context_server_store::Event::ResourceUpdated { server_id, uri } =
+                    event
+                {
+                    this.mention_set.update(cx, |mention_set, cx| {
+                        mention_set.refresh_mcp_resource(&server_id.0, uri, cx)
+                    });
+                }
+            },
+        ));
 
         Self {
             editor,
@@ -917,8 +930,12 @@ mod tests {
     };
     use language_model::LanguageModelRegistry;
     use lsp::{CompletionContext, CompletionTriggerKind};
-    use project::{CompletionIntent, Project, ProjectPath};
+    use project::{
+        CompletionIntent, Project, ProjectPath,
+        project_settings::{ContextServerSettings, ProjectSettings},
+    };
     use serde_json::json;
+    use settings::Settings as _;
     use text::Point;
     use ui::{App, Context, IntoElement, Render, SharedString, Window};
     use util::{path, paths::PathStyle, rel_path::rel_path};
@@ -1908,6 +1925,212 @@ mod tests {
             .collect::<Vec<_>>()
     }
 
+    #[gpui::test]
+    async fn test_mcp_resource_template_completion(cx: &mut TestAppContext) {
+        use context_server::{
+            ContextServer, ContextServerId,
+            test::FakeTransport,
+            types::{
+                CompletionCompleteResponse, CompletionResult, Implementation, InitializeResponse,
+                LATEST_PROTOCOL_VERSION, ListResourceTemplatesResponse, ProtocolVersion,
+                ResourceContentsType, ResourceTemplate, ResourcesCapabilities,
+                ResourcesListResponse, ResourcesReadResponse, ServerCapabilities,
+                TextResourceContents, requests,
+            },
+        };
+
+        init_test(cx);
+
+        let app_state = cx.update(AppState::test);
+        cx.update(|cx| {
+            editor::init(cx);
+            workspace::init(app_state.clone(), cx);
+            let mut settings = ProjectSettings::get_global(cx).clone();
+            settings.context_servers.insert(
+                "test-server".into(),
+                ContextServerSettings::Http {
+                    enabled: true,
+                    url: "https://mcp.example.com/mcp".into(),
+                    headers: Default::default(),
+                },
+            );
+            ProjectSettings::override_global(settings, cx);
+        });
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(path!("/dir"), json!({ "editor": "" }))
+            .await;
+
+        let project = Project::test(app_state.fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let window = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let workspace = window.root(cx).unwrap();
+        let mut cx = VisualTestContext::from_window(*window, cx);
+
+        let completed_arguments = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let transport = FakeTransport::new(cx.executor())
+            .on_request::<requests::Initialize, _>(|_| async {
+                InitializeResponse {
+                    protocol_version: ProtocolVersion(LATEST_PROTOCOL_VERSION.to_string()),
+                    capabilities: ServerCapabilities {
+                        resources: Some(ResourcesCapabilities {
+                            subscribe: None,
+                            list_changed: None,
+                        }),
+                        ..Default::default()
+                    },
+                    server_info: Implementation {
+                        name: "test-server".into(),
+                        version: "1.0.0".into(),
+                    },
+                    meta: None,
+                }
+            })
+            .on_request::<requests::ResourcesList, _>(|_| async {
+                ResourcesListResponse {
+                    resources: Vec::new(),
+                    next_cursor: None,
+                    meta: None,
+                }
+            })
+            .on_request::<requests::ListResourceTemplates, _>(|_| async {
+                ListResourceTemplatesResponse {
+                    resource_templates: vec![ResourceTemplate {
+                        uri_template: "github://{repo}/issues/{number}".into(),
+                        name: "Issue".into(),
+                        description: None,
+                        mime_type: None,
+                    }],
+                    next_cursor: None,
+                    meta: None,
+                }
+            })
+            .on_request::<requests::CompletionComplete, _>({
+                let completed_arguments = completed_arguments.clone();
+                move |params| {
+                    let values = match params.argument.name.as_str() {
+                        "repo" => vec!["zed-industries,zed".to_string()],
+                        _ => vec!["7".to_string()],
+                    };
+                    completed_arguments
+                        .lock()
+                        .unwrap()
+                        .push((params.argument.name, params.argument.value));
+                    async move {
+                        CompletionCompleteResponse {
+                            completion: CompletionResult {
+                                values,
+                                total: None,
+                                has_more: None,
+                                meta: None,
+                            },
+                            meta: None,
+                        }
+                    }
+                }
+            })
+            .on_request::<requests::ResourcesRead, _>(|params| async move {
+                ResourcesReadResponse {
+                    contents: vec![ResourceContentsType::Text(TextResourceContents {
+                        uri: params.uri,
+                        mime_type: None,
+                        text: "Issue 7".into(),
+                    })],
+                    meta: None,
+                }
+            });
+        let context_server_store =
+            project.read_with(&cx, |project, _| project.context_server_store());
+        context_server_store.update(&mut cx, |store, cx| {
+            store.start_server(
+                Arc::new(ContextServer::new(
+                    ContextServerId("test-server".into()),
+                    Arc::new(transport),
+                )),
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        let text_thread_store = cx.new(|cx| TextThreadStore::fake(project.clone(), cx));
+        let history_store = cx.new(|cx| HistoryStore::new(text_thread_store, cx));
+        let prompt_capabilities = Rc::new(RefCell::new(acp::PromptCapabilities {
+            embedded_context: true,
+            ..Default::default()
+        }));
+        let editor = workspace.update_in(&mut cx, |workspace, window, cx| {
+            let workspace_handle = cx.weak_entity();
+            let message_editor = cx.new(|cx| {
+                MessageEditor::new(
+                    workspace_handle,
+                    project.clone(),
+                    history_store.clone(),
+                    None,
+                    prompt_capabilities.clone(),
+                    Default::default(),
+                    "Test Agent".into(),
+                    "Test",
+                    EditorMode::AutoHeight {
+                        max_lines: None,
+                        min_lines: 1,
+                    },
+                    window,
+                    cx,
+                )
+            });
+            workspace.active_pane().update(cx, |pane, cx| {
+                pane.add_item(
+                    Box::new(cx.new(|_| MessageEditorItem(message_editor.clone()))),
+                    true,
+                    true,
+                    None,
+                    window,
+                    cx,
+                );
+            });
+            message_editor.read(cx).focus_handle(cx).focus(window);
+            message_editor.read(cx).editor().clone()
+        });
+
+        cx.simulate_input("@resource Issue:");
+        editor.update(&mut cx, |editor, _| {
+            assert!(editor.has_visible_completions_menu());
+            assert_eq!(current_completion_labels(editor), &["zed-industries,zed"]);
+        });
+
+        editor.update_in(&mut cx, |editor, window, cx| {
+            editor.confirm_completion(&editor::actions::ConfirmCompletion::default(), window, cx);
+        });
+        cx.run_until_parked();
+
+        // The comma in the chosen value is escaped, so that the value is still
+        // the repository when completing the next variable.
+        editor.update(&mut cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "@resource Issue:zed-industries%2Czed,");
+            assert!(editor.has_visible_completions_menu());
+            assert_eq!(current_completion_labels(editor), &["7"]);
+        });
+        assert_eq!(
+            completed_arguments.lock().unwrap().last(),
+            Some(&("number".to_string(), String::new()))
+        );
+
+        editor.update_in(&mut cx, |editor, window, cx| {
+            editor.confirm_completion(&editor::actions::ConfirmCompletion::default(), window, cx);
+        });
+        cx.run_until_parked();
+
+        let mention_uri = MentionUri::McpResource {
+            server_id: "test-server".into(),
+            uri: url::Url::parse("github://zed-industries%2Czed/issues/7").unwrap(),
+            name: "Issue (zed-industries,zed, 7)".into(),
+        };
+        editor.update(&mut cx, |editor, cx| {
+            assert_eq!(editor.text(cx), format!("{} ", mention_uri.as_link()));
+            assert!(!editor.has_visible_completions_menu());
+        });
+    }
+
     #[gpui::test]
     async fn test_large_file_mention_fallback(cx: &mut TestAppContext) {
         init_test(cx);
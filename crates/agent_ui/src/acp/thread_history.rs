@@ -1,20 +1,23 @@
 use crate::acp::AcpThreadView;
 use crate::{AgentPanel, RemoveSelectedThread};
-use agent::{HistoryEntry, HistoryStore};
-use chrono::{Datelike as _, Local, NaiveDate, TimeDelta};
+use agent::{
+    HistoryEntry, HistoryStore, ThreadSearchFacets, ThreadSearchFilter, ThreadSearchQuery,
+};
+use chrono::{Datelike as _, Local, NaiveDate, TimeDelta, Utc};
 use editor::{Editor, EditorEvent};
 use fuzzy::StringMatchCandidate;
 use gpui::{
-    App, Entity, EventEmitter, FocusHandle, Focusable, ScrollStrategy, Task,
+    App, Corner, Entity, EventEmitter, FocusHandle, Focusable, ScrollStrategy, Task,
     UniformListScrollHandle, WeakEntity, Window, uniform_list,
 };
-use std::{fmt::Display, ops::Range};
+use std::{fmt::Display, ops::Range, path::Path};
 use text::Bias;
 use time::{OffsetDateTime, UtcOffset};
 use ui::{
-    HighlightedLabel, IconButtonShape, ListItem, ListItemSpacing, Tooltip, WithScrollbar,
-    prelude::*,
+    ContextMenu, HighlightedLabel, IconButtonShape, ListItem, ListItemSpacing, PopoverMenu,
+    Tooltip, WithScrollbar, prelude::*,
 };
+use util::ResultExt as _;
 
 pub struct AcpThreadHistory {
     pub(crate) history_store: Entity<HistoryStore>,
@@ -23,9 +26,12 @@ pub struct AcpThreadHistory {
     hovered_index: Option<usize>,
     search_editor: Entity<Editor>,
     search_query: SharedString,
+    search_filter: ThreadSearchFilter,
+    search_facets: ThreadSearchFacets,
     visible_items: Vec<ListItemType>,
     local_timezone: UtcOffset,
     _update_task: Task<()>,
+    _search_facets_task: Task<()>,
     _subscriptions: Vec<gpui::Subscription>,
 }
 
@@ -38,6 +44,7 @@ enum ListItemType {
     SearchResult {
         entry: HistoryEntry,
         positions: Vec<usize>,
+        snippet: Option<SharedString>,
     },
 }
 
@@ -82,6 +89,7 @@ impl AcpThreadHistory {
 
         let history_store_subscription = cx.observe(&history_store, |this, _, cx| {
             this.update_visible_items(true, cx);
+            this.update_search_facets(cx);
         });
 
         let scroll_handle = UniformListScrollHandle::default();
@@ -98,18 +106,45 @@ impl AcpThreadHistory {
             )
             .unwrap(),
             search_query: SharedString::default(),
+            search_filter: ThreadSearchFilter::default(),
+            search_facets: ThreadSearchFacets::default(),
             _subscriptions: vec![search_editor_subscription, history_store_subscription],
             _update_task: Task::ready(()),
+            _search_facets_task: Task::ready(()),
         };
         this.update_visible_items(false, cx);
+        this.update_search_facets(cx);
         this
     }
 
+    fn update_search_facets(&mut self, cx: &mut Context<Self>) {
+        let search_facets = self
+            .history_store
+            .update(cx, |store, cx| store.search_facets(cx));
+        self._search_facets_task = cx.spawn(async move |this, cx| {
+            let Some(search_facets) = search_facets.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.search_facets = search_facets;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn set_search_filter(&mut self, search_filter: ThreadSearchFilter, cx: &mut Context<Self>) {
+        if self.search_filter != search_filter {
+            self.search_filter = search_filter;
+            self.update_visible_items(false, cx);
+        }
+    }
+
     fn update_visible_items(&mut self, preserve_selected_item: bool, cx: &mut Context<Self>) {
         let entries = self
             .history_store
             .update(cx, |store, _| store.entries().collect());
-        let new_list_items = if self.search_query.is_empty() {
+        let new_list_items = if self.search_query.is_empty() && self.search_filter.is_empty() {
             self.add_list_separators(entries, cx)
         } else {
             self.filter_search_results(entries, cx)
@@ -173,15 +208,30 @@ impl AcpThreadHistory {
         })
     }
 
+    /// Combines fuzzy matches on titles with full-text matches on the content
+    /// of agent threads. Text threads aren't indexed, so they only appear
+    /// when no filter is active.
     fn filter_search_results(
         &self,
         entries: Vec<HistoryEntry>,
-        cx: &App,
+        cx: &mut Context<Self>,
     ) -> Task<Vec<ListItemType>> {
         let query = self.search_query.clone();
-        cx.background_spawn({
-            let executor = cx.background_executor().clone();
-            async move {
+        let search_filter = self.search_filter.clone();
+        let content_matches = self.history_store.update(cx, |store, cx| {
+            store.search_threads(
+                ThreadSearchQuery {
+                    text: query.to_string(),
+                    filter: search_filter.clone(),
+                },
+                cx,
+            )
+        });
+        let executor = cx.background_executor().clone();
+        cx.background_spawn(async move {
+            let mut items = Vec::new();
+
+            if search_filter.is_empty() {
                 let mut candidates = Vec::with_capacity(entries.len());
 
                 for (idx, entry) in entries.iter().enumerate() {
@@ -201,19 +251,43 @@ impl AcpThreadHistory {
                 )
                 .await;
 
-                matches
-                    .into_iter()
-                    .map(|search_match| ListItemType::SearchResult {
-                        entry: entries[search_match.candidate_id].clone(),
-                        positions: search_match.positions,
-                    })
-                    .collect()
+                items.extend(
+                    matches
+                        .into_iter()
+                        .map(|search_match| ListItemType::SearchResult {
+                            entry: entries[search_match.candidate_id].clone(),
+                            positions: search_match.positions,
+                            snippet: None,
+                        }),
+                );
             }
+
+            for content_match in content_matches.await.log_err().unwrap_or_default() {
+                let entry = HistoryEntry::AcpThread(content_match.metadata);
+                let entry_id = entry.id();
+                let existing_item = items.iter_mut().find(|item| {
+                    item.history_entry()
+                        .is_some_and(|existing_entry| existing_entry.id() == entry_id)
+                });
+                match existing_item {
+                    Some(ListItemType::SearchResult { snippet, .. }) => {
+                        *snippet = content_match.snippet;
+                    }
+                    _ => items.push(ListItemType::SearchResult {
+                        entry,
+                        positions: Vec::new(),
+                        snippet: content_match.snippet,
+                    }),
+                }
+            }
+
+            items
         })
     }
 
     fn search_produced_no_matches(&self) -> bool {
-        self.visible_items.is_empty() && !self.search_query.is_empty()
+        self.visible_items.is_empty()
+            && (!self.search_query.is_empty() || !self.search_filter.is_empty())
     }
 
     fn selected_history_entry(&self) -> Option<&HistoryEntry> {
@@ -349,13 +423,18 @@ impl AcpThreadHistory {
     fn render_list_item(&self, item: &ListItemType, ix: usize, cx: &Context<Self>) -> AnyElement {
         match item {
             ListItemType::Entry { entry, format } => self
-                .render_history_entry(entry, *format, ix, Vec::default(), cx)
+                .render_history_entry(entry, *format, ix, Vec::default(), None, cx)
                 .into_any(),
-            ListItemType::SearchResult { entry, positions } => self.render_history_entry(
+            ListItemType::SearchResult {
+                entry,
+                positions,
+                snippet,
+            } => self.render_history_entry(
                 entry,
                 EntryTimeFormat::DateAndTime,
                 ix,
                 positions.clone(),
+                snippet.clone(),
                 cx,
             ),
             ListItemType::BucketSeparator(bucket) => div()
@@ -377,6 +456,7 @@ impl AcpThreadHistory {
         format: EntryTimeFormat,
         ix: usize,
         highlight_positions: Vec<usize>,
+        snippet: Option<SharedString>,
         cx: &Context<Self>,
     ) -> AnyElement {
         let selected = ix == self.selected_index;
@@ -393,20 +473,33 @@ impl AcpThreadHistory {
                     .toggle_state(selected)
                     .spacing(ListItemSpacing::Sparse)
                     .start_slot(
-                        h_flex()
+                        v_flex()
                             .w_full()
-                            .gap_2()
-                            .justify_between()
+                            .min_w_0()
                             .child(
-                                HighlightedLabel::new(entry.title(), highlight_positions)
-                                    .size(LabelSize::Small)
-                                    .truncate(),
+                                h_flex()
+                                    .w_full()
+                                    .gap_2()
+                                    .justify_between()
+                                    .child(
+                                        HighlightedLabel::new(entry.title(), highlight_positions)
+                                            .size(LabelSize::Small)
+                                            .truncate(),
+                                    )
+                                    .child(
+                                        Label::new(thread_timestamp)
+                                            .color(Color::Muted)
+                                            .size(LabelSize::XSmall),
+                                    ),
                             )
-                            .child(
-                                Label::new(thread_timestamp)
-                                    .color(Color::Muted)
-                                    .size(LabelSize::XSmall),
-                            ),
+                            .when_some(snippet, |this, snippet| {
+                                this.child(
+                                    Label::new(snippet)
+                                        .color(Color::Muted)
+                                        .size(LabelSize::XSmall)
+                                        .truncate(),
+                                )
+                            }),
                     )
                     .on_hover(cx.listener(move |this, is_hovered, _window, cx| {
                         if *is_hovered {
@@ -439,6 +532,95 @@ impl AcpThreadHistory {
     }
 }
 
+impl AcpThreadHistory {
+    fn render_search_filter_menu(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let this = cx.weak_entity();
+        let search_filter = self.search_filter.clone();
+        let search_facets = self.search_facets.clone();
+        let icon_color = if search_filter.is_empty() {
+            Color::Muted
+        } else {
+            Color::Accent
+        };
+
+        PopoverMenu::new("thread-history-filter-menu")
+            .trigger_with_tooltip(
+                IconButton::new("thread-history-filter", IconName::Filter)
+                    .icon_size(IconSize::Small)
+                    .icon_color(icon_color),
+                Tooltip::text("Filter Threads"),
+            )
+            .anchor(Corner::TopRight)
+            .menu(move |window, cx| {
+                let this = this.clone();
+                let search_filter = search_filter.clone();
+                let search_facets = search_facets.clone();
+                Some(ContextMenu::build(window, cx, move |mut menu, _, _| {
+                    let set_filter = {
+                        let this = this.clone();
+                        move |search_filter: ThreadSearchFilter| {
+                            let this = this.clone();
+                            move |_: &mut Window, cx: &mut App| {
+                                this.update(cx, |this, cx| {
+                                    this.set_search_filter(search_filter.clone(), cx)
+                                })
+                                .ok();
+                            }
+                        }
+                    };
+
+                    menu = menu.header("Updated");
+                    for range in DateRange::ALL {
+                        menu = menu.toggleable_entry(
+                            range.label(),
+                            DateRange::from_filter(&search_filter) == range,
+                            IconPosition::Start,
+                            None,
+                            set_filter(ThreadSearchFilter {
+                                updated_after: range.updated_after(),
+                                ..search_filter.clone()
+                            }),
+                        );
+                    }
+
+                    for field in SearchFilterField::ALL {
+                        let values = field.values(&search_facets);
+                        if values.is_empty() {
+                            continue;
+                        }
+                        menu = menu.separator().header(field.header());
+                        for value in values.iter().cloned().map(Some).chain([None]) {
+                            let label = match &value {
+                                Some(value) => field.label(value),
+                                None => format!("Any {}", field.header().to_lowercase()),
+                            };
+                            let toggled = field.get(&search_filter) == &value;
+                            let mut search_filter = search_filter.clone();
+                            *field.get_mut(&mut search_filter) = value;
+                            menu = menu.toggleable_entry(
+                                label,
+                                toggled,
+                                IconPosition::Start,
+                                None,
+                                set_filter(search_filter),
+                            );
+                        }
+                    }
+
+                    if !search_filter.is_empty() {
+                        menu = menu.separator().entry(
+                            "Clear Filters",
+                            None,
+                            set_filter(Default::default()),
+                        );
+                    }
+
+                    menu
+                }))
+            })
+    }
+}
+
 impl Focusable for AcpThreadHistory {
     fn focus_handle(&self, cx: &App) -> FocusHandle {
         self.search_editor.focus_handle(cx)
@@ -472,7 +654,8 @@ impl Render for AcpThreadHistory {
                             .color(Color::Muted)
                             .size(IconSize::Small),
                     )
-                    .child(self.search_editor.clone()),
+                    .child(self.search_editor.clone())
+                    .child(self.render_search_filter_menu(cx)),
             )
             .child({
                 let view = v_flex()
@@ -684,6 +867,117 @@ impl From<TimeBucket> for EntryTimeFormat {
     }
 }
 
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum SearchFilterField {
+    Model,
+    Profile,
+    Project,
+}
+
+impl SearchFilterField {
+    const ALL: [SearchFilterField; 3] = [
+        SearchFilterField::Model,
+        SearchFilterField::Profile,
+        SearchFilterField::Project,
+    ];
+
+    fn header(&self) -> &'static str {
+        match self {
+            SearchFilterField::Model => "Model",
+            SearchFilterField::Profile => "Profile",
+            SearchFilterField::Project => "Project",
+        }
+    }
+
+    fn values<'a>(&self, facets: &'a ThreadSearchFacets) -> &'a [String] {
+        match self {
+            SearchFilterField::Model => &facets.models,
+            SearchFilterField::Profile => &facets.profiles,
+            SearchFilterField::Project => &facets.projects,
+        }
+    }
+
+    fn get<'a>(&self, filter: &'a ThreadSearchFilter) -> &'a Option<String> {
+        match self {
+            SearchFilterField::Model => &filter.model,
+            SearchFilterField::Profile => &filter.profile,
+            SearchFilterField::Project => &filter.project,
+        }
+    }
+
+    fn get_mut<'a>(&self, filter: &'a mut ThreadSearchFilter) -> &'a mut Option<String> {
+        match self {
+            SearchFilterField::Model => &mut filter.model,
+            SearchFilterField::Profile => &mut filter.profile,
+            SearchFilterField::Project => &mut filter.project,
+        }
+    }
+
+    fn label(&self, value: &str) -> String {
+        match self {
+            // Projects are stored as absolute worktree paths.
+            SearchFilterField::Project => Path::new(value)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| value.to_string()),
+            SearchFilterField::Model | SearchFilterField::Profile => value.to_string(),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum DateRange {
+    AnyTime,
+    PastDay,
+    PastWeek,
+    PastMonth,
+}
+
+impl DateRange {
+    const ALL: [DateRange; 4] = [
+        DateRange::AnyTime,
+        DateRange::PastDay,
+        DateRange::PastWeek,
+        DateRange::PastMonth,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            DateRange::AnyTime => "Any Time",
+            DateRange::PastDay => "Past Day",
+            DateRange::PastWeek => "Past Week",
+            DateRange::PastMonth => "Past Month",
+        }
+    }
+
+    fn duration(&self) -> Option<TimeDelta> {
+        match self {
+            DateRange::AnyTime => None,
+            DateRange::PastDay => Some(TimeDelta::days(1)),
+            DateRange::PastWeek => Some(TimeDelta::weeks(1)),
+            DateRange::PastMonth => Some(TimeDelta::days(30)),
+        }
+    }
+
+    fn updated_after(&self) -> Option<chrono::DateTime<Utc>> {
+        self.duration().map(|duration| Utc::now() - duration)
+    }
+
+    /// Recovers the range that produced the filter's `updated_after`, allowing
+    /// for the time that passed since it was selected.
+    fn from_filter(filter: &ThreadSearchFilter) -> Self {
+        let Some(updated_after) = filter.updated_after else {
+            return DateRange::AnyTime;
+        };
+        let elapsed = Utc::now() - updated_after;
+        Self::ALL
+            .into_iter()
+            .filter_map(|range| Some((range, range.duration()?)))
+            .min_by_key(|(_, duration)| (elapsed - *duration).abs())
+            .map_or(DateRange::AnyTime, |(range, _)| range)
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 enum TimeBucket {
     Today,
@@ -7,7 +7,7 @@ use acp_thread::{AgentConnection, Plan};
 use action_log::{ActionLog, ActionLogTelemetry};
 use agent::{
     ActivityStatusChanged, AgentActivityStatus, DbThreadMetadata, HistoryEntry, HistoryEntryId,
-    HistoryStore, NativeAgentConnection, NativeAgentServer,
+    HistoryStore, NativeAgentConnection, NativeAgentServer, WorktreeResolution,
 };
 use agent_client_protocol::{self as acp, PromptCapabilities};
 use agent_servers::{AgentServer, AgentServerDelegate};
@@ -20,6 +20,7 @@ use call::ActiveCall;
 use client::zed_urls;
 use cloud_llm_client::PlanV1;
 use collections::{HashMap, HashSet};
+use context_server::ContextServerId;
 use editor::scroll::Autoscroll;
 use editor::{
     Editor, EditorEvent, EditorMode, MultiBuffer, PathKey, SelectionEffects, SizingBehavior,
@@ -30,9 +31,10 @@ use futures::FutureExt as _;
 use gpui::{
     Action, Animation, AnimationExt, AnyView, App, BorderStyle, ClickEvent, ClipboardItem,
     CursorStyle, EdgesRefinement, ElementId, Empty, Entity, FocusHandle, Focusable, Hsla, Length,
-    ListOffset, ListState, PlatformDisplay, SharedString, StyleRefinement, Subscription, Task,
-    TextStyle, TextStyleRefinement, UnderlineStyle, WeakEntity, Window, WindowHandle, div,
-    ease_in_out, linear_color_stop, linear_gradient, list, point, pulsating_between,
+    ListOffset, ListState, PlatformDisplay, PromptLevel, SharedString, StyleRefinement,
+    Subscription, Task, TextStyle, TextStyleRefinement, UnderlineStyle, WeakEntity, Window,
+    WindowHandle, div, ease_in_out, linear_color_stop, linear_gradient, list, point,
+    pulsating_between,
 };
 use language::Buffer;
 
@@ -55,7 +57,7 @@ use ui::{
     PopoverMenuHandle, SpinnerLabel, TintColor, Tooltip, WithScrollbar, prelude::*,
 };
 use util::{ResultExt, size::format_file_size, time::duration_alt_display};
-use workspace::{CollaboratorId, NewTerminal, Workspace};
+use workspace::{CollaboratorId, NewTerminal, Workspace, notifications::DetachAndPromptErr as _};
 use zed_actions::agent::{Chat, ToggleModelSelector};
 use zed_actions::assistant::OpenRulesLibrary;
 
@@ -65,7 +67,9 @@ use crate::acp::ModeSelector;
 use crate::acp::entry_view_state::{EntryViewEvent, ViewEvent};
 use crate::acp::message_editor::{MessageEditor, MessageEditorEvent};
 use crate::agent_diff::AgentDiff;
+use crate::mention_set::read_mcp_resource;
 use crate::profile_selector::{ProfileProvider, ProfileSelector};
+use crate::thread_worktree::open_thread_in_worktree;
 
 use crate::ui::{
     AgentNotification, AgentNotificationEvent, BurnModeTooltip, UnavailableEditingTooltip,
@@ -88,6 +92,7 @@ enum ThreadError {
     PaymentRequired,
     ModelRequestLimitReached(cloud_llm_client::Plan),
     ToolUseLimitReached,
+    BudgetExceeded(agent::BudgetExceededError),
     Refusal,
     AuthenticationRequired(SharedString),
     Other(SharedString),
@@ -99,6 +104,8 @@ impl ThreadError {
             Self::PaymentRequired
         } else if error.is::<language_model::ToolUseLimitReachedError>() {
             Self::ToolUseLimitReached
+        } else if let Some(error) = error.downcast_ref::<agent::BudgetExceededError>() {
+            Self::BudgetExceeded(*error)
         } else if let Some(error) =
             error.downcast_ref::<language_model::ModelRequestLimitReachedError>()
         {
@@ -281,6 +288,7 @@ pub struct AcpThreadView {
     notifications: Vec<WindowHandle<AgentNotification>>,
     notification_subscriptions: HashMap<WindowHandle<AgentNotification>, Vec<Subscription>>,
     thread_retry_status: Option<RetryStatus>,
+    dismissed_model_fallback: Option<agent::ModelFallback>,
     thread_error: Option<ThreadError>,
     thread_error_markdown: Option<Entity<Markdown>>,
     thread_feedback: ThreadFeedbackState,
@@ -420,6 +428,7 @@ impl AcpThreadView {
             notification_subscriptions: HashMap::default(),
             list_state: list_state,
             thread_retry_status: None,
+            dismissed_model_fallback: None,
             thread_error: None,
             thread_error_markdown: None,
             thread_feedback: Default::default(),
@@ -716,6 +725,12 @@ impl AcpThreadView {
                             }
                         }
 
+                        // Commands may have been advertised before we subscribed.
+                        let available_commands = thread.read(cx).available_commands().to_vec();
+                        if !available_commands.is_empty() {
+                            this.set_available_commands(&thread, available_commands, window, cx);
+                        }
+
                         this.thread_state = ThreadState::Ready {
                             thread,
                             title_editor,
@@ -1376,6 +1391,202 @@ impl AcpThreadView {
         .detach();
     }
 
+    /// Forks are checked out in a worktree on this machine, which can't be
+    /// done for repositories on a remote host.
+    fn can_fork(&self, cx: &App) -> bool {
+        self.project.read(cx).is_local() && self.as_native_thread(cx).is_some()
+    }
+
+    fn fork_from_message(&mut self, entry_ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.can_fork(cx) {
+            return;
+        }
+        let Some(thread) = self.thread() else {
+            return;
+        };
+        let Some(native_thread) = self.as_native_thread(cx) else {
+            return;
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let Some(message) = thread
+            .read(cx)
+            .entries()
+            .get(entry_ix)
+            .and_then(|entry| entry.user_message())
+        else {
+            return;
+        };
+        let Some(message_id) = message.id.clone() else {
+            return;
+        };
+
+        // Restore the worktree to the state the project was in when this
+        // message was sent, falling back to `HEAD` if no checkpoint was taken.
+        let repository = self.project.read(cx).active_repository(cx);
+        let commit = repository.as_ref().and_then(|repository| {
+            let checkpoint = message.checkpoint.as_ref()?.git_checkpoint();
+            let work_directory = repository.read(cx).work_directory_abs_path.clone();
+            Some(
+                checkpoint
+                    .repository_checkpoint(&work_directory)?
+                    .commit_sha
+                    .to_string(),
+            )
+        });
+
+        let fork = native_thread.read(cx).fork(&message_id, cx);
+        let worktree = repository.map(|repository| {
+            let fs = workspace.read(cx).app_state().fs.clone();
+            agent::worktree::create_worktree(&repository, commit, fs, cx)
+        });
+        let history_store = self.history_store.clone();
+        let app_state = workspace.read(cx).app_state().clone();
+
+        cx.spawn_in(window, async move |_, cx| {
+            let mut db_thread = fork.await?;
+            if let Some(worktree) = worktree {
+                db_thread.worktree = Some(worktree.await?);
+            }
+            let worktree = db_thread.worktree.clone();
+            let metadata = history_store
+                .update(cx, |history_store, cx| {
+                    history_store.save_new_thread(db_thread, cx)
+                })?
+                .await?;
+
+            if let Some(worktree) = worktree {
+                cx.update(|_, cx| open_thread_in_worktree(&worktree, metadata, app_state, cx))?
+                    .await?;
+            } else {
+                workspace.update_in(cx, |workspace, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| {
+                            panel.load_agent_thread(metadata, window, cx);
+                        });
+                    }
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to fork thread", window, cx, |_, _, _| None);
+    }
+
+    fn compare_with_original(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(worktree) = self
+            .as_native_thread(cx)
+            .and_then(|thread| thread.read(cx).worktree().cloned())
+        else {
+            return;
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+
+        let title = format!("Changes in {}", worktree.branch);
+        let diff_language_task = workspace
+            .read(cx)
+            .app_state()
+            .languages
+            .language_for_name("Diff");
+        let fs = workspace.read(cx).app_state().fs.clone();
+        let diff = agent::worktree::diff_worktree(&worktree, fs, cx);
+        let project = workspace.read(cx).project().clone();
+        window
+            .spawn(cx, async move |cx| {
+                let diff = diff.await?;
+                let diff_language = diff_language_task.await.log_err();
+
+                let buffer = project
+                    .update(cx, |project, cx| project.create_buffer(false, cx))?
+                    .await?;
+                buffer.update(cx, |buffer, cx| {
+                    buffer.set_text(diff, cx);
+                    buffer.set_language(diff_language, cx);
+                    buffer.set_capability(language::Capability::ReadOnly, cx);
+                })?;
+
+                workspace.update_in(cx, |workspace, window, cx| {
+                    let buffer =
+                        cx.new(|cx| MultiBuffer::singleton(buffer, cx).with_title(title.clone()));
+                    workspace.add_item_to_active_pane(
+                        Box::new(cx.new(|cx| {
+                            let mut editor =
+                                Editor::for_multibuffer(buffer, Some(project.clone()), window, cx);
+                            editor.set_breadcrumb_header(title);
+                            editor
+                        })),
+                        None,
+                        true,
+                        window,
+                        cx,
+                    );
+                })?;
+                anyhow::Ok(())
+            })
+            .detach_and_prompt_err("Failed to compare with original", window, cx, |_, _, _| {
+                None
+            });
+    }
+
+    fn resolve_worktree(
+        &mut self,
+        resolution: WorktreeResolution,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(connection) = self.as_native_connection(cx) else {
+            return;
+        };
+        let Some(thread) = self.thread() else {
+            return;
+        };
+        let session_id = thread.read(cx).session_id().clone();
+        let confirmation = match resolution {
+            WorktreeResolution::Merge => None,
+            WorktreeResolution::Discard => Some(window.prompt(
+                PromptLevel::Warning,
+                "Discard all changes made in this worktree?",
+                Some("The worktree and its branch will be deleted."),
+                &["Discard", "Cancel"],
+                cx,
+            )),
+        };
+
+        cx.spawn_in(window, async move |_, cx| {
+            if let Some(confirmation) = confirmation
+                && confirmation.await? != 0
+            {
+                return Ok(());
+            }
+            let conflicts = cx
+                .update(|_, cx| connection.resolve_worktree(&session_id, resolution, cx))?
+                .await?;
+            if !conflicts.is_empty() {
+                let detail = format!(
+                    "Resolve the conflict markers in the original checkout:\n{}",
+                    conflicts.join("\n")
+                );
+                cx.update(|window, cx| {
+                    window.prompt(
+                        PromptLevel::Warning,
+                        "The changes were merged with conflicts",
+                        Some(&detail),
+                        &["OK"],
+                        cx,
+                    )
+                })?
+                .await
+                .ok();
+            }
+            // The worktree this window was opened for no longer exists.
+            cx.update(|window, _| window.remove_window())?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to finish worktree", window, cx, |_, _, _| None);
+    }
+
     fn open_edited_buffer(
         &mut self,
         buffer: &Entity<Buffer>,
@@ -1531,37 +1742,7 @@ impl AcpThreadView {
             }
             AcpThreadEvent::TokenUsageUpdated => {}
             AcpThreadEvent::AvailableCommandsUpdated(available_commands) => {
-                let mut available_commands = available_commands.clone();
-
-                if thread
-                    .read(cx)
-                    .connection()
-                    .auth_methods()
-                    .iter()
-                    .any(|method| method.id.0.as_ref() == "claude-login")
-                {
-                    available_commands.push(acp::AvailableCommand {
-                        name: "login".to_owned(),
-                        description: "Authenticate".to_owned(),
-                        input: None,
-                        meta: None,
-                    });
-                    available_commands.push(acp::AvailableCommand {
-                        name: "logout".to_owned(),
-                        description: "Authenticate".to_owned(),
-                        input: None,
-                        meta: None,
-                    });
-                }
-
-                let has_commands = !available_commands.is_empty();
-                self.available_commands.replace(available_commands);
-
-                let new_placeholder = placeholder_text(self.agent.name().as_ref(), has_commands);
-
-                self.message_editor.update(cx, |editor, cx| {
-                    editor.set_placeholder_text(&new_placeholder, window, cx);
-                });
+                self.set_available_commands(thread, available_commands.clone(), window, cx);
             }
             AcpThreadEvent::ModeUpdated(_mode) => {
                 // The connection keeps track of the mode
@@ -1571,6 +1752,44 @@ impl AcpThreadView {
         cx.notify();
     }
 
+    fn set_available_commands(
+        &mut self,
+        thread: &Entity<AcpThread>,
+        mut available_commands: Vec<acp::AvailableCommand>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if thread
+            .read(cx)
+            .connection()
+            .auth_methods()
+            .iter()
+            .any(|method| method.id.0.as_ref() == "claude-login")
+        {
+            available_commands.push(acp::AvailableCommand {
+                name: "login".to_owned(),
+                description: "Authenticate".to_owned(),
+                input: None,
+                meta: None,
+            });
+            available_commands.push(acp::AvailableCommand {
+                name: "logout".to_owned(),
+                description: "Authenticate".to_owned(),
+                input: None,
+                meta: None,
+            });
+        }
+
+        let has_commands = !available_commands.is_empty();
+        self.available_commands.replace(available_commands);
+
+        let new_placeholder = placeholder_text(self.agent.name().as_ref(), has_commands);
+
+        self.message_editor.update(cx, |editor, cx| {
+            editor.set_placeholder_text(&new_placeholder, window, cx);
+        });
+    }
+
     fn authenticate(
         &mut self,
         method: acp::AuthMethodId,
@@ -2118,6 +2337,19 @@ impl AcpThreadView {
                                 if message.id.is_some() {
                                     this.child(
                                         base_container
+                                            .when(self.can_fork(cx), |this| {
+                                                this.child(
+                                                    IconButton::new("fork", IconName::GitBranch)
+                                                        .icon_color(Color::Muted)
+                                                        .icon_size(IconSize::XSmall)
+                                                        .tooltip(Tooltip::text(
+                                                            "Fork a new thread from this message"
+                                                        ))
+                                                        .on_click(cx.listener(move |this, _, window, cx| {
+                                                            this.fork_from_message(entry_ix, window, cx);
+                                                        }))
+                                                )
+                                            })
                                             .child(
                                                 IconButton::new("cancel", IconName::Close)
                                                     .disabled(self.is_loading_contents)
@@ -4314,6 +4546,7 @@ impl AcpThreadView {
                     .child(
                         h_flex()
                             .gap_1()
+                            .children(self.render_spend(cx))
                             .children(self.render_token_usage(cx))
                             .children(self.profile_selector.clone())
                             .children(self.mode_selector().cloned())
@@ -4387,6 +4620,42 @@ impl AcpThreadView {
         )
     }
 
+    fn render_spend(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let thread = self.as_native_thread(cx)?.read(cx);
+        let has_pricing = AgentSettings::pricing_for_model(thread.model()?, cx).is_some();
+        let cost = thread.cost();
+        if !has_pricing && cost == 0. {
+            return None;
+        }
+        let today = agent::SpendTracker::today(cx);
+        let settings = AgentSettings::get_global(cx);
+
+        let of_budget = |budget: Option<f64>| {
+            budget
+                .map(|limit| format!(" of {}", crate::text_thread_editor::humanize_cost(limit)))
+                .unwrap_or_default()
+        };
+        let tooltip = format!(
+            "Spent in this thread: {}{}\nSpent today: {}{}",
+            crate::text_thread_editor::humanize_cost(cost),
+            of_budget(settings.thread_budget),
+            crate::text_thread_editor::humanize_cost(today),
+            of_budget(settings.daily_budget),
+        );
+
+        Some(
+            div()
+                .id("thread-spend")
+                .mr_1p5()
+                .child(
+                    Label::new(crate::text_thread_editor::humanize_cost(cost))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .tooltip(Tooltip::text(tooltip)),
+        )
+    }
+
     fn toggle_burn_mode(
         &mut self,
         _: &ToggleBurnMode,
@@ -4769,6 +5038,54 @@ impl AcpThreadView {
                 MentionUri::Fetch { url } => {
                     cx.open_url(url.as_str());
                 }
+                MentionUri::McpResource {
+                    server_id,
+                    uri,
+                    name,
+                } => {
+                    let project = workspace.project().clone();
+                    let Some(server) = project
+                        .read(cx)
+                        .context_server_store()
+                        .read(cx)
+                        .get_running_server(&ContextServerId(server_id.into()))
+                    else {
+                        return;
+                    };
+                    cx.spawn_in(window, async move |workspace, cx| {
+                        let content = read_mcp_resource(&server, uri).await?;
+                        let buffer = project
+                            .update(cx, |project, cx| project.create_buffer(false, cx))?
+                            .await?;
+                        buffer.update(cx, |buffer, cx| {
+                            buffer.set_text(content, cx);
+                            buffer.set_capability(language::Capability::ReadOnly, cx);
+                        })?;
+                        workspace.update_in(cx, |workspace, window, cx| {
+                            let buffer = cx.new(|cx| {
+                                MultiBuffer::singleton(buffer, cx).with_title(name.clone())
+                            });
+                            workspace.add_item_to_active_pane(
+                                Box::new(cx.new(|cx| {
+                                    let mut editor = Editor::for_multibuffer(
+                                        buffer,
+                                        Some(project.clone()),
+                                        window,
+                                        cx,
+                                    );
+                                    editor.set_breadcrumb_header(name);
+                                    editor
+                                })),
+                                None,
+                                true,
+                                window,
+                                cx,
+                            );
+                        })?;
+                        anyhow::Ok(())
+                    })
+                    .detach_and_log_err(cx);
+                }
             })
         } else {
             cx.open_url(&url);
@@ -5420,6 +5737,121 @@ impl AcpThreadView {
         )
     }
 
+    fn render_model_fallback_callout(&self, cx: &mut Context<Self>) -> Option<Callout> {
+        let thread = self.as_native_thread(cx)?;
+        let fallback = thread.read(cx).latest_model_fallback()?.clone();
+        if self.dismissed_model_fallback.as_ref() == Some(&fallback) {
+            return None;
+        }
+
+        let description = match fallback.reason {
+            agent::ModelFallbackReason::Error => format!(
+                "{} couldn't complete the request, so the thread continued with {}.",
+                fallback.from.model, fallback.to.model
+            ),
+            agent::ModelFallbackReason::ContextOverflow => format!(
+                "The conversation no longer fit in {}'s context window, so the thread continued with {}.",
+                fallback.from.model, fallback.to.model
+            ),
+        };
+
+        Some(
+            Callout::new()
+                .icon(IconName::Info)
+                .severity(Severity::Info)
+                .title(format!("Switched to {}", fallback.to.model))
+                .description(description)
+                .dismiss_action(
+                    IconButton::new("dismiss-model-fallback", IconName::Close)
+                        .icon_size(IconSize::Small)
+                        .tooltip(Tooltip::text("Dismiss"))
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.dismissed_model_fallback = Some(fallback.clone());
+                            cx.notify();
+                        })),
+                ),
+        )
+    }
+
+    fn render_worktree_callout(&self, cx: &mut Context<Self>) -> Option<Callout> {
+        let thread = self.as_native_thread(cx)?;
+        let thread = thread.read(cx);
+        let forked_from_title = thread
+            .forked_from()
+            .map(|forked_from| forked_from.thread_title.clone());
+        let branch = thread.worktree().map(|worktree| worktree.branch.clone());
+        if forked_from_title.is_none() && branch.is_none() {
+            return None;
+        }
+        let has_unreviewed_edits = !thread.action_log().read(cx).changed_buffers(cx).is_empty();
+
+        let title = match &forked_from_title {
+            Some(title) => format!("Forked from \"{title}\""),
+            None => "Running in a separate worktree".to_string(),
+        };
+
+        Some(
+            Callout::new()
+                .icon(IconName::GitBranch)
+                .severity(Severity::Info)
+                .title(title)
+                .when_some(branch, |this, branch| {
+                    this.description(format!("Changes are isolated on branch {branch}"))
+                        .actions_slot(
+                            h_flex()
+                                .gap_0p5()
+                                .child(
+                                    Button::new("compare-with-original", "Compare")
+                                        .icon(IconName::Diff)
+                                        .icon_position(IconPosition::Start)
+                                        .icon_size(IconSize::Small)
+                                        .icon_color(Color::Muted)
+                                        .tooltip(Tooltip::text(
+                                            "Compare the worktree with the original checkout",
+                                        ))
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.compare_with_original(window, cx);
+                                        })),
+                                )
+                                .child(
+                                    Button::new("merge-worktree", "Merge Back")
+                                        .icon(IconName::Check)
+                                        .icon_position(IconPosition::Start)
+                                        .icon_size(IconSize::Small)
+                                        .icon_color(Color::Muted)
+                                        .disabled(has_unreviewed_edits)
+                                        .when(has_unreviewed_edits, |this| {
+                                            this.tooltip(Tooltip::text(
+                                                "Review all edits before merging",
+                                            ))
+                                        })
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.resolve_worktree(
+                                                WorktreeResolution::Merge,
+                                                window,
+                                                cx,
+                                            );
+                                        })),
+                                )
+                                .child(
+                                    Button::new("discard-worktree", "Discard")
+                                        .icon(IconName::Trash)
+                                        .icon_position(IconPosition::Start)
+                                        .icon_size(IconSize::Small)
+                                        .icon_color(Color::Muted)
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.resolve_worktree(
+                                                WorktreeResolution::Discard,
+                                                window,
+                                                cx,
+                                            );
+                                        })),
+                                ),
+                        )
+                }),
+        )
+    }
+
     fn render_codex_windows_warning(&self, cx: &mut Context<Self>) -> Option<Callout> {
         if self.show_codex_windows_warning {
             Some(
@@ -5475,6 +5907,7 @@ impl AcpThreadView {
                 self.render_model_request_limit_reached_error(*plan, cx)
             }
             ThreadError::ToolUseLimitReached => self.render_tool_use_limit_reached_error(cx)?,
+            ThreadError::BudgetExceeded(error) => self.render_budget_exceeded_error(*error, cx)?,
         };
 
         Some(div().child(content))
@@ -5753,6 +6186,52 @@ impl AcpThreadView {
         )
     }
 
+    fn render_budget_exceeded_error(
+        &self,
+        error: agent::BudgetExceededError,
+        cx: &mut Context<Self>,
+    ) -> Option<Callout> {
+        let thread = self.as_native_thread(cx)?;
+        let (title, description) = match error.kind {
+            agent::BudgetKind::Thread => (
+                "Thread budget reached.",
+                format!(
+                    "This thread has spent {} of its {} budget.",
+                    crate::text_thread_editor::humanize_cost(error.spent),
+                    crate::text_thread_editor::humanize_cost(error.limit)
+                ),
+            ),
+            agent::BudgetKind::Daily => (
+                "Daily budget reached.",
+                format!(
+                    "{} of the {} daily budget has been spent today.",
+                    crate::text_thread_editor::humanize_cost(error.spent),
+                    crate::text_thread_editor::humanize_cost(error.limit)
+                ),
+            ),
+        };
+
+        Some(
+            Callout::new()
+                .icon(IconName::Info)
+                .title(title)
+                .description(description)
+                .actions_slot(
+                    Button::new("continue-over-budget", "Continue Anyway")
+                        .layer(ElevationIndex::ModalSurface)
+                        .label_size(LabelSize::Small)
+                        .tooltip(Tooltip::text(
+                            "Keep going until the next message, ignoring budgets.",
+                        ))
+                        .on_click(cx.listener(move |this, _, _window, cx| {
+                            thread.update(cx, |thread, _| thread.allow_budget_exceeded());
+                            this.resume_chat(cx);
+                        })),
+                )
+                .dismiss_action(self.dismiss_error_button(cx)),
+        )
+    }
+
     fn create_copy_button(&self, message: impl Into<String>) -> impl IntoElement {
         let message = message.into();
 
@@ -5981,7 +6460,9 @@ impl Render for AcpThreadView {
                 }
                 _ => this,
             })
+            .children(self.render_worktree_callout(cx))
             .children(self.render_thread_retry_status_callout(window, cx))
+            .children(self.render_model_fallback_callout(cx))
             .children({
                 if cfg!(windows) && self.project.read(cx).is_local() {
                     self.render_codex_windows_warning(cx)
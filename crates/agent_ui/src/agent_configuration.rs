@@ -39,7 +39,7 @@ use ui::{
     PopoverMenu, Switch, SwitchColor, Tooltip, WithScrollbar, prelude::*,
 };
 use util::ResultExt as _;
-use workspace::{Workspace, create_and_open_local_file};
+use workspace::{Workspace, create_and_open_local_file, notifications::DetachAndPromptErr as _};
 use zed_actions::{ExtensionCategoryFilter, OpenBrowser};
 
 pub(crate) use configure_context_server_modal::ConfigureContextServerModal;
@@ -650,6 +650,8 @@ impl AgentConfiguration {
             .configuration_for_server(&context_server_id);
 
         let is_running = matches!(server_status, ContextServerStatus::Running);
+        let requires_authentication =
+            matches!(server_status, ContextServerStatus::AuthenticationRequired);
         let item_id = SharedString::from(context_server_id.0.clone());
         // Servers without a configuration can only be provided by extensions.
         let provided_by_extension = server_configuration.as_ref().is_none_or(|config| {
@@ -707,6 +709,10 @@ impl AgentConfiguration {
                 Indicator::dot().color(Color::Muted).into_any_element(),
                 "Server is stopped.",
             ),
+            ContextServerStatus::AuthenticationRequired => (
+                Indicator::dot().color(Color::Warning).into_any_element(),
+                "Server requires authentication.",
+            ),
         };
         let is_remote = server_configuration
             .as_ref()
@@ -876,6 +882,33 @@ impl AgentConfiguration {
                         h_flex()
                             .gap_0p5()
                             .flex_none()
+                            .when(requires_authentication, |this| {
+                                this.child(
+                                    Button::new("context-server-sign-in", "Sign In")
+                                        .style(ButtonStyle::Outlined)
+                                        .label_size(LabelSize::Small)
+                                        .on_click({
+                                            let context_server_store =
+                                                self.context_server_store.clone();
+                                            let context_server_id = context_server_id.clone();
+                                            move |_, window, cx| {
+                                                context_server_store
+                                                    .update(cx, |store, cx| {
+                                                        store.authenticate_server(
+                                                            &context_server_id,
+                                                            cx,
+                                                        )
+                                                    })
+                                                    .detach_and_prompt_err(
+                                                        "Failed to sign in",
+                                                        window,
+                                                        cx,
+                                                        |_, _, _| None,
+                                                    );
+                                            }
+                                        }),
+                                )
+                            })
                             .child(context_server_configuration_menu)
                             .child(
                             Switch::new("context-server-switch", is_running.into())
@@ -882,7 +882,9 @@ fn wait_for_context_server(
     let subscription = cx.subscribe(context_server_store, move |_, event, _cx| match event {
         project::context_server_store::Event::ServerStatusChanged { server_id, status } => {
             match status {
-                ContextServerStatus::Running => {
+                // The user signs in from the configuration view, so the
+                // server was configured correctly.
+                ContextServerStatus::Running | ContextServerStatus::AuthenticationRequired => {
                     if server_id == &context_server_id
                         && let Some(tx) = tx.lock().unwrap().take()
                     {
@@ -906,6 +908,7 @@ fn wait_for_context_server(
                 _ => {}
             }
         }
+        project::context_server_store::Event::ResourceUpdated { .. } => {}
     });
 
     cx.spawn(async move |_cx| {
@@ -315,6 +315,8 @@ impl PickerDelegate for ToolPickerDelegate {
                             })
                             .collect(),
                         default_model: default_profile.default_model.clone(),
+                        fallback_models: (!default_profile.fallback_models.is_empty())
+                            .then(|| default_profile.fallback_models.clone()),
                     });
 
                 if let Some(server_id) = server_id {
@@ -3,8 +3,11 @@ use std::path::Path;
 use std::rc::Rc;
 use std::sync::Arc;
 
-use acp_thread::AcpThread;
-use agent::{ContextServerRegistry, DbThreadMetadata, HistoryEntry, HistoryStore};
+use acp_thread::{AcpThread, AgentThreadEntry};
+use agent::{
+    ContextServerRegistry, DbThreadMetadata, ExportedCheckpoint, ExportedRepositoryCheckpoint,
+    ExportedThread, HistoryEntry, HistoryStore,
+};
 use call::participant::{AgentActivity, AgentActivityStatus};
 use db::kvp::{Dismissable, KEY_VALUE_STORE};
 use project::{
@@ -19,12 +22,14 @@ use settings::{
 use zed_actions::agent::{OpenClaudeCodeOnboardingModal, ReauthenticateAgent};
 
 use crate::ManageProfiles;
+use crate::batch_run_view::BatchRunModal;
+use crate::thread_worktree::{open_thread_in_worktree, take_pending_worktree_thread};
 use crate::ui::{AcpOnboardingModal, ClaudeCodeOnboardingModal};
 use crate::{
-    AddContextServer, AgentDiffPane, DeleteRecentlyOpenThread, Follow, InlineAssistant,
-    NewTextThread, NewThread, OpenActiveThreadAsMarkdown, OpenAgentDiff, OpenHistory,
-    ResetTrialEndUpsell, ResetTrialUpsell, ToggleNavigationMenu, ToggleNewThreadMenu,
-    ToggleOptionsMenu,
+    AddContextServer, AgentDiffPane, DeleteRecentlyOpenThread, ExportActiveThread, Follow,
+    ImportThread, InlineAssistant, NewBatchRun, NewTextThread, NewThread, NewWorktreeThread,
+    OpenActiveThreadAsMarkdown, OpenAgentDiff, OpenHistory, ResetTrialEndUpsell, ResetTrialUpsell,
+    ToggleNavigationMenu, ToggleNewThreadMenu, ToggleOptionsMenu,
     acp::AcpThreadView,
     agent_configuration::{AgentConfiguration, AssistantConfigurationEvent},
     slash_command::SlashCommandCompletionProvider,
@@ -49,8 +54,8 @@ use extension_host::ExtensionStore;
 use fs::Fs;
 use gpui::{
     Action, AnyElement, App, AsyncWindowContext, Corner, DismissEvent, Entity, EventEmitter,
-    ExternalPaths, FocusHandle, Focusable, KeyContext, Pixels, Subscription, Task, UpdateGlobal,
-    WeakEntity, prelude::*,
+    ExternalPaths, FocusHandle, Focusable, KeyContext, PathPromptOptions, Pixels, Subscription,
+    Task, UpdateGlobal, WeakEntity, prelude::*,
 };
 use language::LanguageRegistry;
 use language_model::{ConfigurationError, LanguageModelRegistry};
@@ -69,6 +74,7 @@ use util::ResultExt as _;
 use workspace::{
     CollaboratorId, DraggedSelection, DraggedTab, ToggleZoom, ToolbarItemView, Workspace,
     dock::{DockPosition, Panel, PanelEvent},
+    notifications::DetachAndPromptErr as _,
 };
 use zed_actions::{
     DecreaseBufferFontSize, IncreaseBufferFontSize, ResetBufferFontSize,
@@ -96,6 +102,22 @@ pub fn init(cx: &mut App) {
                         workspace.focus_panel::<AgentPanel>(window, cx);
                     }
                 })
+                .register_action(|workspace, _: &NewWorktreeThread, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| panel.new_worktree_thread(window, cx));
+                    }
+                })
+                .register_action(|workspace, _: &NewBatchRun, window, cx| {
+                    let Some(panel) = workspace.panel::<AgentPanel>(cx) else {
+                        return;
+                    };
+                    let fs = panel.read(cx).fs.clone();
+                    let history_store = panel.read(cx).history_store.clone();
+                    let weak_workspace = workspace.weak_handle();
+                    workspace.toggle_modal(window, cx, |window, cx| {
+                        BatchRunModal::new(weak_workspace, fs, history_store, window, cx)
+                    });
+                })
                 .register_action(
                     |workspace, action: &NewNativeAgentThreadFromSummary, window, cx| {
                         if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
@@ -118,6 +140,12 @@ pub fn init(cx: &mut App) {
                         panel.update(cx, |panel, cx| panel.open_history(window, cx));
                     }
                 })
+                .register_action(|workspace, _: &ImportThread, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        workspace.focus_panel::<AgentPanel>(window, cx);
+                        panel.update(cx, |panel, cx| panel.import_thread(window, cx));
+                    }
+                })
                 .register_action(|workspace, _: &OpenSettings, window, cx| {
                     if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
                         workspace.focus_panel::<AgentPanel>(window, cx);
@@ -522,6 +550,9 @@ impl AgentPanel {
                     });
                 }
                 panel.as_mut(cx).loading = false;
+                panel.update(cx, |panel, cx| {
+                    panel.load_pending_worktree_thread(window, cx)
+                });
                 panel
             })?;
 
@@ -1205,6 +1236,78 @@ impl AgentPanel {
         }
     }
 
+    pub(crate) fn export_active_thread(
+        &mut self,
+        _: &ExportActiveThread,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let ActiveView::ExternalAgentThread { thread_view } = &self.active_view else {
+            return;
+        };
+        let Some(thread) = self.active_native_agent_thread(cx) else {
+            return;
+        };
+        let checkpoints = thread_view
+            .read(cx)
+            .thread()
+            .map(|thread| exported_checkpoints(thread.read(cx)))
+            .unwrap_or_default();
+        let title = thread.read(cx).title();
+
+        let file_stem = title
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect::<String>();
+        let suggested_name = format!(
+            "{}.{}",
+            file_stem.trim_matches('-'),
+            ExportedThread::FILE_EXTENSION
+        );
+        let path = cx.prompt_for_new_path(paths::home_dir(), Some(&suggested_name));
+        let export = thread.read(cx).to_export(checkpoints, cx);
+        let fs = self.fs.clone();
+
+        cx.spawn(async move |_, _| {
+            let Some(path) = path.await?? else {
+                return anyhow::Ok(());
+            };
+            fs.atomic_write(path, export.await?.to_json()?).await
+        })
+        .detach_and_prompt_err("Failed to export thread", window, cx, |_, _, _| None);
+    }
+
+    pub(crate) fn import_thread(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Import Thread".into()),
+        });
+        let history_store = self.history_store.clone();
+        let fs = self.fs.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Some(path) = paths.await??.and_then(|paths| paths.into_iter().next()) else {
+                return anyhow::Ok(());
+            };
+            let json = fs.load_bytes(&path).await?;
+            let thread = history_store
+                .update(cx, |store, cx| store.import_thread(json, cx))?
+                .await?;
+            this.update_in(cx, |this, window, cx| {
+                this.load_agent_thread(thread, window, cx)
+            })
+        })
+        .detach_and_prompt_err("Failed to import thread", window, cx, |_, _, _| None);
+    }
+
     fn handle_agent_configuration_event(
         &mut self,
         _entity: &Entity<AgentConfiguration>,
@@ -1472,6 +1575,43 @@ impl AgentPanel {
         }
     }
 
+    fn new_worktree_thread(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let app_state = workspace.read(cx).app_state().clone();
+        let create_thread = agent::worktree::create_worktree_thread(
+            &self.project,
+            &self.history_store,
+            self.fs.clone(),
+            cx,
+        );
+        cx.spawn_in(window, async move |_, cx| {
+            let (worktree, thread) = create_thread.await?;
+            cx.update(|_, cx| open_thread_in_worktree(&worktree, thread, app_state, cx))?
+                .await?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to create worktree", window, cx, |_, _, _| None);
+    }
+
+    /// Loads the thread that was forked into this window's worktree, if any.
+    pub(crate) fn load_pending_worktree_thread(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let worktree_paths = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .collect::<Vec<_>>();
+        if let Some(thread) = take_pending_worktree_thread(worktree_paths, cx) {
+            self.load_agent_thread(thread, window, cx);
+        }
+    }
+
     pub fn load_agent_thread(
         &mut self,
         thread: DbThreadMetadata,
@@ -1511,6 +1651,35 @@ fn agent_panel_dock_position(cx: &App) -> DockPosition {
     AgentSettings::get_global(cx).dock.into()
 }
 
+fn exported_checkpoints(thread: &AcpThread) -> Vec<ExportedCheckpoint> {
+    thread
+        .entries()
+        .iter()
+        .filter_map(|entry| match entry {
+            AgentThreadEntry::UserMessage(message) => {
+                let user_message_id = message.id.clone()?;
+                let checkpoint = message.checkpoint.as_ref()?;
+                let mut repositories = checkpoint
+                    .git_checkpoint()
+                    .repository_checkpoints()
+                    .map(
+                        |(work_directory, checkpoint)| ExportedRepositoryCheckpoint {
+                            work_directory: work_directory.to_path_buf(),
+                            commit_sha: checkpoint.commit_sha.to_string(),
+                        },
+                    )
+                    .collect::<Vec<_>>();
+                repositories.sort_by(|a, b| a.work_directory.cmp(&b.work_directory));
+                Some(ExportedCheckpoint {
+                    user_message_id,
+                    repositories,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 impl EventEmitter<PanelEvent> for AgentPanel {}
 
 impl Panel for AgentPanel {
@@ -1711,6 +1880,7 @@ impl AgentPanel {
         };
 
         let selected_agent = self.selected_agent.clone();
+        let can_export_thread = self.active_native_agent_thread(cx).is_some();
 
         PopoverMenu::new("agent-options-menu")
             .trigger_with_tooltip(
@@ -1787,6 +1957,14 @@ impl AgentPanel {
                             .action("Rules", Box::new(OpenRulesLibrary::default()))
                             .action("Profiles", Box::new(ManageProfiles::default()))
                             .action("Settings", Box::new(OpenSettings))
+                            .separator();
+
+                        if can_export_thread {
+                            menu = menu.action("Export Thread…", Box::new(ExportActiveThread));
+                        }
+
+                        menu = menu
+                            .action("Import Thread…", Box::new(ImportThread))
                             .separator()
                             .action(full_screen_label, Box::new(ToggleZoom));
 
@@ -1904,6 +2082,10 @@ impl AgentPanel {
                         workspace.project().read(cx).is_via_collab()
                     })
                     .unwrap_or_default();
+                let can_use_worktree = {
+                    let project = self.project.read(cx);
+                    project.is_local() && project.active_repository(cx).is_some()
+                };
 
                 move |window, cx| {
                     telemetry::event!("New Thread Clicked");
@@ -1961,6 +2143,20 @@ impl AgentPanel {
                                         }
                                     }),
                             )
+                            .when(can_use_worktree, |this| {
+                                this.item(
+                                    ContextMenuEntry::new("Zed Agent in New Worktree")
+                                        .action(NewWorktreeThread.boxed_clone())
+                                        .icon(IconName::GitBranch)
+                                        .icon_color(Color::Muted)
+                                        .handler(|window, cx| {
+                                            window.dispatch_action(
+                                                NewWorktreeThread.boxed_clone(),
+                                                cx,
+                                            );
+                                        }),
+                                )
+                            })
                             .item(
                                 ContextMenuEntry::new("Text Thread")
                                     .action(NewTextThread.boxed_clone())
@@ -2587,6 +2783,7 @@ impl Render for AgentPanel {
                 this.open_configuration(window, cx);
             }))
             .on_action(cx.listener(Self::open_active_thread_as_markdown))
+            .on_action(cx.listener(Self::export_active_thread))
             .on_action(cx.listener(Self::deploy_rules_library))
             .on_action(cx.listener(Self::go_back))
             .on_action(cx.listener(Self::toggle_navigation_menu))
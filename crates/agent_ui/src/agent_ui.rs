@@ -4,10 +4,14 @@ mod agent_debug_panel;
 mod agent_diff;
 mod agent_model_selector;
 mod agent_panel;
+mod batch_run;
+mod batch_run_view;
 mod buffer_codegen;
 mod completion_provider;
 mod context;
 mod context_server_configuration;
+mod context_server_elicitation;
+mod context_server_sampling;
 mod inline_assistant;
 mod inline_prompt_editor;
 mod language_model_selector;
@@ -18,6 +22,7 @@ mod slash_command_picker;
 mod terminal_codegen;
 mod terminal_inline_assistant;
 mod text_thread_editor;
+mod thread_worktree;
 mod ui;
 
 use std::rc::Rc;
@@ -59,6 +64,10 @@ actions!(
         NewTextThread,
         /// Toggles the menu to create new agent threads.
         ToggleNewThreadMenu,
+        /// Creates a new agent thread that runs in its own git worktree, opened in a new window.
+        NewWorktreeThread,
+        /// Runs a prompt against many files, one agent thread per file.
+        NewBatchRun,
         /// Toggles the navigation menu for switching between threads and views.
         ToggleNavigationMenu,
         /// Toggles the options menu for agent settings and preferences.
@@ -93,6 +102,10 @@ actions!(
         FocusRight,
         /// Opens the active thread as a markdown file.
         OpenActiveThreadAsMarkdown,
+        /// Exports the active thread to a file that can be imported in another Zed installation.
+        ExportActiveThread,
+        /// Imports a thread that was previously exported from Zed.
+        ImportThread,
         /// Opens the agent diff view to review changes.
         OpenAgentDiff,
         /// Keeps the current suggestion or change.
@@ -226,6 +239,7 @@ pub fn init(
 ) {
     assistant_text_thread::init(client.clone(), cx);
     rules_library::init(cx);
+    agent::SpendTracker::init(cx);
     if !is_eval {
         // Initializing the language model from the user settings messes with the eval, so we only initialize them when
         // we're not running inside of the eval.
@@ -234,6 +248,8 @@ pub fn init(
     assistant_slash_command::init(cx);
     agent_panel::init(cx);
     context_server_configuration::init(language_registry.clone(), fs.clone(), cx);
+    context_server_elicitation::init(cx);
+    context_server_sampling::init(cx);
     TextThreadEditor::init(cx);
 
     register_slash_commands(cx);
@@ -322,6 +338,7 @@ fn update_command_palette_filter(cx: &mut App) {
                 }
                 EditPredictionProvider::Zed
                 | EditPredictionProvider::Codestral
+                | EditPredictionProvider::Local
                 | EditPredictionProvider::Experimental(_) => {
                     filter.show_namespace("edit_prediction");
                     filter.hide_namespace("copilot");
@@ -470,6 +487,9 @@ mod tests {
             expand_terminal_card: true,
             use_modifier_to_send: true,
             message_editor_min_lines: 1,
+            thread_budget: None,
+            daily_budget: None,
+            model_pricing: vec![],
         };
 
         cx.update(|cx| {
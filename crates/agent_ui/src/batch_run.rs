@@ -0,0 +1,971 @@
+use std::{rc::Rc, sync::Arc};
+
+use acp_thread::{AcpThread, AcpThreadEvent, AgentConnection, ToolCall, ToolCallStatus};
+use agent::{HistoryStore, NativeAgentServer};
+use agent_client_protocol as acp;
+use agent_servers::{AgentServer, AgentServerDelegate};
+use anyhow::{Result, anyhow};
+use collections::{BTreeMap, HashSet};
+use fs::Fs;
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, SharedString, Subscription, Task};
+use language::{Point, ToPoint as _};
+use project::{Project, ProjectItem as _, ProjectPath};
+use search::ProjectSearchView;
+use util::{ResultExt as _, paths::PathMatcher};
+use workspace::Workspace;
+
+/// How many threads of a batch run are allowed to run at the same time.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// The maximum number of diagnostics included in a target's context.
+const MAX_DIAGNOSTICS_PER_TARGET: usize = 20;
+
+/// Where the targets of a batch run come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchTargetSource {
+    /// Every file in the project matching a glob.
+    Glob(String),
+    /// The files containing matches in the active project search.
+    ProjectSearch,
+    /// The files that currently have errors or warnings.
+    Diagnostics,
+}
+
+/// A file that the batch run's prompt template is run against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchTarget {
+    pub path: ProjectPath,
+    /// The path substituted for `{path}` in the prompt template.
+    pub display_path: SharedString,
+    /// Details about why the file was picked, substituted for `{context}`.
+    pub context: Option<String>,
+}
+
+impl BatchTarget {
+    /// Fills in the `{path}` and `{context}` placeholders of a prompt template.
+    ///
+    /// If the template doesn't mention `{path}`, the path is appended so that
+    /// every thread knows which file it is responsible for.
+    pub fn render_prompt(&self, template: &str) -> String {
+        let context = self.context.as_deref().unwrap_or_default();
+        let mut prompt = template
+            .replace("{path}", &self.display_path)
+            .replace("{context}", context);
+        if !template.contains("{path}") {
+            prompt.push_str(&format!("\n\nFile: {}", self.display_path));
+        }
+        prompt
+    }
+}
+
+pub fn collect_targets(
+    source: &BatchTargetSource,
+    workspace: &Workspace,
+    cx: &mut App,
+) -> Task<Result<Vec<BatchTarget>>> {
+    match source {
+        BatchTargetSource::Glob(glob) => collect_glob_targets(glob, workspace.project(), cx),
+        BatchTargetSource::ProjectSearch => {
+            let Some(search) = workspace.active_item_as::<ProjectSearchView>(cx) else {
+                return Task::ready(Err(anyhow!("no project search is open")));
+            };
+            Task::ready(Ok(collect_search_targets(&search, workspace.project(), cx)))
+        }
+        BatchTargetSource::Diagnostics => collect_diagnostic_targets(workspace.project(), cx),
+    }
+}
+
+fn target_for_path(project: &Project, path: ProjectPath, cx: &App) -> Option<BatchTarget> {
+    let worktree = project.worktree_for_id(path.worktree_id, cx)?;
+    let display_path = worktree
+        .read(cx)
+        .root_name()
+        .join(&path.path)
+        .display(project.path_style(cx))
+        .into_owned()
+        .into();
+    Some(BatchTarget {
+        path,
+        display_path,
+        context: None,
+    })
+}
+
+fn collect_glob_targets(
+    glob: &str,
+    project: &Entity<Project>,
+    cx: &mut App,
+) -> Task<Result<Vec<BatchTarget>>> {
+    let path_style = project.read(cx).path_style(cx);
+    let path_matcher = match PathMatcher::new([glob], path_style) {
+        Ok(matcher) => matcher,
+        Err(err) => return Task::ready(Err(anyhow!("Invalid glob: {err}"))),
+    };
+    let snapshots: Vec<_> = project
+        .read(cx)
+        .visible_worktrees(cx)
+        .map(|worktree| worktree.read(cx).snapshot())
+        .collect();
+    // As in project search, globs only start with the name of a worktree when
+    // there are several to tell apart.
+    let match_full_paths = snapshots.len() > 1;
+
+    cx.background_spawn(async move {
+        let mut targets = Vec::new();
+        for snapshot in snapshots {
+            for entry in snapshot.files(false, 0) {
+                let full_path = snapshot.root_name().join(&entry.path);
+                let matched_path = if match_full_paths {
+                    full_path.as_std_path()
+                } else {
+                    entry.path.as_std_path()
+                };
+                // A glob matching a directory includes the files in it.
+                if matched_path
+                    .ancestors()
+                    .any(|path| path_matcher.is_match(path))
+                {
+                    targets.push(BatchTarget {
+                        path: ProjectPath {
+                            worktree_id: snapshot.id(),
+                            path: entry.path.clone(),
+                        },
+                        display_path: full_path.display(path_style).into_owned().into(),
+                        context: None,
+                    });
+                }
+            }
+        }
+        Ok(targets)
+    })
+}
+
+fn collect_search_targets(
+    search: &Entity<ProjectSearchView>,
+    project: &Entity<Project>,
+    cx: &App,
+) -> Vec<BatchTarget> {
+    let project = project.read(cx);
+    let mut matches_by_path = BTreeMap::<ProjectPath, Vec<String>>::default();
+    for range in search.read(cx).get_matches(cx) {
+        let Some(buffer) = range
+            .start
+            .buffer_id
+            .and_then(|buffer_id| project.buffer_for_id(buffer_id, cx))
+        else {
+            continue;
+        };
+        let buffer = buffer.read(cx);
+        let Some(path) = buffer.project_path(cx) else {
+            continue;
+        };
+        let start = range.start.text_anchor.to_point(buffer);
+        let line = buffer
+            .text_for_range(
+                Point::new(start.row, 0)..Point::new(start.row, buffer.line_len(start.row)),
+            )
+            .collect::<String>();
+        matches_by_path.entry(path).or_default().push(format!(
+            "{}: {}",
+            start.row + 1,
+            line.trim()
+        ));
+    }
+
+    matches_by_path
+        .into_iter()
+        .filter_map(|(path, lines)| {
+            let mut target = target_for_path(project, path, cx)?;
+            target.context = Some(format!("Matching lines:\n{}", lines.join("\n")));
+            Some(target)
+        })
+        .collect()
+}
+
+fn collect_diagnostic_targets(
+    project: &Entity<Project>,
+    cx: &mut App,
+) -> Task<Result<Vec<BatchTarget>>> {
+    let mut seen = HashSet::default();
+    let paths = project
+        .read(cx)
+        .diagnostic_summaries(false, cx)
+        .filter(|(_, _, summary)| summary.error_count + summary.warning_count > 0)
+        .filter_map(|(path, _, _)| seen.insert(path.clone()).then_some(path))
+        .collect::<Vec<_>>();
+    let buffers = paths
+        .into_iter()
+        .map(|path| {
+            project.update(cx, |project, cx| {
+                let buffer = project.open_buffer(path.clone(), cx);
+                async move { (path, buffer.await) }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let project = project.downgrade();
+    cx.spawn(async move |cx| {
+        let buffers = futures::future::join_all(buffers).await;
+        project.read_with(cx, |project, cx| {
+            buffers
+                .into_iter()
+                .filter_map(|(path, buffer)| {
+                    let buffer = buffer.log_err()?;
+                    let snapshot = buffer.read(cx).snapshot();
+                    let diagnostics = snapshot
+                        .diagnostics_in_range::<_, Point>(0..snapshot.len(), false)
+                        .filter(|entry| {
+                            entry.diagnostic.is_primary
+                                && entry.diagnostic.severity <= lsp::DiagnosticSeverity::WARNING
+                        })
+                        .take(MAX_DIAGNOSTICS_PER_TARGET)
+                        .map(|entry| {
+                            format!(
+                                "{}: {}",
+                                entry.range.start.row + 1,
+                                entry.diagnostic.message
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    if diagnostics.is_empty() {
+                        return None;
+                    }
+                    let mut target = target_for_path(project, path, cx)?;
+                    target.context = Some(format!("Diagnostics:\n{}", diagnostics.join("\n")));
+                    Some(target)
+                })
+                .collect()
+        })
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchEntryStatus {
+    Queued,
+    Running,
+    /// The thread finished and made changes that haven't been reviewed yet.
+    NeedsReview,
+    /// The thread finished without changing anything.
+    NoChanges,
+    Kept,
+    Rejected,
+    Cancelled,
+    Failed(SharedString),
+}
+
+impl BatchEntryStatus {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, Self::Queued | Self::Running)
+    }
+}
+
+pub struct BatchEntry {
+    pub target: BatchTarget,
+    pub status: BatchEntryStatus,
+    pub thread: Option<Entity<AcpThread>>,
+    _task: Option<Task<()>>,
+    _thread_subscription: Option<Subscription>,
+}
+
+pub enum BatchRunEvent {
+    EntryUpdated(usize),
+}
+
+/// Runs a prompt template against many targets, one thread per target, with a
+/// bounded number of threads running at once.
+pub struct BatchRun {
+    project: Entity<Project>,
+    prompt_template: String,
+    entries: Vec<BatchEntry>,
+    max_concurrency: usize,
+    connection: Option<Rc<dyn AgentConnection>>,
+    _connect_task: Task<()>,
+}
+
+impl EventEmitter<BatchRunEvent> for BatchRun {}
+
+impl BatchRun {
+    pub fn new(
+        project: Entity<Project>,
+        prompt_template: String,
+        targets: Vec<BatchTarget>,
+        max_concurrency: usize,
+        fs: Arc<dyn Fs>,
+        history_store: Entity<HistoryStore>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let server = Rc::new(NativeAgentServer::new(fs, history_store));
+        let delegate = AgentServerDelegate::new(
+            project.read(cx).agent_server_store().clone(),
+            project.clone(),
+            None,
+            None,
+        );
+        let connect = server.connect(None, delegate, cx);
+        Self::with_connection(
+            project,
+            prompt_template,
+            targets,
+            max_concurrency,
+            async move { Ok(connect.await?.0) },
+            cx,
+        )
+    }
+
+    fn with_connection(
+        project: Entity<Project>,
+        prompt_template: String,
+        targets: Vec<BatchTarget>,
+        max_concurrency: usize,
+        connect: impl Future<Output = Result<Rc<dyn AgentConnection>>> + 'static,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let connect_task = cx.spawn(async move |this, cx| {
+            let result = connect.await;
+            this.update(cx, |this, cx| match result {
+                Ok(connection) => {
+                    this.connection = Some(connection);
+                    this.schedule(cx);
+                }
+                Err(error) => {
+                    let error: SharedString = error.to_string().into();
+                    for ix in 0..this.entries.len() {
+                        this.set_status(ix, BatchEntryStatus::Failed(error.clone()), cx);
+                    }
+                }
+            })
+            .ok();
+        });
+
+        Self {
+            project,
+            prompt_template,
+            entries: targets
+                .into_iter()
+                .map(|target| BatchEntry {
+                    target,
+                    status: BatchEntryStatus::Queued,
+                    thread: None,
+                    _task: None,
+                    _thread_subscription: None,
+                })
+                .collect(),
+            max_concurrency: max_concurrency.max(1),
+            connection: None,
+            _connect_task: connect_task,
+        }
+    }
+
+    pub fn entries(&self) -> &[BatchEntry] {
+        &self.entries
+    }
+
+    pub fn prompt_template(&self) -> &str {
+        &self.prompt_template
+    }
+
+    pub fn count(&self, predicate: impl Fn(&BatchEntryStatus) -> bool) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| predicate(&entry.status))
+            .count()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.entries.iter().any(|entry| !entry.status.is_finished())
+    }
+
+    fn set_status(&mut self, ix: usize, status: BatchEntryStatus, cx: &mut Context<Self>) {
+        if let Some(entry) = self.entries.get_mut(ix) {
+            entry.status = status;
+            cx.emit(BatchRunEvent::EntryUpdated(ix));
+            cx.notify();
+        }
+    }
+
+    fn schedule(&mut self, cx: &mut Context<Self>) {
+        let Some(connection) = self.connection.clone() else {
+            return;
+        };
+        let mut running = self.count(|status| *status == BatchEntryStatus::Running);
+        while running < self.max_concurrency {
+            let Some(ix) = self
+                .entries
+                .iter()
+                .position(|entry| entry.status == BatchEntryStatus::Queued)
+            else {
+                break;
+            };
+            self.start(ix, connection.clone(), cx);
+            running += 1;
+        }
+    }
+
+    fn start(&mut self, ix: usize, connection: Rc<dyn AgentConnection>, cx: &mut Context<Self>) {
+        let entry = &self.entries[ix];
+        let prompt = entry.target.render_prompt(&self.prompt_template);
+        let root_dir = self
+            .project
+            .read(cx)
+            .worktree_for_id(entry.target.path.worktree_id, cx)
+            .map(|worktree| worktree.read(cx).abs_path())
+            .unwrap_or_else(|| paths::home_dir().as_path().into());
+        let new_thread = connection.new_thread(self.project.clone(), &root_dir, cx);
+
+        let task = cx.spawn(async move |this, cx| {
+            let result = async {
+                let thread = new_thread.await?;
+                let send = this.update(cx, |this, cx| {
+                    this.entries[ix].thread = Some(thread.clone());
+                    // Nobody is looking at the thread itself, so the review
+                    // queue is where its tool calls get allowed or denied.
+                    this.entries[ix]._thread_subscription =
+                        Some(cx.subscribe(&thread, move |_, _, event, cx| {
+                            if let AcpThreadEvent::ToolAuthorizationRequired = event {
+                                cx.emit(BatchRunEvent::EntryUpdated(ix));
+                                cx.notify();
+                            }
+                        }));
+                    cx.notify();
+                    thread.update(cx, |thread, cx| {
+                        thread.send(vec![prompt.as_str().into()], cx)
+                    })
+                })?;
+                send.await?;
+                anyhow::Ok(thread)
+            }
+            .await;
+
+            this.update(cx, |this, cx| {
+                if this.entries[ix].status != BatchEntryStatus::Running {
+                    return;
+                }
+                let status = match result {
+                    Ok(thread) => {
+                        let action_log = thread.read(cx).action_log().read(cx);
+                        if action_log.changed_buffers(cx).is_empty() {
+                            BatchEntryStatus::NoChanges
+                        } else {
+                            BatchEntryStatus::NeedsReview
+                        }
+                    }
+                    Err(error) => BatchEntryStatus::Failed(error.to_string().into()),
+                };
+                this.set_status(ix, status, cx);
+                this.schedule(cx);
+            })
+            .ok();
+        });
+
+        self.entries[ix]._task = Some(task);
+        self.set_status(ix, BatchEntryStatus::Running, cx);
+    }
+
+    /// Returns the tool call that the thread of the given entry is waiting for
+    /// the user to allow or deny.
+    pub fn pending_authorization<'a>(&self, ix: usize, cx: &'a App) -> Option<&'a ToolCall> {
+        let entry = self.entries.get(ix)?;
+        if entry.status != BatchEntryStatus::Running {
+            return None;
+        }
+        entry
+            .thread
+            .as_ref()?
+            .read(cx)
+            .first_tool_awaiting_confirmation()
+    }
+
+    pub fn count_pending_authorizations(&self, cx: &App) -> usize {
+        (0..self.entries.len())
+            .filter(|ix| self.pending_authorization(*ix, cx).is_some())
+            .count()
+    }
+
+    /// Allows or denies, once, the tool call that the thread of the given entry
+    /// is waiting on.
+    pub fn authorize(&mut self, ix: usize, allow: bool, cx: &mut Context<Self>) {
+        let Some(thread) = self.entries.get(ix).and_then(|entry| entry.thread.clone()) else {
+            return;
+        };
+        thread.update(cx, |thread, cx| {
+            let Some(tool_call) = thread.first_tool_awaiting_confirmation() else {
+                return;
+            };
+            let ToolCallStatus::WaitingForConfirmation { options, .. } = &tool_call.status else {
+                return;
+            };
+            let Some(option) = options.iter().find(|option| {
+                if allow {
+                    matches!(option.kind, acp::PermissionOptionKind::AllowOnce)
+                } else {
+                    matches!(option.kind, acp::PermissionOptionKind::RejectOnce)
+                }
+            }) else {
+                return;
+            };
+            let (tool_call_id, option_id, option_kind) =
+                (tool_call.id.clone(), option.id.clone(), option.kind);
+            thread.authorize_tool_call(tool_call_id, option_id, option_kind, cx);
+        });
+        cx.emit(BatchRunEvent::EntryUpdated(ix));
+        cx.notify();
+    }
+
+    /// Accepts the changes made by the thread of the given entry.
+    pub fn keep(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(thread) = self.reviewable_thread(ix) else {
+            return;
+        };
+        thread.update(cx, |thread, cx| {
+            thread
+                .action_log()
+                .update(cx, |action_log, cx| action_log.keep_all_edits(None, cx));
+        });
+        self.set_status(ix, BatchEntryStatus::Kept, cx);
+    }
+
+    /// Reverts the changes made by the thread of the given entry.
+    pub fn reject(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(thread) = self.reviewable_thread(ix) else {
+            return;
+        };
+        thread
+            .update(cx, |thread, cx| {
+                thread
+                    .action_log()
+                    .update(cx, |action_log, cx| action_log.reject_all_edits(None, cx))
+            })
+            .detach();
+        self.set_status(ix, BatchEntryStatus::Rejected, cx);
+    }
+
+    pub fn keep_all(&mut self, cx: &mut Context<Self>) {
+        for ix in 0..self.entries.len() {
+            self.keep(ix, cx);
+        }
+    }
+
+    pub fn reject_all(&mut self, cx: &mut Context<Self>) {
+        for ix in 0..self.entries.len() {
+            self.reject(ix, cx);
+        }
+    }
+
+    fn reviewable_thread(&self, ix: usize) -> Option<Entity<AcpThread>> {
+        let entry = self.entries.get(ix)?;
+        if entry.status == BatchEntryStatus::NeedsReview {
+            entry.thread.clone()
+        } else {
+            None
+        }
+    }
+
+    /// Stops all running threads and skips the targets that haven't started.
+    pub fn cancel(&mut self, cx: &mut Context<Self>) {
+        for ix in 0..self.entries.len() {
+            match self.entries[ix].status {
+                BatchEntryStatus::Queued => {}
+                BatchEntryStatus::Running => {
+                    if let Some(thread) = self.entries[ix].thread.clone() {
+                        thread.update(cx, |thread, cx| thread.cancel(cx)).detach();
+                    }
+                    self.entries[ix]._task = None;
+                }
+                _ => continue,
+            }
+            self.set_status(ix, BatchEntryStatus::Cancelled, cx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use acp_thread::{StubAgentConnection, ThreadStatus};
+    use collections::HashMap;
+    use gpui::TestAppContext;
+    use project::{FakeFs, WorktreeId};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::cell::Cell;
+    use util::{path, rel_path::rel_path};
+
+    fn target(context: Option<&str>) -> BatchTarget {
+        BatchTarget {
+            path: ProjectPath {
+                worktree_id: WorktreeId::from_usize(0),
+                path: rel_path("src/main.rs").into(),
+            },
+            display_path: "project/src/main.rs".into(),
+            context: context.map(ToString::to_string),
+        }
+    }
+
+    #[test]
+    fn test_render_prompt() {
+        assert_eq!(
+            target(None).render_prompt("Migrate {path} to the new API."),
+            "Migrate project/src/main.rs to the new API."
+        );
+        assert_eq!(
+            target(Some("1: error")).render_prompt("Fix {path}.\n{context}"),
+            "Fix project/src/main.rs.\n1: error"
+        );
+        assert_eq!(
+            target(None).render_prompt("Add a license header."),
+            "Add a license header.\n\nFile: project/src/main.rs"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_batch_run_review(cx: &mut TestAppContext) {
+        init_test(cx);
+        let project = test_project(&["a", "b", "c", "d", "e"], cx).await;
+        let connection = StubAgentConnection::new();
+        let batch_run = new_batch_run(&project, Ok(connection.clone()), 2, cx).await;
+
+        let max_running = Rc::new(Cell::new(0));
+        cx.update(|cx| {
+            let max_running = max_running.clone();
+            cx.subscribe(&batch_run, move |batch_run, _: &BatchRunEvent, cx| {
+                let running = batch_run
+                    .read(cx)
+                    .count(|status| *status == BatchEntryStatus::Running);
+                max_running.set(max_running.get().max(running));
+            })
+            .detach();
+        });
+
+        cx.run_until_parked();
+        use BatchEntryStatus::*;
+        assert_eq!(
+            statuses(&batch_run, cx),
+            [Running, Running, Queued, Queued, Queued]
+        );
+
+        edit_target(&batch_run, 0, &project, cx).await;
+        connection.end_turn(session_id(0), acp::StopReason::EndTurn);
+        cx.run_until_parked();
+        assert_eq!(
+            statuses(&batch_run, cx),
+            [NeedsReview, Running, Running, Queued, Queued]
+        );
+
+        connection.end_turn(session_id(1), acp::StopReason::EndTurn);
+        cx.run_until_parked();
+        assert_eq!(
+            statuses(&batch_run, cx),
+            [NeedsReview, NoChanges, Running, Running, Queued]
+        );
+
+        edit_target(&batch_run, 2, &project, cx).await;
+        connection.end_turn(session_id(2), acp::StopReason::EndTurn);
+        edit_target(&batch_run, 3, &project, cx).await;
+        connection.end_turn(session_id(3), acp::StopReason::EndTurn);
+        cx.run_until_parked();
+        connection.end_turn(session_id(4), acp::StopReason::EndTurn);
+        cx.run_until_parked();
+        assert_eq!(
+            statuses(&batch_run, cx),
+            [NeedsReview, NoChanges, NeedsReview, NeedsReview, NoChanges]
+        );
+        assert!(!batch_run.read_with(cx, |batch_run, _| batch_run.is_running()));
+        assert_eq!(max_running.get(), 2);
+
+        batch_run.update(cx, |batch_run, cx| batch_run.keep(0, cx));
+        batch_run.update(cx, |batch_run, cx| batch_run.reject(2, cx));
+        cx.run_until_parked();
+        assert_eq!(
+            statuses(&batch_run, cx),
+            [Kept, NoChanges, Rejected, NeedsReview, NoChanges]
+        );
+        assert_eq!(buffer_text("a", &project, cx).await, "A\n");
+        assert_eq!(buffer_text("c", &project, cx).await, "c\n");
+        assert!(changed_buffers(&batch_run, 0, cx).is_empty());
+        assert!(changed_buffers(&batch_run, 2, cx).is_empty());
+
+        batch_run.update(cx, |batch_run, cx| batch_run.keep_all(cx));
+        cx.run_until_parked();
+        assert_eq!(
+            statuses(&batch_run, cx),
+            [Kept, NoChanges, Rejected, Kept, NoChanges]
+        );
+        assert_eq!(buffer_text("d", &project, cx).await, "D\n");
+        assert!(changed_buffers(&batch_run, 3, cx).is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_batch_run_cancel(cx: &mut TestAppContext) {
+        init_test(cx);
+        let project = test_project(&["a", "b", "c"], cx).await;
+        let connection = StubAgentConnection::new();
+        let batch_run = new_batch_run(&project, Ok(connection.clone()), 1, cx).await;
+        cx.run_until_parked();
+        use BatchEntryStatus::*;
+        assert_eq!(statuses(&batch_run, cx), [Running, Queued, Queued]);
+
+        edit_target(&batch_run, 0, &project, cx).await;
+        batch_run.update(cx, |batch_run, cx| batch_run.cancel(cx));
+        cx.run_until_parked();
+        assert_eq!(statuses(&batch_run, cx), [Cancelled, Cancelled, Cancelled]);
+
+        // The running thread is stopped, but the edits it already made stay in
+        // its action log so they can still be reviewed from the thread.
+        let thread = entry_thread(&batch_run, 0, cx);
+        thread.read_with(cx, |thread, _| {
+            assert_eq!(thread.status(), ThreadStatus::Idle)
+        });
+        assert_eq!(changed_buffers(&batch_run, 0, cx).len(), 1);
+        assert_eq!(buffer_text("a", &project, cx).await, "A\n");
+        batch_run.read_with(cx, |batch_run, _| {
+            assert!(
+                batch_run.entries()[1..]
+                    .iter()
+                    .all(|entry| entry.thread.is_none())
+            );
+        });
+
+        // Cancelled entries can't be kept or rejected anymore.
+        batch_run.update(cx, |batch_run, cx| batch_run.reject_all(cx));
+        cx.run_until_parked();
+        assert_eq!(buffer_text("a", &project, cx).await, "A\n");
+    }
+
+    #[gpui::test]
+    async fn test_batch_run_tool_authorization(cx: &mut TestAppContext) {
+        init_test(cx);
+        let project = test_project(&["a", "b"], cx).await;
+        let tool_call_id = acp::ToolCallId("1".into());
+        let connection =
+            StubAgentConnection::new().with_permission_requests(HashMap::from_iter([(
+                tool_call_id.clone(),
+                vec![
+                    acp::PermissionOption {
+                        id: acp::PermissionOptionId("allow".into()),
+                        name: "Allow".into(),
+                        kind: acp::PermissionOptionKind::AllowOnce,
+                        meta: None,
+                    },
+                    acp::PermissionOption {
+                        id: acp::PermissionOptionId("deny".into()),
+                        name: "Deny".into(),
+                        kind: acp::PermissionOptionKind::RejectOnce,
+                        meta: None,
+                    },
+                ],
+            )]));
+        connection.set_next_prompt_updates(vec![acp::SessionUpdate::ToolCall(acp::ToolCall {
+            id: tool_call_id,
+            title: "Run tests".into(),
+            kind: acp::ToolKind::Execute,
+            status: acp::ToolCallStatus::Pending,
+            content: vec![],
+            locations: vec![],
+            raw_input: None,
+            raw_output: None,
+            meta: None,
+        })]);
+        let batch_run = new_batch_run(&project, Ok(connection.clone()), 2, cx).await;
+        cx.run_until_parked();
+
+        // The first thread waits for its tool call to be allowed or denied
+        // from the review queue.
+        use BatchEntryStatus::*;
+        assert_eq!(statuses(&batch_run, cx), [Running, Running]);
+        batch_run.read_with(cx, |batch_run, cx| {
+            let tool_call = batch_run.pending_authorization(0, cx).unwrap();
+            assert_eq!(tool_call.label.read(cx).source(), "Run tests");
+            assert!(batch_run.pending_authorization(1, cx).is_none());
+            assert_eq!(batch_run.count_pending_authorizations(cx), 1);
+        });
+
+        batch_run.update(cx, |batch_run, cx| batch_run.authorize(0, false, cx));
+        cx.run_until_parked();
+        assert_eq!(statuses(&batch_run, cx), [NoChanges, Running]);
+        batch_run.read_with(cx, |batch_run, cx| {
+            assert_eq!(batch_run.count_pending_authorizations(cx), 0);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_batch_run_connection_failure(cx: &mut TestAppContext) {
+        init_test(cx);
+        let project = test_project(&["a", "b"], cx).await;
+        let batch_run = new_batch_run(&project, Err(anyhow!("not authenticated")), 1, cx).await;
+        cx.run_until_parked();
+        let failed = BatchEntryStatus::Failed("not authenticated".into());
+        assert_eq!(statuses(&batch_run, cx), [failed.clone(), failed]);
+        assert!(!batch_run.read_with(cx, |batch_run, _| batch_run.is_running()));
+    }
+
+    #[gpui::test]
+    async fn test_glob_targets(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                "src": { "main.rs": "", "lib": { "mod.rs": "" } },
+                "tests": { "test.rs": "" },
+                "README.md": "",
+            }),
+        )
+        .await;
+        fs.insert_tree(path!("/other"), json!({ "src": { "other.rs": "" } }))
+            .await;
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+
+        let glob_targets = async |glob: &str, cx: &mut TestAppContext| {
+            let mut paths = cx
+                .update(|cx| collect_glob_targets(glob, &project, cx))
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|target| target.display_path.replace('\\', "/"))
+                .collect::<Vec<_>>();
+            paths.sort();
+            paths
+        };
+
+        // With a single worktree, globs are relative to it.
+        assert_eq!(
+            glob_targets("src/**/*.rs", cx).await,
+            ["project/src/lib/mod.rs", "project/src/main.rs"]
+        );
+        assert_eq!(glob_targets("tests", cx).await, ["project/tests/test.rs"]);
+        assert!(glob_targets("project/src/**/*.rs", cx).await.is_empty());
+
+        // With several, they start with the worktree's name.
+        project
+            .update(cx, |project, cx| {
+                project.find_or_create_worktree(path!("/other"), true, cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            glob_targets("other/**/*.rs", cx).await,
+            ["other/src/other.rs"]
+        );
+        assert!(glob_targets("src/**/*.rs", cx).await.is_empty());
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+    }
+
+    async fn test_project(names: &[&str], cx: &mut TestAppContext) -> Entity<Project> {
+        let fs = FakeFs::new(cx.executor());
+        let files = names
+            .iter()
+            .map(|name| (format!("{name}.txt"), json!(format!("{name}\n"))))
+            .collect::<serde_json::Map<_, _>>();
+        fs.insert_tree(path!("/project"), json!(files)).await;
+        Project::test(fs, [path!("/project").as_ref()], cx).await
+    }
+
+    async fn new_batch_run(
+        project: &Entity<Project>,
+        connection: Result<StubAgentConnection>,
+        max_concurrency: usize,
+        cx: &mut TestAppContext,
+    ) -> Entity<BatchRun> {
+        let targets = cx
+            .update(|cx| collect_glob_targets("*.txt", project, cx))
+            .await
+            .unwrap();
+        cx.new(|cx| {
+            BatchRun::with_connection(
+                project.clone(),
+                "Capitalize {path}".into(),
+                targets,
+                max_concurrency,
+                async move { Ok(Rc::new(connection?) as Rc<dyn AgentConnection>) },
+                cx,
+            )
+        })
+    }
+
+    fn session_id(ix: usize) -> acp::SessionId {
+        // The stub connection numbers its sessions in creation order.
+        acp::SessionId(ix.to_string().into())
+    }
+
+    fn statuses(batch_run: &Entity<BatchRun>, cx: &TestAppContext) -> Vec<BatchEntryStatus> {
+        batch_run.read_with(cx, |batch_run, _| {
+            batch_run
+                .entries()
+                .iter()
+                .map(|entry| entry.status.clone())
+                .collect()
+        })
+    }
+
+    fn entry_thread(
+        batch_run: &Entity<BatchRun>,
+        ix: usize,
+        cx: &TestAppContext,
+    ) -> Entity<AcpThread> {
+        batch_run.read_with(cx, |batch_run, _| {
+            batch_run.entries()[ix].thread.clone().unwrap()
+        })
+    }
+
+    fn changed_buffers(
+        batch_run: &Entity<BatchRun>,
+        ix: usize,
+        cx: &TestAppContext,
+    ) -> Vec<Entity<language::Buffer>> {
+        entry_thread(batch_run, ix, cx).read_with(cx, |thread, cx| {
+            thread
+                .action_log()
+                .read(cx)
+                .changed_buffers(cx)
+                .into_keys()
+                .collect()
+        })
+    }
+
+    /// Capitalizes the target's contents on behalf of the entry's thread.
+    async fn edit_target(
+        batch_run: &Entity<BatchRun>,
+        ix: usize,
+        project: &Entity<Project>,
+        cx: &mut TestAppContext,
+    ) {
+        let path = batch_run.read_with(cx, |batch_run, _| {
+            batch_run.entries()[ix].target.path.clone()
+        });
+        let buffer = project
+            .update(cx, |project, cx| project.open_buffer(path, cx))
+            .await
+            .unwrap();
+        let action_log =
+            entry_thread(batch_run, ix, cx).read_with(cx, |thread, _| thread.action_log().clone());
+        cx.update(|cx| {
+            action_log.update(cx, |log, cx| log.buffer_read(buffer.clone(), cx));
+            buffer.update(cx, |buffer, cx| {
+                let text = buffer.text().to_uppercase();
+                buffer.set_text(text, cx);
+            });
+            action_log.update(cx, |log, cx| log.buffer_edited(buffer.clone(), cx));
+        });
+        cx.run_until_parked();
+    }
+
+    async fn buffer_text(name: &str, project: &Entity<Project>, cx: &mut TestAppContext) -> String {
+        let path = project
+            .read_with(cx, |project, cx| {
+                project.find_project_path(format!("project/{name}.txt"), cx)
+            })
+            .unwrap();
+        let buffer = project
+            .update(cx, |project, cx| project.open_buffer(path, cx))
+            .await
+            .unwrap();
+        buffer.read_with(cx, |buffer, _| buffer.text())
+    }
+}
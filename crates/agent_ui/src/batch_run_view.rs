@@ -0,0 +1,581 @@
+use std::sync::Arc;
+
+use agent::HistoryStore;
+use editor::{Editor, EditorElement, EditorStyle};
+use fs::Fs;
+use gpui::{
+    DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Subscription, Task, TextStyle,
+    WeakEntity, uniform_list,
+};
+use settings::Settings as _;
+use theme::ThemeSettings;
+use ui::{
+    Banner, Divider, KeyBinding, Modal, ModalFooter, ModalHeader, Section, TintColor, Tooltip,
+    prelude::*,
+};
+use ui_input::InputField;
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace, item::Item};
+
+use crate::AgentDiffPane;
+use crate::batch_run::{
+    BatchEntryStatus, BatchRun, BatchRunEvent, BatchTargetSource, DEFAULT_MAX_CONCURRENCY,
+    collect_targets,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SourceKind {
+    Glob,
+    ProjectSearch,
+    Diagnostics,
+}
+
+impl SourceKind {
+    const ALL: [SourceKind; 3] = [Self::Glob, Self::ProjectSearch, Self::Diagnostics];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Glob => "Files Matching Glob",
+            Self::ProjectSearch => "Project Search Results",
+            Self::Diagnostics => "Files with Diagnostics",
+        }
+    }
+}
+
+/// Collects the prompt template and targets for a new batch run.
+pub struct BatchRunModal {
+    workspace: WeakEntity<Workspace>,
+    fs: Arc<dyn Fs>,
+    history_store: Entity<HistoryStore>,
+    source_kind: SourceKind,
+    glob: Entity<InputField>,
+    max_concurrency: Entity<InputField>,
+    prompt_template: Entity<Editor>,
+    last_error: Option<SharedString>,
+    pending_start: Option<Task<()>>,
+    focus_handle: FocusHandle,
+}
+
+impl BatchRunModal {
+    pub fn new(
+        workspace: WeakEntity<Workspace>,
+        fs: Arc<dyn Fs>,
+        history_store: Entity<HistoryStore>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let glob = cx.new(|cx| {
+            InputField::new(window, cx, "src/**/*.rs")
+                .label("Glob")
+                .tab_index(1)
+        });
+        let max_concurrency = cx.new(|cx| {
+            let input = InputField::new(window, cx, DEFAULT_MAX_CONCURRENCY.to_string())
+                .label("Concurrent Threads")
+                .tab_index(2);
+            input.set_text(DEFAULT_MAX_CONCURRENCY.to_string(), window, cx);
+            input
+        });
+        let prompt_template = cx.new(|cx| {
+            let mut editor = Editor::auto_height(4, 12, window, cx);
+            editor.set_placeholder_text(
+                "Migrate {path} to the new API. Use {context} to refer to matches or diagnostics.",
+                window,
+                cx,
+            );
+            editor
+        });
+
+        Self {
+            workspace,
+            fs,
+            history_store,
+            source_kind: SourceKind::Glob,
+            glob,
+            max_concurrency,
+            prompt_template,
+            last_error: None,
+            pending_start: None,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if self.pending_start.is_some() {
+            return;
+        }
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+
+        let prompt_template = self.prompt_template.read(cx).text(cx);
+        if prompt_template.trim().is_empty() {
+            self.set_error("The prompt cannot be empty", cx);
+            return;
+        }
+        let Ok(max_concurrency) = self
+            .max_concurrency
+            .read(cx)
+            .text(cx)
+            .trim()
+            .parse::<usize>()
+        else {
+            self.set_error("The number of concurrent threads must be a number", cx);
+            return;
+        };
+        let source = match self.source_kind {
+            SourceKind::Glob => {
+                let glob = self.glob.read(cx).text(cx);
+                if glob.trim().is_empty() {
+                    self.set_error("The glob cannot be empty", cx);
+                    return;
+                }
+                BatchTargetSource::Glob(glob.trim().to_string())
+            }
+            SourceKind::ProjectSearch => BatchTargetSource::ProjectSearch,
+            SourceKind::Diagnostics => BatchTargetSource::Diagnostics,
+        };
+
+        let targets = collect_targets(&source, workspace.read(cx), cx);
+        let fs = self.fs.clone();
+        let history_store = self.history_store.clone();
+        self.pending_start = Some(cx.spawn_in(window, async move |this, cx| {
+            let targets = targets.await;
+            this.update_in(cx, |this, window, cx| {
+                this.pending_start = None;
+                let targets = match targets {
+                    Ok(targets) if targets.is_empty() => {
+                        this.set_error("No targets found", cx);
+                        return;
+                    }
+                    Ok(targets) => targets,
+                    Err(error) => {
+                        this.set_error(error.to_string(), cx);
+                        return;
+                    }
+                };
+
+                workspace.update(cx, |workspace, cx| {
+                    let project = workspace.project().clone();
+                    let batch_run = cx.new(|cx| {
+                        BatchRun::new(
+                            project,
+                            prompt_template,
+                            targets,
+                            max_concurrency,
+                            fs,
+                            history_store,
+                            cx,
+                        )
+                    });
+                    let view =
+                        cx.new(|cx| BatchRunView::new(batch_run, workspace.weak_handle(), cx));
+                    workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+                });
+                cx.emit(DismissEvent);
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn set_error(&mut self, error: impl Into<SharedString>, cx: &mut Context<Self>) {
+        self.last_error = Some(error.into());
+        cx.notify();
+    }
+
+    fn render_prompt_template(&self, cx: &App) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: cx.theme().colors().text,
+            font_family: settings.buffer_font.family.clone(),
+            font_features: settings.buffer_font.features.clone(),
+            font_size: settings.buffer_font_size(cx).into(),
+            line_height: relative(settings.buffer_line_height.value()),
+            ..Default::default()
+        };
+
+        v_flex()
+            .gap_1()
+            .child(Label::new("Prompt").size(LabelSize::Small))
+            .child(
+                div()
+                    .p_2()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .bg(cx.theme().colors().editor_background)
+                    .child(EditorElement::new(
+                        &self.prompt_template,
+                        EditorStyle {
+                            background: cx.theme().colors().editor_background,
+                            local_player: cx.theme().players().local(),
+                            text: text_style,
+                            ..Default::default()
+                        },
+                    )),
+            )
+            .child(
+                Label::new("{path} and {context} are replaced with each target's details.")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+    }
+}
+
+impl ModalView for BatchRunModal {}
+
+impl EventEmitter<DismissEvent> for BatchRunModal {}
+
+impl Focusable for BatchRunModal {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for BatchRunModal {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx);
+
+        v_flex()
+            .id("batch-run-modal")
+            .key_context("BatchRunModal")
+            .w(rems(34.))
+            .elevation_3(cx)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .child(
+                Modal::new("batch-run", None)
+                    .header(ModalHeader::new().headline("New Batch Run").description(
+                        "Run the same prompt against many files, one thread per file.",
+                    ))
+                    .when_some(self.last_error.clone(), |this, error| {
+                        this.section(
+                            Section::new().child(
+                                Banner::new()
+                                    .severity(Severity::Warning)
+                                    .child(div().text_xs().child(error)),
+                            ),
+                        )
+                    })
+                    .child(
+                        v_flex()
+                            .px_3()
+                            .gap_2()
+                            .child(h_flex().gap_1().children(SourceKind::ALL.map(|kind| {
+                                Button::new(kind.label(), kind.label())
+                                    .label_size(LabelSize::Small)
+                                    .toggle_state(self.source_kind == kind)
+                                    .selected_style(ButtonStyle::Tinted(TintColor::Accent))
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        this.source_kind = kind;
+                                        cx.notify();
+                                    }))
+                            })))
+                            .when(self.source_kind == SourceKind::Glob, |this| {
+                                this.child(self.glob.clone())
+                            })
+                            .child(self.render_prompt_template(cx))
+                            .child(self.max_concurrency.clone()),
+                    )
+                    .footer(
+                        ModalFooter::new().end_slot(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    Button::new("cancel", "Cancel")
+                                        .key_binding(
+                                            KeyBinding::for_action_in(
+                                                &menu::Cancel,
+                                                &focus_handle,
+                                                cx,
+                                            )
+                                            .map(|kb| kb.size(rems_from_px(12.))),
+                                        )
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.cancel(&menu::Cancel, window, cx)
+                                        })),
+                                )
+                                .child(
+                                    Button::new("start", "Start")
+                                        .disabled(self.pending_start.is_some())
+                                        .key_binding(
+                                            KeyBinding::for_action_in(
+                                                &menu::Confirm,
+                                                &focus_handle,
+                                                cx,
+                                            )
+                                            .map(|kb| kb.size(rems_from_px(12.))),
+                                        )
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.confirm(&menu::Confirm, window, cx)
+                                        })),
+                                ),
+                        ),
+                    ),
+            )
+    }
+}
+
+/// Shows the progress of a batch run and lets the user review the changes of
+/// each thread as they finish.
+pub struct BatchRunView {
+    batch_run: Entity<BatchRun>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl BatchRunView {
+    pub fn new(
+        batch_run: Entity<BatchRun>,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let subscriptions = vec![cx.subscribe(&batch_run, |_, _, event, cx| match event {
+            BatchRunEvent::EntryUpdated(_) => cx.notify(),
+        })];
+        Self {
+            batch_run,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn review(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(thread) = self
+            .batch_run
+            .read(cx)
+            .entries()
+            .get(ix)
+            .and_then(|entry| entry.thread.clone())
+        else {
+            return;
+        };
+        AgentDiffPane::deploy(thread, self.workspace.clone(), window, cx).log_err();
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let batch_run = self.batch_run.read(cx);
+        let total = batch_run.entries().len();
+        let finished = batch_run.count(BatchEntryStatus::is_finished);
+        let needs_review = batch_run.count(|status| *status == BatchEntryStatus::NeedsReview);
+        let failed = batch_run.count(|status| matches!(status, BatchEntryStatus::Failed(_)));
+        let awaiting_approval = batch_run.count_pending_authorizations(cx);
+        let is_running = batch_run.is_running();
+
+        h_flex()
+            .p_2()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(format!("{finished} of {total} finished")))
+                    .when(awaiting_approval > 0, |this| {
+                        this.child(
+                            Label::new(format!("{awaiting_approval} awaiting approval"))
+                                .color(Color::Warning),
+                        )
+                    })
+                    .when(needs_review > 0, |this| {
+                        this.child(
+                            Label::new(format!("{needs_review} to review")).color(Color::Accent),
+                        )
+                    })
+                    .when(failed > 0, |this| {
+                        this.child(Label::new(format!("{failed} failed")).color(Color::Error))
+                    }),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .when(is_running, |this| {
+                        this.child(Button::new("cancel-batch", "Cancel").on_click(cx.listener(
+                            |this, _, _, cx| {
+                                this.batch_run
+                                    .update(cx, |batch_run, cx| batch_run.cancel(cx));
+                            },
+                        )))
+                    })
+                    .child(
+                        Button::new("reject-all", "Reject All")
+                            .disabled(needs_review == 0)
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.batch_run
+                                    .update(cx, |batch_run, cx| batch_run.reject_all(cx));
+                            })),
+                    )
+                    .child(
+                        Button::new("keep-all", "Keep All")
+                            .disabled(needs_review == 0)
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.batch_run
+                                    .update(cx, |batch_run, cx| batch_run.keep_all(cx));
+                            })),
+                    ),
+            )
+    }
+
+    fn render_entry(&self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let batch_run = self.batch_run.read(cx);
+        let entry = &batch_run.entries()[ix];
+        let pending_tool = batch_run
+            .pending_authorization(ix, cx)
+            .map(|tool_call| SharedString::from(tool_call.label.read(cx).source().to_string()));
+        let (icon, color, status) = match &entry.status {
+            BatchEntryStatus::Queued => (IconName::Circle, Color::Muted, "Queued".into()),
+            BatchEntryStatus::Running => (IconName::ArrowCircle, Color::Accent, "Running…".into()),
+            BatchEntryStatus::NeedsReview => {
+                (IconName::Diff, Color::Accent, "Ready for Review".into())
+            }
+            BatchEntryStatus::NoChanges => (IconName::Check, Color::Muted, "No Changes".into()),
+            BatchEntryStatus::Kept => (IconName::Check, Color::Success, "Kept".into()),
+            BatchEntryStatus::Rejected => (IconName::Close, Color::Muted, "Rejected".into()),
+            BatchEntryStatus::Cancelled => (IconName::Close, Color::Muted, "Cancelled".into()),
+            BatchEntryStatus::Failed(error) => (IconName::XCircle, Color::Error, error.clone()),
+        };
+        let (icon, color, status) = match &pending_tool {
+            Some(tool) => (
+                IconName::Warning,
+                Color::Warning,
+                format!("Waiting for approval: {tool}").into(),
+            ),
+            None => (icon, color, status),
+        };
+        let needs_review = entry.status == BatchEntryStatus::NeedsReview;
+        let has_thread = entry.thread.is_some();
+
+        h_flex()
+            .id(("batch-entry", ix))
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .justify_between()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .min_w_0()
+                    .child(Icon::new(icon).size(IconSize::Small).color(color))
+                    .child(Label::new(entry.target.display_path.clone()).truncate())
+                    .child(
+                        Label::new(status)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .truncate(),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .when(has_thread, |this| {
+                        this.child(
+                            IconButton::new(("review", ix), IconName::Eye)
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Review Changes"))
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.review(ix, window, cx);
+                                })),
+                        )
+                    })
+                    .when(pending_tool.is_some(), |this| {
+                        this.child(
+                            IconButton::new(("deny", ix), IconName::Close)
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Deny Tool Call"))
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    this.batch_run.update(cx, |batch_run, cx| {
+                                        batch_run.authorize(ix, false, cx)
+                                    });
+                                })),
+                        )
+                        .child(
+                            IconButton::new(("allow", ix), IconName::Check)
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Allow Tool Call"))
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    this.batch_run.update(cx, |batch_run, cx| {
+                                        batch_run.authorize(ix, true, cx)
+                                    });
+                                })),
+                        )
+                    })
+                    .when(needs_review, |this| {
+                        this.child(
+                            IconButton::new(("reject", ix), IconName::Close)
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Reject"))
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    this.batch_run
+                                        .update(cx, |batch_run, cx| batch_run.reject(ix, cx));
+                                })),
+                        )
+                        .child(
+                            IconButton::new(("keep", ix), IconName::Check)
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Keep"))
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    this.batch_run
+                                        .update(cx, |batch_run, cx| batch_run.keep(ix, cx));
+                                })),
+                        )
+                    }),
+            )
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<()> for BatchRunView {}
+
+impl Focusable for BatchRunView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for BatchRunView {
+    type Event = ();
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Batch Run".into()
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::ZedAssistant).color(Color::Muted))
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        Some(self.batch_run.read(cx).prompt_template().to_string().into())
+    }
+}
+
+impl Render for BatchRunView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let entry_count = self.batch_run.read(cx).entries().len();
+
+        v_flex()
+            .size_full()
+            .track_focus(&self.focus_handle)
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .child(Divider::horizontal())
+            .child(
+                uniform_list(
+                    "batch-run-entries",
+                    entry_count,
+                    cx.processor(|this, range: std::ops::Range<usize>, _window, cx| {
+                        range.map(|ix| this.render_entry(ix, cx)).collect()
+                    }),
+                )
+                .flex_grow(),
+            )
+    }
+}
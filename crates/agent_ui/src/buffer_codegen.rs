@@ -1,4 +1,5 @@
 use crate::{context::LoadedContext, inline_prompt_editor::CodegenStatus};
+use agent::SpendTracker;
 use agent_settings::AgentSettings;
 use anyhow::{Context as _, Result};
 use client::telemetry::Telemetry;
@@ -14,8 +15,8 @@ use futures::{
 use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Subscription, Task};
 use language::{Buffer, IndentKind, Point, TransactionId, line_diff};
 use language_model::{
-    LanguageModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
-    LanguageModelTextStream, Role, report_assistant_event,
+    LanguageModel, LanguageModelPricing, LanguageModelRegistry, LanguageModelRequest,
+    LanguageModelRequestMessage, LanguageModelTextStream, Role, report_assistant_event,
 };
 use multi_buffer::MultiBufferRow;
 use parking_lot::Mutex;
@@ -100,6 +101,14 @@ impl BufferCodegen {
         &self.active_alternative().read(cx).status
     }
 
+    /// What this assist has cost across all of its alternatives, in US dollars.
+    pub fn cost(&self, cx: &App) -> f64 {
+        self.alternatives
+            .iter()
+            .map(|alternative| alternative.read(cx).cost())
+            .sum()
+    }
+
     pub fn alternative_count(&self, cx: &App) -> usize {
         LanguageModelRegistry::read_global(cx)
             .inline_alternative_models()
@@ -237,6 +246,9 @@ pub struct CodegenAlternative {
     line_operations: Vec<LineOperation>,
     elapsed_time: Option<f64>,
     completion: Option<String>,
+    pricing: Option<LanguageModelPricing>,
+    /// What this alternative's completions have cost, in US dollars.
+    cost: f64,
     pub message_id: Option<String>,
 }
 
@@ -295,6 +307,8 @@ impl CodegenAlternative {
             line_operations: Vec::new(),
             range,
             elapsed_time: None,
+            pricing: None,
+            cost: 0.,
             completion: None,
         }
     }
@@ -340,6 +354,11 @@ impl CodegenAlternative {
         &self.last_equal_ranges
     }
 
+    /// What this alternative's completions have cost, in US dollars.
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
     pub fn start(
         &mut self,
         user_prompt: String,
@@ -355,6 +374,7 @@ impl CodegenAlternative {
 
         self.edit_position = Some(self.range.start.bias_right(&self.snapshot));
 
+        self.pricing = AgentSettings::pricing_for_model(&model, cx);
         let api_key = model.api_key(cx);
         let telemetry_id = model.telemetry_id();
         let provider_id = model.provider_id();
@@ -443,6 +463,8 @@ impl CodegenAlternative {
                 temperature,
                 messages: vec![request_message],
                 thinking_allowed: false,
+                response_format: None,
+                max_tokens: None,
             }
         }))
     }
@@ -710,7 +732,12 @@ impl CodegenAlternative {
                     this.elapsed_time = Some(elapsed_time);
                     this.completion = Some(completion.lock().clone());
                     if let Some(usage) = token_usage {
-                        let usage = usage.lock();
+                        let usage = *usage.lock();
+                        if let Some(pricing) = this.pricing {
+                            let cost = pricing.cost(&usage);
+                            this.cost += cost;
+                            SpendTracker::record(cost, cx);
+                        }
                         telemetry::event!(
                             "Inline Assistant Completion",
                             model = model_telemetry_id,
@@ -7,6 +7,9 @@ use std::sync::atomic::AtomicBool;
 use acp_thread::MentionUri;
 use agent::{HistoryEntry, HistoryStore};
 use anyhow::Result;
+use collections::HashMap;
+use context_server::ContextServerId;
+use context_server::protocol::{InitializedContextServerProtocol, ServerCapability};
 use editor::{CompletionProvider, Editor, ExcerptId};
 use fuzzy::{PathMatch, StringMatch, StringMatchCandidate};
 use gpui::{App, Entity, Task, WeakEntity};
@@ -52,6 +55,7 @@ pub(crate) enum PromptContextType {
     Fetch,
     Thread,
     Rules,
+    McpResource,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -89,6 +93,7 @@ impl TryFrom<&str> for PromptContextType {
             "fetch" => Ok(Self::Fetch),
             "thread" => Ok(Self::Thread),
             "rule" => Ok(Self::Rules),
+            "resource" => Ok(Self::McpResource),
             _ => Err(format!("Invalid context picker mode: {}", value)),
         }
     }
@@ -102,6 +107,7 @@ impl PromptContextType {
             Self::Fetch => "fetch",
             Self::Thread => "thread",
             Self::Rules => "rule",
+            Self::McpResource => "resource",
         }
     }
 
@@ -112,6 +118,7 @@ impl PromptContextType {
             Self::Fetch => "Fetch",
             Self::Thread => "Threads",
             Self::Rules => "Rules",
+            Self::McpResource => "MCP Resources",
         }
     }
 
@@ -122,6 +129,7 @@ impl PromptContextType {
             Self::Fetch => IconName::ToolWeb,
             Self::Thread => IconName::Thread,
             Self::Rules => IconName::Reader,
+            Self::McpResource => IconName::Server,
         }
     }
 }
@@ -133,6 +141,7 @@ pub(crate) enum Match {
     RecentThread(HistoryEntry),
     Fetch(SharedString),
     Rules(RulesContextEntry),
+    McpResource(McpResourceMatch),
     Entry(EntryMatch),
 }
 
@@ -146,6 +155,7 @@ impl Match {
             Match::Symbol(_) => 1.,
             Match::Rules(_) => 1.,
             Match::Fetch(_) => 1.,
+            Match::McpResource(_) => 1.,
         }
     }
 }
@@ -161,6 +171,23 @@ pub struct RulesContextEntry {
     pub title: SharedString,
 }
 
+pub(crate) enum McpResourceMatch {
+    Resource {
+        server_id: ContextServerId,
+        resource: Arc<context_server::types::Resource>,
+    },
+    Template {
+        server_id: ContextServerId,
+        template: Arc<context_server::types::ResourceTemplate>,
+    },
+    /// A template with values for its leading variables.
+    TemplateValues {
+        server_id: ContextServerId,
+        template: Arc<context_server::types::ResourceTemplate>,
+        values: Vec<String>,
+    },
+}
+
 #[derive(Debug, Clone)]
 pub struct AvailableCommand {
     pub name: Arc<str>,
@@ -488,6 +515,132 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
         })
     }
 
+    fn completion_for_mcp_resource(
+        mat: McpResourceMatch,
+        source_range: Range<Anchor>,
+        source: Arc<T>,
+        editor: WeakEntity<Editor>,
+        mention_set: WeakEntity<MentionSet>,
+        workspace: Entity<Workspace>,
+        cx: &mut App,
+    ) -> Option<Completion> {
+        let icon_path: SharedString = PromptContextType::McpResource.icon().path().into();
+        let (uri, label, documentation) = match mat {
+            McpResourceMatch::Resource {
+                server_id,
+                resource,
+            } => (
+                MentionUri::McpResource {
+                    server_id: server_id.0.to_string(),
+                    uri: resource.uri.clone(),
+                    name: resource.name.clone(),
+                },
+                resource.name.clone(),
+                resource.description.clone(),
+            ),
+            McpResourceMatch::Template { template, .. } => {
+                return Some(Self::completion_for_partial_template(
+                    &template,
+                    &[],
+                    source_range,
+                    icon_path,
+                ));
+            }
+            McpResourceMatch::TemplateValues {
+                server_id,
+                template,
+                values,
+            } => {
+                let variables = uri_template_variables(&template.uri_template);
+                if values.len() < variables.len() {
+                    return Some(Self::completion_for_partial_template(
+                        &template,
+                        &values,
+                        source_range,
+                        icon_path,
+                    ));
+                }
+                let arguments = variables.into_iter().zip(values.iter().cloned()).collect();
+                let uri = url::Url::parse(&expand_uri_template(&template.uri_template, &arguments))
+                    .ok()?;
+                (
+                    MentionUri::McpResource {
+                        server_id: server_id.0.to_string(),
+                        uri,
+                        name: format!("{} ({})", template.name, values.join(", ")),
+                    },
+                    values.last().cloned().unwrap_or_default(),
+                    template.description.clone(),
+                )
+            }
+        };
+
+        let new_text = format!("{} ", uri.as_link());
+        let new_text_len = new_text.len();
+        Some(Completion {
+            replace_range: source_range.clone(),
+            new_text,
+            label: CodeLabel::plain(label, None),
+            documentation: documentation
+                .map(|documentation| CompletionDocumentation::SingleLine(documentation.into())),
+            source: project::CompletionSource::Custom,
+            icon_path: Some(icon_path),
+            match_start: None,
+            snippet_deduplication_key: None,
+            insert_text_mode: None,
+            confirm: Some(confirm_completion_callback(
+                uri.name().into(),
+                source_range.start,
+                new_text_len - 1,
+                uri,
+                source,
+                editor,
+                mention_set,
+                workspace,
+            )),
+        })
+    }
+
+    /// Inserts the values chosen so far for a resource template and keeps the
+    /// completion menu open so that the next variable can be completed.
+    ///
+    /// The values are percent-encoded, since they're separated by commas and
+    /// the mention ends at the first whitespace.
+    fn completion_for_partial_template(
+        template: &context_server::types::ResourceTemplate,
+        values: &[String],
+        source_range: Range<Anchor>,
+        icon_path: SharedString,
+    ) -> Completion {
+        let mut new_text = format!(
+            "@{} {}:",
+            PromptContextType::McpResource.keyword(),
+            template_key(&template.name)
+        );
+        for value in values {
+            new_text.push_str(&urlencoding::encode(value));
+            new_text.push(',');
+        }
+        let label = values
+            .last()
+            .cloned()
+            .unwrap_or_else(|| template.name.clone());
+        Completion {
+            replace_range: source_range,
+            new_text,
+            label: CodeLabel::plain(label, None),
+            documentation: Some(CompletionDocumentation::SingleLine(
+                template.uri_template.clone().into(),
+            )),
+            source: project::CompletionSource::Custom,
+            icon_path: Some(icon_path),
+            match_start: None,
+            snippet_deduplication_key: None,
+            insert_text_mode: None,
+            confirm: Some(Arc::new(|_, _, _| true)),
+        }
+    }
+
     pub(crate) fn completion_for_action(
         action: PromptContextAction,
         source_range: Range<Anchor>,
@@ -660,6 +813,17 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
                 }
             }
 
+            Some(PromptContextType::McpResource) => {
+                let search_task = search_mcp_resources(query, cancellation_flag, &workspace, cx);
+                cx.background_spawn(async move {
+                    search_task
+                        .await
+                        .into_iter()
+                        .map(Match::McpResource)
+                        .collect()
+                })
+            }
+
             None if query.is_empty() => {
                 let mut matches = self.recent_context_picker_entries(&workspace, cx);
 
@@ -841,6 +1005,14 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
             entries.push(PromptContextEntry::Mode(PromptContextType::Fetch));
         }
 
+        if self
+            .source
+            .supports_context(PromptContextType::McpResource, cx)
+            && !resource_servers(workspace, cx).is_empty()
+        {
+            entries.push(PromptContextEntry::Mode(PromptContextType::McpResource));
+        }
+
         entries
     }
 }
@@ -1042,6 +1214,16 @@ impl<T: PromptCompletionProviderDelegate> CompletionProvider for PromptCompletio
                                     cx,
                                 ),
 
+                                Match::McpResource(mat) => Self::completion_for_mcp_resource(
+                                    mat,
+                                    source_range.clone(),
+                                    source.clone(),
+                                    editor.clone(),
+                                    mention_set.clone(),
+                                    workspace.clone(),
+                                    cx,
+                                ),
+
                                 Match::Entry(EntryMatch { entry, .. }) => {
                                     Self::completion_for_entry(
                                         entry,
@@ -1561,6 +1743,276 @@ pub(crate) fn search_rules(
     })
 }
 
+/// Running context servers that provide resources.
+fn resource_servers(
+    workspace: &Entity<Workspace>,
+    cx: &App,
+) -> Vec<(ContextServerId, Arc<InitializedContextServerProtocol>)> {
+    let context_server_store = workspace.read(cx).project().read(cx).context_server_store();
+    context_server_store
+        .read(cx)
+        .running_servers()
+        .into_iter()
+        .filter_map(|server| {
+            let client = server.client()?;
+            client
+                .capable(ServerCapability::Resources)
+                .then(|| (server.id(), client))
+        })
+        .collect()
+}
+
+/// Searches the resources and resource templates of running context servers.
+///
+/// A query of the form `template-name:value,value` completes the variables
+/// of a resource template, in order, via `completion/complete`. The values
+/// are percent-encoded, as they're inserted when completing a template.
+pub(crate) fn search_mcp_resources(
+    query: String,
+    cancellation_flag: Arc<AtomicBool>,
+    workspace: &Entity<Workspace>,
+    cx: &mut App,
+) -> Task<Vec<McpResourceMatch>> {
+    let servers = resource_servers(workspace, cx);
+    let executor = cx.background_executor().clone();
+    cx.spawn(async move |_cx| {
+        let mut resources = Vec::new();
+        let mut templates = Vec::new();
+        for (server_id, client) in &servers {
+            if let Some(response) = client
+                .request::<context_server::types::requests::ResourcesList>(())
+                .await
+                .log_err()
+            {
+                resources.extend(
+                    response
+                        .resources
+                        .into_iter()
+                        .map(|resource| (server_id.clone(), Arc::new(resource))),
+                );
+            }
+            if let Some(response) = client
+                .request::<context_server::types::requests::ListResourceTemplates>(())
+                .await
+                .log_err()
+            {
+                templates.extend(
+                    response
+                        .resource_templates
+                        .into_iter()
+                        .map(|template| (server_id.clone(), client.clone(), Arc::new(template))),
+                );
+            }
+        }
+
+        if let Some((key, values)) = query.split_once(':')
+            && let Some((server_id, client, template)) = templates
+                .iter()
+                .find(|(_, _, template)| template_key(&template.name) == key)
+        {
+            let mut values = values
+                .split(',')
+                .map(|value| {
+                    urlencoding::decode(value)
+                        .map_or_else(|_| value.to_string(), |value| value.into_owned())
+                })
+                .collect::<Vec<_>>();
+            let variables = uri_template_variables(&template.uri_template);
+            let Some(variable) = variables.get(values.len() - 1) else {
+                return Vec::new();
+            };
+            let partial = values.pop().unwrap_or_default();
+            let mut candidates = client
+                .request::<context_server::types::requests::CompletionComplete>(
+                    context_server::types::CompletionCompleteParams {
+                        reference: context_server::types::CompletionReference::Resource(
+                            context_server::types::ResourceReference {
+                                ty: context_server::types::PromptReferenceType::Resource,
+                                uri: template.uri_template.clone(),
+                            },
+                        ),
+                        argument: context_server::types::CompletionArgument {
+                            name: variable.clone(),
+                            value: partial.clone(),
+                        },
+                        meta: None,
+                    },
+                )
+                .await
+                .map(|response| response.completion.values)
+                .unwrap_or_default();
+            // Servers may not support completion, so always offer what was typed.
+            if !partial.is_empty() && !candidates.contains(&partial) {
+                candidates.insert(0, partial);
+            }
+            return candidates
+                .into_iter()
+                .map(|candidate| {
+                    let mut values = values.clone();
+                    values.push(candidate);
+                    McpResourceMatch::TemplateValues {
+                        server_id: server_id.clone(),
+                        template: template.clone(),
+                        values,
+                    }
+                })
+                .collect();
+        }
+
+        let mut matches = resources
+            .into_iter()
+            .map(|(server_id, resource)| McpResourceMatch::Resource {
+                server_id,
+                resource,
+            })
+            .chain(templates.into_iter().map(|(server_id, _, template)| {
+                McpResourceMatch::Template {
+                    server_id,
+                    template,
+                }
+            }))
+            .collect::<Vec<_>>();
+        if query.is_empty() {
+            return matches;
+        }
+
+        let candidates = matches
+            .iter()
+            .enumerate()
+            .map(|(id, mat)| match mat {
+                McpResourceMatch::Resource { resource, .. } => {
+                    StringMatchCandidate::new(id, &resource.name)
+                }
+                McpResourceMatch::Template { template, .. }
+                | McpResourceMatch::TemplateValues { template, .. } => {
+                    StringMatchCandidate::new(id, &template.name)
+                }
+            })
+            .collect::<Vec<_>>();
+        let string_matches = fuzzy::match_strings(
+            &candidates,
+            &query,
+            false,
+            true,
+            100,
+            &cancellation_flag,
+            executor,
+        )
+        .await;
+
+        let mut matches = matches.drain(..).map(Some).collect::<Vec<_>>();
+        string_matches
+            .into_iter()
+            .filter_map(|mat| matches[mat.candidate_id].take())
+            .collect()
+    })
+}
+
+/// Mentions are parsed up to the next whitespace, so templates are referred
+/// to by their name with whitespace replaced.
+fn template_key(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+/// Returns the names of the variables in an RFC 6570 URI template, in order.
+fn uri_template_variables(template: &str) -> Vec<String> {
+    let mut variables = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let expression = &rest[start + 1..start + end];
+        let expression = expression.trim_start_matches(['+', '#', '.', '/', ';', '?', '&']);
+        for variable in expression.split(',') {
+            let name = variable
+                .split(':')
+                .next()
+                .unwrap_or_default()
+                .trim_end_matches('*');
+            if !name.is_empty() && !variables.iter().any(|existing| existing == name) {
+                variables.push(name.to_string());
+            }
+        }
+        rest = &rest[start + end + 1..];
+    }
+    variables
+}
+
+/// Expands an RFC 6570 URI template with string values.
+fn expand_uri_template(template: &str, values: &HashMap<String, String>) -> String {
+    let mut expanded = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        let expression = &rest[start + 1..start + end];
+        let (operator, expression) = match expression.chars().next() {
+            Some(operator @ ('+' | '#' | '.' | '/' | ';' | '?' | '&')) => {
+                (Some(operator), &expression[1..])
+            }
+            _ => (None, expression),
+        };
+        let (first, separator, named, allow_reserved) = match operator {
+            None => ("", ",", false, false),
+            Some('+') => ("", ",", false, true),
+            Some('#') => ("#", ",", false, true),
+            Some('.') => (".", ".", false, false),
+            Some('/') => ("/", "/", false, false),
+            Some(';') => (";", ";", true, false),
+            Some('?') => ("?", "&", true, false),
+            Some(_) => ("&", "&", true, false),
+        };
+
+        let mut is_first = true;
+        for variable in expression.split(',') {
+            let (name, max_length) = match variable.split_once(':') {
+                Some((name, max_length)) => (name, max_length.parse::<usize>().ok()),
+                None => (variable.trim_end_matches('*'), None),
+            };
+            let Some(value) = values.get(name) else {
+                continue;
+            };
+            let value = match max_length {
+                Some(max_length) => value.chars().take(max_length).collect(),
+                None => value.clone(),
+            };
+            expanded.push_str(if is_first { first } else { separator });
+            is_first = false;
+            if named {
+                expanded.push_str(name);
+                if value.is_empty() && operator == Some(';') {
+                    continue;
+                }
+                expanded.push('=');
+            }
+            expanded.push_str(&percent_encode(&value, allow_reserved));
+        }
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+fn percent_encode(value: &str, allow_reserved: bool) -> String {
+    const RESERVED: &str = ":/?#[]@!$&'()*+,;=";
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        let char = byte as char;
+        if char.is_ascii_alphanumeric()
+            || matches!(char, '-' | '.' | '_' | '~')
+            || (allow_reserved && RESERVED.contains(char))
+        {
+            encoded.push(char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
 pub struct SymbolMatch {
     pub symbol: Symbol,
 }
@@ -1846,4 +2298,27 @@ mod tests {
             "Should not parse with a space after @ at the start of the line"
         );
     }
+
+    #[test]
+    fn test_uri_template_expansion() {
+        assert_eq!(
+            uri_template_variables("file:///{path}/{+rest}{?query,limit}"),
+            vec!["path", "rest", "query", "limit"]
+        );
+
+        let values = HashMap::from_iter([
+            ("path".to_string(), "a b".to_string()),
+            ("rest".to_string(), "c/d".to_string()),
+            ("query".to_string(), "x&y".to_string()),
+        ]);
+        assert_eq!(
+            expand_uri_template("file:///{path}/{+rest}{?query,limit}", &values),
+            "file:///a%20b/c/d?query=x%26y"
+        );
+        assert_eq!(
+            expand_uri_template("db://{path}{/rest}{#query}", &values),
+            "db://a%20b/c%2Fd#x&y"
+        );
+        assert_eq!(template_key("GitHub  Issue"), "GitHub-Issue");
+    }
 }
@@ -0,0 +1,505 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, anyhow};
+use context_server::{ContextServerId, ElicitationHandler, types};
+use futures::channel::oneshot;
+use gpui::{DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, WeakEntity};
+use project::Project;
+use ui::{Checkbox, KeyBinding, Modal, ModalFooter, ModalHeader, Section, ToggleState, prelude::*};
+use ui_input::InputField;
+use workspace::{ModalView, Workspace};
+
+use crate::context_server_sampling::window_for_project;
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, cx| {
+        let project = workspace.project().clone();
+        project
+            .read(cx)
+            .context_server_store()
+            .update(cx, |store, cx| {
+                if !store.has_elicitation_handler() {
+                    store
+                        .set_elicitation_handler(Some(elicitation_handler(project.downgrade())), cx)
+                }
+            });
+    })
+    .detach();
+}
+
+/// Answers elicitation requests by showing the requested form in a window
+/// showing the project. Dismissing the form cancels the request.
+fn elicitation_handler(project: WeakEntity<Project>) -> ElicitationHandler {
+    Arc::new(move |server_id, request, cx| {
+        let project = project.clone();
+        cx.spawn(async move |cx| {
+            let (tx, rx) = oneshot::channel();
+            let window = cx
+                .update(|cx| window_for_project(&project, cx))?
+                .context("no window is showing the project")?;
+            window.update(cx, |workspace, window, cx| {
+                if workspace.active_modal::<ElicitationModal>(cx).is_some() {
+                    return Err(anyhow!(
+                        "another elicitation request is awaiting a response"
+                    ));
+                }
+                workspace.toggle_modal(window, cx, |window, cx| {
+                    ElicitationModal::new(server_id, request, tx, window, cx)
+                });
+                Ok(())
+            })??;
+            Ok(rx.await.unwrap_or(types::ElicitResult {
+                action: types::ElicitAction::Cancel,
+                content: None,
+            }))
+        })
+    })
+}
+
+enum FieldInput {
+    Text(Entity<InputField>),
+    Boolean(ToggleState),
+    Choice {
+        options: Vec<(String, SharedString)>,
+        selected: Option<usize>,
+    },
+}
+
+struct Field {
+    name: String,
+    label: SharedString,
+    description: Option<SharedString>,
+    required: bool,
+    property: types::ElicitationProperty,
+    input: FieldInput,
+    error: Option<SharedString>,
+}
+
+impl Field {
+    fn new(
+        name: String,
+        property: types::ElicitationProperty,
+        required: bool,
+        tab_index: isize,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self {
+        let (title, description) = match &property {
+            types::ElicitationProperty::String(schema) => (&schema.title, &schema.description),
+            types::ElicitationProperty::Number(schema)
+            | types::ElicitationProperty::Integer(schema) => (&schema.title, &schema.description),
+            types::ElicitationProperty::Boolean(schema) => (&schema.title, &schema.description),
+        };
+        let label: SharedString = title.clone().unwrap_or_else(|| name.clone()).into();
+        let description = description.clone().map(SharedString::from);
+
+        let input = match &property {
+            types::ElicitationProperty::String(types::StringSchema {
+                enum_values: Some(values),
+                enum_names,
+                default,
+                ..
+            }) => {
+                let options = values
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, value)| {
+                        let name = enum_names
+                            .as_ref()
+                            .and_then(|names| names.get(ix))
+                            .unwrap_or(value);
+                        (value.clone(), name.clone().into())
+                    })
+                    .collect::<Vec<_>>();
+                let selected = default
+                    .as_ref()
+                    .and_then(|default| options.iter().position(|(value, _)| value == default));
+                FieldInput::Choice { options, selected }
+            }
+            types::ElicitationProperty::Boolean(schema) => {
+                FieldInput::Boolean(schema.default.unwrap_or(false).into())
+            }
+            types::ElicitationProperty::String(_)
+            | types::ElicitationProperty::Number(_)
+            | types::ElicitationProperty::Integer(_) => {
+                let default = match &property {
+                    types::ElicitationProperty::String(schema) => schema.default.clone(),
+                    types::ElicitationProperty::Number(schema)
+                    | types::ElicitationProperty::Integer(schema) => {
+                        schema.default.map(|default| default.to_string())
+                    }
+                    types::ElicitationProperty::Boolean(_) => None,
+                };
+                let label = if required {
+                    format!("{label} (required)")
+                } else {
+                    label.to_string()
+                };
+                FieldInput::Text(cx.new(|cx| {
+                    let input = InputField::new(window, cx, "")
+                        .label(label)
+                        .tab_index(tab_index)
+                        .tab_stop(true);
+                    if let Some(default) = default {
+                        input.set_text(default, window, cx);
+                    }
+                    input
+                }))
+            }
+        };
+
+        Self {
+            name,
+            label,
+            description,
+            required,
+            property,
+            input,
+            error: None,
+        }
+    }
+
+    fn value(&self, cx: &App) -> Result<Option<serde_json::Value>> {
+        match &self.input {
+            FieldInput::Text(input) => parse_text_value(&self.property, &input.read(cx).text(cx)),
+            FieldInput::Boolean(state) => Ok(Some(serde_json::Value::Bool(state.selected()))),
+            FieldInput::Choice { options, selected } => Ok(selected
+                .and_then(|ix| options.get(ix))
+                .map(|(value, _)| serde_json::Value::String(value.clone()))),
+        }
+    }
+}
+
+/// Parses the text entered for a string or number property, validating it
+/// against the property's schema. Empty text means no value was given.
+fn parse_text_value(
+    property: &types::ElicitationProperty,
+    text: &str,
+) -> Result<Option<serde_json::Value>> {
+    if text.is_empty() {
+        return Ok(None);
+    }
+    match property {
+        types::ElicitationProperty::String(schema) => {
+            let length = text.chars().count();
+            if let Some(min_length) = schema.min_length
+                && length < min_length
+            {
+                anyhow::bail!("Must be at least {min_length} characters");
+            }
+            if let Some(max_length) = schema.max_length
+                && length > max_length
+            {
+                anyhow::bail!("Must be at most {max_length} characters");
+            }
+            match schema.format.as_deref() {
+                Some("email") if !text.contains('@') => anyhow::bail!("Must be an email address"),
+                Some("uri") if url::Url::parse(text).is_err() => anyhow::bail!("Must be a URI"),
+                _ => {}
+            }
+            Ok(Some(serde_json::Value::String(text.to_string())))
+        }
+        types::ElicitationProperty::Number(schema)
+        | types::ElicitationProperty::Integer(schema) => {
+            let is_integer = matches!(property, types::ElicitationProperty::Integer(_));
+            let number = if is_integer {
+                text.trim()
+                    .parse::<i64>()
+                    .map_err(|_| anyhow!("Must be a whole number"))? as f64
+            } else {
+                text.trim()
+                    .parse::<f64>()
+                    .map_err(|_| anyhow!("Must be a number"))?
+            };
+            if let Some(minimum) = schema.minimum
+                && number < minimum
+            {
+                anyhow::bail!("Must be at least {minimum}");
+            }
+            if let Some(maximum) = schema.maximum
+                && number > maximum
+            {
+                anyhow::bail!("Must be at most {maximum}");
+            }
+            Ok(Some(if is_integer {
+                serde_json::Value::from(number as i64)
+            } else {
+                serde_json::Value::from(number)
+            }))
+        }
+        types::ElicitationProperty::Boolean(_) => Ok(Some(serde_json::Value::Bool(
+            text.parse().map_err(|_| anyhow!("Must be true or false"))?,
+        ))),
+    }
+}
+
+struct ElicitationModal {
+    server_id: ContextServerId,
+    message: SharedString,
+    fields: Vec<Field>,
+    response_tx: Option<oneshot::Sender<types::ElicitResult>>,
+    focus_handle: FocusHandle,
+}
+
+impl ElicitationModal {
+    fn new(
+        server_id: ContextServerId,
+        request: types::ElicitRequest,
+        response_tx: oneshot::Sender<types::ElicitResult>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let schema = request.requested_schema;
+        let fields = schema
+            .properties
+            .into_iter()
+            .enumerate()
+            .map(|(ix, (name, property))| {
+                let required = schema.required.contains(&name);
+                Field::new(name, property, required, ix as isize + 1, window, cx)
+            })
+            .collect();
+
+        Self {
+            server_id,
+            message: request.message.into(),
+            fields,
+            response_tx: Some(response_tx),
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn respond(&mut self, result: types::ElicitResult, cx: &mut Context<Self>) {
+        if let Some(response_tx) = self.response_tx.take() {
+            response_tx.send(result).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn submit(&mut self, cx: &mut Context<Self>) {
+        let mut content = serde_json::Map::new();
+        let mut is_valid = true;
+        for ix in 0..self.fields.len() {
+            let field = &self.fields[ix];
+            let error = match field.value(cx) {
+                Ok(Some(value)) => {
+                    content.insert(field.name.clone(), value);
+                    None
+                }
+                Ok(None) if field.required => Some("This field is required".into()),
+                Ok(None) => None,
+                Err(error) => Some(error.to_string().into()),
+            };
+            is_valid &= error.is_none();
+            self.fields[ix].error = error;
+        }
+
+        if is_valid {
+            self.respond(
+                types::ElicitResult {
+                    action: types::ElicitAction::Accept,
+                    content: Some(content),
+                },
+                cx,
+            );
+        } else {
+            cx.notify();
+        }
+    }
+
+    fn decline(&mut self, cx: &mut Context<Self>) {
+        self.respond(
+            types::ElicitResult {
+                action: types::ElicitAction::Decline,
+                content: None,
+            },
+            cx,
+        );
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        self.submit(cx);
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        self.respond(
+            types::ElicitResult {
+                action: types::ElicitAction::Cancel,
+                content: None,
+            },
+            cx,
+        );
+    }
+
+    fn render_field(&self, ix: usize, cx: &mut Context<Self>) -> impl IntoElement {
+        let field = &self.fields[ix];
+        let input = match &field.input {
+            FieldInput::Text(input) => input.clone().into_any_element(),
+            FieldInput::Boolean(state) => Checkbox::new(("elicitation-checkbox", ix), *state)
+                .label(field.label.clone())
+                .on_click(cx.listener(move |this, state, _, cx| {
+                    this.fields[ix].input = FieldInput::Boolean(*state);
+                    cx.notify();
+                }))
+                .into_any_element(),
+            FieldInput::Choice { options, selected } => v_flex()
+                .gap_1()
+                .child(Label::new(if field.required {
+                    format!("{} (required)", field.label)
+                } else {
+                    field.label.to_string()
+                }))
+                .child(
+                    h_flex()
+                        .flex_wrap()
+                        .gap_1()
+                        .children(options.iter().enumerate().map(|(option_ix, (_, name))| {
+                            Button::new(
+                                SharedString::from(format!("elicitation-option-{ix}-{option_ix}")),
+                                name.clone(),
+                            )
+                            .style(ButtonStyle::Outlined)
+                            .toggle_state(*selected == Some(option_ix))
+                            .on_click(cx.listener(
+                                move |this, _, _, cx| {
+                                    if let FieldInput::Choice { selected, .. } =
+                                        &mut this.fields[ix].input
+                                    {
+                                        *selected = Some(option_ix);
+                                    }
+                                    cx.notify();
+                                },
+                            ))
+                        })),
+                )
+                .into_any_element(),
+        };
+
+        v_flex()
+            .gap_1()
+            .child(input)
+            .when_some(field.description.clone(), |this, description| {
+                this.child(
+                    Label::new(description)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .when_some(field.error.clone(), |this, error| {
+                this.child(Label::new(error).size(LabelSize::Small).color(Color::Error))
+            })
+    }
+}
+
+impl ModalView for ElicitationModal {}
+
+impl EventEmitter<DismissEvent> for ElicitationModal {}
+
+impl Focusable for ElicitationModal {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for ElicitationModal {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx);
+        let fields = (0..self.fields.len())
+            .map(|ix| self.render_field(ix, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .id("context-server-elicitation-modal")
+            .key_context("ElicitationModal")
+            .w(rems(34.))
+            .elevation_3(cx)
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .child(
+                Modal::new("context-server-elicitation", None)
+                    .header(
+                        ModalHeader::new()
+                            .headline(format!("{} Requests Input", self.server_id))
+                            .description(self.message.clone()),
+                    )
+                    .section(
+                        Section::new().child(
+                            v_flex()
+                                .id("elicitation-fields")
+                                .max_h(rems(24.))
+                                .overflow_y_scroll()
+                                .gap_3()
+                                .children(fields),
+                        ),
+                    )
+                    .footer(
+                        ModalFooter::new().end_slot(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    Button::new("decline", "Decline")
+                                        .on_click(cx.listener(|this, _, _, cx| this.decline(cx))),
+                                )
+                                .child(
+                                    Button::new("submit", "Submit")
+                                        .key_binding(
+                                            KeyBinding::for_action_in(
+                                                &menu::Confirm,
+                                                &focus_handle,
+                                                cx,
+                                            )
+                                            .map(|kb| kb.size(rems_from_px(12.))),
+                                        )
+                                        .on_click(cx.listener(|this, _, _, cx| this.submit(cx))),
+                                ),
+                        ),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_text_value() {
+        let property: types::ElicitationProperty = serde_json::from_value(serde_json::json!({
+            "type": "string",
+            "minLength": 2,
+            "maxLength": 4
+        }))
+        .unwrap();
+        assert_eq!(parse_text_value(&property, "").unwrap(), None);
+        assert_eq!(
+            parse_text_value(&property, "abc").unwrap(),
+            Some(serde_json::json!("abc"))
+        );
+        assert!(parse_text_value(&property, "a").is_err());
+        assert!(parse_text_value(&property, "abcde").is_err());
+
+        let property: types::ElicitationProperty = serde_json::from_value(serde_json::json!({
+            "type": "integer",
+            "minimum": 1,
+            "maximum": 10
+        }))
+        .unwrap();
+        assert_eq!(
+            parse_text_value(&property, "5").unwrap(),
+            Some(serde_json::json!(5))
+        );
+        assert!(parse_text_value(&property, "5.5").is_err());
+        assert!(parse_text_value(&property, "11").is_err());
+
+        let property: types::ElicitationProperty = serde_json::from_value(serde_json::json!({
+            "type": "number"
+        }))
+        .unwrap();
+        assert_eq!(
+            parse_text_value(&property, "2.5").unwrap(),
+            Some(serde_json::json!(2.5))
+        );
+        assert!(parse_text_value(&property, "two").is_err());
+    }
+}
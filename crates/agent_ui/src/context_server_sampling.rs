@@ -0,0 +1,418 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, anyhow};
+use context_server::{ContextServerId, SamplingHandler, types};
+use futures::{StreamExt as _, channel::oneshot};
+use gpui::{DismissEvent, EventEmitter, FocusHandle, Focusable, WeakEntity, WindowHandle};
+use language_model::{
+    LanguageModel, LanguageModelCompletionEvent, LanguageModelRegistry, LanguageModelRequest,
+    LanguageModelRequestMessage, MessageContent, Role, StopReason,
+};
+use project::Project;
+use ui::{KeyBinding, Modal, ModalFooter, ModalHeader, Section, prelude::*};
+use workspace::{ModalView, Workspace};
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, cx| {
+        let project = workspace.project().clone();
+        project
+            .read(cx)
+            .context_server_store()
+            .update(cx, |store, cx| {
+                // Several windows can show the same project, but its servers
+                // only need one handler.
+                if !store.has_sampling_handler() {
+                    store.set_sampling_handler(Some(sampling_handler(project.downgrade())), cx)
+                }
+            });
+    })
+    .detach();
+}
+
+/// Returns the window to ask the user in about a request from one of the
+/// project's context servers: the active window if it shows the project,
+/// otherwise any other window that does.
+pub(crate) fn window_for_project(
+    project: &WeakEntity<Project>,
+    cx: &App,
+) -> Option<WindowHandle<Workspace>> {
+    let shows_project = |window: &WindowHandle<Workspace>| {
+        window
+            .read(cx)
+            .is_ok_and(|workspace| workspace.project().entity_id() == project.entity_id())
+    };
+    cx.active_window()
+        .and_then(|window| window.downcast::<Workspace>())
+        .filter(shows_project)
+        .or_else(|| {
+            cx.windows()
+                .into_iter()
+                .filter_map(|window| window.downcast::<Workspace>())
+                .find(shows_project)
+        })
+}
+
+/// Answers sampling requests by asking the user for approval in a window
+/// showing the project and then running the completion with a configured
+/// model.
+fn sampling_handler(project: WeakEntity<Project>) -> SamplingHandler {
+    Arc::new(move |server_id, request, cx| {
+        let project = project.clone();
+        cx.spawn(async move |cx| {
+            let model = cx
+                .update(|cx| select_model(request.model_preferences.as_ref(), cx))?
+                .context("no language model is configured")?;
+            let completion_request = completion_request(&request)?;
+
+            let (tx, rx) = oneshot::channel();
+            let window = cx
+                .update(|cx| window_for_project(&project, cx))?
+                .context("no window is showing the project")?;
+            window.update(cx, |workspace, window, cx| {
+                if workspace
+                    .active_modal::<SamplingApprovalModal>(cx)
+                    .is_some()
+                {
+                    return Err(anyhow!("another sampling request is awaiting approval"));
+                }
+                let model_name = model.name().0;
+                workspace.toggle_modal(window, cx, |_, cx| {
+                    SamplingApprovalModal::new(server_id, model_name, &request, tx, cx)
+                });
+                Ok(())
+            })??;
+            if !rx.await.unwrap_or(false) {
+                return Err(anyhow!("the user declined the sampling request"));
+            }
+
+            let mut events = model.stream_completion(completion_request, cx).await?;
+            let mut text = String::new();
+            let mut stop_reason = None;
+            while let Some(event) = events.next().await {
+                match event? {
+                    LanguageModelCompletionEvent::Text(chunk) => text.push_str(&chunk),
+                    LanguageModelCompletionEvent::Stop(reason) => {
+                        stop_reason = Some(mcp_stop_reason(reason).into())
+                    }
+                    _ => {}
+                }
+            }
+            Ok(types::CreateMessageResult {
+                role: types::Role::Assistant,
+                content: types::MessageContent::Text {
+                    text,
+                    annotations: None,
+                },
+                model: model.id().0.to_string(),
+                stop_reason,
+            })
+        })
+    })
+}
+
+/// Picks the first available model matching the server's hints, in order,
+/// falling back to the default model.
+fn select_model(
+    preferences: Option<&types::ModelPreferences>,
+    cx: &App,
+) -> Option<Arc<dyn LanguageModel>> {
+    let registry = LanguageModelRegistry::read_global(cx);
+    let hints = preferences
+        .and_then(|preferences| preferences.hints.as_ref())
+        .into_iter()
+        .flatten()
+        .filter_map(|hint| hint.name.as_deref());
+    for hint in hints {
+        if let Some(model) = registry.available_models(cx).find(|model| {
+            model_matches_hint(&model.id().0, hint) || model_matches_hint(&model.name().0, hint)
+        }) {
+            return Some(model);
+        }
+    }
+    registry.default_model().map(|configured| configured.model)
+}
+
+/// Hints are treated as case-insensitive substrings of the model's name, so
+/// that e.g. `sonnet` matches any Claude Sonnet model.
+fn model_matches_hint(model_name: &str, hint: &str) -> bool {
+    !hint.is_empty() && model_name.to_lowercase().contains(&hint.to_lowercase())
+}
+
+/// Names a stop reason the way MCP does, which uses camel case and leaves
+/// reasons other than `endTurn`, `stopSequence` and `maxTokens` open.
+fn mcp_stop_reason(reason: StopReason) -> &'static str {
+    match reason {
+        StopReason::EndTurn => "endTurn",
+        StopReason::MaxTokens => "maxTokens",
+        StopReason::ToolUse => "toolUse",
+        StopReason::Refusal => "refusal",
+    }
+}
+
+fn completion_request(request: &types::CreateMessageRequest) -> Result<LanguageModelRequest> {
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = &request.system_prompt {
+        messages.push(LanguageModelRequestMessage {
+            role: Role::System,
+            content: vec![system_prompt.clone().into()],
+            cache: false,
+            reasoning_details: None,
+        });
+    }
+    for message in &request.messages {
+        let types::MessageContent::Text { text, .. } = &message.content else {
+            anyhow::bail!("only text content is supported in sampling requests");
+        };
+        messages.push(LanguageModelRequestMessage {
+            role: match message.role {
+                types::Role::User => Role::User,
+                types::Role::Assistant => Role::Assistant,
+            },
+            content: vec![MessageContent::Text(text.clone())],
+            cache: false,
+            reasoning_details: None,
+        });
+    }
+
+    Ok(LanguageModelRequest {
+        thread_id: None,
+        prompt_id: None,
+        intent: None,
+        mode: None,
+        messages,
+        tools: Vec::new(),
+        tool_choice: None,
+        stop: request.stop_sequences.clone().unwrap_or_default(),
+        temperature: request.temperature.map(|temperature| temperature as f32),
+        thinking_allowed: false,
+        response_format: None,
+        max_tokens: Some(request.max_tokens.into()),
+    })
+}
+
+struct SamplingApprovalModal {
+    server_id: ContextServerId,
+    model_name: SharedString,
+    system_prompt: Option<String>,
+    messages: Vec<(SharedString, String)>,
+    response_tx: Option<oneshot::Sender<bool>>,
+    focus_handle: FocusHandle,
+}
+
+impl SamplingApprovalModal {
+    fn new(
+        server_id: ContextServerId,
+        model_name: SharedString,
+        request: &types::CreateMessageRequest,
+        response_tx: oneshot::Sender<bool>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let messages = request
+            .messages
+            .iter()
+            .map(|message| {
+                let role = match message.role {
+                    types::Role::User => "User",
+                    types::Role::Assistant => "Assistant",
+                };
+                let text = match &message.content {
+                    types::MessageContent::Text { text, .. } => text.clone(),
+                    types::MessageContent::Image { .. } => "[Image]".into(),
+                    types::MessageContent::Audio { .. } => "[Audio]".into(),
+                    types::MessageContent::Resource { resource, .. } => {
+                        format!("[Resource: {}]", resource.uri)
+                    }
+                };
+                (role.into(), text)
+            })
+            .collect();
+
+        Self {
+            server_id,
+            model_name,
+            system_prompt: request.system_prompt.clone(),
+            messages,
+            response_tx: Some(response_tx),
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn respond(&mut self, approved: bool, cx: &mut Context<Self>) {
+        if let Some(response_tx) = self.response_tx.take() {
+            response_tx.send(approved).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        self.respond(true, cx);
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        self.respond(false, cx);
+    }
+
+    fn render_message(
+        &self,
+        role: SharedString,
+        text: String,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        v_flex()
+            .p_2()
+            .gap_1()
+            .rounded_sm()
+            .border_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Label::new(role).size(LabelSize::Small).color(Color::Muted))
+            .child(div().text_sm().child(text))
+    }
+}
+
+impl ModalView for SamplingApprovalModal {}
+
+impl EventEmitter<DismissEvent> for SamplingApprovalModal {}
+
+impl Focusable for SamplingApprovalModal {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for SamplingApprovalModal {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx);
+        let system_prompt = self
+            .system_prompt
+            .clone()
+            .map(|system_prompt| self.render_message("System".into(), system_prompt, cx));
+        let messages = self
+            .messages
+            .clone()
+            .into_iter()
+            .map(|(role, text)| self.render_message(role, text, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .id("context-server-sampling-modal")
+            .key_context("SamplingApprovalModal")
+            .w(rems(34.))
+            .elevation_3(cx)
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .child(
+                Modal::new("context-server-sampling", None)
+                    .header(
+                        ModalHeader::new()
+                            .headline(format!("{} Requests a Completion", self.server_id))
+                            .description(format!(
+                                "The following messages will be sent to {}.",
+                                self.model_name
+                            )),
+                    )
+                    .section(
+                        Section::new().child(
+                            v_flex()
+                                .id("sampling-messages")
+                                .max_h(rems(24.))
+                                .overflow_y_scroll()
+                                .gap_1()
+                                .children(system_prompt)
+                                .children(messages),
+                        ),
+                    )
+                    .footer(
+                        ModalFooter::new().end_slot(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    Button::new("deny", "Deny")
+                                        .key_binding(
+                                            KeyBinding::for_action_in(
+                                                &menu::Cancel,
+                                                &focus_handle,
+                                                cx,
+                                            )
+                                            .map(|kb| kb.size(rems_from_px(12.))),
+                                        )
+                                        .on_click(
+                                            cx.listener(|this, _, _, cx| this.respond(false, cx)),
+                                        ),
+                                )
+                                .child(
+                                    Button::new("allow", "Allow")
+                                        .key_binding(
+                                            KeyBinding::for_action_in(
+                                                &menu::Confirm,
+                                                &focus_handle,
+                                                cx,
+                                            )
+                                            .map(|kb| kb.size(rems_from_px(12.))),
+                                        )
+                                        .on_click(
+                                            cx.listener(|this, _, _, cx| this.respond(true, cx)),
+                                        ),
+                                ),
+                        ),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_matches_hint() {
+        assert!(model_matches_hint("claude-sonnet-4", "sonnet"));
+        assert!(model_matches_hint("Claude Sonnet 4", "claude sonnet"));
+        assert!(model_matches_hint("gpt-4o", "GPT"));
+        assert!(!model_matches_hint("gpt-4o", "claude"));
+        assert!(!model_matches_hint("gpt-4o", ""));
+    }
+
+    #[test]
+    fn test_completion_request() {
+        let request: types::CreateMessageRequest = serde_json::from_value(serde_json::json!({
+            "messages": [
+                { "role": "user", "content": { "type": "text", "text": "Summarize this" } },
+                { "role": "assistant", "content": { "type": "text", "text": "Sure" } }
+            ],
+            "systemPrompt": "Be brief",
+            "temperature": 0.5,
+            "maxTokens": 100,
+            "stopSequences": ["END"]
+        }))
+        .unwrap();
+        let completion = completion_request(&request).unwrap();
+        assert_eq!(
+            completion
+                .messages
+                .iter()
+                .map(|message| (message.role, message.string_contents()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Role::System, "Be brief".to_string()),
+                (Role::User, "Summarize this".to_string()),
+                (Role::Assistant, "Sure".to_string()),
+            ]
+        );
+        assert_eq!(completion.stop, vec!["END".to_string()]);
+        assert_eq!(completion.temperature, Some(0.5));
+        assert_eq!(completion.max_tokens, Some(100));
+
+        let request: types::CreateMessageRequest = serde_json::from_value(serde_json::json!({
+            "messages": [
+                {
+                    "role": "user",
+                    "content": { "type": "image", "data": "", "mimeType": "image/png" }
+                }
+            ],
+            "maxTokens": 100
+        }))
+        .unwrap();
+        assert!(completion_request(&request).is_err());
+    }
+}
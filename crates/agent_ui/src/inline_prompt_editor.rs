@@ -575,7 +575,27 @@ impl<T: 'static> PromptEditor<T> {
                                 }))
                                 .into_any_element(),
                         ],
-                        PromptEditorMode::Buffer { .. } => vec![accept],
+                        PromptEditorMode::Buffer { codegen, .. } => {
+                            let cost = codegen.read(cx).cost(cx);
+                            if cost > 0. {
+                                vec![
+                                    div()
+                                        .id("inline-assist-cost")
+                                        .child(
+                                            Label::new(crate::text_thread_editor::humanize_cost(
+                                                cost,
+                                            ))
+                                            .size(LabelSize::Small)
+                                            .color(Color::Muted),
+                                        )
+                                        .tooltip(Tooltip::text("Cost of this inline assist"))
+                                        .into_any_element(),
+                                    accept,
+                                ]
+                            } else {
+                                vec![accept]
+                            }
+                        }
                     }
                 }
             }
@@ -5,6 +5,7 @@ use agent_servers::{AgentServer, AgentServerDelegate};
 use anyhow::{Context as _, Result, anyhow};
 use assistant_slash_commands::codeblock_fence_for_path;
 use collections::{HashMap, HashSet};
+use context_server::{ContextServer, ContextServerId};
 use editor::{
     Anchor, Editor, EditorSnapshot, ExcerptId, FoldPlaceholder, ToOffset,
     display_map::{Crease, CreaseId, CreaseMetadata, FoldId},
@@ -13,7 +14,7 @@ use editor::{
 use futures::{AsyncReadExt as _, FutureExt as _, future::Shared};
 use gpui::{
     Animation, AnimationExt as _, AppContext, ClipboardEntry, Context, Empty, Entity, EntityId,
-    Image, ImageFormat, Img, SharedString, Task, WeakEntity, pulsating_between,
+    ForegroundExecutor, Image, ImageFormat, Img, SharedString, Task, WeakEntity, pulsating_between,
 };
 use http_client::{AsyncBody, HttpClientWithUrl};
 use itertools::Either;
@@ -62,6 +63,33 @@ pub struct MentionSet {
     history_store: Entity<HistoryStore>,
     prompt_store: Option<Entity<PromptStore>>,
     mentions: HashMap<CreaseId, (MentionUri, MentionTask)>,
+    mcp_resource_subscriptions: HashMap<(String, url::Url), McpResourceSubscription>,
+}
+
+/// Unsubscribes from an MCP resource when the last mention of it goes away.
+struct McpResourceSubscription {
+    server: Arc<ContextServer>,
+    uri: url::Url,
+    executor: ForegroundExecutor,
+}
+
+impl Drop for McpResourceSubscription {
+    fn drop(&mut self) {
+        let Some(protocol) = self.server.client() else {
+            return;
+        };
+        let uri = self.uri.clone();
+        self.executor
+            .spawn(async move {
+                protocol
+                    .request::<context_server::types::requests::ResourcesUnsubscribe>(
+                        context_server::types::ResourcesUnsubscribeParams { uri, meta: None },
+                    )
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
 }
 
 impl MentionSet {
@@ -75,6 +103,7 @@ impl MentionSet {
             history_store,
             prompt_store,
             mentions: HashMap::default(),
+            mcp_resource_subscriptions: HashMap::default(),
         }
     }
 
@@ -111,6 +140,7 @@ impl MentionSet {
                 self.mentions.remove(&crease_id);
             }
         }
+        self.drop_unused_mcp_resource_subscriptions();
     }
 
     pub fn insert_mention(&mut self, crease_id: CreaseId, uri: MentionUri, task: MentionTask) {
@@ -119,6 +149,7 @@ impl MentionSet {
 
     pub fn remove_mention(&mut self, crease_id: &CreaseId) {
         self.mentions.remove(crease_id);
+        self.drop_unused_mcp_resource_subscriptions();
     }
 
     pub fn creases(&self) -> HashSet<CreaseId> {
@@ -131,12 +162,29 @@ impl MentionSet {
 
     pub fn set_mentions(&mut self, mentions: HashMap<CreaseId, (MentionUri, MentionTask)>) {
         self.mentions = mentions;
+        self.drop_unused_mcp_resource_subscriptions();
     }
 
     pub fn clear(&mut self) -> impl Iterator<Item = (CreaseId, (MentionUri, MentionTask))> {
+        self.mcp_resource_subscriptions.clear();
         self.mentions.drain()
     }
 
+    fn drop_unused_mcp_resource_subscriptions(&mut self) {
+        let mentioned_resources = self
+            .mentions
+            .values()
+            .filter_map(|(mention_uri, _)| match mention_uri {
+                MentionUri::McpResource { server_id, uri, .. } => {
+                    Some((server_id.clone(), uri.clone()))
+                }
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        self.mcp_resource_subscriptions
+            .retain(|resource, _| mentioned_resources.contains(resource));
+    }
+
     pub fn confirm_mention_completion(
         &mut self,
         crease_text: SharedString,
@@ -229,6 +277,9 @@ impl MentionSet {
                 ..
             } => self.confirm_mention_for_symbol(abs_path, line_range, cx),
             MentionUri::Rule { id, .. } => self.confirm_mention_for_rule(id, cx),
+            MentionUri::McpResource { server_id, uri, .. } => {
+                self.confirm_mention_for_mcp_resource(server_id, uri, cx)
+            }
             MentionUri::PastedImage => {
                 debug_panic!("pasted image URI should not be included in completions");
                 Task::ready(Err(anyhow!(
@@ -255,7 +306,7 @@ impl MentionSet {
                         // Remove mention
                         editor.edit([(start_anchor..end_anchor, "")], cx);
                     });
-                    this.mentions.remove(&crease_id);
+                    this.remove_mention(&crease_id);
                 })
                 .ok();
             }
@@ -387,6 +438,123 @@ impl MentionSet {
         })
     }
 
+    fn confirm_mention_for_mcp_resource(
+        &mut self,
+        server_id: String,
+        uri: url::Url,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Mention>> {
+        let Some(project) = self.project.upgrade() else {
+            return Task::ready(Err(anyhow!("project not found")));
+        };
+        let Some(server) = project
+            .read(cx)
+            .context_server_store()
+            .read(cx)
+            .get_running_server(&ContextServerId(server_id.clone().into()))
+        else {
+            return Task::ready(Err(anyhow!("Context server not found")));
+        };
+        self.subscribe_to_mcp_resource(server_id, uri.clone(), server.clone(), cx);
+        cx.spawn(async move |_, _| {
+            Ok(Mention::Text {
+                content: read_mcp_resource(&server, uri).await?,
+                tracked_buffers: Vec::new(),
+            })
+        })
+    }
+
+    /// Subscribes to updates of a mentioned resource, once per resource.
+    /// Updates are delivered as `ResourceUpdated` events, see
+    /// `refresh_mcp_resource`.
+    fn subscribe_to_mcp_resource(
+        &mut self,
+        server_id: String,
+        uri: url::Url,
+        server: Arc<ContextServer>,
+        cx: &mut Context<Self>,
+    ) {
+        let key = (server_id, uri.clone());
+        if self.mcp_resource_subscriptions.contains_key(&key) {
+            return;
+        }
+        let Some(protocol) = server.client() else {
+            return;
+        };
+        let supports_subscribe = protocol
+            .initialize
+            .capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false);
+        if !supports_subscribe {
+            return;
+        }
+        let subscribe_uri = uri.clone();
+        cx.spawn(async move |_, _| {
+            protocol
+                .request::<context_server::types::requests::ResourcesSubscribe>(
+                    context_server::types::ResourcesSubscribeParams {
+                        uri: subscribe_uri,
+                        meta: None,
+                    },
+                )
+                .await
+                .log_err();
+        })
+        .detach();
+        self.mcp_resource_subscriptions.insert(
+            key,
+            McpResourceSubscription {
+                server,
+                uri,
+                executor: cx.foreground_executor().clone(),
+            },
+        );
+    }
+
+    /// Reloads mentions of a resource after the server reported a change.
+    pub fn refresh_mcp_resource(&mut self, server_id: &str, uri: &str, cx: &mut Context<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let Some(server) = project
+            .read(cx)
+            .context_server_store()
+            .read(cx)
+            .get_running_server(&ContextServerId(server_id.to_string().into()))
+        else {
+            return;
+        };
+        for (mention_uri, mention_task) in self.mentions.values_mut() {
+            let MentionUri::McpResource {
+                server_id: mention_server_id,
+                uri: resource_uri,
+                ..
+            } = mention_uri
+            else {
+                continue;
+            };
+            if mention_server_id != server_id || resource_uri.as_str() != uri {
+                continue;
+            }
+            let server = server.clone();
+            let resource_uri = resource_uri.clone();
+            *mention_task = cx
+                .spawn(async move |_, _| {
+                    read_mcp_resource(&server, resource_uri)
+                        .await
+                        .map(|content| Mention::Text {
+                            content,
+                            tracked_buffers: Vec::new(),
+                        })
+                        .map_err(|e| e.to_string())
+                })
+                .shared();
+        }
+    }
+
     pub fn confirm_mention_for_selection(
         &mut self,
         source_range: Range<text::Anchor>,
@@ -1096,3 +1264,22 @@ async fn fetch_url_content(http_client: Arc<HttpClientWithUrl>, url: String) ->
         }
     }
 }
+
+pub(crate) async fn read_mcp_resource(server: &ContextServer, uri: url::Url) -> Result<String> {
+    let protocol = server.client().context("Context server not initialized")?;
+    let response = protocol
+        .request::<context_server::types::requests::ResourcesRead>(
+            context_server::types::ResourcesReadParams { uri, meta: None },
+        )
+        .await?;
+    let content = response
+        .contents
+        .into_iter()
+        .filter_map(|contents| match contents {
+            context_server::types::ResourceContentsType::Text(text) => Some(text.text),
+            context_server::types::ResourceContentsType::Blob(_) => None,
+        })
+        .collect::<Vec<_>>();
+    anyhow::ensure!(!content.is_empty(), "Resource has no text content");
+    Ok(content.join("\n\n"))
+}
@@ -282,6 +282,8 @@ impl TerminalInlineAssistant {
                 stop: Vec::new(),
                 temperature,
                 thinking_allowed: false,
+                response_format: None,
+                max_tokens: None,
             }
         }))
     }
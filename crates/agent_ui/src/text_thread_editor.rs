@@ -2944,6 +2944,16 @@ pub fn humanize_token_count(count: u64) -> String {
     }
 }
 
+/// Formats an amount of US dollars, rounding sub-cent amounts up so that they
+/// don't read as free.
+pub fn humanize_cost(dollars: f64) -> String {
+    if dollars > 0. && dollars < 0.01 {
+        "<$0.01".to_string()
+    } else {
+        format!("${dollars:.2}")
+    }
+}
+
 pub fn make_lsp_adapter_delegate(
     project: &Entity<Project>,
     cx: &mut App,
@@ -0,0 +1,67 @@
+use std::{path::PathBuf, sync::Arc};
+
+use agent::{DbThreadMetadata, ThreadWorktree};
+use anyhow::Result;
+use collections::HashMap;
+use gpui::{App, Global, Task, WindowHandle};
+use workspace::{AppState, OpenOptions, Workspace};
+
+use crate::AgentPanel;
+
+/// Threads waiting to be opened by the agent panel of a worktree window that
+/// is still loading, keyed by the worktree's path.
+#[derive(Default)]
+struct PendingWorktreeThreads(HashMap<PathBuf, DbThreadMetadata>);
+
+impl Global for PendingWorktreeThreads {}
+
+/// Opens the worktree in a new window and loads `thread` into its agent panel.
+pub fn open_thread_in_worktree(
+    worktree: &ThreadWorktree,
+    thread: DbThreadMetadata,
+    app_state: Arc<AppState>,
+    cx: &mut App,
+) -> Task<Result<WindowHandle<Workspace>>> {
+    let worktree_path = worktree.worktree_path.clone();
+    cx.default_global::<PendingWorktreeThreads>()
+        .0
+        .insert(worktree_path.clone(), thread);
+
+    let open_task = workspace::open_paths(
+        &[worktree_path],
+        app_state,
+        OpenOptions {
+            open_new_workspace: Some(true),
+            ..Default::default()
+        },
+        cx,
+    );
+    cx.spawn(async move |cx| {
+        let (window, _) = open_task.await?;
+        // The panel may have been restored before the workspace finished
+        // opening, in which case it never saw the pending thread.
+        window.update(cx, |workspace, window, cx| {
+            if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                panel.update(cx, |panel, cx| {
+                    panel.load_pending_worktree_thread(window, cx);
+                });
+                workspace.focus_panel::<AgentPanel>(window, cx);
+            }
+        })?;
+        Ok(window)
+    })
+}
+
+/// Takes the thread queued for any of the given worktree paths, if any.
+pub(crate) fn take_pending_worktree_thread(
+    worktree_paths: impl IntoIterator<Item = PathBuf>,
+    cx: &mut App,
+) -> Option<DbThreadMetadata> {
+    if !cx.has_global::<PendingWorktreeThreads>() {
+        return None;
+    }
+    let pending = &mut cx.global_mut::<PendingWorktreeThreads>().0;
+    worktree_paths
+        .into_iter()
+        .find_map(|path| pending.remove(&path))
+}
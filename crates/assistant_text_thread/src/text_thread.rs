@@ -2303,6 +2303,8 @@ impl TextThread {
             stop: Vec::new(),
             temperature: model.and_then(|model| AgentSettings::temperature_for_model(model, cx)),
             thinking_allowed: true,
+            response_format: None,
+            max_tokens: None,
         };
         for message in self.messages(cx) {
             if message.status != MessageStatus::Done {
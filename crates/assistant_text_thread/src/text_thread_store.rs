@@ -854,7 +854,9 @@ impl TextThreadStore {
                             cx,
                         );
                     }
-                    ContextServerStatus::Stopped | ContextServerStatus::Error(_) => {
+                    ContextServerStatus::Stopped
+                    | ContextServerStatus::AuthenticationRequired
+                    | ContextServerStatus::Error(_) => {
                         if let Some(slash_command_ids) =
                             self.context_server_slash_command_ids.remove(server_id)
                         {
@@ -864,6 +866,7 @@ impl TextThreadStore {
                     _ => {}
                 }
             }
+            project::context_server_store::Event::ResourceUpdated { .. } => {}
         }
     }
 
@@ -6,9 +6,9 @@ pub use aws_sdk_bedrockruntime as bedrock_client;
 use aws_sdk_bedrockruntime::types::InferenceConfiguration;
 pub use aws_sdk_bedrockruntime::types::{
     AnyToolChoice as BedrockAnyToolChoice, AutoToolChoice as BedrockAutoToolChoice,
-    ContentBlock as BedrockInnerContent, Tool as BedrockTool, ToolChoice as BedrockToolChoice,
-    ToolConfiguration as BedrockToolConfig, ToolInputSchema as BedrockToolInputSchema,
-    ToolSpecification as BedrockToolSpec,
+    ContentBlock as BedrockInnerContent, SpecificToolChoice as BedrockSpecificToolChoice,
+    Tool as BedrockTool, ToolChoice as BedrockToolChoice, ToolConfiguration as BedrockToolConfig,
+    ToolInputSchema as BedrockToolInputSchema, ToolSpecification as BedrockToolSpec,
 };
 pub use aws_smithy_types::Blob as BedrockBlob;
 use aws_smithy_types::{Document, Number as AwsNumber};
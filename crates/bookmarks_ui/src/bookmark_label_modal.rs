@@ -0,0 +1,94 @@
+use editor::{BookmarkStore, Editor, actions::SelectAll};
+use gpui::{
+    App, AppContext as _, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    SharedString, Window,
+};
+use language::Buffer;
+use ui::prelude::*;
+use workspace::ModalView;
+
+pub(crate) struct BookmarkLabelModal {
+    store: Entity<BookmarkStore>,
+    buffer: Entity<Buffer>,
+    line: u32,
+    editor: Entity<Editor>,
+}
+
+impl EventEmitter<DismissEvent> for BookmarkLabelModal {}
+impl ModalView for BookmarkLabelModal {}
+
+impl Focusable for BookmarkLabelModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl BookmarkLabelModal {
+    pub(crate) fn new(
+        store: Entity<BookmarkStore>,
+        buffer: Entity<Buffer>,
+        line: u32,
+        label: Option<SharedString>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Bookmark label", window, cx);
+            if let Some(label) = label {
+                editor.set_text(label.to_string(), window, cx);
+                editor.select_all(&SelectAll, window, cx);
+            }
+            editor
+        });
+        Self {
+            store,
+            buffer,
+            line,
+            editor,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _window: &mut Window, cx: &mut Context<Self>) {
+        let text = self.editor.read(cx).text(cx);
+        let label = Some(text.trim())
+            .filter(|label| !label.is_empty())
+            .map(|label| SharedString::from(label.to_owned()));
+        self.store.update(cx, |store, cx| {
+            store.set_label(&self.buffer, self.line, label, cx)
+        });
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for BookmarkLabelModal {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(24.))
+            .elevation_2(cx)
+            .key_context("BookmarkLabel")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.editor.clone()),
+            )
+            .child(
+                h_flex().px_2().py_1().child(
+                    Label::new(format!(
+                        "Label the bookmark on line {}; leave empty to remove the label",
+                        self.line + 1
+                    ))
+                    .color(Color::Muted),
+                ),
+            )
+    }
+}
@@ -0,0 +1,199 @@
+use std::{path::Path, sync::Arc};
+
+use editor::BookmarkStore;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{App, Context, DismissEvent, Entity, SharedString, Task, WeakEntity, Window};
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use workspace::{Workspace, notifications::DetachAndPromptErr as _};
+
+use crate::open_bookmark;
+
+struct BookmarkEntry {
+    abs_path: Arc<Path>,
+    line: u32,
+    label: Option<SharedString>,
+    path: SharedString,
+}
+
+pub(crate) struct BookmarkPickerDelegate {
+    workspace: WeakEntity<Workspace>,
+    entries: Vec<BookmarkEntry>,
+    candidates: Arc<[StringMatchCandidate]>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl BookmarkPickerDelegate {
+    pub(crate) fn new(
+        workspace: WeakEntity<Workspace>,
+        store: &Entity<BookmarkStore>,
+        project: &Entity<Project>,
+        cx: &mut App,
+    ) -> Self {
+        let project = project.read(cx);
+        let path_style = project.path_style(cx);
+        let mut entries = Vec::new();
+        for (abs_path, bookmarks) in store.read(cx).all_bookmarks() {
+            let path: SharedString = project
+                .project_path_for_absolute_path(abs_path, cx)
+                .map(|project_path| project_path.path.display(path_style).into_owned())
+                .unwrap_or_else(|| abs_path.to_string_lossy().into_owned())
+                .into();
+            for bookmark in bookmarks {
+                entries.push(BookmarkEntry {
+                    abs_path: abs_path.clone(),
+                    line: bookmark.line,
+                    label: bookmark.label.clone(),
+                    path: path.clone(),
+                });
+            }
+        }
+        let candidates = entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| {
+                let text = match &entry.label {
+                    Some(label) => format!("{label} {}", entry.path),
+                    None => entry.path.to_string(),
+                };
+                StringMatchCandidate::new(id, &text)
+            })
+            .collect();
+        Self {
+            workspace,
+            entries,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for BookmarkPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search bookmarks…".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let candidates = self.candidates.clone();
+        let executor = cx.background_executor().clone();
+        cx.spawn_in(window, async move |picker, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        score: 0.,
+                        positions: Vec::new(),
+                        string: candidate.string.clone(),
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &Default::default(),
+                    executor,
+                )
+                .await
+            };
+            picker
+                .update(cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = 0;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|string_match| self.entries.get(string_match.candidate_id))
+        else {
+            return;
+        };
+        let abs_path = entry.abs_path.clone();
+        let line = entry.line;
+        if let Some(workspace) = self.workspace.upgrade() {
+            workspace
+                .update(cx, |workspace, cx| {
+                    open_bookmark(workspace, abs_path, line, window, cx)
+                })
+                .detach_and_prompt_err("Failed to open bookmark", window, cx, |error, _, _| {
+                    Some(error.to_string())
+                });
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let string_match = self.matches.get(ix)?;
+        let entry = self.entries.get(string_match.candidate_id)?;
+        let location = Label::new(format!(":{}", entry.line + 1))
+            .color(Color::Muted)
+            .size(LabelSize::Small);
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(IconName::Bookmark).color(Color::Accent))
+                .child(
+                    HighlightedLabel::new(
+                        string_match.string.clone(),
+                        string_match.positions.clone(),
+                    )
+                    .truncate(),
+                )
+                .end_slot(location),
+        )
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No bookmarks".into())
+    }
+}
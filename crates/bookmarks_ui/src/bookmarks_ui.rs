@@ -0,0 +1,214 @@
+mod bookmark_label_modal;
+mod bookmark_picker;
+
+use std::{path::Path, sync::Arc};
+
+use anyhow::Result;
+use editor::{
+    BookmarkStore, Editor, MultibufferSelectionMode, SelectionEffects, scroll::Autoscroll,
+};
+use gpui::{App, AsyncWindowContext, Context, Entity, Task, Window, actions, rems};
+use language::{Point, ToPoint as _};
+use picker::Picker;
+use util::ResultExt as _;
+use workspace::{OpenOptions, OpenVisible, Workspace, notifications::DetachAndPromptErr as _};
+
+use bookmark_label_modal::BookmarkLabelModal;
+use bookmark_picker::BookmarkPickerDelegate;
+
+actions!(
+    bookmarks,
+    [
+        /// Shows the bookmarks in the workspace to jump to one.
+        ShowBookmarks,
+        /// Opens every bookmarked line, with the lines around it, in a
+        /// multibuffer.
+        ViewBookmarks,
+        /// Sets the label of the bookmark on the current line, adding one if
+        /// there isn't one.
+        LabelBookmark,
+        /// Removes every bookmark in the workspace.
+        ClearBookmarks,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(show_bookmarks);
+        workspace.register_action(view_bookmarks);
+        workspace.register_action(label_bookmark);
+        workspace.register_action(clear_bookmarks);
+    })
+    .detach();
+}
+
+fn bookmark_store(cx: &Context<Workspace>) -> Option<Entity<BookmarkStore>> {
+    BookmarkStore::for_workspace(&cx.entity(), cx)
+}
+
+fn show_bookmarks(
+    workspace: &mut Workspace,
+    _: &ShowBookmarks,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(store) = bookmark_store(cx) else {
+        return;
+    };
+    let delegate =
+        BookmarkPickerDelegate::new(workspace.weak_handle(), &store, workspace.project(), cx);
+    workspace.toggle_modal(window, cx, |window, cx| {
+        Picker::uniform_list(delegate, window, cx)
+            .width(rems(34.))
+            .modal(true)
+    });
+}
+
+fn view_bookmarks(
+    workspace: &mut Workspace,
+    _: &ViewBookmarks,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(store) = bookmark_store(cx) else {
+        return;
+    };
+    let project = workspace.project().clone();
+    let project_paths = store
+        .read(cx)
+        .all_bookmarks()
+        .keys()
+        .filter_map(|abs_path| {
+            project
+                .read(cx)
+                .project_path_for_absolute_path(abs_path, cx)
+        })
+        .collect::<Vec<_>>();
+    let open_buffers = project_paths
+        .into_iter()
+        .map(|project_path| project.update(cx, |project, cx| project.open_buffer(project_path, cx)))
+        .collect::<Vec<_>>();
+    if open_buffers.is_empty() {
+        return;
+    }
+
+    cx.spawn_in(window, async move |workspace, cx| {
+        let mut locations = std::collections::HashMap::default();
+        for open_buffer in open_buffers {
+            let Some(buffer) = open_buffer.await.log_err() else {
+                continue;
+            };
+            // Now that the buffer is open its bookmarks are anchored in it.
+            let lines = store.read_with(cx, |store, cx| {
+                let snapshot = buffer.read(cx).snapshot();
+                store
+                    .bookmarks_for_buffer(snapshot.remote_id())
+                    .iter()
+                    .map(|bookmark| {
+                        let row = bookmark.position.to_point(&snapshot).row;
+                        Point::new(row, 0)..Point::new(row, snapshot.line_len(row))
+                    })
+                    .collect::<Vec<_>>()
+            })?;
+            if !lines.is_empty() {
+                locations.insert(buffer, lines);
+            }
+        }
+        if locations.is_empty() {
+            return Ok(());
+        }
+        workspace.update_in(cx, |workspace, window, cx| {
+            Editor::open_locations_in_multibuffer(
+                workspace,
+                locations,
+                "Bookmarks".into(),
+                false,
+                MultibufferSelectionMode::First,
+                window,
+                cx,
+            );
+        })
+    })
+    .detach_and_prompt_err("Failed to open bookmarks", window, cx, |error, _, _| {
+        Some(error.to_string())
+    });
+}
+
+fn label_bookmark(
+    workspace: &mut Workspace,
+    _: &LabelBookmark,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(store) = bookmark_store(cx) else {
+        return;
+    };
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let Some((buffer, line)) = editor.update(cx, |editor, cx| {
+        let head = editor
+            .selections
+            .newest::<Point>(&editor.display_snapshot(cx))
+            .head();
+        let (buffer, point, _) = editor.buffer().read(cx).point_to_buffer_point(head, cx)?;
+        Some((buffer, point.row))
+    }) else {
+        return;
+    };
+    let label = store
+        .read(cx)
+        .bookmark_at(&buffer, line, cx)
+        .and_then(|bookmark| bookmark.label.clone());
+
+    workspace.toggle_modal(window, cx, |window, cx| {
+        BookmarkLabelModal::new(store, buffer, line, label, window, cx)
+    });
+}
+
+fn clear_bookmarks(
+    _: &mut Workspace,
+    _: &ClearBookmarks,
+    _: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    if let Some(store) = bookmark_store(cx) {
+        store.update(cx, |store, cx| store.clear(cx));
+    }
+}
+
+/// Opens the file and moves the cursor to the start of the bookmarked line.
+pub(crate) fn open_bookmark(
+    workspace: &mut Workspace,
+    abs_path: Arc<Path>,
+    line: u32,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<Result<()>> {
+    let open_task = workspace.open_abs_path(
+        abs_path.to_path_buf(),
+        OpenOptions {
+            visible: Some(OpenVisible::All),
+            focus: Some(true),
+            ..Default::default()
+        },
+        window,
+        cx,
+    );
+    cx.spawn_in(window, async move |_, cx: &mut AsyncWindowContext| {
+        let item = open_task.await?;
+        cx.update(|window, cx| {
+            if let Some(editor) = item.act_as::<Editor>(cx) {
+                editor.update(cx, |editor, cx| {
+                    let point = Point::new(line, 0);
+                    editor.change_selections(
+                        SelectionEffects::scroll(Autoscroll::center()),
+                        window,
+                        cx,
+                        |s| s.select_ranges([point..point]),
+                    );
+                });
+            }
+        })
+    })
+}
@@ -1,17 +1,14 @@
 use anyhow::{Context as _, Result};
-use edit_prediction::{Direction, EditPrediction, EditPredictionProvider};
+use edit_prediction::{Direction, EditPrediction, EditPredictionProvider, FimCompletionState};
 use edit_prediction_context::{EditPredictionExcerpt, EditPredictionExcerptOptions};
 use futures::AsyncReadExt;
-use gpui::{App, Context, Entity, Task};
+use gpui::{App, Context, Entity};
 use http_client::HttpClient;
-use language::{
-    language_settings::all_language_settings, Anchor, Buffer, BufferSnapshot, EditPreview, ToPoint,
-};
+use language::{language_settings::all_language_settings, Anchor, Buffer, ToPoint};
 use language_models::MistralLanguageModelProvider;
 use mistral::CODESTRAL_API_URL;
 use serde::{Deserialize, Serialize};
 use std::{
-    ops::Range,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -25,40 +22,16 @@ const EXCERPT_OPTIONS: EditPredictionExcerptOptions = EditPredictionExcerptOptio
     target_before_cursor_over_total_bytes: 0.66,
 };
 
-/// Represents a completion that has been received and processed from Codestral.
-/// This struct maintains the state needed to interpolate the completion as the user types.
-#[derive(Clone)]
-struct CurrentCompletion {
-    /// The buffer snapshot at the time the completion was generated.
-    /// Used to detect changes and interpolate edits.
-    snapshot: BufferSnapshot,
-    /// The edits that should be applied to transform the original text into the predicted text.
-    /// Each edit is a range in the buffer and the text to replace it with.
-    edits: Arc<[(Range<Anchor>, Arc<str>)]>,
-    /// Preview of how the buffer will look after applying the edits.
-    edit_preview: EditPreview,
-}
-
-impl CurrentCompletion {
-    /// Attempts to adjust the edits based on changes made to the buffer since the completion was generated.
-    /// Returns None if the user's edits conflict with the predicted edits.
-    fn interpolate(&self, new_snapshot: &BufferSnapshot) -> Option<Vec<(Range<Anchor>, Arc<str>)>> {
-        edit_prediction::interpolate_edits(&self.snapshot, new_snapshot, &self.edits)
-    }
-}
-
 pub struct CodestralCompletionProvider {
     http_client: Arc<dyn HttpClient>,
-    pending_request: Option<Task<Result<()>>>,
-    current_completion: Option<CurrentCompletion>,
+    state: FimCompletionState,
 }
 
 impl CodestralCompletionProvider {
     pub fn new(http_client: Arc<dyn HttpClient>) -> Self {
         Self {
             http_client,
-            pending_request: None,
-            current_completion: None,
+            state: FimCompletionState::default(),
         }
     }
 
@@ -183,7 +156,7 @@ impl EditPredictionProvider for CodestralCompletionProvider {
     }
 
     fn is_refreshing(&self, _cx: &App) -> bool {
-        self.pending_request.is_some()
+        self.state.is_refreshing()
     }
 
     fn refresh(
@@ -200,15 +173,6 @@ impl EditPredictionProvider for CodestralCompletionProvider {
             return;
         };
 
-        let snapshot = buffer.read(cx).snapshot();
-
-        // Check if current completion is still valid
-        if let Some(current_completion) = self.current_completion.as_ref() {
-            if current_completion.interpolate(&snapshot).is_some() {
-                return;
-            }
-        }
-
         let http_client = self.http_client.clone();
 
         // Get settings
@@ -227,78 +191,42 @@ impl EditPredictionProvider for CodestralCompletionProvider {
             .clone()
             .unwrap_or_else(|| CODESTRAL_API_URL.to_string());
 
-        self.pending_request = Some(cx.spawn(async move |this, cx| {
-            if debounce {
-                log::debug!("Codestral: Debouncing for {:?}", DEBOUNCE_TIMEOUT);
-                smol::Timer::after(DEBOUNCE_TIMEOUT).await;
-            }
-
-            let cursor_offset = cursor_position.to_offset(&snapshot);
-            let cursor_point = cursor_offset.to_point(&snapshot);
-            let excerpt = EditPredictionExcerpt::select_from_buffer(
-                cursor_point,
-                &snapshot,
-                &EXCERPT_OPTIONS,
-                None,
-            )
-            .context("Line containing cursor doesn't fit in excerpt max bytes")?;
-
-            let excerpt_text = excerpt.text(&snapshot);
-            let cursor_within_excerpt = cursor_offset
-                .saturating_sub(excerpt.range.start)
-                .min(excerpt_text.body.len());
-            let prompt = excerpt_text.body[..cursor_within_excerpt].to_string();
-            let suffix = excerpt_text.body[cursor_within_excerpt..].to_string();
-
-            let completion_text = match Self::fetch_completion(
-                http_client,
-                &api_key,
-                prompt,
-                suffix,
-                model,
-                max_tokens,
-                api_url,
-            )
-            .await
-            {
-                Ok(completion) => completion,
-                Err(e) => {
-                    log::error!("Codestral: Failed to fetch completion: {}", e);
-                    this.update(cx, |this, cx| {
-                        this.pending_request = None;
-                        cx.notify();
-                    })?;
-                    return Err(e);
-                }
-            };
-
-            if completion_text.trim().is_empty() {
-                log::debug!("Codestral: Completion was empty after trimming; ignoring");
-                this.update(cx, |this, cx| {
-                    this.pending_request = None;
-                    cx.notify();
-                })?;
-                return Ok(());
-            }
-
-            let edits: Arc<[(Range<Anchor>, Arc<str>)]> =
-                vec![(cursor_position..cursor_position, completion_text.into())].into();
-            let edit_preview = buffer
-                .read_with(cx, |buffer, cx| buffer.preview_edits(edits.clone(), cx))?
-                .await;
-
-            this.update(cx, |this, cx| {
-                this.current_completion = Some(CurrentCompletion {
-                    snapshot,
-                    edits,
-                    edit_preview,
-                });
-                this.pending_request = None;
-                cx.notify();
-            })?;
-
-            Ok(())
-        }));
+        self.state.refresh(
+            buffer,
+            cursor_position,
+            debounce.then_some(DEBOUNCE_TIMEOUT),
+            |this: &mut Self| &mut this.state,
+            move |snapshot, _cx| async move {
+                let cursor_offset = cursor_position.to_offset(&snapshot);
+                let cursor_point = cursor_offset.to_point(&snapshot);
+                let excerpt = EditPredictionExcerpt::select_from_buffer(
+                    cursor_point,
+                    &snapshot,
+                    &EXCERPT_OPTIONS,
+                    None,
+                )
+                .context("Line containing cursor doesn't fit in excerpt max bytes")?;
+
+                let excerpt_text = excerpt.text(&snapshot);
+                let cursor_within_excerpt = cursor_offset
+                    .saturating_sub(excerpt.range.start)
+                    .min(excerpt_text.body.len());
+                let prompt = excerpt_text.body[..cursor_within_excerpt].to_string();
+                let suffix = excerpt_text.body[cursor_within_excerpt..].to_string();
+
+                Self::fetch_completion(
+                    http_client,
+                    &api_key,
+                    prompt,
+                    suffix,
+                    model,
+                    max_tokens,
+                    api_url,
+                )
+                .await
+            },
+            cx,
+        );
     }
 
     fn cycle(
@@ -313,14 +241,12 @@ impl EditPredictionProvider for CodestralCompletionProvider {
 
     fn accept(&mut self, _cx: &mut Context<Self>) {
         log::debug!("Codestral: Completion accepted");
-        self.pending_request = None;
-        self.current_completion = None;
+        self.state.clear();
     }
 
     fn discard(&mut self, _cx: &mut Context<Self>) {
         log::debug!("Codestral: Completion discarded");
-        self.pending_request = None;
-        self.current_completion = None;
+        self.state.clear();
     }
 
     /// Returns the completion suggestion, adjusted or invalidated based on user edits
@@ -330,17 +256,7 @@ impl EditPredictionProvider for CodestralCompletionProvider {
         _cursor_position: Anchor,
         cx: &mut Context<Self>,
     ) -> Option<EditPrediction> {
-        let current_completion = self.current_completion.as_ref()?;
-        let buffer = buffer.read(cx);
-        let edits = current_completion.interpolate(&buffer.snapshot())?;
-        if edits.is_empty() {
-            return None;
-        }
-        Some(EditPrediction::Local {
-            id: None,
-            edits,
-            edit_preview: Some(current_completion.edit_preview.clone()),
-        })
+        self.state.suggest(buffer, cx)
     }
 }
 
@@ -51,6 +51,7 @@ pub(crate) struct Client {
     outbound_tx: channel::Sender<String>,
     name: Arc<str>,
     notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
@@ -100,7 +101,6 @@ struct AnyResponse<'a> {
 }
 
 #[derive(Serialize, Deserialize)]
-#[allow(dead_code)]
 pub(crate) struct Response<T> {
     pub jsonrpc: &'static str,
     pub id: RequestId,
@@ -113,7 +113,6 @@ pub(crate) struct Response<T> {
 pub(crate) enum CspResult<T> {
     #[serde(rename = "result")]
     Ok(Option<T>),
-    #[allow(dead_code)]
     Error(Option<Error>),
 }
 
@@ -237,6 +236,7 @@ impl Client {
         Ok(Self {
             server_id,
             notification_handlers,
+            request_handlers,
             response_handlers,
             name: server_name,
             next_id: Default::default(),
@@ -458,6 +458,60 @@ impl Client {
     ) {
         self.notification_handlers.lock().insert(method, f);
     }
+
+    /// Registers a handler for requests sent by the context server to the client.
+    ///
+    /// The task returned by the handler is awaited in the background and its
+    /// result is sent back to the server as the response to the request.
+    pub fn on_request<T, R>(
+        &self,
+        method: &'static str,
+        mut f: impl 'static + Send + FnMut(T, AsyncApp) -> Task<Result<R>>,
+    ) where
+        T: DeserializeOwned,
+        R: 'static + Serialize,
+    {
+        let outbound_tx = self.outbound_tx.clone();
+        self.request_handlers.lock().insert(
+            method,
+            Box::new(move |id, params, cx| {
+                let response = match serde_json::from_str::<T>(params.get()) {
+                    Ok(params) => f(params, cx.clone()),
+                    Err(error) => {
+                        let response = error_response::<R>(id, INVALID_PARAMS, error.to_string());
+                        outbound_tx.try_send(response).log_err();
+                        return;
+                    }
+                };
+                let outbound_tx = outbound_tx.clone();
+                cx.foreground_executor()
+                    .spawn(async move {
+                        let response = match response.await {
+                            Ok(result) => serde_json::to_string(&Response {
+                                jsonrpc: JSON_RPC_VERSION,
+                                id,
+                                value: CspResult::Ok(Some(result)),
+                            })
+                            .unwrap(),
+                            Err(error) => {
+                                error_response::<R>(id, INTERNAL_ERROR, error.to_string())
+                            }
+                        };
+                        outbound_tx.try_send(response).log_err();
+                    })
+                    .detach();
+            }),
+        );
+    }
+}
+
+fn error_response<T: Serialize>(id: RequestId, code: i32, message: String) -> String {
+    serde_json::to_string(&Response::<T> {
+        jsonrpc: JSON_RPC_VERSION,
+        id,
+        value: CspResult::Error(Some(Error { message, code })),
+    })
+    .unwrap()
 }
 
 #[derive(Debug)]
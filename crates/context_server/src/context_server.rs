@@ -1,5 +1,6 @@
 pub mod client;
 pub mod listener;
+pub mod oauth;
 pub mod protocol;
 #[cfg(any(test, feature = "test-support"))]
 pub mod test;
@@ -14,12 +15,15 @@ use std::{fmt::Display, path::PathBuf};
 
 use anyhow::Result;
 use client::Client;
-use gpui::AsyncApp;
+use gpui::{AsyncApp, Task};
 use parking_lot::RwLock;
 pub use settings::ContextServerCommand;
 use url::Url;
 
+use crate::oauth::OAuthAuthorization;
 use crate::transport::HttpTransport;
+use crate::types::Request as _;
+use util::ResultExt as _;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ContextServerId(pub Arc<str>);
@@ -30,6 +34,26 @@ impl Display for ContextServerId {
     }
 }
 
+/// Answers `sampling/createMessage` requests, letting a context server ask the
+/// client for a language model completion.
+pub type SamplingHandler = Arc<
+    dyn Send
+        + Sync
+        + Fn(
+            ContextServerId,
+            types::CreateMessageRequest,
+            AsyncApp,
+        ) -> Task<Result<types::CreateMessageResult>>,
+>;
+
+/// Answers `elicitation/create` requests, letting a context server ask the
+/// user for structured input.
+pub type ElicitationHandler = Arc<
+    dyn Send
+        + Sync
+        + Fn(ContextServerId, types::ElicitRequest, AsyncApp) -> Task<Result<types::ElicitResult>>,
+>;
+
 enum ContextServerTransport {
     Stdio(ContextServerCommand, Option<PathBuf>),
     Custom(Arc<dyn crate::transport::Transport>),
@@ -39,6 +63,10 @@ pub struct ContextServer {
     id: ContextServerId,
     client: RwLock<Option<Arc<crate::protocol::InitializedContextServerProtocol>>>,
     configuration: ContextServerTransport,
+    sampling_handler: RwLock<Option<SamplingHandler>>,
+    elicitation_handler: RwLock<Option<ElicitationHandler>>,
+    roots: Arc<RwLock<Vec<types::Root>>>,
+    oauth: Option<Arc<OAuthAuthorization>>,
 }
 
 impl ContextServer {
@@ -50,6 +78,10 @@ impl ContextServer {
         Self {
             id,
             client: RwLock::new(None),
+            sampling_handler: RwLock::new(None),
+            elicitation_handler: RwLock::new(None),
+            roots: Default::default(),
+            oauth: None,
             configuration: ContextServerTransport::Stdio(
                 command,
                 working_directory.map(|directory| directory.to_path_buf()),
@@ -64,22 +96,31 @@ impl ContextServer {
         http_client: Arc<dyn HttpClient>,
         executor: gpui::BackgroundExecutor,
     ) -> Result<Self> {
+        let oauth = Arc::new(OAuthAuthorization::new(http_client.clone()));
         let transport = match endpoint.scheme() {
             "http" | "https" => {
                 log::info!("Using HTTP transport for {}", endpoint);
                 let transport =
-                    HttpTransport::new(http_client, endpoint.to_string(), headers, executor);
+                    HttpTransport::new(http_client, endpoint.to_string(), headers, executor)
+                        .with_oauth(oauth.clone());
                 Arc::new(transport) as _
             }
             _ => anyhow::bail!("unsupported MCP url scheme {}", endpoint.scheme()),
         };
-        Ok(Self::new(id, transport))
+        Ok(Self {
+            oauth: Some(oauth),
+            ..Self::new(id, transport)
+        })
     }
 
     pub fn new(id: ContextServerId, transport: Arc<dyn crate::transport::Transport>) -> Self {
         Self {
             id,
             client: RwLock::new(None),
+            sampling_handler: RwLock::new(None),
+            elicitation_handler: RwLock::new(None),
+            roots: Default::default(),
+            oauth: None,
             configuration: ContextServerTransport::Custom(transport),
         }
     }
@@ -92,6 +133,43 @@ impl ContextServer {
         self.client.read().clone()
     }
 
+    /// The OAuth state of servers reached over HTTP.
+    pub fn oauth(&self) -> Option<Arc<OAuthAuthorization>> {
+        self.oauth.clone()
+    }
+
+    /// Sets the handler used to answer sampling requests from the server.
+    ///
+    /// The sampling capability is advertised during initialization, so this
+    /// only takes effect the next time the server is started.
+    pub fn set_sampling_handler(&self, handler: Option<SamplingHandler>) {
+        *self.sampling_handler.write() = handler;
+    }
+
+    /// Sets the handler used to answer elicitation requests from the server.
+    ///
+    /// Like sampling, the capability is only advertised on the next start.
+    pub fn set_elicitation_handler(&self, handler: Option<ElicitationHandler>) {
+        *self.elicitation_handler.write() = handler;
+    }
+
+    /// Sets the roots reported to the server via `roots/list`, notifying a
+    /// running server when they change.
+    pub fn set_roots(&self, roots: Vec<types::Root>) {
+        {
+            let mut current_roots = self.roots.write();
+            if *current_roots == roots {
+                return;
+            }
+            *current_roots = roots;
+        }
+        if let Some(client) = self.client() {
+            client
+                .notify::<types::notifications::RootsListChanged>(())
+                .log_err();
+        }
+    }
+
     pub async fn start(&self, cx: &AsyncApp) -> Result<()> {
         self.initialize(self.new_client(cx)?).await
     }
@@ -137,12 +215,66 @@ impl ContextServer {
 
     async fn initialize(&self, client: Client) -> Result<()> {
         log::debug!("starting context server {}", self.id);
+        let sampling_handler = self.sampling_handler.read().clone();
+        if let Some(handler) = sampling_handler.clone() {
+            let id = self.id.clone();
+            client.on_request(
+                types::requests::CreateMessage::METHOD,
+                move |params: types::CreateMessageRequest, cx| handler(id.clone(), params, cx),
+            );
+        }
+        let elicitation_handler = self.elicitation_handler.read().clone();
+        if let Some(handler) = elicitation_handler.clone() {
+            let id = self.id.clone();
+            client.on_request(
+                types::requests::Elicit::METHOD,
+                move |params: types::ElicitRequest, cx| handler(id.clone(), params, cx),
+            );
+        }
+        let roots = self.roots.clone();
+        // Servers may send `roots/list` with either empty or missing params.
+        client.on_request(
+            types::requests::ListRoots::METHOD,
+            move |_: serde_json::Value, _| {
+                Task::ready(Ok(types::ListRootsResponse {
+                    roots: roots.read().clone(),
+                    meta: None,
+                }))
+            },
+        );
+
         let protocol = crate::protocol::ModelContextProtocol::new(client);
         let client_info = types::Implementation {
             name: "Zed".to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
         };
-        let initialized_protocol = protocol.initialize(client_info).await?;
+        let capabilities = types::ClientCapabilities {
+            experimental: None,
+            sampling: sampling_handler
+                .is_some()
+                .then(|| serde_json::Value::Object(Default::default())),
+            roots: Some(types::RootsCapabilities {
+                list_changed: Some(true),
+            }),
+            elicitation: elicitation_handler
+                .is_some()
+                .then(|| serde_json::Value::Object(Default::default())),
+        };
+        let initialized_protocol = match protocol.initialize(client_info, capabilities).await {
+            Ok(initialized_protocol) => initialized_protocol,
+            Err(error) => {
+                // The transport fails without forwarding its error, so surface
+                // a rejected authorization explicitly.
+                if let Some(challenge) = self
+                    .oauth
+                    .as_ref()
+                    .and_then(|oauth| oauth.take_authorization_required())
+                {
+                    return Err(challenge.into());
+                }
+                return Err(error);
+            }
+        };
 
         log::debug!(
             "context server {} initialized: {:?}",
@@ -0,0 +1,815 @@
+//! OAuth 2.1 authorization for HTTP context servers.
+//!
+//! This implements the flow described by the MCP authorization specification:
+//! the authorization server is discovered via the protected resource metadata
+//! (RFC 9728) and authorization server metadata (RFC 8414) documents, the
+//! client registers itself dynamically (RFC 7591), and the authorization code
+//! is obtained through the user's browser using PKCE, with the redirect
+//! received by a listener on the loopback interface.
+
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use futures::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _};
+use http_client::{AsyncBody, HttpClient, Request, StatusCode, http::Method};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sha2::{Digest as _, Sha256};
+use smol::channel;
+use url::Url;
+use util::ResultExt as _;
+
+const CLIENT_NAME: &str = "Zed";
+const REDIRECT_PATH: &str = "/callback";
+const REDIRECT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// How long before it expires an access token is refreshed, so that requests
+/// aren't rejected first.
+const REFRESH_BEFORE_EXPIRY: Duration = Duration::from_secs(60);
+
+/// Returned when a context server rejects a request because the client isn't
+/// authorized, and the credentials couldn't be refreshed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationRequired {
+    /// The `WWW-Authenticate` header of the rejected request, which may point
+    /// at the server's protected resource metadata.
+    pub www_authenticate: Option<String>,
+}
+
+impl std::error::Error for AuthorizationRequired {}
+
+impl fmt::Display for AuthorizationRequired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Context server requires authorization")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorizationServerMetadata {
+    pub authorization_endpoint: Url,
+    pub token_endpoint: Url,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registration_endpoint: Option<Url>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub code_challenge_methods_supported: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProtectedResourceMetadata {
+    #[serde(default)]
+    resource: Option<String>,
+    #[serde(default)]
+    authorization_servers: Vec<Url>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientRegistration {
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Seconds since the Unix epoch at which the access token expires.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+impl OAuthTokens {
+    fn expires_soon(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= unix_now() + REFRESH_BEFORE_EXPIRY.as_secs())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+impl TokenResponse {
+    fn into_tokens(self, previous_refresh_token: Option<String>) -> OAuthTokens {
+        OAuthTokens {
+            access_token: self.access_token,
+            // Authorization servers may keep the refresh token unchanged
+            // without sending it again.
+            refresh_token: self.refresh_token.or(previous_refresh_token),
+            expires_at: self.expires_in.map(|expires_in| unix_now() + expires_in),
+        }
+    }
+}
+
+/// Everything needed to authorize requests to a context server and to refresh
+/// its access token, persisted between restarts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OAuthSession {
+    pub authorization_server: AuthorizationServerMetadata,
+    pub client: ClientRegistration,
+    /// The canonical URI of the context server the tokens are bound to.
+    pub resource: String,
+    pub tokens: OAuthTokens,
+}
+
+/// OAuth state shared between an HTTP transport, which uses and refreshes the
+/// tokens, and its owner, which signs in and persists the session.
+pub struct OAuthAuthorization {
+    http_client: Arc<dyn HttpClient>,
+    session: Mutex<Option<OAuthSession>>,
+    pending_challenge: Mutex<Option<AuthorizationRequired>>,
+    refresh_lock: smol::lock::Mutex<()>,
+    refreshed_tx: channel::Sender<OAuthSession>,
+    refreshed_rx: channel::Receiver<OAuthSession>,
+}
+
+impl OAuthAuthorization {
+    pub fn new(http_client: Arc<dyn HttpClient>) -> Self {
+        let (refreshed_tx, refreshed_rx) = channel::unbounded();
+        Self {
+            http_client,
+            session: Mutex::new(None),
+            pending_challenge: Mutex::new(None),
+            refresh_lock: smol::lock::Mutex::new(()),
+            refreshed_tx,
+            refreshed_rx,
+        }
+    }
+
+    pub fn session(&self) -> Option<OAuthSession> {
+        self.session.lock().clone()
+    }
+
+    pub fn set_session(&self, session: Option<OAuthSession>) {
+        *self.session.lock() = session;
+    }
+
+    pub fn access_token(&self) -> Option<String> {
+        self.session
+            .lock()
+            .as_ref()
+            .map(|session| session.tokens.access_token.clone())
+    }
+
+    /// The access token to authorize a request with, refreshed first when
+    /// it's about to expire.
+    pub(crate) async fn fresh_access_token(&self) -> Option<String> {
+        let tokens = self.session()?.tokens;
+        if tokens.refresh_token.is_some() && tokens.expires_soon() {
+            self.refresh(&tokens.access_token)
+                .await
+                .context("failed to refresh expiring access token")
+                .log_err();
+        }
+        self.access_token()
+    }
+
+    /// Sessions whose tokens were refreshed by the transport, so that they can
+    /// be persisted.
+    pub fn refreshed_sessions(&self) -> channel::Receiver<OAuthSession> {
+        self.refreshed_rx.clone()
+    }
+
+    /// Takes the challenge of the last request the server rejected.
+    pub fn take_authorization_required(&self) -> Option<AuthorizationRequired> {
+        self.pending_challenge.lock().take()
+    }
+
+    pub(crate) fn set_authorization_required(&self, challenge: AuthorizationRequired) {
+        *self.pending_challenge.lock() = Some(challenge);
+    }
+
+    /// Exchanges the refresh token for tokens that replace
+    /// `stale_access_token`, returning whether the session could be refreshed.
+    ///
+    /// Refreshes are serialized, so that requests rejected at the same time
+    /// don't all use a refresh token that the server only accepts once.
+    pub(crate) async fn refresh(&self, stale_access_token: &str) -> Result<bool> {
+        let _refreshing = self.refresh_lock.lock().await;
+        let Some(mut session) = self.session() else {
+            return Ok(false);
+        };
+        if session.tokens.access_token != stale_access_token {
+            // Another request refreshed the tokens while this one waited.
+            return Ok(true);
+        }
+        let Some(refresh_token) = session.tokens.refresh_token.clone() else {
+            return Ok(false);
+        };
+
+        let mut params = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+            ("client_id", session.client.client_id.as_str()),
+            ("resource", session.resource.as_str()),
+        ];
+        if let Some(client_secret) = session.client.client_secret.as_deref() {
+            params.push(("client_secret", client_secret));
+        }
+        let response: TokenResponse = post_form(
+            self.http_client.as_ref(),
+            &session.authorization_server.token_endpoint,
+            &params,
+        )
+        .await
+        .context("failed to refresh access token")?;
+
+        session.tokens = response.into_tokens(Some(refresh_token));
+        self.set_session(Some(session.clone()));
+        self.refreshed_tx.try_send(session).ok();
+        Ok(true)
+    }
+}
+
+/// An authorization code flow waiting for the user to sign in via their
+/// browser.
+pub struct OAuthFlow {
+    http_client: Arc<dyn HttpClient>,
+    authorization_server: AuthorizationServerMetadata,
+    client: ClientRegistration,
+    resource: String,
+    pkce: Pkce,
+    state: String,
+    listener: RedirectListener,
+    authorization_url: Url,
+}
+
+impl OAuthFlow {
+    /// Discovers the authorization server for `endpoint`, registers the client
+    /// and starts listening for the redirect.
+    pub async fn start(
+        http_client: Arc<dyn HttpClient>,
+        endpoint: &Url,
+        www_authenticate: Option<&str>,
+    ) -> Result<Self> {
+        let (resource, authorization_server) =
+            discover(http_client.as_ref(), endpoint, www_authenticate).await?;
+        if !authorization_server
+            .code_challenge_methods_supported
+            .is_empty()
+            && !authorization_server
+                .code_challenge_methods_supported
+                .iter()
+                .any(|method| method == "S256")
+        {
+            anyhow::bail!("authorization server does not support PKCE with S256");
+        }
+
+        let listener = RedirectListener::bind().await?;
+        let client = register_client(
+            http_client.as_ref(),
+            &authorization_server,
+            listener.redirect_uri(),
+        )
+        .await?;
+        let pkce = Pkce::new();
+        let state = random_string();
+        let authorization_url = authorization_url(
+            &authorization_server,
+            &client,
+            listener.redirect_uri(),
+            &pkce,
+            &state,
+            &resource,
+        );
+
+        Ok(Self {
+            http_client,
+            authorization_server,
+            client,
+            resource,
+            pkce,
+            state,
+            listener,
+            authorization_url,
+        })
+    }
+
+    /// The URL the user should open to sign in.
+    pub fn authorization_url(&self) -> &Url {
+        &self.authorization_url
+    }
+
+    /// Waits for the browser to be redirected back and exchanges the code for
+    /// tokens.
+    pub async fn finish(self) -> Result<OAuthSession> {
+        let redirect_uri = self.listener.redirect_uri().clone();
+        let code = self.listener.receive_code(&self.state).await?;
+
+        let mut params = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("client_id", self.client.client_id.as_str()),
+            ("code_verifier", self.pkce.verifier.as_str()),
+            ("resource", self.resource.as_str()),
+        ];
+        if let Some(client_secret) = self.client.client_secret.as_deref() {
+            params.push(("client_secret", client_secret));
+        }
+        let response: TokenResponse = post_form(
+            self.http_client.as_ref(),
+            &self.authorization_server.token_endpoint,
+            &params,
+        )
+        .await
+        .context("failed to exchange authorization code")?;
+
+        Ok(OAuthSession {
+            authorization_server: self.authorization_server,
+            client: self.client,
+            resource: self.resource,
+            tokens: response.into_tokens(None),
+        })
+    }
+}
+
+/// Finds the authorization server protecting `endpoint`, returning the
+/// canonical resource URI along with the server's metadata.
+async fn discover(
+    http_client: &dyn HttpClient,
+    endpoint: &Url,
+    www_authenticate: Option<&str>,
+) -> Result<(String, AuthorizationServerMetadata)> {
+    let resource_metadata_url = www_authenticate
+        .and_then(resource_metadata_url)
+        .unwrap_or_else(|| well_known_url(endpoint, "oauth-protected-resource"));
+    let resource_metadata = ok_or_log_debug(
+        get_json::<ProtectedResourceMetadata>(http_client, &resource_metadata_url).await,
+        "protected resource metadata",
+    );
+
+    let mut resource = canonical_resource_uri(endpoint);
+    // Servers that predate protected resource metadata act as their own
+    // authorization server.
+    let mut authorization_server = endpoint.clone();
+    authorization_server.set_path("");
+    authorization_server.set_query(None);
+    if let Some(metadata) = resource_metadata {
+        if let Some(metadata_resource) = metadata.resource {
+            resource = metadata_resource;
+        }
+        if let Some(server) = metadata.authorization_servers.into_iter().next() {
+            authorization_server = server;
+        }
+    }
+
+    for suffix in ["oauth-authorization-server", "openid-configuration"] {
+        let url = well_known_url(&authorization_server, suffix);
+        if let Some(metadata) = ok_or_log_debug(
+            get_json::<AuthorizationServerMetadata>(http_client, &url).await,
+            "authorization server metadata",
+        ) {
+            return Ok((resource, metadata));
+        }
+    }
+    Err(anyhow!(
+        "failed to discover authorization server metadata for {authorization_server}"
+    ))
+}
+
+async fn register_client(
+    http_client: &dyn HttpClient,
+    authorization_server: &AuthorizationServerMetadata,
+    redirect_uri: &Url,
+) -> Result<ClientRegistration> {
+    let registration_endpoint = authorization_server
+        .registration_endpoint
+        .as_ref()
+        .context("authorization server does not support dynamic client registration")?;
+    let body = serde_json::json!({
+        "client_name": CLIENT_NAME,
+        "redirect_uris": [redirect_uri],
+        "grant_types": ["authorization_code", "refresh_token"],
+        "response_types": ["code"],
+        "token_endpoint_auth_method": "none",
+    });
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(registration_endpoint.as_str())
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json")
+        .body(AsyncBody::from(serde_json::to_vec(&body)?))?;
+    send_json(http_client, request)
+        .await
+        .context("failed to register client")
+}
+
+fn authorization_url(
+    authorization_server: &AuthorizationServerMetadata,
+    client: &ClientRegistration,
+    redirect_uri: &Url,
+    pkce: &Pkce,
+    state: &str,
+    resource: &str,
+) -> Url {
+    let mut url = authorization_server.authorization_endpoint.clone();
+    url.query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", &client.client_id)
+        .append_pair("redirect_uri", redirect_uri.as_str())
+        .append_pair("code_challenge", &pkce.challenge)
+        .append_pair("code_challenge_method", "S256")
+        .append_pair("state", state)
+        .append_pair("resource", resource);
+    url
+}
+
+/// Extracts the `resource_metadata` parameter of a `WWW-Authenticate` header.
+fn resource_metadata_url(www_authenticate: &str) -> Option<Url> {
+    let (_, rest) = www_authenticate.split_once("resource_metadata=")?;
+    let value = match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => rest.split([',', ' ']).next()?,
+    };
+    Url::parse(value).ok()
+}
+
+/// Builds a well-known URL by inserting `/.well-known/{suffix}` between the
+/// host and the path, as specified by RFC 8414 and RFC 9728.
+fn well_known_url(base: &Url, suffix: &str) -> Url {
+    let mut url = base.clone();
+    let path = base.path().trim_end_matches('/');
+    url.set_path(&format!("/.well-known/{suffix}{path}"));
+    url.set_query(None);
+    url.set_fragment(None);
+    url
+}
+
+fn canonical_resource_uri(endpoint: &Url) -> String {
+    let mut url = endpoint.clone();
+    url.set_query(None);
+    url.set_fragment(None);
+    url.as_str().trim_end_matches('/').to_string()
+}
+
+async fn get_json<T: DeserializeOwned>(http_client: &dyn HttpClient, url: &Url) -> Result<T> {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(url.as_str())
+        .header("Accept", "application/json")
+        .body(AsyncBody::empty())?;
+    send_json(http_client, request).await
+}
+
+async fn post_form<T: DeserializeOwned>(
+    http_client: &dyn HttpClient,
+    url: &Url,
+    params: &[(&str, &str)],
+) -> Result<T> {
+    let body = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(params)
+        .finish();
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(url.as_str())
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept", "application/json")
+        .body(AsyncBody::from(body))?;
+    send_json(http_client, request).await
+}
+
+async fn send_json<T: DeserializeOwned>(
+    http_client: &dyn HttpClient,
+    request: Request<AsyncBody>,
+) -> Result<T> {
+    let uri = request.uri().clone();
+    let mut response = http_client.send(request).await?;
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+    anyhow::ensure!(
+        response.status().is_success(),
+        "request to {uri} failed with status {}: {body}",
+        response.status()
+    );
+    serde_json::from_str(&body).with_context(|| format!("invalid response from {uri}"))
+}
+
+/// Discovery falls back through several well-known locations, so failing to
+/// fetch one of them is expected.
+fn ok_or_log_debug<T>(result: Result<T>, what: &str) -> Option<T> {
+    result
+        .inspect_err(|error| log::debug!("failed to fetch {what}: {error:#}"))
+        .ok()
+}
+
+struct Pkce {
+    verifier: String,
+    challenge: String,
+}
+
+impl Pkce {
+    fn new() -> Self {
+        let verifier = random_string();
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Self {
+            verifier,
+            challenge,
+        }
+    }
+}
+
+fn random_string() -> String {
+    URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Receives the authorization code from the browser on a loopback port.
+struct RedirectListener {
+    listener: smol::net::TcpListener,
+    redirect_uri: Url,
+}
+
+impl RedirectListener {
+    async fn bind() -> Result<Self> {
+        let listener = smol::net::TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        let redirect_uri = Url::parse(&format!("http://127.0.0.1:{port}{REDIRECT_PATH}"))?;
+        Ok(Self {
+            listener,
+            redirect_uri,
+        })
+    }
+
+    fn redirect_uri(&self) -> &Url {
+        &self.redirect_uri
+    }
+
+    async fn receive_code(self, expected_state: &str) -> Result<String> {
+        let receive = async {
+            loop {
+                let (stream, _) = self.listener.accept().await?;
+                if let Some(code) = Self::handle_connection(stream, expected_state).await? {
+                    return anyhow::Ok(code);
+                }
+            }
+        };
+        let timeout = async {
+            smol::Timer::after(REDIRECT_TIMEOUT).await;
+            Err(anyhow!("timed out waiting for authorization"))
+        };
+        smol::future::or(receive, timeout).await
+    }
+
+    async fn handle_connection(
+        mut stream: smol::net::TcpStream,
+        expected_state: &str,
+    ) -> Result<Option<String>> {
+        let mut reader = futures::io::BufReader::new(&mut stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).await?;
+        // Drain the headers so that closing the connection doesn't reset it
+        // before the browser reads the response.
+        let mut header = String::new();
+        while reader.read_line(&mut header).await? > 0 && header.trim() != "" {
+            header.clear();
+        }
+        drop(reader);
+        let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+        let url = Url::parse(&format!("http://127.0.0.1{target}"))?;
+        if url.path() != REDIRECT_PATH {
+            respond(&mut stream, StatusCode::NOT_FOUND, "Not found").await?;
+            return Ok(None);
+        }
+
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        let result = if let Some(error) = param("error") {
+            let description = param("error_description").unwrap_or_default();
+            Err(anyhow!("authorization failed: {error} {description}"))
+        } else if param("state").as_deref() != Some(expected_state) {
+            Err(anyhow!("authorization response has an unexpected state"))
+        } else {
+            param("code").context("authorization response is missing the code")
+        };
+
+        let message = if result.is_ok() {
+            "Signed in. You can close this tab and return to Zed."
+        } else {
+            "Sign in failed. You can close this tab and return to Zed."
+        };
+        respond(&mut stream, StatusCode::OK, message).await?;
+        result.map(Some)
+    }
+}
+
+async fn respond(
+    stream: &mut smol::net::TcpStream,
+    status: StatusCode,
+    message: &str,
+) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{message}",
+        message.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_client::{FakeHttpClient, Response};
+
+    #[test]
+    fn test_resource_metadata_url() {
+        assert_eq!(
+            resource_metadata_url(
+                r#"Bearer error="invalid_token", resource_metadata="https://mcp.example.com/.well-known/oauth-protected-resource""#
+            ),
+            Some(
+                Url::parse("https://mcp.example.com/.well-known/oauth-protected-resource").unwrap()
+            )
+        );
+        assert_eq!(resource_metadata_url("Bearer realm=\"mcp\""), None);
+    }
+
+    #[test]
+    fn test_well_known_url() {
+        assert_eq!(
+            well_known_url(
+                &Url::parse("https://example.com/tenant/mcp").unwrap(),
+                "oauth-protected-resource"
+            )
+            .as_str(),
+            "https://example.com/.well-known/oauth-protected-resource/tenant/mcp"
+        );
+        assert_eq!(
+            well_known_url(
+                &Url::parse("https://auth.example.com/").unwrap(),
+                "oauth-authorization-server"
+            )
+            .as_str(),
+            "https://auth.example.com/.well-known/oauth-authorization-server"
+        );
+    }
+
+    #[test]
+    fn test_pkce_challenge() {
+        let pkce = Pkce::new();
+        assert_eq!(
+            pkce.challenge,
+            URL_SAFE_NO_PAD.encode(Sha256::digest(pkce.verifier.as_bytes()))
+        );
+        assert!(pkce.verifier.len() >= 43);
+    }
+
+    /// Stands in for an authorization server that protects
+    /// `https://mcp.example.com/mcp`.
+    fn fake_authorization_server() -> Arc<dyn HttpClient> {
+        FakeHttpClient::create(|request| async move {
+            let uri = request.uri().to_string();
+            let mut body = String::new();
+            request.into_body().read_to_string(&mut body).await?;
+            let json = match uri.as_str() {
+                "https://mcp.example.com/.well-known/oauth-protected-resource/mcp" => {
+                    serde_json::json!({
+                        "resource": "https://mcp.example.com/mcp",
+                        "authorization_servers": ["https://auth.example.com"],
+                    })
+                }
+                "https://auth.example.com/.well-known/oauth-authorization-server" => {
+                    serde_json::json!({
+                        "issuer": "https://auth.example.com",
+                        "authorization_endpoint": "https://auth.example.com/authorize",
+                        "token_endpoint": "https://auth.example.com/token",
+                        "registration_endpoint": "https://auth.example.com/register",
+                        "code_challenge_methods_supported": ["S256"],
+                    })
+                }
+                "https://auth.example.com/register" => {
+                    serde_json::json!({ "client_id": "zed-client" })
+                }
+                "https://auth.example.com/token" => {
+                    let params = url::form_urlencoded::parse(body.as_bytes())
+                        .into_owned()
+                        .collect::<collections::HashMap<_, _>>();
+                    match params.get("grant_type").map(String::as_str) {
+                        Some("authorization_code") => {
+                            assert_eq!(params["code"], "the-code");
+                            assert_eq!(params["client_id"], "zed-client");
+                            assert_eq!(params["resource"], "https://mcp.example.com/mcp");
+                            assert!(params.contains_key("code_verifier"));
+                            serde_json::json!({
+                                "access_token": "access-1",
+                                "refresh_token": "refresh-1",
+                                "token_type": "Bearer",
+                                "expires_in": 3600,
+                            })
+                        }
+                        Some("refresh_token") => {
+                            assert_eq!(params["refresh_token"], "refresh-1");
+                            serde_json::json!({
+                                "access_token": "access-2",
+                                "token_type": "Bearer",
+                            })
+                        }
+                        grant_type => panic!("unexpected grant type {grant_type:?}"),
+                    }
+                }
+                _ => {
+                    return Ok(Response::builder()
+                        .status(404)
+                        .body(AsyncBody::default())
+                        .unwrap());
+                }
+            };
+            Ok(Response::builder()
+                .status(200)
+                .header("Content-Type", "application/json")
+                .body(AsyncBody::from(json.to_string()))
+                .unwrap())
+        })
+    }
+
+    #[test]
+    fn test_oauth_flow() {
+        smol::block_on(async {
+            let http_client = fake_authorization_server();
+            let endpoint = Url::parse("https://mcp.example.com/mcp").unwrap();
+            let flow = OAuthFlow::start(http_client.clone(), &endpoint, None)
+                .await
+                .unwrap();
+
+            let authorization_url = flow.authorization_url().clone();
+            assert_eq!(
+                authorization_url.path(),
+                "/authorize",
+                "unexpected authorization url {authorization_url}"
+            );
+            let params = authorization_url
+                .query_pairs()
+                .into_owned()
+                .collect::<collections::HashMap<_, _>>();
+            assert_eq!(params["client_id"], "zed-client");
+            assert_eq!(params["code_challenge_method"], "S256");
+            assert_eq!(params["resource"], "https://mcp.example.com/mcp");
+
+            // Simulate the browser being redirected back after signing in.
+            let redirect = format!(
+                "{}?code=the-code&state={}",
+                params["redirect_uri"], params["state"]
+            );
+            let browser = smol::spawn(async move {
+                let redirect = Url::parse(&redirect).unwrap();
+                let mut stream = smol::net::TcpStream::connect(format!(
+                    "127.0.0.1:{}",
+                    redirect.port().unwrap()
+                ))
+                .await
+                .unwrap();
+                stream
+                    .write_all(
+                        format!(
+                            "GET {}?{} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n",
+                            redirect.path(),
+                            redirect.query().unwrap()
+                        )
+                        .as_bytes(),
+                    )
+                    .await
+                    .unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).await.unwrap();
+                response
+            });
+
+            let session = flow.finish().await.unwrap();
+            assert!(browser.await.starts_with("HTTP/1.1 200"));
+            assert_eq!(session.resource, "https://mcp.example.com/mcp");
+            assert_eq!(session.client.client_id, "zed-client");
+            assert_eq!(session.tokens.access_token, "access-1");
+            assert_eq!(session.tokens.refresh_token.as_deref(), Some("refresh-1"));
+            assert!(session.tokens.expires_at.is_some());
+
+            let authorization = OAuthAuthorization::new(http_client);
+            authorization.set_session(Some(session));
+            assert!(authorization.refresh("access-1").await.unwrap());
+            assert_eq!(authorization.access_token().as_deref(), Some("access-2"));
+            let refreshed = authorization.refreshed_sessions().recv().await.unwrap();
+            assert_eq!(refreshed.tokens.access_token, "access-2");
+            // The refresh token is kept when the server doesn't rotate it.
+            assert_eq!(refreshed.tokens.refresh_token.as_deref(), Some("refresh-1"));
+        });
+    }
+}
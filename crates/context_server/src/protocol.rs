@@ -34,14 +34,11 @@ impl ModelContextProtocol {
     pub async fn initialize(
         self,
         client_info: types::Implementation,
+        capabilities: types::ClientCapabilities,
     ) -> Result<InitializedContextServerProtocol> {
         let params = types::InitializeParams {
             protocol_version: types::ProtocolVersion(types::LATEST_PROTOCOL_VERSION.to_string()),
-            capabilities: types::ClientCapabilities {
-                experimental: None,
-                sampling: None,
-                roots: None,
-            },
+            capabilities,
             meta: None,
             client_info,
         };
@@ -3,11 +3,12 @@ use async_trait::async_trait;
 use collections::HashMap;
 use futures::{Stream, StreamExt};
 use gpui::BackgroundExecutor;
-use http_client::{AsyncBody, HttpClient, Request, Response, http::Method};
+use http_client::{AsyncBody, HttpClient, Request, Response, StatusCode, http::Method};
 use parking_lot::Mutex as SyncMutex;
 use smol::channel;
 use std::{pin::Pin, sync::Arc};
 
+use crate::oauth::{AuthorizationRequired, OAuthAuthorization};
 use crate::transport::Transport;
 
 // Constants from MCP spec
@@ -27,6 +28,7 @@ pub struct HttpTransport {
     error_rx: channel::Receiver<String>,
     // Authentication headers to include in requests
     headers: HashMap<String, String>,
+    oauth: Option<Arc<OAuthAuthorization>>,
 }
 
 impl HttpTransport {
@@ -49,14 +51,35 @@ impl HttpTransport {
             error_tx,
             error_rx,
             headers,
+            oauth: None,
         }
     }
 
-    /// Send a message and handle the response based on content type
-    async fn send_message(&self, message: String) -> Result<()> {
-        let is_notification =
-            !message.contains("\"id\":") || message.contains("notifications/initialized");
+    /// Authorizes requests with OAuth, refreshing the access token when it
+    /// expires or the server rejects it.
+    pub fn with_oauth(mut self, oauth: Arc<OAuthAuthorization>) -> Self {
+        self.oauth = Some(oauth);
+        self
+    }
 
+    /// The OAuth authorization of requests, unless the configured headers
+    /// already authorize them.
+    fn oauth(&self) -> Option<&Arc<OAuthAuthorization>> {
+        if self
+            .headers
+            .keys()
+            .any(|key| key.eq_ignore_ascii_case("Authorization"))
+        {
+            return None;
+        }
+        self.oauth.as_ref()
+    }
+
+    fn build_request(
+        &self,
+        message: &str,
+        access_token: Option<&str>,
+    ) -> Result<Request<AsyncBody>> {
         let mut request_builder = Request::builder()
             .method(Method::POST)
             .uri(&self.endpoint)
@@ -70,13 +93,62 @@ impl HttpTransport {
             request_builder = request_builder.header(key.as_str(), value.as_str());
         }
 
+        if let Some(access_token) = access_token {
+            request_builder =
+                request_builder.header("Authorization", format!("Bearer {access_token}"));
+        }
+
         // Add session ID if we have one (except for initialize)
         if let Some(ref session_id) = *self.session_id.lock() {
             request_builder = request_builder.header(HEADER_SESSION_ID, session_id.as_str());
         }
 
-        let request = request_builder.body(AsyncBody::from(message.into_bytes()))?;
-        let mut response = self.http_client.send(request).await?;
+        Ok(request_builder.body(AsyncBody::from(message.to_string()))?)
+    }
+
+    /// Send a message and handle the response based on content type
+    async fn send_message(&self, message: String) -> Result<()> {
+        let is_notification =
+            !message.contains("\"id\":") || message.contains("notifications/initialized");
+
+        let access_token = match self.oauth() {
+            Some(oauth) => oauth.fresh_access_token().await,
+            None => None,
+        };
+        let mut response = self
+            .http_client
+            .send(self.build_request(&message, access_token.as_deref())?)
+            .await?;
+
+        if response.status() == StatusCode::UNAUTHORIZED
+            && let Some(oauth) = self.oauth.as_ref()
+        {
+            let refreshed = match access_token.as_deref() {
+                Some(access_token) => oauth.refresh(access_token).await.unwrap_or_else(|error| {
+                    log::warn!("failed to refresh context server access token: {error:#}");
+                    false
+                }),
+                None => false,
+            };
+            if refreshed {
+                let access_token = oauth.access_token();
+                response = self
+                    .http_client
+                    .send(self.build_request(&message, access_token.as_deref())?)
+                    .await?;
+            }
+            if response.status() == StatusCode::UNAUTHORIZED {
+                let challenge = AuthorizationRequired {
+                    www_authenticate: response
+                        .headers()
+                        .get("WWW-Authenticate")
+                        .and_then(|value| value.to_str().ok())
+                        .map(ToString::to_string),
+                };
+                oauth.set_authorization_required(challenge.clone());
+                return Err(challenge.into());
+            }
+        }
 
         // Handle different response types based on status and content-type
         match response.status() {
@@ -233,6 +305,7 @@ impl Drop for HttpTransport {
         let endpoint = self.endpoint.clone();
         let session_id = self.session_id.lock().clone();
         let headers = self.headers.clone();
+        let access_token = self.oauth().and_then(|oauth| oauth.access_token());
 
         if let Some(session_id) = session_id {
             self.executor
@@ -246,6 +319,10 @@ impl Drop for HttpTransport {
                     for (key, value) in headers {
                         request_builder = request_builder.header(key.as_str(), value.as_str());
                     }
+                    if let Some(access_token) = access_token {
+                        request_builder = request_builder
+                            .header("Authorization", format!("Bearer {access_token}"));
+                    }
 
                     let request = request_builder.body(AsyncBody::empty());
 
@@ -257,3 +334,121 @@ impl Drop for HttpTransport {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oauth::{
+        AuthorizationServerMetadata, ClientRegistration, OAuthSession, OAuthTokens,
+    };
+    use futures::AsyncReadExt as _;
+    use gpui::TestAppContext;
+    use http_client::FakeHttpClient;
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+    use url::Url;
+
+    #[gpui::test]
+    async fn test_refresh_expiring_access_token_once(cx: &mut TestAppContext) {
+        let token_requests = Arc::new(AtomicUsize::new(0));
+        let rejected_requests = Arc::new(AtomicUsize::new(0));
+        let http_client = FakeHttpClient::create({
+            let token_requests = token_requests.clone();
+            let rejected_requests = rejected_requests.clone();
+            move |request| {
+                let token_requests = token_requests.clone();
+                let rejected_requests = rejected_requests.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    let authorization = request
+                        .headers()
+                        .get("Authorization")
+                        .and_then(|value| value.to_str().ok())
+                        .map(ToString::to_string);
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    let (status, json) = match uri.as_str() {
+                        "https://auth.example.com/token" => {
+                            token_requests.fetch_add(1, SeqCst);
+                            // The refresh token is rotated, so it can only
+                            // be used once.
+                            if body.contains("refresh_token=refresh-1") {
+                                (
+                                    200,
+                                    serde_json::json!({
+                                        "access_token": "access-2",
+                                        "refresh_token": "refresh-2",
+                                        "expires_in": 3600,
+                                    }),
+                                )
+                            } else {
+                                (400, serde_json::json!({ "error": "invalid_grant" }))
+                            }
+                        }
+                        _ if authorization.as_deref() == Some("Bearer access-2") => (
+                            200,
+                            serde_json::json!({ "jsonrpc": "2.0", "id": 0, "result": {} }),
+                        ),
+                        _ => {
+                            rejected_requests.fetch_add(1, SeqCst);
+                            (401, serde_json::json!({}))
+                        }
+                    };
+                    Ok(Response::builder()
+                        .status(status)
+                        .header("Content-Type", JSON_MIME_TYPE)
+                        .body(AsyncBody::from(json.to_string()))
+                        .unwrap())
+                }
+            }
+        });
+
+        let oauth = Arc::new(OAuthAuthorization::new(http_client.clone()));
+        oauth.set_session(Some(OAuthSession {
+            authorization_server: AuthorizationServerMetadata {
+                authorization_endpoint: Url::parse("https://auth.example.com/authorize").unwrap(),
+                token_endpoint: Url::parse("https://auth.example.com/token").unwrap(),
+                registration_endpoint: None,
+                code_challenge_methods_supported: Vec::new(),
+            },
+            client: ClientRegistration {
+                client_id: "zed-client".into(),
+                client_secret: None,
+            },
+            resource: "https://mcp.example.com/mcp".into(),
+            tokens: OAuthTokens {
+                access_token: "access-1".into(),
+                refresh_token: Some("refresh-1".into()),
+                // Already expired.
+                expires_at: Some(0),
+            },
+        }));
+        let transport = HttpTransport::new(
+            http_client,
+            "https://mcp.example.com/mcp".into(),
+            HashMap::default(),
+            cx.executor(),
+        )
+        .with_oauth(oauth.clone());
+
+        let message = r#"{"jsonrpc":"2.0","id":0,"method":"ping"}"#;
+        let (first, second) = futures::join!(
+            transport.send(message.into()),
+            transport.send(message.into())
+        );
+        first.unwrap();
+        second.unwrap();
+
+        // Both requests were sent with the refreshed token, which was only
+        // requested once.
+        assert_eq!(token_requests.load(SeqCst), 1);
+        assert_eq!(rejected_requests.load(SeqCst), 0);
+        let tokens = oauth.session().unwrap().tokens;
+        assert_eq!(tokens.access_token, "access-2");
+        assert_eq!(tokens.refresh_token.as_deref(), Some("refresh-2"));
+        assert_eq!(
+            oauth.refreshed_sessions().recv().await.unwrap().tokens,
+            tokens
+        );
+        assert!(oauth.refreshed_sessions().try_recv().is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use editor::Editor;
+use gpui::{App, actions};
+use workspace::Workspace;
+
+pub mod csv_preview_view;
+mod csv_table;
+mod rainbow_columns;
+
+actions!(
+    csv,
+    [
+        /// Opens a table preview for the current CSV or TSV file.
+        OpenPreview,
+        /// Opens a table preview in a split pane.
+        OpenPreviewToTheSide,
+        /// Toggles coloring and aligning the columns of the current CSV or TSV file.
+        ToggleRainbowColumns,
+        /// Toggles whether the first row of the table preview is its header.
+        ToggleHeaderRow,
+        /// Selects the cell above in the table preview.
+        SelectUp,
+        /// Selects the cell below in the table preview.
+        SelectDown,
+        /// Selects the cell to the left in the table preview.
+        SelectLeft,
+        /// Selects the cell to the right in the table preview.
+        SelectRight
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, window, cx| {
+        let Some(window) = window else {
+            return;
+        };
+        crate::csv_preview_view::CsvPreviewView::register(workspace, window, cx);
+        workspace.register_action(|workspace, _: &ToggleRainbowColumns, _, cx| {
+            if let Some(editor) = workspace.active_item_as::<Editor>(cx) {
+                rainbow_columns::RainbowColumns::toggle(&editor, cx);
+            }
+        });
+    })
+    .detach();
+}
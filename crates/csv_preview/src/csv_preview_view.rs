@@ -0,0 +1,926 @@
+use std::{ops::Range, sync::Arc};
+
+use editor::{Editor, EditorEvent, actions::SelectAll};
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, App, Context, Entity, EventEmitter, FocusHandle, Focusable, IntoElement,
+    MouseButton, MouseDownEvent, ParentElement, Pixels, Render, ScrollStrategy, Styled,
+    Subscription, Task, UniformListScrollHandle, WeakEntity, Window, div, px, uniform_list,
+};
+use language::{Anchor, Buffer, BufferEvent, BufferSnapshot, OffsetRangeExt as _};
+use multi_buffer::MultiBuffer;
+use settings::Settings as _;
+use theme::ThemeSettings;
+use ui::{ContextMenu, DropdownMenu, prelude::*};
+use util::ResultExt as _;
+use workspace::item::Item;
+use workspace::{Pane, Workspace};
+
+use crate::csv_table::{CsvFormat, CsvTable, compare_values, quote_field};
+use crate::{
+    OpenPreview, OpenPreviewToTheSide, SelectDown, SelectLeft, SelectRight, SelectUp,
+    ToggleHeaderRow,
+};
+
+/// Values longer than this are cut off in the grid, but can still be edited
+/// in full.
+const MAX_COLUMN_WIDTH: usize = 48;
+const MIN_COLUMN_WIDTH: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+struct CellEditor {
+    record_ix: usize,
+    column: usize,
+    target: CellTarget,
+    editor: Entity<Editor>,
+}
+
+/// Where a cell's value is written in the buffer, anchored so that it
+/// follows edits made to the buffer after the table was parsed.
+struct CellTarget {
+    range: Range<Anchor>,
+    /// Empty fields inserted before the value, when the record is too short
+    /// to have the cell's column.
+    padding: String,
+    value: String,
+}
+
+/// A grid of the records in a CSV or TSV buffer, which can be sorted and
+/// filtered without changing the buffer, and whose cells write back to it
+/// when edited.
+pub struct CsvPreviewView {
+    focus_handle: FocusHandle,
+    buffer: Option<Entity<Buffer>>,
+    /// The buffer contents the table was parsed from, used to find the
+    /// fields' current positions when editing them.
+    snapshot: Option<BufferSnapshot>,
+    table: Arc<CsvTable>,
+    /// Whether the first record is shown as the header, if the user chose
+    /// rather than relying on detection.
+    header_row: Option<bool>,
+    sort: Option<(usize, SortOrder)>,
+    filter_editor: Entity<Editor>,
+    /// The column the filter applies to, or all of them.
+    filter_column: Option<usize>,
+    /// The indices of the records that are shown, in the order they're
+    /// shown.
+    visible_records: Vec<usize>,
+    /// The width of each column, in characters.
+    column_widths: Vec<usize>,
+    selected_cell: Option<(usize, usize)>,
+    cell_editor: Option<CellEditor>,
+    scroll_handle: UniformListScrollHandle,
+    _parse: Task<()>,
+    _buffer_subscription: Option<Subscription>,
+    _filter_subscription: Subscription,
+}
+
+impl CsvPreviewView {
+    pub fn new(
+        buffer: Entity<MultiBuffer>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        cx.new(|cx| {
+            let buffer = buffer.read(cx).as_singleton();
+            let buffer_subscription = buffer
+                .as_ref()
+                .map(|buffer| Self::create_buffer_subscription(buffer, window, cx));
+            let filter_editor = cx.new(|cx| {
+                let mut editor = Editor::single_line(window, cx);
+                editor.set_placeholder_text("Filter rows…", window, cx);
+                editor
+            });
+            let filter_subscription =
+                cx.subscribe(&filter_editor, |this: &mut Self, _, event, cx| {
+                    if let EditorEvent::BufferEdited = event {
+                        this.update_visible_records(cx);
+                    }
+                });
+
+            let mut this = Self {
+                focus_handle: cx.focus_handle(),
+                buffer,
+                snapshot: None,
+                table: Arc::default(),
+                header_row: None,
+                sort: None,
+                filter_editor,
+                filter_column: None,
+                visible_records: Vec::new(),
+                column_widths: Vec::new(),
+                selected_cell: None,
+                cell_editor: None,
+                scroll_handle: UniformListScrollHandle::new(),
+                _parse: Task::ready(()),
+                _buffer_subscription: buffer_subscription,
+                _filter_subscription: filter_subscription,
+            };
+            this.parse(window, cx);
+            this
+        })
+    }
+
+    fn create_buffer_subscription(
+        buffer: &Entity<Buffer>,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) -> Subscription {
+        cx.subscribe_in(
+            buffer,
+            window,
+            move |this, _buffer, event: &BufferEvent, window, cx| match event {
+                BufferEvent::Edited | BufferEvent::Reloaded => {
+                    this.parse(window, cx);
+                }
+                _ => {}
+            },
+        )
+    }
+
+    fn parse(&mut self, window: &Window, cx: &mut Context<Self>) {
+        let Some(buffer) = self.buffer.as_ref() else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let prefer_tabs = Self::has_extension(buffer, "tsv", cx);
+        let parse = cx.background_spawn(async move {
+            let text = snapshot.text();
+            let table = CsvTable::parse(&text, CsvFormat::detect(&text, prefer_tabs));
+            (snapshot, table)
+        });
+
+        self._parse = cx.spawn_in(window, async move |this, cx| {
+            let (snapshot, table) = parse.await;
+            this.update(cx, |this, cx| {
+                this.table = Arc::new(table);
+                this.relocate_cell_editor(&snapshot);
+                this.snapshot = Some(snapshot);
+                this.update_visible_records(cx);
+            })
+            .ok();
+        });
+    }
+
+    /// Moves the cell being edited to the record that now contains its
+    /// field, or stops editing it if the field was removed or changed.
+    fn relocate_cell_editor(&mut self, snapshot: &BufferSnapshot) {
+        let Some(cell_editor) = self.cell_editor.as_mut() else {
+            return;
+        };
+        let range = cell_editor.target.range.to_offset(snapshot);
+        let records = &self.table.records;
+        let record_ix = records.partition_point(|record| record.range.end < range.start);
+        let has_field = records.get(record_ix).is_some_and(|record| {
+            match record.fields.get(cell_editor.column) {
+                Some(field) => cell_editor.target.padding.is_empty() && field.range == range,
+                None => !cell_editor.target.padding.is_empty() && record.range.end == range.start,
+            }
+        });
+        if has_field {
+            if self.selected_cell == Some((cell_editor.record_ix, cell_editor.column)) {
+                self.selected_cell = Some((record_ix, cell_editor.column));
+            }
+            cell_editor.record_ix = record_ix;
+        } else {
+            self.cell_editor = None;
+        }
+    }
+
+    fn has_header(&self) -> bool {
+        self.header_row.unwrap_or(self.table.has_header) && !self.table.records.is_empty()
+    }
+
+    fn value(&self, record_ix: usize, column: usize) -> &str {
+        self.table
+            .records
+            .get(record_ix)
+            .and_then(|record| record.fields.get(column))
+            .map_or("", |field| field.value.as_str())
+    }
+
+    fn column_name(&self, column: usize) -> SharedString {
+        if self.has_header() {
+            let name = self.value(0, column);
+            if !name.is_empty() {
+                return name.to_string().into();
+            }
+        }
+        format!("Column {}", column + 1).into()
+    }
+
+    /// Filters and sorts the records, and measures the columns.
+    fn update_visible_records(&mut self, cx: &mut Context<Self>) {
+        let first_record = if self.has_header() { 1 } else { 0 };
+        let filter = self.filter_editor.read(cx).text(cx).to_lowercase();
+        let matches_filter = |record_ix: usize| {
+            if filter.is_empty() {
+                return true;
+            }
+            let fields = &self.table.records[record_ix].fields;
+            match self.filter_column {
+                Some(column) => fields
+                    .get(column)
+                    .is_some_and(|field| field.value.to_lowercase().contains(&filter)),
+                None => fields
+                    .iter()
+                    .any(|field| field.value.to_lowercase().contains(&filter)),
+            }
+        };
+        let mut visible_records = (first_record..self.table.records.len())
+            .filter(|&record_ix| matches_filter(record_ix))
+            .collect::<Vec<_>>();
+        if let Some((column, order)) = self.sort {
+            visible_records.sort_by(|&a, &b| {
+                let ordering = compare_values(self.value(a, column), self.value(b, column));
+                match order {
+                    SortOrder::Ascending => ordering,
+                    SortOrder::Descending => ordering.reverse(),
+                }
+            });
+        }
+        self.visible_records = visible_records;
+
+        let mut column_widths = vec![MIN_COLUMN_WIDTH; self.table.column_count];
+        for (column, width) in column_widths.iter_mut().enumerate() {
+            *width = (*width).max(self.column_name(column).chars().count() + 2);
+        }
+        for record in &self.table.records {
+            for (field, width) in record.fields.iter().zip(&mut column_widths) {
+                *width = (*width).max(field.value.chars().count().min(MAX_COLUMN_WIDTH));
+            }
+        }
+        self.column_widths = column_widths;
+
+        if self
+            .selected_cell
+            .is_some_and(|(record_ix, _)| !self.visible_records.contains(&record_ix))
+        {
+            self.selected_cell = None;
+        }
+        cx.notify();
+    }
+
+    fn toggle_sort(&mut self, column: usize, cx: &mut Context<Self>) {
+        self.sort = match self.sort {
+            Some((sorted_column, SortOrder::Ascending)) if sorted_column == column => {
+                Some((column, SortOrder::Descending))
+            }
+            Some((sorted_column, SortOrder::Descending)) if sorted_column == column => None,
+            _ => Some((column, SortOrder::Ascending)),
+        };
+        self.update_visible_records(cx);
+    }
+
+    fn set_filter_column(&mut self, column: Option<usize>, cx: &mut Context<Self>) {
+        self.filter_column = column;
+        self.update_visible_records(cx);
+    }
+
+    fn toggle_header_row(&mut self, _: &ToggleHeaderRow, _: &mut Window, cx: &mut Context<Self>) {
+        self.header_row = Some(!self.has_header());
+        self.sort = None;
+        self.selected_cell = None;
+        self.update_visible_records(cx);
+    }
+
+    fn select_cell(
+        &mut self,
+        record_ix: usize,
+        column: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.commit_cell_edit(window, cx);
+        self.selected_cell = Some((record_ix, column));
+        if let Some(row) = self.visible_records.iter().position(|&ix| ix == record_ix) {
+            self.scroll_handle
+                .scroll_to_item(row, ScrollStrategy::Nearest);
+        }
+        cx.notify();
+    }
+
+    /// Moves the selection by the given number of visible rows and columns.
+    fn move_selection(
+        &mut self,
+        rows: isize,
+        columns: isize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.visible_records.is_empty() || self.table.column_count == 0 {
+            return;
+        }
+        let (row, column) = match self.selected_cell {
+            Some((record_ix, column)) => (
+                self.visible_records
+                    .iter()
+                    .position(|&ix| ix == record_ix)
+                    .unwrap_or(0)
+                    .saturating_add_signed(rows)
+                    .min(self.visible_records.len() - 1),
+                column
+                    .saturating_add_signed(columns)
+                    .min(self.table.column_count - 1),
+            ),
+            None => (0, 0),
+        };
+        self.select_cell(self.visible_records[row], column, window, cx);
+    }
+
+    fn select_up(&mut self, _: &SelectUp, window: &mut Window, cx: &mut Context<Self>) {
+        self.move_selection(-1, 0, window, cx);
+    }
+
+    fn select_down(&mut self, _: &SelectDown, window: &mut Window, cx: &mut Context<Self>) {
+        self.move_selection(1, 0, window, cx);
+    }
+
+    fn select_left(&mut self, _: &SelectLeft, window: &mut Window, cx: &mut Context<Self>) {
+        self.move_selection(0, -1, window, cx);
+    }
+
+    fn select_right(&mut self, _: &SelectRight, window: &mut Window, cx: &mut Context<Self>) {
+        self.move_selection(0, 1, window, cx);
+    }
+
+    fn edit_cell(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((record_ix, column)) = self.selected_cell else {
+            return;
+        };
+        let Some(target) = self.cell_target(record_ix, column) else {
+            return;
+        };
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_text(target.value.clone(), window, cx);
+            editor.select_all(&SelectAll, window, cx);
+            editor
+        });
+        window.focus(&editor.focus_handle(cx));
+        self.cell_editor = Some(CellEditor {
+            record_ix,
+            column,
+            target,
+            editor,
+        });
+        cx.notify();
+    }
+
+    /// Writes the value of the cell being edited to the buffer.
+    fn commit_cell_edit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(cell_editor) = self.cell_editor.take() else {
+            return;
+        };
+        let value = cell_editor.editor.read(cx).text(cx);
+        self.write_cell(&cell_editor.target, &value, cx);
+        window.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    fn cell_target(&self, record_ix: usize, column: usize) -> Option<CellTarget> {
+        let snapshot = self.snapshot.as_ref()?;
+        let record = self.table.records.get(record_ix)?;
+        let (range, padding) = match record.fields.get(column) {
+            Some(field) => (field.range.clone(), String::new()),
+            // Short records are padded with empty fields up to the column.
+            None => (
+                record.range.end..record.range.end,
+                char::from(self.table.format.delimiter)
+                    .to_string()
+                    .repeat(column + 1 - record.fields.len()),
+            ),
+        };
+        Some(CellTarget {
+            range: snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
+            padding,
+            value: self.value(record_ix, column).to_string(),
+        })
+    }
+
+    fn write_cell(&self, target: &CellTarget, value: &str, cx: &mut Context<Self>) {
+        let Some(buffer) = self.buffer.as_ref() else {
+            return;
+        };
+        if target.padding.is_empty() && target.value == value {
+            return;
+        }
+        let text = target.padding.clone() + &quote_field(value, self.table.format);
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(target.range.clone(), text)], None, cx);
+        });
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if self.cell_editor.is_some() {
+            self.commit_cell_edit(window, cx);
+        } else if self.filter_editor.focus_handle(cx).is_focused(window) {
+            window.focus(&self.focus_handle);
+        } else {
+            self.edit_cell(window, cx);
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        if self.cell_editor.take().is_some() {
+            window.focus(&self.focus_handle);
+            cx.notify();
+        } else if self.filter_editor.focus_handle(cx).is_focused(window) {
+            window.focus(&self.focus_handle);
+        } else {
+            cx.propagate();
+        }
+    }
+
+    fn find_existing_preview_item_idx(
+        pane: &Pane,
+        buffer: &Entity<MultiBuffer>,
+        cx: &App,
+    ) -> Option<usize> {
+        let buffer = buffer.read(cx).as_singleton()?;
+        pane.items_of_type::<CsvPreviewView>()
+            .find(|view| view.read(cx).buffer.as_ref() == Some(&buffer))
+            .and_then(|view| pane.index_for_item(&view))
+    }
+
+    pub fn resolve_active_item_as_csv_buffer(
+        workspace: &Workspace,
+        cx: &mut Context<Workspace>,
+    ) -> Option<Entity<MultiBuffer>> {
+        workspace
+            .active_item(cx)?
+            .act_as::<MultiBuffer>(cx)
+            .filter(|buffer| Self::is_csv_file(buffer, cx))
+    }
+
+    fn has_extension(buffer: &Entity<Buffer>, extension: &str, cx: &App) -> bool {
+        buffer.read(cx).file().is_some_and(|file| {
+            file.path()
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+        })
+    }
+
+    pub fn is_csv_file(buffer: &Entity<MultiBuffer>, cx: &App) -> bool {
+        buffer.read(cx).as_singleton().is_some_and(|buffer| {
+            Self::has_extension(&buffer, "csv", cx) || Self::has_extension(&buffer, "tsv", cx)
+        })
+    }
+
+    pub fn register(workspace: &mut Workspace, _window: &mut Window, _cx: &mut Context<Workspace>) {
+        workspace.register_action(move |workspace, _: &OpenPreview, window, cx| {
+            if let Some(buffer) = Self::resolve_active_item_as_csv_buffer(workspace, cx) {
+                let pane = workspace.active_pane().clone();
+                if let Some(existing_view_idx) =
+                    Self::find_existing_preview_item_idx(pane.read(cx), &buffer, cx)
+                {
+                    pane.update(cx, |pane, cx| {
+                        pane.activate_item(existing_view_idx, true, true, window, cx);
+                    });
+                } else {
+                    let view = Self::new(buffer, window, cx);
+                    pane.update(cx, |pane, cx| {
+                        pane.add_item(Box::new(view), true, true, None, window, cx)
+                    });
+                }
+                cx.notify();
+            }
+        });
+
+        workspace.register_action(move |workspace, _: &OpenPreviewToTheSide, window, cx| {
+            if let Some(buffer) = Self::resolve_active_item_as_csv_buffer(workspace, cx) {
+                let pane = workspace
+                    .find_pane_in_direction(workspace::SplitDirection::Right, cx)
+                    .unwrap_or_else(|| {
+                        workspace.split_pane(
+                            workspace.active_pane().clone(),
+                            workspace::SplitDirection::Right,
+                            window,
+                            cx,
+                        )
+                    });
+                if let Some(existing_view_idx) =
+                    Self::find_existing_preview_item_idx(pane.read(cx), &buffer, cx)
+                {
+                    pane.update(cx, |pane, cx| {
+                        pane.activate_item(existing_view_idx, true, true, window, cx);
+                    });
+                } else {
+                    let view = Self::new(buffer, window, cx);
+                    pane.update(cx, |pane, cx| {
+                        pane.add_item(Box::new(view), false, false, None, window, cx)
+                    });
+                }
+                cx.notify();
+            }
+        });
+    }
+
+    fn render_filter_column_menu(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> DropdownMenu {
+        let this = cx.entity().downgrade();
+        let label = match self.filter_column {
+            Some(column) => self.column_name(column),
+            None => "All Columns".into(),
+        };
+        let column_names = (0..self.table.column_count)
+            .map(|column| self.column_name(column))
+            .collect::<Vec<_>>();
+        let menu = ContextMenu::build(window, cx, move |mut menu, _, _| {
+            let entry = |menu: ContextMenu, label: SharedString, column: Option<usize>| {
+                let this: WeakEntity<Self> = this.clone();
+                menu.entry(label, None, move |_, cx| {
+                    this.update(cx, |this, cx| this.set_filter_column(column, cx))
+                        .log_err();
+                })
+            };
+            menu = entry(menu, "All Columns".into(), None);
+            for (column, name) in column_names.into_iter().enumerate() {
+                menu = entry(menu, name, Some(column));
+            }
+            menu
+        });
+        DropdownMenu::new("csv-filter-column", label, menu).trigger_size(ButtonSize::Compact)
+    }
+
+    fn render_header(&self, widths: &[Pixels], gutter_width: Pixels, cx: &Context<Self>) -> Div {
+        let editor_background = cx.theme().colors().editor_background;
+        h_flex()
+            .flex_none()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().editor_subheader_background)
+            .child(div().flex_none().w(gutter_width))
+            .children(widths.iter().enumerate().map(|(column, &width)| {
+                let sort_icon = match self.sort {
+                    Some((sorted_column, SortOrder::Ascending)) if sorted_column == column => {
+                        Some(IconName::ArrowUp)
+                    }
+                    Some((sorted_column, SortOrder::Descending)) if sorted_column == column => {
+                        Some(IconName::ArrowDown)
+                    }
+                    _ => None,
+                };
+                let color = cx.theme().accents().color_for_index(column as u32);
+                h_flex()
+                    .id(("csv-header", column))
+                    .flex_none()
+                    .w(width)
+                    .px_2()
+                    .gap_1()
+                    .justify_between()
+                    .border_r_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .cursor_pointer()
+                    .hover(|style| style.bg(cx.theme().colors().element_hover))
+                    .on_click(cx.listener(move |this, _, _, cx| this.toggle_sort(column, cx)))
+                    .child(
+                        div()
+                            .truncate()
+                            .text_color(ui::utils::ensure_minimum_contrast(
+                                color,
+                                editor_background,
+                                55.0,
+                            ))
+                            .child(self.column_name(column)),
+                    )
+                    .when_some(sort_icon, |this, icon| {
+                        this.child(Icon::new(icon).size(IconSize::XSmall).color(Color::Muted))
+                    })
+            }))
+    }
+
+    fn render_row(
+        &self,
+        row: usize,
+        widths: &[Pixels],
+        gutter_width: Pixels,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        let record_ix = self.visible_records[row];
+        h_flex()
+            .child(
+                div()
+                    .flex_none()
+                    .w(gutter_width)
+                    .px_2()
+                    .text_color(cx.theme().colors().editor_line_number)
+                    .child((record_ix + 1).to_string()),
+            )
+            .children(
+                widths
+                    .iter()
+                    .enumerate()
+                    .map(|(column, &width)| self.render_cell(record_ix, column, width, cx)),
+            )
+            .into_any_element()
+    }
+
+    fn render_cell(
+        &self,
+        record_ix: usize,
+        column: usize,
+        width: Pixels,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        if let Some(cell_editor) = self.cell_editor.as_ref().filter(|cell_editor| {
+            cell_editor.record_ix == record_ix && cell_editor.column == column
+        }) {
+            return div()
+                .flex_none()
+                .w(width)
+                .px_2()
+                .border_1()
+                .border_color(cx.theme().colors().border_focused)
+                .child(cell_editor.editor.clone())
+                .into_any_element();
+        }
+
+        let text = self.value(record_ix, column).replace(['\r', '\n'], " ");
+        let is_selected = self.selected_cell == Some((record_ix, column));
+        div()
+            .flex_none()
+            .w(width)
+            .px_2()
+            .truncate()
+            .border_r_1()
+            .border_color(cx.theme().colors().border_variant)
+            .when(is_selected, |this| {
+                this.bg(cx.theme().colors().element_selected)
+            })
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, event: &MouseDownEvent, window, cx| {
+                    this.select_cell(record_ix, column, window, cx);
+                    if event.click_count >= 2 {
+                        this.edit_cell(window, cx);
+                    } else {
+                        window.focus(&this.focus_handle);
+                    }
+                }),
+            )
+            .child(text)
+            .into_any_element()
+    }
+}
+
+impl Render for CsvPreviewView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme_settings = ThemeSettings::get_global(cx);
+        let buffer_font = theme_settings.buffer_font.clone();
+        let buffer_font_size = theme_settings.buffer_font_size(cx);
+        let line_height = buffer_font_size * theme_settings.line_height();
+        let text_system = window.text_system();
+        let em_width = text_system
+            .em_width(text_system.resolve_font(&buffer_font), buffer_font_size)
+            .log_err()
+            .unwrap_or(buffer_font_size * 0.6);
+        // Cells are padded on both sides.
+        let cell_padding = px(16.);
+        let widths = self
+            .column_widths
+            .iter()
+            .map(|&width| em_width * width as f32 + cell_padding)
+            .collect::<Vec<_>>();
+        let gutter_width =
+            em_width * self.table.records.len().max(1).to_string().len() as f32 + cell_padding;
+        let total_width = widths
+            .iter()
+            .fold(gutter_width, |total, &width| total + width);
+
+        let status = format!(
+            "{} of {} rows · {} separated",
+            self.visible_records.len(),
+            self.table.records.len() - usize::from(self.has_header()),
+            self.table.format.delimiter_name(),
+        );
+
+        v_flex()
+            .key_context("CsvPreview")
+            .track_focus(&self.focus_handle(cx))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::select_up))
+            .on_action(cx.listener(Self::select_down))
+            .on_action(cx.listener(Self::select_left))
+            .on_action(cx.listener(Self::select_right))
+            .on_action(cx.listener(Self::toggle_header_row))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .child(
+                h_flex()
+                    .flex_none()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(self.render_filter_column_menu(window, cx))
+                    .child(
+                        h_flex()
+                            .flex_1()
+                            .h_8()
+                            .px_2()
+                            .py_1()
+                            .border_1()
+                            .border_color(cx.theme().colors().border)
+                            .rounded_md()
+                            .child(self.filter_editor.clone()),
+                    )
+                    .child(
+                        Button::new("csv-header-row", "Header Row")
+                            .style(ButtonStyle::Subtle)
+                            .toggle_state(self.has_header())
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.toggle_header_row(&ToggleHeaderRow, window, cx)
+                            })),
+                    )
+                    .child(
+                        Label::new(status)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(
+                div()
+                    .id("csv-grid")
+                    .flex_1()
+                    .overflow_x_scroll()
+                    .font(buffer_font)
+                    .text_size(buffer_font_size)
+                    .line_height(line_height)
+                    .text_color(cx.theme().colors().editor_foreground)
+                    .child(
+                        v_flex()
+                            .w(total_width)
+                            .h_full()
+                            .child(self.render_header(&widths, gutter_width, cx))
+                            .child(
+                                uniform_list(
+                                    "csv-records",
+                                    self.visible_records.len(),
+                                    cx.processor(move |this, range: Range<usize>, _window, cx| {
+                                        range
+                                            .map(|row| {
+                                                this.render_row(row, &widths, gutter_width, cx)
+                                            })
+                                            .collect()
+                                    }),
+                                )
+                                .flex_1()
+                                .track_scroll(self.scroll_handle.clone()),
+                            ),
+                    ),
+            )
+    }
+}
+
+impl Focusable for CsvPreviewView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<()> for CsvPreviewView {}
+
+impl Item for CsvPreviewView {
+    type Event = ();
+
+    fn tab_icon(&self, _window: &Window, cx: &App) -> Option<Icon> {
+        self.buffer
+            .as_ref()
+            .and_then(|buffer| buffer.read(cx).file())
+            .and_then(|file| FileIcons::get_icon(file.path().as_std_path(), cx))
+            .map(Icon::from_path)
+            .or_else(|| Some(Icon::new(IconName::FileGeneric)))
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        self.buffer
+            .as_ref()
+            .and_then(|buffer| buffer.read(cx).file())
+            .map(|file| format!("Preview {}", file.file_name(cx)).into())
+            .unwrap_or_else(|| "CSV Preview".into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("csv preview: open")
+    }
+
+    fn to_item_events(_event: &Self::Event, _f: impl FnMut(workspace::item::ItemEvent)) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::{FakeFs, Project};
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_write_cell(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            editor::init(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let buffer = cx.new(|cx| Buffer::local("name,note\nalice,hi\nbob\n", cx));
+        let multibuffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+        let view = workspace.update_in(cx, |_, window, cx| {
+            CsvPreviewView::new(multibuffer, window, cx)
+        });
+        cx.run_until_parked();
+
+        let write_cell = |view: &mut CsvPreviewView, record_ix, column, value, cx: &mut _| {
+            let target = view.cell_target(record_ix, column).unwrap();
+            view.write_cell(&target, value, cx);
+        };
+        view.update(cx, |view, cx| write_cell(view, 1, 1, "say \"hi\", bye", cx));
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "name,note\nalice,\"say \"\"hi\"\", bye\"\nbob\n"
+        );
+        cx.run_until_parked();
+
+        // Short records are padded up to the edited column.
+        view.update(cx, |view, cx| write_cell(view, 2, 1, "x", cx));
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "name,note\nalice,\"say \"\"hi\"\", bye\"\nbob,x\n"
+        );
+        cx.run_until_parked();
+
+        // The parsed value round-trips, so writing it again changes nothing.
+        view.update(cx, |view, cx| {
+            assert_eq!(view.value(1, 1), "say \"hi\", bye");
+            write_cell(view, 1, 1, "say \"hi\", bye", cx);
+        });
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "name,note\nalice,\"say \"\"hi\"\", bye\"\nbob,x\n"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_edit_cell_while_buffer_changes(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            editor::init(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let buffer = cx.new(|cx| Buffer::local("name,note\nalice,hi\nbob,yo\n", cx));
+        let multibuffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+        let view = workspace.update_in(cx, |_, window, cx| {
+            CsvPreviewView::new(multibuffer, window, cx)
+        });
+        cx.run_until_parked();
+
+        view.update_in(cx, |view, window, cx| {
+            view.selected_cell = Some((1, 1));
+            view.edit_cell(window, cx);
+        });
+        // A record is inserted above the one being edited, and the table is
+        // parsed again before the edit is committed.
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(10..10, "carol,hey\n")], None, cx)
+        });
+        cx.run_until_parked();
+
+        view.update_in(cx, |view, window, cx| {
+            let cell_editor = view.cell_editor.as_ref().unwrap();
+            assert_eq!((cell_editor.record_ix, cell_editor.column), (2, 1));
+            assert_eq!(view.selected_cell, Some((2, 1)));
+            cell_editor
+                .editor
+                .update(cx, |editor, cx| editor.set_text("bye", window, cx));
+            view.commit_cell_edit(window, cx);
+        });
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "name,note\ncarol,hey\nalice,bye\nbob,yo\n"
+        );
+
+        // Editing stops when the record being edited is removed.
+        view.update_in(cx, |view, window, cx| {
+            view.selected_cell = Some((3, 0));
+            view.edit_cell(window, cx);
+        });
+        buffer.update(cx, |buffer, cx| buffer.edit([(30..37, "")], None, cx));
+        cx.run_until_parked();
+        view.read_with(cx, |view, _| assert!(view.cell_editor.is_none()));
+    }
+}
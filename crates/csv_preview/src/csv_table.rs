@@ -0,0 +1,435 @@
+use std::{borrow::Cow, cmp::Ordering, ops::Range};
+
+/// The delimiters that are considered when detecting the format of a file,
+/// in order of preference.
+const DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+const QUOTES: [u8; 2] = [b'"', b'\''];
+
+/// How many lines or records are looked at to detect the format and the
+/// header row.
+const SAMPLE_LEN: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CsvFormat {
+    pub delimiter: u8,
+    pub quote: u8,
+}
+
+impl Default for CsvFormat {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+        }
+    }
+}
+
+impl CsvFormat {
+    /// Guesses the delimiter and quote character from the first lines of the
+    /// text, as the delimiter that occurs the same number of times on the
+    /// most lines. Ties go to tabs when `prefer_tabs` is set, as for `.tsv`
+    /// files.
+    pub fn detect(text: &str, prefer_tabs: bool) -> Self {
+        let lines = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .take(SAMPLE_LEN)
+            .collect::<Vec<_>>();
+        let quote = detect_quote(&lines);
+
+        let mut delimiters = DELIMITERS;
+        if prefer_tabs {
+            delimiters.sort_by_key(|&delimiter| delimiter != b'\t');
+        }
+        let mut best: Option<(u8, (usize, usize))> = None;
+        for delimiter in delimiters {
+            let counts = lines
+                .iter()
+                .map(|line| count_unquoted(line, delimiter, quote))
+                .collect::<Vec<_>>();
+            let Some(&first) = counts.first().filter(|&&count| count > 0) else {
+                continue;
+            };
+            let consistent_lines = counts.iter().filter(|&&count| count == first).count();
+            let score = (consistent_lines, first);
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((delimiter, score));
+            }
+        }
+
+        let delimiter = match best {
+            Some((delimiter, _)) => delimiter,
+            None if prefer_tabs => b'\t',
+            None => b',',
+        };
+        Self { delimiter, quote }
+    }
+
+    pub fn delimiter_name(&self) -> &'static str {
+        match self.delimiter {
+            b',' => "Comma",
+            b'\t' => "Tab",
+            b';' => "Semicolon",
+            b'|' => "Pipe",
+            _ => "Custom",
+        }
+    }
+}
+
+/// Picks the quote character that starts the most fields.
+fn detect_quote(lines: &[&str]) -> u8 {
+    let mut counts = [0; QUOTES.len()];
+    for line in lines {
+        let bytes = line.as_bytes();
+        for (ix, byte) in bytes.iter().enumerate() {
+            let starts_field = ix == 0 || DELIMITERS.contains(&bytes[ix - 1]);
+            if starts_field && let Some(quote_ix) = QUOTES.iter().position(|quote| quote == byte) {
+                counts[quote_ix] += 1;
+            }
+        }
+    }
+    if counts[1] > counts[0] {
+        QUOTES[1]
+    } else {
+        QUOTES[0]
+    }
+}
+
+fn count_unquoted(line: &str, delimiter: u8, quote: u8) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+    for byte in line.bytes() {
+        if byte == quote {
+            in_quotes = !in_quotes;
+        } else if byte == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvField {
+    /// Where the field is in the text, including any quotes around it.
+    pub range: Range<usize>,
+    /// The field's value, without quotes.
+    pub value: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvRecord {
+    /// Where the record is in the text, without its line ending.
+    pub range: Range<usize>,
+    pub fields: Vec<CsvField>,
+}
+
+/// The records of a CSV or TSV file, with the positions of their fields so
+/// that they can be edited in place.
+#[derive(Clone, Debug, Default)]
+pub struct CsvTable {
+    pub format: CsvFormat,
+    pub records: Vec<CsvRecord>,
+    /// Whether the first record looks like a header row.
+    pub has_header: bool,
+    pub column_count: usize,
+}
+
+impl CsvTable {
+    pub fn parse(text: &str, format: CsvFormat) -> Self {
+        let records = parse_records(text, format);
+        let has_header = detect_header(&records);
+        let column_count = records
+            .iter()
+            .map(|record| record.fields.len())
+            .max()
+            .unwrap_or(0);
+        Self {
+            format,
+            records,
+            has_header,
+            column_count,
+        }
+    }
+}
+
+fn is_field_end(bytes: &[u8], ix: usize, delimiter: u8) -> bool {
+    match bytes.get(ix) {
+        None => true,
+        Some(&byte) if byte == delimiter || byte == b'\n' => true,
+        Some(b'\r') => bytes.get(ix + 1) == Some(&b'\n'),
+        Some(_) => false,
+    }
+}
+
+fn parse_records(text: &str, format: CsvFormat) -> Vec<CsvRecord> {
+    let bytes = text.as_bytes();
+    let mut records = Vec::new();
+    let mut ix = 0;
+    while ix < bytes.len() {
+        let record_start = ix;
+        let mut fields = Vec::new();
+        loop {
+            let (field, end) = parse_field(text, ix, format);
+            fields.push(field);
+            ix = end;
+            if bytes.get(ix) == Some(&format.delimiter) {
+                ix += 1;
+            } else {
+                break;
+            }
+        }
+        let record_end = ix;
+        if bytes.get(ix) == Some(&b'\r') {
+            ix += 1;
+        }
+        if bytes.get(ix) == Some(&b'\n') {
+            ix += 1;
+        }
+
+        // Blank lines aren't records.
+        if record_end > record_start {
+            records.push(CsvRecord {
+                range: record_start..record_end,
+                fields,
+            });
+        }
+    }
+    records
+}
+
+/// Parses the field starting at `start`, returning it and the offset of the
+/// delimiter or line ending after it.
+fn parse_field(text: &str, start: usize, format: CsvFormat) -> (CsvField, usize) {
+    let bytes = text.as_bytes();
+    let mut ix = start;
+    if bytes.get(ix) != Some(&format.quote) {
+        while !is_field_end(bytes, ix, format.delimiter) {
+            ix += 1;
+        }
+        let field = CsvField {
+            range: start..ix,
+            value: text[start..ix].to_string(),
+        };
+        return (field, ix);
+    }
+
+    // Delimiters and quotes are ASCII, so slicing at them keeps the value
+    // valid UTF-8.
+    let mut value = Vec::new();
+    ix += 1;
+    while let Some(&byte) = bytes.get(ix) {
+        ix += 1;
+        if byte != format.quote {
+            value.push(byte);
+        } else if bytes.get(ix) == Some(&format.quote) {
+            value.push(byte);
+            ix += 1;
+        } else {
+            break;
+        }
+    }
+    // Anything between the closing quote and the delimiter is kept as is.
+    while !is_field_end(bytes, ix, format.delimiter) {
+        value.push(bytes[ix]);
+        ix += 1;
+    }
+    let field = CsvField {
+        range: start..ix,
+        value: String::from_utf8_lossy(&value).into_owned(),
+    };
+    (field, ix)
+}
+
+fn is_number(value: &str) -> bool {
+    value.trim().parse::<f64>().is_ok()
+}
+
+/// Guesses whether the first record is a header, by checking whether its
+/// fields differ in kind from the values below them: text above numbers, or
+/// a different length above values that all have the same length.
+fn detect_header(records: &[CsvRecord]) -> bool {
+    let Some((header, rows)) = records.split_first() else {
+        return false;
+    };
+    let rows = &rows[..rows.len().min(SAMPLE_LEN)];
+    if rows.is_empty() {
+        return false;
+    }
+
+    let mut votes = 0;
+    for (column, header_field) in header.fields.iter().enumerate() {
+        let values = rows
+            .iter()
+            .filter_map(|row| row.fields.get(column))
+            .map(|field| field.value.as_str())
+            .filter(|value| !value.is_empty())
+            .collect::<Vec<_>>();
+        let Some(first_value) = values.first() else {
+            continue;
+        };
+        if values.iter().all(|value| is_number(value)) {
+            votes += if is_number(&header_field.value) {
+                -1
+            } else {
+                1
+            };
+        } else {
+            let len = first_value.chars().count();
+            if values.iter().all(|value| value.chars().count() == len) {
+                votes += if header_field.value.chars().count() == len {
+                    -1
+                } else {
+                    1
+                };
+            }
+        }
+    }
+    if votes != 0 {
+        return votes > 0;
+    }
+
+    // When the columns don't tell either way, distinct names are a header.
+    let mut names = header
+        .fields
+        .iter()
+        .map(|field| field.value.trim())
+        .collect::<Vec<_>>();
+    if names.iter().any(|name| name.is_empty() || is_number(name)) {
+        return false;
+    }
+    names.sort_unstable();
+    names.windows(2).all(|pair| pair[0] != pair[1])
+}
+
+/// Quotes a value if it contains the delimiter, the quote character or a
+/// line break, doubling any quotes in it.
+pub fn quote_field(value: &str, format: CsvFormat) -> Cow<'_, str> {
+    let needs_quotes = value.bytes().any(|byte| {
+        byte == format.delimiter || byte == format.quote || byte == b'\n' || byte == b'\r'
+    });
+    if !needs_quotes {
+        return Cow::Borrowed(value);
+    }
+    let quote = char::from(format.quote);
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push(quote);
+    for character in value.chars() {
+        if character == quote {
+            quoted.push(quote);
+        }
+        quoted.push(character);
+    }
+    quoted.push(quote);
+    Cow::Owned(quoted)
+}
+
+/// Orders values numerically when both are numbers, putting numbers before
+/// text, and case-insensitively otherwise.
+pub fn compare_values(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a
+            .to_lowercase()
+            .cmp(&b.to_lowercase())
+            .then_with(|| a.cmp(b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(table: &CsvTable) -> Vec<Vec<&str>> {
+        table
+            .records
+            .iter()
+            .map(|record| {
+                record
+                    .fields
+                    .iter()
+                    .map(|field| field.value.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(
+            CsvFormat::detect("a,b,c\n1,2,3\n", false),
+            CsvFormat {
+                delimiter: b',',
+                quote: b'"'
+            }
+        );
+        assert_eq!(CsvFormat::detect("a\tb\n1\t2\n", false).delimiter, b'\t');
+        assert_eq!(CsvFormat::detect("a;b;c\n1,5;2;3\n", false).delimiter, b';');
+        assert_eq!(CsvFormat::detect("'a,b',c\n'd',e\n", false).quote, b'\'');
+        // Commas inside quotes don't count.
+        assert_eq!(
+            CsvFormat::detect("name|note\nx|\"a, b, c\"\n", false).delimiter,
+            b'|'
+        );
+        assert_eq!(CsvFormat::detect("single column\n", true).delimiter, b'\t');
+        assert_eq!(CsvFormat::detect("single column\n", false).delimiter, b',');
+    }
+
+    #[test]
+    fn test_parse() {
+        let text = "name,note\r\n\"Doe, Jane\",\"said \"\"hi\"\"\"\n\nx,\"two\nlines\"\nshort";
+        let table = CsvTable::parse(text, CsvFormat::default());
+        assert_eq!(
+            values(&table),
+            vec![
+                vec!["name", "note"],
+                vec!["Doe, Jane", "said \"hi\""],
+                vec!["x", "two\nlines"],
+                vec!["short"],
+            ]
+        );
+        assert_eq!(table.column_count, 2);
+        assert_eq!(&text[table.records[0].range.clone()], "name,note");
+        assert_eq!(
+            &text[table.records[1].fields[0].range.clone()],
+            "\"Doe, Jane\""
+        );
+        assert_eq!(&text[table.records[2].range.clone()], "x,\"two\nlines\"");
+
+        let table = CsvTable::parse("a,,\n", CsvFormat::default());
+        assert_eq!(values(&table), vec![vec!["a", "", ""]]);
+        assert_eq!(table.records[0].fields[2].range, 3..3);
+    }
+
+    #[test]
+    fn test_detect_header() {
+        let format = CsvFormat::default();
+        assert!(CsvTable::parse("id,price\n1,2.5\n2,3\n", format).has_header);
+        assert!(!CsvTable::parse("1,2.5\n2,3\n", format).has_header);
+        assert!(CsvTable::parse("code\nAB\nCD\n", format).has_header);
+        assert!(CsvTable::parse("name,city\nJane,Paris\nJo,Rome\n", format).has_header);
+        assert!(!CsvTable::parse("a,a\nJane,Paris\nJo,Rome\n", format).has_header);
+        assert!(!CsvTable::parse("name,city\n", format).has_header);
+    }
+
+    #[test]
+    fn test_quote_field() {
+        let format = CsvFormat::default();
+        assert_eq!(quote_field("plain", format), "plain");
+        assert_eq!(quote_field("a,b", format), "\"a,b\"");
+        assert_eq!(quote_field("say \"hi\"", format), "\"say \"\"hi\"\"\"");
+        assert_eq!(quote_field("two\nlines", format), "\"two\nlines\"");
+
+        let value = "x, \"y\"\nz";
+        let table = CsvTable::parse(&quote_field(value, format), format);
+        assert_eq!(values(&table), vec![vec![value]]);
+    }
+
+    #[test]
+    fn test_compare_values() {
+        let mut values = vec!["10", "b", "2", "A", "", "1.5"];
+        values.sort_by(|a, b| compare_values(a, b));
+        assert_eq!(values, vec!["1.5", "2", "10", "", "A", "b"]);
+    }
+}
@@ -0,0 +1,192 @@
+use std::{any::Any, ops::Range, time::Duration};
+
+use editor::{Addon, Anchor, Editor, EditorEvent, Inlay};
+use gpui::{App, AppContext as _, Context, Entity, HighlightStyle, Subscription, Task, WeakEntity};
+use project::InlayId;
+use ui::{ActiveTheme as _, utils::ensure_minimum_contrast};
+
+use crate::csv_table::{CsvFormat, CsvTable};
+
+/// Files with more records than this are only colored and aligned up to it,
+/// to keep the number of inlays reasonable.
+const MAX_RECORDS: usize = 10_000;
+/// Fields wider than this don't push their column wider.
+const MAX_COLUMN_WIDTH: usize = 40;
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(50);
+
+struct RainbowColumnsHighlight;
+
+/// Colors each column of a CSV or TSV file shown in an editor, and pads its
+/// fields with inlays so that the columns line up.
+pub(crate) struct RainbowColumns {
+    editor: WeakEntity<Editor>,
+    inlays: Vec<InlayId>,
+    next_inlay_id: usize,
+    refresh: Task<()>,
+    _subscription: Subscription,
+}
+
+struct RainbowColumnsAddon(Entity<RainbowColumns>);
+
+impl Addon for RainbowColumnsAddon {
+    fn to_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+struct ColumnLayout {
+    /// The ranges of the fields in each column, by column.
+    fields_by_column: Vec<Vec<Range<Anchor>>>,
+    /// Where to insert padding, and how many spaces.
+    padding: Vec<(Anchor, usize)>,
+}
+
+impl RainbowColumns {
+    pub(crate) fn toggle(editor: &Entity<Editor>, cx: &mut App) {
+        let existing = editor
+            .read(cx)
+            .addon::<RainbowColumnsAddon>()
+            .map(|addon| addon.0.clone());
+        match existing {
+            Some(rainbow_columns) => {
+                let inlays = rainbow_columns.update(cx, |this, _| {
+                    this.refresh = Task::ready(());
+                    std::mem::take(&mut this.inlays)
+                });
+                editor.update(cx, |editor, cx| {
+                    editor.splice_inlays(&inlays, Vec::new(), cx);
+                    editor.clear_highlights::<RainbowColumnsHighlight>(cx);
+                    editor.unregister_addon::<RainbowColumnsAddon>();
+                });
+            }
+            None => {
+                let rainbow_columns = cx.new(|cx| {
+                    let mut this = Self {
+                        editor: editor.downgrade(),
+                        inlays: Vec::new(),
+                        next_inlay_id: 0,
+                        refresh: Task::ready(()),
+                        _subscription: cx.subscribe(editor, |this, _, event, cx| {
+                            if let EditorEvent::BufferEdited = event {
+                                this.schedule_refresh(REFRESH_DEBOUNCE, cx);
+                            }
+                        }),
+                    };
+                    this.schedule_refresh(Duration::ZERO, cx);
+                    this
+                });
+                editor.update(cx, |editor, _| {
+                    editor.register_addon(RainbowColumnsAddon(rainbow_columns))
+                });
+            }
+        }
+    }
+
+    fn schedule_refresh(&mut self, delay: Duration, cx: &mut Context<Self>) {
+        let Some(editor) = self.editor.upgrade() else {
+            return;
+        };
+        let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+        let prefer_tabs = snapshot.as_singleton().is_some_and(|(_, _, buffer)| {
+            buffer.file().is_some_and(|file| {
+                file.path()
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case("tsv"))
+            })
+        });
+
+        self.refresh = cx.spawn(async move |this, cx| {
+            if !delay.is_zero() {
+                cx.background_executor().timer(delay).await;
+            }
+            let layout = cx
+                .background_spawn(async move {
+                    let (&excerpt_id, buffer_id, buffer) = snapshot.as_singleton()?;
+                    let text = buffer.text();
+                    let table = CsvTable::parse(&text, CsvFormat::detect(&text, prefer_tabs));
+                    let records = &table.records[..table.records.len().min(MAX_RECORDS)];
+                    let anchor = |offset: usize, bias_right: bool| {
+                        let text_anchor = if bias_right {
+                            buffer.anchor_after(offset)
+                        } else {
+                            buffer.anchor_before(offset)
+                        };
+                        Anchor::in_buffer(excerpt_id, buffer_id, text_anchor)
+                    };
+
+                    let mut widths = vec![0; table.column_count];
+                    for record in records {
+                        for (field, width) in record.fields.iter().zip(&mut widths) {
+                            let len = text[field.range.clone()].chars().count();
+                            *width = (*width).max(len.min(MAX_COLUMN_WIDTH));
+                        }
+                    }
+
+                    let mut layout = ColumnLayout {
+                        fields_by_column: vec![Vec::new(); table.column_count],
+                        padding: Vec::new(),
+                    };
+                    for record in records {
+                        // Fields spanning lines can't be lined up.
+                        let is_multiline = text[record.range.clone()].contains('\n');
+                        let last_column = record.fields.len() - 1;
+                        for (column, field) in record.fields.iter().enumerate() {
+                            if field.range.is_empty() {
+                                continue;
+                            }
+                            layout.fields_by_column[column].push(
+                                anchor(field.range.start, false)..anchor(field.range.end, true),
+                            );
+                            let len = text[field.range.clone()].chars().count();
+                            if !is_multiline && column < last_column && len < widths[column] {
+                                layout
+                                    .padding
+                                    .push((anchor(field.range.end, true), widths[column] - len));
+                            }
+                        }
+                    }
+                    Some(layout)
+                })
+                .await;
+
+            this.update(cx, |this, cx| {
+                let Some(editor) = this.editor.upgrade() else {
+                    return;
+                };
+                let layout = layout.unwrap_or(ColumnLayout {
+                    fields_by_column: Vec::new(),
+                    padding: Vec::new(),
+                });
+                let to_insert = layout
+                    .padding
+                    .into_iter()
+                    .map(|(position, width)| {
+                        this.next_inlay_id += 1;
+                        Inlay::padding(this.next_inlay_id, position, width)
+                    })
+                    .collect::<Vec<_>>();
+                let to_remove = std::mem::replace(
+                    &mut this.inlays,
+                    to_insert.iter().map(|inlay| inlay.id).collect(),
+                );
+
+                editor.update(cx, |editor, cx| {
+                    editor.splice_inlays(&to_remove, to_insert, cx);
+                    editor.clear_highlights::<RainbowColumnsHighlight>(cx);
+                    let editor_background = cx.theme().colors().editor_background;
+                    for (column, ranges) in layout.fields_by_column.into_iter().enumerate() {
+                        let color = cx.theme().accents().color_for_index(column as u32);
+                        let style = HighlightStyle {
+                            color: Some(ensure_minimum_contrast(color, editor_background, 55.0)),
+                            ..HighlightStyle::default()
+                        };
+                        editor.highlight_text_key::<RainbowColumnsHighlight>(
+                            column, ranges, style, false, cx,
+                        );
+                    }
+                });
+            })
+            .ok();
+        });
+    }
+}
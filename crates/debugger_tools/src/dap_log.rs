@@ -1071,6 +1071,7 @@ impl SearchableItem for DapLogView {
             case: true,
             word: true,
             regex: true,
+            structural: false,
             find_in_results: true,
             // DAP log is read-only.
             replacement: false,
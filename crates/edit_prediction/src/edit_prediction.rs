@@ -1,9 +1,13 @@
+mod fim_completion;
+
 use std::{ops::Range, sync::Arc};
 
 use client::EditPredictionUsage;
 use gpui::{App, Context, Entity, SharedString};
 use language::{Anchor, Buffer, BufferSnapshot, OffsetRangeExt};
 
+pub use fim_completion::FimCompletionState;
+
 // TODO: Find a better home for `Direction`.
 //
 // This should live in an ancestor crate of `editor` and `edit_prediction`,
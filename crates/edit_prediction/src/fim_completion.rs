@@ -0,0 +1,139 @@
+use std::{future::Future, ops::Range, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use gpui::{App, AsyncApp, Context, Entity, Task};
+use language::{Anchor, Buffer, BufferSnapshot, EditPreview};
+
+use crate::{EditPrediction, EditPredictionProvider, interpolate_edits};
+
+/// A completion that has been received from the model, kept so that it can be
+/// interpolated as the user types.
+#[derive(Clone)]
+struct CurrentCompletion {
+    /// The buffer snapshot at the time the completion was generated.
+    snapshot: BufferSnapshot,
+    /// The edits that transform the original text into the predicted text.
+    edits: Arc<[(Range<Anchor>, Arc<str>)]>,
+    /// Preview of how the buffer will look after applying the edits.
+    edit_preview: EditPreview,
+}
+
+impl CurrentCompletion {
+    /// Adjusts the edits for changes made to the buffer since the completion
+    /// was generated. Returns `None` if the user's edits conflict with them.
+    fn interpolate(&self, new_snapshot: &BufferSnapshot) -> Option<Vec<(Range<Anchor>, Arc<str>)>> {
+        interpolate_edits(&self.snapshot, new_snapshot, &self.edits)
+    }
+}
+
+/// The pending request and current completion of a provider backed by a
+/// fill-in-the-middle model, which predicts a single insertion at the cursor.
+#[derive(Default)]
+pub struct FimCompletionState {
+    pending_request: Option<Task<Result<()>>>,
+    current_completion: Option<CurrentCompletion>,
+}
+
+impl FimCompletionState {
+    pub fn is_refreshing(&self) -> bool {
+        self.pending_request.is_some()
+    }
+
+    /// Requests a new completion with `fetch`, unless the current one still
+    /// applies to the buffer.
+    ///
+    /// `state` returns this state from the provider, so that it can be
+    /// updated once the request completes.
+    pub fn refresh<T: EditPredictionProvider, F>(
+        &mut self,
+        buffer: Entity<Buffer>,
+        cursor_position: Anchor,
+        debounce: Option<Duration>,
+        state: fn(&mut T) -> &mut Self,
+        fetch: impl FnOnce(BufferSnapshot, AsyncApp) -> F + 'static,
+        cx: &mut Context<T>,
+    ) where
+        F: Future<Output = Result<String>> + 'static,
+    {
+        let snapshot = buffer.read(cx).snapshot();
+
+        if let Some(current_completion) = self.current_completion.as_ref()
+            && current_completion.interpolate(&snapshot).is_some()
+        {
+            return;
+        }
+
+        // Replacing the pending task drops the previous one, which cancels its
+        // debounce timer or in-flight request.
+        self.pending_request = Some(cx.spawn(async move |this, cx| {
+            if let Some(debounce) = debounce {
+                log::debug!("{}: Debouncing for {:?}", T::display_name(), debounce);
+                cx.background_executor().timer(debounce).await;
+            }
+
+            let completion_text = match fetch(snapshot.clone(), cx.clone()).await {
+                Ok(completion) => completion,
+                Err(error) => {
+                    log::error!("{}: Failed to fetch completion: {error}", T::display_name());
+                    this.update(cx, |this, cx| {
+                        state(this).pending_request = None;
+                        cx.notify();
+                    })?;
+                    return Err(error);
+                }
+            };
+
+            if completion_text.trim().is_empty() {
+                log::debug!(
+                    "{}: Completion was empty after trimming; ignoring",
+                    T::display_name()
+                );
+                this.update(cx, |this, cx| {
+                    state(this).pending_request = None;
+                    cx.notify();
+                })?;
+                return Ok(());
+            }
+
+            let edits: Arc<[(Range<Anchor>, Arc<str>)]> =
+                vec![(cursor_position..cursor_position, completion_text.into())].into();
+            let edit_preview = buffer
+                .read_with(cx, |buffer, cx| buffer.preview_edits(edits.clone(), cx))?
+                .await;
+
+            this.update(cx, |this, cx| {
+                let state = state(this);
+                state.current_completion = Some(CurrentCompletion {
+                    snapshot,
+                    edits,
+                    edit_preview,
+                });
+                state.pending_request = None;
+                cx.notify();
+            })?;
+
+            Ok(())
+        }));
+    }
+
+    /// Drops the current completion and cancels any pending request.
+    pub fn clear(&mut self) {
+        self.pending_request = None;
+        self.current_completion = None;
+    }
+
+    /// Returns the current completion, adjusted or invalidated based on user
+    /// edits.
+    pub fn suggest(&self, buffer: &Entity<Buffer>, cx: &App) -> Option<EditPrediction> {
+        let current_completion = self.current_completion.as_ref()?;
+        let edits = current_completion.interpolate(&buffer.read(cx).snapshot())?;
+        if edits.is_empty() {
+            return None;
+        }
+        Some(EditPrediction::Local {
+            id: None,
+            edits,
+            edit_preview: Some(current_completion.edit_preview.clone()),
+        })
+    }
+}
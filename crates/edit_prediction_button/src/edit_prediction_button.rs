@@ -300,6 +300,31 @@ impl Render for EditPredictionButton {
                         .with_handle(self.popover_menu_handle.clone()),
                 )
             }
+            EditPredictionProvider::Local => {
+                let enabled = self.editor_enabled.unwrap_or(true);
+                let this = cx.weak_entity();
+
+                div().child(
+                    PopoverMenu::new("local-edit-prediction")
+                        .menu(move |window, cx| {
+                            this.update(cx, |this, cx| this.build_local_context_menu(window, cx))
+                                .ok()
+                        })
+                        .anchor(Corner::BottomRight)
+                        .trigger_with_tooltip(
+                            IconButton::new("local-edit-prediction-icon", IconName::AiOllama)
+                                .shape(IconButtonShape::Square)
+                                .when(!enabled, |this| {
+                                    this.indicator(Indicator::dot().color(Color::Ignored))
+                                        .indicator_border_color(Some(
+                                            cx.theme().colors().status_bar_background,
+                                        ))
+                                }),
+                            move |_window, cx| Tooltip::for_action("Local Model", &ToggleMenu, cx),
+                        )
+                        .with_handle(self.popover_menu_handle.clone()),
+                )
+            }
             provider @ (EditPredictionProvider::Experimental(
                 EXPERIMENTAL_SWEEP_EDIT_PREDICTION_PROVIDER_NAME,
             )
@@ -571,7 +596,9 @@ impl EditPredictionButton {
 
                         menu.item(entry)
                     }
-                    EditPredictionProvider::None | EditPredictionProvider::Experimental(_) => {
+                    EditPredictionProvider::None
+                    | EditPredictionProvider::Local
+                    | EditPredictionProvider::Experimental(_) => {
                         continue;
                     }
                 };
@@ -685,6 +712,7 @@ impl EditPredictionButton {
                 | EditPredictionProvider::Copilot
                 | EditPredictionProvider::Supermaven
                 | EditPredictionProvider::Codestral
+                | EditPredictionProvider::Local
         ) {
             menu = menu
                 .separator()
@@ -941,6 +969,17 @@ impl EditPredictionButton {
         })
     }
 
+    fn build_local_context_menu(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<ContextMenu> {
+        ContextMenu::build(window, cx, |menu, window, cx| {
+            let menu = self.build_language_settings_menu(menu, window, cx);
+            self.add_provider_switching_section(menu, EditPredictionProvider::Local, cx)
+        })
+    }
+
     fn build_zeta_context_menu(
         &self,
         provider: EditPredictionProvider,
@@ -0,0 +1,730 @@
+//! Bookmarks are lines that the user has marked to come back to, optionally
+//! with a label. They're kept per workspace, anchored in the buffers that are
+//! open so that they follow edits, and stored by line for the files that
+//! aren't.
+
+use std::{ops::Range, path::Path, sync::Arc};
+
+use collections::{BTreeMap, HashMap};
+use gpui::{
+    App, AppContext as _, ClickEvent, Context, Entity, EntityId, Global, SharedString,
+    Subscription, WeakEntity, Window,
+};
+use language::{Buffer, BufferEvent, Point, ToPoint as _};
+use multi_buffer::{Anchor, ToPoint as _};
+use project::{Project, buffer_store::BufferStoreEvent};
+use text::BufferId;
+use ui::{ButtonStyle, IconButton, IconName, IconSize, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{Workspace, WorkspaceId};
+
+use crate::{
+    Autoscroll, Direction, DisplayPoint, DisplayRow, Editor, EditorSnapshot, GoToNextBookmark,
+    GoToPreviousBookmark, SelectionEffects, ToggleBookmark, display_map::ToDisplayPoint as _,
+    persistence::DB,
+};
+
+#[derive(Clone, Debug)]
+pub struct Bookmark {
+    pub position: text::Anchor,
+    pub label: Option<SharedString>,
+}
+
+/// A bookmark as it's stored, by the line it was on when last serialized.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SerializedBookmark {
+    pub line: u32,
+    pub label: Option<SharedString>,
+}
+
+struct BufferBookmarks {
+    abs_path: Arc<Path>,
+    bookmarks: Vec<Bookmark>,
+    _subscriptions: [Subscription; 2],
+}
+
+#[derive(Default)]
+struct GlobalBookmarkStores(HashMap<EntityId, Entity<BookmarkStore>>);
+
+impl Global for GlobalBookmarkStores {}
+
+pub struct BookmarkStore {
+    workspace_id: Option<WorkspaceId>,
+    project: WeakEntity<Project>,
+    buffers: HashMap<BufferId, BufferBookmarks>,
+    serialized: BTreeMap<Arc<Path>, Vec<SerializedBookmark>>,
+    _subscription: Subscription,
+}
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, cx| {
+        let store = BookmarkStore::new(workspace, cx);
+        let workspace_id = cx.entity_id();
+        cx.default_global::<GlobalBookmarkStores>()
+            .0
+            .insert(workspace_id, store);
+        cx.on_release(move |_, cx| {
+            cx.default_global::<GlobalBookmarkStores>()
+                .0
+                .remove(&workspace_id);
+        })
+        .detach();
+    })
+    .detach();
+}
+
+impl BookmarkStore {
+    fn new(workspace: &Workspace, cx: &mut App) -> Entity<Self> {
+        let project = workspace.project().clone();
+        cx.new(|cx| {
+            let buffer_store = project.read(cx).buffer_store().clone();
+            let subscription = cx.subscribe(&buffer_store, |this, _, event, cx| {
+                if let BufferStoreEvent::BufferAdded(buffer) = event {
+                    this.buffer_opened(buffer, cx);
+                }
+            });
+            let mut this = Self {
+                workspace_id: workspace.database_id(),
+                project: project.downgrade(),
+                buffers: HashMap::default(),
+                serialized: BTreeMap::default(),
+                _subscription: subscription,
+            };
+            this.load(cx);
+            this
+        })
+    }
+
+    /// Returns the bookmarks of the given workspace.
+    pub fn for_workspace(workspace: &Entity<Workspace>, cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalBookmarkStores>()?
+            .0
+            .get(&workspace.entity_id())
+            .cloned()
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        let Some(workspace_id) = self.workspace_id else {
+            return;
+        };
+        cx.spawn(async move |this, cx| {
+            let rows = cx
+                .background_spawn(async move { DB.get_bookmarks(workspace_id) })
+                .await
+                .log_err()?;
+            this.update(cx, |this, cx| {
+                for (abs_path, line, label) in rows {
+                    // Bookmarks made before these were loaded take precedence.
+                    if this.buffers.values().any(|b| b.abs_path == abs_path) {
+                        continue;
+                    }
+                    this.serialized
+                        .entry(abs_path)
+                        .or_default()
+                        .push(SerializedBookmark {
+                            line,
+                            label: label.map(SharedString::from),
+                        });
+                }
+                if let Some(project) = this.project.upgrade() {
+                    let buffers = project
+                        .read(cx)
+                        .buffer_store()
+                        .read(cx)
+                        .buffers()
+                        .collect::<Vec<_>>();
+                    for buffer in buffers {
+                        this.buffer_opened(&buffer, cx);
+                    }
+                }
+                cx.notify();
+            })
+            .ok()
+        })
+        .detach();
+    }
+
+    fn abs_path(&self, buffer: &Entity<Buffer>, cx: &App) -> Option<Arc<Path>> {
+        let project_path = buffer.read(cx).project_path(cx)?;
+        let abs_path = self
+            .project
+            .upgrade()?
+            .read(cx)
+            .absolute_path(&project_path, cx)?;
+        Some(abs_path.into())
+    }
+
+    fn buffer_opened(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let buffer_id = buffer.read(cx).remote_id();
+        if self.buffers.contains_key(&buffer_id) {
+            return;
+        }
+        let Some(abs_path) = self.abs_path(buffer, cx) else {
+            return;
+        };
+        let Some(serialized) = self.serialized.get(&abs_path) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let bookmarks = serialized
+            .iter()
+            .map(|bookmark| Bookmark {
+                position: snapshot.anchor_after(
+                    snapshot.clip_point(Point::new(bookmark.line, 0), text::Bias::Left),
+                ),
+                label: bookmark.label.clone(),
+            })
+            .collect();
+        self.watch_buffer(buffer, abs_path, bookmarks, cx);
+    }
+
+    fn watch_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        abs_path: Arc<Path>,
+        bookmarks: Vec<Bookmark>,
+        cx: &mut Context<Self>,
+    ) {
+        let buffer_id = buffer.read(cx).remote_id();
+        let subscriptions = [
+            cx.subscribe(buffer, |this, buffer, event, cx| {
+                if let BufferEvent::Edited = event {
+                    this.serialize_buffer(&buffer, cx);
+                }
+            }),
+            cx.observe_release(buffer, move |this, _, _| {
+                this.buffers.remove(&buffer_id);
+            }),
+        ];
+        self.buffers.insert(
+            buffer_id,
+            BufferBookmarks {
+                abs_path,
+                bookmarks,
+                _subscriptions: subscriptions,
+            },
+        );
+    }
+
+    /// Stores the lines that the bookmarks in the buffer are on, if they
+    /// changed.
+    fn serialize_buffer(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let snapshot = buffer.read(cx).snapshot();
+        let Some(buffer_bookmarks) = self.buffers.get_mut(&snapshot.remote_id()) else {
+            return;
+        };
+        // Edits can join the lines of two bookmarks together.
+        buffer_bookmarks.bookmarks.dedup_by(|a, b| {
+            a.position.to_point(&snapshot).row == b.position.to_point(&snapshot).row
+        });
+        let serialized = buffer_bookmarks
+            .bookmarks
+            .iter()
+            .map(|bookmark| SerializedBookmark {
+                line: bookmark.position.to_point(&snapshot).row,
+                label: bookmark.label.clone(),
+            })
+            .collect::<Vec<_>>();
+        let abs_path = buffer_bookmarks.abs_path.clone();
+        if self
+            .serialized
+            .get(&abs_path)
+            .map_or(&[][..], Vec::as_slice)
+            == serialized
+        {
+            return;
+        }
+
+        if let Some(workspace_id) = self.workspace_id {
+            let rows = serialized
+                .iter()
+                .map(|bookmark| {
+                    (
+                        bookmark.line,
+                        bookmark.label.as_ref().map(ToString::to_string),
+                    )
+                })
+                .collect();
+            let abs_path = abs_path.clone();
+            cx.background_spawn(
+                async move { DB.save_bookmarks(workspace_id, abs_path, rows).await },
+            )
+            .detach_and_log_err(cx);
+        }
+        if serialized.is_empty() {
+            self.serialized.remove(&abs_path);
+        } else {
+            self.serialized.insert(abs_path, serialized);
+        }
+        cx.notify();
+    }
+
+    fn bookmarks_mut(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Option<&mut Vec<Bookmark>> {
+        let buffer_id = buffer.read(cx).remote_id();
+        if !self.buffers.contains_key(&buffer_id) {
+            let abs_path = self.abs_path(buffer, cx)?;
+            self.watch_buffer(buffer, abs_path, Vec::new(), cx);
+        }
+        self.buffers
+            .get_mut(&buffer_id)
+            .map(|buffer_bookmarks| &mut buffer_bookmarks.bookmarks)
+    }
+
+    /// Adds a bookmark on the given line of the buffer, or removes the one
+    /// that's already there.
+    pub fn toggle(&mut self, buffer: &Entity<Buffer>, line: u32, cx: &mut Context<Self>) {
+        let snapshot = buffer.read(cx).snapshot();
+        let Some(bookmarks) = self.bookmarks_mut(buffer, cx) else {
+            return;
+        };
+        match bookmark_ix_for_line(bookmarks, line, &snapshot) {
+            Ok(ix) => {
+                bookmarks.remove(ix);
+            }
+            Err(ix) => bookmarks.insert(
+                ix,
+                Bookmark {
+                    position: snapshot.anchor_after(Point::new(line, 0)),
+                    label: None,
+                },
+            ),
+        }
+        self.serialize_buffer(buffer, cx);
+    }
+
+    /// Sets the label of the bookmark on the given line of the buffer, adding
+    /// a bookmark there if there isn't one.
+    pub fn set_label(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        line: u32,
+        label: Option<SharedString>,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = buffer.read(cx).snapshot();
+        let Some(bookmarks) = self.bookmarks_mut(buffer, cx) else {
+            return;
+        };
+        match bookmark_ix_for_line(bookmarks, line, &snapshot) {
+            Ok(ix) => bookmarks[ix].label = label,
+            Err(ix) => bookmarks.insert(
+                ix,
+                Bookmark {
+                    position: snapshot.anchor_after(Point::new(line, 0)),
+                    label,
+                },
+            ),
+        }
+        self.serialize_buffer(buffer, cx);
+    }
+
+    /// Returns the bookmark on the given line of the buffer.
+    pub fn bookmark_at(&self, buffer: &Entity<Buffer>, line: u32, cx: &App) -> Option<&Bookmark> {
+        let snapshot = buffer.read(cx).snapshot();
+        let bookmarks = self.bookmarks_for_buffer(snapshot.remote_id());
+        let ix = bookmark_ix_for_line(bookmarks, line, &snapshot).ok()?;
+        bookmarks.get(ix)
+    }
+
+    /// Returns the bookmarks in an open buffer, in order.
+    pub fn bookmarks_for_buffer(&self, buffer_id: BufferId) -> &[Bookmark] {
+        self.buffers
+            .get(&buffer_id)
+            .map_or(&[], |buffer_bookmarks| &buffer_bookmarks.bookmarks)
+    }
+
+    /// Returns every bookmark in the workspace, by file.
+    pub fn all_bookmarks(&self) -> &BTreeMap<Arc<Path>, Vec<SerializedBookmark>> {
+        &self.serialized
+    }
+
+    /// Removes every bookmark in the workspace.
+    pub fn clear(&mut self, cx: &mut Context<Self>) {
+        for buffer_bookmarks in self.buffers.values_mut() {
+            buffer_bookmarks.bookmarks.clear();
+        }
+        if let Some(workspace_id) = self.workspace_id {
+            let paths = self.serialized.keys().cloned().collect::<Vec<_>>();
+            cx.background_spawn(async move {
+                for abs_path in paths {
+                    DB.save_bookmarks(workspace_id, abs_path, Vec::new())
+                        .await?;
+                }
+                anyhow::Ok(())
+            })
+            .detach_and_log_err(cx);
+        }
+        self.serialized.clear();
+        cx.notify();
+    }
+}
+
+fn bookmark_ix_for_line(
+    bookmarks: &[Bookmark],
+    line: u32,
+    snapshot: &text::BufferSnapshot,
+) -> Result<usize, usize> {
+    bookmarks.binary_search_by(|bookmark| bookmark.position.to_point(snapshot).row.cmp(&line))
+}
+
+impl Editor {
+    pub fn bookmark_store(&self, cx: &App) -> Option<Entity<BookmarkStore>> {
+        BookmarkStore::for_workspace(&self.workspace()?, cx)
+    }
+
+    /// Returns the bookmarks on the given display rows.
+    pub(crate) fn bookmarks_in_range(
+        &self,
+        range: Range<DisplayRow>,
+        snapshot: &EditorSnapshot,
+        cx: &App,
+    ) -> HashMap<DisplayRow, (Anchor, Option<SharedString>)> {
+        let mut bookmarks = HashMap::default();
+        let Some(store) = self.bookmark_store(cx) else {
+            return bookmarks;
+        };
+        let store = store.read(cx);
+        let multi_buffer_snapshot = snapshot.buffer_snapshot();
+        let start =
+            snapshot.display_point_to_point(DisplayPoint::new(range.start, 0), text::Bias::Left);
+        let end =
+            snapshot.display_point_to_point(DisplayPoint::new(range.end, 0), text::Bias::Right);
+        for (buffer_snapshot, _, excerpt_id) in
+            multi_buffer_snapshot.range_to_buffer_ranges(start..end)
+        {
+            for bookmark in store.bookmarks_for_buffer(buffer_snapshot.remote_id()) {
+                let Some(anchor) =
+                    multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, bookmark.position)
+                else {
+                    continue;
+                };
+                let row = anchor.to_display_point(snapshot).row();
+                if range.contains(&row) {
+                    bookmarks.insert(row, (anchor, bookmark.label.clone()));
+                }
+            }
+        }
+        bookmarks
+    }
+
+    pub(crate) fn render_bookmark(
+        &self,
+        position: Anchor,
+        row: DisplayRow,
+        label: Option<SharedString>,
+        cx: &mut Context<Self>,
+    ) -> IconButton {
+        let focus_handle = self.focus_handle.clone();
+        IconButton::new(("bookmark_indicator", row.0 as usize), IconName::Bookmark)
+            .icon_size(IconSize::XSmall)
+            .size(ui::ButtonSize::None)
+            .icon_color(Color::Accent)
+            .style(ButtonStyle::Transparent)
+            .on_click(cx.listener(move |editor, _: &ClickEvent, window, cx| {
+                window.focus(&editor.focus_handle(cx));
+                editor.toggle_bookmark_at(position, cx);
+            }))
+            .tooltip(move |_window, cx| {
+                let title = label
+                    .clone()
+                    .unwrap_or_else(|| SharedString::new_static("Remove bookmark"));
+                Tooltip::for_action_in(title, &ToggleBookmark, &focus_handle, cx)
+            })
+    }
+
+    fn toggle_bookmark_at(&mut self, position: Anchor, cx: &mut Context<Self>) {
+        let Some(store) = self.bookmark_store(cx) else {
+            return;
+        };
+        let Some((buffer, point, _)) = self
+            .buffer
+            .read(cx)
+            .point_to_buffer_point(position.to_point(&self.buffer.read(cx).snapshot(cx)), cx)
+        else {
+            return;
+        };
+        store.update(cx, |store, cx| store.toggle(&buffer, point.row, cx));
+    }
+
+    pub fn toggle_bookmark(
+        &mut self,
+        _: &ToggleBookmark,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(store) = self.bookmark_store(cx) else {
+            return;
+        };
+        let display_snapshot = self.display_snapshot(cx);
+        let mut lines = self
+            .selections
+            .all::<Point>(&display_snapshot)
+            .into_iter()
+            .filter_map(|selection| {
+                let (buffer, point, _) = self
+                    .buffer
+                    .read(cx)
+                    .point_to_buffer_point(selection.head(), cx)?;
+                Some((buffer, point.row))
+            })
+            .collect::<Vec<_>>();
+        lines.dedup_by(|(a, a_row), (b, b_row)| a == b && a_row == b_row);
+        store.update(cx, |store, cx| {
+            for (buffer, row) in lines {
+                store.toggle(&buffer, row, cx);
+            }
+        });
+    }
+
+    pub fn go_to_next_bookmark(
+        &mut self,
+        _: &GoToNextBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_bookmark(Direction::Next, window, cx);
+    }
+
+    pub fn go_to_previous_bookmark(
+        &mut self,
+        _: &GoToPreviousBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_bookmark(Direction::Prev, window, cx);
+    }
+
+    fn go_to_bookmark(
+        &mut self,
+        direction: Direction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(store) = self.bookmark_store(cx) else {
+            return;
+        };
+        let multi_buffer = self.buffer.read(cx);
+        let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+        let mut rows = multi_buffer
+            .all_buffers()
+            .into_iter()
+            .flat_map(|buffer| {
+                store
+                    .read(cx)
+                    .bookmarks_for_buffer(buffer.read(cx).remote_id())
+                    .iter()
+                    .filter_map(|bookmark| {
+                        multi_buffer.buffer_anchor_to_anchor(&buffer, bookmark.position, cx)
+                    })
+                    .map(|anchor| anchor.to_point(&multi_buffer_snapshot).row)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        rows.sort_unstable();
+        rows.dedup();
+
+        let cursor_row = self
+            .selections
+            .newest::<Point>(&self.display_snapshot(cx))
+            .head()
+            .row;
+        let Some(row) = adjacent_bookmark_row(&rows, cursor_row, direction) else {
+            return;
+        };
+        let destination = Point::new(row, 0);
+        self.unfold_ranges(&[destination..destination], false, false, cx);
+        self.change_selections(
+            SelectionEffects::scroll(Autoscroll::center()),
+            window,
+            cx,
+            |s| s.select_ranges([destination..destination]),
+        );
+    }
+}
+
+/// Returns the bookmarked row after (or before) the cursor, wrapping around
+/// at the end of the buffer.
+fn adjacent_bookmark_row(rows: &[u32], cursor_row: u32, direction: Direction) -> Option<u32> {
+    match direction {
+        Direction::Next => rows
+            .iter()
+            .find(|&&row| row > cursor_row)
+            .or_else(|| rows.first())
+            .copied(),
+        Direction::Prev => rows
+            .iter()
+            .rev()
+            .find(|&&row| row < cursor_row)
+            .or_else(|| rows.last())
+            .copied(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RowExt as _, editor_tests::init_test};
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use util::{path, rel_path::rel_path};
+
+    async fn open_editor(
+        workspace: &Entity<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> Entity<Editor> {
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                let worktree_id = workspace
+                    .project()
+                    .read(cx)
+                    .worktrees(cx)
+                    .next()
+                    .unwrap()
+                    .read(cx)
+                    .id();
+                workspace.open_path((worktree_id, rel_path("a.txt")), None, true, window, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap()
+    }
+
+    fn select_row(editor: &Entity<Editor>, row: u32, cx: &mut VisualTestContext) {
+        editor.update_in(cx, |editor, window, cx| {
+            editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                s.select_ranges([Point::new(row, 0)..Point::new(row, 0)])
+            });
+        });
+    }
+
+    fn bookmarked_rows(editor: &Entity<Editor>, cx: &mut VisualTestContext) -> Vec<u32> {
+        editor.update_in(cx, |editor, window, cx| {
+            let snapshot = editor.snapshot(window, cx);
+            let end = snapshot.display_snapshot.max_point().row().next_row();
+            let mut rows = editor
+                .bookmarks_in_range(DisplayRow(0)..end, &snapshot, cx)
+                .into_keys()
+                .map(|row| row.0)
+                .collect::<Vec<_>>();
+            rows.sort_unstable();
+            rows
+        })
+    }
+
+    #[gpui::test]
+    async fn test_bookmarks_merged_when_lines_are_joined(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({ "a.txt": "one\ntwo\nthree\nfour\n" }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let store = cx
+            .update(|_, cx| BookmarkStore::for_workspace(&workspace, cx))
+            .unwrap();
+        let editor = open_editor(&workspace, cx).await;
+        for row in [1, 2] {
+            select_row(&editor, row, cx);
+            editor.update_in(cx, |editor, window, cx| {
+                editor.toggle_bookmark(&ToggleBookmark, window, cx)
+            });
+        }
+        assert_eq!(bookmarked_rows(&editor, cx), [1, 2]);
+
+        // When the lines of two bookmarks are joined, only one is kept.
+        editor.update_in(cx, |editor, window, cx| {
+            editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                s.select_ranges([Point::new(1, 3)..Point::new(2, 0)])
+            });
+            editor.insert("", window, cx);
+        });
+        assert_eq!(
+            editor.update(cx, |editor, cx| editor.text(cx)),
+            "one\ntwothree\nfour\n"
+        );
+        assert_eq!(bookmarked_rows(&editor, cx), [1]);
+        store.read_with(cx, |store, _| {
+            assert_eq!(
+                store.all_bookmarks().get(Path::new(path!("/root/a.txt"))),
+                Some(&vec![SerializedBookmark {
+                    line: 1,
+                    label: None
+                }])
+            );
+            assert_eq!(store.buffers.values().next().unwrap().bookmarks.len(), 1);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_bookmarks_restored_on_reopen(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({ "a.txt": "one\ntwo\nthree\nfour\n" }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let store = cx
+            .update(|_, cx| BookmarkStore::for_workspace(&workspace, cx))
+            .unwrap();
+        let editor = open_editor(&workspace, cx).await;
+        select_row(&editor, 2, cx);
+        editor.update_in(cx, |editor, window, cx| {
+            editor.toggle_bookmark(&ToggleBookmark, window, cx)
+        });
+
+        // Once the file is closed, its bookmarks are only kept by line.
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.active_pane().update(cx, |pane, cx| {
+                    pane.close_item_by_id(
+                        editor.entity_id(),
+                        workspace::SaveIntent::Skip,
+                        window,
+                        cx,
+                    )
+                })
+            })
+            .await
+            .unwrap();
+        drop(editor);
+        cx.run_until_parked();
+        store.read_with(cx, |store, _| {
+            assert!(store.buffers.is_empty());
+            assert_eq!(
+                store.all_bookmarks().get(Path::new(path!("/root/a.txt"))),
+                Some(&vec![SerializedBookmark {
+                    line: 2,
+                    label: None
+                }])
+            );
+        });
+
+        let editor = open_editor(&workspace, cx).await;
+        assert_eq!(bookmarked_rows(&editor, cx), [2]);
+    }
+
+    #[test]
+    fn test_adjacent_bookmark_row() {
+        let rows = [2, 5, 9];
+        assert_eq!(adjacent_bookmark_row(&rows, 0, Direction::Next), Some(2));
+        assert_eq!(adjacent_bookmark_row(&rows, 5, Direction::Next), Some(9));
+        assert_eq!(adjacent_bookmark_row(&rows, 9, Direction::Next), Some(2));
+        assert_eq!(adjacent_bookmark_row(&rows, 5, Direction::Prev), Some(2));
+        assert_eq!(adjacent_bookmark_row(&rows, 1, Direction::Prev), Some(9));
+        assert_eq!(adjacent_bookmark_row(&[], 1, Direction::Prev), None);
+    }
+}
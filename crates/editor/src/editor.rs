@@ -13,6 +13,7 @@
 //! If you're looking to improve Vim mode, you should check out Vim crate that wraps Editor and overrides its behavior.
 pub mod actions;
 pub mod blink_manager;
+mod bookmarks;
 mod bracket_colorization;
 mod clangd_ext;
 pub mod code_context_menus;
@@ -49,6 +50,7 @@ mod signature_help;
 pub mod test;
 
 pub(crate) use actions::*;
+pub use bookmarks::{Bookmark, BookmarkStore, SerializedBookmark};
 pub use display_map::{ChunkRenderer, ChunkRendererContext, DisplayPoint, FoldPlaceholder};
 pub use edit_prediction::Direction;
 pub use editor_settings::{
@@ -319,6 +321,7 @@ pub fn init(cx: &mut App) {
     workspace::register_project_item::<Editor>(cx);
     workspace::FollowableViewRegistry::register::<Editor>(cx);
     workspace::register_serializable_item::<Editor>(cx);
+    bookmarks::init(cx);
 
     cx.observe_new(
         |workspace: &mut Workspace, _: Option<&mut Window>, _cx: &mut Context<Workspace>| {
@@ -3380,6 +3383,8 @@ impl Editor {
         use text::ToOffset as _;
         use text::ToPoint as _;
 
+        cx.emit(EditorEvent::FoldsChanged);
+
         if self.mode.is_minimap()
             || WorkspaceSettings::get(None, cx).restore_on_startup == RestoreOnStartupBehavior::None
         {
@@ -24338,6 +24343,7 @@ pub enum EditorEvent {
         ids: Vec<ExcerptId>,
         folded: bool,
     },
+    FoldsChanged,
     ExcerptsEdited {
         ids: Vec<ExcerptId>,
     },
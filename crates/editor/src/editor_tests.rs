@@ -23464,6 +23464,121 @@ async fn test_breakpoint_toggling(cx: &mut TestAppContext) {
     assert_breakpoint(&breakpoints, &abs_path, vec![]);
 }
 
+#[gpui::test]
+async fn test_bookmarks(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/a"),
+        json!({
+            "main.rs": "one\ntwo\nthree\nfour\nfive\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs, [path!("/a").as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace.deref(), cx);
+    let worktree_id = workspace
+        .update(cx, |workspace, _window, cx| {
+            workspace.project().update(cx, |project, cx| {
+                project.worktrees(cx).next().unwrap().read(cx).id()
+            })
+        })
+        .unwrap();
+    let editor = workspace
+        .update(cx, |workspace, window, cx| {
+            workspace.open_path((worktree_id, rel_path("main.rs")), None, true, window, cx)
+        })
+        .unwrap()
+        .await
+        .unwrap()
+        .downcast::<Editor>()
+        .unwrap();
+
+    let bookmarked_lines = |editor: &Entity<Editor>, cx: &mut VisualTestContext| {
+        editor.update(cx, |editor, cx| {
+            let store = editor.bookmark_store(cx).unwrap();
+            store
+                .read(cx)
+                .all_bookmarks()
+                .values()
+                .flatten()
+                .map(|bookmark| bookmark.line)
+                .collect::<Vec<_>>()
+        })
+    };
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+            s.select_ranges([Point::new(1, 1)..Point::new(1, 1)])
+        });
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+        editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+            s.select_ranges([Point::new(3, 0)..Point::new(3, 0)])
+        });
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+    });
+    assert_eq!(bookmarked_lines(&editor, cx), vec![1, 3]);
+
+    // Bookmarks follow the lines they were made on.
+    editor.update_in(cx, |editor, window, cx| {
+        editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+            s.select_ranges([Point::new(0, 0)..Point::new(0, 0)])
+        });
+        editor.insert("zero\n", window, cx);
+    });
+    assert_eq!(bookmarked_lines(&editor, cx), vec![2, 4]);
+    editor.update_in(cx, |editor, window, cx| {
+        let snapshot = editor.snapshot(window, cx);
+        let mut rows = editor
+            .bookmarks_in_range(DisplayRow(0)..DisplayRow(6), &snapshot, cx)
+            .into_keys()
+            .collect::<Vec<_>>();
+        rows.sort_unstable();
+        assert_eq!(rows, [DisplayRow(2), DisplayRow(4)]);
+    });
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.go_to_next_bookmark(&GoToNextBookmark, window, cx);
+        assert_eq!(
+            editor
+                .selections
+                .newest::<Point>(&editor.display_snapshot(cx))
+                .head(),
+            Point::new(2, 0)
+        );
+        editor.go_to_next_bookmark(&GoToNextBookmark, window, cx);
+        assert_eq!(
+            editor
+                .selections
+                .newest::<Point>(&editor.display_snapshot(cx))
+                .head(),
+            Point::new(4, 0)
+        );
+        editor.go_to_next_bookmark(&GoToNextBookmark, window, cx);
+        assert_eq!(
+            editor
+                .selections
+                .newest::<Point>(&editor.display_snapshot(cx))
+                .head(),
+            Point::new(2, 0)
+        );
+        editor.go_to_previous_bookmark(&GoToPreviousBookmark, window, cx);
+        assert_eq!(
+            editor
+                .selections
+                .newest::<Point>(&editor.display_snapshot(cx))
+                .head(),
+            Point::new(4, 0)
+        );
+
+        // Toggling on a bookmarked line removes the bookmark.
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+    });
+    assert_eq!(bookmarked_lines(&editor, cx), vec![2]);
+}
+
 #[gpui::test]
 async fn test_log_breakpoint_editing(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
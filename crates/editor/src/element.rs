@@ -617,6 +617,9 @@ impl EditorElement {
         register_action(editor, window, Editor::edit_log_breakpoint);
         register_action(editor, window, Editor::enable_breakpoint);
         register_action(editor, window, Editor::disable_breakpoint);
+        register_action(editor, window, Editor::toggle_bookmark);
+        register_action(editor, window, Editor::go_to_next_bookmark);
+        register_action(editor, window, Editor::go_to_previous_bookmark);
         if editor.read(cx).enable_wrap_selections_in_tag(cx) {
             register_action(editor, window, Editor::wrap_selections_in_tag);
         }
@@ -2960,6 +2963,58 @@ impl EditorElement {
         })
     }
 
+    fn layout_bookmarks(
+        &self,
+        line_height: Pixels,
+        range: Range<DisplayRow>,
+        scroll_position: gpui::Point<ScrollOffset>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        display_hunks: &[(DisplayDiffHunk, Option<Hitbox>)],
+        snapshot: &EditorSnapshot,
+        breakpoints: &HashMap<DisplayRow, (Anchor, Breakpoint, Option<BreakpointSessionState>)>,
+        row_infos: &[RowInfo],
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            editor
+                .bookmarks_in_range(range.clone(), snapshot, cx)
+                .into_iter()
+                .filter_map(|(display_row, (anchor, label))| {
+                    // Breakpoints are shown in the same place, and take precedence.
+                    if breakpoints.contains_key(&display_row) {
+                        return None;
+                    }
+                    if row_infos
+                        .get((display_row.0.saturating_sub(range.start.0)) as usize)
+                        .is_some_and(|row_info| row_info.expand_info.is_some())
+                    {
+                        return None;
+                    }
+                    let row =
+                        MultiBufferRow(DisplayPoint::new(display_row, 0).to_point(snapshot).row);
+                    if snapshot.is_line_folded(row) {
+                        return None;
+                    }
+
+                    let button = editor.render_bookmark(anchor, display_row, label, cx);
+                    Some(prepaint_gutter_button(
+                        button,
+                        display_row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_position,
+                        gutter_hitbox,
+                        display_hunks,
+                        window,
+                        cx,
+                    ))
+                })
+                .collect_vec()
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_run_indicators(
         &self,
@@ -6323,6 +6378,10 @@ impl EditorElement {
                 }
             });
 
+            for bookmark in layout.bookmarks.iter_mut() {
+                bookmark.paint(window, cx);
+            }
+
             for breakpoint in layout.breakpoints.iter_mut() {
                 breakpoint.paint(window, cx);
             }
@@ -9892,6 +9951,20 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let bookmarks = self.layout_bookmarks(
+                        line_height,
+                        start_row..end_row,
+                        scroll_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &display_hunks,
+                        &snapshot,
+                        &breakpoint_rows,
+                        &row_infos,
+                        window,
+                        cx,
+                    );
+
                     let test_indicators = if gutter_settings.runnables {
                         self.layout_run_indicators(
                             line_height,
@@ -10124,6 +10197,7 @@ impl Element for EditorElement {
                         mouse_context_menu,
                         test_indicators,
                         breakpoints,
+                        bookmarks,
                         crease_toggles,
                         crease_trailers,
                         tab_invisible,
@@ -10300,6 +10374,7 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     test_indicators: Vec<AnyElement>,
     breakpoints: Vec<AnyElement>,
+    bookmarks: Vec<AnyElement>,
     crease_toggles: Vec<Option<AnyElement>>,
     expand_toggles: Vec<Option<(AnyElement, gpui::Point<Pixels>)>>,
     diff_hunk_controls: Vec<AnyElement>,
@@ -104,6 +104,14 @@ impl Inlay {
         }
     }
 
+    pub fn padding(id: usize, position: Anchor, width: usize) -> Self {
+        Self {
+            id: InlayId::Padding(id),
+            position,
+            content: InlayContent::Text(" ".repeat(width).into()),
+        }
+    }
+
     pub fn text(&self) -> &Rope {
         static COLOR_TEXT: OnceLock<Rope> = OnceLock::new();
         match &self.content {
@@ -1,6 +1,6 @@
 use crate::{
-    Anchor, Autoscroll, BufferSerialization, Editor, EditorEvent, EditorSettings, ExcerptId,
-    ExcerptRange, FormatTarget, MultiBuffer, MultiBufferSnapshot, NavigationData,
+    Anchor, Autoscroll, BookmarkStore, BufferSerialization, Editor, EditorEvent, EditorSettings,
+    ExcerptId, ExcerptRange, FormatTarget, MultiBuffer, MultiBufferSnapshot, NavigationData,
     ReportEditorEvent, SearchWithinRange, SelectionEffects, ToPoint as _,
     display_map::HighlightKey,
     editor_settings::SeedQuerySetting,
@@ -1016,6 +1016,9 @@ impl Item for Editor {
                 }
             })
             .detach();
+            if let Some(bookmark_store) = BookmarkStore::for_workspace(workspace, cx) {
+                cx.observe(&bookmark_store, |_, _, cx| cx.notify()).detach();
+            }
         }
     }
 
@@ -1551,6 +1554,7 @@ impl SearchableItem for Editor {
                 case: true,
                 word: true,
                 regex: true,
+                structural: true,
                 replacement: false,
                 selection: false,
                 find_in_results: true,
@@ -1560,6 +1564,7 @@ impl SearchableItem for Editor {
                 case: true,
                 word: true,
                 regex: true,
+                structural: true,
                 replacement: true,
                 selection: true,
                 find_in_results: false,
@@ -1637,19 +1642,10 @@ impl SearchableItem for Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
-        let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
-        let text: Cow<_> = if text.len() == 1 {
-            text.first().cloned().unwrap().into()
-        } else {
-            let joined_chunks = text.join("");
-            joined_chunks.into()
-        };
-
-        if let Some(replacement) = query.replacement_for(&text) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        if let Some(replacement) = replacement_for_match(query, &snapshot, identifier) {
             self.transact(window, cx, |this, _, cx| {
-                this.edit([(identifier.clone(), Arc::from(&*replacement))], cx);
+                this.edit([(identifier.clone(), replacement)], cx);
             });
         }
     }
@@ -1660,22 +1656,12 @@ impl SearchableItem for Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
+        let snapshot = self.buffer.read(cx).snapshot(cx);
         let mut edits = vec![];
 
         for m in matches {
-            let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
-
-            let text: Cow<_> = if text.len() == 1 {
-                text.first().cloned().unwrap().into()
-            } else {
-                let joined_chunks = text.join("");
-                joined_chunks.into()
-            };
-
-            if let Some(replacement) = query.replacement_for(&text) {
-                edits.push((m.clone(), Arc::from(&*replacement)));
+            if let Some(replacement) = replacement_for_match(query, &snapshot, m) {
+                edits.push((m.clone(), replacement));
             }
         }
 
@@ -1826,6 +1812,33 @@ impl SearchableItem for Editor {
     }
 }
 
+fn replacement_for_match(
+    query: &SearchQuery,
+    buffer: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+) -> Option<Arc<str>> {
+    if query.is_structural() {
+        let (buffer, range, _) = buffer
+            .range_to_buffer_ranges(range.clone())
+            .into_iter()
+            .next()?;
+        return query
+            .structural_replacement_for(buffer, range.start.0..range.end.0)
+            .map(Arc::from);
+    }
+
+    let text = buffer.text_for_range(range.clone()).collect::<Vec<_>>();
+    let text: Cow<_> = if text.len() == 1 {
+        text.first().cloned().unwrap().into()
+    } else {
+        let joined_chunks = text.join("");
+        joined_chunks.into()
+    };
+    query
+        .replacement_for(&text)
+        .map(|replacement| Arc::from(&*replacement))
+}
+
 pub fn active_match_index(
     direction: Direction,
     ranges: &[Range<Anchor>],
@@ -10,7 +10,10 @@ use db::{
 };
 use fs::MTime;
 use itertools::Itertools as _;
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use workspace::{ItemId, WorkspaceDb, WorkspaceId};
 
@@ -121,6 +124,13 @@ impl Domain for EditorDb {
     //   start: usize,
     //   end: usize,
     // )
+    //
+    // editor_bookmarks(
+    //   workspace_id: usize,
+    //   path: PathBuf,
+    //   line: u32,
+    //   label: Option<String>,
+    // )
 
     const MIGRATIONS: &[&str] = &[
         sql! (
@@ -197,6 +207,17 @@ impl Domain for EditorDb {
                 ON DELETE CASCADE
             ) STRICT;
         ),
+        sql! (
+            CREATE TABLE editor_bookmarks (
+                workspace_id INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                line INTEGER NOT NULL,
+                label TEXT,
+                PRIMARY KEY(workspace_id, path, line),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        ),
     ];
 }
 
@@ -281,6 +302,46 @@ impl EditorDb {
         }
     }
 
+    query! {
+        pub fn get_bookmarks(
+            workspace_id: WorkspaceId
+        ) -> Result<Vec<(Arc<Path>, u32, Option<String>)>> {
+            SELECT path, line, label
+            FROM editor_bookmarks
+            WHERE workspace_id = ?1
+            ORDER BY path, line
+        }
+    }
+
+    /// Replaces the bookmarks stored for the file at `path`.
+    pub async fn save_bookmarks(
+        &self,
+        workspace_id: WorkspaceId,
+        path: Arc<Path>,
+        bookmarks: Vec<(u32, Option<String>)>,
+    ) -> Result<()> {
+        log::debug!(
+            "Saving {} bookmarks for {path:?} in workspace {workspace_id:?}",
+            bookmarks.len()
+        );
+        self.write(move |conn| {
+            conn.with_savepoint("save_bookmarks", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM editor_bookmarks WHERE workspace_id = ? AND path = ?
+                ))?((workspace_id, path.clone()))?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT OR REPLACE INTO editor_bookmarks (workspace_id, path, line, label)
+                    VALUES (?, ?, ?, ?)
+                ))?;
+                for (line, label) in bookmarks {
+                    insert((workspace_id, path.clone(), line, label))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
     pub async fn save_editor_selections(
         &self,
         editor_id: ItemId,
@@ -465,4 +526,42 @@ mod tests {
             .unwrap();
         assert_eq!(have, serialized_editor);
     }
+
+    #[gpui::test]
+    async fn test_save_and_get_bookmarks() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let first: Arc<Path> = Path::new("/root/first.rs").into();
+        let second: Arc<Path> = Path::new("/root/second.rs").into();
+
+        DB.save_bookmarks(
+            workspace_id,
+            first.clone(),
+            vec![(12, None), (3, Some("setup".to_owned()))],
+        )
+        .await
+        .unwrap();
+        DB.save_bookmarks(workspace_id, second.clone(), vec![(0, None)])
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_bookmarks(workspace_id).unwrap(),
+            vec![
+                (first.clone(), 3, Some("setup".to_owned())),
+                (first.clone(), 12, None),
+                (second.clone(), 0, None),
+            ]
+        );
+
+        // Saving a file's bookmarks replaces the ones stored for it.
+        DB.save_bookmarks(workspace_id, first.clone(), vec![(4, None)])
+            .await
+            .unwrap();
+        DB.save_bookmarks(workspace_id, second.clone(), Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_bookmarks(workspace_id).unwrap(),
+            vec![(first, 4, None)]
+        );
+    }
 }
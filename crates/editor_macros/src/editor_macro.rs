@@ -0,0 +1,142 @@
+use std::{ops::Range, sync::Arc};
+
+use anyhow::{Context as _, Result, anyhow};
+use gpui::{Action, App};
+use settings::MacroStepContent;
+
+/// A step of an editor macro.
+#[derive(Debug)]
+pub enum MacroStep {
+    /// An action dispatched to the focused element.
+    Action(Box<dyn Action>),
+    /// Text typed into the editor.
+    Insertion {
+        text: Arc<str>,
+        utf16_range_to_replace: Option<Range<isize>>,
+    },
+}
+
+impl Clone for MacroStep {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Action(action) => Self::Action(action.boxed_clone()),
+            Self::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => Self::Insertion {
+                text: text.clone(),
+                utf16_range_to_replace: utf16_range_to_replace.clone(),
+            },
+        }
+    }
+}
+
+impl MacroStep {
+    pub fn from_content(content: &MacroStepContent, cx: &App) -> Result<Self> {
+        Ok(match content {
+            MacroStepContent::Text { text } => Self::Insertion {
+                text: text.as_str().into(),
+                utf16_range_to_replace: None,
+            },
+            MacroStepContent::Action(name) => Self::Action(cx.build_action(name, None)?),
+            MacroStepContent::ActionWithArguments(name, arguments) => {
+                Self::Action(cx.build_action(name, Some(arguments.clone()))?)
+            }
+        })
+    }
+
+    /// Converts the step into its form in the settings.
+    ///
+    /// Actions don't know how to serialize their arguments, so an action with
+    /// arguments is only saved if a key binding in the keymap has the same
+    /// arguments, which is the case when it was recorded from a keystroke.
+    pub fn to_content(&self, cx: &App) -> Result<MacroStepContent> {
+        match self {
+            Self::Insertion {
+                text,
+                utf16_range_to_replace: None,
+            } => Ok(MacroStepContent::Text {
+                text: text.to_string(),
+            }),
+            Self::Insertion { .. } => Err(anyhow!(
+                "text that replaced existing text, such as from an input method, can't be saved"
+            )),
+            Self::Action(action) => {
+                let name = action.name();
+                if cx
+                    .build_action(name, None)
+                    .is_ok_and(|default_action| default_action.partial_eq(action.as_ref()))
+                {
+                    return Ok(MacroStepContent::Action(name.to_string()));
+                }
+                let arguments = cx
+                    .key_bindings()
+                    .borrow()
+                    .bindings_for_action(action.as_ref())
+                    .find_map(|binding| binding.action_input())
+                    .with_context(|| format!("the arguments of {name} can't be saved"))?;
+                let arguments = serde_json::from_str(&arguments)
+                    .with_context(|| format!("the arguments of {name} can't be saved"))?;
+                Ok(MacroStepContent::ActionWithArguments(
+                    name.to_string(),
+                    arguments,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use editor::actions::{MoveDown, SelectNext};
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    fn test_macro_step_content(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let step = MacroStep::Action(Box::new(MoveDown));
+            let content = step.to_content(cx).unwrap();
+            assert_eq!(content, MacroStepContent::Action("editor::MoveDown".into()));
+            let MacroStep::Action(action) = MacroStep::from_content(&content, cx).unwrap() else {
+                panic!("expected an action");
+            };
+            assert!(action.partial_eq(&MoveDown));
+
+            let content = MacroStepContent::ActionWithArguments(
+                "editor::SelectNext".into(),
+                serde_json::json!({ "replace_newest": true }),
+            );
+            let step = MacroStep::from_content(&content, cx).unwrap();
+            let MacroStep::Action(action) = &step else {
+                panic!("expected an action");
+            };
+            assert!(action.partial_eq(&SelectNext {
+                replace_newest: true
+            }));
+            // Without a key binding with the same arguments, they're unknown.
+            assert!(step.to_content(cx).is_err());
+
+            let step = MacroStep::Insertion {
+                text: "hello".into(),
+                utf16_range_to_replace: None,
+            };
+            assert_eq!(
+                step.to_content(cx).unwrap(),
+                MacroStepContent::Text {
+                    text: "hello".into()
+                }
+            );
+            let step = MacroStep::Insertion {
+                text: "é".into(),
+                utf16_range_to_replace: Some(-1..0),
+            };
+            assert!(step.to_content(cx).is_err());
+
+            assert!(
+                MacroStep::from_content(&MacroStepContent::Action("editor::Nope".into()), cx)
+                    .is_err()
+            );
+        });
+    }
+}
@@ -0,0 +1,704 @@
+mod editor_macro;
+mod editor_macros_settings;
+mod macro_picker;
+mod recording_indicator;
+mod replayer;
+mod save_macro_modal;
+
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use editor::{Editor, EditorEvent};
+use gpui::{
+    Action, AnyWindowHandle, App, Entity, Focusable as _, Global, Subscription, WeakEntity, Window,
+    actions, rems,
+};
+use language::Point;
+use picker::Picker;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use settings::Settings as _;
+use workspace::{Toast, Workspace, notifications::NotificationId, searchable::SearchableItem as _};
+
+pub use crate::editor_macro::MacroStep;
+pub use crate::editor_macros_settings::EditorMacrosSettings;
+pub use crate::recording_indicator::MacroRecordingIndicator;
+
+use crate::macro_picker::MacroPickerDelegate;
+use crate::replayer::{ReplayStep, Replayer};
+use crate::save_macro_modal::SaveMacroModal;
+
+actions!(
+    editor_macros,
+    [
+        /// Starts recording the actions and text typed in the editor as a
+        /// macro, or stops recording.
+        ToggleRecording,
+        /// Replays the last recorded macro once at the start of every line in
+        /// the selections.
+        ReplayLastMacroOnEachLine,
+        /// Replays the last recorded macro once for every buffer search match,
+        /// with the match selected.
+        ReplayLastMacroOnEachMatch,
+        /// Saves the last recorded macro in the settings under a name.
+        SaveLastMacro,
+        /// Shows the macros saved in the settings to run one.
+        ShowMacros,
+    ]
+);
+
+/// Where a macro is replayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayTarget {
+    /// At the current selections.
+    #[default]
+    Selections,
+    /// At the start of every line in the selections, one line at a time.
+    EachLine,
+    /// At every buffer search match, one match at a time, with the match
+    /// selected.
+    EachMatch,
+}
+
+/// Replays the last recorded macro.
+#[derive(PartialEq, Clone, Deserialize, JsonSchema, Action)]
+#[action(namespace = editor_macros)]
+#[serde(deny_unknown_fields)]
+pub struct ReplayLastMacro {
+    /// How many times to replay the macro at each place it's replayed at.
+    #[serde(default = "default_times")]
+    pub times: usize,
+    #[serde(default)]
+    pub target: ReplayTarget,
+}
+
+/// Runs a macro saved in the settings.
+#[derive(PartialEq, Clone, Deserialize, JsonSchema, Action)]
+#[action(namespace = editor_macros)]
+#[serde(deny_unknown_fields)]
+pub struct RunMacro {
+    /// The name of the macro in the `macros` setting.
+    pub name: String,
+    /// How many times to run the macro at each place it's run at.
+    #[serde(default = "default_times")]
+    pub times: usize,
+    #[serde(default)]
+    pub target: ReplayTarget,
+}
+
+fn default_times() -> usize {
+    1
+}
+
+/// Replays are stopped after this many steps, including those of the macros
+/// that the macro runs, in case it never finishes, such as by running itself.
+const MAX_REPLAY_STEPS: usize = 10_000;
+
+struct Recording {
+    editor: WeakEntity<Editor>,
+    window: AnyWindowHandle,
+    steps: Vec<MacroStep>,
+    _subscription: Subscription,
+}
+
+/// The macro being recorded, the last one that was, and the one being
+/// replayed.
+#[derive(Default)]
+pub struct MacroRecorder {
+    recording: Option<Recording>,
+    last_macro: Option<Arc<[MacroStep]>>,
+    replayer: Option<Replayer>,
+}
+
+impl Global for MacroRecorder {}
+
+impl MacroRecorder {
+    pub fn is_recording(cx: &App) -> bool {
+        cx.try_global::<Self>()
+            .is_some_and(|recorder| recorder.recording.is_some())
+    }
+
+    pub fn last_macro(cx: &App) -> Option<Arc<[MacroStep]>> {
+        cx.try_global::<Self>()?.last_macro.clone()
+    }
+
+    /// The editor being recorded, if it's focused in the given window and no
+    /// macro is being replayed.
+    fn focused_recording_editor(window: &Window, cx: &App) -> Option<Entity<Editor>> {
+        let recorder = cx.try_global::<Self>()?;
+        if recorder.replayer.is_some() {
+            return None;
+        }
+        let recording = recorder.recording.as_ref()?;
+        if recording.window != window.window_handle() {
+            return None;
+        }
+        recording
+            .editor
+            .upgrade()
+            .filter(|editor| editor.focus_handle(cx).contains_focused(window, cx))
+    }
+
+    /// Records actions dispatched in the editor being recorded, whether they
+    /// were bound to a keystroke or run from the command palette or a menu.
+    ///
+    /// Actions are only recorded once they've run, if the editor is still
+    /// focused: those that move focus elsewhere, like the one that opens the
+    /// command palette, would send the steps after them there when replayed.
+    fn observe_action(action: &dyn Action, window: &mut Window, cx: &mut App) {
+        if action.name().starts_with("editor_macros::")
+            || Self::focused_recording_editor(window, cx).is_none()
+        {
+            return;
+        }
+        let action = action.boxed_clone();
+        window.defer(cx, move |window, cx| {
+            if Self::focused_recording_editor(window, cx).is_none() {
+                return;
+            }
+            if let Some(recording) = cx.global_mut::<Self>().recording.as_mut() {
+                recording.steps.push(MacroStep::Action(action));
+            }
+        });
+    }
+
+    fn observe_editor_event(&mut self, event: &EditorEvent) {
+        if self.replayer.is_some() {
+            return;
+        }
+        let Some(recording) = self.recording.as_mut() else {
+            return;
+        };
+        match event {
+            EditorEvent::InputHandled {
+                text,
+                utf16_range_to_replace,
+            } => recording.steps.push(MacroStep::Insertion {
+                text: text.clone(),
+                utf16_range_to_replace: utf16_range_to_replace.clone(),
+            }),
+            EditorEvent::InputIgnored { text } => recording.steps.push(MacroStep::Insertion {
+                text: text.clone(),
+                utf16_range_to_replace: None,
+            }),
+            _ => {}
+        }
+    }
+}
+
+pub fn init(cx: &mut App) {
+    cx.set_global(MacroRecorder::default());
+
+    cx.observe_action_dispatch(MacroRecorder::observe_action)
+        .detach();
+
+    cx.observe_new(|editor: &mut Editor, _, cx| {
+        if !editor.mode().is_full() {
+            return;
+        }
+        let handle = cx.entity().downgrade();
+        editor
+            .register_action({
+                let handle = handle.clone();
+                move |_: &ToggleRecording, window, cx| {
+                    if let Some(editor) = handle.upgrade() {
+                        toggle_recording(editor, window, cx);
+                    }
+                }
+            })
+            .detach();
+        editor
+            .register_action({
+                let handle = handle.clone();
+                move |action: &ReplayLastMacro, window, cx| {
+                    if let Some(editor) = handle.upgrade() {
+                        replay_last_macro(editor, action.times, action.target, window, cx);
+                    }
+                }
+            })
+            .detach();
+        editor
+            .register_action({
+                let handle = handle.clone();
+                move |_: &ReplayLastMacroOnEachLine, window, cx| {
+                    if let Some(editor) = handle.upgrade() {
+                        replay_last_macro(editor, 1, ReplayTarget::EachLine, window, cx);
+                    }
+                }
+            })
+            .detach();
+        editor
+            .register_action({
+                let handle = handle.clone();
+                move |_: &ReplayLastMacroOnEachMatch, window, cx| {
+                    if let Some(editor) = handle.upgrade() {
+                        replay_last_macro(editor, 1, ReplayTarget::EachMatch, window, cx);
+                    }
+                }
+            })
+            .detach();
+        editor
+            .register_action({
+                let handle = handle.clone();
+                move |action: &RunMacro, window, cx| {
+                    if let Some(editor) = handle.upgrade() {
+                        run_macro(
+                            editor,
+                            &action.name,
+                            action.times,
+                            action.target,
+                            window,
+                            cx,
+                        );
+                    }
+                }
+            })
+            .detach();
+        editor
+            .register_action({
+                let handle = handle.clone();
+                move |_: &SaveLastMacro, window, cx| {
+                    if let Some(editor) = handle.upgrade() {
+                        save_last_macro(editor, window, cx);
+                    }
+                }
+            })
+            .detach();
+        editor
+            .register_action(move |_: &ShowMacros, window, cx| {
+                if let Some(editor) = handle.upgrade() {
+                    show_macros(editor, window, cx);
+                }
+            })
+            .detach();
+    })
+    .detach();
+}
+
+fn toggle_recording(editor: Entity<Editor>, window: &mut Window, cx: &mut App) {
+    if stop_recording(cx) {
+        return;
+    }
+    let subscription = cx.subscribe(&editor, |_, event: &EditorEvent, cx| {
+        cx.update_global(|recorder: &mut MacroRecorder, _| recorder.observe_editor_event(event));
+    });
+    cx.global_mut::<MacroRecorder>().recording = Some(Recording {
+        editor: editor.downgrade(),
+        window: window.window_handle(),
+        steps: Vec::new(),
+        _subscription: subscription,
+    });
+}
+
+/// Stops recording, keeping what was recorded as the last macro, and returns
+/// whether a macro was being recorded.
+fn stop_recording(cx: &mut App) -> bool {
+    if !MacroRecorder::is_recording(cx) {
+        return false;
+    }
+    let recorder = cx.global_mut::<MacroRecorder>();
+    if let Some(recording) = recorder.recording.take()
+        && !recording.steps.is_empty()
+    {
+        recorder.last_macro = Some(recording.steps.into());
+    }
+    true
+}
+
+fn replay_last_macro(
+    editor: Entity<Editor>,
+    times: usize,
+    target: ReplayTarget,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    // Replaying while recording records the macro's steps into the new one,
+    // which is rarely intended, so the recording is finished first.
+    stop_recording(cx);
+    let Some(steps) = MacroRecorder::last_macro(cx) else {
+        show_toast(&editor, "No macro has been recorded", cx);
+        return;
+    };
+    replay(editor, &steps, times, target, window, cx);
+}
+
+fn run_macro(
+    editor: Entity<Editor>,
+    name: &str,
+    times: usize,
+    target: ReplayTarget,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    match load_macro(name, cx) {
+        Ok(steps) => replay(editor, &steps, times, target, window, cx),
+        Err(error) => show_toast(&editor, format!("{error:#}"), cx),
+    }
+}
+
+fn load_macro(name: &str, cx: &App) -> Result<Vec<MacroStep>> {
+    EditorMacrosSettings::get_global(cx)
+        .macros
+        .get(name)
+        .with_context(|| format!("No macro named {name:?} in the settings"))?
+        .iter()
+        .map(|step| MacroStep::from_content(step, cx))
+        .collect::<Result<Vec<_>>>()
+        .with_context(|| format!("Macro {name:?} is invalid"))
+}
+
+fn replay(
+    editor: Entity<Editor>,
+    steps: &[MacroStep],
+    times: usize,
+    target: ReplayTarget,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let positions = match target {
+        ReplayTarget::Selections => None,
+        ReplayTarget::EachLine => Some(editor.update(cx, |editor, cx| {
+            let display_snapshot = editor.display_snapshot(cx);
+            let buffer = display_snapshot.buffer_snapshot();
+            let mut rows = editor
+                .selections
+                .all::<Point>(&display_snapshot)
+                .into_iter()
+                .flat_map(|selection| {
+                    let mut end_row = selection.end.row;
+                    // A selection ending at the start of a line doesn't
+                    // include that line.
+                    if selection.end.column == 0 && end_row > selection.start.row {
+                        end_row -= 1;
+                    }
+                    selection.start.row..=end_row
+                })
+                .collect::<Vec<_>>();
+            rows.dedup();
+            rows.into_iter()
+                .map(|row| {
+                    let anchor = buffer.anchor_before(Point::new(row, 0));
+                    anchor..anchor
+                })
+                .collect::<Vec<_>>()
+        })),
+        ReplayTarget::EachMatch => Some(editor.update(cx, |editor, cx| {
+            let mut matches = editor.get_matches(window, cx);
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            matches.sort_by(|a, b| a.start.cmp(&b.start, &buffer));
+            matches
+        })),
+    };
+
+    let mut replay_steps = Vec::new();
+    let push_steps = |replay_steps: &mut Vec<ReplayStep>| {
+        for _ in 0..times {
+            replay_steps.extend(steps.iter().cloned().map(ReplayStep::Step));
+        }
+    };
+    match positions {
+        None => push_steps(&mut replay_steps),
+        Some(positions) if positions.is_empty() => {
+            let message = match target {
+                ReplayTarget::EachMatch => "There are no search matches to replay the macro at",
+                _ => "There are no lines to replay the macro at",
+            };
+            show_toast(&editor, message, cx);
+            return;
+        }
+        Some(positions) => {
+            for position in positions {
+                replay_steps.push(ReplayStep::Select(position));
+                push_steps(&mut replay_steps);
+            }
+        }
+    }
+
+    if let Some(replayer) = cx.global::<MacroRecorder>().replayer.clone()
+        && replayer.window() == window.window_handle()
+    {
+        replayer.insert(replay_steps);
+        return;
+    }
+    let replayer = Replayer::new(editor, replay_steps, window);
+    cx.global_mut::<MacroRecorder>().replayer = Some(replayer.clone());
+    replayer.start(window, cx);
+}
+
+fn save_last_macro(editor: Entity<Editor>, window: &mut Window, cx: &mut App) {
+    let Some(steps) = MacroRecorder::last_macro(cx) else {
+        show_toast(&editor, "No macro has been recorded", cx);
+        return;
+    };
+    let steps = match steps
+        .iter()
+        .map(|step| step.to_content(cx))
+        .collect::<Result<Vec<_>>>()
+    {
+        Ok(steps) => steps,
+        Err(error) => {
+            show_toast(&editor, format!("The macro can't be saved: {error:#}"), cx);
+            return;
+        }
+    };
+    let Some(workspace) = editor.read(cx).workspace() else {
+        return;
+    };
+    workspace.update(cx, |workspace, cx| {
+        workspace.toggle_modal(window, cx, |window, cx| {
+            SaveMacroModal::new(steps, window, cx)
+        });
+    });
+}
+
+fn show_macros(editor: Entity<Editor>, window: &mut Window, cx: &mut App) {
+    let Some(workspace) = editor.read(cx).workspace() else {
+        return;
+    };
+    let delegate = MacroPickerDelegate::new(editor.downgrade(), cx);
+    workspace.update(cx, |workspace, cx| {
+        workspace.toggle_modal(window, cx, |window, cx| {
+            Picker::uniform_list(delegate, window, cx)
+                .width(rems(34.))
+                .modal(true)
+        });
+    });
+}
+
+fn show_toast(editor: &Entity<Editor>, message: impl Into<String>, cx: &mut App) {
+    let Some(workspace) = editor.read(cx).workspace() else {
+        return;
+    };
+    let message = message.into();
+    workspace.update(cx, |workspace: &mut Workspace, cx| {
+        workspace.show_toast(
+            Toast::new(NotificationId::unique::<MacroRecorder>(), message).autohide(),
+            cx,
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use command_palette::CommandPalette;
+    use editor::{
+        MultiBufferOffset,
+        actions::{MoveDown, MoveLeft},
+        test::editor_test_context::EditorTestContext,
+    };
+    use gpui::{KeyBinding, TestAppContext, VisualTestContext};
+    use project::Project;
+    use settings::{MacroStepContent, SettingsStore};
+    use workspace::AppState;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            editor::init(cx);
+            crate::init(cx);
+            cx.bind_keys([
+                KeyBinding::new("left", MoveLeft, None),
+                KeyBinding::new("down", MoveDown, None),
+            ]);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_record_and_replay(cx: &mut TestAppContext) {
+        init_test(cx);
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state("ˇa\nb\nc\nd\ne\n");
+
+        cx.dispatch_action(ToggleRecording);
+        cx.simulate_keystrokes("* left down");
+        cx.dispatch_action(ToggleRecording);
+        cx.assert_editor_state("*a\nˇb\nc\nd\ne\n");
+        assert_eq!(
+            cx.read(|cx| MacroRecorder::last_macro(cx).map(|steps| steps.len())),
+            Some(3)
+        );
+
+        cx.dispatch_action(ReplayLastMacro {
+            times: 1,
+            target: ReplayTarget::Selections,
+        });
+        cx.run_until_parked();
+        cx.assert_editor_state("*a\n*b\nˇc\nd\ne\n");
+
+        cx.dispatch_action(ReplayLastMacro {
+            times: 2,
+            target: ReplayTarget::Selections,
+        });
+        cx.run_until_parked();
+        cx.assert_editor_state("*a\n*b\n*c\n*d\nˇe\n");
+    }
+
+    #[gpui::test]
+    async fn test_record_actions_without_keystrokes(cx: &mut TestAppContext) {
+        init_test(cx);
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state("ˇa\nb\nc\n");
+
+        // Actions run from the command palette or a menu are recorded too.
+        cx.dispatch_action(ToggleRecording);
+        cx.dispatch_action(MoveDown);
+        cx.dispatch_action(ToggleRecording);
+        cx.assert_editor_state("a\nˇb\nc\n");
+
+        cx.dispatch_action(ReplayLastMacro {
+            times: 1,
+            target: ReplayTarget::Selections,
+        });
+        cx.run_until_parked();
+        cx.assert_editor_state("a\nb\nˇc\n");
+    }
+
+    #[gpui::test]
+    async fn test_record_action_from_command_palette(cx: &mut TestAppContext) {
+        let app_state = cx.update(|cx| {
+            let app_state = AppState::test(cx);
+            editor::init(cx);
+            menu::init();
+            workspace::init(app_state.clone(), cx);
+            command_palette::init(cx);
+            crate::init(cx);
+            cx.bind_keys([
+                KeyBinding::new("cmd-shift-p", zed_actions::command_palette::Toggle, None),
+                KeyBinding::new("enter", menu::Confirm, None),
+            ]);
+            app_state
+        });
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let editor = cx.new_window_entity(|window, cx| {
+            let mut editor = Editor::multi_line(window, cx);
+            editor.set_text("a\nb\nc\n", window, cx);
+            editor
+        });
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, window, cx);
+            window.focus(&editor.focus_handle(cx));
+        });
+        let cursor_row = |cx: &mut VisualTestContext| {
+            editor.update(cx, |editor, cx| {
+                editor
+                    .selections
+                    .newest::<Point>(&editor.display_snapshot(cx))
+                    .head()
+                    .row
+            })
+        };
+
+        // Opening the palette moves focus out of the editor, so only the
+        // action chosen in it is recorded.
+        cx.dispatch_action(ToggleRecording);
+        cx.simulate_keystrokes("cmd-shift-p");
+        cx.simulate_input("editor: move down");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.dispatch_action(ToggleRecording);
+        assert_eq!(cursor_row(cx), 1);
+        let steps = cx.read(|cx| MacroRecorder::last_macro(cx)).unwrap();
+        assert!(
+            matches!(&*steps, [MacroStep::Action(action)] if action.partial_eq(&MoveDown)),
+            "unexpected steps: {steps:?}"
+        );
+
+        cx.dispatch_action(ReplayLastMacro {
+            times: 1,
+            target: ReplayTarget::Selections,
+        });
+        cx.run_until_parked();
+        assert_eq!(cursor_row(cx), 2);
+        workspace.read_with(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<CommandPalette>(cx).is_none());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_replay_on_each_line(cx: &mut TestAppContext) {
+        init_test(cx);
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state("ˇone\ntwo\nthree\nfour\n");
+
+        cx.dispatch_action(ToggleRecording);
+        cx.simulate_keystrokes("> space");
+        cx.dispatch_action(ToggleRecording);
+        cx.assert_editor_state("> ˇone\ntwo\nthree\nfour\n");
+
+        // A selection that ends at the start of a line doesn't include it.
+        cx.set_selections_state("> one\n«two\nthree\nˇ»four\n");
+        cx.dispatch_action(ReplayLastMacroOnEachLine);
+        cx.run_until_parked();
+        cx.assert_editor_state("> one\n> two\n> ˇthree\nfour\n");
+    }
+
+    #[gpui::test]
+    async fn test_replay_on_each_match(cx: &mut TestAppContext) {
+        init_test(cx);
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state("«fooˇ» bar foo baz foo\n");
+
+        cx.dispatch_action(ToggleRecording);
+        cx.simulate_keystrokes("q u x");
+        cx.dispatch_action(ToggleRecording);
+        cx.assert_editor_state("quxˇ bar foo baz foo\n");
+
+        let ranges = cx.ranges("qux bar «foo» baz «foo»\n");
+        cx.update_editor(|editor, window, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let matches = ranges
+                .into_iter()
+                .map(|range| {
+                    buffer.anchor_after(MultiBufferOffset(range.start))
+                        ..buffer.anchor_before(MultiBufferOffset(range.end))
+                })
+                .collect::<Vec<_>>();
+            editor.update_matches(&matches, window, cx);
+        });
+        cx.dispatch_action(ReplayLastMacroOnEachMatch);
+        cx.run_until_parked();
+        cx.assert_editor_state("qux bar qux baz quxˇ\n");
+    }
+
+    #[gpui::test]
+    async fn test_macro_running_itself(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings(cx, |settings| {
+                settings.macros = Some(
+                    [(
+                        "again".to_string(),
+                        vec![
+                            MacroStepContent::Text { text: "x".into() },
+                            MacroStepContent::ActionWithArguments(
+                                "editor_macros::RunMacro".into(),
+                                serde_json::json!({ "name": "again" }),
+                            ),
+                        ],
+                    )]
+                    .into_iter()
+                    .collect(),
+                );
+            });
+        });
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state("ˇ\n");
+
+        // The macro runs itself until the replay is stopped, after it has
+        // inserted text for half of the steps.
+        cx.dispatch_action(RunMacro {
+            name: "again".into(),
+            times: 1,
+            target: ReplayTarget::Selections,
+        });
+        cx.run_until_parked();
+        assert_eq!(cx.buffer_text(), "x".repeat(MAX_REPLAY_STEPS / 2) + "\n");
+        assert!(cx.read(|cx| cx.global::<MacroRecorder>().replayer.is_none()));
+    }
+}
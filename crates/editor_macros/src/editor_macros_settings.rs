@@ -0,0 +1,17 @@
+use collections::HashMap;
+use settings::{MacroStepContent, RegisterSetting, Settings};
+
+/// The editor macros saved in the settings.
+#[derive(Clone, Debug, Default, RegisterSetting)]
+pub struct EditorMacrosSettings {
+    /// The steps of each macro, by name.
+    pub macros: HashMap<String, Vec<MacroStepContent>>,
+}
+
+impl Settings for EditorMacrosSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        Self {
+            macros: content.macros.clone().unwrap(),
+        }
+    }
+}
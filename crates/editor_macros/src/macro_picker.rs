@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use editor::Editor;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{App, Context, DismissEvent, SharedString, Task, WeakEntity, Window};
+use picker::{Picker, PickerDelegate};
+use settings::Settings as _;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+
+use crate::{EditorMacrosSettings, ReplayTarget, run_macro};
+
+pub(crate) struct MacroPickerDelegate {
+    editor: WeakEntity<Editor>,
+    /// The names of the saved macros, and how many steps each has.
+    macros: Vec<(String, usize)>,
+    candidates: Arc<[StringMatchCandidate]>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl MacroPickerDelegate {
+    pub(crate) fn new(editor: WeakEntity<Editor>, cx: &App) -> Self {
+        let mut macros = EditorMacrosSettings::get_global(cx)
+            .macros
+            .iter()
+            .map(|(name, steps)| (name.clone(), steps.len()))
+            .collect::<Vec<_>>();
+        macros.sort();
+        let candidates = macros
+            .iter()
+            .enumerate()
+            .map(|(id, (name, _))| StringMatchCandidate::new(id, name))
+            .collect();
+        Self {
+            editor,
+            macros,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for MacroPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Run a saved macro…".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let candidates = self.candidates.clone();
+        let executor = cx.background_executor().clone();
+        cx.spawn_in(window, async move |picker, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        score: 0.,
+                        positions: Vec::new(),
+                        string: candidate.string.clone(),
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &Default::default(),
+                    executor,
+                )
+                .await
+            };
+            picker
+                .update(cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = 0;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some((name, _)) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|string_match| self.macros.get(string_match.candidate_id))
+        else {
+            return;
+        };
+        let name = name.clone();
+        cx.emit(DismissEvent);
+        // The macro is run once the editor is focused again.
+        if let Some(editor) = self.editor.upgrade() {
+            window.defer(cx, move |window, cx| {
+                run_macro(editor, &name, 1, ReplayTarget::Selections, window, cx);
+            });
+        }
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let string_match = self.matches.get(ix)?;
+        let (_, step_count) = self.macros.get(string_match.candidate_id)?;
+        let steps = Label::new(match step_count {
+            1 => SharedString::from("1 step"),
+            count => format!("{count} steps").into(),
+        })
+        .color(Color::Muted)
+        .size(LabelSize::Small);
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    HighlightedLabel::new(
+                        string_match.string.clone(),
+                        string_match.positions.clone(),
+                    )
+                    .truncate(),
+                )
+                .end_slot(steps),
+        )
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No macros saved in the settings".into())
+    }
+}
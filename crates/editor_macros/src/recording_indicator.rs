@@ -0,0 +1,43 @@
+use gpui::{Context, Render, Window};
+use ui::{Tooltip, prelude::*};
+use workspace::{StatusItemView, item::ItemHandle};
+
+use crate::{MacroRecorder, ToggleRecording};
+
+/// Shows in the status bar that a macro is being recorded.
+pub struct MacroRecordingIndicator;
+
+impl MacroRecordingIndicator {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<MacroRecorder>(|_, cx| cx.notify())
+            .detach();
+        Self
+    }
+}
+
+impl Render for MacroRecordingIndicator {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !MacroRecorder::is_recording(cx) {
+            return div().into_any_element();
+        }
+        Button::new("macro-recording", "Recording Macro")
+            .label_size(LabelSize::Small)
+            .icon(IconName::Circle)
+            .icon_size(IconSize::XSmall)
+            .icon_color(Color::Error)
+            .icon_position(IconPosition::Start)
+            .tooltip(|_window, cx| Tooltip::for_action("Stop Recording", &ToggleRecording, cx))
+            .on_click(|_, window, cx| window.dispatch_action(Box::new(ToggleRecording), cx))
+            .into_any_element()
+    }
+}
+
+impl StatusItemView for MacroRecordingIndicator {
+    fn set_active_pane_item(
+        &mut self,
+        _active_pane_item: Option<&dyn ItemHandle>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) {
+    }
+}
@@ -0,0 +1,109 @@
+use std::{cell::RefCell, collections::VecDeque, ops::Range, rc::Rc};
+
+use editor::{Anchor, Editor, SelectionEffects};
+use gpui::{AnyWindowHandle, App, Entity, Focusable as _, Window};
+
+use crate::{MAX_REPLAY_STEPS, MacroRecorder, MacroStep};
+
+pub(crate) enum ReplayStep {
+    Step(MacroStep),
+    /// Selects the given range before replaying the macro at the next place.
+    Select(Range<Anchor>),
+}
+
+struct ReplayerState {
+    editor: Entity<Editor>,
+    window: AnyWindowHandle,
+    steps: VecDeque<ReplayStep>,
+    replayed: usize,
+}
+
+/// Replays the steps of a macro one at a time, letting the effects of each
+/// one, such as focus changes, settle before the next.
+#[derive(Clone)]
+pub(crate) struct Replayer(Rc<RefCell<ReplayerState>>);
+
+impl Replayer {
+    pub(crate) fn new(editor: Entity<Editor>, steps: Vec<ReplayStep>, window: &Window) -> Self {
+        Self(Rc::new(RefCell::new(ReplayerState {
+            editor,
+            window: window.window_handle(),
+            steps: steps.into(),
+            replayed: 0,
+        })))
+    }
+
+    pub(crate) fn window(&self) -> AnyWindowHandle {
+        self.0.borrow().window
+    }
+
+    /// Replays the given steps before the remaining ones, as when a macro
+    /// runs another macro.
+    pub(crate) fn insert(&self, steps: Vec<ReplayStep>) {
+        let mut state = self.0.borrow_mut();
+        for step in steps.into_iter().rev() {
+            state.steps.push_front(step);
+        }
+    }
+
+    pub(crate) fn start(self, window: &mut Window, cx: &mut App) {
+        window.defer(cx, move |window, cx| self.next(window, cx));
+    }
+
+    fn next(self, window: &mut Window, cx: &mut App) {
+        let mut state = self.0.borrow_mut();
+        let step = if state.replayed < MAX_REPLAY_STEPS {
+            state.steps.pop_front()
+        } else {
+            log::error!("Aborting macro replay after {MAX_REPLAY_STEPS} steps");
+            None
+        };
+        state.replayed += 1;
+        let editor = state.editor.clone();
+        drop(state);
+
+        let Some(step) = step else {
+            self.finish(cx);
+            return;
+        };
+        match step {
+            ReplayStep::Step(MacroStep::Action(action)) => {
+                window.dispatch_action(action, cx);
+            }
+            ReplayStep::Step(MacroStep::Insertion {
+                text,
+                utf16_range_to_replace,
+            }) => {
+                editor.update(cx, |editor, cx| {
+                    editor.replay_insert_event(&text, utf16_range_to_replace, window, cx)
+                });
+            }
+            ReplayStep::Select(range) => {
+                window.focus(&editor.focus_handle(cx));
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(SelectionEffects::default(), window, cx, |s| {
+                        s.select_anchor_ranges([range])
+                    });
+                });
+            }
+        }
+        window.defer(cx, move |window, cx| self.next(window, cx));
+    }
+
+    fn finish(self, cx: &mut App) {
+        let editor = self.0.borrow().editor.clone();
+        editor.update(cx, |editor, cx| {
+            editor
+                .buffer()
+                .update(cx, |buffer, cx| buffer.finalize_last_transaction(cx))
+        });
+        let recorder = cx.global_mut::<MacroRecorder>();
+        if recorder
+            .replayer
+            .as_ref()
+            .is_some_and(|replayer| Rc::ptr_eq(&replayer.0, &self.0))
+        {
+            recorder.replayer = None;
+        }
+    }
+}
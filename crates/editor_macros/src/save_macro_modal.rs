@@ -0,0 +1,100 @@
+use editor::Editor;
+use fs::Fs;
+use gpui::{
+    App, AppContext as _, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    Subscription, Window,
+};
+use settings::{MacroStepContent, Settings as _, update_settings_file};
+use ui::prelude::*;
+use workspace::ModalView;
+
+use crate::EditorMacrosSettings;
+
+pub(crate) struct SaveMacroModal {
+    steps: Vec<MacroStepContent>,
+    editor: Entity<Editor>,
+    _editor_subscription: Subscription,
+}
+
+impl EventEmitter<DismissEvent> for SaveMacroModal {}
+impl ModalView for SaveMacroModal {}
+
+impl Focusable for SaveMacroModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl SaveMacroModal {
+    pub(crate) fn new(
+        steps: Vec<MacroStepContent>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Macro name", window, cx);
+            editor
+        });
+        // The message below the name depends on whether it's taken.
+        let editor_subscription = cx.observe(&editor, |_, _, cx| cx.notify());
+        Self {
+            steps,
+            editor,
+            _editor_subscription: editor_subscription,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _window: &mut Window, cx: &mut Context<Self>) {
+        let name = self.editor.read(cx).text(cx).trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let steps = std::mem::take(&mut self.steps);
+        update_settings_file(<dyn Fs>::global(cx), cx, move |settings, _| {
+            settings.macros.get_or_insert_default().insert(name, steps);
+        });
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for SaveMacroModal {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let name = self.editor.read(cx).text(cx);
+        let message = if EditorMacrosSettings::get_global(cx)
+            .macros
+            .contains_key(name.trim())
+        {
+            format!("Replace the macro named {:?} in the settings", name.trim())
+        } else {
+            format!(
+                "Save the last recorded macro, with {} steps, in the settings",
+                self.steps.len()
+            )
+        };
+        v_flex()
+            .w(rems(24.))
+            .elevation_2(cx)
+            .key_context("SaveMacro")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .child(Label::new(message).color(Color::Muted)),
+            )
+    }
+}
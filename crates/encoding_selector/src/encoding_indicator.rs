@@ -0,0 +1,72 @@
+use editor::Editor;
+use gpui::{Entity, Subscription, WeakEntity};
+use language::Encoding;
+use ui::{Tooltip, prelude::*};
+use workspace::{StatusBarSettings, StatusItemView, item::ItemHandle, item::Settings};
+
+use crate::{EncodingAction, EncodingSelector, ReopenWithEncoding};
+
+#[derive(Default)]
+pub struct EncodingIndicator {
+    encoding: Option<Encoding>,
+    active_editor: Option<WeakEntity<Editor>>,
+    _observe_active_editor: Option<Subscription>,
+}
+
+impl EncodingIndicator {
+    fn update(&mut self, editor: Entity<Editor>, _: &mut Window, cx: &mut Context<Self>) {
+        self.encoding = None;
+        self.active_editor = None;
+
+        if let Some((_, buffer, _)) = editor.read(cx).active_excerpt(cx) {
+            let encoding = buffer.read(cx).encoding();
+            self.encoding = Some(encoding);
+            self.active_editor = Some(editor.downgrade());
+        }
+
+        cx.notify();
+    }
+}
+
+impl Render for EncodingIndicator {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !StatusBarSettings::get_global(cx).encoding_button {
+            return div();
+        }
+
+        // Most files are UTF-8, so the encoding is only worth showing when it isn't.
+        let encoding = self.encoding.filter(|encoding| !encoding.is_utf8());
+        div().when_some(encoding, |el, encoding| {
+            el.child(
+                Button::new("change-encoding", encoding.label())
+                    .label_size(LabelSize::Small)
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        if let Some(editor) = this.active_editor.as_ref() {
+                            EncodingSelector::toggle(editor, EncodingAction::Reopen, window, cx);
+                        }
+                    }))
+                    .tooltip(|_window, cx| {
+                        Tooltip::for_action("Reopen with Encoding", &ReopenWithEncoding, cx)
+                    }),
+            )
+        })
+    }
+}
+
+impl StatusItemView for EncodingIndicator {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(editor) = active_pane_item.and_then(|item| item.downcast::<Editor>()) {
+            self._observe_active_editor = Some(cx.observe_in(&editor, window, Self::update));
+            self.update(editor, window, cx);
+        } else {
+            self.encoding = None;
+            self._observe_active_editor = None;
+        }
+        cx.notify();
+    }
+}
@@ -0,0 +1,300 @@
+mod encoding_indicator;
+
+use collections::HashSet;
+use editor::Editor;
+pub use encoding_indicator::EncodingIndicator;
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task, WeakEntity, actions};
+use language::{Buffer, Encoding};
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use std::sync::Arc;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt;
+use workspace::{ModalView, notifications::DetachAndPromptErr as _};
+
+actions!(
+    encoding_selector,
+    [
+        /// Reopens the current file, decoding it with a different encoding.
+        ReopenWithEncoding,
+        /// Saves the current file with a different encoding.
+        SaveWithEncoding
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(EncodingSelector::register).detach();
+}
+
+/// What to do with the encoding picked in the selector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncodingAction {
+    Reopen,
+    Save,
+}
+
+pub struct EncodingSelector {
+    picker: Entity<Picker<EncodingSelectorDelegate>>,
+}
+
+impl EncodingSelector {
+    fn register(editor: &mut Editor, _window: Option<&mut Window>, cx: &mut Context<Editor>) {
+        let editor_handle = cx.weak_entity();
+        editor
+            .register_action({
+                let editor_handle = editor_handle.clone();
+                move |_: &ReopenWithEncoding, window, cx| {
+                    Self::toggle(&editor_handle, EncodingAction::Reopen, window, cx);
+                }
+            })
+            .detach();
+        editor
+            .register_action(move |_: &SaveWithEncoding, window, cx| {
+                Self::toggle(&editor_handle, EncodingAction::Save, window, cx);
+            })
+            .detach();
+    }
+
+    pub fn toggle(
+        editor: &WeakEntity<Editor>,
+        action: EncodingAction,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let Some((workspace, buffer)) = editor
+            .update(cx, |editor, cx| {
+                Some((editor.workspace()?, editor.active_excerpt(cx)?.1))
+            })
+            .ok()
+            .flatten()
+        else {
+            return;
+        };
+
+        workspace.update(cx, |workspace, cx| {
+            let project = workspace.project().clone();
+            workspace.toggle_modal(window, cx, move |window, cx| {
+                EncodingSelector::new(buffer, project, action, window, cx)
+            });
+        })
+    }
+
+    fn new(
+        buffer: Entity<Buffer>,
+        project: Entity<Project>,
+        action: EncodingAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate =
+            EncodingSelectorDelegate::new(cx.entity().downgrade(), buffer, project, action, cx);
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        Self { picker }
+    }
+}
+
+impl Render for EncodingSelector {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl Focusable for EncodingSelector {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for EncodingSelector {}
+impl ModalView for EncodingSelector {}
+
+struct EncodingSelectorDelegate {
+    encoding_selector: WeakEntity<EncodingSelector>,
+    buffer: Entity<Buffer>,
+    project: Entity<Project>,
+    action: EncodingAction,
+    encoding: Encoding,
+    encodings: Vec<Encoding>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl EncodingSelectorDelegate {
+    fn new(
+        encoding_selector: WeakEntity<EncodingSelector>,
+        buffer: Entity<Buffer>,
+        project: Entity<Project>,
+        action: EncodingAction,
+        cx: &App,
+    ) -> Self {
+        let encoding = buffer.read(cx).encoding();
+        let mut encodings = Encoding::all();
+        if !encodings.contains(&encoding) {
+            encodings.push(encoding);
+        }
+        let candidates = encodings
+            .iter()
+            .enumerate()
+            .map(|(id, encoding)| StringMatchCandidate::new(id, encoding.label()))
+            .collect();
+        let selected_index = encodings
+            .iter()
+            .position(|candidate| *candidate == encoding)
+            .unwrap_or(0);
+        Self {
+            encoding_selector,
+            buffer,
+            project,
+            action,
+            encoding,
+            encodings,
+            candidates,
+            matches: Vec::new(),
+            selected_index,
+        }
+    }
+}
+
+impl PickerDelegate for EncodingSelectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match self.action {
+            EncodingAction::Reopen => "Reopen with encoding…".into(),
+            EncodingAction::Save => "Save with encoding…".into(),
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let encoding = self.encodings[mat.candidate_id];
+            let previous_encoding = self.encoding;
+            let buffer = self.buffer.clone();
+            let project = self.project.clone();
+            let action = self.action;
+            buffer.update(cx, |buffer, cx| buffer.set_encoding(encoding, cx));
+
+            let task = project.update(cx, |project, cx| match action {
+                EncodingAction::Reopen => {
+                    let reload =
+                        project.reload_buffers(HashSet::from_iter([buffer.clone()]), true, cx);
+                    cx.background_spawn(async move { reload.await.map(|_| ()) })
+                }
+                EncodingAction::Save => project.save_buffer(buffer.clone(), cx),
+            });
+            let message = match action {
+                EncodingAction::Reopen => format!("Failed to reopen with {}", encoding.label()),
+                EncodingAction::Save => format!("Failed to save with {}", encoding.label()),
+            };
+            window
+                .spawn(cx, async move |cx| {
+                    let result = task.await;
+                    if result.is_err() {
+                        // Keep the encoding that the file can actually be read and written with.
+                        buffer
+                            .update(cx, |buffer, cx| buffer.set_encoding(previous_encoding, cx))
+                            .ok();
+                    }
+                    result
+                })
+                .detach_and_prompt_err(&message, window, cx, |_, _, _| None);
+        }
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.encoding_selector
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let encoding = self.encodings.get(mat.candidate_id)?;
+
+        let mut list_item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(selected)
+            .child(HighlightedLabel::new(
+                mat.string.clone(),
+                mat.positions.clone(),
+            ));
+
+        if &self.encoding == encoding {
+            list_item = list_item.end_slot(Icon::new(IconName::Check).color(Color::Muted));
+        }
+
+        Some(list_item)
+    }
+}
@@ -8,26 +8,23 @@ mod tool_metrics;
 
 use assertions::{AssertionsReport, display_error_row};
 use instance::{ExampleInstance, JudgeOutput, RunOutput, run_git};
-use language_extension::LspAccess;
 pub(crate) use tool_metrics::*;
 
-use ::fs::RealFs;
 use clap::Parser;
-use client::{Client, ProxySettings, UserStore};
+use client::{Client, UserStore};
 use collections::{HashMap, HashSet};
-use extension::ExtensionHostProxy;
 use futures::future;
-use gpui::http_client::read_proxy_from_env;
-use gpui::{App, AppContext, Application, AsyncApp, Entity, UpdateGlobal};
-use gpui_tokio::Tokio;
+use gpui::{App, Application, AsyncApp, Entity, UpdateGlobal};
+use headless::HeadlessAppState;
 use language::LanguageRegistry;
+use language_model::replay_provider::{
+    RecordingLanguageModel, ReplayLanguageModelProvider, RequestMatching,
+};
 use language_model::{ConfiguredModel, LanguageModel, LanguageModelRegistry, SelectedModel};
-use node_runtime::{NodeBinaryOptions, NodeRuntime};
-use project::project_settings::ProjectSettings;
+use node_runtime::NodeRuntime;
 use prompt_store::PromptBuilder;
-use release_channel::{AppCommitSha, AppVersion};
 use reqwest_client::ReqwestClient;
-use settings::{Settings, SettingsStore};
+use settings::SettingsStore;
 use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::env;
@@ -35,7 +32,6 @@ use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::{Arc, LazyLock};
-use util::ResultExt as _;
 
 static CARGO_MANIFEST_DIR: LazyLock<PathBuf> =
     LazyLock::new(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")));
@@ -63,6 +59,13 @@ struct Args {
     /// Output current environment variables as JSON to stdout
     #[arg(long, hide = true)]
     printenv: bool,
+    /// Record the agent's and judge's completions to cassettes in this directory.
+    #[arg(long, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+    /// Serve completions from the cassettes in this directory instead of
+    /// calling the model providers.
+    #[arg(long, value_name = "DIR")]
+    replay: Option<PathBuf>,
 }
 
 fn main() {
@@ -144,8 +147,37 @@ fn main() {
         cx.spawn(async move |cx| {
             future::join_all(tasks).await;
             let judge_model = cx.update(|cx| {
-                let agent_model = load_model(&args.model, cx).unwrap();
-                let judge_model = load_model(&args.judge_model, cx).unwrap();
+                let (mut agent_model, mut judge_model) = if let Some(replay_dir) = &args.replay {
+                    let provider = Arc::new(
+                        ReplayLanguageModelProvider::load_dir(
+                            replay_dir,
+                            RequestMatching::default(),
+                        )
+                        .unwrap(),
+                    );
+                    LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
+                        registry.register_provider(provider.clone(), cx);
+                    });
+                    (
+                        load_replay_model(&args.model, &provider).unwrap(),
+                        load_replay_model(&args.judge_model, &provider).unwrap(),
+                    )
+                } else {
+                    (
+                        load_model(&args.model, cx).unwrap(),
+                        load_model(&args.judge_model, cx).unwrap(),
+                    )
+                };
+                if let Some(record_dir) = &args.record {
+                    let same_model = agent_model.is_same_as(&judge_model);
+                    agent_model = record_model(agent_model, record_dir);
+                    // Both must share a recorder, as they'd write the same cassette.
+                    judge_model = if same_model {
+                        agent_model.clone()
+                    } else {
+                        record_model(judge_model, record_dir)
+                    };
+                }
                 LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
                     registry.set_default_model(Some(agent_model.clone()), cx);
                 });
@@ -347,91 +379,14 @@ pub struct AgentAppState {
 }
 
 pub fn init(cx: &mut App) -> Arc<AgentAppState> {
-    let app_commit_sha = option_env!("ZED_COMMIT_SHA").map(|s| AppCommitSha::new(s.to_owned()));
-
-    let app_version = AppVersion::load(
-        env!("ZED_PKG_VERSION"),
-        option_env!("ZED_BUILD_ID"),
-        app_commit_sha,
-    );
-
-    release_channel::init(app_version.clone(), cx);
-    gpui_tokio::init(cx);
-
-    let settings_store = SettingsStore::new(cx, &settings::default_settings());
-    cx.set_global(settings_store);
+    let HeadlessAppState {
+        languages,
+        client,
+        user_store,
+        fs,
+        node_runtime,
+    } = headless::init("Zed Agent Eval", env!("ZED_PKG_VERSION"), cx);
 
-    // Set User-Agent so we can download language servers from GitHub
-    let user_agent = format!(
-        "Zed Agent Eval/{} ({}; {})",
-        app_version,
-        std::env::consts::OS,
-        std::env::consts::ARCH
-    );
-    let proxy_str = ProxySettings::get_global(cx).proxy.to_owned();
-    let proxy_url = proxy_str
-        .as_ref()
-        .and_then(|input| input.parse().ok())
-        .or_else(read_proxy_from_env);
-    let http = {
-        let _guard = Tokio::handle(cx).enter();
-
-        ReqwestClient::proxy_and_user_agent(proxy_url, &user_agent)
-            .expect("could not start HTTP client")
-    };
-    cx.set_http_client(Arc::new(http));
-
-    let client = Client::production(cx);
-    cx.set_http_client(client.http_client());
-
-    let git_binary_path = None;
-    let fs = Arc::new(RealFs::new(
-        git_binary_path,
-        cx.background_executor().clone(),
-    ));
-
-    let mut languages = LanguageRegistry::new(cx.background_executor().clone());
-    languages.set_language_server_download_dir(paths::languages_dir().clone());
-    let languages = Arc::new(languages);
-
-    let user_store = cx.new(|cx| UserStore::new(client.clone(), cx));
-
-    extension::init(cx);
-
-    let (mut tx, rx) = watch::channel(None);
-    cx.observe_global::<SettingsStore>(move |cx| {
-        let settings = &ProjectSettings::get_global(cx).node;
-        let options = NodeBinaryOptions {
-            allow_path_lookup: !settings.ignore_system_version,
-            allow_binary_download: true,
-            use_paths: settings.path.as_ref().map(|node_path| {
-                let node_path = PathBuf::from(shellexpand::tilde(node_path).as_ref());
-                let npm_path = settings
-                    .npm_path
-                    .as_ref()
-                    .map(|path| PathBuf::from(shellexpand::tilde(&path).as_ref()));
-                (
-                    node_path.clone(),
-                    npm_path.unwrap_or_else(|| {
-                        let base_path = PathBuf::new();
-                        node_path.parent().unwrap_or(&base_path).join("npm")
-                    }),
-                )
-            }),
-        };
-        tx.send(Some(options)).log_err();
-    })
-    .detach();
-    let node_runtime = NodeRuntime::new(client.http_client(), None, rx);
-
-    let extension_host_proxy = ExtensionHostProxy::global(cx);
-    debug_adapter_extension::init(extension_host_proxy.clone(), cx);
-    language_extension::init(LspAccess::Noop, extension_host_proxy, languages.clone());
-    language_model::init(client.clone(), cx);
-    language_models::init(user_store.clone(), client.clone(), cx);
-    languages::init(languages.clone(), fs.clone(), node_runtime.clone(), cx);
-    prompt_store::init(cx);
-    terminal_view::init(cx);
     let stdout_is_a_pty = false;
     let prompt_builder = PromptBuilder::load(fs.clone(), stdout_is_a_pty, cx);
     agent_ui::init(
@@ -500,6 +455,38 @@ pub fn load_model(model_name: &str, cx: &mut App) -> anyhow::Result<ConfiguredMo
     })
 }
 
+/// Finds the replay of a model recorded as `provider/model`.
+fn load_replay_model(
+    model_name: &str,
+    provider: &Arc<ReplayLanguageModelProvider>,
+) -> anyhow::Result<ConfiguredModel> {
+    let selected = SelectedModel::from_str(model_name).map_err(|e| anyhow::anyhow!(e))?;
+    let model = provider
+        .model(&selected.provider, &selected.model)
+        .ok_or_else(|| anyhow::anyhow!("No cassette was recorded for {model_name}"))?;
+    Ok(ConfiguredModel {
+        provider: provider.clone(),
+        model,
+    })
+}
+
+fn record_model(model: ConfiguredModel, record_dir: &Path) -> ConfiguredModel {
+    let cassette_name = format!(
+        "{}-{}.json",
+        model.model.provider_id().0,
+        model.model.id().0
+    )
+    .replace(['/', '\\', ':'], "-");
+    ConfiguredModel {
+        provider: model.provider,
+        model: Arc::new(RecordingLanguageModel::new(
+            model.model,
+            record_dir.join(cassette_name),
+            RequestMatching::default(),
+        )),
+    }
+}
+
 pub fn commit_sha_for_path(repo_path: &Path) -> String {
     futures::executor::block_on(run_git(repo_path, &["rev-parse", "HEAD"])).unwrap()
 }
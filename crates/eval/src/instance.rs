@@ -560,6 +560,8 @@ impl ExampleInstance {
                 tool_choice: None,
                 stop: Vec::new(),
                 thinking_allowed: true,
+                response_format: None,
+                max_tokens: None,
             };
 
             let model = model.clone();
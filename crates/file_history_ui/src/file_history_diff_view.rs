@@ -0,0 +1,383 @@
+use std::{
+    any::{Any, TypeId},
+    pin::pin,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use buffer_diff::{BufferDiff, BufferDiffSnapshot};
+use editor::{Editor, EditorEvent, MultiBuffer};
+use futures::{FutureExt as _, select_biased};
+use gpui::{
+    AnyElement, App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, FocusHandle,
+    Focusable, IntoElement, Render, Task, WeakEntity, Window,
+};
+use language::{Buffer, Capability};
+use project::{
+    Project,
+    file_history::{FILE_HISTORY, FileSnapshot},
+};
+use time::{OffsetDateTime, UtcOffset};
+use ui::{KeyBinding, Tooltip, prelude::*};
+use util::paths::PathExt as _;
+use workspace::{
+    Item, ItemHandle as _, ItemNavHistory, ToolbarItemLocation, Workspace,
+    item::{BreadcrumbText, ItemEvent, SaveOptions, TabContentParams},
+    notifications::NotifyTaskExt as _,
+    searchable::SearchableItemHandle,
+};
+
+use crate::{RestoreSnapshot, restore_snapshot};
+
+const RECALCULATE_DIFF_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Compares a snapshot from a file's history with the file's current
+/// contents, which can still be edited.
+pub struct FileHistoryDiffView {
+    diff_editor: Entity<Editor>,
+    buffer: Entity<Buffer>,
+    snapshot_text: String,
+    title: SharedString,
+    path: SharedString,
+    created_at: SharedString,
+    buffer_changes_tx: watch::Sender<()>,
+    _recalculate_diff_task: Task<Result<()>>,
+}
+
+impl FileHistoryDiffView {
+    pub fn open(
+        workspace: WeakEntity<Workspace>,
+        buffer: Entity<Buffer>,
+        snapshot: FileSnapshot,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        window.spawn(cx, async move |cx| {
+            let project = workspace.update(cx, |workspace, _| workspace.project().clone())?;
+            let snapshot_text = cx
+                .background_spawn(async move { FILE_HISTORY.snapshot_contents(snapshot.id) })
+                .await?
+                .context("the snapshot no longer exists")?;
+            let language = buffer.read_with(cx, |buffer, _| buffer.language().cloned())?;
+            let base_buffer = cx.new(|cx| {
+                let mut base_buffer = Buffer::local(snapshot_text.clone(), cx);
+                base_buffer.set_language(language, cx);
+                base_buffer.set_capability(Capability::ReadOnly, cx);
+                base_buffer
+            })?;
+            let diff = buffer.update(cx, |buffer, cx| {
+                BufferDiff::new(&buffer.text_snapshot(), cx)
+            })?;
+            update_diff(&diff, &buffer, &base_buffer, cx).await?;
+
+            workspace.update_in(cx, |workspace, window, cx| {
+                let diff_view = cx.new(|cx| {
+                    Self::new(
+                        buffer,
+                        base_buffer,
+                        diff,
+                        snapshot_text,
+                        snapshot,
+                        project,
+                        window,
+                        cx,
+                    )
+                });
+                workspace.active_pane().update(cx, |pane, cx| {
+                    pane.add_item(Box::new(diff_view.clone()), true, true, None, window, cx);
+                });
+                diff_view
+            })
+        })
+    }
+
+    fn new(
+        buffer: Entity<Buffer>,
+        base_buffer: Entity<Buffer>,
+        diff: Entity<BufferDiff>,
+        snapshot_text: String,
+        snapshot: FileSnapshot,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let diff_editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, Some(project), window, cx);
+            editor.start_temporary_diff_override();
+            editor.disable_diagnostics(cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor.set_render_diff_hunk_controls(
+                Arc::new(|_, _, _, _, _, _, _, _| gpui::Empty.into_any_element()),
+                cx,
+            );
+            editor
+        });
+
+        let (buffer_changes_tx, mut buffer_changes_rx) = watch::channel(());
+        cx.subscribe(&buffer, |this, _, event, _| {
+            if let language::BufferEvent::Edited = event {
+                this.buffer_changes_tx.send(()).ok();
+            }
+        })
+        .detach();
+
+        let file_name = buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string())
+            .unwrap_or_else(|| "untitled".into());
+        let path = buffer
+            .read(cx)
+            .file()
+            .map(|file| file.full_path(cx).compact().to_string_lossy().into_owned())
+            .unwrap_or_else(|| "untitled".into());
+        let created_at = OffsetDateTime::from(snapshot.created_at);
+        let timezone = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+        let relative_created_at = time_format::format_localized_timestamp(
+            created_at,
+            OffsetDateTime::now_utc(),
+            timezone,
+            time_format::TimestampFormat::Relative,
+        );
+        let absolute_created_at = time_format::format_localized_timestamp(
+            created_at,
+            OffsetDateTime::now_utc(),
+            timezone,
+            time_format::TimestampFormat::EnhancedAbsolute,
+        );
+
+        Self {
+            diff_editor,
+            buffer: buffer.clone(),
+            snapshot_text,
+            title: format!("{file_name} ({relative_created_at}) ↔ Current").into(),
+            path: format!("{path} ({absolute_created_at}) ↔ Current").into(),
+            created_at: absolute_created_at.into(),
+            buffer_changes_tx,
+            _recalculate_diff_task: cx.spawn(async move |_, cx| {
+                while buffer_changes_rx.recv().await.is_ok() {
+                    loop {
+                        let mut timer = cx
+                            .background_executor()
+                            .timer(RECALCULATE_DIFF_DEBOUNCE)
+                            .fuse();
+                        let mut recv = pin!(buffer_changes_rx.recv().fuse());
+                        select_biased! {
+                            _ = timer => break,
+                            _ = recv => continue,
+                        }
+                    }
+                    update_diff(&diff, &buffer, &base_buffer, cx).await?;
+                }
+                Ok(())
+            }),
+        }
+    }
+
+    fn restore(&mut self, _: &RestoreSnapshot, window: &mut Window, cx: &mut Context<Self>) {
+        restore_snapshot(self.buffer.clone(), self.snapshot_text.clone(), cx)
+            .detach_and_notify_err(window, cx);
+    }
+}
+
+async fn update_diff(
+    diff: &Entity<BufferDiff>,
+    buffer: &Entity<Buffer>,
+    base_buffer: &Entity<Buffer>,
+    cx: &mut AsyncApp,
+) -> Result<()> {
+    let buffer_snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot())?;
+    let base_buffer_snapshot = base_buffer.read_with(cx, |buffer, _| buffer.snapshot())?;
+    let base_text = base_buffer_snapshot.text();
+
+    let diff_snapshot = cx
+        .update(|cx| {
+            BufferDiffSnapshot::new_with_base_buffer(
+                buffer_snapshot.text.clone(),
+                Some(Arc::new(base_text)),
+                base_buffer_snapshot,
+                cx,
+            )
+        })?
+        .await;
+
+    diff.update(cx, |diff, cx| {
+        diff.set_snapshot(diff_snapshot, &buffer_snapshot.text, cx);
+    })?;
+    Ok(())
+}
+
+impl EventEmitter<EditorEvent> for FileHistoryDiffView {}
+
+impl Focusable for FileHistoryDiffView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.diff_editor.focus_handle(cx)
+    }
+}
+
+impl Item for FileHistoryDiffView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, _: &App) -> SharedString {
+        self.title.clone()
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(self.path.clone())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("File History Diff View Opened")
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.diff_editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<gpui::AnyEntity> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.diff_editor.clone().into())
+        } else {
+            None
+        }
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.diff_editor.clone()))
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        self.diff_editor.for_each_project_item(cx, f)
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.diff_editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.diff_editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn breadcrumb_location(&self, _: &App) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, theme: &theme::Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        self.diff_editor.breadcrumbs(theme, cx)
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.diff_editor.update(cx, |editor, cx| {
+            editor.added_to_workspace(workspace, window, cx)
+        });
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.diff_editor.read(cx).can_save(cx)
+    }
+
+    fn save(
+        &mut self,
+        options: SaveOptions,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.diff_editor
+            .update(cx, |editor, cx| editor.save(options, project, window, cx))
+    }
+}
+
+impl Render for FileHistoryDiffView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.diff_editor.focus_handle(cx);
+        v_flex()
+            .key_context("FileHistoryDiffView")
+            .size_full()
+            .on_action(cx.listener(Self::restore))
+            .child(
+                h_flex()
+                    .w_full()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new(format!("Snapshot from {}", self.created_at))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Button::new("restore-snapshot", "Restore Snapshot")
+                            .label_size(LabelSize::Small)
+                            .key_binding(
+                                KeyBinding::for_action_in(&RestoreSnapshot, &focus_handle, cx)
+                                    .map(|kb| kb.size(rems_from_px(12.))),
+                            )
+                            .tooltip(Tooltip::text(
+                                "Replace the contents of the file with this snapshot",
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.restore(&RestoreSnapshot, window, cx)
+                            })),
+                    ),
+            )
+            .child(div().flex_1().min_h_0().child(self.diff_editor.clone()))
+    }
+}
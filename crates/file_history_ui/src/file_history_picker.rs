@@ -0,0 +1,208 @@
+use std::{path::PathBuf, sync::Arc};
+
+use gpui::{
+    Action as _, AnyElement, App, Context, DismissEvent, Entity, FocusHandle, SharedString, Task,
+    WeakEntity, Window,
+};
+use language::Buffer;
+use picker::{Picker, PickerDelegate};
+use project::file_history::{FILE_HISTORY, FileSnapshot};
+use time::{OffsetDateTime, UtcOffset};
+use ui::{KeyBinding, ListItem, ListItemSpacing, prelude::*};
+use util::{ResultExt as _, size::format_file_size};
+use workspace::{Workspace, notifications::DetachAndPromptErr as _};
+
+use crate::{FileHistoryDiffView, restore_snapshot};
+
+pub(crate) struct FileHistoryDelegate {
+    workspace: WeakEntity<Workspace>,
+    buffer: Entity<Buffer>,
+    abs_path: PathBuf,
+    snapshots: Vec<FileSnapshot>,
+    selected_index: usize,
+    timezone: UtcOffset,
+    pub(crate) focus_handle: FocusHandle,
+}
+
+impl FileHistoryDelegate {
+    pub(crate) fn new(
+        workspace: WeakEntity<Workspace>,
+        buffer: Entity<Buffer>,
+        abs_path: PathBuf,
+        cx: &mut App,
+    ) -> Self {
+        Self {
+            workspace,
+            buffer,
+            abs_path,
+            snapshots: Vec::new(),
+            selected_index: 0,
+            timezone: UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC),
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn restore(&self, snapshot: FileSnapshot, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let buffer = self.buffer.clone();
+        cx.spawn(async move |_, cx| {
+            let contents = cx
+                .background_spawn(async move { FILE_HISTORY.snapshot_contents(snapshot.id) })
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("the snapshot no longer exists"))?;
+            cx.update(|cx| restore_snapshot(buffer, contents, cx))?
+                .await
+        })
+        .detach_and_prompt_err("Failed to restore snapshot", window, cx, |error, _, _| {
+            Some(error.to_string())
+        });
+    }
+}
+
+impl PickerDelegate for FileHistoryDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Select a snapshot…".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        _query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let abs_path = self.abs_path.clone();
+        cx.spawn_in(window, async move |picker, cx| {
+            let snapshots = cx
+                .background_spawn(async move { FILE_HISTORY.snapshots(&abs_path) })
+                .await
+                .log_err()
+                .unwrap_or_default();
+            picker
+                .update(cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.snapshots = snapshots;
+                    delegate.selected_index = delegate
+                        .selected_index
+                        .min(delegate.snapshots.len().saturating_sub(1));
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(snapshot) = self.snapshots.get(self.selected_index).copied() else {
+            return;
+        };
+        if secondary {
+            self.restore(snapshot, window, cx);
+        } else {
+            FileHistoryDiffView::open(
+                self.workspace.clone(),
+                self.buffer.clone(),
+                snapshot,
+                window,
+                cx,
+            )
+            .detach_and_prompt_err(
+                "Failed to open snapshot",
+                window,
+                cx,
+                |error, _, _| Some(error.to_string()),
+            );
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let snapshot = self.snapshots.get(ix)?;
+        let created_at = time_format::format_localized_timestamp(
+            OffsetDateTime::from(snapshot.created_at),
+            OffsetDateTime::now_utc(),
+            self.timezone,
+            time_format::TimestampFormat::Relative,
+        );
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(Label::new(created_at))
+                .end_slot(
+                    Label::new(format_file_size(snapshot.len as u64, false))
+                        .color(Color::Muted)
+                        .size(LabelSize::Small),
+                ),
+        )
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("This file has no saved snapshots".into())
+    }
+
+    fn render_footer(&self, _: &mut Window, cx: &mut Context<Picker<Self>>) -> Option<AnyElement> {
+        if self.snapshots.is_empty() {
+            return None;
+        }
+        let focus_handle = self.focus_handle.clone();
+
+        Some(
+            h_flex()
+                .w_full()
+                .p_1p5()
+                .gap_0p5()
+                .justify_end()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Button::new("restore-snapshot", "Restore")
+                        .key_binding(
+                            KeyBinding::for_action_in(&menu::SecondaryConfirm, &focus_handle, cx)
+                                .map(|kb| kb.size(rems_from_px(12.))),
+                        )
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(menu::SecondaryConfirm.boxed_clone(), cx)
+                        }),
+                )
+                .child(
+                    Button::new("compare-snapshot", "Compare")
+                        .key_binding(
+                            KeyBinding::for_action_in(&menu::Confirm, &focus_handle, cx)
+                                .map(|kb| kb.size(rems_from_px(12.))),
+                        )
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(menu::Confirm.boxed_clone(), cx)
+                        }),
+                )
+                .into_any(),
+        )
+    }
+}
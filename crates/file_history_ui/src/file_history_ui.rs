@@ -0,0 +1,119 @@
+mod file_history_diff_view;
+mod file_history_picker;
+
+use anyhow::Result;
+use editor::Editor;
+use gpui::{App, AppContext as _, Context, Entity, Focusable as _, Task, Window, actions, rems};
+use language::Buffer;
+use picker::Picker;
+use workspace::Workspace;
+
+pub use file_history_diff_view::FileHistoryDiffView;
+use file_history_picker::FileHistoryDelegate;
+
+actions!(
+    file_history,
+    [
+        /// Shows the snapshots of the active file that were taken when it was
+        /// saved, to compare with or restore.
+        ShowHistory,
+        /// Replaces the contents of the file with the snapshot being compared.
+        RestoreSnapshot,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(show_history);
+    })
+    .detach();
+}
+
+fn show_history(
+    workspace: &mut Workspace,
+    _: &ShowHistory,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(buffer) = workspace
+        .active_item_as::<Editor>(cx)
+        .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+    else {
+        return;
+    };
+    // Snapshots are recorded on the machine where the file is saved, so
+    // there's only a history for files on this one.
+    let Some(abs_path) = buffer
+        .read(cx)
+        .file()
+        .and_then(|file| file.as_local())
+        .map(|file| file.abs_path(cx))
+    else {
+        return;
+    };
+
+    let weak_workspace = workspace.weak_handle();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        let delegate = FileHistoryDelegate::new(weak_workspace, buffer, abs_path, cx);
+        let mut picker = Picker::nonsearchable_uniform_list(delegate, window, cx)
+            .width(rems(34.))
+            .modal(true);
+        picker.delegate.focus_handle = picker.focus_handle(cx);
+        picker
+    });
+}
+
+/// Replaces the contents of the buffer with those of a snapshot, as a single
+/// edit that can be undone.
+pub fn restore_snapshot(
+    buffer: Entity<Buffer>,
+    contents: String,
+    cx: &mut App,
+) -> Task<Result<()>> {
+    let diff = buffer.read(cx).diff(contents, cx);
+    cx.spawn(async move |cx| {
+        let mut diff = diff.await;
+        buffer.update(cx, |buffer, cx| {
+            // Snapshots are stored with normalized line endings, so keep the
+            // ones that the file uses.
+            diff.line_ending = buffer.line_ending();
+            buffer.finalize_last_transaction();
+            buffer.apply_diff(diff, cx);
+            buffer.finalize_last_transaction();
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use language::LineEnding;
+
+    #[gpui::test]
+    async fn test_restore_snapshot(cx: &mut TestAppContext) {
+        let buffer = cx.new(|cx| Buffer::local("one\r\ntwo\r\nthree\r\n", cx));
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..3, "ONE")], None, cx);
+        });
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.line_ending()),
+            LineEnding::Windows
+        );
+
+        cx.update(|cx| restore_snapshot(buffer.clone(), "one\nthree\nfour\n".into(), cx))
+            .await
+            .unwrap();
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.text(), "one\nthree\nfour\n");
+            assert_eq!(buffer.line_ending(), LineEnding::Windows);
+        });
+
+        // Restoring is undone in one step.
+        buffer.update(cx, |buffer, cx| buffer.undo(cx));
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "ONE\ntwo\nthree\n"
+        );
+    }
+}
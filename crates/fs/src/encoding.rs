@@ -0,0 +1,300 @@
+use anyhow::{Context as _, Result, bail};
+use std::borrow::Cow;
+
+/// How many bytes are inspected when guessing the encoding of a file without
+/// a byte order mark.
+const DETECTION_SAMPLE_LEN: usize = 64 * 1024;
+
+/// The character encoding of a file on disk.
+///
+/// Buffers are always UTF-8 in memory. Files are decoded when they're loaded
+/// and encoded again when they're saved, so that they keep their encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Encoding {
+    encoding: &'static encoding_rs::Encoding,
+    /// Whether the file starts with a byte order mark. Only meaningful for
+    /// UTF-8 and UTF-16.
+    bom: bool,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self {
+            encoding: encoding_rs::UTF_8,
+            bom: false,
+        }
+    }
+}
+
+impl Encoding {
+    /// Looks up an encoding by its name, as returned by [`Encoding::name`],
+    /// or by any of the labels it's known by, such as `latin1` or `sjis`.
+    pub fn from_name(name: &str, bom: bool) -> Option<Self> {
+        let encoding = encoding_rs::Encoding::for_label(name.as_bytes())?;
+        if encoding == encoding_rs::REPLACEMENT || encoding == encoding_rs::X_USER_DEFINED {
+            return None;
+        }
+        Some(Self {
+            encoding,
+            bom: bom && Self::supports_bom(encoding),
+        })
+    }
+
+    /// The encodings that can be chosen when reopening or saving a file.
+    pub fn all() -> Vec<Self> {
+        let mut all = vec![
+            Self::default(),
+            Self::with_bom(encoding_rs::UTF_8),
+            Self::with_bom(encoding_rs::UTF_16LE),
+            Self::with_bom(encoding_rs::UTF_16BE),
+        ];
+        all.extend(
+            [
+                encoding_rs::WINDOWS_1252,
+                encoding_rs::WINDOWS_1250,
+                encoding_rs::WINDOWS_1251,
+                encoding_rs::WINDOWS_1253,
+                encoding_rs::WINDOWS_1254,
+                encoding_rs::WINDOWS_1255,
+                encoding_rs::WINDOWS_1256,
+                encoding_rs::WINDOWS_1257,
+                encoding_rs::WINDOWS_1258,
+                encoding_rs::WINDOWS_874,
+                encoding_rs::ISO_8859_2,
+                encoding_rs::ISO_8859_3,
+                encoding_rs::ISO_8859_4,
+                encoding_rs::ISO_8859_5,
+                encoding_rs::ISO_8859_6,
+                encoding_rs::ISO_8859_7,
+                encoding_rs::ISO_8859_8,
+                encoding_rs::ISO_8859_10,
+                encoding_rs::ISO_8859_13,
+                encoding_rs::ISO_8859_14,
+                encoding_rs::ISO_8859_15,
+                encoding_rs::ISO_8859_16,
+                encoding_rs::KOI8_R,
+                encoding_rs::KOI8_U,
+                encoding_rs::IBM866,
+                encoding_rs::MACINTOSH,
+                encoding_rs::SHIFT_JIS,
+                encoding_rs::EUC_JP,
+                encoding_rs::ISO_2022_JP,
+                encoding_rs::GBK,
+                encoding_rs::GB18030,
+                encoding_rs::BIG5,
+                encoding_rs::EUC_KR,
+            ]
+            .into_iter()
+            .map(|encoding| Self {
+                encoding,
+                bom: false,
+            }),
+        );
+        all
+    }
+
+    fn with_bom(encoding: &'static encoding_rs::Encoding) -> Self {
+        Self {
+            encoding,
+            bom: true,
+        }
+    }
+
+    fn supports_bom(encoding: &'static encoding_rs::Encoding) -> bool {
+        encoding == encoding_rs::UTF_8
+            || encoding == encoding_rs::UTF_16LE
+            || encoding == encoding_rs::UTF_16BE
+    }
+
+    /// The canonical name of the encoding, e.g. `UTF-8` or `Shift_JIS`.
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    /// A name for the encoding that also says whether there's a byte order
+    /// mark, where that differs from what's usual for the encoding.
+    pub fn label(&self) -> &'static str {
+        match (self.name(), self.bom) {
+            ("UTF-8", true) => "UTF-8 with BOM",
+            ("UTF-16LE", false) => "UTF-16LE without BOM",
+            ("UTF-16BE", false) => "UTF-16BE without BOM",
+            (name, _) => name,
+        }
+    }
+
+    pub fn is_utf8(&self) -> bool {
+        self.encoding == encoding_rs::UTF_8 && !self.bom
+    }
+
+    /// Detects the encoding of a file's contents from its byte order mark or,
+    /// failing that, from the contents themselves.
+    ///
+    /// Returns `None` if the contents look like binary data.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(bytes) {
+            return Some(Self::with_bom(encoding));
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return Some(Self::default());
+        }
+
+        let sample = &bytes[..bytes.len().min(DETECTION_SAMPLE_LEN)];
+        if let Some(encoding) = detect_utf16_without_bom(sample) {
+            return Some(Self {
+                encoding,
+                bom: false,
+            });
+        }
+        if sample.contains(&0) {
+            return None;
+        }
+
+        let mut detector = chardetng::EncodingDetector::new();
+        detector.feed(sample, sample.len() == bytes.len());
+        Some(Self {
+            encoding: detector.guess(None, true),
+            bom: false,
+        })
+    }
+
+    /// Decodes a file's contents, failing if they aren't valid in this encoding.
+    pub fn decode(&self, bytes: Vec<u8>) -> Result<String> {
+        if self.is_utf8() {
+            return String::from_utf8(bytes)
+                .with_context(|| format!("file is not valid {}", self.name()));
+        }
+
+        let contents = match encoding_rs::Encoding::for_bom(&bytes) {
+            Some((encoding, bom_len)) if encoding == self.encoding => &bytes[bom_len..],
+            _ => &bytes[..],
+        };
+        Ok(self
+            .encoding
+            .decode_without_bom_handling_and_without_replacement(contents)
+            .with_context(|| format!("file is not valid {}", self.name()))?
+            .into_owned())
+    }
+
+    /// Encodes text to be written to a file, failing if it contains characters
+    /// that this encoding can't represent.
+    pub fn encode<'a>(&self, text: &'a str) -> Result<Cow<'a, [u8]>> {
+        let bom: &[u8] = match (self.name(), self.bom) {
+            ("UTF-8", true) => b"\xEF\xBB\xBF",
+            ("UTF-16LE", true) => b"\xFF\xFE",
+            ("UTF-16BE", true) => b"\xFE\xFF",
+            _ => b"",
+        };
+
+        // encoding_rs only decodes UTF-16, following the WHATWG Encoding Standard.
+        if self.encoding == encoding_rs::UTF_16LE || self.encoding == encoding_rs::UTF_16BE {
+            let little_endian = self.encoding == encoding_rs::UTF_16LE;
+            let mut bytes = Vec::with_capacity(bom.len() + text.len() * 2);
+            bytes.extend_from_slice(bom);
+            for unit in text.encode_utf16() {
+                if little_endian {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                } else {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+            return Ok(Cow::Owned(bytes));
+        }
+
+        let (encoded, _, had_unmappable_characters) = self.encoding.encode(text);
+        if had_unmappable_characters {
+            bail!(
+                "the text contains characters that can't be encoded as {}",
+                self.name()
+            );
+        }
+        if bom.is_empty() {
+            Ok(encoded)
+        } else {
+            let mut bytes = Vec::with_capacity(bom.len() + encoded.len());
+            bytes.extend_from_slice(bom);
+            bytes.extend_from_slice(&encoded);
+            Ok(Cow::Owned(bytes))
+        }
+    }
+}
+
+/// Recognizes UTF-16 without a byte order mark by the zero bytes that
+/// accompany ASCII characters, which are common even in non-English text.
+fn detect_utf16_without_bom(sample: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    let pairs = sample.len() / 2;
+    if pairs == 0 {
+        return None;
+    }
+    let (mut even_zeros, mut odd_zeros) = (0, 0);
+    for pair in sample.chunks_exact(2) {
+        if pair[0] == 0 {
+            even_zeros += 1;
+        }
+        if pair[1] == 0 {
+            odd_zeros += 1;
+        }
+    }
+
+    let is_mostly_zeros = |count: usize| count * 10 >= pairs * 4;
+    let is_mostly_non_zero = |count: usize| count * 10 < pairs;
+    if is_mostly_zeros(odd_zeros) && is_mostly_non_zero(even_zeros) {
+        Some(encoding_rs::UTF_16LE)
+    } else if is_mostly_zeros(even_zeros) && is_mostly_non_zero(odd_zeros) {
+        Some(encoding_rs::UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_and_round_trip() {
+        let text = "naïve café — 日本語";
+        for (encoding, bom) in [
+            ("UTF-8", false),
+            ("UTF-8", true),
+            ("UTF-16LE", true),
+            ("UTF-16BE", true),
+            ("UTF-16LE", false),
+        ] {
+            let encoding = Encoding::from_name(encoding, bom).unwrap();
+            let bytes = encoding.encode(text).unwrap().into_owned();
+            assert_eq!(Encoding::detect(&bytes), Some(encoding), "{encoding:?}");
+            assert_eq!(encoding.decode(bytes).unwrap(), text, "{encoding:?}");
+        }
+
+        let shift_jis = Encoding::from_name("sjis", false).unwrap();
+        let bytes = shift_jis
+            .encode("これは日本語のテキストです。文字コードを判定します。")
+            .unwrap()
+            .into_owned();
+        assert_eq!(Encoding::detect(&bytes), Some(shift_jis));
+
+        let windows_1252 = Encoding::from_name("latin1", false).unwrap();
+        assert_eq!(windows_1252.name(), "windows-1252");
+        let bytes = windows_1252
+            .encode("Le garçon a mangé une crème brûlée à la fenêtre.")
+            .unwrap()
+            .into_owned();
+        assert_eq!(Encoding::detect(&bytes), Some(windows_1252));
+    }
+
+    #[test]
+    fn test_binary_and_invalid_contents() {
+        assert_eq!(Encoding::detect(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\xff"), None);
+        assert!(Encoding::default().decode(b"caf\xe9".to_vec()).is_err());
+        assert!(
+            Encoding::from_name("windows-1252", false)
+                .unwrap()
+                .encode("日本語")
+                .is_err()
+        );
+    }
+}
@@ -7,7 +7,7 @@ use git::{
     blame::Blame,
     repository::{
         AskPassDelegate, Branch, CommitDetails, CommitOptions, FetchOptions, GitRepository,
-        GitRepositoryCheckpoint, PushOptions, Remote, RepoPath, ResetMode, Worktree,
+        GitRepositoryCheckpoint, IndexStage, PushOptions, Remote, RepoPath, ResetMode, Worktree,
     },
     status::{
         DiffTreeType, FileStatus, GitStatus, StatusCode, TrackedStatus, TreeDiff, TreeDiffStatus,
@@ -44,6 +44,8 @@ pub struct FakeGitRepositoryState {
     pub unmerged_paths: HashMap<RepoPath, UnmergedStatus>,
     pub head_contents: HashMap<RepoPath, String>,
     pub index_contents: HashMap<RepoPath, String>,
+    /// The contents of conflicted paths at stages other than the normal one.
+    pub conflict_stage_contents: HashMap<(RepoPath, IndexStage), String>,
     // everything in commit contents is in oids
     pub merge_base_contents: HashMap<RepoPath, Oid>,
     pub oids: HashMap<Oid, String>,
@@ -60,6 +62,7 @@ impl FakeGitRepositoryState {
             event_emitter,
             head_contents: Default::default(),
             index_contents: Default::default(),
+            conflict_stage_contents: Default::default(),
             unmerged_paths: Default::default(),
             blames: Default::default(),
             current_branch_name: Default::default(),
@@ -105,6 +108,26 @@ impl GitRepository for FakeGitRepository {
             .boxed()
     }
 
+    fn load_index_text_at_stage(
+        &self,
+        path: RepoPath,
+        stage: IndexStage,
+    ) -> BoxFuture<'_, Option<String>> {
+        if stage == IndexStage::Normal {
+            return self.load_index_text(path);
+        }
+        let fut = self.with_state_async(false, move |state| {
+            state
+                .conflict_stage_contents
+                .get(&(path, stage))
+                .context("not present in index")
+                .cloned()
+        });
+        self.executor
+            .spawn_labeled(*LOAD_INDEX_TEXT_TASK, async move { fut.await.ok() })
+            .boxed()
+    }
+
     fn load_committed_text(&self, path: RepoPath) -> BoxFuture<'_, Option<String>> {
         let fut = self.with_state_async(false, move |state| {
             state
@@ -400,6 +423,18 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
+    fn remove_worktree(&self, _: PathBuf, _: bool) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn delete_branch(&self, _: String) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn apply_diff(&self, _: String) -> BoxFuture<'_, Result<Vec<String>>> {
+        unimplemented!()
+    }
+
     fn change_branch(&self, name: String) -> BoxFuture<'_, Result<()>> {
         self.with_state_async(true, |state| {
             state.current_branch_name = Some(name);
@@ -1,3 +1,4 @@
+mod encoding;
 #[cfg(target_os = "macos")]
 mod mac_watcher;
 
@@ -45,6 +46,8 @@ use std::{
 use tempfile::TempDir;
 use text::LineEnding;
 
+pub use encoding::Encoding;
+
 #[cfg(any(test, feature = "test-support"))]
 mod fake_git_repo;
 #[cfg(any(test, feature = "test-support"))]
@@ -53,7 +56,7 @@ use collections::{BTreeMap, btree_map};
 use fake_git_repo::FakeGitRepositoryState;
 #[cfg(any(test, feature = "test-support"))]
 use git::{
-    repository::{RepoPath, repo_path},
+    repository::{IndexStage, RepoPath, repo_path},
     status::{FileStatus, StatusCode, TrackedStatus, UnmergedStatus},
 };
 
@@ -120,8 +123,35 @@ pub trait Fs: Send + Sync {
         Ok(String::from_utf8(self.load_bytes(path).await?)?)
     }
     async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+    /// Loads a text file, decoding it with the given encoding or with the one
+    /// detected from its contents.
+    async fn load_with_encoding(
+        &self,
+        path: &Path,
+        encoding: Option<Encoding>,
+    ) -> Result<(String, Encoding)> {
+        let bytes = self.load_bytes(path).await?;
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None => Encoding::detect(&bytes).context("file appears to be binary")?,
+        };
+        Ok((encoding.decode(bytes)?, encoding))
+    }
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()> {
+        if encoding.is_utf8() {
+            return self.save(path, text, line_ending).await;
+        }
+        let text = chunks(text, line_ending).collect::<String>();
+        self.write(path, &encoding.encode(&text)?).await
+    }
     async fn write(&self, path: &Path, content: &[u8]) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
     async fn is_file(&self, path: &Path) -> bool;
@@ -1857,6 +1887,22 @@ impl FakeFs {
         .unwrap();
     }
 
+    pub fn set_conflict_stages_for_repo(
+        &self,
+        dot_git: &Path,
+        stages: &[(&str, IndexStage, String)],
+    ) {
+        self.with_git_state(dot_git, true, |state| {
+            state.conflict_stage_contents.clear();
+            state.conflict_stage_contents.extend(
+                stages
+                    .iter()
+                    .map(|(path, stage, content)| ((repo_path(path), *stage), content.clone())),
+            );
+        })
+        .unwrap();
+    }
+
     pub fn set_index_for_repo(&self, dot_git: &Path, index_state: &[(&str, String)]) {
         self.with_git_state(dot_git, true, |state| {
             state.index_contents.clear();
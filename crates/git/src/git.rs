@@ -98,6 +98,10 @@ actions!(
         Clone,
         /// Adds a file to .gitignore.
         AddToGitignore,
+        /// Opens a three-way merge editor to resolve the conflicts in a file.
+        OpenMergeEditor,
+        /// Opens a side-by-side diff of the file at the cursor.
+        OpenSplitDiff,
     ]
 );
 
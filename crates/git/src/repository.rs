@@ -230,6 +230,20 @@ pub struct Remote {
     pub name: SharedString,
 }
 
+/// The stage of an entry in the index. While a path has a merge conflict,
+/// its entry at the normal stage is replaced by up to three entries, one for
+/// each version of the file being merged.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum IndexStage {
+    Normal = 0,
+    /// The version in the common ancestor.
+    Base = 1,
+    /// The version in the branch being merged into (HEAD).
+    Ours = 2,
+    /// The version in the branch being merged.
+    Theirs = 3,
+}
+
 pub enum ResetMode {
     /// Reset the branch pointer, leave index and worktree unchanged (this will make it look like things that were
     /// committed are now staged).
@@ -389,6 +403,16 @@ pub trait GitRepository: Send + Sync {
     /// Also returns `None` for symlinks.
     fn load_index_text(&self, path: RepoPath) -> BoxFuture<'_, Option<String>>;
 
+    /// Returns the contents of an entry at the given stage of the repository's index, or None if
+    /// there is no entry for the given path at that stage.
+    ///
+    /// Also returns `None` for symlinks.
+    fn load_index_text_at_stage(
+        &self,
+        path: RepoPath,
+        stage: IndexStage,
+    ) -> BoxFuture<'_, Option<String>>;
+
     /// Returns the contents of an entry in the repository's HEAD, or None if HEAD does not exist or has no entry for the given path.
     ///
     /// Also returns `None` for symlinks.
@@ -443,6 +467,21 @@ pub trait GitRepository: Send + Sync {
         from_commit: Option<String>,
     ) -> BoxFuture<'_, Result<()>>;
 
+    /// Removes the worktree checked out at `path`, discarding any local
+    /// changes in it when `force` is set.
+    fn remove_worktree(&self, path: PathBuf, force: bool) -> BoxFuture<'_, Result<()>>;
+
+    /// Deletes a local branch, even if it has not been merged.
+    fn delete_branch(&self, name: String) -> BoxFuture<'_, Result<()>>;
+
+    /// Applies a unified diff to the working copy, falling back to a
+    /// three-way merge when the diff does not apply cleanly, and returns the
+    /// paths that were left with conflict markers.
+    ///
+    /// Fails without changing anything when a three-way merge is needed but
+    /// the working copy or the index has uncommitted changes.
+    fn apply_diff(&self, diff: String) -> BoxFuture<'_, Result<Vec<String>>>;
+
     fn reset(
         &self,
         commit: String,
@@ -925,19 +964,30 @@ impl GitRepository for RealGitRepository {
     }
 
     fn load_index_text(&self, path: RepoPath) -> BoxFuture<'_, Option<String>> {
+        self.load_index_text_at_stage(path, IndexStage::Normal)
+    }
+
+    fn load_index_text_at_stage(
+        &self,
+        path: RepoPath,
+        stage: IndexStage,
+    ) -> BoxFuture<'_, Option<String>> {
         // https://git-scm.com/book/en/v2/Git-Internals-Git-Objects
         const GIT_MODE_SYMLINK: u32 = 0o120000;
 
         let repo = self.repository.clone();
         self.executor
             .spawn(async move {
-                fn logic(repo: &git2::Repository, path: &RepoPath) -> Result<Option<String>> {
+                fn logic(
+                    repo: &git2::Repository,
+                    path: &RepoPath,
+                    stage: IndexStage,
+                ) -> Result<Option<String>> {
                     // This check is required because index.get_path() unwraps internally :(
                     let mut index = repo.index()?;
                     index.read(false)?;
 
-                    const STAGE_NORMAL: i32 = 0;
-                    let oid = match index.get_path(path.as_std_path(), STAGE_NORMAL) {
+                    let oid = match index.get_path(path.as_std_path(), stage as i32) {
                         Some(entry) if entry.mode != GIT_MODE_SYMLINK => entry.id,
                         _ => return Ok(None),
                     };
@@ -946,7 +996,7 @@ impl GitRepository for RealGitRepository {
                     Ok(String::from_utf8(content).ok())
                 }
 
-                match logic(&repo.lock(), &path) {
+                match logic(&repo.lock(), &path, stage) {
                     Ok(value) => return value,
                     Err(err) => log::error!("Error loading index text: {:?}", err),
                 }
@@ -1327,6 +1377,129 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
+    fn remove_worktree(&self, path: PathBuf, force: bool) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let mut args = vec![
+            OsString::from("--no-optional-locks"),
+            OsString::from("worktree"),
+            OsString::from("remove"),
+        ];
+        if force {
+            args.push(OsString::from("--force"));
+        }
+        args.push(OsString::from(path.as_os_str()));
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(working_directory?)
+                    .args(args)
+                    .output()
+                    .await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "git worktree remove failed: {}",
+                    String::from_utf8_lossy(&output.stderr),
+                );
+                Ok(())
+            })
+            .boxed()
+    }
+
+    fn delete_branch(&self, name: String) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(working_directory?)
+                    .args(["--no-optional-locks", "branch", "-D", &name])
+                    .output()
+                    .await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "git branch -D failed: {}",
+                    String::from_utf8_lossy(&output.stderr),
+                );
+                Ok(())
+            })
+            .boxed()
+    }
+
+    fn apply_diff(&self, diff: String) -> BoxFuture<'_, Result<Vec<String>>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                let apply = async |args: &[&str]| -> Result<std::process::Output> {
+                    let mut child = new_smol_command(&git_binary_path)
+                        .current_dir(&working_directory)
+                        .args(["apply", "--whitespace=nowarn"])
+                        .args(args)
+                        .arg("-")
+                        .stdin(Stdio::piped())
+                        .stdout(Stdio::piped())
+                        .stderr(Stdio::piped())
+                        .spawn()
+                        .context("starting git apply process")?;
+                    let mut stdin = child
+                        .stdin
+                        .take()
+                        .context("failed to open git apply stdin")?;
+                    stdin.write_all(diff.as_bytes()).await?;
+                    drop(stdin);
+                    Ok(child.output().await?)
+                };
+
+                // Diffs that apply cleanly only touch the working copy, leaving
+                // whatever the user has staged alone.
+                if apply(&["--check"]).await?.status.success() {
+                    let output = apply(&[]).await?;
+                    anyhow::ensure!(
+                        output.status.success(),
+                        "git apply failed: {}",
+                        String::from_utf8_lossy(&output.stderr),
+                    );
+                    return Ok(Vec::new());
+                }
+
+                // A three-way merge goes through the index, and refuses to run
+                // when it differs from the working copy, so it's only attempted
+                // without uncommitted changes. The diff hasn't been applied at
+                // this point, so it can be applied again once they're gone.
+                let git =
+                    GitBinary::new(git_binary_path.clone(), working_directory.clone(), executor);
+                let status = git
+                    .run(&["status", "--porcelain=v1", "--untracked-files=no"])
+                    .await?;
+                anyhow::ensure!(
+                    status.is_empty(),
+                    "the changes don't apply cleanly, and merging them requires committing or \
+                     stashing the uncommitted changes first",
+                );
+
+                let output = apply(&["--3way"]).await?;
+                let conflicts = git
+                    .run(&["diff", "--name-only", "--diff-filter=U"])
+                    .await?
+                    .lines()
+                    .map(ToOwned::to_owned)
+                    .collect::<Vec<_>>();
+                anyhow::ensure!(
+                    output.status.success() || !conflicts.is_empty(),
+                    "git apply failed: {}",
+                    String::from_utf8_lossy(&output.stderr),
+                );
+                // The merge staged its result, but the index was clean before,
+                // so resetting it restores what the user had staged.
+                git.run(&["reset", "--quiet"]).await?;
+                Ok(conflicts)
+            })
+            .boxed()
+    }
+
     fn change_branch(&self, name: String) -> BoxFuture<'_, Result<()>> {
         let repo = self.repository.clone();
         let working_directory = self.working_directory();
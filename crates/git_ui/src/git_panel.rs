@@ -2,6 +2,7 @@ use crate::askpass_modal::AskPassModal;
 use crate::commit_modal::CommitModal;
 use crate::commit_tooltip::CommitTooltip;
 use crate::commit_view::CommitView;
+use crate::merge_editor::MergeEditor;
 use crate::project_diff::{self, Diff, ProjectDiff};
 use crate::remote_output::{self, RemoteAction, SuccessMessage};
 use crate::{branch_picker, picker_prompt, render_remote_button};
@@ -950,6 +951,32 @@ impl GitPanel {
         });
     }
 
+    fn open_merge_editor(
+        &mut self,
+        _: &git::OpenMergeEditor,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        maybe!({
+            let entry = self.entries.get(self.selected_entry?)?.status_entry()?;
+            if !entry.status.is_conflicted() {
+                return None;
+            }
+            let path = self
+                .active_repository
+                .as_ref()?
+                .read(cx)
+                .repo_path_to_project_path(&entry.repo_path, cx)?;
+            MergeEditor::open(self.workspace.clone(), path, window, cx).detach_and_prompt_err(
+                "Failed to open merge editor",
+                window,
+                cx,
+                |error, _, _| Some(error.to_string()),
+            );
+            Some(())
+        });
+    }
+
     fn add_to_gitignore(
         &mut self,
         _: &git::AddToGitignore,
@@ -2058,6 +2085,8 @@ impl GitPanel {
                     stop: Vec::new(),
                     temperature,
                     thinking_allowed: false,
+                    response_format: None,
+                    max_tokens: None,
                 };
 
                 let stream = model.stream_completion_text(request, cx);
@@ -4041,10 +4070,17 @@ impl GitPanel {
                     context_menu.action("Add to .gitignore", git::AddToGitignore.boxed_clone());
             }
 
-            context_menu
+            context_menu = context_menu
                 .separator()
                 .action("Open Diff", Confirm.boxed_clone())
-                .action("Open File", SecondaryConfirm.boxed_clone())
+                .action("Open File", SecondaryConfirm.boxed_clone());
+
+            if entry.status.is_conflicted() {
+                context_menu =
+                    context_menu.action("Open Merge Editor", git::OpenMergeEditor.boxed_clone());
+            }
+
+            context_menu
         });
         self.selected_entry = Some(ix);
         self.set_context_menu(context_menu, position, window, cx);
@@ -4532,6 +4568,7 @@ impl Render for GitPanel {
                     .on_action(cx.listener(Self::restore_tracked_files))
                     .on_action(cx.listener(Self::revert_selected))
                     .on_action(cx.listener(Self::add_to_gitignore))
+                    .on_action(cx.listener(Self::open_merge_editor))
                     .on_action(cx.listener(Self::clean_all))
                     .on_action(cx.listener(Self::generate_commit_message_action))
                     .on_action(cx.listener(Self::stash_all))
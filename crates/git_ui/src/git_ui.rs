@@ -37,11 +37,13 @@ mod conflict_view;
 pub mod file_diff_view;
 pub mod git_panel;
 mod git_panel_settings;
+pub mod merge_editor;
 pub mod onboarding;
 pub mod picker_prompt;
 pub mod project_diff;
 pub(crate) mod remote_output;
 pub mod repository_selector;
+pub mod split_diff_view;
 pub mod stash_picker;
 pub mod text_diff_view;
 pub mod worktree_picker;
@@ -71,6 +73,8 @@ pub fn init(cx: &mut App) {
         branch_picker::register(workspace);
         worktree_picker::register(workspace);
         stash_picker::register(workspace);
+        merge_editor::register(workspace);
+        split_diff_view::register(workspace);
 
         let project = workspace.project().read(cx);
         if project.is_read_only(cx) {
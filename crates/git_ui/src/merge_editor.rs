@@ -0,0 +1,917 @@
+//! A three-way merge editor, which shows the versions of a conflicted file
+//! that are being merged next to the file itself, so that its conflicts can be
+//! resolved one at a time.
+
+use std::{
+    any::{Any, TypeId},
+    ops::Range,
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use editor::{Editor, EditorEvent, RowHighlightOptions, SelectionEffects, scroll::Autoscroll};
+use git::repository::RepoPath;
+use gpui::{
+    AnyElement, App, AppContext as _, Context, Entity, EventEmitter, FocusHandle, Focusable, Hsla,
+    IntoElement, Render, Subscription, Task, WeakEntity, Window, actions,
+};
+use language::{Anchor, Buffer, Capability, Language, ToOffset as _};
+use project::{
+    ConflictRegion, ConflictSet, ConflictSetUpdate, Project, ProjectItem as _, ProjectPath,
+    git_store::{ConflictStages, Repository},
+};
+use ui::{Tooltip, prelude::*};
+use util::paths::PathExt as _;
+use workspace::{
+    Item, ItemHandle as _, ItemNavHistory, SaveIntent, ToolbarItemLocation, Workspace,
+    item::{BreadcrumbText, ItemEvent, SaveOptions, TabContentParams},
+    notifications::DetachAndPromptErr as _,
+    searchable::SearchableItemHandle,
+};
+
+actions!(
+    merge_editor,
+    [
+        /// Moves to the next conflict in the file.
+        NextConflict,
+        /// Moves to the previous conflict in the file.
+        PreviousConflict,
+        /// Resolves the current conflict by keeping our version.
+        AcceptOurs,
+        /// Resolves the current conflict by keeping their version.
+        AcceptTheirs,
+        /// Resolves the current conflict by keeping both versions, ours first.
+        AcceptBoth,
+        /// Saves and stages the file, once all of its conflicts are resolved.
+        MarkResolved,
+    ]
+);
+
+pub(crate) fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &git::OpenMergeEditor, window, cx| {
+        let Some(project_path) = workspace
+            .active_item_as::<Editor>(cx)
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+            .and_then(|buffer| buffer.read(cx).project_path(cx))
+        else {
+            return;
+        };
+        MergeEditor::open(workspace.weak_handle(), project_path, window, cx).detach_and_prompt_err(
+            "Failed to open merge editor",
+            window,
+            cx,
+            |error, _, _| Some(error.to_string()),
+        );
+    });
+}
+
+/// Highlights the current conflict in the versions being merged.
+struct CurrentConflict;
+
+/// One of the versions of the file being merged, which is shown read-only.
+struct StagePane {
+    label: SharedString,
+    buffer: Entity<Buffer>,
+    editor: Entity<Editor>,
+}
+
+impl StagePane {
+    fn new(
+        label: impl Into<SharedString>,
+        text: Option<String>,
+        language: Option<Arc<Language>>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self {
+        let buffer = cx.new(|cx| {
+            let mut buffer = Buffer::local(text.unwrap_or_default(), cx);
+            buffer.set_language(language, cx);
+            buffer.set_capability(Capability::ReadOnly, cx);
+            buffer
+        });
+        // These editors have no project, so that they don't get the conflict
+        // resolution buttons that editors for project files do.
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_buffer(buffer.clone(), None, window, cx);
+            editor.set_read_only(true);
+            editor.disable_diagnostics(cx);
+            editor
+        });
+        Self {
+            label: label.into(),
+            buffer,
+            editor,
+        }
+    }
+
+    fn highlight(
+        &self,
+        range: Option<Range<usize>>,
+        color: Hsla,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let buffer_snapshot = self.buffer.read(cx).snapshot();
+        self.editor.update(cx, |editor, cx| {
+            editor.clear_row_highlights::<CurrentConflict>();
+            let Some(range) = range else {
+                cx.notify();
+                return;
+            };
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let (Some(start), Some(end)) = (
+                snapshot.as_singleton_anchor(buffer_snapshot.anchor_after(range.start)),
+                snapshot.as_singleton_anchor(buffer_snapshot.anchor_before(range.end)),
+            ) else {
+                return;
+            };
+            editor.highlight_rows::<CurrentConflict>(
+                start..end,
+                color,
+                RowHighlightOptions {
+                    include_gutter: true,
+                    ..Default::default()
+                },
+                cx,
+            );
+            editor.change_selections(
+                SelectionEffects::scroll(Autoscroll::center()),
+                window,
+                cx,
+                |selections| selections.select_anchor_ranges([start..start]),
+            );
+        });
+    }
+
+    fn render(&self, cx: &App) -> impl IntoElement {
+        v_flex()
+            .flex_1()
+            .min_w_0()
+            .h_full()
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new(self.label.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(div().flex_1().min_h_0().child(self.editor.clone()))
+    }
+}
+
+/// Shows our, the base, and their versions of a conflicted file above the file
+/// itself, in which the conflicts are resolved.
+pub struct MergeEditor {
+    workspace: WeakEntity<Workspace>,
+    project: Entity<Project>,
+    repository: Entity<Repository>,
+    repo_path: RepoPath,
+    buffer: Entity<Buffer>,
+    conflict_set: Entity<ConflictSet>,
+    result_editor: Entity<Editor>,
+    ours: StagePane,
+    base: StagePane,
+    theirs: StagePane,
+    title: SharedString,
+    path: SharedString,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl MergeEditor {
+    /// Opens a merge editor for the given file, or activates the one that's
+    /// already open for it.
+    pub fn open(
+        workspace: WeakEntity<Workspace>,
+        project_path: ProjectPath,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        window.spawn(cx, async move |cx| {
+            let project = workspace.read_with(cx, |workspace, _| workspace.project().clone())?;
+            let buffer = project
+                .update(cx, |project, cx| project.open_buffer(project_path, cx))?
+                .await?;
+
+            let existing = workspace.update_in(cx, |workspace, window, cx| {
+                let existing = workspace
+                    .items_of_type::<Self>(cx)
+                    .find(|merge_editor| merge_editor.read(cx).buffer == buffer)?;
+                workspace.activate_item(&existing, true, true, window, cx);
+                Some(existing)
+            })?;
+            if let Some(existing) = existing {
+                return Ok(existing);
+            }
+
+            let (repository, repo_path) = project
+                .read_with(cx, |project, cx| {
+                    project
+                        .git_store()
+                        .read(cx)
+                        .repository_and_path_for_buffer_id(buffer.read(cx).remote_id(), cx)
+                })?
+                .context("the file isn't in a git repository")?;
+            let stages = repository
+                .update(cx, |repository, cx| {
+                    repository.load_conflict_stages(repo_path.clone(), cx)
+                })?
+                .await?;
+            anyhow::ensure!(
+                stages.ours.is_some() || stages.theirs.is_some(),
+                "the file has no conflicts"
+            );
+
+            workspace.update_in(cx, |workspace, window, cx| {
+                let merge_editor = cx.new(|cx| {
+                    Self::new(
+                        workspace.weak_handle(),
+                        project,
+                        repository,
+                        repo_path,
+                        buffer,
+                        stages,
+                        window,
+                        cx,
+                    )
+                });
+                workspace.active_pane().update(cx, |pane, cx| {
+                    pane.add_item(Box::new(merge_editor.clone()), true, true, None, window, cx);
+                });
+                merge_editor.update(cx, |merge_editor, cx| {
+                    merge_editor.reveal_conflict(0, window, cx)
+                });
+                merge_editor
+            })
+        })
+    }
+
+    fn new(
+        workspace: WeakEntity<Workspace>,
+        project: Entity<Project>,
+        repository: Entity<Repository>,
+        repo_path: RepoPath,
+        buffer: Entity<Buffer>,
+        stages: ConflictStages,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let conflict_set = project.read(cx).git_store().update(cx, |git_store, cx| {
+            git_store.open_conflict_set(buffer.clone(), cx)
+        });
+        let result_editor =
+            cx.new(|cx| Editor::for_buffer(buffer.clone(), Some(project.clone()), window, cx));
+
+        let language = buffer.read(cx).language().cloned();
+        let ours = StagePane::new("Ours", stages.ours, language.clone(), window, cx);
+        let base_label = if stages.base.is_some() {
+            "Base"
+        } else {
+            "Base (no common ancestor)"
+        };
+        let base = StagePane::new(base_label, stages.base, language.clone(), window, cx);
+        let theirs = StagePane::new("Theirs", stages.theirs, language, window, cx);
+
+        let subscriptions = vec![
+            cx.subscribe_in(&result_editor, window, |this, _, event, window, cx| {
+                if let EditorEvent::SelectionsChanged { .. } = event {
+                    this.highlight_current_conflict(window, cx);
+                }
+                cx.emit(event.clone());
+            }),
+            cx.subscribe_in(
+                &conflict_set,
+                window,
+                |this, _, _: &ConflictSetUpdate, window, cx| {
+                    this.highlight_current_conflict(window, cx);
+                    cx.notify();
+                },
+            ),
+        ];
+
+        let file_name = buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string())
+            .unwrap_or_else(|| "untitled".into());
+        let path = buffer
+            .read(cx)
+            .file()
+            .map(|file| file.full_path(cx).compact().to_string_lossy().into_owned())
+            .unwrap_or_else(|| "untitled".into());
+
+        Self {
+            workspace,
+            project,
+            repository,
+            repo_path,
+            buffer,
+            conflict_set,
+            result_editor,
+            ours,
+            base,
+            theirs,
+            title: format!("{file_name} (Merge)").into(),
+            path: path.into(),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn conflicts(&self, cx: &App) -> Arc<[ConflictRegion]> {
+        self.conflict_set.read(cx).snapshot.conflicts.clone()
+    }
+
+    fn cursor_offset(&self, cx: &App) -> usize {
+        let head = self
+            .result_editor
+            .read(cx)
+            .selections
+            .newest_anchor()
+            .head();
+        head.text_anchor.to_offset(&self.buffer.read(cx).snapshot())
+    }
+
+    /// The conflict at or after the cursor, or the last one if the cursor is
+    /// past all of them.
+    fn current_conflict_ix(&self, cx: &App) -> Option<usize> {
+        let conflicts = self.conflicts(cx);
+        let cursor = self.cursor_offset(cx);
+        let snapshot = self.buffer.read(cx).snapshot();
+        let ix = conflicts
+            .iter()
+            .position(|conflict| conflict.range.end.to_offset(&snapshot) >= cursor)
+            .unwrap_or(conflicts.len());
+        (!conflicts.is_empty()).then(|| ix.min(conflicts.len() - 1))
+    }
+
+    fn reveal_conflict(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(conflict) = self.conflicts(cx).get(ix).cloned() else {
+            return;
+        };
+        self.result_editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let Some(start) = snapshot.as_singleton_anchor(conflict.range.start) else {
+                return;
+            };
+            editor.change_selections(
+                SelectionEffects::scroll(Autoscroll::center()),
+                window,
+                cx,
+                |selections| selections.select_anchor_ranges([start..start]),
+            );
+        });
+    }
+
+    fn highlight_current_conflict(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let conflicts = self.conflicts(cx);
+        let current = self.current_conflict_ix(cx);
+        let snapshot = self.buffer.read(cx).snapshot();
+        let side_text = |range: Option<&Range<Anchor>>| {
+            range.map(|range| snapshot.text_for_range(range.clone()).collect::<String>())
+        };
+        let ours_ranges = locate_conflict_sides(
+            &self.ours.buffer.read(cx).text(),
+            conflicts
+                .iter()
+                .map(|conflict| side_text(Some(&conflict.ours))),
+        );
+        let base_ranges = locate_conflict_sides(
+            &self.base.buffer.read(cx).text(),
+            conflicts
+                .iter()
+                .map(|conflict| side_text(conflict.base.as_ref())),
+        );
+        let theirs_ranges = locate_conflict_sides(
+            &self.theirs.buffer.read(cx).text(),
+            conflicts
+                .iter()
+                .map(|conflict| side_text(Some(&conflict.theirs))),
+        );
+
+        let range_at = |ranges: Vec<Option<Range<usize>>>| {
+            current.and_then(|ix| ranges.into_iter().nth(ix).flatten())
+        };
+        let colors = cx.theme().colors();
+        let ours_color = colors.version_control_conflict_marker_ours;
+        let theirs_color = colors.version_control_conflict_marker_theirs;
+        let base_color = colors.editor_active_line_background;
+        self.ours
+            .highlight(range_at(ours_ranges), ours_color, window, cx);
+        self.base
+            .highlight(range_at(base_ranges), base_color, window, cx);
+        self.theirs
+            .highlight(range_at(theirs_ranges), theirs_color, window, cx);
+    }
+
+    fn next_conflict(&mut self, _: &NextConflict, window: &mut Window, cx: &mut Context<Self>) {
+        let conflicts = self.conflicts(cx);
+        let cursor = self.cursor_offset(cx);
+        let snapshot = self.buffer.read(cx).snapshot();
+        let ix = conflicts
+            .iter()
+            .position(|conflict| conflict.range.start.to_offset(&snapshot) > cursor)
+            .unwrap_or(0);
+        self.reveal_conflict(ix, window, cx);
+    }
+
+    fn previous_conflict(
+        &mut self,
+        _: &PreviousConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let conflicts = self.conflicts(cx);
+        let cursor = self.cursor_offset(cx);
+        let snapshot = self.buffer.read(cx).snapshot();
+        let ix = conflicts
+            .iter()
+            .rposition(|conflict| conflict.range.start.to_offset(&snapshot) < cursor)
+            .unwrap_or(conflicts.len().saturating_sub(1));
+        self.reveal_conflict(ix, window, cx);
+    }
+
+    fn accept(
+        &mut self,
+        sides: impl FnOnce(&ConflictRegion) -> Vec<Range<Anchor>>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(conflict) = self
+            .current_conflict_ix(cx)
+            .and_then(|ix| self.conflicts(cx).get(ix).cloned())
+        else {
+            return;
+        };
+        conflict.resolve(self.buffer.clone(), &sides(&conflict), cx);
+    }
+
+    fn accept_ours(&mut self, _: &AcceptOurs, _: &mut Window, cx: &mut Context<Self>) {
+        self.accept(|conflict| vec![conflict.ours.clone()], cx);
+    }
+
+    fn accept_theirs(&mut self, _: &AcceptTheirs, _: &mut Window, cx: &mut Context<Self>) {
+        self.accept(|conflict| vec![conflict.theirs.clone()], cx);
+    }
+
+    fn accept_both(&mut self, _: &AcceptBoth, _: &mut Window, cx: &mut Context<Self>) {
+        self.accept(
+            |conflict| vec![conflict.ours.clone(), conflict.theirs.clone()],
+            cx,
+        );
+    }
+
+    fn mark_resolved(&mut self, _: &MarkResolved, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.conflicts(cx).is_empty() {
+            return;
+        }
+        let save = self.project.update(cx, |project, cx| {
+            project.save_buffer(self.buffer.clone(), cx)
+        });
+        let repository = self.repository.clone();
+        let repo_path = self.repo_path.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            save.await?;
+            repository
+                .update(cx, |repository, cx| {
+                    repository.stage_entries(vec![repo_path], cx)
+                })?
+                .await?;
+            let close = workspace.update_in(cx, |workspace, window, cx| {
+                let this = this.upgrade()?;
+                let pane = workspace.pane_for(&this)?;
+                Some(pane.update(cx, |pane, cx| {
+                    pane.close_item_by_id(this.entity_id(), SaveIntent::Skip, window, cx)
+                }))
+            })?;
+            if let Some(close) = close {
+                close.await?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to mark as resolved", window, cx, |error, _, _| {
+            Some(error.to_string())
+        });
+    }
+}
+
+/// Finds where each side of the conflicts in a file came from in one of the
+/// versions being merged. The sides are searched for in order, each after the
+/// previous one that was found, since conflicts appear in the same order as in
+/// the version.
+fn locate_conflict_sides(
+    stage_text: &str,
+    sides: impl IntoIterator<Item = Option<String>>,
+) -> Vec<Option<Range<usize>>> {
+    let mut offset = 0;
+    sides
+        .into_iter()
+        .map(|side| {
+            let side = side?;
+            let start = offset + stage_text[offset..].find(&side)?;
+            offset = start + side.len();
+            Some(start..offset)
+        })
+        .collect()
+}
+
+impl EventEmitter<EditorEvent> for MergeEditor {}
+
+impl Focusable for MergeEditor {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.result_editor.focus_handle(cx)
+    }
+}
+
+impl Item for MergeEditor {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, _: &App) -> SharedString {
+        self.title.clone()
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(self.path.clone())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Merge Editor Opened")
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.result_editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<gpui::AnyEntity> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.result_editor.clone().into())
+        } else {
+            None
+        }
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.result_editor.clone()))
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        self.result_editor.for_each_project_item(cx, f)
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.result_editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.result_editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn breadcrumb_location(&self, _: &App) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, theme: &theme::Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        self.result_editor.breadcrumbs(theme, cx)
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.result_editor.update(cx, |editor, cx| {
+            editor.added_to_workspace(workspace, window, cx)
+        });
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &App) -> bool {
+        self.buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.result_editor.read(cx).can_save(cx)
+    }
+
+    fn save(
+        &mut self,
+        options: SaveOptions,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.result_editor
+            .update(cx, |editor, cx| editor.save(options, project, window, cx))
+    }
+}
+
+impl Render for MergeEditor {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.result_editor.focus_handle(cx);
+        let conflict_count = self.conflicts(cx).len();
+        let has_conflicts = conflict_count > 0;
+        let border_color = cx.theme().colors().border_variant;
+
+        let accept_button = |id: &'static str, label: &'static str, action: &dyn gpui::Action| {
+            let action = action.boxed_clone();
+            Button::new(id, label)
+                .label_size(LabelSize::Small)
+                .disabled(!has_conflicts)
+                .tooltip(Tooltip::for_action_title_in(label, &*action, &focus_handle))
+                .on_click(move |_, window, cx| window.dispatch_action(action.boxed_clone(), cx))
+        };
+
+        v_flex()
+            .key_context("MergeEditor")
+            .size_full()
+            .on_action(cx.listener(Self::next_conflict))
+            .on_action(cx.listener(Self::previous_conflict))
+            .on_action(cx.listener(Self::accept_ours))
+            .on_action(cx.listener(Self::accept_theirs))
+            .on_action(cx.listener(Self::accept_both))
+            .on_action(cx.listener(Self::mark_resolved))
+            .child(
+                h_flex()
+                    .w_full()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(border_color)
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                IconButton::new("previous-conflict", IconName::ArrowUp)
+                                    .icon_size(IconSize::Small)
+                                    .disabled(!has_conflicts)
+                                    .tooltip(Tooltip::for_action_title_in(
+                                        "Previous Conflict",
+                                        &PreviousConflict,
+                                        &focus_handle,
+                                    ))
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.previous_conflict(&PreviousConflict, window, cx)
+                                    })),
+                            )
+                            .child(
+                                IconButton::new("next-conflict", IconName::ArrowDown)
+                                    .icon_size(IconSize::Small)
+                                    .disabled(!has_conflicts)
+                                    .tooltip(Tooltip::for_action_title_in(
+                                        "Next Conflict",
+                                        &NextConflict,
+                                        &focus_handle,
+                                    ))
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.next_conflict(&NextConflict, window, cx)
+                                    })),
+                            )
+                            .child(
+                                Label::new(match conflict_count {
+                                    0 => "No conflicts remaining".to_string(),
+                                    1 => "1 conflict remaining".to_string(),
+                                    count => format!("{count} conflicts remaining"),
+                                })
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                            ),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(accept_button("accept-ours", "Accept Ours", &AcceptOurs))
+                            .child(accept_button(
+                                "accept-theirs",
+                                "Accept Theirs",
+                                &AcceptTheirs,
+                            ))
+                            .child(accept_button("accept-both", "Accept Both", &AcceptBoth))
+                            .child(
+                                Button::new("mark-resolved", "Mark as Resolved")
+                                    .label_size(LabelSize::Small)
+                                    .style(ButtonStyle::Filled)
+                                    .disabled(has_conflicts)
+                                    .tooltip(Tooltip::for_action_title_in(
+                                        "Save and stage the file",
+                                        &MarkResolved,
+                                        &focus_handle,
+                                    ))
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.mark_resolved(&MarkResolved, window, cx)
+                                    })),
+                            ),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .w_full()
+                    .border_b_1()
+                    .border_color(border_color)
+                    .child(self.ours.render(cx))
+                    .child(div().h_full().w_px().bg(border_color))
+                    .child(self.base.render(cx))
+                    .child(div().h_full().w_px().bg(border_color))
+                    .child(self.theirs.render(cx)),
+            )
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .child(
+                        h_flex()
+                            .px_2()
+                            .py_1()
+                            .border_b_1()
+                            .border_color(border_color)
+                            .child(Label::new("Result").size(LabelSize::Small)),
+                    )
+                    .child(div().flex_1().min_h_0().child(self.result_editor.clone())),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::{
+        repository::IndexStage,
+        status::{UnmergedStatus, UnmergedStatusCode},
+    };
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_accept_sides(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            editor::init(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "a.txt": "one\n<<<<<<< HEAD\ntwo\n=======\nTWO\n>>>>>>> branch\nthree\n<<<<<<< HEAD\nfour\n=======\nFOUR\n>>>>>>> branch\n",
+            }),
+        )
+        .await;
+        let dot_git = Path::new(path!("/project/.git"));
+        fs.set_status_for_repo(
+            dot_git,
+            &[(
+                "a.txt",
+                UnmergedStatus {
+                    first_head: UnmergedStatusCode::Updated,
+                    second_head: UnmergedStatusCode::Updated,
+                }
+                .into(),
+            )],
+        );
+        fs.set_conflict_stages_for_repo(
+            dot_git,
+            &[
+                ("a.txt", IndexStage::Base, "one\n2\nthree\n4\n".into()),
+                ("a.txt", IndexStage::Ours, "one\ntwo\nthree\nfour\n".into()),
+                (
+                    "a.txt",
+                    IndexStage::Theirs,
+                    "one\nTWO\nthree\nFOUR\n".into(),
+                ),
+            ],
+        );
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let project_path = project
+            .read_with(cx, |project, cx| {
+                project.find_project_path(path!("/project/a.txt"), cx)
+            })
+            .unwrap();
+        let merge_editor = workspace
+            .update_in(cx, |workspace, window, cx| {
+                MergeEditor::open(workspace.weak_handle(), project_path, window, cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        merge_editor.read_with(cx, |merge_editor, cx| {
+            assert_eq!(merge_editor.conflicts(cx).len(), 2);
+            assert_eq!(
+                merge_editor.ours.buffer.read(cx).text(),
+                "one\ntwo\nthree\nfour\n"
+            );
+            assert_eq!(
+                merge_editor.base.buffer.read(cx).text(),
+                "one\n2\nthree\n4\n"
+            );
+            assert_eq!(
+                merge_editor.theirs.buffer.read(cx).text(),
+                "one\nTWO\nthree\nFOUR\n"
+            );
+        });
+
+        // The cursor starts at the first conflict, and once it's resolved the
+        // next one is the current one.
+        merge_editor.update_in(cx, |merge_editor, window, cx| {
+            merge_editor.accept_theirs(&AcceptTheirs, window, cx)
+        });
+        cx.run_until_parked();
+        merge_editor.read_with(cx, |merge_editor, cx| {
+            assert_eq!(
+                merge_editor.buffer.read(cx).text(),
+                "one\nTWO\nthree\n<<<<<<< HEAD\nfour\n=======\nFOUR\n>>>>>>> branch\n"
+            );
+            assert_eq!(merge_editor.conflicts(cx).len(), 1);
+        });
+
+        merge_editor.update_in(cx, |merge_editor, window, cx| {
+            merge_editor.accept_both(&AcceptBoth, window, cx)
+        });
+        cx.run_until_parked();
+        merge_editor.read_with(cx, |merge_editor, cx| {
+            assert_eq!(
+                merge_editor.buffer.read(cx).text(),
+                "one\nTWO\nthree\nfour\nFOUR\n"
+            );
+            assert!(merge_editor.conflicts(cx).is_empty());
+        });
+    }
+
+    #[test]
+    fn test_locate_conflict_sides() {
+        let stage_text = "a\nours 1\nb\nours 2\nc\nours 1\n";
+        let sides = [
+            Some("ours 1\n".to_string()),
+            None,
+            Some("missing\n".to_string()),
+            Some("ours 1\n".to_string()),
+            Some(String::new()),
+        ];
+        // Sides that repeat are found after the ones before them.
+        assert_eq!(
+            locate_conflict_sides(stage_text, sides),
+            [Some(2..9), None, None, Some(20..27), Some(27..27)]
+        );
+    }
+}
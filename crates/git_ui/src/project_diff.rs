@@ -1190,6 +1190,18 @@ impl Render for ProjectDiffToolbar {
                             .on_click(cx.listener(|this, _, window, cx| {
                                 this.dispatch_action(&GoToHunk, window, cx)
                             })),
+                    )
+                    .child(
+                        IconButton::new("split-diff", IconName::Split)
+                            .shape(ui::IconButtonShape::Square)
+                            .tooltip(Tooltip::for_action_title_in(
+                                "Open side-by-side diff",
+                                &git::OpenSplitDiff,
+                                &focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.dispatch_action(&git::OpenSplitDiff, window, cx)
+                            })),
                     ),
             )
             .child(vertical_divider())
@@ -0,0 +1,939 @@
+//! SplitDiffView shows a diff as two editors side by side, with the base text
+//! on the left and the current text on the right.
+//!
+//! The editors are kept aligned by padding each side of a hunk with blocks, so
+//! that both sides of it span the same number of rows. As long as neither
+//! editor soft wraps, a row in one editor then lines up with the same display
+//! row in the other, and scrolling can be synchronized by copying the scroll
+//! position over. Folds are mirrored between the editors for the same reason.
+
+use std::{
+    any::{Any, TypeId},
+    ops::Range,
+    sync::Arc,
+};
+
+use anyhow::Result;
+use buffer_diff::{BufferDiff, BufferDiffEvent};
+use collections::HashSet;
+use editor::{
+    Bias, Editor, EditorEvent, MultiBufferOffset, RowHighlightOptions, SelectionEffects,
+    ToPoint as _,
+    display_map::{BlockPlacement, BlockProperties, BlockStyle, CustomBlockId},
+};
+use gpui::{
+    AnyElement, App, AppContext as _, Context, Entity, EventEmitter, FocusHandle, Focusable,
+    IntoElement, Render, Subscription, Task, Window,
+};
+use language::{Buffer, Capability, Point, language_settings::SoftWrap};
+use project::Project;
+use ui::prelude::*;
+use util::paths::PathExt as _;
+use workspace::{
+    Item, ItemHandle as _, ItemNavHistory, ToolbarItemLocation, Workspace,
+    item::{BreadcrumbText, ItemEvent, SaveOptions, TabContentParams},
+    notifications::DetachAndPromptErr as _,
+    searchable::SearchableItemHandle,
+};
+
+pub(crate) fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &git::OpenSplitDiff, window, cx| {
+        SplitDiffView::deploy(workspace, window, cx).detach_and_prompt_err(
+            "Failed to open side-by-side diff",
+            window,
+            cx,
+            |error, _, _| Some(error.to_string()),
+        );
+    });
+}
+
+struct DeletedRows;
+struct AddedRows;
+struct DeletedWords;
+struct AddedWords;
+
+/// The rows that a hunk spans on each side of the diff.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct HunkRows {
+    base: Range<u32>,
+    buffer: Range<u32>,
+}
+
+pub struct SplitDiffView {
+    buffer: Entity<Buffer>,
+    base_buffer: Entity<Buffer>,
+    diff: Entity<BufferDiff>,
+    base_editor: Entity<Editor>,
+    editor: Entity<Editor>,
+    hunks: Vec<HunkRows>,
+    base_padding: HashSet<CustomBlockId>,
+    padding: HashSet<CustomBlockId>,
+    /// The folds last mirrored into the base editor (`true`) or the editor.
+    mirrored_folds: Option<(bool, Vec<Range<Point>>)>,
+    title: SharedString,
+    path: SharedString,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SplitDiffView {
+    /// Opens a side-by-side diff of the buffer at the cursor in the active
+    /// item, using the diff that the item shows, such as the uncommitted
+    /// changes in an editor or the changes made by a commit in a commit view.
+    pub fn deploy(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Task<Result<Entity<Self>>> {
+        let Some(editor) = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+        else {
+            return Task::ready(Err(anyhow::anyhow!("no file is open")));
+        };
+        let multibuffer = editor.read(cx).buffer().read(cx);
+        let head = editor.read(cx).selections.newest_anchor().head();
+        let Some(buffer) = head
+            .buffer_id
+            .or_else(|| {
+                multibuffer
+                    .as_singleton()
+                    .map(|buffer| buffer.read(cx).remote_id())
+            })
+            .and_then(|buffer_id| multibuffer.buffer(buffer_id))
+        else {
+            return Task::ready(Err(anyhow::anyhow!("no file at the cursor")));
+        };
+        let diff = multibuffer.diff_for(buffer.read(cx).remote_id());
+
+        let project = workspace.project().clone();
+        let workspace = workspace.weak_handle();
+        window.spawn(cx, async move |cx| {
+            let diff = match diff {
+                Some(diff) => diff,
+                None => {
+                    project
+                        .update(cx, |project, cx| {
+                            project.open_uncommitted_diff(buffer.clone(), cx)
+                        })?
+                        .await?
+                }
+            };
+            workspace.update_in(cx, |workspace, window, cx| {
+                let view = cx.new(|cx| Self::new(buffer, diff, Some(project), window, cx));
+                workspace.active_pane().update(cx, |pane, cx| {
+                    pane.add_item(Box::new(view.clone()), true, true, None, window, cx);
+                });
+                view
+            })
+        })
+    }
+
+    pub fn new(
+        buffer: Entity<Buffer>,
+        diff: Entity<BufferDiff>,
+        project: Option<Entity<Project>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let base_buffer = cx.new(|cx| {
+            let mut base_buffer =
+                Buffer::local(diff.read(cx).base_text_string().unwrap_or_default(), cx);
+            base_buffer.set_language(buffer.read(cx).language().cloned(), cx);
+            base_buffer.set_capability(Capability::ReadOnly, cx);
+            base_buffer
+        });
+        let base_editor = cx.new(|cx| {
+            let mut editor = Editor::for_buffer(base_buffer.clone(), None, window, cx);
+            editor.set_read_only(true);
+            editor.disable_diagnostics(cx);
+            editor.set_soft_wrap_mode(SoftWrap::None, cx);
+            editor
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_buffer(buffer.clone(), project, window, cx);
+            editor.set_soft_wrap_mode(SoftWrap::None, cx);
+            editor
+        });
+
+        let subscriptions = vec![
+            cx.subscribe_in(&diff, window, |this, _, event, _, cx| {
+                if let BufferDiffEvent::DiffChanged { .. } | BufferDiffEvent::LanguageChanged =
+                    event
+                {
+                    this.update_alignment(cx);
+                }
+            }),
+            cx.subscribe_in(&base_editor, window, |this, _, event, window, cx| {
+                this.sync_editors(true, event, window, cx);
+            }),
+            cx.subscribe_in(&editor, window, |this, _, event, window, cx| {
+                this.sync_editors(false, event, window, cx);
+                cx.emit(event.clone());
+            }),
+        ];
+
+        let file_name = buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string())
+            .unwrap_or_else(|| "untitled".into());
+        let path = buffer
+            .read(cx)
+            .file()
+            .map(|file| file.full_path(cx).compact().to_string_lossy().into_owned())
+            .unwrap_or_else(|| "untitled".into());
+
+        let mut this = Self {
+            buffer,
+            base_buffer,
+            diff,
+            base_editor,
+            editor,
+            hunks: Vec::new(),
+            base_padding: HashSet::default(),
+            padding: HashSet::default(),
+            mirrored_folds: None,
+            title: format!("{file_name} (Side by Side)").into(),
+            path: path.into(),
+            _subscriptions: subscriptions,
+        };
+        this.update_alignment(cx);
+        this
+    }
+
+    /// Pads and highlights the hunks of the diff in both editors.
+    fn update_alignment(&mut self, cx: &mut Context<Self>) {
+        let diff = self.diff.read(cx);
+        let base_text = diff.base_text_string().unwrap_or_default();
+        if self.base_buffer.read(cx).text() != base_text {
+            self.base_buffer.update(cx, |base_buffer, cx| {
+                base_buffer.set_text(base_text, cx);
+            });
+        }
+
+        let base_snapshot = self.base_buffer.read(cx).snapshot();
+        let buffer_snapshot = self.buffer.read(cx).snapshot();
+        let mut hunks = Vec::new();
+        let mut deleted_words = Vec::new();
+        let mut added_words = Vec::new();
+        for hunk in self.diff.read(cx).hunks(&buffer_snapshot, cx) {
+            let base_range = hunk.diff_base_byte_range.clone();
+            let buffer_range = buffer_snapshot.point_to_offset(hunk.range.start)
+                ..buffer_snapshot.point_to_offset(hunk.range.end);
+            hunks.push(HunkRows {
+                base: row_range(
+                    base_snapshot.offset_to_point(base_range.start)
+                        ..base_snapshot.offset_to_point(base_range.end),
+                ),
+                buffer: row_range(hunk.range.clone()),
+            });
+            if !base_range.is_empty() && !buffer_range.is_empty() {
+                let (deleted, added) = word_diff(
+                    &base_snapshot
+                        .text_for_range(base_range.clone())
+                        .collect::<String>(),
+                    &buffer_snapshot
+                        .text_for_range(buffer_range.clone())
+                        .collect::<String>(),
+                );
+                deleted_words.extend(
+                    deleted
+                        .into_iter()
+                        .map(|range| base_range.start + range.start..base_range.start + range.end),
+                );
+                added_words.extend(
+                    added.into_iter().map(|range| {
+                        buffer_range.start + range.start..buffer_range.start + range.end
+                    }),
+                );
+            }
+        }
+
+        let base_padding = hunks.iter().filter_map(|hunk| {
+            let padding = hunk.buffer.len().saturating_sub(hunk.base.len());
+            (padding > 0).then_some((hunk.base.end, padding as u32))
+        });
+        let padding = hunks.iter().filter_map(|hunk| {
+            let padding = hunk.base.len().saturating_sub(hunk.buffer.len());
+            (padding > 0).then_some((hunk.buffer.end, padding as u32))
+        });
+        let old_base_padding = std::mem::take(&mut self.base_padding);
+        self.base_padding = self.base_editor.update(cx, |editor, cx| {
+            update_editor(
+                editor,
+                old_base_padding,
+                base_padding,
+                hunks.iter().map(|hunk| hunk.base.clone()),
+                &deleted_words,
+                true,
+                cx,
+            )
+        });
+        let old_padding = std::mem::take(&mut self.padding);
+        self.padding = self.editor.update(cx, |editor, cx| {
+            update_editor(
+                editor,
+                old_padding,
+                padding,
+                hunks.iter().map(|hunk| hunk.buffer.clone()),
+                &added_words,
+                false,
+                cx,
+            )
+        });
+        self.hunks = hunks;
+        cx.notify();
+    }
+
+    /// Mirrors scrolling, cursor movement and folding from one editor in the
+    /// other.
+    fn sync_editors(
+        &mut self,
+        from_base: bool,
+        event: &EditorEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (source, target) = if from_base {
+            (&self.base_editor, &self.editor)
+        } else {
+            (&self.editor, &self.base_editor)
+        };
+        match event {
+            EditorEvent::ScrollPositionChanged { local: true, .. } => {
+                let position = source.update(cx, |editor, cx| editor.scroll_position(cx));
+                target.update(cx, |editor, cx| {
+                    // The scroll positions are equal once they're in sync,
+                    // which stops the target from echoing the change back.
+                    if editor.scroll_position(cx) != position {
+                        editor.set_scroll_position(position, window, cx);
+                    }
+                });
+            }
+            EditorEvent::SelectionsChanged { local: true } => {
+                // Only follow the editor that's being used, since mapping a
+                // row back and forth doesn't always lead to the same row.
+                if !source.focus_handle(cx).is_focused(window) {
+                    return;
+                }
+                let head = source.update(cx, |editor, cx| {
+                    editor
+                        .selections
+                        .newest::<Point>(&editor.display_snapshot(cx))
+                        .head()
+                });
+                let row = map_row(head.row, self.mapped_hunks(from_base));
+                target.update(cx, |editor, cx| {
+                    let point = editor
+                        .buffer()
+                        .read(cx)
+                        .snapshot(cx)
+                        .clip_point(Point::new(row, head.column), Bias::Left);
+                    editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                        s.select_ranges([point..point])
+                    });
+                });
+            }
+            EditorEvent::FoldsChanged => {
+                // Folding changes the display rows of one editor, which would
+                // break the alignment unless the other one folds the same
+                // rows. The folds that were mirrored into an editor are
+                // remembered, so that they aren't mirrored back.
+                let folds = source.update(cx, |editor, cx| folded_ranges(editor, cx));
+                if self
+                    .mirrored_folds
+                    .as_ref()
+                    .is_some_and(|(is_base, mirrored)| *is_base == from_base && *mirrored == folds)
+                {
+                    return;
+                }
+                let hunks = self.mapped_hunks(from_base);
+                let mirrored = target.update(cx, |editor, cx| {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let map_point = |point: Point| {
+                        snapshot.clip_point(
+                            Point::new(map_row(point.row, hunks.clone()), point.column),
+                            Bias::Left,
+                        )
+                    };
+                    let ranges = folds
+                        .iter()
+                        .map(|fold| map_point(fold.start)..map_point(fold.end))
+                        .filter(|range| range.start < range.end)
+                        .collect::<Vec<_>>();
+                    if folded_ranges(editor, cx) != ranges {
+                        editor.unfold_ranges(
+                            &[Point::zero()..snapshot.max_point()],
+                            true,
+                            false,
+                            cx,
+                        );
+                        editor.fold_ranges(ranges.clone(), false, window, cx);
+                    }
+                    ranges
+                });
+                self.mirrored_folds = Some((!from_base, mirrored));
+            }
+            _ => {}
+        }
+    }
+
+    /// The rows that each hunk spans in the given editor, paired with the rows
+    /// it spans in the other one.
+    fn mapped_hunks(&self, from_base: bool) -> Vec<(Range<u32>, Range<u32>)> {
+        self.hunks
+            .iter()
+            .map(|hunk| {
+                if from_base {
+                    (hunk.base.clone(), hunk.buffer.clone())
+                } else {
+                    (hunk.buffer.clone(), hunk.base.clone())
+                }
+            })
+            .collect()
+    }
+}
+
+/// The ranges that are folded in an editor.
+fn folded_ranges(editor: &mut Editor, cx: &mut Context<Editor>) -> Vec<Range<Point>> {
+    let snapshot = editor.display_snapshot(cx);
+    let buffer_snapshot = snapshot.buffer_snapshot();
+    snapshot
+        .folds_in_range(MultiBufferOffset(0)..buffer_snapshot.len())
+        .map(|fold| {
+            fold.range.start.to_point(buffer_snapshot)..fold.range.end.to_point(buffer_snapshot)
+        })
+        .collect()
+}
+
+/// The rows that a range spans, including its last row only if the range
+/// doesn't end at the start of it.
+fn row_range(range: Range<Point>) -> Range<u32> {
+    let end = if range.end.column > 0 {
+        range.end.row + 1
+    } else {
+        range.end.row
+    };
+    range.start.row..end.max(range.start.row)
+}
+
+/// Returns the byte ranges of the words that were deleted from and added to a
+/// modified hunk. Hunks that are too large to diff by word, by the same limits
+/// as the inline diff, are left out, as are hunks whose text was replaced
+/// entirely: highlighting every word of them wouldn't add anything to the row
+/// highlights.
+fn word_diff(old_text: &str, new_text: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let limits = language::DiffOptions::default();
+    if [old_text, new_text].iter().any(|text| {
+        text.len() > limits.max_word_diff_len
+            || text.lines().count() > limits.max_word_diff_line_count
+    }) {
+        return (Vec::new(), Vec::new());
+    }
+
+    let mut deleted = Vec::new();
+    let mut added = Vec::new();
+    let mut delta = 0isize;
+    for (old_range, new_text_for_range) in language::text_diff(old_text, new_text) {
+        let new_start = (old_range.start as isize + delta) as usize;
+        let new_range = new_start..new_start + new_text_for_range.len();
+        delta += new_text_for_range.len() as isize - old_range.len() as isize;
+        if old_range == (0..old_text.len()) && new_range == (0..new_text.len()) {
+            return (Vec::new(), Vec::new());
+        }
+        if !old_range.is_empty() {
+            deleted.push(old_range);
+        }
+        if !new_range.is_empty() {
+            added.push(new_range);
+        }
+    }
+    (deleted, added)
+}
+
+/// Maps a row on one side of a diff to the corresponding row on the other,
+/// given the rows that each hunk spans on both sides, in order.
+fn map_row(row: u32, hunks: impl IntoIterator<Item = (Range<u32>, Range<u32>)>) -> u32 {
+    let mut delta = 0i64;
+    for (from, to) in hunks {
+        if row < from.start {
+            break;
+        }
+        if row < from.end {
+            return to.start + (row - from.start).min(to.len().saturating_sub(1) as u32);
+        }
+        delta = to.end as i64 - from.end as i64;
+    }
+    (row as i64 + delta).max(0) as u32
+}
+
+/// Replaces the padding blocks and highlights of one of the editors, and
+/// returns the ids of the new blocks.
+fn update_editor(
+    editor: &mut Editor,
+    old_padding: HashSet<CustomBlockId>,
+    padding: impl Iterator<Item = (u32, u32)>,
+    hunk_rows: impl Iterator<Item = Range<u32>>,
+    words: &[Range<usize>],
+    is_base: bool,
+    cx: &mut Context<Editor>,
+) -> HashSet<CustomBlockId> {
+    editor.remove_blocks(old_padding, None, cx);
+    editor.clear_row_highlights::<DeletedRows>();
+    editor.clear_row_highlights::<AddedRows>();
+
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let max_point = snapshot.max_point();
+    let blocks = padding
+        .map(|(row, height)| {
+            // Padding goes after the hunk, which is above the row that
+            // follows it, unless the hunk is at the end of the text.
+            let placement = if row <= max_point.row {
+                BlockPlacement::Above(snapshot.anchor_before(Point::new(row, 0)))
+            } else {
+                BlockPlacement::Below(snapshot.anchor_after(max_point))
+            };
+            BlockProperties {
+                placement,
+                height: Some(height),
+                style: BlockStyle::Fixed,
+                render: Arc::new(|_| div().into_any_element()),
+                priority: 0,
+            }
+        })
+        .collect::<Vec<_>>();
+    let block_ids = editor.insert_blocks(blocks, None, cx);
+
+    let colors = cx.theme().status();
+    let (row_color, deleted_or_added) = if is_base {
+        (colors.deleted_background, true)
+    } else {
+        (colors.created_background, false)
+    };
+    for rows in hunk_rows.filter(|rows| !rows.is_empty()) {
+        let range = snapshot.anchor_before(Point::new(rows.start, 0))
+            ..snapshot.anchor_after(Point::new(rows.end - 1, 0));
+        let options = RowHighlightOptions {
+            include_gutter: true,
+            ..Default::default()
+        };
+        if deleted_or_added {
+            editor.highlight_rows::<DeletedRows>(range, row_color, options, cx);
+        } else {
+            editor.highlight_rows::<AddedRows>(range, row_color, options, cx);
+        }
+    }
+
+    let word_ranges = words
+        .iter()
+        .map(|range| {
+            snapshot.anchor_after(MultiBufferOffset(range.start))
+                ..snapshot.anchor_before(MultiBufferOffset(range.end))
+        })
+        .collect::<Vec<_>>();
+    if is_base {
+        editor.highlight_background::<DeletedWords>(
+            &word_ranges,
+            |theme| theme.colors().version_control_deleted.opacity(0.3),
+            cx,
+        );
+    } else {
+        editor.highlight_background::<AddedWords>(
+            &word_ranges,
+            |theme| theme.colors().version_control_added.opacity(0.3),
+            cx,
+        );
+    }
+
+    block_ids.into_iter().collect()
+}
+
+impl EventEmitter<EditorEvent> for SplitDiffView {}
+
+impl Focusable for SplitDiffView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for SplitDiffView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Diff).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, _: &App) -> SharedString {
+        self.title.clone()
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(self.path.clone())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Split Diff View Opened")
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<gpui::AnyEntity> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.clone().into())
+        } else {
+            None
+        }
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        self.editor.for_each_project_item(cx, f)
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn breadcrumb_location(&self, _: &App) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, theme: &theme::Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        self.editor.breadcrumbs(theme, cx)
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            editor.added_to_workspace(workspace, window, cx)
+        });
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.editor.read(cx).can_save(cx)
+    }
+
+    fn save(
+        &mut self,
+        options: SaveOptions,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.editor
+            .update(cx, |editor, cx| editor.save(options, project, window, cx))
+    }
+}
+
+impl Render for SplitDiffView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .size_full()
+            .child(
+                div()
+                    .flex_1()
+                    .min_w_0()
+                    .h_full()
+                    .child(self.base_editor.clone()),
+            )
+            .child(div().h_full().w_px().bg(cx.theme().colors().border_variant))
+            .child(div().flex_1().min_w_0().h_full().child(self.editor.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+        });
+    }
+
+    #[test]
+    fn test_map_row() {
+        // Base rows 2..3 are replaced by buffer rows 2..5, and buffer rows
+        // 7..8 are inserted before base row 5.
+        let hunks = [(2..3, 2..5), (5..5, 7..8)];
+        let mapped = (0..7)
+            .map(|row| map_row(row, hunks.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(mapped, [0, 1, 2, 5, 6, 8, 9]);
+
+        let reversed = hunks.map(|(base, buffer)| (buffer, base));
+        let mapped = (0..10)
+            .map(|row| map_row(row, reversed.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(mapped, [0, 1, 2, 2, 2, 3, 4, 5, 5, 6]);
+    }
+
+    #[test]
+    fn test_word_diff() {
+        assert_eq!(
+            word_diff("let a = 1;\n", "let b = 1;\n"),
+            (vec![4..5], vec![4..5])
+        );
+        assert_eq!(
+            word_diff("one two\n", "one three\n"),
+            (vec![4..7], vec![4..9])
+        );
+
+        let old_text = "let a = 1;\n".repeat(20);
+        let new_text = old_text.replacen("a", "b", 1);
+        assert_eq!(word_diff(&old_text, &new_text), (vec![], vec![]));
+    }
+
+    #[gpui::test]
+    async fn test_split_diff_alignment(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/test"),
+            json!({ "a.txt": "one\nTWO\nTWO AND A HALF\nthree\nfive\n" }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/test").as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/test/a.txt"), cx)
+            })
+            .await
+            .unwrap();
+        let diff = cx
+            .new(|cx| BufferDiff::new_with_base_text("one\ntwo\nthree\nfour\nfive\n", &buffer, cx));
+
+        let (view, cx) = cx.add_window_view(|window, cx| {
+            SplitDiffView::new(buffer.clone(), diff, Some(project), window, cx)
+        });
+        cx.run_until_parked();
+
+        let max_row = |editor: &Entity<Editor>, cx: &mut VisualTestContext| {
+            editor.update_in(cx, |editor, window, cx| {
+                editor
+                    .snapshot(window, cx)
+                    .display_snapshot
+                    .max_point()
+                    .row()
+                    .0
+            })
+        };
+        let (base_editor, editor) = view.read_with(cx, |view, _| {
+            (view.base_editor.clone(), view.editor.clone())
+        });
+        assert_eq!(
+            view.read_with(cx, |view, _| view.hunks.clone()),
+            [
+                HunkRows {
+                    base: 1..2,
+                    buffer: 1..3,
+                },
+                HunkRows {
+                    base: 3..4,
+                    buffer: 4..4,
+                },
+            ]
+        );
+        // Each side is padded to the length of the other, so both editors
+        // have the same number of rows.
+        assert_eq!(max_row(&base_editor, cx), 6);
+        assert_eq!(max_row(&editor, cx), 6);
+
+        // Once the hunks are gone, so is the padding.
+        buffer.update(cx, |buffer, cx| {
+            buffer.set_text("one\ntwo\nthree\nfour\nfive\n", cx)
+        });
+        view.update(cx, |view, cx| {
+            view.diff.update(cx, |diff, cx| {
+                diff.recalculate_diff_sync(view.buffer.read(cx).text_snapshot(), cx)
+            });
+        });
+        cx.run_until_parked();
+        assert_eq!(view.read_with(cx, |view, _| view.hunks.len()), 0);
+        assert_eq!(max_row(&base_editor, cx), 5);
+        assert_eq!(max_row(&editor, cx), 5);
+    }
+
+    #[gpui::test]
+    async fn test_split_diff_padding_after_edit(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/test"),
+            json!({ "a.txt": "one\nTWO\nTWO AND A HALF\nthree\nfour\nfive\n" }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/test").as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/test/a.txt"), cx)
+            })
+            .await
+            .unwrap();
+        let diff = cx
+            .new(|cx| BufferDiff::new_with_base_text("one\ntwo\nthree\nfour\nfive\n", &buffer, cx));
+
+        let (view, cx) = cx.add_window_view(|window, cx| {
+            SplitDiffView::new(buffer.clone(), diff, Some(project), window, cx)
+        });
+        cx.run_until_parked();
+
+        let (base_padding, padding) = view.read_with(cx, |view, _| {
+            (view.base_padding.clone(), view.padding.clone())
+        });
+        assert_eq!(base_padding.len(), 1);
+        assert_eq!(padding.len(), 0);
+
+        // Deleting a line adds a hunk that's padded in the other editor, and
+        // the padding of the existing hunk is replaced.
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(4, 0)..Point::new(5, 0), "")], None, cx)
+        });
+        view.update(cx, |view, cx| {
+            view.diff.update(cx, |diff, cx| {
+                diff.recalculate_diff_sync(view.buffer.read(cx).text_snapshot(), cx)
+            });
+        });
+        cx.run_until_parked();
+
+        assert_eq!(
+            view.read_with(cx, |view, _| view.hunks.clone()),
+            [
+                HunkRows {
+                    base: 1..2,
+                    buffer: 1..3,
+                },
+                HunkRows {
+                    base: 3..4,
+                    buffer: 4..4,
+                },
+            ]
+        );
+        let (new_base_padding, new_padding) = view.read_with(cx, |view, _| {
+            (view.base_padding.clone(), view.padding.clone())
+        });
+        assert_eq!(new_base_padding.len(), 1);
+        assert_eq!(new_padding.len(), 1);
+        assert!(new_base_padding.is_disjoint(&base_padding));
+    }
+
+    #[gpui::test]
+    async fn test_split_diff_folds(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/test"),
+            json!({ "a.txt": "one\nTWO\nTWO AND A HALF\nthree\nfive\n" }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/test").as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/test/a.txt"), cx)
+            })
+            .await
+            .unwrap();
+        let diff = cx
+            .new(|cx| BufferDiff::new_with_base_text("one\ntwo\nthree\nfour\nfive\n", &buffer, cx));
+
+        let (view, cx) = cx.add_window_view(|window, cx| {
+            SplitDiffView::new(buffer.clone(), diff, Some(project), window, cx)
+        });
+        cx.run_until_parked();
+        let (base_editor, editor) = view.read_with(cx, |view, _| {
+            (view.base_editor.clone(), view.editor.clone())
+        });
+
+        // Folding "three" through "five" folds the same rows of the base
+        // text, including the deleted "four".
+        editor.update_in(cx, |editor, window, cx| {
+            editor.fold_ranges(vec![Point::new(3, 0)..Point::new(4, 4)], false, window, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            base_editor.update(cx, |editor, cx| folded_ranges(editor, cx)),
+            [Point::new(2, 0)..Point::new(4, 4)]
+        );
+        assert_eq!(
+            editor.update(cx, |editor, cx| folded_ranges(editor, cx)),
+            [Point::new(3, 0)..Point::new(4, 4)]
+        );
+
+        // Unfolding the base text unfolds the buffer too.
+        base_editor.update(cx, |editor, cx| {
+            editor.unfold_ranges(&[Point::zero()..Point::new(5, 0)], true, false, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            editor.update(cx, |editor, cx| folded_ranges(editor, cx)),
+            []
+        );
+        assert_eq!(
+            base_editor.update(cx, |editor, cx| folded_ranges(editor, cx)),
+            []
+        );
+    }
+}
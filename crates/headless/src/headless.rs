@@ -1,3 +1,6 @@
+//! Shared setup for command-line tools that run Zed's agent and language
+//! infrastructure without opening a window.
+
 use client::{Client, ProxySettings, UserStore};
 use extension::ExtensionHostProxy;
 use fs::RealFs;
@@ -16,7 +19,7 @@ use std::sync::Arc;
 use util::ResultExt as _;
 
 /// Headless subset of `workspace::AppState`.
-pub struct ZetaCliAppState {
+pub struct HeadlessAppState {
     pub languages: Arc<LanguageRegistry>,
     pub client: Arc<Client>,
     pub user_store: Entity<UserStore>,
@@ -24,15 +27,14 @@ pub struct ZetaCliAppState {
     pub node_runtime: NodeRuntime,
 }
 
-// TODO: dedupe with crates/eval/src/eval.rs
-pub fn init(cx: &mut App) -> ZetaCliAppState {
+/// Initializes settings, the HTTP client, languages and language models.
+///
+/// `app_name` and `pkg_version` identify the tool in the User-Agent header;
+/// pass the `ZED_PKG_VERSION` set by the tool's build script as the version.
+pub fn init(app_name: &str, pkg_version: &str, cx: &mut App) -> HeadlessAppState {
     let app_commit_sha = option_env!("ZED_COMMIT_SHA").map(|s| AppCommitSha::new(s.to_owned()));
 
-    let app_version = AppVersion::load(
-        env!("ZED_PKG_VERSION"),
-        option_env!("ZED_BUILD_ID"),
-        app_commit_sha,
-    );
+    let app_version = AppVersion::load(pkg_version, option_env!("ZED_BUILD_ID"), app_commit_sha);
     release_channel::init(app_version.clone(), cx);
     gpui_tokio::init(cx);
 
@@ -41,7 +43,8 @@ pub fn init(cx: &mut App) -> ZetaCliAppState {
 
     // Set User-Agent so we can download language servers from GitHub
     let user_agent = format!(
-        "Zeta CLI/{} ({}; {})",
+        "{}/{} ({}; {})",
+        app_name,
         app_version,
         std::env::consts::OS,
         std::env::consts::ARCH
@@ -112,7 +115,7 @@ pub fn init(cx: &mut App) -> ZetaCliAppState {
     prompt_store::init(cx);
     terminal_view::init(cx);
 
-    ZetaCliAppState {
+    HeadlessAppState {
         languages,
         client,
         user_store,
@@ -0,0 +1,99 @@
+mod hex_editor_view;
+mod hex_file;
+
+use gpui::{App, AppContext as _, Context, Window, actions};
+use workspace::{
+    Workspace, invalid_item_view::InvalidItemView, notifications::DetachAndPromptErr as _,
+};
+use zed_actions::hex_editor::OpenInHexEditor;
+
+pub use crate::hex_editor_view::*;
+pub use crate::hex_file::*;
+
+actions!(
+    hex_editor,
+    [
+        /// Moves the cursor to the previous byte in the hex editor.
+        MoveLeft,
+        /// Moves the cursor to the next byte in the hex editor.
+        MoveRight,
+        /// Moves the cursor up by one row in the hex editor.
+        MoveUp,
+        /// Moves the cursor down by one row in the hex editor.
+        MoveDown,
+        /// Moves the cursor up by one page in the hex editor.
+        MovePageUp,
+        /// Moves the cursor down by one page in the hex editor.
+        MovePageDown,
+        /// Moves the cursor to the first byte of the file.
+        MoveToBeginning,
+        /// Moves the cursor to the last byte of the file.
+        MoveToEnd,
+        /// Switches between editing the bytes as hex digits and as text.
+        SwitchColumn,
+        /// Focuses the search input of the hex editor.
+        Find,
+        /// Toggles searching for a hex byte pattern or for text.
+        ToggleSearchMode,
+        /// Focuses the go to offset input of the hex editor.
+        GoToOffset,
+        /// Selects the next match of the search query.
+        SelectNextMatch,
+        /// Selects the previous match of the search query.
+        SelectPreviousMatch
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(open_in_hex_editor);
+    })
+    .detach();
+}
+
+/// Opens the active file in a hex editor, replacing the tab that shows why it
+/// couldn't be opened if there is one.
+fn open_in_hex_editor(
+    workspace: &mut Workspace,
+    _: &OpenInHexEditor,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(active_item) = workspace.active_item(cx) else {
+        return;
+    };
+    let project = workspace.project().clone();
+    let invalid_item = active_item.downcast::<InvalidItemView>();
+    let Some(project_path) = active_item.project_path(cx).or_else(|| {
+        let abs_path = invalid_item.as_ref()?.read(cx).abs_path.clone();
+        project
+            .read(cx)
+            .project_path_for_absolute_path(&abs_path, cx)
+    }) else {
+        return;
+    };
+
+    if let Some(existing) = workspace
+        .items_of_type::<HexEditorView>(cx)
+        .find(|view| view.read(cx).file().read(cx).project_path() == &project_path)
+    {
+        workspace.activate_item(&existing, true, true, window, cx);
+        return;
+    }
+
+    let open_file = HexFile::open(&project, project_path, cx);
+    let pane = workspace.active_pane().downgrade();
+    cx.spawn_in(window, async move |_, cx| {
+        let file = open_file.await?;
+        pane.update_in(cx, |pane, window, cx| {
+            let view = cx.new(|cx| HexEditorView::new(file, project, window, cx));
+            pane.add_item(Box::new(view), true, true, None, window, cx);
+            if let Some(invalid_item) = invalid_item {
+                pane.remove_item(invalid_item.entity_id(), false, false, window, cx);
+            }
+        })
+    })
+    .detach_and_prompt_err("Failed to open hex editor", window, cx, |error, _, _| {
+        Some(error.to_string())
+    });
+}
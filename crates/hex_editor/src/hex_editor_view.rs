@@ -0,0 +1,810 @@
+use std::ops::Range;
+
+use editor::{Editor, EditorSettings, actions::SelectAll, items::entry_git_aware_label_color};
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, KeyDownEvent, MouseButton,
+    Pixels, ScrollWheelEvent, Subscription, Task, Window,
+};
+use project::Project;
+use settings::Settings as _;
+use theme::{Theme, ThemeSettings};
+use ui::{Tooltip, prelude::*};
+use util::{
+    byte_search::{find_next, find_previous},
+    paths::PathExt as _,
+};
+use workspace::{
+    ItemSettings, ToolbarItemLocation, WorkspaceId,
+    item::{BreadcrumbText, Item, ItemEvent, SaveOptions, TabContentParams},
+};
+
+use crate::{
+    Find, GoToOffset, MoveDown, MoveLeft, MovePageDown, MovePageUp, MoveRight, MoveToBeginning,
+    MoveToEnd, MoveUp, SelectNextMatch, SelectPreviousMatch, SwitchColumn, ToggleSearchMode,
+    hex_file::{HexFile, HexFileEvent, parse_byte_pattern, parse_offset},
+};
+
+const BYTES_PER_ROW: usize = 16;
+
+/// Which of the two representations of the bytes is being edited.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Hex,
+    Text,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SearchMode {
+    Bytes,
+    Text,
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Next,
+    Previous,
+}
+
+pub enum HexEditorEvent {
+    Edited,
+}
+
+/// An editor for the raw bytes of a [`HexFile`], showing their offsets, their
+/// values in hex and their ASCII characters.
+///
+/// Bytes can only be overwritten, never inserted or removed, and only the
+/// visible rows are rendered, so that large files stay responsive.
+pub struct HexEditorView {
+    file: Entity<HexFile>,
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    search_editor: Entity<Editor>,
+    offset_editor: Entity<Editor>,
+    /// The offset of the byte being edited.
+    cursor: usize,
+    column: Column,
+    /// Whether the high digit of the byte under the cursor has just been
+    /// typed, so that the next digit completes it.
+    editing_low_nibble: bool,
+    /// The row at the top of the view.
+    scroll_row: usize,
+    /// Vertical scrolling that doesn't yet add up to a whole row.
+    pending_scroll_y: Pixels,
+    /// How many rows fit in the view, as of the last render.
+    page_rows: usize,
+    search_mode: SearchMode,
+    selected_match: Option<Range<usize>>,
+    search_status: Option<SharedString>,
+    search: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl HexEditorView {
+    pub fn new(
+        file: Entity<HexFile>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let search_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Find bytes, e.g. DE AD BE EF…", window, cx);
+            editor
+        });
+        let offset_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Go to offset…", window, cx);
+            editor
+        });
+        let subscriptions = vec![cx.subscribe(&file, Self::on_file_event)];
+
+        Self {
+            file,
+            project,
+            focus_handle: cx.focus_handle(),
+            search_editor,
+            offset_editor,
+            cursor: 0,
+            column: Column::Hex,
+            editing_low_nibble: false,
+            scroll_row: 0,
+            pending_scroll_y: px(0.),
+            page_rows: 0,
+            search_mode: SearchMode::Bytes,
+            selected_match: None,
+            search_status: None,
+            search: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn file(&self) -> &Entity<HexFile> {
+        &self.file
+    }
+
+    fn on_file_event(&mut self, _: Entity<HexFile>, event: &HexFileEvent, cx: &mut Context<Self>) {
+        match event {
+            HexFileEvent::Edited => {
+                cx.emit(HexEditorEvent::Edited);
+                cx.notify();
+            }
+        }
+    }
+
+    fn line_height(cx: &App) -> Pixels {
+        let settings = ThemeSettings::get_global(cx);
+        settings.buffer_font_size(cx) * settings.line_height()
+    }
+
+    fn len(&self, cx: &App) -> usize {
+        self.file.read(cx).contents().len()
+    }
+
+    fn row_count(&self, cx: &App) -> usize {
+        self.len(cx).div_ceil(BYTES_PER_ROW).max(1)
+    }
+
+    /// Moves the cursor to the byte at the given offset, scrolling it into
+    /// view.
+    fn move_cursor_to(&mut self, offset: usize, cx: &mut Context<Self>) {
+        self.cursor = offset.min(self.len(cx).saturating_sub(1));
+        self.editing_low_nibble = false;
+        let row = self.cursor / BYTES_PER_ROW;
+        let page_rows = self.page_rows.max(1);
+        if row < self.scroll_row {
+            self.scroll_row = row;
+        } else if row >= self.scroll_row + page_rows {
+            self.scroll_row = row + 1 - page_rows;
+        }
+        cx.notify();
+    }
+
+    fn move_cursor_by(&mut self, bytes: isize, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor.saturating_add_signed(bytes), cx);
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_by(-1, cx);
+    }
+
+    fn move_right(&mut self, _: &MoveRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_by(1, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        if self.cursor >= BYTES_PER_ROW {
+            self.move_cursor_by(-(BYTES_PER_ROW as isize), cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        if self.cursor + BYTES_PER_ROW < self.len(cx) {
+            self.move_cursor_by(BYTES_PER_ROW as isize, cx);
+        }
+    }
+
+    fn move_page_up(&mut self, _: &MovePageUp, _: &mut Window, cx: &mut Context<Self>) {
+        let rows = self.page_rows.max(1).min(self.cursor / BYTES_PER_ROW);
+        self.scroll_row = self.scroll_row.saturating_sub(rows);
+        self.move_cursor_by(-((rows * BYTES_PER_ROW) as isize), cx);
+    }
+
+    fn move_page_down(&mut self, _: &MovePageDown, _: &mut Window, cx: &mut Context<Self>) {
+        let last_row = self.row_count(cx) - 1;
+        let rows = self
+            .page_rows
+            .max(1)
+            .min(last_row - self.cursor / BYTES_PER_ROW);
+        self.scroll_row = (self.scroll_row + rows).min(last_row);
+        self.move_cursor_by((rows * BYTES_PER_ROW) as isize, cx);
+    }
+
+    fn move_to_beginning(&mut self, _: &MoveToBeginning, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(0, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(usize::MAX, cx);
+    }
+
+    fn switch_column(&mut self, _: &SwitchColumn, _: &mut Window, cx: &mut Context<Self>) {
+        self.column = match self.column {
+            Column::Hex => Column::Text,
+            Column::Text => Column::Hex,
+        };
+        self.editing_low_nibble = false;
+        cx.notify();
+    }
+
+    fn scroll_wheel(&mut self, event: &ScrollWheelEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let line_height = Self::line_height(cx);
+        self.pending_scroll_y -= event.delta.pixel_delta(line_height).y;
+        let rows = (self.pending_scroll_y / line_height) as isize;
+        self.pending_scroll_y -= line_height * rows as f32;
+        self.scroll_row = self
+            .scroll_row
+            .saturating_add_signed(rows)
+            .min(self.row_count(cx) - 1);
+        cx.notify();
+    }
+
+    fn key_down(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        // Keys typed into the search and offset inputs bubble up to here.
+        if !self.focus_handle.is_focused(window) {
+            return;
+        }
+        let modifiers = &event.keystroke.modifiers;
+        if modifiers.control || modifiers.alt || modifiers.platform || modifiers.function {
+            return;
+        }
+        let mut chars = event
+            .keystroke
+            .key_char
+            .as_deref()
+            .unwrap_or_default()
+            .chars();
+        if let (Some(character), None) = (chars.next(), chars.next())
+            && self.overwrite(character, cx)
+        {
+            cx.stop_propagation();
+        }
+    }
+
+    /// Overwrites the byte under the cursor with a typed hex digit or ASCII
+    /// character, depending on the column being edited.
+    fn overwrite(&mut self, character: char, cx: &mut Context<Self>) -> bool {
+        let Some(&byte) = self.file.read(cx).contents().get(self.cursor) else {
+            return false;
+        };
+        let value = match self.column {
+            Column::Hex => {
+                let Some(digit) = character.to_digit(16) else {
+                    return false;
+                };
+                if self.editing_low_nibble {
+                    (byte & 0xf0) | digit as u8
+                } else {
+                    ((digit as u8) << 4) | (byte & 0x0f)
+                }
+            }
+            Column::Text => {
+                if !character.is_ascii() || character.is_ascii_control() {
+                    return false;
+                }
+                character as u8
+            }
+        };
+        let offset = self.cursor;
+        self.file
+            .update(cx, |file, cx| file.set_byte(offset, value, cx));
+
+        if self.column == Column::Hex && !self.editing_low_nibble {
+            self.editing_low_nibble = true;
+            cx.notify();
+        } else {
+            self.move_cursor_by(1, cx);
+        }
+        true
+    }
+
+    fn find(&mut self, _: &Find, window: &mut Window, cx: &mut Context<Self>) {
+        self.search_editor.update(cx, |editor, cx| {
+            editor.select_all(&SelectAll, window, cx);
+        });
+        window.focus(&self.search_editor.focus_handle(cx));
+    }
+
+    fn toggle_search_mode(
+        &mut self,
+        _: &ToggleSearchMode,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.search_mode = match self.search_mode {
+            SearchMode::Bytes => SearchMode::Text,
+            SearchMode::Text => SearchMode::Bytes,
+        };
+        let placeholder = match self.search_mode {
+            SearchMode::Bytes => "Find bytes, e.g. DE AD BE EF…",
+            SearchMode::Text => "Find text…",
+        };
+        self.search_editor.update(cx, |editor, cx| {
+            editor.set_placeholder_text(placeholder, window, cx);
+        });
+        self.search_status = None;
+        cx.notify();
+    }
+
+    fn go_to_offset(&mut self, _: &GoToOffset, window: &mut Window, cx: &mut Context<Self>) {
+        self.offset_editor.update(cx, |editor, cx| {
+            editor.select_all(&SelectAll, window, cx);
+        });
+        window.focus(&self.offset_editor.focus_handle(cx));
+    }
+
+    fn confirm_go_to_offset(
+        &mut self,
+        _: &menu::Confirm,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let text = self.offset_editor.read(cx).text(cx);
+        if let Some(offset) = parse_offset(&text) {
+            self.move_cursor_to(offset, cx);
+            window.focus(&self.focus_handle);
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, window: &mut Window, _: &mut Context<Self>) {
+        window.focus(&self.focus_handle);
+    }
+
+    fn select_next_match(&mut self, _: &SelectNextMatch, _: &mut Window, cx: &mut Context<Self>) {
+        self.search(Direction::Next, cx);
+    }
+
+    fn select_previous_match(
+        &mut self,
+        _: &SelectPreviousMatch,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.search(Direction::Previous, cx);
+    }
+
+    /// Searches for the query from the selected match, or from the cursor if
+    /// there isn't one.
+    fn search(&mut self, direction: Direction, cx: &mut Context<Self>) {
+        let query = self.search_editor.read(cx).text(cx);
+        if query.is_empty() {
+            return;
+        }
+        let pattern = match self.search_mode {
+            SearchMode::Bytes => match parse_byte_pattern(&query) {
+                Some(pattern) => pattern,
+                None => {
+                    self.search_status = Some("Invalid byte pattern".into());
+                    cx.notify();
+                    return;
+                }
+            },
+            SearchMode::Text => query.into_bytes(),
+        };
+        let contents = self.file.read(cx).contents().clone();
+        let offset = match (direction, &self.selected_match) {
+            (Direction::Next, Some(selected_match)) => selected_match.start + 1,
+            (Direction::Previous, Some(selected_match)) => selected_match.end - 1,
+            (Direction::Next, None) => self.cursor,
+            (Direction::Previous, None) => self.cursor + 1,
+        };
+
+        self.search_status = Some("Searching…".into());
+        self.search = Some(cx.spawn(async move |this, cx| {
+            let found = cx
+                .background_spawn(async move {
+                    match direction {
+                        Direction::Next => find_next(contents.as_slice(), &pattern, offset),
+                        Direction::Previous => find_previous(contents.as_slice(), &pattern, offset),
+                    }
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                this.search = None;
+                match found {
+                    Some(range) => {
+                        this.search_status = None;
+                        this.move_cursor_to(range.start, cx);
+                        this.selected_match = Some(range);
+                    }
+                    None => {
+                        this.search_status = Some("No matches".into());
+                        this.selected_match = None;
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn render_byte(
+        &self,
+        offset: usize,
+        byte: Option<u8>,
+        column: Column,
+        is_focused: bool,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let file = self.file.read(cx);
+        let text = match (column, byte) {
+            (Column::Hex, Some(byte)) => format!("{byte:02X}"),
+            (Column::Hex, None) => "  ".to_string(),
+            (Column::Text, Some(byte)) if byte.is_ascii_graphic() || byte == b' ' => {
+                char::from(byte).to_string()
+            }
+            (Column::Text, Some(_)) => ".".to_string(),
+            (Column::Text, None) => " ".to_string(),
+        };
+        let background = if byte.is_some() && offset == self.cursor {
+            Some(if column == self.column && is_focused {
+                cx.theme().players().local().selection
+            } else {
+                cx.theme().colors().element_selected
+            })
+        } else if self
+            .selected_match
+            .as_ref()
+            .is_some_and(|selected_match| selected_match.contains(&offset))
+        {
+            Some(cx.theme().colors().search_match_background)
+        } else {
+            None
+        };
+
+        div()
+            .when(column == Column::Hex, |this| {
+                this.px_0p5()
+                    .when(offset % BYTES_PER_ROW == BYTES_PER_ROW / 2, |this| {
+                        this.ml_2()
+                    })
+            })
+            .when_some(background, |this, background| this.bg(background))
+            .when(file.is_modified(offset), |this| {
+                this.text_color(cx.theme().status().modified)
+            })
+            .when(byte.is_some(), |this| {
+                this.on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |this, _, window, cx| {
+                        this.column = column;
+                        this.move_cursor_to(offset, cx);
+                        window.focus(&this.focus_handle);
+                    }),
+                )
+            })
+            .child(text)
+    }
+
+    fn render_row(
+        &self,
+        row: usize,
+        offset_digits: usize,
+        line_height: Pixels,
+        is_focused: bool,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let contents = self.file.read(cx).contents();
+        let start = row * BYTES_PER_ROW;
+        let bytes = (start..start + BYTES_PER_ROW)
+            .map(|offset| (offset, contents.get(offset).copied()))
+            .collect::<Vec<_>>();
+
+        h_flex()
+            .h(line_height)
+            .child(
+                div()
+                    .flex_none()
+                    .pl_2()
+                    .pr_4()
+                    .text_color(cx.theme().colors().editor_line_number)
+                    .child(format!("{start:0offset_digits$X}")),
+            )
+            .child(
+                h_flex()
+                    .flex_none()
+                    .children(bytes.iter().map(|&(offset, byte)| {
+                        self.render_byte(offset, byte, Column::Hex, is_focused, cx)
+                    })),
+            )
+            .child(
+                h_flex()
+                    .flex_none()
+                    .pl_4()
+                    .children(bytes.iter().map(|&(offset, byte)| {
+                        self.render_byte(offset, byte, Column::Text, is_focused, cx)
+                    })),
+            )
+    }
+
+    fn render_input(&self, editor: &Entity<Editor>, cx: &Context<Self>) -> impl IntoElement {
+        h_flex()
+            .min_w_32()
+            .h_8()
+            .px_2()
+            .py_1()
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .rounded_md()
+            .child(editor.clone())
+    }
+}
+
+impl EventEmitter<HexEditorEvent> for HexEditorView {}
+
+impl Focusable for HexEditorView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for HexEditorView {
+    type Event = HexEditorEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            HexEditorEvent::Edited => {
+                f(ItemEvent::UpdateTab);
+                f(ItemEvent::Edit);
+            }
+        }
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.file.entity_id(), self.file.read(cx))
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.file.read(cx).abs_path().compact();
+        Some(abs_path.to_string_lossy().into_owned().into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &Window, cx: &App) -> AnyElement {
+        let project_path = self.file.read(cx).project_path().clone();
+        let label_color = if ItemSettings::get_global(cx).git_status {
+            let git_status = self
+                .project
+                .read(cx)
+                .project_path_git_status(&project_path, cx)
+                .map(|status| status.summary())
+                .unwrap_or_default();
+
+            self.project
+                .read(cx)
+                .entry_for_path(&project_path, cx)
+                .map(|entry| {
+                    entry_git_aware_label_color(git_status, entry.is_ignored, params.selected)
+                })
+                .unwrap_or_else(|| params.text_color())
+        } else {
+            params.text_color()
+        };
+
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .single_line()
+            .color(label_color)
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.file
+            .read(cx)
+            .abs_path()
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned().into())
+            .unwrap_or_default()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.file.read(cx).abs_path();
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn breadcrumb_location(&self, cx: &App) -> ToolbarItemLocation {
+        if EditorSettings::get_global(cx).toolbar.breadcrumbs {
+            ToolbarItemLocation::PrimaryLeft
+        } else {
+            ToolbarItemLocation::Hidden
+        }
+    }
+
+    fn breadcrumbs(&self, _theme: &Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        let project = self.project.read(cx);
+        let project_path = self.file.read(cx).project_path();
+        let mut path = project_path.path.clone();
+        if project.visible_worktrees(cx).count() > 1
+            && let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx)
+        {
+            path = worktree.read(cx).root_name().join(&path);
+        }
+
+        Some(vec![BreadcrumbText {
+            text: path.display(project.path_style(cx)).to_string(),
+            highlights: None,
+            font: Some(ThemeSettings::get_global(cx).buffer_font.clone()),
+        }])
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        project::ProjectItem::is_dirty(self.file.read(cx))
+    }
+
+    fn can_save(&self, _: &App) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        _: SaveOptions,
+        _: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.file.update(cx, |file, cx| file.save(cx))
+    }
+
+    fn reload(
+        &mut self,
+        _: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.file.update(cx, |file, cx| file.reload(cx))
+    }
+
+    fn can_split(&self) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Entity<Self>>>
+    where
+        Self: Sized,
+    {
+        let file = self.file.clone();
+        let project = self.project.clone();
+        Task::ready(Some(cx.new(|cx| Self::new(file, project, window, cx))))
+    }
+
+    fn buffer_kind(&self, _: &App) -> workspace::item::ItemBufferKind {
+        workspace::item::ItemBufferKind::Singleton
+    }
+}
+
+impl Render for HexEditorView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let line_height = Self::line_height(cx);
+        let theme_settings = ThemeSettings::get_global(cx);
+        let buffer_font = theme_settings.buffer_font.clone();
+        let buffer_font_size = theme_settings.buffer_font_size(cx);
+
+        // The header and footer take up about two rows.
+        self.page_rows = ((window.viewport_size().height / line_height) as usize).saturating_sub(2);
+        let len = self.len(cx);
+        let row_count = self.row_count(cx);
+        self.scroll_row = self.scroll_row.min(row_count - 1);
+        let visible_rows = self.scroll_row..(self.scroll_row + self.page_rows + 2).min(row_count);
+        let offset_digits = format!("{:X}", len.saturating_sub(1)).len().max(8);
+        let is_focused = self.focus_handle.is_focused(window);
+
+        let position = match self.file.read(cx).contents().get(self.cursor) {
+            Some(&byte) => format!(
+                "Offset 0x{:X} ({}) of {} bytes · Value 0x{byte:02X} ({byte}, {})",
+                self.cursor, self.cursor, len, byte as i8
+            ),
+            None => "Empty file".to_string(),
+        };
+        let search_mode_label = match self.search_mode {
+            SearchMode::Bytes => "Hex",
+            SearchMode::Text => "Text",
+        };
+
+        v_flex()
+            .key_context("HexEditor")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::move_left))
+            .on_action(cx.listener(Self::move_right))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::move_page_up))
+            .on_action(cx.listener(Self::move_page_down))
+            .on_action(cx.listener(Self::move_to_beginning))
+            .on_action(cx.listener(Self::move_to_end))
+            .on_action(cx.listener(Self::switch_column))
+            .on_action(cx.listener(Self::find))
+            .on_action(cx.listener(Self::toggle_search_mode))
+            .on_action(cx.listener(Self::go_to_offset))
+            .on_key_down(cx.listener(Self::key_down))
+            .child(
+                h_flex()
+                    .flex_none()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        h_flex()
+                            .key_context("HexEditorSearch")
+                            .flex_1()
+                            .gap_2()
+                            .on_action(cx.listener(Self::select_next_match))
+                            .on_action(cx.listener(Self::select_previous_match))
+                            .on_action(cx.listener(Self::cancel))
+                            .child(
+                                Button::new("search-mode", search_mode_label)
+                                    .style(ButtonStyle::Subtle)
+                                    .tooltip(Tooltip::for_action_title(
+                                        "Toggle Search Between Hex Bytes and Text",
+                                        &ToggleSearchMode,
+                                    ))
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.toggle_search_mode(&ToggleSearchMode, window, cx)
+                                    })),
+                            )
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .child(self.render_input(&self.search_editor, cx)),
+                            )
+                            .when_some(self.search_status.clone(), |this, status| {
+                                this.child(
+                                    Label::new(status)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                )
+                            }),
+                    )
+                    .child(
+                        div()
+                            .w_40()
+                            .on_action(cx.listener(Self::confirm_go_to_offset))
+                            .on_action(cx.listener(Self::cancel))
+                            .child(self.render_input(&self.offset_editor, cx)),
+                    ),
+            )
+            .child(
+                div()
+                    .id("hex-editor-rows")
+                    .flex_1()
+                    .overflow_hidden()
+                    .font(buffer_font)
+                    .text_size(buffer_font_size)
+                    .line_height(line_height)
+                    .text_color(cx.theme().colors().editor_foreground)
+                    .on_click(cx.listener(|this, _, window, _| window.focus(&this.focus_handle)))
+                    .on_scroll_wheel(cx.listener(Self::scroll_wheel))
+                    .children(visible_rows.map(|row| {
+                        self.render_row(row, offset_digits, line_height, is_focused, cx)
+                    })),
+            )
+            .child(
+                h_flex()
+                    .flex_none()
+                    .justify_between()
+                    .px_2()
+                    .py_1()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new(position)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(match self.column {
+                            Column::Hex => "Overwrite: hex",
+                            Column::Text => "Overwrite: text",
+                        })
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    ),
+            )
+    }
+}
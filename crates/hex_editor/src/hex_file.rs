@@ -0,0 +1,383 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use fs::{Fs, MTime};
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Task};
+use project::{Project, ProjectEntryId, ProjectItem, ProjectPath};
+
+/// Files larger than this aren't opened, since the hex editor keeps the whole
+/// file in memory while it's edited.
+pub const MAX_FILE_LEN: u64 = 256 * 1024 * 1024;
+
+pub enum HexFileEvent {
+    /// Bytes were overwritten, or the file was saved or reloaded.
+    Edited,
+}
+
+/// The contents of a file, loaded as raw bytes so that they can be edited in
+/// place regardless of what they contain.
+pub struct HexFile {
+    project_path: ProjectPath,
+    abs_path: PathBuf,
+    entry_id: Option<ProjectEntryId>,
+    fs: Arc<dyn Fs>,
+    /// Shared with searches and saves running in the background, so that
+    /// they don't have to copy it up front.
+    contents: Arc<Vec<u8>>,
+    /// The file's modification time when it was last loaded or saved, used to
+    /// avoid overwriting changes made on disk since then.
+    mtime: MTime,
+    /// The bytes that were overwritten since the file was last loaded or
+    /// saved, by offset, with their original value.
+    original_bytes: BTreeMap<usize, u8>,
+}
+
+impl EventEmitter<HexFileEvent> for HexFile {}
+
+impl HexFile {
+    pub fn contents(&self) -> &Arc<Vec<u8>> {
+        &self.contents
+    }
+
+    pub fn abs_path(&self) -> &Path {
+        &self.abs_path
+    }
+
+    pub fn project_path(&self) -> &ProjectPath {
+        &self.project_path
+    }
+
+    pub fn is_modified(&self, offset: usize) -> bool {
+        self.original_bytes.contains_key(&offset)
+    }
+
+    /// Opens the file at the given path as raw bytes, whatever it contains.
+    pub fn open(
+        project: &Entity<Project>,
+        project_path: ProjectPath,
+        cx: &mut App,
+    ) -> Task<anyhow::Result<Entity<Self>>> {
+        let project = project.read(cx);
+        // The file is read through this machine's file system.
+        if !project.is_local() {
+            return Task::ready(Err(anyhow::anyhow!(
+                "the hex editor is only available for local projects"
+            )));
+        }
+        let fs = project.fs().clone();
+        let entry_id = project
+            .entry_for_path(&project_path, cx)
+            .map(|entry| entry.id);
+        let Some(abs_path) = project.absolute_path(&project_path, cx) else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "no absolute path for {:?}",
+                project_path.path
+            )));
+        };
+        cx.spawn(async move |cx| {
+            let (contents, mtime) = load(fs.as_ref(), &abs_path).await?;
+            cx.new(|_| HexFile {
+                project_path,
+                abs_path,
+                entry_id,
+                fs,
+                contents: Arc::new(contents),
+                mtime,
+                original_bytes: BTreeMap::default(),
+            })
+        })
+    }
+
+    /// Overwrites the byte at the given offset. The file's length never
+    /// changes.
+    pub fn set_byte(&mut self, offset: usize, value: u8, cx: &mut Context<Self>) {
+        let Some(&byte) = self.contents.get(offset) else {
+            return;
+        };
+        if byte == value {
+            return;
+        }
+        let original = *self.original_bytes.entry(offset).or_insert(byte);
+        if original == value {
+            self.original_bytes.remove(&offset);
+        }
+        Arc::make_mut(&mut self.contents)[offset] = value;
+        cx.emit(HexFileEvent::Edited);
+        cx.notify();
+    }
+
+    pub fn save(&mut self, cx: &mut Context<Self>) -> Task<anyhow::Result<()>> {
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        let contents = self.contents.clone();
+        let mtime = self.mtime;
+        cx.spawn(async move |this, cx| {
+            let metadata = fs
+                .metadata(&abs_path)
+                .await
+                .with_context(|| format!("saving {abs_path:?}"))?;
+            if let Some(metadata) = metadata {
+                anyhow::ensure!(
+                    metadata.mtime == mtime,
+                    "{abs_path:?} has changed on disk since it was loaded, reload it before saving"
+                );
+            }
+            fs.write(&abs_path, &contents)
+                .await
+                .with_context(|| format!("saving {abs_path:?}"))?;
+            let metadata = fs
+                .metadata(&abs_path)
+                .await
+                .with_context(|| format!("saving {abs_path:?}"))?
+                .with_context(|| format!("{abs_path:?} is missing after saving"))?;
+            this.update(cx, |this, cx| {
+                this.mtime = metadata.mtime;
+                // Bytes that were overwritten while saving are still unsaved.
+                this.original_bytes
+                    .retain(|offset, original| match contents.get(*offset) {
+                        Some(saved) => {
+                            *original = *saved;
+                            this.contents[*offset] != *saved
+                        }
+                        None => true,
+                    });
+                cx.emit(HexFileEvent::Edited);
+                cx.notify();
+            })
+        })
+    }
+
+    /// Discards any edits and loads the file from disk again.
+    pub fn reload(&mut self, cx: &mut Context<Self>) -> Task<anyhow::Result<()>> {
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        cx.spawn(async move |this, cx| {
+            let (contents, mtime) = load(fs.as_ref(), &abs_path).await?;
+            this.update(cx, |this, cx| {
+                this.contents = Arc::new(contents);
+                this.mtime = mtime;
+                this.original_bytes.clear();
+                cx.emit(HexFileEvent::Edited);
+                cx.notify();
+            })
+        })
+    }
+}
+
+/// Loads the contents of the file, along with its modification time, unless
+/// it's too large to edit.
+async fn load(fs: &dyn Fs, abs_path: &Path) -> anyhow::Result<(Vec<u8>, MTime)> {
+    let metadata = fs
+        .metadata(abs_path)
+        .await
+        .with_context(|| format!("loading {abs_path:?}"))?
+        .with_context(|| format!("{abs_path:?} does not exist"))?;
+    anyhow::ensure!(
+        metadata.len <= MAX_FILE_LEN,
+        "{abs_path:?} is too large for the hex editor ({} bytes, the limit is {MAX_FILE_LEN})",
+        metadata.len
+    );
+    let contents = fs
+        .load_bytes(abs_path)
+        .await
+        .with_context(|| format!("loading {abs_path:?}"))?;
+    Ok((contents, metadata.mtime))
+}
+
+impl ProjectItem for HexFile {
+    /// Files are only opened in the hex editor on request, with
+    /// [`zed_actions::hex_editor::OpenInHexEditor`].
+    fn try_open(
+        _: &Entity<Project>,
+        _: &ProjectPath,
+        _: &mut App,
+    ) -> Option<Task<anyhow::Result<Entity<Self>>>> {
+        None
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+
+    fn is_dirty(&self) -> bool {
+        !self.original_bytes.is_empty()
+    }
+}
+
+/// Parses a byte pattern written as hex digits, such as `DE AD be ef`,
+/// `deadbeef` or `0xde 0xad`.
+pub fn parse_byte_pattern(query: &str) -> Option<Vec<u8>> {
+    let digits = query
+        .split_whitespace()
+        .map(|token| {
+            token
+                .strip_prefix("0x")
+                .or_else(|| token.strip_prefix("0X"))
+                .unwrap_or(token)
+        })
+        .collect::<String>();
+    if digits.is_empty() {
+        return None;
+    }
+    hex::decode(digits).ok()
+}
+
+/// Parses an offset written in decimal, or in hex with a `0x` prefix or when
+/// it contains hex letters.
+pub fn parse_offset(text: &str) -> Option<usize> {
+    let text = text.trim();
+    if let Some(digits) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        return usize::from_str_radix(digits, 16).ok();
+    }
+    text.parse::<usize>()
+        .ok()
+        .or_else(|| usize::from_str_radix(text, 16).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_set_byte_and_save(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/test"), json!({})).await;
+        fs.insert_file(path!("/test/a.bin"), vec![0x00, 0x01, 0x02, 0x03])
+            .await;
+        let project = Project::test(fs.clone(), [path!("/test").as_ref()], cx).await;
+        let project_path = project
+            .read_with(cx, |project, cx| {
+                project.find_project_path(path!("/test/a.bin"), cx)
+            })
+            .unwrap();
+        let file = cx
+            .update(|cx| HexFile::open(&project, project_path, cx))
+            .await
+            .unwrap();
+        file.read_with(cx, |file, _| assert!(!file.is_dirty()));
+
+        // Writing the original value back leaves the file clean, and writing
+        // past the end does nothing.
+        file.update(cx, |file, cx| {
+            file.set_byte(1, 0xff, cx);
+            file.set_byte(2, 0xee, cx);
+            file.set_byte(2, 0x02, cx);
+            file.set_byte(4, 0xdd, cx);
+        });
+        file.read_with(cx, |file, _| {
+            assert!(file.is_dirty());
+            assert!(file.is_modified(1));
+            assert!(!file.is_modified(2));
+            assert_eq!(file.contents().as_slice(), [0x00, 0xff, 0x02, 0x03]);
+        });
+        file.update(cx, |file, cx| file.set_byte(1, 0x01, cx));
+        file.read_with(cx, |file, _| assert!(!file.is_dirty()));
+
+        file.update(cx, |file, cx| file.set_byte(3, 0xab, cx));
+        file.update(cx, |file, cx| file.save(cx)).await.unwrap();
+        file.read_with(cx, |file, _| {
+            assert!(!file.is_dirty());
+            assert!(!file.is_modified(3));
+        });
+        assert_eq!(
+            fs.load_bytes(path!("/test/a.bin").as_ref()).await.unwrap(),
+            [0x00, 0x01, 0x02, 0xab]
+        );
+
+        // Writing the value that was saved over a modified byte leaves the
+        // file clean.
+        file.update(cx, |file, cx| {
+            file.set_byte(3, 0x03, cx);
+            file.set_byte(3, 0xab, cx);
+        });
+        file.read_with(cx, |file, _| assert!(!file.is_dirty()));
+    }
+
+    #[gpui::test]
+    async fn test_save_after_change_on_disk(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/test"), json!({})).await;
+        fs.insert_file(path!("/test/a.bin"), vec![0x00, 0x01]).await;
+        let project = Project::test(fs.clone(), [path!("/test").as_ref()], cx).await;
+        let project_path = project
+            .read_with(cx, |project, cx| {
+                project.find_project_path(path!("/test/a.bin"), cx)
+            })
+            .unwrap();
+        let file = cx
+            .update(|cx| HexFile::open(&project, project_path, cx))
+            .await
+            .unwrap();
+
+        // Saving doesn't overwrite changes made on disk since the file was
+        // loaded.
+        fs.insert_file(path!("/test/a.bin"), vec![0x10, 0x11]).await;
+        file.update(cx, |file, cx| file.set_byte(0, 0xff, cx));
+        file.update(cx, |file, cx| file.save(cx)).await.unwrap_err();
+        assert_eq!(
+            fs.load_bytes(path!("/test/a.bin").as_ref()).await.unwrap(),
+            [0x10, 0x11]
+        );
+        file.read_with(cx, |file, _| assert!(file.is_dirty()));
+
+        file.update(cx, |file, cx| file.reload(cx)).await.unwrap();
+        file.read_with(cx, |file, _| {
+            assert!(!file.is_dirty());
+            assert_eq!(file.contents().as_slice(), [0x10, 0x11]);
+        });
+        file.update(cx, |file, cx| file.set_byte(0, 0xff, cx));
+        file.update(cx, |file, cx| file.save(cx)).await.unwrap();
+        file.update(cx, |file, cx| file.set_byte(1, 0xee, cx));
+        file.update(cx, |file, cx| file.save(cx)).await.unwrap();
+        assert_eq!(
+            fs.load_bytes(path!("/test/a.bin").as_ref()).await.unwrap(),
+            [0xff, 0xee]
+        );
+    }
+
+    #[test]
+    fn test_parse_byte_pattern() {
+        assert_eq!(
+            parse_byte_pattern("DE AD be ef"),
+            Some(vec![0xde, 0xad, 0xbe, 0xef])
+        );
+        assert_eq!(
+            parse_byte_pattern("deadbeef"),
+            Some(vec![0xde, 0xad, 0xbe, 0xef])
+        );
+        assert_eq!(parse_byte_pattern("0x7f 0X45"), Some(vec![0x7f, 0x45]));
+        assert_eq!(parse_byte_pattern("abc"), None);
+        assert_eq!(parse_byte_pattern("zz"), None);
+        assert_eq!(parse_byte_pattern("  "), None);
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("1024"), Some(1024));
+        assert_eq!(parse_offset(" 0x400 "), Some(1024));
+        assert_eq!(parse_offset("1fF"), Some(0x1ff));
+        assert_eq!(parse_offset("0x"), None);
+        assert_eq!(parse_offset("-1"), None);
+    }
+}
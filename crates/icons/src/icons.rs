@@ -48,6 +48,7 @@ pub enum IconName {
     BoltOutlined,
     BoltFilled,
     Book,
+    Bookmark,
     BookCopy,
     CaseSensitive,
     Chat,
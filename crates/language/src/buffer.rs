@@ -24,7 +24,7 @@ use anyhow::{Context as _, Result};
 pub use clock::ReplicaId;
 use clock::{Global, Lamport};
 use collections::{HashMap, HashSet};
-use fs::MTime;
+use fs::{Encoding, MTime};
 use futures::channel::oneshot;
 use gpui::{
     App, AppContext as _, Context, Entity, EventEmitter, HighlightStyle, SharedString, StyledText,
@@ -100,6 +100,8 @@ pub struct Buffer {
     /// The mtime of the file when this buffer was last loaded from
     /// or saved to disk.
     saved_mtime: Option<MTime>,
+    /// The encoding of the file on disk.
+    encoding: Encoding,
     /// The version vector when this buffer was last loaded from
     /// or saved to disk.
     saved_version: clock::Global,
@@ -327,6 +329,14 @@ pub enum Operation {
         /// The buffer's lamport timestamp.
         lamport_timestamp: clock::Lamport,
     },
+
+    /// An update to the encoding of this buffer's file.
+    UpdateEncoding {
+        /// The encoding.
+        encoding: Encoding,
+        /// The buffer's lamport timestamp.
+        lamport_timestamp: clock::Lamport,
+    },
 }
 
 /// An event that occurs in a buffer.
@@ -921,6 +931,9 @@ impl Buffer {
         this.text.set_line_ending(proto::deserialize_line_ending(
             rpc::proto::LineEnding::from_i32(message.line_ending).context("missing line_ending")?,
         ));
+        if let Some(encoding) = message.encoding {
+            this.encoding = proto::deserialize_encoding(encoding)?;
+        }
         this.saved_version = proto::deserialize_version(&message.saved_version);
         this.saved_mtime = message.saved_mtime.map(|time| time.into());
         Ok(this)
@@ -933,6 +946,7 @@ impl Buffer {
             file: self.file.as_ref().map(|f| f.to_proto(cx)),
             base_text: self.base_text().to_string(),
             line_ending: proto::serialize_line_ending(self.line_ending()) as i32,
+            encoding: Some(proto::serialize_encoding(self.encoding)),
             saved_version: proto::serialize_version(&self.saved_version),
             saved_mtime: self.saved_mtime.map(|time| time.into()),
         }
@@ -988,6 +1002,12 @@ impl Buffer {
         })
     }
 
+    /// Assign the encoding of the buffer's file, returning the buffer.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
     /// Assign a language to the buffer, returning the buffer.
     pub fn with_language(mut self, language: Arc<Language>, cx: &mut Context<Self>) -> Self {
         self.set_language(Some(language), cx);
@@ -1012,6 +1032,7 @@ impl Buffer {
         let tree_sitter_data = TreeSitterData::new(snapshot);
         Self {
             saved_mtime,
+            encoding: Encoding::default(),
             tree_sitter_data: Arc::new(Mutex::new(tree_sitter_data)),
             saved_version: buffer.version(),
             preview_version: buffer.version(),
@@ -1160,6 +1181,7 @@ impl Buffer {
                 language: self.language.clone(),
                 has_conflict: self.has_conflict,
                 has_unsaved_edits: Cell::new(self.has_unsaved_edits.get_mut().clone()),
+                encoding: self.encoding,
                 _subscriptions: vec![cx.subscribe(&this, Self::on_base_buffer_event)],
                 ..Self::build(self.text.branch(), self.file.clone(), self.capability())
             };
@@ -1356,6 +1378,29 @@ impl Buffer {
         );
     }
 
+    /// The encoding that the buffer's file is read and written with.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Assign the encoding that the buffer's file is read and written with.
+    /// This doesn't change the buffer's text; reload or save the buffer to
+    /// apply the new encoding.
+    pub fn set_encoding(&mut self, encoding: Encoding, cx: &mut Context<Self>) {
+        self.encoding = encoding;
+
+        let lamport_timestamp = self.text.lamport_clock.tick();
+        self.send_operation(
+            Operation::UpdateEncoding {
+                encoding,
+                lamport_timestamp,
+            },
+            true,
+            cx,
+        );
+        cx.notify();
+    }
+
     /// Assign the buffer a new [`Capability`].
     pub fn set_capability(&mut self, capability: Capability, cx: &mut Context<Self>) {
         if self.capability != capability {
@@ -1385,16 +1430,23 @@ impl Buffer {
         let (tx, rx) = futures::channel::oneshot::channel();
         let prev_version = self.text.version();
         self.reload_task = Some(cx.spawn(async move |this, cx| {
-            let Some((new_mtime, new_text)) = this.update(cx, |this, cx| {
+            let Some((new_mtime, new_bytes, encoding)) = this.update(cx, |this, cx| {
                 let file = this.file.as_ref()?.as_local()?;
 
-                Some((file.disk_state().mtime(), file.load(cx)))
+                Some((
+                    file.disk_state().mtime(),
+                    file.load_bytes(cx),
+                    this.encoding,
+                ))
             })?
             else {
                 return Ok(());
             };
 
-            let new_text = new_text.await?;
+            let new_bytes = new_bytes.await?;
+            let new_text = cx
+                .background_spawn(async move { encoding.decode(new_bytes) })
+                .await?;
             let diff = this
                 .update(cx, |this, cx| this.diff(new_text.clone(), cx))?
                 .await;
@@ -2696,7 +2748,9 @@ impl Buffer {
             Operation::UpdateSelections { selections, .. } => selections
                 .iter()
                 .all(|s| self.can_resolve(&s.start) && self.can_resolve(&s.end)),
-            Operation::UpdateCompletionTriggers { .. } | Operation::UpdateLineEnding { .. } => true,
+            Operation::UpdateCompletionTriggers { .. }
+            | Operation::UpdateLineEnding { .. }
+            | Operation::UpdateEncoding { .. } => true,
         }
     }
 
@@ -2769,6 +2823,14 @@ impl Buffer {
                 self.text.set_line_ending(line_ending);
                 self.text.lamport_clock.observe(lamport_timestamp);
             }
+            Operation::UpdateEncoding {
+                encoding,
+                lamport_timestamp,
+            } => {
+                self.encoding = encoding;
+                self.text.lamport_clock.observe(lamport_timestamp);
+                cx.notify();
+            }
         }
     }
 
@@ -5263,6 +5325,9 @@ impl operation_queue::Operation for Operation {
             }
             | Operation::UpdateLineEnding {
                 lamport_timestamp, ..
+            }
+            | Operation::UpdateEncoding {
+                lamport_timestamp, ..
             } => *lamport_timestamp,
         }
     }
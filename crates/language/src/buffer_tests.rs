@@ -144,6 +144,53 @@ fn test_set_line_ending(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+fn test_set_encoding(cx: &mut TestAppContext) {
+    let latin1 = fs::Encoding::from_name("latin1", false).unwrap();
+    let utf16 = fs::Encoding::from_name("utf-16le", true).unwrap();
+
+    let base = cx.new(|cx| Buffer::local("café\n", cx).with_encoding(latin1));
+    let base_replica = cx.new(|cx| {
+        Buffer::from_proto(
+            ReplicaId::new(1),
+            Capability::ReadWrite,
+            base.read(cx).to_proto(cx),
+            None,
+        )
+        .unwrap()
+    });
+    base_replica.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.encoding(), latin1);
+    });
+
+    // Operations are sent through their RPC representation.
+    base_replica.update(cx, |_buffer, cx| {
+        cx.subscribe(&base, |this, _, event, cx| {
+            if let BufferEvent::Operation {
+                operation,
+                is_local: true,
+            } = event
+            {
+                let operation =
+                    proto::deserialize_operation(proto::serialize_operation(operation)).unwrap();
+                this.apply_ops([operation], cx);
+            }
+        })
+        .detach();
+    });
+
+    base.update(cx, |buffer, cx| {
+        buffer.set_encoding(utf16, cx);
+        assert_eq!(buffer.encoding(), utf16);
+    });
+    base_replica.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.encoding(), utf16);
+        assert!(buffer.encoding().has_bom());
+        // Changing the encoding doesn't change the text.
+        assert_eq!(buffer.text(), "café\n");
+    });
+}
+
 #[gpui::test]
 fn test_select_language(cx: &mut App) {
     init_settings(cx, |_| {});
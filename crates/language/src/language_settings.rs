@@ -11,9 +11,10 @@ use gpui::{App, Modifiers, SharedString};
 use itertools::{Either, Itertools};
 
 pub use settings::{
-    CompletionSettingsContent, EditPredictionProvider, EditPredictionsMode, FormatOnSave,
-    Formatter, FormatterList, InlayHintKind, LanguageSettingsContent, LspInsertMode,
-    RewrapBehavior, ShowWhitespaceSetting, SoftWrap, WordsCompletionMode,
+    CompletionSettingsContent, EditPredictionProvider, EditPredictionsMode, FimTemplate,
+    FormatOnSave, Formatter, FormatterList, InlayHintKind, LanguageSettingsContent,
+    LocalEditPredictionApi, LspInsertMode, RewrapBehavior, ShowWhitespaceSetting, SoftWrap,
+    WordsCompletionMode,
 };
 use settings::{RegisterSetting, Settings, SettingsLocation, SettingsStore};
 use shellexpand;
@@ -376,6 +377,8 @@ pub struct EditPredictionSettings {
     pub copilot: CopilotSettings,
     /// Settings specific to Codestral.
     pub codestral: CodestralSettings,
+    /// Settings specific to locally hosted models.
+    pub local: LocalEditPredictionSettings,
     /// Whether edit predictions are enabled in the assistant panel.
     /// This setting has no effect if globally disabled.
     pub enabled_in_text_threads: bool,
@@ -421,6 +424,20 @@ pub struct CodestralSettings {
     pub api_url: Option<String>,
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct LocalEditPredictionSettings {
+    /// The API spoken by the model server.
+    pub api: LocalEditPredictionApi,
+    /// Custom API URL of the model server.
+    pub api_url: Option<String>,
+    /// Model to use for completions.
+    pub model: Option<String>,
+    /// Fill-in-the-middle prompt format, inferred from the model when unset.
+    pub fim_template: Option<FimTemplate>,
+    /// Maximum tokens to generate.
+    pub max_tokens: Option<u32>,
+}
+
 impl AllLanguageSettings {
     /// Returns the [`LanguageSettings`] for the language with the specified name.
     pub fn language<'a>(
@@ -639,6 +656,15 @@ impl settings::Settings for AllLanguageSettings {
             api_url: codestral.api_url,
         };
 
+        let local = edit_predictions.local.unwrap();
+        let local_settings = LocalEditPredictionSettings {
+            api: local.api.unwrap_or_default(),
+            api_url: local.api_url,
+            model: local.model,
+            fim_template: local.fim_template,
+            max_tokens: local.max_tokens,
+        };
+
         let enabled_in_text_threads = edit_predictions.enabled_in_text_threads.unwrap();
 
         let mut file_types: FxHashMap<Arc<str>, GlobSet> = FxHashMap::default();
@@ -673,6 +699,7 @@ impl settings::Settings for AllLanguageSettings {
                 mode: edit_predictions_mode,
                 copilot: copilot_settings,
                 codestral: codestral_settings,
+                local: local_settings,
                 enabled_in_text_threads,
             },
             defaults: default_language_settings,
@@ -29,6 +29,20 @@ pub fn serialize_line_ending(message: text::LineEnding) -> proto::LineEnding {
     }
 }
 
+/// Deserializes a [`fs::Encoding`] from the RPC representation.
+pub fn deserialize_encoding(message: proto::Encoding) -> Result<fs::Encoding> {
+    fs::Encoding::from_name(&message.name, message.bom)
+        .with_context(|| format!("unknown encoding {:?}", message.name))
+}
+
+/// Serializes a [`fs::Encoding`] to be sent over RPC.
+pub fn serialize_encoding(encoding: fs::Encoding) -> proto::Encoding {
+    proto::Encoding {
+        name: encoding.name().to_string(),
+        bom: encoding.has_bom(),
+    }
+}
+
 /// Serializes a [`crate::Operation`] to be sent over RPC.
 pub fn serialize_operation(operation: &crate::Operation) -> proto::Operation {
     proto::Operation {
@@ -99,6 +113,15 @@ pub fn serialize_operation(operation: &crate::Operation) -> proto::Operation {
                 lamport_timestamp: lamport_timestamp.value,
                 line_ending: serialize_line_ending(*line_ending) as i32,
             }),
+
+            crate::Operation::UpdateEncoding {
+                encoding,
+                lamport_timestamp,
+            } => proto::operation::Variant::UpdateEncoding(proto::operation::UpdateEncoding {
+                replica_id: lamport_timestamp.replica_id.as_u16() as u32,
+                lamport_timestamp: lamport_timestamp.value,
+                encoding: Some(serialize_encoding(*encoding)),
+            }),
         }),
     }
 }
@@ -362,6 +385,15 @@ pub fn deserialize_operation(message: proto::Operation) -> Result<crate::Operati
                     ),
                 }
             }
+            proto::operation::Variant::UpdateEncoding(message) => {
+                crate::Operation::UpdateEncoding {
+                    lamport_timestamp: clock::Lamport {
+                        replica_id: ReplicaId::new(message.replica_id as u16),
+                        value: message.lamport_timestamp,
+                    },
+                    encoding: deserialize_encoding(message.encoding.context("missing encoding")?)?,
+                }
+            }
         },
     )
 }
@@ -521,6 +553,10 @@ pub fn lamport_timestamp_for_operation(operation: &proto::Operation) -> Option<c
             replica_id = op.replica_id;
             value = op.lamport_timestamp;
         }
+        proto::operation::Variant::UpdateEncoding(op) => {
+            replica_id = op.replica_id;
+            value = op.lamport_timestamp;
+        }
     }
 
     Some(clock::Lamport {
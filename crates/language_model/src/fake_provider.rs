@@ -1,8 +1,8 @@
 use crate::{
     AuthenticateError, ConfigurationViewTargetAgent, LanguageModel, LanguageModelCompletionError,
-    LanguageModelCompletionEvent, LanguageModelId, LanguageModelName, LanguageModelProvider,
-    LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
-    LanguageModelRequest, LanguageModelToolChoice,
+    LanguageModelCompletionEvent, LanguageModelId, LanguageModelName, LanguageModelPricing,
+    LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
+    LanguageModelProviderState, LanguageModelRequest, LanguageModelToolChoice,
 };
 use anyhow::anyhow;
 use futures::{FutureExt, channel::mpsc, future::BoxFuture, stream::BoxStream};
@@ -19,6 +19,7 @@ use std::sync::{
 pub struct FakeLanguageModelProvider {
     id: LanguageModelProviderId,
     name: LanguageModelProviderName,
+    model: Option<Arc<FakeLanguageModel>>,
 }
 
 impl Default for FakeLanguageModelProvider {
@@ -26,6 +27,7 @@ impl Default for FakeLanguageModelProvider {
         Self {
             id: LanguageModelProviderId::from("fake".to_string()),
             name: LanguageModelProviderName::from("Fake".to_string()),
+            model: None,
         }
     }
 }
@@ -56,7 +58,11 @@ impl LanguageModelProvider for FakeLanguageModelProvider {
     }
 
     fn provided_models(&self, _: &App) -> Vec<Arc<dyn LanguageModel>> {
-        vec![Arc::new(FakeLanguageModel::default())]
+        if let Some(model) = &self.model {
+            vec![model.clone()]
+        } else {
+            vec![Arc::new(FakeLanguageModel::default())]
+        }
     }
 
     fn is_authenticated(&self, _: &App) -> bool {
@@ -83,11 +89,26 @@ impl LanguageModelProvider for FakeLanguageModelProvider {
 
 impl FakeLanguageModelProvider {
     pub fn new(id: LanguageModelProviderId, name: LanguageModelProviderName) -> Self {
-        Self { id, name }
+        Self {
+            id,
+            name,
+            model: None,
+        }
+    }
+
+    /// Makes the provider always offer the given model, so that tests can
+    /// drive the completions of a model selected through the registry.
+    pub fn with_model(mut self, model: Arc<FakeLanguageModel>) -> Self {
+        self.model = Some(model);
+        self
     }
 
     pub fn test_model(&self) -> FakeLanguageModel {
-        FakeLanguageModel::default()
+        FakeLanguageModel {
+            provider_id: self.id.clone(),
+            provider_name: self.name.clone(),
+            ..FakeLanguageModel::default()
+        }
     }
 }
 
@@ -111,6 +132,7 @@ pub struct FakeLanguageModel {
         )>,
     >,
     forbid_requests: AtomicBool,
+    pricing: Mutex<Option<LanguageModelPricing>>,
 }
 
 impl Default for FakeLanguageModel {
@@ -120,11 +142,16 @@ impl Default for FakeLanguageModel {
             provider_name: LanguageModelProviderName::from("Fake".to_string()),
             current_completion_txs: Mutex::new(Vec::new()),
             forbid_requests: AtomicBool::new(false),
+            pricing: Mutex::new(None),
         }
     }
 }
 
 impl FakeLanguageModel {
+    pub fn set_pricing(&self, pricing: Option<LanguageModelPricing>) {
+        *self.pricing.lock() = pricing;
+    }
+
     pub fn allow_requests(&self) {
         self.forbid_requests.store(false, SeqCst);
     }
@@ -250,6 +277,10 @@ impl LanguageModel for FakeLanguageModel {
         1000000
     }
 
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        *self.pricing.lock()
+    }
+
     fn count_tokens(&self, _: LanguageModelRequest, _: &App) -> BoxFuture<'static, Result<u64>> {
         futures::future::ready(Ok(0)).boxed()
     }
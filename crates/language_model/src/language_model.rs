@@ -1,6 +1,7 @@
 mod model;
 mod rate_limiter;
 mod registry;
+pub mod replay_provider;
 mod request;
 mod role;
 mod telemetry;
@@ -10,7 +11,7 @@ pub mod tool_schema;
 pub mod fake_provider;
 
 use anthropic::{AnthropicError, parse_prompt_too_long};
-use anyhow::{Result, anyhow};
+use anyhow::{Context as _, Result, anyhow};
 use client::Client;
 use cloud_llm_client::{CompletionMode, CompletionRequestStatus, UsageLimit};
 use futures::FutureExt;
@@ -20,7 +21,8 @@ use http_client::{StatusCode, http};
 use icons::IconName;
 use open_router::OpenRouterError;
 use parking_lot::Mutex;
-use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 pub use settings::LanguageModelCacheConfiguration;
 use std::ops::{Add, Sub};
 use std::str::FromStr;
@@ -500,6 +502,51 @@ impl TokenUsage {
             + self.cache_read_input_tokens
             + self.cache_creation_input_tokens
     }
+
+    /// Like subtraction, but clamps each count at zero.
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self {
+            input_tokens: self.input_tokens.saturating_sub(other.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(other.output_tokens),
+            cache_creation_input_tokens: self
+                .cache_creation_input_tokens
+                .saturating_sub(other.cache_creation_input_tokens),
+            cache_read_input_tokens: self
+                .cache_read_input_tokens
+                .saturating_sub(other.cache_read_input_tokens),
+        }
+    }
+}
+
+/// What a model charges for tokens, in US dollars per million tokens.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Default)]
+pub struct LanguageModelPricing {
+    pub input: f64,
+    pub output: f64,
+    #[serde(default)]
+    pub cache_read: f64,
+    #[serde(default)]
+    pub cache_write: f64,
+}
+
+impl LanguageModelPricing {
+    pub const fn new(input: f64, output: f64, cache_read: f64, cache_write: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_read,
+            cache_write,
+        }
+    }
+
+    /// The cost of the given usage, in US dollars.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_read_input_tokens as f64 * self.cache_read
+            + usage.cache_creation_input_tokens as f64 * self.cache_write)
+            / 1_000_000.
+    }
 }
 
 impl Add<TokenUsage> for TokenUsage {
@@ -609,6 +656,11 @@ pub trait LanguageModel: Send + Sync {
         false
     }
 
+    /// Whether this model honors [`LanguageModelRequest::response_format`].
+    fn supports_response_format(&self) -> bool {
+        false
+    }
+
     fn tool_input_format(&self) -> LanguageModelToolSchemaFormat {
         LanguageModelToolSchemaFormat::JsonSchema
     }
@@ -622,6 +674,12 @@ pub trait LanguageModel: Send + Sync {
         None
     }
 
+    /// What this model charges per token, if known. Models that are free to
+    /// use, such as local ones, also return `None`.
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        None
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -726,9 +784,73 @@ pub trait LanguageModelExt: LanguageModel {
                 .unwrap_or_else(|| self.max_token_count()),
         }
     }
+
+    /// Requests a completion constrained to the JSON schema of `T`, then
+    /// validates and deserializes the response.
+    ///
+    /// Models that don't support [`LanguageModelRequest::response_format`]
+    /// are asked for JSON in the prompt instead.
+    fn complete_json<T: JsonSchema + DeserializeOwned + 'static>(
+        &self,
+        mut request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> BoxFuture<'static, Result<T>> {
+        let format = LanguageModelResponseFormat::json_schema::<T>(self.tool_input_format());
+        if self.supports_response_format() {
+            request.response_format = Some(format.clone());
+        } else {
+            request.messages.push(LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![MessageContent::Text(format!(
+                    "Respond with only a JSON value matching the following JSON schema, \
+                    without any surrounding text or code fences:\n\n{}",
+                    format.schema
+                ))],
+                cache: false,
+                reasoning_details: None,
+            });
+        }
+
+        let events = self.stream_completion(request, cx);
+        async move {
+            let mut events = events.await?;
+            let mut text = String::new();
+            let mut tool_input = None;
+            while let Some(event) = events.next().await {
+                match event? {
+                    LanguageModelCompletionEvent::Text(chunk) => text.push_str(&chunk),
+                    LanguageModelCompletionEvent::ToolUse(tool_use)
+                        if tool_use.is_input_complete && *tool_use.name == *format.name =>
+                    {
+                        tool_input = Some(tool_use.input);
+                    }
+                    _ => {}
+                }
+            }
+
+            let response = match tool_input {
+                Some(input) => input,
+                None => serde_json::from_str(strip_code_fence(&text))
+                    .context("model response is not valid JSON")?,
+            };
+            format.parse(response)
+        }
+        .boxed()
+    }
 }
 impl LanguageModelExt for dyn LanguageModel {}
 
+fn strip_code_fence(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_prefix("```")
+        .and_then(|text| text.strip_suffix("```"))
+        .map_or(text, |fenced| {
+            fenced
+                .split_once('\n')
+                .map_or(fenced, |(_language, body)| body)
+        })
+}
+
 /// An error that occurred when trying to authenticate the language model provider.
 #[derive(Debug, Error)]
 pub enum AuthenticateError {
@@ -1049,4 +1171,66 @@ mod tests {
         assert_eq!(deserialized.name, original.name);
         assert_eq!(deserialized.thought_signature, None);
     }
+
+    #[test]
+    fn test_pricing_cost() {
+        let pricing = LanguageModelPricing::new(3., 15., 0.3, 3.75);
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_creation_input_tokens: 200_000,
+            cache_read_input_tokens: 2_000_000,
+        };
+        assert!((pricing.cost(&usage) - 5.85).abs() < 1e-9);
+        assert_eq!(pricing.cost(&TokenUsage::default()), 0.);
+    }
+
+    #[gpui::test]
+    async fn test_complete_json(cx: &mut gpui::TestAppContext) {
+        #[derive(Debug, PartialEq, Deserialize, JsonSchema)]
+        struct CommitMessage {
+            subject: String,
+            body: Option<String>,
+        }
+
+        let fake = Arc::new(fake_provider::FakeLanguageModel::default());
+        let model: Arc<dyn LanguageModel> = fake.clone();
+
+        let response = cx.update(|cx| {
+            model.complete_json::<CommitMessage>(LanguageModelRequest::default(), &cx.to_async())
+        });
+        let response = cx.executor().spawn(response);
+        cx.run_until_parked();
+
+        // The fake model doesn't support response formats, so the schema is
+        // requested in the prompt.
+        let request = fake.pending_completions().pop().unwrap();
+        assert_eq!(request.response_format, None);
+        assert!(
+            request.messages[0]
+                .string_contents()
+                .contains("\"subject\"")
+        );
+
+        fake.send_last_completion_stream_text_chunk("```json\n{\"subject\": ");
+        fake.send_last_completion_stream_text_chunk("\"Fix typo\"}\n```");
+        fake.end_last_completion_stream();
+        assert_eq!(
+            response.await.unwrap(),
+            CommitMessage {
+                subject: "Fix typo".into(),
+                body: None,
+            }
+        );
+
+        let response = cx.update(|cx| {
+            model.complete_json::<CommitMessage>(LanguageModelRequest::default(), &cx.to_async())
+        });
+        let response = cx.executor().spawn(response);
+        cx.run_until_parked();
+        fake.send_last_completion_stream_text_chunk("{\"body\": \"Missing subject\"}");
+        fake.end_last_completion_stream();
+        let error = response.await.unwrap_err();
+        assert!(error.to_string().contains("does not match"), "{error}");
+    }
 }
@@ -0,0 +1,739 @@
+//! Recording and replaying of language model completions.
+//!
+//! [`RecordingLanguageModel`] wraps any model and writes every request it
+//! serves, along with the streamed completion events, to a cassette file.
+//! [`ReplayLanguageModel`] serves those events back without network access,
+//! looking up recordings by a fingerprint of the request.
+
+use crate::{
+    AuthenticateError, ConfigurationViewTargetAgent, LanguageModel, LanguageModelCompletionError,
+    LanguageModelCompletionEvent, LanguageModelId, LanguageModelName, LanguageModelPricing,
+    LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
+    LanguageModelProviderState, LanguageModelRequest, LanguageModelToolChoice,
+    LanguageModelToolSchemaFormat,
+};
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use futures::{FutureExt, StreamExt, future::BoxFuture, stream::BoxStream};
+use gpui::{AnyView, App, AppContext as _, AsyncApp, EmptyView, Entity, Task, Window};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use util::ResultExt as _;
+
+pub const REPLAY_PROVIDER_ID: LanguageModelProviderId = LanguageModelProviderId::new("replay");
+pub const REPLAY_PROVIDER_NAME: LanguageModelProviderName =
+    LanguageModelProviderName::new("Replay");
+
+/// The recorded completions of a single model.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Cassette {
+    pub model: RecordedModel,
+    pub interactions: Vec<RecordedInteraction>,
+}
+
+impl Cassette {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading cassette {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("parsing cassette {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("writing cassette {}", path.display()))
+    }
+}
+
+/// The properties of the recorded model that callers may depend on.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RecordedModel {
+    pub provider_id: String,
+    pub provider_name: String,
+    pub id: String,
+    pub name: String,
+    pub telemetry_id: String,
+    pub max_token_count: u64,
+    pub max_output_tokens: Option<u64>,
+    pub supports_images: bool,
+    pub supports_tools: bool,
+    pub supported_tool_choices: Vec<LanguageModelToolChoice>,
+    pub uses_json_schema_subset: bool,
+    #[serde(default)]
+    pub pricing: Option<LanguageModelPricing>,
+}
+
+impl RecordedModel {
+    fn new(model: &dyn LanguageModel) -> Self {
+        Self {
+            provider_id: model.provider_id().0.to_string(),
+            provider_name: model.provider_name().0.to_string(),
+            id: model.id().0.to_string(),
+            name: model.name().0.to_string(),
+            telemetry_id: model.telemetry_id(),
+            max_token_count: model.max_token_count(),
+            max_output_tokens: model.max_output_tokens(),
+            supports_images: model.supports_images(),
+            supports_tools: model.supports_tools(),
+            supported_tool_choices: [
+                LanguageModelToolChoice::Auto,
+                LanguageModelToolChoice::Any,
+                LanguageModelToolChoice::None,
+            ]
+            .into_iter()
+            .filter(|choice| model.supports_tool_choice(choice.clone()))
+            .collect(),
+            uses_json_schema_subset: model.tool_input_format()
+                == LanguageModelToolSchemaFormat::JsonSchemaSubset,
+            pricing: model.pricing(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedInteraction {
+    pub fingerprint: String,
+    pub request: LanguageModelRequest,
+    /// Set when the completion failed before any events were streamed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub events: Vec<RecordedEvent>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedEvent {
+    Event(LanguageModelCompletionEvent),
+    /// Errors are recorded by their message, since they aren't serializable.
+    Error(String),
+}
+
+impl RecordedEvent {
+    fn new(event: &Result<LanguageModelCompletionEvent, LanguageModelCompletionError>) -> Self {
+        match event {
+            Ok(event) => Self::Event(event.clone()),
+            Err(error) => Self::Error(error.to_string()),
+        }
+    }
+
+    fn replay(&self) -> Result<LanguageModelCompletionEvent, LanguageModelCompletionError> {
+        match self {
+            Self::Event(event) => Ok(event.clone()),
+            Self::Error(message) => Err(LanguageModelCompletionError::Other(anyhow!(
+                message.clone()
+            ))),
+        }
+    }
+}
+
+/// Determines which parts of a request identify it when looking up a
+/// recording.
+#[derive(Clone, Debug)]
+pub struct RequestMatching {
+    /// JSON pointers into the serialized request that are removed before the
+    /// request is fingerprinted. A `*` segment matches every element of an
+    /// array or every value of an object, e.g. `/messages/*/cache`.
+    pub ignored_fields: Vec<String>,
+}
+
+impl Default for RequestMatching {
+    fn default() -> Self {
+        Self {
+            // These are generated anew for every thread.
+            ignored_fields: vec!["/thread_id".into(), "/prompt_id".into()],
+        }
+    }
+}
+
+impl RequestMatching {
+    pub fn ignoring(mut self, field: impl Into<String>) -> Self {
+        self.ignored_fields.push(field.into());
+        self
+    }
+
+    pub fn fingerprint(&self, request: &LanguageModelRequest) -> String {
+        let mut request = serde_json::to_value(request).unwrap_or_default();
+        for field in &self.ignored_fields {
+            let segments = field
+                .trim_start_matches('/')
+                .split('/')
+                .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+                .collect::<Vec<_>>();
+            remove_field(&mut request, &segments);
+        }
+        let digest = Sha256::digest(request.to_string().as_bytes());
+        digest
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>()
+    }
+}
+
+fn remove_field(value: &mut serde_json::Value, path: &[String]) {
+    let Some((segment, rest)) = path.split_first() else {
+        return;
+    };
+    match value {
+        serde_json::Value::Object(map) => {
+            if segment == "*" {
+                if rest.is_empty() {
+                    map.clear();
+                } else {
+                    for value in map.values_mut() {
+                        remove_field(value, rest);
+                    }
+                }
+            } else if rest.is_empty() {
+                map.remove(segment);
+            } else if let Some(value) = map.get_mut(segment) {
+                remove_field(value, rest);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            if segment == "*" {
+                if rest.is_empty() {
+                    items.clear();
+                } else {
+                    for value in items {
+                        remove_field(value, rest);
+                    }
+                }
+            } else if let Ok(ix) = segment.parse::<usize>()
+                && ix < items.len()
+            {
+                if rest.is_empty() {
+                    items.remove(ix);
+                } else {
+                    remove_field(&mut items[ix], rest);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Wraps a model, recording every completion it streams to a cassette file.
+///
+/// The cassette is rewritten after each completion finishes. Completions
+/// whose streams are dropped before finishing are not recorded.
+pub struct RecordingLanguageModel {
+    inner: Arc<dyn LanguageModel>,
+    cassette: Arc<Mutex<Cassette>>,
+    path: Arc<Path>,
+    matching: RequestMatching,
+}
+
+impl RecordingLanguageModel {
+    pub fn new(
+        inner: Arc<dyn LanguageModel>,
+        path: impl Into<PathBuf>,
+        matching: RequestMatching,
+    ) -> Self {
+        let cassette = Cassette {
+            model: RecordedModel::new(inner.as_ref()),
+            interactions: Vec::new(),
+        };
+        Self {
+            inner,
+            cassette: Arc::new(Mutex::new(cassette)),
+            path: path.into().into(),
+            matching,
+        }
+    }
+
+    pub fn cassette(&self) -> Cassette {
+        self.cassette.lock().clone()
+    }
+}
+
+fn record_interaction(cassette: &Mutex<Cassette>, path: &Path, interaction: RecordedInteraction) {
+    let mut cassette = cassette.lock();
+    cassette.interactions.push(interaction);
+    // Saving under the lock ensures the last write contains every interaction.
+    cassette.save(path).log_err();
+}
+
+impl LanguageModel for RecordingLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.inner.id()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        self.inner.name()
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        self.inner.provider_id()
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        self.inner.provider_name()
+    }
+
+    fn upstream_provider_id(&self) -> LanguageModelProviderId {
+        self.inner.upstream_provider_id()
+    }
+
+    fn upstream_provider_name(&self) -> LanguageModelProviderName {
+        self.inner.upstream_provider_name()
+    }
+
+    fn telemetry_id(&self) -> String {
+        self.inner.telemetry_id()
+    }
+
+    fn api_key(&self, cx: &App) -> Option<String> {
+        self.inner.api_key(cx)
+    }
+
+    fn supports_images(&self) -> bool {
+        self.inner.supports_images()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.inner.supports_tools()
+    }
+
+    fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
+        self.inner.supports_tool_choice(choice)
+    }
+
+    fn supports_burn_mode(&self) -> bool {
+        self.inner.supports_burn_mode()
+    }
+
+    fn tool_input_format(&self) -> LanguageModelToolSchemaFormat {
+        self.inner.tool_input_format()
+    }
+
+    fn max_token_count(&self) -> u64 {
+        self.inner.max_token_count()
+    }
+
+    fn max_token_count_in_burn_mode(&self) -> Option<u64> {
+        self.inner.max_token_count_in_burn_mode()
+    }
+
+    fn max_output_tokens(&self) -> Option<u64> {
+        self.inner.max_output_tokens()
+    }
+
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        self.inner.pricing()
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &App,
+    ) -> BoxFuture<'static, Result<u64>> {
+        self.inner.count_tokens(request, cx)
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let fingerprint = self.matching.fingerprint(&request);
+        let future = self.inner.stream_completion(request.clone(), cx);
+        let cassette = self.cassette.clone();
+        let path = self.path.clone();
+        async move {
+            let events = match future.await {
+                Ok(events) => events,
+                Err(error) => {
+                    let interaction = RecordedInteraction {
+                        fingerprint,
+                        request,
+                        error: Some(error.to_string()),
+                        events: Vec::new(),
+                    };
+                    record_interaction(&cassette, &path, interaction);
+                    return Err(error);
+                }
+            };
+
+            let recorded_events = Arc::new(Mutex::new(Vec::new()));
+            let finish = futures::stream::once({
+                let recorded_events = recorded_events.clone();
+                async move {
+                    let interaction = RecordedInteraction {
+                        fingerprint,
+                        request,
+                        error: None,
+                        events: std::mem::take(&mut *recorded_events.lock()),
+                    };
+                    record_interaction(&cassette, &path, interaction);
+                    None
+                }
+            })
+            .filter_map(futures::future::ready);
+            Ok(events
+                .inspect(move |event| recorded_events.lock().push(RecordedEvent::new(event)))
+                .chain(finish)
+                .boxed())
+        }
+        .boxed()
+    }
+
+    fn cache_configuration(&self) -> Option<crate::LanguageModelCacheConfiguration> {
+        self.inner.cache_configuration()
+    }
+}
+
+/// Serves completions recorded in a cassette.
+///
+/// When a request was recorded several times, the recordings are served in
+/// order, repeating the last one once they run out.
+pub struct ReplayLanguageModel {
+    cassette: Cassette,
+    matching: RequestMatching,
+    next_interaction: Mutex<HashMap<String, usize>>,
+}
+
+impl ReplayLanguageModel {
+    pub fn new(cassette: Cassette, matching: RequestMatching) -> Self {
+        Self {
+            cassette,
+            matching,
+            next_interaction: Mutex::default(),
+        }
+    }
+
+    pub fn load(path: &Path, matching: RequestMatching) -> Result<Self> {
+        Ok(Self::new(Cassette::load(path)?, matching))
+    }
+
+    /// The provider of the recorded model, as opposed to [`REPLAY_PROVIDER_ID`].
+    pub fn recorded_provider_id(&self) -> LanguageModelProviderId {
+        LanguageModelProviderId::from(self.cassette.model.provider_id.clone())
+    }
+
+    fn find_interaction(&self, fingerprint: &str) -> Option<&RecordedInteraction> {
+        let interactions = self
+            .cassette
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.fingerprint == fingerprint)
+            .collect::<Vec<_>>();
+        let mut next_interaction = self.next_interaction.lock();
+        let ix = next_interaction.entry(fingerprint.to_string()).or_default();
+        let interaction = interactions.get(*ix).or(interactions.last()).copied();
+        *ix += 1;
+        interaction
+    }
+}
+
+impl LanguageModel for ReplayLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        LanguageModelId::from(self.cassette.model.id.clone())
+    }
+
+    fn name(&self) -> LanguageModelName {
+        LanguageModelName::from(self.cassette.model.name.clone())
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        REPLAY_PROVIDER_ID
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        REPLAY_PROVIDER_NAME
+    }
+
+    fn upstream_provider_id(&self) -> LanguageModelProviderId {
+        self.recorded_provider_id()
+    }
+
+    fn upstream_provider_name(&self) -> LanguageModelProviderName {
+        LanguageModelProviderName::from(self.cassette.model.provider_name.clone())
+    }
+
+    fn telemetry_id(&self) -> String {
+        self.cassette.model.telemetry_id.clone()
+    }
+
+    fn supports_images(&self) -> bool {
+        self.cassette.model.supports_images
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.cassette.model.supports_tools
+    }
+
+    fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
+        self.cassette.model.supported_tool_choices.contains(&choice)
+    }
+
+    fn tool_input_format(&self) -> LanguageModelToolSchemaFormat {
+        if self.cassette.model.uses_json_schema_subset {
+            LanguageModelToolSchemaFormat::JsonSchemaSubset
+        } else {
+            LanguageModelToolSchemaFormat::JsonSchema
+        }
+    }
+
+    fn max_token_count(&self) -> u64 {
+        self.cassette.model.max_token_count
+    }
+
+    fn max_output_tokens(&self) -> Option<u64> {
+        self.cassette.model.max_output_tokens
+    }
+
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        self.cassette.model.pricing
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        _: &App,
+    ) -> BoxFuture<'static, Result<u64>> {
+        // Token counts aren't recorded, so approximate them from the text.
+        let characters = request
+            .messages
+            .iter()
+            .map(|message| message.string_contents().len())
+            .sum::<usize>();
+        futures::future::ready(Ok(characters as u64 / 4)).boxed()
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        _: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let fingerprint = self.matching.fingerprint(&request);
+        let result = match self.find_interaction(&fingerprint) {
+            Some(RecordedInteraction {
+                error: Some(error), ..
+            }) => Err(LanguageModelCompletionError::Other(anyhow!(error.clone()))),
+            Some(interaction) => {
+                let events = interaction
+                    .events
+                    .iter()
+                    .map(RecordedEvent::replay)
+                    .collect::<Vec<_>>();
+                Ok(futures::stream::iter(events).boxed())
+            }
+            None => Err(LanguageModelCompletionError::Other(anyhow!(
+                "no recorded completion of {} matches request {fingerprint}",
+                self.cassette.model.id
+            ))),
+        };
+        futures::future::ready(result).boxed()
+    }
+}
+
+/// Provides the models recorded in a set of cassettes.
+pub struct ReplayLanguageModelProvider {
+    models: Vec<Arc<ReplayLanguageModel>>,
+}
+
+impl ReplayLanguageModelProvider {
+    pub fn new(models: Vec<Arc<ReplayLanguageModel>>) -> Self {
+        Self { models }
+    }
+
+    /// Loads every `.json` cassette in the given directory.
+    pub fn load_dir(dir: &Path, matching: RequestMatching) -> Result<Self> {
+        let mut models = Vec::new();
+        for entry in std::fs::read_dir(dir)
+            .with_context(|| format!("reading cassettes in {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                models.push(Arc::new(ReplayLanguageModel::load(
+                    &path,
+                    matching.clone(),
+                )?));
+            }
+        }
+        Ok(Self::new(models))
+    }
+
+    /// Finds the replay of a model by the provider and ID it was recorded with.
+    pub fn model(
+        &self,
+        provider_id: &LanguageModelProviderId,
+        model_id: &LanguageModelId,
+    ) -> Option<Arc<dyn LanguageModel>> {
+        self.models
+            .iter()
+            .find(|model| model.recorded_provider_id() == *provider_id && model.id() == *model_id)
+            .map(|model| model.clone() as Arc<dyn LanguageModel>)
+    }
+}
+
+impl LanguageModelProviderState for ReplayLanguageModelProvider {
+    type ObservableEntity = ();
+
+    fn observable_entity(&self) -> Option<Entity<Self::ObservableEntity>> {
+        None
+    }
+}
+
+impl LanguageModelProvider for ReplayLanguageModelProvider {
+    fn id(&self) -> LanguageModelProviderId {
+        REPLAY_PROVIDER_ID
+    }
+
+    fn name(&self) -> LanguageModelProviderName {
+        REPLAY_PROVIDER_NAME
+    }
+
+    fn default_model(&self, _cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        self.models
+            .first()
+            .map(|model| model.clone() as Arc<dyn LanguageModel>)
+    }
+
+    fn default_fast_model(&self, cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        self.default_model(cx)
+    }
+
+    fn provided_models(&self, _cx: &App) -> Vec<Arc<dyn LanguageModel>> {
+        self.models
+            .iter()
+            .map(|model| model.clone() as Arc<dyn LanguageModel>)
+            .collect()
+    }
+
+    fn is_authenticated(&self, _cx: &App) -> bool {
+        true
+    }
+
+    fn authenticate(&self, _cx: &mut App) -> Task<Result<(), AuthenticateError>> {
+        Task::ready(Ok(()))
+    }
+
+    fn configuration_view(
+        &self,
+        _target_agent: ConfigurationViewTargetAgent,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> AnyView {
+        // Replayed models have nothing to configure.
+        cx.new(|_| EmptyView).into()
+    }
+
+    fn reset_credentials(&self, _cx: &mut App) -> Task<Result<()>> {
+        Task::ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_provider::FakeLanguageModel;
+    use crate::{LanguageModelRequestMessage, MessageContent, Role};
+    use gpui::TestAppContext;
+
+    fn request(text: &str, thread_id: &str) -> LanguageModelRequest {
+        LanguageModelRequest {
+            thread_id: Some(thread_id.into()),
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![MessageContent::Text(text.into())],
+                cache: false,
+                reasoning_details: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[gpui::test]
+    async fn test_record_and_replay(cx: &mut TestAppContext) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fake.json");
+        let fake = Arc::new(FakeLanguageModel::default());
+        let recorder =
+            RecordingLanguageModel::new(fake.clone(), path.clone(), RequestMatching::default());
+
+        let stream =
+            cx.update(|cx| recorder.stream_completion(request("Hi", "thread-1"), &cx.to_async()));
+        let task = cx.executor().spawn(async move {
+            stream
+                .await
+                .unwrap()
+                .map(|event| event.unwrap())
+                .collect::<Vec<_>>()
+                .await
+        });
+        cx.run_until_parked();
+        fake.send_last_completion_stream_text_chunk("Hello");
+        fake.send_last_completion_stream_text_chunk(" there");
+        fake.end_last_completion_stream();
+        let recorded_events = task.await;
+        cx.run_until_parked();
+        assert_eq!(
+            recorded_events,
+            vec![
+                LanguageModelCompletionEvent::Text("Hello".into()),
+                LanguageModelCompletionEvent::Text(" there".into()),
+            ]
+        );
+
+        let replay = ReplayLanguageModel::load(&path, RequestMatching::default()).unwrap();
+        assert_eq!(replay.id(), fake.id());
+        assert_eq!(replay.upstream_provider_id(), fake.provider_id());
+
+        // The thread ID differs, but is ignored when matching requests.
+        let stream =
+            cx.update(|cx| replay.stream_completion(request("Hi", "thread-2"), &cx.to_async()));
+        let replayed_events = stream
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(replayed_events, recorded_events);
+
+        let stream =
+            cx.update(|cx| replay.stream_completion(request("Bye", "thread-1"), &cx.to_async()));
+        assert!(stream.await.is_err());
+    }
+
+    #[test]
+    fn test_request_matching() {
+        let matching = RequestMatching::default();
+        assert_eq!(
+            matching.fingerprint(&request("Hi", "thread-1")),
+            matching.fingerprint(&request("Hi", "thread-2"))
+        );
+        assert_ne!(
+            matching.fingerprint(&request("Hi", "thread-1")),
+            matching.fingerprint(&request("Bye", "thread-1"))
+        );
+
+        let matching = RequestMatching::default().ignoring("/messages/*/content");
+        assert_eq!(
+            matching.fingerprint(&request("Hi", "thread-1")),
+            matching.fingerprint(&request("Bye", "thread-1"))
+        );
+    }
+}
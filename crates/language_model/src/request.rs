@@ -1,7 +1,7 @@
 use std::io::{Cursor, Write};
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context as _, Result, bail};
 use base64::write::EncoderWriter;
 use cloud_llm_client::{CompletionIntent, CompletionMode};
 use gpui::{
@@ -9,10 +9,12 @@ use gpui::{
     point, px, size,
 };
 use image::codecs::png::PngEncoder;
-use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use util::ResultExt;
 
 use crate::role::Role;
+use crate::tool_schema::{LanguageModelToolSchemaFormat, root_schema_for};
 use crate::{LanguageModelToolUse, LanguageModelToolUseId};
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
@@ -390,6 +392,40 @@ pub enum LanguageModelToolChoice {
     None,
 }
 
+/// Constrains a completion to a JSON value matching a schema.
+///
+/// Providers map this onto their native structured output mechanism. Those
+/// without one emulate it by forcing a call to a tool named [`Self::name`]
+/// whose input is the response.
+#[derive(Debug, PartialEq, Hash, Clone, Serialize, Deserialize)]
+pub struct LanguageModelResponseFormat {
+    pub name: String,
+    pub schema: serde_json::Value,
+}
+
+impl LanguageModelResponseFormat {
+    pub fn json_schema<T: JsonSchema>(format: LanguageModelToolSchemaFormat) -> Self {
+        let name = T::schema_name()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        Self {
+            name,
+            schema: root_schema_for::<T>(format).to_value(),
+        }
+    }
+
+    /// Checks that a response matches the schema and deserializes it.
+    pub fn parse<T: DeserializeOwned>(&self, response: serde_json::Value) -> Result<T> {
+        let validator =
+            jsonschema::validator_for(&self.schema).context("invalid response format schema")?;
+        if let Err(error) = validator.validate(&response) {
+            bail!("response does not match the {} schema: {error}", self.name);
+        }
+        Ok(serde_json::from_value(response)?)
+    }
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
 pub struct LanguageModelRequest {
     pub thread_id: Option<String>,
@@ -402,6 +438,21 @@ pub struct LanguageModelRequest {
     pub stop: Vec<String>,
     pub temperature: Option<f32>,
     pub thinking_allowed: bool,
+    pub response_format: Option<LanguageModelResponseFormat>,
+    /// Limits how many tokens the response may contain, below the model's own
+    /// output limit.
+    pub max_tokens: Option<u64>,
+}
+
+impl LanguageModelRequest {
+    /// Returns the number of tokens the response may contain given the
+    /// model's own output limit, if either of them sets one.
+    pub fn output_token_limit(&self, model_limit: Option<u64>) -> Option<u64> {
+        match (self.max_tokens, model_limit) {
+            (Some(max_tokens), Some(model_limit)) => Some(max_tokens.min(model_limit)),
+            (max_tokens, model_limit) => max_tokens.or(model_limit),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
@@ -414,6 +465,22 @@ pub struct LanguageModelResponseMessage {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_output_token_limit() {
+        let request = |max_tokens| LanguageModelRequest {
+            max_tokens,
+            ..Default::default()
+        };
+        assert_eq!(request(None).output_token_limit(None), None);
+        assert_eq!(request(None).output_token_limit(Some(8192)), Some(8192));
+        assert_eq!(request(Some(100)).output_token_limit(None), Some(100));
+        assert_eq!(request(Some(100)).output_token_limit(Some(8192)), Some(100));
+        assert_eq!(
+            request(Some(10_000)).output_token_limit(Some(8192)),
+            Some(8192)
+        );
+    }
+
     #[test]
     fn test_language_model_tool_result_content_deserialization() {
         let json = r#""This is plain text""#;
@@ -8,6 +8,7 @@ use language_model::{LanguageModelProviderId, LanguageModelRegistry};
 use provider::deepseek::DeepSeekLanguageModelProvider;
 
 mod api_key;
+mod pricing;
 pub mod provider;
 mod settings;
 pub mod ui;
@@ -0,0 +1,223 @@
+//! List prices of the models offered by each provider, in US dollars per
+//! million tokens. Custom models have no known price, though one can be set
+//! with the `agent.model_pricing` setting.
+//!
+//! Where a provider offers no discount for cached input, cache reads are priced
+//! as regular input tokens.
+
+use language_model::LanguageModelPricing;
+use strum::IntoEnumIterator as _;
+
+pub(crate) fn anthropic(model: &anthropic::Model) -> Option<LanguageModelPricing> {
+    use anthropic::Model::*;
+    Some(match model {
+        ClaudeOpus4 | ClaudeOpus4Thinking | ClaudeOpus4_1 | ClaudeOpus4_1Thinking | Claude3Opus => {
+            LanguageModelPricing::new(15., 75., 1.5, 18.75)
+        }
+        ClaudeOpus4_5 | ClaudeOpus4_5Thinking => LanguageModelPricing::new(5., 25., 0.5, 6.25),
+        ClaudeSonnet4
+        | ClaudeSonnet4Thinking
+        | ClaudeSonnet4_5
+        | ClaudeSonnet4_5Thinking
+        | Claude3_7Sonnet
+        | Claude3_7SonnetThinking
+        | Claude3_5Sonnet
+        | Claude3Sonnet => LanguageModelPricing::new(3., 15., 0.3, 3.75),
+        ClaudeHaiku4_5 | ClaudeHaiku4_5Thinking => LanguageModelPricing::new(1., 5., 0.1, 1.25),
+        Claude3_5Haiku => LanguageModelPricing::new(0.8, 4., 0.08, 1.),
+        Claude3Haiku => LanguageModelPricing::new(0.25, 1.25, 0.03, 0.3),
+        Custom { .. } => return None,
+    })
+}
+
+pub(crate) fn open_ai(model: &open_ai::Model) -> Option<LanguageModelPricing> {
+    use open_ai::Model::*;
+    Some(match model {
+        ThreePointFiveTurbo => LanguageModelPricing::new(0.5, 1.5, 0.5, 0.),
+        Four => LanguageModelPricing::new(30., 60., 30., 0.),
+        FourTurbo => LanguageModelPricing::new(10., 30., 10., 0.),
+        FourOmni => LanguageModelPricing::new(2.5, 10., 1.25, 0.),
+        FourOmniMini => LanguageModelPricing::new(0.15, 0.6, 0.075, 0.),
+        FourPointOne => LanguageModelPricing::new(2., 8., 0.5, 0.),
+        FourPointOneMini => LanguageModelPricing::new(0.4, 1.6, 0.1, 0.),
+        FourPointOneNano => LanguageModelPricing::new(0.1, 0.4, 0.025, 0.),
+        O1 => LanguageModelPricing::new(15., 60., 7.5, 0.),
+        O3Mini => LanguageModelPricing::new(1.1, 4.4, 0.55, 0.),
+        O3 => LanguageModelPricing::new(2., 8., 0.5, 0.),
+        O4Mini => LanguageModelPricing::new(1.1, 4.4, 0.275, 0.),
+        Five => LanguageModelPricing::new(1.25, 10., 0.125, 0.),
+        FiveMini => LanguageModelPricing::new(0.25, 2., 0.025, 0.),
+        FiveNano => LanguageModelPricing::new(0.05, 0.4, 0.005, 0.),
+        Custom { .. } => return None,
+    })
+}
+
+pub(crate) fn google(model: &google_ai::Model) -> Option<LanguageModelPricing> {
+    use google_ai::Model::*;
+    // Prices are for prompts of up to 200k tokens.
+    Some(match model {
+        Gemini15Pro => LanguageModelPricing::new(1.25, 5., 0.3125, 0.),
+        Gemini15Flash8b => LanguageModelPricing::new(0.0375, 0.15, 0.01, 0.),
+        Gemini15Flash => LanguageModelPricing::new(0.075, 0.3, 0.01875, 0.),
+        Gemini20FlashLite => LanguageModelPricing::new(0.075, 0.3, 0.01875, 0.),
+        Gemini20Flash => LanguageModelPricing::new(0.1, 0.4, 0.025, 0.),
+        Gemini25FlashLitePreview => LanguageModelPricing::new(0.1, 0.4, 0.025, 0.),
+        Gemini25Flash => LanguageModelPricing::new(0.3, 2.5, 0.075, 0.),
+        Gemini25Pro => LanguageModelPricing::new(1.25, 10., 0.31, 0.),
+        Gemini3ProPreview => LanguageModelPricing::new(2., 12., 0.2, 0.),
+        Custom { .. } => return None,
+    })
+}
+
+pub(crate) fn deepseek(model: &deepseek::Model) -> Option<LanguageModelPricing> {
+    Some(match model {
+        deepseek::Model::Chat => LanguageModelPricing::new(0.27, 1.1, 0.07, 0.),
+        deepseek::Model::Reasoner => LanguageModelPricing::new(0.55, 2.19, 0.14, 0.),
+        deepseek::Model::Custom { .. } => return None,
+    })
+}
+
+pub(crate) fn mistral(model: &mistral::Model) -> Option<LanguageModelPricing> {
+    use mistral::Model::*;
+    Some(match model {
+        CodestralLatest => LanguageModelPricing::new(0.3, 0.9, 0.3, 0.),
+        MistralLargeLatest | PixtralLargeLatest => LanguageModelPricing::new(2., 6., 2., 0.),
+        MistralMediumLatest | DevstralMediumLatest => LanguageModelPricing::new(0.4, 2., 0.4, 0.),
+        MistralSmallLatest | DevstralSmallLatest => LanguageModelPricing::new(0.1, 0.3, 0.1, 0.),
+        MagistralMediumLatest => LanguageModelPricing::new(2., 5., 2., 0.),
+        MagistralSmallLatest => LanguageModelPricing::new(0.5, 1.5, 0.5, 0.),
+        OpenMistralNemo | Pixtral12BLatest => LanguageModelPricing::new(0.15, 0.15, 0.15, 0.),
+        OpenCodestralMamba => LanguageModelPricing::new(0.25, 0.25, 0.25, 0.),
+        Custom { .. } => return None,
+    })
+}
+
+pub(crate) fn x_ai(model: &x_ai::Model) -> Option<LanguageModelPricing> {
+    use x_ai::Model::*;
+    Some(match model {
+        Grok2Vision => LanguageModelPricing::new(2., 10., 2., 0.),
+        Grok3 | Grok4 => LanguageModelPricing::new(3., 15., 0.75, 0.),
+        Grok3Mini => LanguageModelPricing::new(0.3, 0.5, 0.075, 0.),
+        Grok3Fast => LanguageModelPricing::new(5., 25., 1.25, 0.),
+        Grok3MiniFast => LanguageModelPricing::new(0.6, 4., 0.15, 0.),
+        Grok4FastReasoning | Grok4FastNonReasoning => LanguageModelPricing::new(0.2, 0.5, 0.05, 0.),
+        GrokCodeFast1 => LanguageModelPricing::new(0.2, 1.5, 0.02, 0.),
+        Custom { .. } => return None,
+    })
+}
+
+pub(crate) fn bedrock(model: &bedrock::Model) -> Option<LanguageModelPricing> {
+    use bedrock::Model::*;
+    // Bedrock charges Anthropic's list prices for Claude models.
+    Some(match model {
+        ClaudeOpus4 | ClaudeOpus4Thinking | ClaudeOpus4_1 | ClaudeOpus4_1Thinking | Claude3Opus => {
+            LanguageModelPricing::new(15., 75., 1.5, 18.75)
+        }
+        ClaudeOpus4_5 | ClaudeOpus4_5Thinking => LanguageModelPricing::new(5., 25., 0.5, 6.25),
+        ClaudeSonnet4
+        | ClaudeSonnet4Thinking
+        | ClaudeSonnet4_5
+        | ClaudeSonnet4_5Thinking
+        | Claude3_7Sonnet
+        | Claude3_7SonnetThinking
+        | Claude3_5SonnetV2
+        | Claude3_5Sonnet
+        | Claude3Sonnet => LanguageModelPricing::new(3., 15., 0.3, 3.75),
+        ClaudeHaiku4_5 => LanguageModelPricing::new(1., 5., 0.1, 1.25),
+        Claude3_5Haiku => LanguageModelPricing::new(0.8, 4., 0.08, 1.),
+        Claude3Haiku => LanguageModelPricing::new(0.25, 1.25, 0.03, 0.3),
+        AmazonNovaMicro => LanguageModelPricing::new(0.035, 0.14, 0.00875, 0.),
+        AmazonNovaLite => LanguageModelPricing::new(0.06, 0.24, 0.015, 0.),
+        AmazonNovaPro => LanguageModelPricing::new(0.8, 3.2, 0.2, 0.),
+        AmazonNovaPremier => LanguageModelPricing::new(2.5, 12.5, 0.625, 0.),
+        DeepSeekR1 => LanguageModelPricing::new(1.35, 5.4, 1.35, 0.),
+        MetaLlama4Scout17BInstructV1 => LanguageModelPricing::new(0.17, 0.66, 0.17, 0.),
+        MetaLlama4Maverick17BInstructV1 => LanguageModelPricing::new(0.24, 0.97, 0.24, 0.),
+        MetaLlama3370BInstructV1 | MetaLlama3170BInstructV1 | MetaLlama3170BInstructV1_128k => {
+            LanguageModelPricing::new(0.72, 0.72, 0.72, 0.)
+        }
+        MistralPixtralLarge2502V1 => LanguageModelPricing::new(2., 6., 2., 0.),
+        _ => return None,
+    })
+}
+
+pub(crate) fn vercel(model: &vercel::Model) -> Option<LanguageModelPricing> {
+    Some(match model {
+        vercel::Model::VZeroOnePointFiveMedium => LanguageModelPricing::new(3., 15., 3., 0.),
+        vercel::Model::Custom { .. } => return None,
+    })
+}
+
+pub(crate) fn open_router(model: &open_router::Model) -> Option<LanguageModelPricing> {
+    // OpenRouter lists prices per token, and uses negative ones for routers
+    // whose price depends on the model they pick.
+    let pricing = model.pricing.as_ref()?;
+    let per_million = |price: &str| {
+        price
+            .parse::<f64>()
+            .ok()
+            .filter(|price| *price >= 0.)
+            .map(|price| price * 1_000_000.)
+    };
+    let input = per_million(&pricing.prompt)?;
+    let output = per_million(&pricing.completion)?;
+    let cache_read = pricing.input_cache_read.as_deref().and_then(per_million);
+    let cache_write = pricing.input_cache_write.as_deref().and_then(per_million);
+    Some(LanguageModelPricing::new(
+        input,
+        output,
+        cache_read.unwrap_or(input),
+        cache_write.unwrap_or(0.),
+    ))
+}
+
+/// Models used through Zed are estimated at their provider's list price.
+pub(crate) fn zed_cloud(model: &cloud_llm_client::LanguageModel) -> Option<LanguageModelPricing> {
+    use cloud_llm_client::LanguageModelProvider::*;
+    let id = model.id.0.as_ref();
+    match model.provider {
+        Anthropic => anthropic(&anthropic::Model::from_id(id).ok()?),
+        OpenAi => open_ai(&open_ai::Model::from_id(id).ok()?),
+        Google => google(&google_ai::Model::iter().find(|model| model.id() == id)?),
+        XAi => x_ai(&x_ai::Model::from_id(id).ok()?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_router_pricing() {
+        let mut model = open_router::Model::new(
+            "anthropic/claude-sonnet-4",
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(open_router(&model), None);
+
+        model.pricing = Some(open_router::ModelPricing {
+            prompt: "0.000003".into(),
+            completion: "0.000015".into(),
+            input_cache_read: Some("0.0000003".into()),
+            input_cache_write: None,
+        });
+        let pricing = open_router(&model).unwrap();
+        assert!((pricing.input - 3.).abs() < 1e-9);
+        assert!((pricing.output - 15.).abs() < 1e-9);
+        assert!((pricing.cache_read - 0.3).abs() < 1e-9);
+        assert_eq!(pricing.cache_write, 0.);
+
+        model.pricing = Some(open_router::ModelPricing {
+            prompt: "-1".into(),
+            completion: "-1".into(),
+            input_cache_read: None,
+            input_cache_write: None,
+        });
+        assert_eq!(open_router(&model), None);
+    }
+}
@@ -10,9 +10,9 @@ use http_client::HttpClient;
 use language_model::{
     AuthenticateError, ConfigurationViewTargetAgent, LanguageModel,
     LanguageModelCacheConfiguration, LanguageModelCompletionError, LanguageModelId,
-    LanguageModelName, LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
-    LanguageModelProviderState, LanguageModelRequest, LanguageModelToolChoice,
-    LanguageModelToolResultContent, MessageContent, RateLimiter, Role,
+    LanguageModelName, LanguageModelPricing, LanguageModelProvider, LanguageModelProviderId,
+    LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest,
+    LanguageModelToolChoice, LanguageModelToolResultContent, MessageContent, RateLimiter, Role,
 };
 use language_model::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason};
 use settings::{Settings, SettingsStore};
@@ -370,6 +370,10 @@ impl LanguageModel for AnthropicModel {
         }
     }
 
+    fn supports_response_format(&self) -> bool {
+        true
+    }
+
     fn telemetry_id(&self) -> String {
         format!("anthropic/{}", self.model.id())
     }
@@ -389,6 +393,10 @@ impl LanguageModel for AnthropicModel {
         Some(self.model.max_output_tokens())
     }
 
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        crate::pricing::anthropic(&self.model)
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -441,6 +449,9 @@ pub fn into_anthropic(
     max_output_tokens: u64,
     mode: AnthropicModelMode,
 ) -> anthropic::Request {
+    let max_output_tokens = request.max_tokens.map_or(max_output_tokens, |max_tokens| {
+        max_tokens.min(max_output_tokens)
+    });
     let mut new_messages: Vec<anthropic::Message> = Vec::new();
     let mut system_message = String::new();
 
@@ -578,6 +589,34 @@ pub fn into_anthropic(
         }
     }
 
+    let mut tools: Vec<_> = request
+        .tools
+        .into_iter()
+        .map(|tool| anthropic::Tool {
+            name: tool.name,
+            description: tool.description,
+            input_schema: tool.input_schema,
+        })
+        .collect();
+    let mut tool_choice = request.tool_choice.map(|choice| match choice {
+        LanguageModelToolChoice::Auto => anthropic::ToolChoice::Auto,
+        LanguageModelToolChoice::Any => anthropic::ToolChoice::Any,
+        LanguageModelToolChoice::None => anthropic::ToolChoice::None,
+    });
+    // Anthropic has no structured output mode, so a response format is
+    // emulated by forcing a call to a tool whose input is the response.
+    let response_format = request.response_format.is_some();
+    if let Some(format) = request.response_format {
+        tool_choice = Some(anthropic::ToolChoice::Tool {
+            name: format.name.clone(),
+        });
+        tools.push(anthropic::Tool {
+            name: format.name,
+            description: "Respond with a value matching this tool's input schema.".into(),
+            input_schema: format.schema,
+        });
+    }
+
     anthropic::Request {
         model,
         messages: new_messages,
@@ -587,27 +626,20 @@ pub fn into_anthropic(
         } else {
             Some(anthropic::StringOrContents::String(system_message))
         },
+        // Extended thinking can't be combined with forced tool use, and its
+        // budget has to fit in the response.
         thinking: if request.thinking_allowed
+            && !response_format
             && let AnthropicModelMode::Thinking { budget_tokens } = mode
+            && budget_tokens
+                .is_none_or(|budget_tokens| u64::from(budget_tokens) < max_output_tokens)
         {
             Some(anthropic::Thinking::Enabled { budget_tokens })
         } else {
             None
         },
-        tools: request
-            .tools
-            .into_iter()
-            .map(|tool| anthropic::Tool {
-                name: tool.name,
-                description: tool.description,
-                input_schema: tool.input_schema,
-            })
-            .collect(),
-        tool_choice: request.tool_choice.map(|choice| match choice {
-            LanguageModelToolChoice::Auto => anthropic::ToolChoice::Auto,
-            LanguageModelToolChoice::Any => anthropic::ToolChoice::Any,
-            LanguageModelToolChoice::None => anthropic::ToolChoice::None,
-        }),
+        tools,
+        tool_choice,
         metadata: None,
         stop_sequences: Vec::new(),
         temperature: request.temperature.or(Some(default_temperature)),
@@ -974,7 +1006,9 @@ impl Render for ConfigurationView {
 mod tests {
     use super::*;
     use anthropic::AnthropicModelMode;
-    use language_model::{LanguageModelRequestMessage, MessageContent};
+    use language_model::{
+        LanguageModelRequestMessage, LanguageModelResponseFormat, MessageContent,
+    };
 
     #[test]
     fn test_cache_control_only_on_last_segment() {
@@ -1000,6 +1034,8 @@ mod tests {
             tools: vec![],
             tool_choice: None,
             thinking_allowed: true,
+            response_format: None,
+            max_tokens: None,
         };
 
         let anthropic_request = into_anthropic(
@@ -1042,4 +1078,43 @@ mod tests {
             }
         ));
     }
+
+    #[test]
+    fn test_response_format_forces_tool_use() {
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![MessageContent::Text("Summarize".to_string())],
+                cache: false,
+                reasoning_details: None,
+            }],
+            thinking_allowed: true,
+            response_format: Some(LanguageModelResponseFormat {
+                name: "Summary".into(),
+                schema: serde_json::json!({
+                    "type": "object",
+                    "properties": { "text": { "type": "string" } },
+                }),
+            }),
+            ..Default::default()
+        };
+
+        let anthropic_request = into_anthropic(
+            request,
+            "claude-sonnet-4-5".to_string(),
+            0.7,
+            4096,
+            AnthropicModelMode::Thinking {
+                budget_tokens: Some(4096),
+            },
+        );
+
+        assert_eq!(anthropic_request.tools.len(), 1);
+        assert_eq!(anthropic_request.tools[0].name, "Summary");
+        assert!(matches!(
+            anthropic_request.tool_choice,
+            Some(anthropic::ToolChoice::Tool { ref name }) if name == "Summary"
+        ));
+        assert!(anthropic_request.thinking.is_none());
+    }
 }
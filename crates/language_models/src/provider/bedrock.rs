@@ -16,10 +16,11 @@ use bedrock::bedrock_client::types::{
 };
 use bedrock::{
     BedrockAnyToolChoice, BedrockAutoToolChoice, BedrockBlob, BedrockError, BedrockInnerContent,
-    BedrockMessage, BedrockModelMode, BedrockStreamingResponse, BedrockThinkingBlock,
-    BedrockThinkingTextBlock, BedrockTool, BedrockToolChoice, BedrockToolConfig,
-    BedrockToolInputSchema, BedrockToolResultBlock, BedrockToolResultContentBlock,
-    BedrockToolResultStatus, BedrockToolSpec, BedrockToolUseBlock, Model, value_to_aws_document,
+    BedrockMessage, BedrockModelMode, BedrockSpecificToolChoice, BedrockStreamingResponse,
+    BedrockThinkingBlock, BedrockThinkingTextBlock, BedrockTool, BedrockToolChoice,
+    BedrockToolConfig, BedrockToolInputSchema, BedrockToolResultBlock,
+    BedrockToolResultContentBlock, BedrockToolResultStatus, BedrockToolSpec, BedrockToolUseBlock,
+    Model, value_to_aws_document,
 };
 use collections::{BTreeMap, HashMap};
 use credentials_provider::CredentialsProvider;
@@ -33,10 +34,10 @@ use http_client::HttpClient;
 use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCacheConfiguration,
     LanguageModelCompletionError, LanguageModelCompletionEvent, LanguageModelId, LanguageModelName,
-    LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
-    LanguageModelProviderState, LanguageModelRequest, LanguageModelToolChoice,
-    LanguageModelToolResultContent, LanguageModelToolUse, MessageContent, RateLimiter, Role,
-    TokenUsage,
+    LanguageModelPricing, LanguageModelProvider, LanguageModelProviderId,
+    LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest,
+    LanguageModelToolChoice, LanguageModelToolResultContent, LanguageModelToolUse, MessageContent,
+    RateLimiter, Role, TokenUsage,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -514,6 +515,10 @@ impl LanguageModel for BedrockModel {
         }
     }
 
+    fn supports_response_format(&self) -> bool {
+        self.model.supports_tool_use()
+    }
+
     fn telemetry_id(&self) -> String {
         format!("bedrock/{}", self.model.id())
     }
@@ -526,6 +531,10 @@ impl LanguageModel for BedrockModel {
         Some(self.model.max_output_tokens())
     }
 
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        crate::pricing::bedrock(&self.model)
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -621,6 +630,9 @@ pub fn into_bedrock(
     mode: BedrockModelMode,
     supports_caching: bool,
 ) -> Result<bedrock::Request> {
+    let max_output_tokens = request.max_tokens.map_or(max_output_tokens, |max_tokens| {
+        max_tokens.min(max_output_tokens)
+    });
     let mut new_messages: Vec<BedrockMessage> = Vec::new();
     let mut system_message = String::new();
 
@@ -768,6 +780,21 @@ pub fn into_bedrock(
         })
         .collect();
 
+    // Bedrock has no structured output mode, so a response format is emulated
+    // by forcing a call to a tool whose input is the response.
+    if let Some(format) = &request.response_format {
+        tool_spec.push(BedrockTool::ToolSpec(
+            BedrockToolSpec::builder()
+                .name(format.name.clone())
+                .description("Respond with a value matching this tool's input schema.")
+                .input_schema(BedrockToolInputSchema::Json(value_to_aws_document(
+                    &format.schema,
+                )))
+                .build()
+                .context("failed to build response format tool")?,
+        ));
+    }
+
     if !tool_spec.is_empty() && supports_caching {
         tool_spec.push(BedrockTool::CachePoint(
             CachePointBlock::builder()
@@ -777,16 +804,25 @@ pub fn into_bedrock(
         ));
     }
 
-    let tool_choice = match request.tool_choice {
-        Some(LanguageModelToolChoice::Auto) | None => {
-            BedrockToolChoice::Auto(BedrockAutoToolChoice::builder().build())
-        }
-        Some(LanguageModelToolChoice::Any) => {
-            BedrockToolChoice::Any(BedrockAnyToolChoice::builder().build())
-        }
-        Some(LanguageModelToolChoice::None) => {
-            // For None, we still use Auto but will filter out tool calls in the response
-            BedrockToolChoice::Auto(BedrockAutoToolChoice::builder().build())
+    let tool_choice = if let Some(format) = &request.response_format {
+        BedrockToolChoice::Tool(
+            BedrockSpecificToolChoice::builder()
+                .name(format.name.clone())
+                .build()
+                .context("failed to build response format tool choice")?,
+        )
+    } else {
+        match request.tool_choice {
+            Some(LanguageModelToolChoice::Auto) | None => {
+                BedrockToolChoice::Auto(BedrockAutoToolChoice::builder().build())
+            }
+            Some(LanguageModelToolChoice::Any) => {
+                BedrockToolChoice::Any(BedrockAnyToolChoice::builder().build())
+            }
+            Some(LanguageModelToolChoice::None) => {
+                // For None, we still use Auto but will filter out tool calls in the response
+                BedrockToolChoice::Auto(BedrockAutoToolChoice::builder().build())
+            }
         }
     };
     let tool_config: BedrockToolConfig = BedrockToolConfig::builder()
@@ -800,8 +836,12 @@ pub fn into_bedrock(
         max_tokens: max_output_tokens,
         system: Some(system_message),
         tools: Some(tool_config),
+        // Extended thinking can't be combined with forced tool use, and its
+        // budget has to fit in the response.
         thinking: if request.thinking_allowed
+            && request.response_format.is_none()
             && let BedrockModelMode::Thinking { budget_tokens } = mode
+            && budget_tokens.is_none_or(|budget_tokens| budget_tokens < max_output_tokens)
         {
             Some(bedrock::Thinking::Enabled { budget_tokens })
         } else {
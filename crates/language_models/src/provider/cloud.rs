@@ -21,10 +21,10 @@ use http_client::{AsyncBody, HttpClient, HttpRequestExt, Method, Response, Statu
 use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCacheConfiguration,
     LanguageModelCompletionError, LanguageModelCompletionEvent, LanguageModelId, LanguageModelName,
-    LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
-    LanguageModelProviderState, LanguageModelRequest, LanguageModelToolChoice,
-    LanguageModelToolSchemaFormat, LlmApiToken, ModelRequestLimitReachedError,
-    PaymentRequiredError, RateLimiter, RefreshLlmTokenListener,
+    LanguageModelPricing, LanguageModelProvider, LanguageModelProviderId,
+    LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest,
+    LanguageModelToolChoice, LanguageModelToolSchemaFormat, LlmApiToken,
+    ModelRequestLimitReachedError, PaymentRequiredError, RateLimiter, RefreshLlmTokenListener,
 };
 use release_channel::AppVersion;
 use schemars::JsonSchema;
@@ -610,6 +610,10 @@ impl LanguageModel for CloudLanguageModel {
         }
     }
 
+    fn supports_response_format(&self) -> bool {
+        true
+    }
+
     fn supports_burn_mode(&self) -> bool {
         self.model.supports_max_mode
     }
@@ -642,6 +646,10 @@ impl LanguageModel for CloudLanguageModel {
             .map(|max_token_count| max_token_count as u64)
     }
 
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        crate::pricing::zed_cloud(&self.model)
+    }
+
     fn cache_configuration(&self) -> Option<LanguageModelCacheConfiguration> {
         match &self.model.provider {
             cloud_llm_client::LanguageModelProvider::Anthropic => {
@@ -867,6 +867,8 @@ fn into_copilot_responses(
         stop: _,
         temperature,
         thinking_allowed: _,
+        response_format: _,
+        max_tokens: _,
     } = request;
 
     let mut input_items: Vec<responses::ResponseInputItem> = Vec::new();
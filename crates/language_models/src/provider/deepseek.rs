@@ -8,10 +8,10 @@ use gpui::{AnyView, App, AsyncApp, Context, Entity, SharedString, Task, Window};
 use http_client::HttpClient;
 use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent,
-    LanguageModelId, LanguageModelName, LanguageModelProvider, LanguageModelProviderId,
-    LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest,
-    LanguageModelToolChoice, LanguageModelToolResultContent, LanguageModelToolUse, MessageContent,
-    RateLimiter, Role, StopReason, TokenUsage,
+    LanguageModelId, LanguageModelName, LanguageModelPricing, LanguageModelProvider,
+    LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
+    LanguageModelRequest, LanguageModelToolChoice, LanguageModelToolResultContent,
+    LanguageModelToolUse, MessageContent, RateLimiter, Role, StopReason, TokenUsage,
 };
 pub use settings::DeepseekAvailableModel as AvailableModel;
 use settings::{Settings, SettingsStore};
@@ -279,6 +279,10 @@ impl LanguageModel for DeepSeekLanguageModel {
         self.model.max_output_tokens()
     }
 
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        crate::pricing::deepseek(&self.model)
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -332,6 +336,7 @@ pub fn into_deepseek(
     model: &deepseek::Model,
     max_output_tokens: Option<u64>,
 ) -> deepseek::Request {
+    let max_output_tokens = request.output_token_limit(max_output_tokens);
     let is_reasoner = *model == deepseek::Model::Reasoner;
 
     let mut messages = Vec::new();
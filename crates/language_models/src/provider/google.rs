@@ -10,8 +10,9 @@ use gpui::{AnyView, App, AsyncApp, Context, Entity, SharedString, Task, Window};
 use http_client::HttpClient;
 use language_model::{
     AuthenticateError, ConfigurationViewTargetAgent, LanguageModelCompletionError,
-    LanguageModelCompletionEvent, LanguageModelToolChoice, LanguageModelToolSchemaFormat,
-    LanguageModelToolUse, LanguageModelToolUseId, MessageContent, StopReason,
+    LanguageModelCompletionEvent, LanguageModelPricing, LanguageModelToolChoice,
+    LanguageModelToolSchemaFormat, LanguageModelToolUse, LanguageModelToolUseId, MessageContent,
+    StopReason,
 };
 use language_model::{
     LanguageModel, LanguageModelId, LanguageModelName, LanguageModelProvider,
@@ -321,6 +322,10 @@ impl LanguageModel for GoogleLanguageModel {
         }
     }
 
+    fn supports_response_format(&self) -> bool {
+        true
+    }
+
     fn tool_input_format(&self) -> LanguageModelToolSchemaFormat {
         LanguageModelToolSchemaFormat::JsonSchemaSubset
     }
@@ -337,6 +342,10 @@ impl LanguageModel for GoogleLanguageModel {
         self.model.max_output_tokens()
     }
 
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        crate::pricing::google(&self.model)
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -528,7 +537,7 @@ pub fn into_google(
         generation_config: Some(google_ai::GenerationConfig {
             candidate_count: Some(1),
             stop_sequences: Some(request.stop),
-            max_output_tokens: None,
+            max_output_tokens: request.max_tokens.map(|max_tokens| max_tokens as usize),
             temperature: request.temperature.map(|t| t as f64).or(Some(1.0)),
             thinking_config: match (request.thinking_allowed, mode) {
                 (true, GoogleModelMode::Thinking { budget_tokens }) => {
@@ -538,6 +547,11 @@ pub fn into_google(
             },
             top_p: None,
             top_k: None,
+            response_mime_type: request
+                .response_format
+                .as_ref()
+                .map(|_| "application/json".into()),
+            response_schema: request.response_format.map(|format| format.schema),
         }),
         safety_settings: None,
         tools: (!request.tools.is_empty()).then(|| {
@@ -343,7 +343,10 @@ impl LmStudioLanguageModel {
             model: self.model.name.clone(),
             messages,
             stream: true,
-            max_tokens: Some(-1),
+            // -1 lets the model generate until it stops.
+            max_tokens: Some(request.max_tokens.map_or(-1, |max_tokens| {
+                i32::try_from(max_tokens).unwrap_or(i32::MAX)
+            })),
             stop: Some(request.stop),
             // In LM Studio you can configure specific settings you'd like to use for your model.
             // For example Qwen3 is recommended to be used with 0.7 temperature.
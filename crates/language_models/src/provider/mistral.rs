@@ -6,10 +6,10 @@ use gpui::{AnyView, App, AsyncApp, Context, Entity, Global, SharedString, Task,
 use http_client::HttpClient;
 use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent,
-    LanguageModelId, LanguageModelName, LanguageModelProvider, LanguageModelProviderId,
-    LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest,
-    LanguageModelToolChoice, LanguageModelToolResultContent, LanguageModelToolUse, MessageContent,
-    RateLimiter, Role, StopReason, TokenUsage,
+    LanguageModelId, LanguageModelName, LanguageModelPricing, LanguageModelProvider,
+    LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
+    LanguageModelRequest, LanguageModelToolChoice, LanguageModelToolResultContent,
+    LanguageModelToolUse, MessageContent, RateLimiter, Role, StopReason, TokenUsage,
 };
 use mistral::{CODESTRAL_API_URL, MISTRAL_API_URL, StreamResponse};
 pub use settings::MistralAvailableModel as AvailableModel;
@@ -346,6 +346,10 @@ impl LanguageModel for MistralLanguageModel {
         self.model.max_output_tokens()
     }
 
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        crate::pricing::mistral(&self.model)
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -400,6 +404,7 @@ pub fn into_mistral(
     model: mistral::Model,
     max_output_tokens: Option<u64>,
 ) -> mistral::Request {
+    let max_output_tokens = request.output_token_limit(max_output_tokens);
     let stream = true;
 
     let mut messages = Vec::new();
@@ -1043,6 +1048,8 @@ mod tests {
             mode: None,
             stop: vec![],
             thinking_allowed: true,
+            response_format: None,
+            max_tokens: None,
         };
 
         let mistral_request = into_mistral(request, mistral::Model::MistralSmallLatest, None);
@@ -1077,6 +1084,8 @@ mod tests {
             mode: None,
             stop: vec![],
             thinking_allowed: true,
+            response_format: None,
+            max_tokens: None,
         };
 
         let mistral_request = into_mistral(request, mistral::Model::Pixtral12BLatest, None);
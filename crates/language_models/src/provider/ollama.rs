@@ -415,6 +415,9 @@ impl OllamaLanguageModel {
             stream: true,
             options: Some(ChatOptions {
                 num_ctx: Some(self.model.max_tokens),
+                num_predict: request
+                    .max_tokens
+                    .map(|max_tokens| isize::try_from(max_tokens).unwrap_or(isize::MAX)),
                 stop: Some(request.stop),
                 temperature: request.temperature.or(Some(1.0)),
                 ..Default::default()
@@ -428,6 +431,7 @@ impl OllamaLanguageModel {
             } else {
                 vec![]
             },
+            format: request.response_format.map(|format| format.schema),
         }
     }
 }
@@ -465,6 +469,10 @@ impl LanguageModel for OllamaLanguageModel {
         }
     }
 
+    fn supports_response_format(&self) -> bool {
+        true
+    }
+
     fn telemetry_id(&self) -> String {
         format!("ollama/{}", self.model.id())
     }
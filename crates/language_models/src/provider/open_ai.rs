@@ -6,10 +6,10 @@ use gpui::{AnyView, App, AsyncApp, Context, Entity, SharedString, Task, Window};
 use http_client::HttpClient;
 use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent,
-    LanguageModelId, LanguageModelName, LanguageModelProvider, LanguageModelProviderId,
-    LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest,
-    LanguageModelToolChoice, LanguageModelToolResultContent, LanguageModelToolUse, MessageContent,
-    RateLimiter, Role, StopReason, TokenUsage,
+    LanguageModelId, LanguageModelName, LanguageModelPricing, LanguageModelProvider,
+    LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
+    LanguageModelRequest, LanguageModelToolChoice, LanguageModelToolResultContent,
+    LanguageModelToolUse, MessageContent, RateLimiter, Role, StopReason, TokenUsage,
 };
 use menu;
 use open_ai::{
@@ -296,6 +296,10 @@ impl LanguageModel for OpenAiLanguageModel {
         }
     }
 
+    fn supports_response_format(&self) -> bool {
+        self.model.supports_response_format()
+    }
+
     fn telemetry_id(&self) -> String {
         format!("openai/{}", self.model.id())
     }
@@ -308,6 +312,10 @@ impl LanguageModel for OpenAiLanguageModel {
         self.model.max_output_tokens()
     }
 
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        crate::pricing::open_ai(&self.model)
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -355,6 +363,7 @@ pub fn into_open_ai(
     max_output_tokens: Option<u64>,
     reasoning_effort: Option<ReasoningEffort>,
 ) -> open_ai::Request {
+    let max_output_tokens = request.output_token_limit(max_output_tokens);
     let stream = !model_id.starts_with("o1-");
 
     let mut messages = Vec::new();
@@ -467,6 +476,14 @@ pub fn into_open_ai(
             LanguageModelToolChoice::None => open_ai::ToolChoice::None,
         }),
         reasoning_effort,
+        response_format: request.response_format.map(|format| {
+            open_ai::ResponseFormat::JsonSchema {
+                json_schema: open_ai::JsonSchemaFormat {
+                    name: format.name,
+                    schema: format.schema,
+                },
+            }
+        }),
     }
 }
 
@@ -889,6 +906,8 @@ mod tests {
             stop: vec![],
             temperature: None,
             thinking_allowed: true,
+            response_format: None,
+            max_tokens: None,
         };
 
         // Validate that all models are supported by tiktoken-rs
@@ -904,4 +923,18 @@ mod tests {
             assert!(count > 0);
         }
     }
+
+    #[test]
+    fn test_supports_response_format() {
+        for model in [Model::ThreePointFiveTurbo, Model::Four, Model::FourTurbo] {
+            assert!(
+                !model.supports_response_format(),
+                "{} rejects json_schema response formats",
+                model.id()
+            );
+        }
+        for model in [Model::FourOmni, Model::FourPointOne, Model::Five, Model::O3] {
+            assert!(model.supports_response_format(), "{}", model.id());
+        }
+    }
 }
@@ -5,10 +5,11 @@ use gpui::{AnyView, App, AsyncApp, Context, Entity, SharedString, Task};
 use http_client::HttpClient;
 use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent,
-    LanguageModelId, LanguageModelName, LanguageModelProvider, LanguageModelProviderId,
-    LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest,
-    LanguageModelToolChoice, LanguageModelToolResultContent, LanguageModelToolSchemaFormat,
-    LanguageModelToolUse, MessageContent, RateLimiter, Role, StopReason, TokenUsage,
+    LanguageModelId, LanguageModelName, LanguageModelPricing, LanguageModelProvider,
+    LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
+    LanguageModelRequest, LanguageModelToolChoice, LanguageModelToolResultContent,
+    LanguageModelToolSchemaFormat, LanguageModelToolUse, MessageContent, RateLimiter, Role,
+    StopReason, TokenUsage,
 };
 use open_router::{
     Model, ModelMode as OpenRouterModelMode, OPEN_ROUTER_API_URL, ResponseStreamEvent, list_models,
@@ -203,6 +204,11 @@ impl LanguageModelProvider for OpenRouterLanguageModelProvider {
         let mut settings_models = Vec::new();
 
         for model in &Self::settings(cx).available_models {
+            // Settings don't include prices, so keep the ones OpenRouter reports.
+            let pricing = models_from_api
+                .iter()
+                .find(|api_model| api_model.name == model.name)
+                .and_then(|api_model| api_model.pricing.clone());
             settings_models.push(open_router::Model {
                 name: model.name.clone(),
                 display_name: model.display_name.clone(),
@@ -211,6 +217,7 @@ impl LanguageModelProvider for OpenRouterLanguageModelProvider {
                 supports_images: model.supports_images,
                 mode: model.mode.unwrap_or_default(),
                 provider: model.provider.clone(),
+                pricing,
             });
         }
 
@@ -342,6 +349,10 @@ impl LanguageModel for OpenRouterLanguageModel {
         self.model.max_output_tokens()
     }
 
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        crate::pricing::open_router(&self.model)
+    }
+
     fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
         match choice {
             LanguageModelToolChoice::Auto => true,
@@ -391,6 +402,7 @@ pub fn into_open_router(
     model: &Model,
     max_output_tokens: Option<u64>,
 ) -> open_router::Request {
+    let max_output_tokens = request.output_token_limit(max_output_tokens);
     let mut messages = Vec::new();
     for message in request.messages {
         let reasoning_details = message.reasoning_details.clone();
@@ -5,9 +5,9 @@ use gpui::{AnyView, App, AsyncApp, Context, Entity, SharedString, Task, Window};
 use http_client::HttpClient;
 use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent,
-    LanguageModelId, LanguageModelName, LanguageModelProvider, LanguageModelProviderId,
-    LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest,
-    LanguageModelToolChoice, RateLimiter, Role,
+    LanguageModelId, LanguageModelName, LanguageModelPricing, LanguageModelProvider,
+    LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
+    LanguageModelRequest, LanguageModelToolChoice, RateLimiter, Role,
 };
 use open_ai::ResponseStreamEvent;
 pub use settings::VercelAvailableModel as AvailableModel;
@@ -272,6 +272,10 @@ impl LanguageModel for VercelLanguageModel {
         }
     }
 
+    fn supports_response_format(&self) -> bool {
+        true
+    }
+
     fn telemetry_id(&self) -> String {
         format!("vercel/{}", self.model.id())
     }
@@ -284,6 +288,10 @@ impl LanguageModel for VercelLanguageModel {
         self.model.max_output_tokens()
     }
 
+    fn pricing(&self) -> Option<LanguageModelPricing> {
+        crate::pricing::vercel(&self.model)
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
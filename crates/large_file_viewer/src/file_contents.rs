@@ -0,0 +1,83 @@
+use std::{borrow::Cow, fs::File, io, ops::Range, path::Path};
+
+use util::byte_search::ByteSource;
+
+/// The contents of a file on disk, read with positional reads whenever
+/// they're needed.
+///
+/// Unlike a memory map, this can't crash the process when the file is
+/// truncated by another process: reads past the new end of the file just come
+/// back short.
+pub struct FileContents {
+    file: File,
+    len: usize,
+}
+
+impl FileContents {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        Ok(Self { file, len })
+    }
+
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::FileExt::read_at(&self.file, buf, offset)
+        }
+        #[cfg(windows)]
+        {
+            std::os::windows::fs::FileExt::seek_read(&self.file, buf, offset)
+        }
+    }
+}
+
+impl ByteSource for FileContents {
+    /// The length of the file when it was opened.
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn read(&self, range: Range<usize>) -> Cow<'_, [u8]> {
+        let end = range.end.min(self.len);
+        let mut bytes = vec![0; end.saturating_sub(range.start)];
+        let mut read = 0;
+        while read < bytes.len() {
+            match self.read_at(&mut bytes[read..], (range.start + read) as u64) {
+                Ok(0) => break,
+                Ok(len) => read += len,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => {
+                    log::error!("failed to read large file: {error}");
+                    break;
+                }
+            }
+        }
+        bytes.truncate(read);
+        Cow::Owned(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write as _;
+
+    #[test]
+    fn test_reads_after_truncation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        std::fs::write(&path, "0123456789").unwrap();
+
+        let contents = FileContents::open(&path).unwrap();
+        assert_eq!(contents.len(), 10);
+        assert_eq!(&*contents.read(2..5), b"234");
+        assert_eq!(&*contents.read(8..20), b"89");
+
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(b"abc").unwrap();
+        drop(file);
+        assert_eq!(&*contents.read(0..10), b"abc");
+        assert_eq!(&*contents.read(5..10), b"");
+    }
+}
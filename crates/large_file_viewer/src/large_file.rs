@@ -0,0 +1,151 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Task};
+use project::{Project, ProjectEntryId, ProjectItem, ProjectPath};
+use settings::Settings as _;
+use util::byte_search::ByteSource;
+
+use crate::{LargeFileViewerSettings, file_contents::FileContents, line_index::LineIndex};
+
+/// How many bytes are indexed at a time, between updates of the view.
+const INDEX_CHUNK_LEN: usize = 32 * 1024 * 1024;
+
+pub enum LargeFileEvent {
+    Indexed,
+}
+
+/// A file that's too large to be loaded into a buffer, which is read from disk
+/// as it's viewed instead.
+pub struct LargeFile {
+    project_path: ProjectPath,
+    abs_path: PathBuf,
+    entry_id: Option<ProjectEntryId>,
+    contents: Arc<FileContents>,
+    line_index: LineIndex,
+    _index_lines: Task<()>,
+}
+
+impl EventEmitter<LargeFileEvent> for LargeFile {}
+
+impl LargeFile {
+    fn new(
+        project_path: ProjectPath,
+        abs_path: PathBuf,
+        entry_id: Option<ProjectEntryId>,
+        contents: FileContents,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let line_index = LineIndex::new(contents.len());
+        let index_lines = cx.spawn(async move |this, cx| {
+            loop {
+                let Ok((cursor, contents)) = this.read_with(cx, |this, _| {
+                    (this.line_index.cursor(), this.contents.clone())
+                }) else {
+                    return;
+                };
+                let chunk = cx
+                    .background_spawn(async move { cursor.index(&*contents, INDEX_CHUNK_LEN) })
+                    .await;
+                let Ok(is_complete) = this.update(cx, |this, cx| {
+                    this.line_index.push(chunk);
+                    cx.emit(LargeFileEvent::Indexed);
+                    cx.notify();
+                    this.line_index.is_complete()
+                }) else {
+                    return;
+                };
+                if is_complete {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            project_path,
+            abs_path,
+            entry_id,
+            contents: Arc::new(contents),
+            line_index,
+            _index_lines: index_lines,
+        }
+    }
+
+    pub fn contents(&self) -> &Arc<FileContents> {
+        &self.contents
+    }
+
+    pub fn line_index(&self) -> &LineIndex {
+        &self.line_index
+    }
+
+    pub fn abs_path(&self) -> &Path {
+        &self.abs_path
+    }
+
+    pub fn project_path(&self) -> &ProjectPath {
+        &self.project_path
+    }
+
+    /// Opens the file at the given path as a large file, without checking its size.
+    pub fn open(
+        project: &Entity<Project>,
+        project_path: ProjectPath,
+        cx: &mut App,
+    ) -> Task<anyhow::Result<Entity<Self>>> {
+        let project = project.read(cx);
+        let entry_id = project
+            .entry_for_path(&project_path, cx)
+            .map(|entry| entry.id);
+        let Some(abs_path) = project.absolute_path(&project_path, cx) else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "no absolute path for {:?}",
+                project_path.path
+            )));
+        };
+        cx.spawn(async move |cx| {
+            let contents = cx
+                .background_spawn({
+                    let abs_path = abs_path.clone();
+                    async move { FileContents::open(&abs_path) }
+                })
+                .await
+                .with_context(|| format!("opening {abs_path:?}"))?;
+            cx.new(|cx| LargeFile::new(project_path, abs_path, entry_id, contents, cx))
+        })
+    }
+}
+
+impl ProjectItem for LargeFile {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<anyhow::Result<Entity<Self>>>> {
+        // Files are read directly from disk, so they have to be on this machine.
+        if !project.read(cx).is_local() {
+            return None;
+        }
+        let threshold = LargeFileViewerSettings::get_global(cx).threshold_bytes();
+        let entry = project.read(cx).entry_for_path(path, cx)?;
+        if !entry.is_file() || entry.size == 0 || entry.size < threshold {
+            return None;
+        }
+        Some(Self::open(project, path.clone(), cx))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+
+    fn is_dirty(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,637 @@
+use std::{ops::Range, path::Path};
+
+use editor::{Editor, EditorSettings, actions::SelectAll, items::entry_git_aware_label_color};
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, HighlightStyle, Pixels,
+    ScrollWheelEvent, StyledText, Subscription, Task, Window, px,
+};
+use project::Project;
+use settings::Settings as _;
+use theme::{Theme, ThemeSettings};
+use ui::prelude::*;
+use util::{
+    byte_search::{ByteSource, find_next, find_previous},
+    paths::PathExt as _,
+};
+use workspace::{
+    ItemSettings, Pane, ToolbarItemLocation, WorkspaceId,
+    invalid_item_view::InvalidItemView,
+    item::{BreadcrumbText, Item, ItemEvent, ProjectItem, TabContentParams},
+};
+
+use crate::{
+    Find, GoToLine, MovePageDown, MovePageUp, MoveToBeginning, MoveToEnd, ScrollDown, ScrollUp,
+    SelectNextMatch, SelectPreviousMatch,
+    large_file::{LargeFile, LargeFileEvent},
+};
+
+/// Lines longer than this are cut off, so that a file that's one huge line
+/// can still be shown.
+const MAX_LINE_LEN: usize = 4096;
+
+#[derive(Clone, Copy)]
+enum ScrollTarget {
+    Row(usize),
+    Offset(usize),
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Next,
+    Previous,
+}
+
+/// A read-only view of a [`LargeFile`].
+///
+/// Only the visible lines are ever read from the file, and rows are scrolled
+/// by index rather than by pixel offset, so that files with hundreds of
+/// millions of lines can be scrolled through.
+pub struct LargeFileView {
+    file: Entity<LargeFile>,
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    search_editor: Entity<Editor>,
+    line_editor: Entity<Editor>,
+    /// The row at the top of the view.
+    scroll_row: usize,
+    /// How far the view is scrolled to the right.
+    scroll_x: Pixels,
+    /// Vertical scrolling that doesn't yet add up to a whole row.
+    pending_scroll_y: Pixels,
+    /// How many rows fit in the view, as of the last render.
+    page_rows: usize,
+    /// A scroll to a part of the file that hasn't been indexed yet.
+    pending_scroll: Option<ScrollTarget>,
+    selected_match: Option<Range<usize>>,
+    search_status: Option<SharedString>,
+    search: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl LargeFileView {
+    pub fn new(
+        file: Entity<LargeFile>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let search_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Find in file…", window, cx);
+            editor
+        });
+        let line_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Go to line…", window, cx);
+            editor
+        });
+        let subscriptions = vec![cx.subscribe(&file, Self::on_file_event)];
+
+        Self {
+            file,
+            project,
+            focus_handle: cx.focus_handle(),
+            search_editor,
+            line_editor,
+            scroll_row: 0,
+            scroll_x: px(0.),
+            pending_scroll_y: px(0.),
+            page_rows: 0,
+            pending_scroll: None,
+            selected_match: None,
+            search_status: None,
+            search: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn on_file_event(
+        &mut self,
+        _: Entity<LargeFile>,
+        event: &LargeFileEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            LargeFileEvent::Indexed => {
+                if let Some(target) = self.pending_scroll.take() {
+                    self.scroll_to(target, cx);
+                }
+                cx.notify();
+            }
+        }
+    }
+
+    fn line_height(cx: &App) -> Pixels {
+        let settings = ThemeSettings::get_global(cx);
+        settings.buffer_font_size(cx) * settings.line_height()
+    }
+
+    fn row_count(&self, cx: &App) -> usize {
+        self.file.read(cx).line_index().row_count()
+    }
+
+    fn max_scroll_row(&self, cx: &App) -> usize {
+        self.row_count(cx).saturating_sub(1)
+    }
+
+    fn scroll_by(&mut self, rows: isize, cx: &mut Context<Self>) {
+        self.pending_scroll = None;
+        self.scroll_row = self
+            .scroll_row
+            .saturating_add_signed(rows)
+            .min(self.max_scroll_row(cx));
+        cx.notify();
+    }
+
+    /// Scrolls so that the target is near the top of the view, or once it has
+    /// been indexed, if it hasn't yet.
+    fn scroll_to(&mut self, target: ScrollTarget, cx: &mut Context<Self>) {
+        let file = self.file.read(cx);
+        let line_index = file.line_index();
+        let row = match target {
+            ScrollTarget::Row(row) if row < line_index.row_count() => Some(row),
+            ScrollTarget::Row(_) if line_index.is_complete() => Some(self.max_scroll_row(cx)),
+            ScrollTarget::Row(_) => None,
+            ScrollTarget::Offset(offset) => {
+                line_index.row_for_offset(file.contents().as_ref(), offset)
+            }
+        };
+        match row {
+            Some(row) => {
+                self.pending_scroll = None;
+                self.scroll_row = row.saturating_sub(self.page_rows / 3);
+                self.scroll_x = px(0.);
+            }
+            None => self.pending_scroll = Some(target),
+        }
+        cx.notify();
+    }
+
+    fn scroll_up(&mut self, _: &ScrollUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_by(-1, cx);
+    }
+
+    fn scroll_down(&mut self, _: &ScrollDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_by(1, cx);
+    }
+
+    fn move_page_up(&mut self, _: &MovePageUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_by(-(self.page_rows.max(1) as isize), cx);
+    }
+
+    fn move_page_down(&mut self, _: &MovePageDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_by(self.page_rows.max(1) as isize, cx);
+    }
+
+    fn move_to_beginning(&mut self, _: &MoveToBeginning, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_to(ScrollTarget::Row(0), cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_to(ScrollTarget::Row(usize::MAX), cx);
+    }
+
+    fn scroll_wheel(&mut self, event: &ScrollWheelEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let line_height = Self::line_height(cx);
+        let delta = event.delta.pixel_delta(line_height);
+        self.scroll_x = (self.scroll_x - delta.x).max(px(0.));
+        self.pending_scroll_y -= delta.y;
+        let rows = (self.pending_scroll_y / line_height) as isize;
+        self.pending_scroll_y -= line_height * rows as f32;
+        self.scroll_by(rows, cx);
+    }
+
+    fn find(&mut self, _: &Find, window: &mut Window, cx: &mut Context<Self>) {
+        self.search_editor.update(cx, |editor, cx| {
+            editor.select_all(&SelectAll, window, cx);
+        });
+        window.focus(&self.search_editor.focus_handle(cx));
+    }
+
+    fn go_to_line(&mut self, _: &GoToLine, window: &mut Window, cx: &mut Context<Self>) {
+        self.line_editor.update(cx, |editor, cx| {
+            editor.select_all(&SelectAll, window, cx);
+        });
+        window.focus(&self.line_editor.focus_handle(cx));
+    }
+
+    fn confirm_go_to_line(
+        &mut self,
+        _: &menu::Confirm,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let text = self.line_editor.read(cx).text(cx);
+        if let Ok(line) = text.trim().parse::<usize>() {
+            self.scroll_to(ScrollTarget::Row(line.saturating_sub(1)), cx);
+            window.focus(&self.focus_handle);
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, window: &mut Window, _: &mut Context<Self>) {
+        window.focus(&self.focus_handle);
+    }
+
+    fn select_next_match(&mut self, _: &SelectNextMatch, _: &mut Window, cx: &mut Context<Self>) {
+        self.search(Direction::Next, cx);
+    }
+
+    fn select_previous_match(
+        &mut self,
+        _: &SelectPreviousMatch,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.search(Direction::Previous, cx);
+    }
+
+    /// Searches for the query from the selected match, or from the top of the
+    /// view if there isn't one.
+    fn search(&mut self, direction: Direction, cx: &mut Context<Self>) {
+        let query = self.search_editor.read(cx).text(cx);
+        if query.is_empty() {
+            return;
+        }
+        let file = self.file.read(cx);
+        let contents = file.contents().clone();
+        let offset = match (direction, &self.selected_match) {
+            (Direction::Next, Some(selected_match)) => selected_match.start + 1,
+            (Direction::Previous, Some(selected_match)) => selected_match.end - 1,
+            (_, None) => file
+                .line_index()
+                .line_start(contents.as_ref(), self.scroll_row)
+                .unwrap_or(0),
+        };
+
+        self.search_status = Some("Searching…".into());
+        self.search = Some(cx.spawn(async move |this, cx| {
+            let found = cx
+                .background_spawn(async move {
+                    match direction {
+                        Direction::Next => find_next(contents.as_ref(), query.as_bytes(), offset),
+                        Direction::Previous => {
+                            find_previous(contents.as_ref(), query.as_bytes(), offset)
+                        }
+                    }
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                this.search = None;
+                match found {
+                    Some(range) => {
+                        this.search_status = None;
+                        this.scroll_to(ScrollTarget::Offset(range.start), cx);
+                        this.selected_match = Some(range);
+                    }
+                    None => {
+                        this.search_status = Some("No matches".into());
+                        this.selected_match = None;
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn render_row(
+        &self,
+        row: usize,
+        gutter_digits: usize,
+        line_height: Pixels,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let file = self.file.read(cx);
+        let contents = file.contents().as_ref();
+        let range = file
+            .line_index()
+            .line_range(contents, row, MAX_LINE_LEN)
+            .unwrap_or_default();
+        let line = contents.read(range.clone());
+        let line_bytes = |bytes: Range<usize>| {
+            let end = (bytes.end - range.start).min(line.len());
+            &line[(bytes.start - range.start).min(end)..end]
+        };
+
+        // Decode the parts of the line before, within and after the selected
+        // match separately, so that the match can be highlighted even though
+        // decoding invalid UTF-8 can change the length of the text.
+        let match_range = self
+            .selected_match
+            .as_ref()
+            .map(|selected_match| {
+                selected_match.start.clamp(range.start, range.end)
+                    ..selected_match.end.clamp(range.start, range.end)
+            })
+            .filter(|match_range| !match_range.is_empty());
+        let mut text = String::new();
+        let mut highlights = Vec::new();
+        let mut push = |bytes: &[u8]| {
+            // Tabs are shown as single spaces, since the text isn't laid out
+            // in columns.
+            text.push_str(&String::from_utf8_lossy(bytes).replace('\t', " "));
+            text.len()
+        };
+        if let Some(match_range) = match_range {
+            let highlight_start = push(line_bytes(range.start..match_range.start));
+            let highlight_end = push(line_bytes(match_range.clone()));
+            push(line_bytes(match_range.end..range.end));
+            highlights.push((
+                highlight_start..highlight_end,
+                HighlightStyle {
+                    background_color: Some(cx.theme().colors().search_match_background),
+                    ..Default::default()
+                },
+            ));
+        } else {
+            push(&line);
+        }
+
+        h_flex()
+            .h(line_height)
+            .child(
+                div()
+                    .flex_none()
+                    .pl_2()
+                    .pr_4()
+                    .text_color(cx.theme().colors().editor_line_number)
+                    .child(format!("{:>gutter_digits$}", row + 1)),
+            )
+            .child(
+                div().overflow_hidden().flex_1().child(
+                    div()
+                        .relative()
+                        .left(-self.scroll_x)
+                        .whitespace_nowrap()
+                        .child(StyledText::new(text).with_highlights(highlights)),
+                ),
+            )
+    }
+
+    fn render_input(&self, editor: &Entity<Editor>, cx: &Context<Self>) -> impl IntoElement {
+        h_flex()
+            .min_w_32()
+            .h_8()
+            .px_2()
+            .py_1()
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .rounded_md()
+            .child(editor.clone())
+    }
+}
+
+impl EventEmitter<()> for LargeFileView {}
+
+impl Focusable for LargeFileView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for LargeFileView {
+    type Event = ();
+
+    fn to_item_events(_: &Self::Event, _: impl FnMut(ItemEvent)) {}
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.file.entity_id(), self.file.read(cx))
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.file.read(cx).abs_path().compact();
+        Some(abs_path.to_string_lossy().into_owned().into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &Window, cx: &App) -> AnyElement {
+        let project_path = self.file.read(cx).project_path().clone();
+        let label_color = if ItemSettings::get_global(cx).git_status {
+            let git_status = self
+                .project
+                .read(cx)
+                .project_path_git_status(&project_path, cx)
+                .map(|status| status.summary())
+                .unwrap_or_default();
+
+            self.project
+                .read(cx)
+                .entry_for_path(&project_path, cx)
+                .map(|entry| {
+                    entry_git_aware_label_color(git_status, entry.is_ignored, params.selected)
+                })
+                .unwrap_or_else(|| params.text_color())
+        } else {
+            params.text_color()
+        };
+
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .single_line()
+            .color(label_color)
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.file
+            .read(cx)
+            .abs_path()
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned().into())
+            .unwrap_or_default()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.file.read(cx).abs_path();
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn breadcrumb_location(&self, cx: &App) -> ToolbarItemLocation {
+        if EditorSettings::get_global(cx).toolbar.breadcrumbs {
+            ToolbarItemLocation::PrimaryLeft
+        } else {
+            ToolbarItemLocation::Hidden
+        }
+    }
+
+    fn breadcrumbs(&self, _theme: &Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        let project = self.project.read(cx);
+        let project_path = self.file.read(cx).project_path();
+        let mut path = project_path.path.clone();
+        if project.visible_worktrees(cx).count() > 1
+            && let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx)
+        {
+            path = worktree.read(cx).root_name().join(&path);
+        }
+
+        Some(vec![BreadcrumbText {
+            text: path.display(project.path_style(cx)).to_string(),
+            highlights: None,
+            font: Some(ThemeSettings::get_global(cx).buffer_font.clone()),
+        }])
+    }
+
+    fn can_split(&self) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Entity<Self>>>
+    where
+        Self: Sized,
+    {
+        let file = self.file.clone();
+        let project = self.project.clone();
+        Task::ready(Some(cx.new(|cx| Self::new(file, project, window, cx))))
+    }
+
+    fn buffer_kind(&self, _: &App) -> workspace::item::ItemBufferKind {
+        workspace::item::ItemBufferKind::Singleton
+    }
+}
+
+impl Render for LargeFileView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let line_height = Self::line_height(cx);
+        let theme_settings = ThemeSettings::get_global(cx);
+        let buffer_font = theme_settings.buffer_font.clone();
+        let buffer_font_size = theme_settings.buffer_font_size(cx);
+
+        // The header and footer take up about two rows.
+        self.page_rows = ((window.viewport_size().height / line_height) as usize).saturating_sub(2);
+        let line_index = self.file.read(cx).line_index();
+        let row_count = line_index.row_count();
+        let is_indexed = line_index.is_complete();
+        let progress = line_index.progress();
+        self.scroll_row = self.scroll_row.min(row_count.saturating_sub(1));
+        let visible_rows = self.scroll_row..(self.scroll_row + self.page_rows + 2).min(row_count);
+        let gutter_digits = row_count.max(1).to_string().len();
+
+        let position = if is_indexed {
+            format!("Line {} of {}", self.scroll_row + 1, row_count)
+        } else {
+            format!(
+                "Line {} of {}+ (indexing {:.0}%)",
+                self.scroll_row + 1,
+                row_count,
+                progress * 100.
+            )
+        };
+
+        v_flex()
+            .key_context("LargeFileView")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::scroll_up))
+            .on_action(cx.listener(Self::scroll_down))
+            .on_action(cx.listener(Self::move_page_up))
+            .on_action(cx.listener(Self::move_page_down))
+            .on_action(cx.listener(Self::move_to_beginning))
+            .on_action(cx.listener(Self::move_to_end))
+            .on_action(cx.listener(Self::find))
+            .on_action(cx.listener(Self::go_to_line))
+            .child(
+                h_flex()
+                    .flex_none()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        h_flex()
+                            .key_context("LargeFileViewSearch")
+                            .flex_1()
+                            .gap_2()
+                            .on_action(cx.listener(Self::select_next_match))
+                            .on_action(cx.listener(Self::select_previous_match))
+                            .on_action(cx.listener(Self::cancel))
+                            .child(div().flex_1().child(self.render_input(&self.search_editor, cx)))
+                            .when_some(self.search_status.clone(), |this, status| {
+                                this.child(Label::new(status).size(LabelSize::Small).color(Color::Muted))
+                            }),
+                    )
+                    .child(
+                        div()
+                            .w_40()
+                            .on_action(cx.listener(Self::confirm_go_to_line))
+                            .on_action(cx.listener(Self::cancel))
+                            .child(self.render_input(&self.line_editor, cx)),
+                    ),
+            )
+            .child(
+                div()
+                    .id("large-file-lines")
+                    .flex_1()
+                    .overflow_hidden()
+                    .font(buffer_font)
+                    .text_size(buffer_font_size)
+                    .line_height(line_height)
+                    .text_color(cx.theme().colors().editor_foreground)
+                    .on_click(cx.listener(|this, _, window, _| window.focus(&this.focus_handle)))
+                    .on_scroll_wheel(cx.listener(Self::scroll_wheel))
+                    .children(
+                        visible_rows.map(|row| self.render_row(row, gutter_digits, line_height, cx)),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .flex_none()
+                    .justify_between()
+                    .px_2()
+                    .py_1()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(position).size(LabelSize::Small).color(Color::Muted))
+                    .child(
+                        Label::new("Read-only: large file, without syntax highlighting or language servers")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+    }
+}
+
+impl ProjectItem for LargeFileView {
+    type Item = LargeFile;
+
+    fn for_project_item(
+        project: Entity<Project>,
+        _: Option<&Pane>,
+        item: Entity<Self::Item>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, project, window, cx)
+    }
+
+    fn for_broken_project_item(
+        abs_path: &Path,
+        is_local: bool,
+        e: &anyhow::Error,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<InvalidItemView>
+    where
+        Self: Sized,
+    {
+        Some(InvalidItemView::new(abs_path, is_local, e, window, cx))
+    }
+}
@@ -0,0 +1,42 @@
+mod file_contents;
+mod large_file;
+mod large_file_view;
+mod large_file_viewer_settings;
+mod line_index;
+
+use gpui::{App, actions};
+
+pub use crate::file_contents::*;
+pub use crate::large_file::*;
+pub use crate::large_file_view::*;
+pub use crate::large_file_viewer_settings::*;
+
+actions!(
+    large_file_viewer,
+    [
+        /// Scrolls up by one line in the large file viewer.
+        ScrollUp,
+        /// Scrolls down by one line in the large file viewer.
+        ScrollDown,
+        /// Scrolls up by one page in the large file viewer.
+        MovePageUp,
+        /// Scrolls down by one page in the large file viewer.
+        MovePageDown,
+        /// Scrolls to the start of the file in the large file viewer.
+        MoveToBeginning,
+        /// Scrolls to the end of the file in the large file viewer.
+        MoveToEnd,
+        /// Focuses the search input of the large file viewer.
+        Find,
+        /// Focuses the go to line input of the large file viewer.
+        GoToLine,
+        /// Selects the next match of the search query.
+        SelectNextMatch,
+        /// Selects the previous match of the search query.
+        SelectPreviousMatch
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    workspace::register_project_item::<LargeFileView>(cx);
+}
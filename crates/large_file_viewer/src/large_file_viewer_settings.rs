@@ -0,0 +1,30 @@
+use settings::{RegisterSetting, Settings};
+
+/// The settings for the large file viewer.
+#[derive(Clone, Debug, Default, RegisterSetting)]
+pub struct LargeFileViewerSettings {
+    /// Files at least this many megabytes in size are opened in the large
+    /// file viewer.
+    ///
+    /// Default: 256
+    pub threshold_mb: u64,
+}
+
+impl LargeFileViewerSettings {
+    pub fn threshold_bytes(&self) -> u64 {
+        self.threshold_mb.saturating_mul(1024 * 1024)
+    }
+}
+
+impl Settings for LargeFileViewerSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        Self {
+            threshold_mb: content
+                .large_file_viewer
+                .clone()
+                .unwrap()
+                .threshold_mb
+                .unwrap(),
+        }
+    }
+}
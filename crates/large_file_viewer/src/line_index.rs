@@ -0,0 +1,261 @@
+use std::ops::Range;
+
+use util::byte_search::ByteSource;
+
+/// A line start is recorded at least once every this many lines...
+const LINES_PER_CHECKPOINT: usize = 256;
+/// ...and at the first line start past this many bytes since the previous
+/// checkpoint, so that looking up a line never scans far, even when lines are
+/// very long.
+const BYTES_PER_CHECKPOINT: usize = 64 * 1024;
+/// How many bytes are read at a time when scanning for a line.
+const SCAN_WINDOW_LEN: usize = 16 * 1024;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Checkpoint {
+    row: usize,
+    offset: usize,
+}
+
+/// A sparse index of the line starts in a file, which is built incrementally
+/// so that the start of the file can be shown while the rest is still being
+/// indexed.
+///
+/// Only every so often is a line start recorded, which keeps the index small
+/// for files with hundreds of millions of lines. Lines in between are found by
+/// scanning forward from the nearest checkpoint.
+pub struct LineIndex {
+    len: usize,
+    checkpoints: Vec<Checkpoint>,
+    cursor: IndexCursor,
+}
+
+/// Where indexing left off. Cheap to copy, so that the next chunk of a file
+/// can be indexed on a background thread.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IndexCursor {
+    /// How many bytes have been indexed.
+    offset: usize,
+    /// How many newlines there are in the indexed bytes.
+    row: usize,
+    last_checkpoint: Checkpoint,
+}
+
+/// The result of indexing a chunk of a file, to be added to a [`LineIndex`].
+pub struct IndexedChunk {
+    cursor: IndexCursor,
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl IndexCursor {
+    /// Indexes up to `len` bytes of `contents`, starting where this cursor
+    /// left off.
+    pub fn index(mut self, contents: &(impl ByteSource + ?Sized), len: usize) -> IndexedChunk {
+        let start = self.offset;
+        let end = start.saturating_add(len).min(contents.len());
+        let chunk = contents.read(start..end);
+        let mut checkpoints = Vec::new();
+        for newline in memchr::memchr_iter(b'\n', &chunk) {
+            let line_start = start + newline + 1;
+            self.row += 1;
+            if self.row - self.last_checkpoint.row >= LINES_PER_CHECKPOINT
+                || line_start - self.last_checkpoint.offset >= BYTES_PER_CHECKPOINT
+            {
+                self.last_checkpoint = Checkpoint {
+                    row: self.row,
+                    offset: line_start,
+                };
+                checkpoints.push(self.last_checkpoint);
+            }
+        }
+        self.offset = start + chunk.len();
+        IndexedChunk {
+            cursor: self,
+            checkpoints,
+        }
+    }
+}
+
+impl LineIndex {
+    /// Creates an empty index for a file that's `len` bytes long.
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            checkpoints: vec![Checkpoint::default()],
+            cursor: IndexCursor::default(),
+        }
+    }
+
+    pub fn cursor(&self) -> IndexCursor {
+        self.cursor
+    }
+
+    pub fn push(&mut self, chunk: IndexedChunk) {
+        // The file ended early, because it was truncated while being indexed.
+        if chunk.cursor.offset == self.cursor.offset {
+            self.len = self.cursor.offset;
+        }
+        self.checkpoints.extend(chunk.checkpoints);
+        self.cursor = chunk.cursor;
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.cursor.offset >= self.len
+    }
+
+    /// How much of the file has been indexed, between 0 and 1.
+    pub fn progress(&self) -> f32 {
+        if self.len == 0 {
+            1.
+        } else {
+            self.cursor.offset as f32 / self.len as f32
+        }
+    }
+
+    /// The number of rows that can be shown. Until the whole file has been
+    /// indexed, the last row may still be incomplete, so it's left out.
+    pub fn row_count(&self) -> usize {
+        if self.is_complete() {
+            self.cursor.row + 1
+        } else {
+            self.cursor.row
+        }
+    }
+
+    /// Returns the byte offset at which the given row starts.
+    pub fn line_start(&self, contents: &(impl ByteSource + ?Sized), row: usize) -> Option<usize> {
+        if row >= self.row_count() {
+            return None;
+        }
+        let checkpoint = self.checkpoints[self.checkpoints.partition_point(|c| c.row <= row) - 1];
+        let mut newlines_left = row - checkpoint.row;
+        let mut offset = checkpoint.offset;
+        while newlines_left > 0 {
+            let window = contents.read(offset..offset.saturating_add(SCAN_WINDOW_LEN));
+            if window.is_empty() {
+                return None;
+            }
+            for newline in memchr::memchr_iter(b'\n', &window) {
+                newlines_left -= 1;
+                if newlines_left == 0 {
+                    return Some(offset + newline + 1);
+                }
+            }
+            offset += window.len();
+        }
+        Some(offset)
+    }
+
+    /// Returns the byte range of the given row, excluding its line ending and
+    /// truncated to `max_len` bytes.
+    pub fn line_range(
+        &self,
+        contents: &(impl ByteSource + ?Sized),
+        row: usize,
+        max_len: usize,
+    ) -> Option<Range<usize>> {
+        let start = self.line_start(contents, row)?;
+        let line = contents.read(start..start.saturating_add(max_len));
+        let mut len = line.len();
+        if let Some(newline) = memchr::memchr(b'\n', &line) {
+            len = newline;
+            if len > 0 && line[len - 1] == b'\r' {
+                len -= 1;
+            }
+        }
+        Some(start..start + len)
+    }
+
+    /// Returns the row containing the given byte offset, if that part of the
+    /// file has been indexed.
+    pub fn row_for_offset(
+        &self,
+        contents: &(impl ByteSource + ?Sized),
+        offset: usize,
+    ) -> Option<usize> {
+        if offset > self.cursor.offset {
+            return None;
+        }
+        let checkpoint =
+            self.checkpoints[self.checkpoints.partition_point(|c| c.offset <= offset) - 1];
+        let mut row = checkpoint.row;
+        let mut window_start = checkpoint.offset;
+        while window_start < offset {
+            let window_end = window_start.saturating_add(SCAN_WINDOW_LEN).min(offset);
+            let window = contents.read(window_start..window_end);
+            if window.is_empty() {
+                break;
+            }
+            row += memchr::memchr_iter(b'\n', &window).count();
+            window_start += window.len();
+        }
+        (row < self.row_count()).then_some(row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    fn build_index(contents: &[u8], chunk_len: usize) -> LineIndex {
+        let mut index = LineIndex::new(contents.len());
+        while !index.is_complete() {
+            index.push(index.cursor().index(contents, chunk_len));
+        }
+        index
+    }
+
+    #[test]
+    fn test_line_index() {
+        let contents = b"one\r\ntwo\n\nfour";
+        let mut index = LineIndex::new(contents.len());
+        assert_eq!(index.row_count(), 0);
+
+        index.push(index.cursor().index(contents, 7));
+        assert_eq!(index.row_count(), 1);
+        assert_eq!(index.line_range(contents, 0, 100), Some(0..3));
+        assert_eq!(index.line_range(contents, 1, 100), None);
+        assert_eq!(index.row_for_offset(contents, 6), None);
+
+        index.push(index.cursor().index(contents, 100));
+        assert!(index.is_complete());
+        assert_eq!(index.row_count(), 4);
+        assert_eq!(index.line_range(contents, 1, 100), Some(5..8));
+        assert_eq!(index.line_range(contents, 2, 100), Some(9..9));
+        assert_eq!(index.line_range(contents, 3, 100), Some(10..14));
+        assert_eq!(index.line_range(contents, 3, 2), Some(10..12));
+        assert_eq!(index.line_range(contents, 4, 100), None);
+        assert_eq!(index.row_for_offset(contents, 6), Some(1));
+        assert_eq!(index.row_for_offset(contents, 14), Some(3));
+
+        let index = build_index(b"", 10);
+        assert_eq!(index.row_count(), 1);
+        assert_eq!(index.line_range(b"", 0, 100), Some(0..0));
+    }
+
+    #[gpui::test(iterations = 10)]
+    fn test_random_line_index(mut rng: StdRng) {
+        let mut contents = Vec::new();
+        let mut line_starts = vec![0];
+        for _ in 0..rng.random_range(0..2000) {
+            // Mix short lines with a few long ones, so that both kinds of
+            // checkpoints are exercised.
+            let len = if rng.random_bool(0.01) {
+                rng.random_range(0..200_000)
+            } else {
+                rng.random_range(0..20)
+            };
+            contents.extend(std::iter::repeat_n(b'a', len));
+            contents.push(b'\n');
+            line_starts.push(contents.len());
+        }
+
+        let index = build_index(&contents, rng.random_range(1..100_000));
+        assert_eq!(index.row_count(), line_starts.len());
+        for (row, &start) in line_starts.iter().enumerate() {
+            assert_eq!(index.line_start(&contents, row), Some(start), "row {row}");
+            assert_eq!(index.row_for_offset(&contents, start), Some(row));
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use language::language_settings::FimTemplate;
+
+/// Guesses the fill-in-the-middle format of a model from its name, falling
+/// back to the Qwen format used by the default model.
+pub(crate) fn infer_template(model: &str) -> FimTemplate {
+    let model = model.to_lowercase();
+    if model.contains("starcoder") {
+        FimTemplate::StarCoder
+    } else if model.contains("codegemma") {
+        FimTemplate::CodeGemma
+    } else if model.contains("codellama") || model.contains("code-llama") {
+        FimTemplate::CodeLlama
+    } else if model.contains("deepseek") {
+        FimTemplate::DeepSeek
+    } else if model.contains("codestral") {
+        FimTemplate::Codestral
+    } else {
+        FimTemplate::Qwen
+    }
+}
+
+pub(crate) fn format_prompt(template: FimTemplate, prefix: &str, suffix: &str) -> String {
+    match template {
+        FimTemplate::Qwen | FimTemplate::CodeGemma => {
+            format!("<|fim_prefix|>{prefix}<|fim_suffix|>{suffix}<|fim_middle|>")
+        }
+        FimTemplate::StarCoder => format!("<fim_prefix>{prefix}<fim_suffix>{suffix}<fim_middle>"),
+        FimTemplate::CodeLlama => format!("<PRE> {prefix} <SUF>{suffix} <MID>"),
+        FimTemplate::DeepSeek => {
+            format!("<｜fim▁begin｜>{prefix}<｜fim▁hole｜>{suffix}<｜fim▁end｜>")
+        }
+        FimTemplate::Codestral => format!("[SUFFIX]{suffix}[PREFIX]{prefix}"),
+    }
+}
+
+/// Tokens that end the middle section. Servers normally strip these from the
+/// output, but not all of them do, so completions are also truncated at them.
+pub(crate) fn stop_tokens(template: FimTemplate) -> &'static [&'static str] {
+    match template {
+        FimTemplate::Qwen => &[
+            "<|endoftext|>",
+            "<|fim_pad|>",
+            "<|file_sep|>",
+            "<|repo_name|>",
+            "<|im_start|>",
+            "<|im_end|>",
+        ],
+        FimTemplate::CodeGemma => &[
+            "<|file_separator|>",
+            "<|fim_prefix|>",
+            "<|fim_suffix|>",
+            "<|fim_middle|>",
+        ],
+        FimTemplate::StarCoder => &["<|endoftext|>", "<file_sep>", "<fim_prefix>"],
+        FimTemplate::CodeLlama => &["<EOT>"],
+        FimTemplate::DeepSeek => &["<｜end▁of▁sentence｜>", "<｜fim▁begin｜>"],
+        FimTemplate::Codestral => &["</s>", "[PREFIX]", "[SUFFIX]"],
+    }
+}
+
+pub(crate) fn truncate_at_stop_token(completion: &str, template: FimTemplate) -> &str {
+    let end = stop_tokens(template)
+        .iter()
+        .filter_map(|token| completion.find(token))
+        .min()
+        .unwrap_or(completion.len());
+    &completion[..end]
+}
@@ -0,0 +1,617 @@
+mod fim;
+
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use edit_prediction::{Direction, EditPrediction, EditPredictionProvider, FimCompletionState};
+use edit_prediction_context::{
+    EditPredictionContext, EditPredictionContextOptions, EditPredictionExcerptOptions,
+    EditPredictionScoreOptions, ScoredDeclaration, SyntaxIndex,
+};
+use futures::AsyncReadExt;
+use gpui::{App, AppContext as _, Context, Entity, EntityId, Global, WeakEntity};
+use http_client::HttpClient;
+use language::{
+    Anchor, Buffer, ToPoint,
+    language_settings::{FimTemplate, LocalEditPredictionApi, all_language_settings},
+};
+use project::Project;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use text::ToOffset;
+
+pub const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(150);
+
+pub const DEFAULT_MODEL: &str = "qwen2.5-coder:1.5b-base";
+const DEFAULT_MAX_TOKENS: u32 = 128;
+const DEFAULT_OPEN_AI_COMPATIBLE_API_URL: &str = "http://localhost:8080/v1";
+
+const CONTEXT_OPTIONS: EditPredictionContextOptions = EditPredictionContextOptions {
+    use_imports: true,
+    excerpt: EditPredictionExcerptOptions {
+        max_bytes: 2048,
+        min_bytes: 1024,
+        target_before_cursor_over_total_bytes: 0.66,
+    },
+    score: EditPredictionScoreOptions {
+        omit_excerpt_overlaps: true,
+    },
+    max_retrieved_declarations: 8,
+};
+/// Retrieved declarations are prepended to the prefix up to this size, so that
+/// small local models aren't slowed down by long prompts.
+const MAX_RETRIEVED_BYTES: usize = 2048;
+const FILE_INDEXING_PARALLELISM: usize = 1;
+
+/// Indexing a project is expensive, so the providers of all its editors share
+/// one index.
+#[derive(Default)]
+struct SyntaxIndexes(HashMap<EntityId, WeakEntity<SyntaxIndex>>);
+
+impl Global for SyntaxIndexes {}
+
+fn syntax_index(project: &Entity<Project>, cx: &mut App) -> Entity<SyntaxIndex> {
+    let indexes = &mut cx.default_global::<SyntaxIndexes>().0;
+    indexes.retain(|_, index| index.upgrade().is_some());
+    if let Some(index) = indexes
+        .get(&project.entity_id())
+        .and_then(|index| index.upgrade())
+    {
+        return index;
+    }
+
+    let index = cx.new(|cx| SyntaxIndex::new(project, FILE_INDEXING_PARALLELISM, cx));
+    cx.default_global::<SyntaxIndexes>()
+        .0
+        .insert(project.entity_id(), index.downgrade());
+    index
+}
+
+/// The resolved `edit_predictions.local` settings for a single request.
+#[derive(Clone, Debug)]
+struct RequestSettings {
+    api: LocalEditPredictionApi,
+    api_url: String,
+    model: String,
+    fim_template: FimTemplate,
+    max_tokens: u32,
+}
+
+impl RequestSettings {
+    fn load(cx: &App) -> Self {
+        let settings = &all_language_settings(None, cx).edit_predictions.local;
+        let model = settings
+            .model
+            .clone()
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        let api_url = settings
+            .api_url
+            .clone()
+            .unwrap_or_else(|| match settings.api {
+                LocalEditPredictionApi::Ollama => ollama::OLLAMA_API_URL.to_string(),
+                LocalEditPredictionApi::OpenAiCompatible => {
+                    DEFAULT_OPEN_AI_COMPATIBLE_API_URL.to_string()
+                }
+            });
+        Self {
+            api: settings.api,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            fim_template: settings
+                .fim_template
+                .unwrap_or_else(|| fim::infer_template(&model)),
+            model,
+            max_tokens: settings.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+        }
+    }
+}
+
+/// Predicts edits with a fill-in-the-middle model hosted on Ollama or an
+/// OpenAI-compatible server, so that code never leaves the user's network.
+pub struct LocalCompletionProvider {
+    http_client: Arc<dyn HttpClient>,
+    syntax_index: Option<Entity<SyntaxIndex>>,
+    state: FimCompletionState,
+}
+
+impl LocalCompletionProvider {
+    /// Declarations related to the code around the cursor are retrieved from
+    /// `project`, when there is one.
+    pub fn new(
+        http_client: Arc<dyn HttpClient>,
+        project: Option<&Entity<Project>>,
+        cx: &mut App,
+    ) -> Self {
+        Self {
+            http_client,
+            syntax_index: project.map(|project| syntax_index(project, cx)),
+            state: FimCompletionState::default(),
+        }
+    }
+
+    async fn fetch_completion(
+        http_client: Arc<dyn HttpClient>,
+        settings: RequestSettings,
+        prefix: &str,
+        suffix: &str,
+    ) -> Result<String> {
+        let start_time = Instant::now();
+        let prompt = fim::format_prompt(settings.fim_template, prefix, suffix);
+        let stop = fim::stop_tokens(settings.fim_template)
+            .iter()
+            .map(|token| token.to_string())
+            .collect();
+
+        log::debug!(
+            "Local edit prediction: Requesting completion (model: {}, template: {:?})",
+            settings.model,
+            settings.fim_template
+        );
+
+        let (uri, request_body) = match settings.api {
+            LocalEditPredictionApi::Ollama => (
+                format!("{}/api/generate", settings.api_url),
+                serde_json::to_string(&OllamaGenerateRequest {
+                    model: settings.model,
+                    prompt,
+                    raw: true,
+                    stream: false,
+                    options: OllamaGenerateOptions {
+                        num_predict: settings.max_tokens,
+                        temperature: 0.2,
+                        stop,
+                    },
+                })?,
+            ),
+            LocalEditPredictionApi::OpenAiCompatible => (
+                format!("{}/completions", settings.api_url),
+                serde_json::to_string(&CompletionRequest {
+                    model: settings.model,
+                    prompt,
+                    max_tokens: settings.max_tokens,
+                    temperature: 0.2,
+                    stop,
+                    stream: false,
+                })?,
+            ),
+        };
+
+        let http_request = http_client::Request::builder()
+            .method(http_client::Method::POST)
+            .uri(uri)
+            .header("Content-Type", "application/json")
+            .body(http_client::AsyncBody::from(request_body))?;
+
+        let mut response = http_client.send(http_request).await?;
+        let status = response.status();
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+
+        if !status.is_success() {
+            return Err(anyhow!("Local model server error: {} - {}", status, body));
+        }
+
+        let completion = match settings.api {
+            LocalEditPredictionApi::Ollama => {
+                serde_json::from_str::<OllamaGenerateResponse>(&body)?.response
+            }
+            LocalEditPredictionApi::OpenAiCompatible => {
+                serde_json::from_str::<CompletionResponse>(&body)?
+                    .choices
+                    .into_iter()
+                    .next()
+                    .context("No completion returned from local model server")?
+                    .text
+            }
+        };
+
+        log::debug!(
+            "Local edit prediction: Completion received ({:.2}s)",
+            start_time.elapsed().as_secs_f64()
+        );
+
+        Ok(fim::truncate_at_stop_token(&completion, settings.fim_template).to_string())
+    }
+}
+
+impl EditPredictionProvider for LocalCompletionProvider {
+    fn name() -> &'static str {
+        "local"
+    }
+
+    fn display_name() -> &'static str {
+        "Local Model"
+    }
+
+    fn show_completions_in_menu() -> bool {
+        true
+    }
+
+    fn is_enabled(&self, _buffer: &Entity<Buffer>, _cursor_position: Anchor, _cx: &App) -> bool {
+        true
+    }
+
+    fn is_refreshing(&self, _cx: &App) -> bool {
+        self.state.is_refreshing()
+    }
+
+    fn refresh(
+        &mut self,
+        buffer: Entity<Buffer>,
+        cursor_position: language::Anchor,
+        debounce: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let http_client = self.http_client.clone();
+        let syntax_index = self.syntax_index.clone();
+        let settings = RequestSettings::load(cx);
+
+        self.state.refresh(
+            buffer,
+            cursor_position,
+            debounce.then_some(DEBOUNCE_TIMEOUT),
+            |this: &mut Self| &mut this.state,
+            move |snapshot, cx| async move {
+                let cursor_offset = cursor_position.to_offset(&snapshot);
+                let cursor_point = cursor_offset.to_point(&snapshot);
+                let comment_prefix = snapshot
+                    .language_scope_at(cursor_offset)
+                    .and_then(|scope| scope.line_comment_prefixes().first().cloned());
+                let context = cx
+                    .update(|cx| {
+                        EditPredictionContext::gather_context_in_background(
+                            cursor_point,
+                            snapshot,
+                            CONTEXT_OPTIONS,
+                            syntax_index,
+                            cx,
+                        )
+                    })?
+                    .await
+                    .context("Line containing cursor doesn't fit in excerpt max bytes")?;
+
+                let excerpt_text = &context.excerpt_text.body;
+                let cursor_within_excerpt = cursor_offset
+                    .saturating_sub(context.excerpt.range.start)
+                    .min(excerpt_text.len());
+                let (prefix, suffix) = excerpt_text.split_at(cursor_within_excerpt);
+                let prefix =
+                    format_declarations(&context.declarations, comment_prefix.as_deref()) + prefix;
+
+                Self::fetch_completion(http_client, settings, &prefix, suffix).await
+            },
+            cx,
+        );
+    }
+
+    fn cycle(
+        &mut self,
+        _buffer: Entity<Buffer>,
+        _cursor_position: Anchor,
+        _direction: Direction,
+        _cx: &mut Context<Self>,
+    ) {
+    }
+
+    fn accept(&mut self, _cx: &mut Context<Self>) {
+        self.state.clear();
+    }
+
+    fn discard(&mut self, _cx: &mut Context<Self>) {
+        self.state.clear();
+    }
+
+    fn suggest(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        _cursor_position: Anchor,
+        cx: &mut Context<Self>,
+    ) -> Option<EditPrediction> {
+        self.state.suggest(buffer, cx)
+    }
+}
+
+/// Renders retrieved declarations as code to precede the excerpt, each headed
+/// by a comment with its path when the language has line comments.
+fn format_declarations(declarations: &[ScoredDeclaration], comment_prefix: Option<&str>) -> String {
+    let mut text = String::new();
+    for scored in declarations {
+        let declaration = &scored.declaration;
+        let (item_text, _) = declaration.item_text();
+        let snippet = if item_text.len() <= MAX_RETRIEVED_BYTES - text.len() {
+            item_text
+        } else {
+            declaration.signature_text().0
+        };
+        let header = comment_prefix
+            .map(|comment_prefix| {
+                format!(
+                    "{comment_prefix}{}\n",
+                    declaration.cached_path().rel_path.as_unix_str()
+                )
+            })
+            .unwrap_or_default();
+        if text.len() + header.len() + snippet.len() + 2 > MAX_RETRIEVED_BYTES {
+            continue;
+        }
+        text.push_str(&header);
+        text.push_str(&snippet);
+        text.push_str("\n\n");
+    }
+    text
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaGenerateRequest {
+    model: String,
+    prompt: String,
+    /// Bypasses the model's chat template so the FIM tokens reach it verbatim.
+    raw: bool,
+    stream: bool,
+    options: OllamaGenerateOptions,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaGenerateOptions {
+    num_predict: u32,
+    temperature: f32,
+    stop: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaGenerateResponse {
+    response: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CompletionRequest {
+    model: String,
+    prompt: String,
+    max_tokens: u32,
+    temperature: f32,
+    stop: Vec<String>,
+    stream: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompletionResponse {
+    choices: Vec<CompletionChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompletionChoice {
+    text: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, TestAppContext};
+    use http_client::{FakeHttpClient, Response};
+    use language::{Language, LanguageConfig, LanguageMatcher, Point, tree_sitter_rust};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::{LocalEditPredictionSettingsContent, SettingsStore};
+    use std::sync::Mutex;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_ollama_fim_request(cx: &mut TestAppContext) {
+        init_test(cx, |settings| {
+            settings.model = Some("codellama:7b-code".into());
+        });
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    requests
+                        .lock()
+                        .unwrap()
+                        .push((uri, serde_json::from_str::<serde_json::Value>(&body)?));
+                    Ok(Response::builder()
+                        .status(200)
+                        .body(r#"{"response": "b<EOT>"}"#.into())
+                        .unwrap())
+                }
+            }
+        });
+
+        let buffer =
+            cx.new(|cx| Buffer::local("fn add(a: i32, b: i32) -> i32 {\n    a + \n}\n", cx));
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_before(Point::new(1, 8)));
+        let provider = cx.new(|cx| LocalCompletionProvider::new(http_client, None, cx));
+
+        // The second refresh cancels the first before it is sent.
+        provider.update(cx, |provider, cx| {
+            provider.refresh(buffer.clone(), cursor, true, cx);
+            provider.refresh(buffer.clone(), cursor, true, cx);
+        });
+        cx.executor().advance_clock(DEBOUNCE_TIMEOUT);
+        cx.run_until_parked();
+
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 1);
+        let (uri, body) = &requests[0];
+        assert_eq!(uri, "http://localhost:11434/api/generate");
+        assert_eq!(body["model"], "codellama:7b-code");
+        assert_eq!(body["raw"], true);
+        assert_eq!(
+            body["prompt"],
+            "<PRE> fn add(a: i32, b: i32) -> i32 {\n    a +  <SUF>\n}\n <MID>"
+        );
+
+        let prediction = provider.update(cx, |provider, cx| provider.suggest(&buffer, cursor, cx));
+        let Some(EditPrediction::Local { edits, .. }) = prediction else {
+            panic!("expected a local edit prediction");
+        };
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].1.as_ref(), "b");
+    }
+
+    #[gpui::test]
+    async fn test_open_ai_compatible_fim_request(cx: &mut TestAppContext) {
+        init_test(cx, |settings| {
+            settings.api = Some(LocalEditPredictionApi::OpenAiCompatible);
+            settings.api_url = Some("http://localhost:1234/v1/".into());
+            settings.model = Some("starcoder2-3b".into());
+        });
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    requests
+                        .lock()
+                        .unwrap()
+                        .push((uri, serde_json::from_str::<serde_json::Value>(&body)?));
+                    Ok(Response::builder()
+                        .status(200)
+                        .body(r#"{"choices": [{"text": "world"}]}"#.into())
+                        .unwrap())
+                }
+            }
+        });
+
+        let buffer = cx.new(|cx| Buffer::local("print(\"hello \")\n", cx));
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_before(Point::new(0, 13)));
+        let provider = cx.new(|cx| LocalCompletionProvider::new(http_client, None, cx));
+        provider.update(cx, |provider, cx| {
+            provider.refresh(buffer.clone(), cursor, false, cx)
+        });
+        cx.run_until_parked();
+
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 1);
+        let (uri, body) = &requests[0];
+        assert_eq!(uri, "http://localhost:1234/v1/completions");
+        assert_eq!(
+            body["prompt"],
+            "<fim_prefix>print(\"hello <fim_suffix>\")\n<fim_middle>"
+        );
+
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(cursor..cursor, "wo")], None, cx)
+        });
+        let prediction = provider.update(cx, |provider, cx| provider.suggest(&buffer, cursor, cx));
+        let Some(EditPrediction::Local { edits, .. }) = prediction else {
+            panic!("expected a local edit prediction");
+        };
+        assert_eq!(edits[0].1.as_ref(), "rld");
+    }
+
+    #[gpui::test]
+    async fn test_retrieved_declarations_in_prefix(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "math.rs": "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+                "main.rs": "fn main() {\n    let sum = add(1, 2);\n    \n}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+        project.read_with(cx, |project, _| {
+            project.languages().add(Arc::new(rust_lang()))
+        });
+        let buffer = project
+            .update(cx, |project, cx| {
+                let project_path = project.find_project_path("main.rs", cx).unwrap();
+                project.open_buffer(project_path, cx)
+            })
+            .await
+            .unwrap();
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    requests
+                        .lock()
+                        .unwrap()
+                        .push(serde_json::from_str::<serde_json::Value>(&body)?);
+                    Ok(Response::builder()
+                        .status(200)
+                        .body(r#"{"response": "sum"}"#.into())
+                        .unwrap())
+                }
+            }
+        });
+        let provider = cx.new(|cx| LocalCompletionProvider::new(http_client, Some(&project), cx));
+        // Let the project finish indexing.
+        cx.run_until_parked();
+
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_before(Point::new(2, 4)));
+        provider.update(cx, |provider, cx| {
+            provider.refresh(buffer.clone(), cursor, false, cx)
+        });
+        cx.run_until_parked();
+
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0]["prompt"],
+            concat!(
+                "<|fim_prefix|>// math.rs\n",
+                "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\n",
+                "fn main() {\n    let sum = add(1, 2);\n    ",
+                "<|fim_suffix|>\n}\n<|fim_middle|>",
+            )
+        );
+    }
+
+    fn rust_lang() -> Language {
+        Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                line_comments: vec!["// ".into()],
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_highlights_query(include_str!("../../languages/src/rust/highlights.scm"))
+        .unwrap()
+        .with_outline_query(include_str!("../../languages/src/rust/outline.scm"))
+        .unwrap()
+    }
+
+    fn init_test(cx: &mut TestAppContext, f: impl FnOnce(&mut LocalEditPredictionSettingsContent)) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            SettingsStore::update_global(cx, |store: &mut SettingsStore, cx| {
+                store.update_user_settings(cx, |settings| {
+                    f(settings
+                        .project
+                        .all_languages
+                        .edit_predictions
+                        .get_or_insert_default()
+                        .local
+                        .get_or_insert_default())
+                });
+            });
+        });
+    }
+}
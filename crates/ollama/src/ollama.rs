@@ -137,6 +137,9 @@ pub struct ChatRequest {
     pub options: Option<ChatOptions>,
     pub tools: Vec<OllamaTool>,
     pub think: Option<bool>,
+    /// A JSON schema the response must conform to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<Value>,
 }
 
 // https://github.com/ollama/ollama/blob/main/docs/modelfile.md#valid-parameters-and-values
@@ -607,6 +610,7 @@ mod tests {
             options: None,
             think: None,
             tools: vec![],
+            format: None,
         };
 
         let serialized = serde_json::to_string(&request).unwrap();
@@ -627,6 +631,7 @@ mod tests {
             options: None,
             think: None,
             tools: vec![],
+            format: None,
         };
 
         let serialized = serde_json::to_string(&request).unwrap();
@@ -648,6 +653,7 @@ mod tests {
             options: None,
             think: None,
             tools: vec![],
+            format: None,
         };
 
         let serialized = serde_json::to_string(&request).unwrap();
@@ -242,6 +242,29 @@ impl Model {
         }
     }
 
+    /// Returns whether the given model supports `json_schema` response formats.
+    ///
+    /// Older chat models only accept `json_object` and reject the request.
+    pub fn supports_response_format(&self) -> bool {
+        match self {
+            Self::FourOmni
+            | Self::FourOmniMini
+            | Self::FourPointOne
+            | Self::FourPointOneMini
+            | Self::FourPointOneNano
+            | Self::Five
+            | Self::FiveMini
+            | Self::FiveNano
+            | Self::O1
+            | Self::O3
+            | Self::O3Mini
+            | Self::O4Mini => true,
+            Self::ThreePointFiveTurbo | Self::Four | Self::FourTurbo | Model::Custom { .. } => {
+                false
+            }
+        }
+    }
+
     /// Returns whether the given model supports the `prompt_cache_key` parameter.
     ///
     /// If the model does not support the parameter, do not pass it up.
@@ -271,6 +294,20 @@ pub struct Request {
     pub prompt_cache_key: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reasoning_effort: Option<ReasoningEffort>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    JsonSchema { json_schema: JsonSchemaFormat },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    pub schema: Value,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
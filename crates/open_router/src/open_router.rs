@@ -79,6 +79,20 @@ pub struct Model {
     #[serde(default)]
     pub mode: ModelMode,
     pub provider: Option<Provider>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
+}
+
+/// What OpenRouter charges for a model, in US dollars per token.
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ModelPricing {
+    pub prompt: String,
+    pub completion: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cache_read: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cache_write: Option<String>,
 }
 
 impl Model {
@@ -115,6 +129,7 @@ impl Model {
             supports_images,
             mode: mode.unwrap_or(ModelMode::Default),
             provider,
+            pricing: None,
         }
     }
 
@@ -431,6 +446,8 @@ pub struct ModelEntry {
     pub supported_parameters: Vec<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub architecture: Option<ModelArchitecture>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Deserialize)]
@@ -605,6 +622,7 @@ pub async fn list_models(
                     ModelMode::Default
                 },
                 provider: None,
+                pricing: entry.pricing,
             })
             .collect();
 
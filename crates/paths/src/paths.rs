@@ -319,6 +319,14 @@ pub fn prompts_dir() -> &'static PathBuf {
     })
 }
 
+/// Returns the path to the agent worktrees directory.
+///
+/// This is where git worktrees created for agent threads are checked out.
+pub fn agent_worktrees_dir() -> &'static PathBuf {
+    static AGENT_WORKTREES_DIR: OnceLock<PathBuf> = OnceLock::new();
+    AGENT_WORKTREES_DIR.get_or_init(|| data_dir().join("agent_worktrees"))
+}
+
 /// Returns the path to the prompt templates directory.
 ///
 /// This is where the prompt templates for core features can be overridden with templates.
@@ -1,5 +1,6 @@
 use crate::{
     ProjectPath,
+    file_history::{FILE_HISTORY, FileHistorySettings},
     lsp_store::OpenLspBufferHandle,
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
 };
@@ -22,7 +23,12 @@ use rpc::{
     proto::{self},
 };
 
-use std::{io, sync::Arc, time::Instant};
+use settings::Settings as _;
+use std::{
+    io,
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
 use text::{BufferId, ReplicaId};
 use util::{ResultExt as _, TryFutureExt, debug_panic, maybe, paths::PathStyle, rel_path::RelPath};
 use worktree::{File, PathChange, ProjectEntryId, Worktree, WorktreeId};
@@ -376,6 +382,7 @@ impl LocalBufferStore {
 
         let text = buffer.as_rope().clone();
         let line_ending = buffer.line_ending();
+        let encoding = buffer.encoding();
         let version = buffer.version();
         let buffer_id = buffer.remote_id();
         let file = buffer.file().cloned();
@@ -386,12 +393,85 @@ impl LocalBufferStore {
             has_changed_file = true;
         }
 
-        let save = worktree.update(cx, |worktree, cx| {
-            worktree.write_file(path, text, line_ending, cx)
+        let history_settings = *FileHistorySettings::get_global(cx);
+        let history = (history_settings.enabled && text.len() <= history_settings.max_file_size())
+            .then(|| (worktree.read(cx).absolutize(&path), text.clone()));
+
+        // The contents that a save overwrites are recorded too, so that a save
+        // that overwrote changes made outside of Zed can be undone. Unless the
+        // file changed on disk since the buffer was loaded or saved, those are
+        // the buffer's saved contents and don't need to be read back.
+        let was_on_disk = file.as_ref().is_some_and(|file| file.disk_state().exists());
+        let changed_on_disk = was_on_disk
+            && worktree
+                .read(cx)
+                .entry_for_path(&path)
+                .and_then(|entry| entry.mtime)
+                != buffer.saved_mtime();
+        let saved_contents = (history.is_some() && was_on_disk && !changed_on_disk)
+            .then(|| (buffer.text_snapshot(), buffer.saved_version().clone()));
+        let overwritten_on_disk =
+            history
+                .as_ref()
+                .filter(|_| changed_on_disk)
+                .and_then(|(abs_path, _)| {
+                    let fs = worktree.read(cx).as_local()?.fs().clone();
+                    Some((fs, abs_path.clone()))
+                });
+
+        let save = overwritten_on_disk.is_none().then(|| {
+            worktree.update(cx, |worktree, cx| {
+                worktree.write_file(path.clone(), text.clone(), line_ending, encoding, cx)
+            })
         });
 
         cx.spawn(async move |this, cx| {
+            let mut previous_contents = None;
+            if let Some((fs, abs_path)) = overwritten_on_disk
+                && fs
+                    .metadata(&abs_path)
+                    .await
+                    .ok()
+                    .flatten()
+                    .is_some_and(|metadata| {
+                        metadata.len as usize <= history_settings.max_file_size()
+                    })
+            {
+                previous_contents = fs
+                    .load_bytes(&abs_path)
+                    .await
+                    .ok()
+                    .and_then(|bytes| encoding.decode(bytes).ok());
+            }
+
+            let save = match save {
+                Some(save) => save,
+                None => worktree.update(cx, |worktree, cx| {
+                    worktree.write_file(path, text, line_ending, encoding, cx)
+                })?,
+            };
             let new_file = save.await?;
+            if let Some((abs_path, text)) = history {
+                cx.background_spawn(async move {
+                    let previous_contents = previous_contents.or_else(|| {
+                        let (snapshot, version) = saved_contents?;
+                        let contents = snapshot.rope_for_version(&version);
+                        (contents.len() <= history_settings.max_file_size())
+                            .then(|| contents.to_string())
+                    });
+                    FILE_HISTORY
+                        .record_snapshot(
+                            abs_path,
+                            previous_contents,
+                            text.to_string(),
+                            SystemTime::now(),
+                            history_settings.retention(),
+                        )
+                        .await
+                        .log_err();
+                })
+                .detach();
+            }
             let mtime = new_file.disk_state().mtime();
             this.update(cx, |this, cx| {
                 if let Some((downstream_client, project_id)) = this.downstream_client.clone() {
@@ -633,6 +713,7 @@ impl LocalBufferStore {
                         .await;
                     cx.insert_entity(reservation, |_| {
                         Buffer::build(text_buffer, Some(loaded.file), Capability::ReadWrite)
+                            .with_encoding(loaded.encoding)
                     })?
                 }
                 Err(error) if is_not_found_error(&error) => cx.new(|cx| {
@@ -5,7 +5,14 @@ use std::sync::Arc;
 
 use anyhow::{Context as _, Result};
 use collections::{HashMap, HashSet};
-use context_server::{ContextServer, ContextServerCommand, ContextServerId};
+use context_server::oauth::{AuthorizationRequired, OAuthFlow, OAuthSession};
+use context_server::protocol::InitializedContextServerProtocol;
+use context_server::types::notifications::ResourcesUpdated;
+use context_server::types::{Notification as _, ResourcesUpdatedParams};
+use context_server::{
+    ContextServer, ContextServerCommand, ContextServerId, ElicitationHandler, SamplingHandler,
+};
+use credentials_provider::CredentialsProvider;
 use futures::{FutureExt as _, future::join_all};
 use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity, actions};
 use registry::ContextServerDescriptorRegistry;
@@ -15,7 +22,7 @@ use util::{ResultExt as _, rel_path::RelPath};
 use crate::{
     Project,
     project_settings::{ContextServerSettings, ProjectSettings},
-    worktree_store::WorktreeStore,
+    worktree_store::{WorktreeStore, WorktreeStoreEvent},
 };
 
 pub fn init(cx: &mut App) {
@@ -35,6 +42,8 @@ pub enum ContextServerStatus {
     Starting,
     Running,
     Stopped,
+    /// The server rejected the client and the user needs to sign in.
+    AuthenticationRequired,
     Error(Arc<str>),
 }
 
@@ -44,6 +53,9 @@ impl ContextServerStatus {
             ContextServerState::Starting { .. } => ContextServerStatus::Starting,
             ContextServerState::Running { .. } => ContextServerStatus::Running,
             ContextServerState::Stopped { .. } => ContextServerStatus::Stopped,
+            ContextServerState::AuthenticationRequired { .. } => {
+                ContextServerStatus::AuthenticationRequired
+            }
             ContextServerState::Error { error, .. } => ContextServerStatus::Error(error.clone()),
         }
     }
@@ -63,6 +75,11 @@ enum ContextServerState {
         server: Arc<ContextServer>,
         configuration: Arc<ContextServerConfiguration>,
     },
+    AuthenticationRequired {
+        server: Arc<ContextServer>,
+        configuration: Arc<ContextServerConfiguration>,
+        www_authenticate: Option<String>,
+    },
     Error {
         server: Arc<ContextServer>,
         configuration: Arc<ContextServerConfiguration>,
@@ -76,6 +93,7 @@ impl ContextServerState {
             ContextServerState::Starting { server, .. } => server.clone(),
             ContextServerState::Running { server, .. } => server.clone(),
             ContextServerState::Stopped { server, .. } => server.clone(),
+            ContextServerState::AuthenticationRequired { server, .. } => server.clone(),
             ContextServerState::Error { server, .. } => server.clone(),
         }
     }
@@ -85,6 +103,9 @@ impl ContextServerState {
             ContextServerState::Starting { configuration, .. } => configuration.clone(),
             ContextServerState::Running { configuration, .. } => configuration.clone(),
             ContextServerState::Stopped { configuration, .. } => configuration.clone(),
+            ContextServerState::AuthenticationRequired { configuration, .. } => {
+                configuration.clone()
+            }
             ContextServerState::Error { configuration, .. } => configuration.clone(),
         }
     }
@@ -170,6 +191,9 @@ pub struct ContextServerStore {
     registry: Entity<ContextServerDescriptorRegistry>,
     update_servers_task: Option<Task<Result<()>>>,
     context_server_factory: Option<ContextServerFactory>,
+    sampling_handler: Option<SamplingHandler>,
+    elicitation_handler: Option<ElicitationHandler>,
+    oauth_persistence_tasks: HashMap<ContextServerId, Task<()>>,
     needs_server_update: bool,
     _subscriptions: Vec<Subscription>,
 }
@@ -179,6 +203,11 @@ pub enum Event {
         server_id: ContextServerId,
         status: ContextServerStatus,
     },
+    /// A resource the client subscribed to via `resources/subscribe` changed.
+    ResourceUpdated {
+        server_id: ContextServerId,
+        uri: String,
+    },
 }
 
 impl EventEmitter<Event> for ContextServerStore {}
@@ -244,7 +273,7 @@ impl ContextServerStore {
         weak_project: WeakEntity<Project>,
         cx: &mut Context<Self>,
     ) -> Self {
-        let subscriptions = if maintain_server_loop {
+        let mut subscriptions = if maintain_server_loop {
             vec![
                 cx.observe(&registry, |this, _registry, cx| {
                     this.available_context_servers_changed(cx);
@@ -261,6 +290,16 @@ impl ContextServerStore {
         } else {
             Vec::new()
         };
+        subscriptions.push(cx.subscribe(&worktree_store, |this, _, event, cx| {
+            if matches!(
+                event,
+                WorktreeStoreEvent::WorktreeAdded(_)
+                    | WorktreeStoreEvent::WorktreeRemoved(..)
+                    | WorktreeStoreEvent::WorktreeOrderChanged
+            ) {
+                this.update_roots(cx);
+            }
+        }));
 
         let mut this = Self {
             _subscriptions: subscriptions,
@@ -273,6 +312,9 @@ impl ContextServerStore {
             servers: HashMap::default(),
             update_servers_task: None,
             context_server_factory,
+            sampling_handler: None,
+            elicitation_handler: None,
+            oauth_persistence_tasks: HashMap::default(),
         };
         if maintain_server_loop {
             this.available_context_servers_changed(cx);
@@ -330,6 +372,85 @@ impl ContextServerStore {
             .collect()
     }
 
+    pub fn has_sampling_handler(&self) -> bool {
+        self.sampling_handler.is_some()
+    }
+
+    /// Sets the handler that answers sampling requests from context servers.
+    ///
+    /// When sampling becomes available, servers that are already running are
+    /// restarted so that they see the updated client capabilities.
+    pub fn set_sampling_handler(
+        &mut self,
+        handler: Option<SamplingHandler>,
+        cx: &mut Context<Self>,
+    ) {
+        let became_available = self.sampling_handler.is_none() && handler.is_some();
+        self.sampling_handler = handler;
+        if became_available {
+            self.restart_running_servers(cx);
+        }
+    }
+
+    pub fn has_elicitation_handler(&self) -> bool {
+        self.elicitation_handler.is_some()
+    }
+
+    /// Sets the handler that answers elicitation requests from context servers.
+    ///
+    /// As with sampling, running servers are restarted when it becomes
+    /// available.
+    pub fn set_elicitation_handler(
+        &mut self,
+        handler: Option<ElicitationHandler>,
+        cx: &mut Context<Self>,
+    ) {
+        let became_available = self.elicitation_handler.is_none() && handler.is_some();
+        self.elicitation_handler = handler;
+        if became_available {
+            self.restart_running_servers(cx);
+        }
+    }
+
+    fn restart_running_servers(&mut self, cx: &mut Context<Self>) {
+        let servers_to_restart = self
+            .servers
+            .values()
+            .filter(|state| {
+                matches!(
+                    state,
+                    ContextServerState::Starting { .. } | ContextServerState::Running { .. }
+                )
+            })
+            .map(|state| (state.server(), state.configuration()))
+            .collect::<Vec<_>>();
+        for (server, configuration) in servers_to_restart {
+            self.run_server(server, configuration, cx);
+        }
+    }
+
+    /// The visible worktrees of the project, reported to servers as roots.
+    fn roots(&self, cx: &App) -> Vec<context_server::types::Root> {
+        self.worktree_store
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter_map(|worktree| {
+                let worktree = worktree.read(cx);
+                Some(context_server::types::Root {
+                    uri: url::Url::from_file_path(worktree.abs_path()).ok()?,
+                    name: Some(worktree.root_name_str().to_string()),
+                })
+            })
+            .collect()
+    }
+
+    fn update_roots(&mut self, cx: &mut Context<Self>) {
+        let roots = self.roots(cx);
+        for state in self.servers.values() {
+            state.server().set_roots(roots.clone());
+        }
+    }
+
     pub fn start_server(&mut self, server: Arc<ContextServer>, cx: &mut Context<Self>) {
         cx.spawn(async move |this, cx| {
             let this = this.upgrade().context("Context server store dropped")?;
@@ -365,6 +486,47 @@ impl ContextServerStore {
         .detach_and_log_err(cx);
     }
 
+    /// Signs in to a server that requires authentication by opening the
+    /// authorization server in the browser, then restarts the server.
+    pub fn authenticate_server(
+        &mut self,
+        id: &ContextServerId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some(ContextServerState::AuthenticationRequired {
+            server,
+            configuration,
+            www_authenticate,
+        }) = self.servers.get(id)
+        else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "context server {id} does not require authentication"
+            )));
+        };
+        let (Some(oauth), ContextServerConfiguration::Http { url, .. }) =
+            (server.oauth(), configuration.as_ref())
+        else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "context server {id} does not support OAuth"
+            )));
+        };
+
+        let server = server.clone();
+        let configuration = configuration.clone();
+        let url = url.clone();
+        let www_authenticate = www_authenticate.clone();
+        let http_client = cx.http_client();
+        cx.spawn(async move |this, cx| {
+            let flow = OAuthFlow::start(http_client, &url, www_authenticate.as_deref()).await?;
+            cx.update(|cx| cx.open_url(flow.authorization_url().as_str()))?;
+            let session = flow.finish().await?;
+
+            write_oauth_session(&configuration, &session, cx).await?;
+            oauth.set_session(Some(session));
+            this.update(cx, |this, cx| this.run_server(server, configuration, cx))
+        })
+    }
+
     pub fn stop_server(&mut self, id: &ContextServerId, cx: &mut Context<Self>) -> Result<()> {
         if matches!(
             self.servers.get(id),
@@ -377,6 +539,7 @@ impl ContextServerStore {
             .servers
             .remove(id)
             .context("Context server not found")?;
+        self.oauth_persistence_tasks.remove(id);
 
         let server = state.server();
         let configuration = state.configuration();
@@ -411,15 +574,44 @@ impl ContextServerStore {
         ) {
             self.stop_server(&id, cx).log_err();
         }
+        server.set_sampling_handler(self.sampling_handler.clone());
+        server.set_elicitation_handler(self.elicitation_handler.clone());
+        server.set_roots(self.roots(cx));
+        if let Some(oauth) = server.oauth() {
+            self.oauth_persistence_tasks.insert(
+                id.clone(),
+                cx.spawn({
+                    let configuration = configuration.clone();
+                    async move |_, cx| {
+                        let refreshed_sessions = oauth.refreshed_sessions();
+                        while let Ok(session) = refreshed_sessions.recv().await {
+                            write_oauth_session(&configuration, &session, cx)
+                                .await
+                                .log_err();
+                        }
+                    }
+                }),
+            );
+        }
 
         let task = cx.spawn({
             let id = server.id();
             let server = server.clone();
             let configuration = configuration.clone();
             async move |this, cx| {
+                if let Some(oauth) = server.oauth()
+                    && oauth.session().is_none()
+                {
+                    let session = read_oauth_session(&configuration, cx).await.log_err();
+                    oauth.set_session(session.flatten());
+                }
+
                 match server.clone().start(cx).await {
                     Ok(_) => {
                         debug_assert!(server.client().is_some());
+                        if let Some(client) = server.client() {
+                            forward_resource_updates(&client, id.clone(), this.clone());
+                        }
 
                         this.update(cx, |this, cx| {
                             this.update_server_state(
@@ -433,6 +625,25 @@ impl ContextServerStore {
                         })
                         .log_err()
                     }
+                    Err(err) if err.is::<AuthorizationRequired>() => {
+                        log::info!("{} context server requires authentication", id);
+                        let www_authenticate = err
+                            .downcast::<AuthorizationRequired>()
+                            .ok()
+                            .and_then(|challenge| challenge.www_authenticate);
+                        this.update(cx, |this, cx| {
+                            this.update_server_state(
+                                id.clone(),
+                                ContextServerState::AuthenticationRequired {
+                                    configuration,
+                                    server,
+                                    www_authenticate,
+                                },
+                                cx,
+                            )
+                        })
+                        .log_err()
+                    }
                     Err(err) => {
                         log::error!("{} context server failed to start: {}", id, err);
                         this.update(cx, |this, cx| {
@@ -658,6 +869,67 @@ impl ContextServerStore {
     }
 }
 
+fn forward_resource_updates(
+    client: &InitializedContextServerProtocol,
+    server_id: ContextServerId,
+    this: WeakEntity<ContextServerStore>,
+) {
+    client.on_notification(
+        ResourcesUpdated::METHOD,
+        Box::new(move |params, cx| {
+            let Some(params) = serde_json::from_value::<ResourcesUpdatedParams>(params).log_err()
+            else {
+                return;
+            };
+            let server_id = server_id.clone();
+            let this = this.clone();
+            // Notification handlers run while the client's handler map is
+            // locked, so emit the event outside of it.
+            cx.spawn(async move |cx| {
+                this.update(cx, |_, cx| {
+                    cx.emit(Event::ResourceUpdated {
+                        server_id,
+                        uri: params.uri,
+                    })
+                })
+                .ok();
+            })
+            .detach();
+        }),
+    );
+}
+
+fn oauth_credentials_url(configuration: &ContextServerConfiguration) -> Result<String> {
+    match configuration {
+        ContextServerConfiguration::Http { url, .. } => Ok(url.to_string()),
+        _ => anyhow::bail!("only HTTP context servers support OAuth"),
+    }
+}
+
+async fn read_oauth_session(
+    configuration: &ContextServerConfiguration,
+    cx: &AsyncApp,
+) -> Result<Option<OAuthSession>> {
+    let url = oauth_credentials_url(configuration)?;
+    let credentials_provider = cx.update(|cx| <dyn CredentialsProvider>::global(cx))?;
+    let Some((_, session)) = credentials_provider.read_credentials(&url, cx).await? else {
+        return Ok(None);
+    };
+    Ok(Some(serde_json::from_slice(&session)?))
+}
+
+async fn write_oauth_session(
+    configuration: &ContextServerConfiguration,
+    session: &OAuthSession,
+    cx: &AsyncApp,
+) -> Result<()> {
+    let url = oauth_credentials_url(configuration)?;
+    let credentials_provider = cx.update(|cx| <dyn CredentialsProvider>::global(cx))?;
+    credentials_provider
+        .write_credentials(&url, "oauth", &serde_json::to_vec(session)?, cx)
+        .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -665,11 +937,19 @@ mod tests {
         FakeFs, Project, context_server_store::registry::ContextServerDescriptor,
         project_settings::ProjectSettings,
     };
+    use context_server::oauth::{AuthorizationServerMetadata, ClientRegistration, OAuthTokens};
     use context_server::test::create_fake_transport;
+    use futures::AsyncReadExt as _;
     use gpui::{AppContext, TestAppContext, UpdateGlobal as _};
     use http_client::{FakeHttpClient, Response};
     use serde_json::json;
-    use std::{cell::RefCell, path::PathBuf, rc::Rc};
+    use std::{
+        cell::RefCell,
+        path::PathBuf,
+        rc::Rc,
+        sync::atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst},
+    };
+    use url::Url;
     use util::path;
 
     #[gpui::test]
@@ -752,6 +1032,172 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_context_server_sampling_capability(cx: &mut TestAppContext) {
+        const SERVER_ID: &str = "mcp-1";
+
+        let (_fs, project) = setup_context_server_test(
+            cx,
+            json!({"code.rs": ""}),
+            vec![(SERVER_ID.into(), dummy_server_settings())],
+        )
+        .await;
+
+        let registry = cx.new(|_| ContextServerDescriptorRegistry::new());
+        let store = cx.new(|cx| {
+            ContextServerStore::test(
+                registry.clone(),
+                project.read(cx).worktree_store(),
+                project.downgrade(),
+                cx,
+            )
+        });
+
+        let advertised_sampling = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let transport = context_server::test::FakeTransport::new(cx.executor())
+            .on_request::<context_server::types::requests::Initialize, _>({
+            let advertised_sampling = advertised_sampling.clone();
+            move |params| {
+                advertised_sampling
+                    .lock()
+                    .unwrap()
+                    .push(params.capabilities.sampling.is_some());
+                async move {
+                    context_server::types::InitializeResponse {
+                        protocol_version: context_server::types::ProtocolVersion(
+                            context_server::types::LATEST_PROTOCOL_VERSION.to_string(),
+                        ),
+                        server_info: context_server::types::Implementation {
+                            name: SERVER_ID.to_string(),
+                            version: "1.0.0".to_string(),
+                        },
+                        capabilities: Default::default(),
+                        meta: None,
+                    }
+                }
+            }
+        });
+        let server = Arc::new(ContextServer::new(
+            ContextServerId(SERVER_ID.into()),
+            Arc::new(transport),
+        ));
+
+        store.update(cx, |store, cx| store.start_server(server, cx));
+        cx.run_until_parked();
+        assert_eq!(*advertised_sampling.lock().unwrap(), vec![false]);
+
+        // Setting a handler restarts the running server so that the new
+        // capability is advertised.
+        store.update(cx, |store, cx| {
+            store.set_sampling_handler(
+                Some(Arc::new(|_, _, _| {
+                    Task::ready(Err(anyhow::anyhow!("sampling is not supported in tests")))
+                })),
+                cx,
+            )
+        });
+        cx.run_until_parked();
+        assert_eq!(*advertised_sampling.lock().unwrap(), vec![false, true]);
+        cx.update(|cx| {
+            assert_eq!(
+                store
+                    .read(cx)
+                    .status_for_server(&ContextServerId(SERVER_ID.into())),
+                Some(ContextServerStatus::Running)
+            );
+        });
+
+        // Replacing the handler doesn't change the capabilities, so the server
+        // keeps running.
+        store.update(cx, |store, cx| {
+            store.set_sampling_handler(
+                Some(Arc::new(|_, _, _| {
+                    Task::ready(Err(anyhow::anyhow!("sampling is not supported in tests")))
+                })),
+                cx,
+            )
+        });
+        cx.run_until_parked();
+        assert_eq!(*advertised_sampling.lock().unwrap(), vec![false, true]);
+    }
+
+    #[gpui::test]
+    async fn test_context_server_roots(cx: &mut TestAppContext) {
+        use context_server::types::notifications::RootsListChanged;
+
+        const SERVER_ID: &str = "mcp-1";
+
+        let (fs, project) = setup_context_server_test(
+            cx,
+            json!({"code.rs": ""}),
+            vec![(SERVER_ID.into(), dummy_server_settings())],
+        )
+        .await;
+        fs.insert_tree(path!("/other"), json!({"lib.rs": ""})).await;
+
+        let registry = cx.new(|_| ContextServerDescriptorRegistry::new());
+        let store = cx.new(|cx| {
+            ContextServerStore::test(
+                registry.clone(),
+                project.read(cx).worktree_store(),
+                project.downgrade(),
+                cx,
+            )
+        });
+
+        let advertised_roots = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let roots_changed = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let transport = context_server::test::FakeTransport::new(cx.executor());
+        let transport = transport.on_notification::<RootsListChanged>({
+            let roots_changed = roots_changed.clone();
+            move |_| {
+                roots_changed.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            }
+        });
+        let transport = transport.on_request::<context_server::types::requests::Initialize, _>({
+            let advertised_roots = advertised_roots.clone();
+            move |params| {
+                advertised_roots.lock().unwrap().push(
+                    params
+                        .capabilities
+                        .roots
+                        .and_then(|roots| roots.list_changed),
+                );
+                async move {
+                    context_server::types::InitializeResponse {
+                        protocol_version: context_server::types::ProtocolVersion(
+                            context_server::types::LATEST_PROTOCOL_VERSION.to_string(),
+                        ),
+                        server_info: context_server::types::Implementation {
+                            name: SERVER_ID.to_string(),
+                            version: "1.0.0".to_string(),
+                        },
+                        capabilities: Default::default(),
+                        meta: None,
+                    }
+                }
+            }
+        });
+        let server = Arc::new(ContextServer::new(
+            ContextServerId(SERVER_ID.into()),
+            Arc::new(transport),
+        ));
+
+        store.update(cx, |store, cx| store.start_server(server, cx));
+        cx.run_until_parked();
+        assert_eq!(*advertised_roots.lock().unwrap(), vec![Some(true)]);
+        assert_eq!(roots_changed.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        project
+            .update(cx, |project, cx| {
+                project.find_or_create_worktree(path!("/other"), true, cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(roots_changed.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
     #[gpui::test]
     async fn test_context_server_status_events(cx: &mut TestAppContext) {
         const SERVER_1_ID: &str = "mcp-1";
@@ -1309,6 +1755,170 @@ mod tests {
         cx.run_until_parked();
     }
 
+    #[gpui::test]
+    async fn test_remote_context_server_oauth(cx: &mut TestAppContext) {
+        const SERVER_ID: &str = "oauth-server";
+        const WWW_AUTHENTICATE: &str = r#"Bearer resource_metadata="https://mcp.example.com/.well-known/oauth-protected-resource/mcp""#;
+        let server_id = ContextServerId(SERVER_ID.into());
+        let server_url = "https://mcp.example.com/mcp";
+
+        let (_fs, project) = setup_context_server_test(
+            cx,
+            json!({ "code.rs": "" }),
+            vec![(
+                SERVER_ID.into(),
+                ContextServerSettings::Http {
+                    enabled: true,
+                    url: server_url.to_string(),
+                    headers: Default::default(),
+                },
+            )],
+        )
+        .await;
+
+        // The server only accepts the access token it issued last, and the
+        // refresh token is rotated whenever it's used.
+        let revoked = Arc::new(AtomicBool::new(false));
+        let token_requests = Arc::new(AtomicUsize::new(0));
+        let client = FakeHttpClient::create({
+            let revoked = revoked.clone();
+            let token_requests = token_requests.clone();
+            move |request| {
+                let revoked = revoked.load(SeqCst);
+                let token_requests = token_requests.clone();
+                async move {
+                    use http_client::AsyncBody;
+
+                    let uri = request.uri().to_string();
+                    let authorization = request
+                        .headers()
+                        .get("Authorization")
+                        .and_then(|value| value.to_str().ok())
+                        .map(ToString::to_string);
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+
+                    let response = if uri == "https://auth.example.com/token" {
+                        token_requests.fetch_add(1, SeqCst);
+                        if !revoked && body.contains("refresh_token=refresh-1") {
+                            Response::builder().status(200).body(AsyncBody::from(
+                                json!({
+                                    "access_token": "access-2",
+                                    "refresh_token": "refresh-2",
+                                })
+                                .to_string(),
+                            ))
+                        } else {
+                            Response::builder().status(400).body(AsyncBody::from(
+                                json!({ "error": "invalid_grant" }).to_string(),
+                            ))
+                        }
+                    } else if !revoked && authorization.as_deref() == Some("Bearer access-2") {
+                        Response::builder()
+                            .status(200)
+                            .header("Content-Type", "application/json")
+                            .body(AsyncBody::from(
+                                json!({
+                                    "jsonrpc": "2.0",
+                                    "id": 0,
+                                    "result": {
+                                        "protocolVersion": "2024-11-05",
+                                        "capabilities": {},
+                                        "serverInfo": {
+                                            "name": "test-server",
+                                            "version": "1.0.0"
+                                        }
+                                    }
+                                })
+                                .to_string(),
+                            ))
+                    } else {
+                        Response::builder()
+                            .status(401)
+                            .header("WWW-Authenticate", WWW_AUTHENTICATE)
+                            .body(AsyncBody::default())
+                    };
+                    Ok(response.unwrap())
+                }
+            }
+        });
+        cx.update(|cx| cx.set_http_client(client));
+
+        // A session from signing in earlier, whose access token the server no
+        // longer accepts.
+        let session = OAuthSession {
+            authorization_server: AuthorizationServerMetadata {
+                authorization_endpoint: Url::parse("https://auth.example.com/authorize").unwrap(),
+                token_endpoint: Url::parse("https://auth.example.com/token").unwrap(),
+                registration_endpoint: None,
+                code_challenge_methods_supported: Vec::new(),
+            },
+            client: ClientRegistration {
+                client_id: "zed-client".into(),
+                client_secret: None,
+            },
+            resource: server_url.into(),
+            tokens: OAuthTokens {
+                access_token: "access-1".into(),
+                refresh_token: Some("refresh-1".into()),
+                expires_at: None,
+            },
+        };
+        cx.update(|cx| {
+            cx.write_credentials(server_url, "oauth", &serde_json::to_vec(&session).unwrap())
+        })
+        .await
+        .unwrap();
+
+        let registry = cx.new(|_| ContextServerDescriptorRegistry::new());
+        let store = cx.new(|cx| {
+            ContextServerStore::test_maintain_server_loop(
+                None,
+                registry.clone(),
+                project.read(cx).worktree_store(),
+                project.downgrade(),
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        // The restored session was refreshed after the server rejected it,
+        // and the rotated tokens were persisted.
+        assert_eq!(
+            store.read_with(cx, |store, _| store.status_for_server(&server_id)),
+            Some(ContextServerStatus::Running)
+        );
+        assert_eq!(token_requests.load(SeqCst), 1);
+        let (_, persisted) = cx
+            .update(|cx| cx.read_credentials(server_url))
+            .await
+            .unwrap()
+            .unwrap();
+        let persisted: OAuthSession = serde_json::from_slice(&persisted).unwrap();
+        assert_eq!(persisted.tokens.access_token, "access-2");
+        assert_eq!(persisted.tokens.refresh_token.as_deref(), Some("refresh-2"));
+
+        // Once the tokens can't be refreshed anymore, the user has to sign in
+        // again.
+        revoked.store(true, SeqCst);
+        let server = store
+            .read_with(cx, |store, _| store.get_server(&server_id))
+            .unwrap();
+        store.update(cx, |store, cx| store.start_server(server, cx));
+        cx.run_until_parked();
+
+        assert_eq!(token_requests.load(SeqCst), 2);
+        store.read_with(cx, |store, _| {
+            let Some(ContextServerState::AuthenticationRequired {
+                www_authenticate, ..
+            }) = store.servers.get(&server_id)
+            else {
+                panic!("expected {server_id} to require authentication");
+            };
+            assert_eq!(www_authenticate.as_deref(), Some(WWW_AUTHENTICATE));
+        });
+    }
+
     struct ServerEvents {
         received_event_count: Rc<RefCell<usize>>,
         expected_event_count: usize,
@@ -0,0 +1,299 @@
+//! A local history of the contents of saved files, kept independently of
+//! version control so that earlier versions of a file can be compared with
+//! the current one and restored.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use db::{
+    sqlez::{connection::Connection, domain::Domain, thread_safe_connection::ThreadSafeConnection},
+    sqlez_macros::sql,
+};
+use settings::{RegisterSetting, Settings, SettingsContent};
+use sha2::{Digest, Sha256};
+
+#[derive(Clone, Copy, Debug, RegisterSetting)]
+pub struct FileHistorySettings {
+    pub enabled: bool,
+    pub max_file_size_kb: u64,
+    pub max_snapshots_per_file: usize,
+    pub max_age_days: u64,
+}
+
+impl FileHistorySettings {
+    pub fn max_file_size(&self) -> usize {
+        (self.max_file_size_kb as usize).saturating_mul(1024)
+    }
+
+    pub fn retention(&self) -> Retention {
+        Retention {
+            max_snapshots: self.max_snapshots_per_file.max(1),
+            max_age: Duration::from_secs(self.max_age_days.saturating_mul(24 * 60 * 60)),
+        }
+    }
+}
+
+impl Settings for FileHistorySettings {
+    fn from_settings(content: &SettingsContent) -> Self {
+        let file_history = content.file_history.as_ref().unwrap();
+        Self {
+            enabled: file_history.enabled.unwrap(),
+            max_file_size_kb: file_history.max_file_size_kb.unwrap(),
+            max_snapshots_per_file: file_history.max_snapshots_per_file.unwrap(),
+            max_age_days: file_history.max_age_days.unwrap(),
+        }
+    }
+}
+
+/// How many snapshots are kept for a file, and for how long.
+#[derive(Clone, Copy, Debug)]
+pub struct Retention {
+    pub max_snapshots: usize,
+    pub max_age: Duration,
+}
+
+/// A saved version of a file. Its contents are loaded separately, with
+/// [`FileHistoryDb::snapshot_contents`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileSnapshot {
+    pub id: i64,
+    pub created_at: SystemTime,
+    /// The length of the contents, in bytes.
+    pub len: usize,
+}
+
+pub struct FileHistoryDb(ThreadSafeConnection);
+
+impl Domain for FileHistoryDb {
+    const NAME: &str = stringify!(FileHistoryDb);
+
+    // Contents are stored once per distinct hash, so that reverting a file
+    // back and forth doesn't store the same contents over and over.
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE file_snapshot_contents (
+            hash BLOB PRIMARY KEY,
+            contents TEXT NOT NULL
+        ) STRICT;
+        CREATE TABLE file_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            abs_path BLOB NOT NULL,
+            hash BLOB NOT NULL,
+            created_at INTEGER NOT NULL,
+            len INTEGER NOT NULL
+        ) STRICT;
+        CREATE INDEX idx_file_snapshots_abs_path ON file_snapshots (abs_path, id);
+        CREATE INDEX idx_file_snapshots_created_at ON file_snapshots (created_at);
+        CREATE INDEX idx_file_snapshots_hash ON file_snapshots (hash);
+    )];
+}
+
+db::static_connection!(FILE_HISTORY, FileHistoryDb, []);
+
+impl FileHistoryDb {
+    /// Records a snapshot of the given file, unless its latest snapshot
+    /// already has the same contents, and then removes snapshots that are past
+    /// the retention limits. Returns whether a snapshot was added.
+    ///
+    /// `previous_contents` are the contents that the file had before they
+    /// were replaced with `contents`. They're recorded first when they aren't
+    /// the latest snapshot, such as when the file was changed outside of Zed,
+    /// so that what was overwritten can be restored.
+    pub async fn record_snapshot(
+        &self,
+        abs_path: PathBuf,
+        previous_contents: Option<String>,
+        contents: String,
+        created_at: SystemTime,
+        retention: Retention,
+    ) -> Result<bool> {
+        let created_at = unix_seconds(created_at);
+        let cutoff = created_at.saturating_sub(retention.max_age.as_secs() as i64);
+        self.write(move |conn| {
+            conn.with_savepoint("record_file_snapshot", || {
+                let added_previous = match previous_contents {
+                    Some(previous_contents) => {
+                        insert_snapshot(conn, &abs_path, &previous_contents, created_at)?
+                    }
+                    None => false,
+                };
+                let added = insert_snapshot(conn, &abs_path, &contents, created_at)?;
+                if !added && !added_previous {
+                    return Ok(false);
+                }
+
+                let mut released_hashes = conn.select_bound::<i64, [u8; 32]>(sql!(
+                    DELETE FROM file_snapshots WHERE created_at < ?
+                    RETURNING hash
+                ))?(cutoff)?;
+                released_hashes.extend(conn.select_bound::<(&Path, usize), [u8; 32]>(sql!(
+                    DELETE FROM file_snapshots
+                    WHERE abs_path = ?1 AND id NOT IN (
+                        SELECT id FROM file_snapshots
+                        WHERE abs_path = ?1
+                        ORDER BY id DESC
+                        LIMIT ?2
+                    )
+                    RETURNING hash
+                ))?((
+                    abs_path.as_path(),
+                    retention.max_snapshots,
+                ))?);
+                released_hashes.sort_unstable();
+                released_hashes.dedup();
+                for hash in released_hashes {
+                    conn.exec_bound::<&[u8; 32]>(sql!(
+                        DELETE FROM file_snapshot_contents
+                        WHERE hash = ?1
+                        AND NOT EXISTS (SELECT 1 FROM file_snapshots WHERE hash = ?1)
+                    ))?(&hash)?;
+                }
+                Ok(added)
+            })
+        })
+        .await
+    }
+
+    pub fn has_snapshots(&self, abs_path: &Path) -> Result<bool> {
+        Ok(self.select_row_bound::<&Path, i64>(sql!(
+            SELECT id FROM file_snapshots WHERE abs_path = ? LIMIT 1
+        ))?(abs_path)?
+        .is_some())
+    }
+
+    /// Returns the snapshots of the given file, newest first.
+    pub fn snapshots(&self, abs_path: &Path) -> Result<Vec<FileSnapshot>> {
+        let rows = self.select_bound::<&Path, (i64, i64, usize)>(sql!(
+            SELECT id, created_at, len FROM file_snapshots
+            WHERE abs_path = ?
+            ORDER BY id DESC
+        ))?(abs_path)?;
+        Ok(rows
+            .into_iter()
+            .map(|(id, created_at, len)| FileSnapshot {
+                id,
+                created_at: UNIX_EPOCH + Duration::from_secs(created_at.max(0) as u64),
+                len,
+            })
+            .collect())
+    }
+
+    pub fn snapshot_contents(&self, id: i64) -> Result<Option<String>> {
+        self.select_row_bound(sql!(
+            SELECT contents FROM file_snapshot_contents
+            WHERE hash = (SELECT hash FROM file_snapshots WHERE id = ?)
+        ))?(id)
+    }
+}
+
+/// Adds a snapshot of the given file unless its latest snapshot already has
+/// the same contents, and returns whether it did.
+fn insert_snapshot(
+    conn: &Connection,
+    abs_path: &Path,
+    contents: &str,
+    created_at: i64,
+) -> Result<bool> {
+    let hash: [u8; 32] = Sha256::digest(contents.as_bytes()).into();
+    let latest_hash = conn.select_row_bound::<&Path, [u8; 32]>(sql!(
+        SELECT hash FROM file_snapshots
+        WHERE abs_path = ?
+        ORDER BY id DESC
+        LIMIT 1
+    ))?(abs_path)?;
+    if latest_hash == Some(hash) {
+        return Ok(false);
+    }
+
+    conn.exec_bound::<(&[u8; 32], &str)>(sql!(
+        INSERT OR IGNORE INTO file_snapshot_contents (hash, contents)
+        VALUES (?, ?)
+    ))?((&hash, contents))?;
+    conn.exec_bound::<(&Path, &[u8; 32], i64, usize)>(sql!(
+        INSERT INTO file_snapshots (abs_path, hash, created_at, len)
+        VALUES (?, ?, ?, ?)
+    ))?((abs_path, &hash, created_at, contents.len()))?;
+    Ok(true)
+}
+
+fn unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_record_snapshots() {
+        let db = FileHistoryDb::open_test_db("test_record_snapshots").await;
+        let retention = Retention {
+            max_snapshots: 3,
+            max_age: Duration::from_secs(60 * 60),
+        };
+        let path = PathBuf::from("/root/a.txt");
+        let other_path = PathBuf::from("/root/b.txt");
+        let start = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let at = |minutes: u64| start + Duration::from_secs(minutes * 60);
+
+        assert!(!db.has_snapshots(&path).unwrap());
+        assert!(
+            db.record_snapshot(path.clone(), None, "one".into(), at(0), retention)
+                .await
+                .unwrap()
+        );
+        // The same contents as the latest snapshot aren't recorded again.
+        assert!(
+            !db.record_snapshot(path.clone(), None, "one".into(), at(1), retention)
+                .await
+                .unwrap()
+        );
+        assert!(
+            db.record_snapshot(path.clone(), None, "two".into(), at(2), retention)
+                .await
+                .unwrap()
+        );
+        assert!(
+            db.record_snapshot(other_path.clone(), None, "one".into(), at(2), retention)
+                .await
+                .unwrap()
+        );
+
+        let snapshots = db.snapshots(&path).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].created_at, at(2));
+        assert_eq!(
+            db.snapshot_contents(snapshots[0].id).unwrap().as_deref(),
+            Some("two")
+        );
+        assert_eq!(
+            db.snapshot_contents(snapshots[1].id).unwrap().as_deref(),
+            Some("one")
+        );
+
+        // Only the newest snapshots of a file are kept.
+        for (minute, contents) in [(3, "three"), (4, "four")] {
+            db.record_snapshot(path.clone(), None, contents.into(), at(minute), retention)
+                .await
+                .unwrap();
+        }
+        let contents = db
+            .snapshots(&path)
+            .unwrap()
+            .into_iter()
+            .map(|snapshot| db.snapshot_contents(snapshot.id).unwrap().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(contents, ["four", "three", "two"]);
+        assert_eq!(db.snapshots(&other_path).unwrap().len(), 1);
+
+        // Snapshots of every file are removed once they're too old.
+        db.record_snapshot(path.clone(), None, "five".into(), at(63), retention)
+            .await
+            .unwrap();
+        assert_eq!(db.snapshots(&path).unwrap().len(), 3);
+        assert!(!db.has_snapshots(&other_path).unwrap());
+    }
+}
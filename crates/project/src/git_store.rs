@@ -30,8 +30,8 @@ use git::{
     parse_git_remote_url,
     repository::{
         Branch, CommitDetails, CommitDiff, CommitFile, CommitOptions, DiffType, FetchOptions,
-        GitRepository, GitRepositoryCheckpoint, PushOptions, Remote, RemoteCommandOutput, RepoPath,
-        ResetMode, UpstreamTrackingStatus, Worktree as GitWorktree,
+        GitRepository, GitRepositoryCheckpoint, IndexStage, PushOptions, Remote,
+        RemoteCommandOutput, RepoPath, ResetMode, UpstreamTrackingStatus, Worktree as GitWorktree,
     },
     stash::{GitStash, StashEntry},
     status::{
@@ -183,6 +183,23 @@ pub struct GitStoreCheckpoint {
     checkpoints_by_work_dir_abs_path: HashMap<Arc<Path>, GitRepositoryCheckpoint>,
 }
 
+impl GitStoreCheckpoint {
+    /// Returns the checkpoint taken for the repository at the given working directory.
+    pub fn repository_checkpoint(
+        &self,
+        work_dir_abs_path: &Path,
+    ) -> Option<&GitRepositoryCheckpoint> {
+        self.checkpoints_by_work_dir_abs_path.get(work_dir_abs_path)
+    }
+
+    /// Returns the checkpoints taken for every repository, keyed by working directory.
+    pub fn repository_checkpoints(
+        &self,
+    ) -> impl Iterator<Item = (&Arc<Path>, &GitRepositoryCheckpoint)> {
+        self.checkpoints_by_work_dir_abs_path.iter()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StatusEntry {
     pub repo_path: RepoPath,
@@ -251,6 +268,16 @@ pub struct MergeDetails {
     pub heads: Vec<Option<SharedString>>,
 }
 
+/// The versions of a conflicted file that are being merged, as stored in the
+/// index. A version is missing if the file doesn't exist in it, e.g. when it
+/// was added on only one side.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConflictStages {
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RepositorySnapshot {
     pub id: RepositoryId,
@@ -445,6 +472,7 @@ impl GitStore {
         client.add_entity_request_handler(Self::handle_git_diff);
         client.add_entity_request_handler(Self::handle_tree_diff);
         client.add_entity_request_handler(Self::handle_get_blob_content);
+        client.add_entity_request_handler(Self::handle_load_conflict_stages);
         client.add_entity_request_handler(Self::handle_open_unstaged_diff);
         client.add_entity_request_handler(Self::handle_open_uncommitted_diff);
         client.add_entity_message_handler(Self::handle_update_diff_bases);
@@ -2463,6 +2491,26 @@ impl GitStore {
         Ok(proto::GetBlobContentResponse { content })
     }
 
+    async fn handle_load_conflict_stages(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::LoadConflictStages>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::LoadConflictStagesResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let repo_path = RepoPath::new(&envelope.payload.path)?;
+        let stages = repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.load_conflict_stages(repo_path, cx)
+            })?
+            .await?;
+        Ok(proto::LoadConflictStagesResponse {
+            base_text: stages.base,
+            ours_text: stages.ours,
+            theirs_text: stages.theirs,
+        })
+    }
+
     async fn handle_open_unstaged_diff(
         this: Entity<Self>,
         request: TypedEnvelope<proto::OpenUnstagedDiff>,
@@ -5355,6 +5403,46 @@ impl Repository {
         cx.spawn(|_: &mut AsyncApp| async move { rx.await? })
     }
 
+    /// Loads the versions of a conflicted file that are being merged.
+    pub fn load_conflict_stages(
+        &mut self,
+        repo_path: RepoPath,
+        cx: &App,
+    ) -> Task<Result<ConflictStages>> {
+        let repository_id = self.snapshot.id;
+        let rx = self.send_job(None, move |state, _| async move {
+            match state {
+                RepositoryState::Local { backend, .. } => {
+                    let base = backend
+                        .load_index_text_at_stage(repo_path.clone(), IndexStage::Base)
+                        .await;
+                    let ours = backend
+                        .load_index_text_at_stage(repo_path.clone(), IndexStage::Ours)
+                        .await;
+                    let theirs = backend
+                        .load_index_text_at_stage(repo_path, IndexStage::Theirs)
+                        .await;
+                    anyhow::Ok(ConflictStages { base, ours, theirs })
+                }
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::LoadConflictStages {
+                            project_id: project_id.to_proto(),
+                            repository_id: repository_id.to_proto(),
+                            path: repo_path.to_proto(),
+                        })
+                        .await?;
+                    Ok(ConflictStages {
+                        base: response.base_text,
+                        ours: response.ours_text,
+                        theirs: response.theirs_text,
+                    })
+                }
+            }
+        });
+        cx.spawn(|_: &mut AsyncApp| async move { rx.await? })
+    }
+
     fn paths_changed(
         &mut self,
         paths: Vec<RepoPath>,
@@ -5,6 +5,7 @@ pub mod connection_manager;
 pub mod context_server_store;
 pub mod debounced_delay;
 pub mod debugger;
+pub mod file_history;
 pub mod git_store;
 pub mod image_store;
 pub mod lsp_command;
@@ -413,6 +414,8 @@ pub enum InlayId {
     // LSP
     Hint(usize),
     Color(usize),
+    /// Whitespace inserted to line up text in columns.
+    Padding(usize),
 }
 
 impl InlayId {
@@ -422,6 +425,7 @@ impl InlayId {
             Self::DebuggerValue(id) => *id,
             Self::Hint(id) => *id,
             Self::Color(id) => *id,
+            Self::Padding(id) => *id,
         }
     }
 }
@@ -5325,7 +5329,13 @@ impl Project {
             worktree
                 .update(cx, |worktree, cx| {
                     let line_ending = text::LineEnding::detect(&new_text);
-                    worktree.write_file(rel_path.clone(), new_text.into(), line_ending, cx)
+                    worktree.write_file(
+                        rel_path.clone(),
+                        new_text.into(),
+                        line_ending,
+                        file.encoding,
+                        cx,
+                    )
                 })?
                 .await
                 .context("Failed to write settings file")?;
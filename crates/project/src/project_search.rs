@@ -326,6 +326,7 @@ impl Search {
                     Some(
                         Self::grab_buffer_snapshots(
                             grab_buffer_snapshot_rx,
+                            query.is_structural(),
                             find_all_matches_tx,
                             sorted_matches_tx,
                             cx.clone(),
@@ -488,6 +489,7 @@ impl Search {
 
     async fn grab_buffer_snapshots(
         rx: Receiver<Entity<Buffer>>,
+        wait_for_parsing: bool,
         find_all_matches_tx: Sender<(
             Entity<Buffer>,
             BufferSnapshot,
@@ -498,6 +500,13 @@ impl Search {
     ) {
         _ = maybe!(async move {
             while let Ok(buffer) = rx.recv().await {
+                // Structural queries search the syntax tree, which buffers that
+                // were just opened may not have yet.
+                if wait_for_parsing {
+                    buffer
+                        .read_with(&mut cx, |this, _| this.parsing_idle())?
+                        .await;
+                }
                 let snapshot = buffer.read_with(&mut cx, |this, _| this.snapshot())?;
                 let (tx, rx) = oneshot::channel();
                 find_all_matches_tx.send((buffer, snapshot, tx)).await?;
@@ -2,7 +2,8 @@
 
 use crate::{
     Event,
-    git_store::{GitStoreEvent, RepositoryEvent, StatusEntry, pending_op},
+    file_history::FILE_HISTORY,
+    git_store::{ConflictStages, GitStoreEvent, RepositoryEvent, StatusEntry, pending_op},
     task_inventory::TaskContexts,
     task_store::TaskSettingsLocation,
     *,
@@ -16,7 +17,7 @@ use fs::FakeFs;
 use futures::{StreamExt, future};
 use git::{
     GitHostingProviderRegistry,
-    repository::{RepoPath, repo_path},
+    repository::{IndexStage, RepoPath, repo_path},
     status::{StatusCode, TrackedStatus},
 };
 use git2::RepositoryInitOptions;
@@ -4102,6 +4103,148 @@ async fn test_save_file(cx: &mut gpui::TestAppContext) {
     assert_eq!(new_text, buffer.update(cx, |buffer, _| buffer.text()));
 }
 
+#[gpui::test]
+async fn test_reload_and_save_with_encoding(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({})).await;
+    // These bytes are valid UTF-8, so the file is opened as such, but they
+    // were written as Latin-1.
+    fs.insert_file(path!("/dir/file.txt"), b"\xc3\xa9t\xc3\xa9\n".to_vec())
+        .await;
+
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer(path!("/dir/file.txt"), cx))
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "été\n");
+        assert!(buffer.encoding().is_utf8());
+    });
+
+    let latin1 = fs::Encoding::from_name("latin1", false).unwrap();
+    buffer
+        .update(cx, |buffer, cx| {
+            buffer.set_encoding(latin1, cx);
+            buffer.reload(cx)
+        })
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "Ã©tÃ©\n");
+        assert_eq!(buffer.encoding(), latin1);
+        assert!(!buffer.is_dirty());
+    });
+
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "¡")], None, cx));
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load_bytes(Path::new(path!("/dir/file.txt")))
+            .await
+            .unwrap(),
+        b"\xa1\xc3\xa9t\xc3\xa9\n"
+    );
+}
+
+#[gpui::test]
+async fn test_file_history_restores_overwritten_contents(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+
+    // The history database is shared by every test, so the file's path is
+    // unique to this one.
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/file_history"),
+        json!({ "file.txt": "uncommitted work\n" }),
+    )
+    .await;
+    let abs_path = PathBuf::from(path!("/file_history/file.txt"));
+
+    let project = Project::test(fs.clone(), [path!("/file_history").as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer(&abs_path, cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, cx| {
+        buffer.set_text("overwritten by accident\n", cx)
+    });
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    cx.run_until_parked();
+    FILE_HISTORY.write(|_| ()).await;
+
+    // The contents that the save overwrote were recorded before the saved
+    // ones, even though they were never saved from Zed.
+    let snapshots = FILE_HISTORY.snapshots(&abs_path).unwrap();
+    let contents = snapshots
+        .iter()
+        .map(|snapshot| {
+            FILE_HISTORY
+                .snapshot_contents(snapshot.id)
+                .unwrap()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        contents,
+        ["overwritten by accident\n", "uncommitted work\n"]
+    );
+
+    buffer.update(cx, |buffer, cx| buffer.set_text(contents[1].as_str(), cx));
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load(Path::new(path!("/file_history/file.txt")))
+            .await
+            .unwrap(),
+        "uncommitted work\n"
+    );
+
+    // When the file changes on disk while the buffer has unsaved edits, what
+    // the save overwrites is read back from disk.
+    buffer.update(cx, |buffer, cx| buffer.set_text("more work\n", cx));
+    fs.save(
+        path!("/file_history/file.txt").as_ref(),
+        &"changed elsewhere\n".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.run_until_parked();
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    cx.run_until_parked();
+    FILE_HISTORY.write(|_| ()).await;
+
+    let snapshots = FILE_HISTORY.snapshots(&abs_path).unwrap();
+    let contents = snapshots
+        .iter()
+        .take(3)
+        .map(|snapshot| {
+            FILE_HISTORY
+                .snapshot_contents(snapshot.id)
+                .unwrap()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        contents,
+        ["more work\n", "changed elsewhere\n", "uncommitted work\n"]
+    );
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_save_file_spawns_language_server(cx: &mut gpui::TestAppContext) {
     // Issue: #24349
@@ -8322,6 +8465,71 @@ async fn test_home_dir_as_git_repository(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_load_conflict_stages(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/root"),
+        json!({
+            ".git": {},
+            "a.txt": "<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\n",
+            "b.txt": "<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\n",
+        }),
+    )
+    .await;
+    fs.set_conflict_stages_for_repo(
+        path!("/root/.git").as_ref(),
+        &[
+            ("a.txt", IndexStage::Base, "base\n".into()),
+            ("a.txt", IndexStage::Ours, "ours\n".into()),
+            ("a.txt", IndexStage::Theirs, "theirs\n".into()),
+            // Added on both sides, so there's no common version.
+            ("b.txt", IndexStage::Ours, "ours\n".into()),
+            ("b.txt", IndexStage::Theirs, "theirs\n".into()),
+        ],
+    );
+
+    let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+    project
+        .update(cx, |project, cx| project.git_scans_complete(cx))
+        .await;
+    cx.run_until_parked();
+    let repository = project.read_with(cx, |project, cx| {
+        project.repositories(cx).values().next().unwrap().clone()
+    });
+
+    let stages = repository
+        .update(cx, |repository, cx| {
+            repository.load_conflict_stages(repo_path("a.txt"), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        stages,
+        ConflictStages {
+            base: Some("base\n".into()),
+            ours: Some("ours\n".into()),
+            theirs: Some("theirs\n".into()),
+        }
+    );
+
+    let stages = repository
+        .update(cx, |repository, cx| {
+            repository.load_conflict_stages(repo_path("b.txt"), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        stages,
+        ConflictStages {
+            base: None,
+            ours: Some("ours\n".into()),
+            theirs: Some("theirs\n".into()),
+        }
+    );
+}
+
 #[gpui::test]
 async fn test_git_repository_status(cx: &mut gpui::TestAppContext) {
     init_test(cx);
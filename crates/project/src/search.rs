@@ -15,6 +15,10 @@ use std::{
 use text::Anchor;
 use util::paths::{PathMatcher, PathStyle};
 
+mod structural_pattern;
+
+pub use structural_pattern::{StructuralMatch, StructuralPattern};
+
 #[derive(Debug)]
 pub enum SearchResult {
     Buffer {
@@ -74,6 +78,13 @@ pub enum SearchQuery {
         one_match_per_line: bool,
         inner: SearchInputs,
     },
+    /// Matches syntax trees, using the grammar of each buffer's language.
+    Structural {
+        pattern: StructuralPattern,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 static WORD_MATCH_TEST: LazyLock<Regex> = LazyLock::new(|| {
@@ -199,6 +210,35 @@ impl SearchQuery {
         })
     }
 
+    /// Create a structural query, whose pattern is code in the language of
+    /// the searched buffers with `$NAME` and `$$$NAME` metavariables.
+    ///
+    /// See [`SearchQuery::text`] for the meaning of `match_full_paths`.
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        match_full_paths: bool,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(&query)?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            match_full_paths,
+            buffers,
+        };
+        Ok(Self::Structural {
+            pattern,
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     /// Extracts case sensitivity settings from pattern items in the provided
     /// query and returns the same query, with the pattern items removed.
     ///
@@ -263,7 +303,16 @@ impl SearchQuery {
             message.files_to_exclude
         };
 
-        if message.regex {
+        if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                PathMatcher::new(files_to_include, path_style)?,
+                PathMatcher::new(files_to_exclude, path_style)?,
+                message.match_full_paths,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -298,6 +347,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -311,6 +364,7 @@ impl SearchQuery {
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
@@ -360,17 +414,24 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structural { pattern, .. } => {
+                let mut text = String::new();
+                reader.read_to_string(&mut text)?;
+                Ok(pattern.may_match(&text))
+            }
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
+    ///
+    /// Structural queries need the syntax tree the match came from, see [`SearchQuery::structural_replacement_for`].
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
             SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
@@ -394,6 +455,25 @@ impl SearchQuery {
                     None
                 }
             }
+            SearchQuery::Structural { .. } => None,
+        }
+    }
+
+    /// Replaces a match of a structural query, substituting the metavariables
+    /// in the replacement with the text they matched. `range` is the range of
+    /// the match in `buffer`.
+    pub fn structural_replacement_for(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        match self {
+            SearchQuery::Structural {
+                pattern,
+                replacement: Some(replacement),
+                ..
+            } => pattern.replacement_for(buffer, range, replacement),
+            _ => None,
         }
     }
 
@@ -492,6 +572,16 @@ impl SearchQuery {
                     }
                 }
             }
+
+            Self::Structural { pattern, .. } => {
+                let range = range_offset..range_offset + rope.len();
+                matches.extend(
+                    pattern
+                        .find(buffer, range)
+                        .into_iter()
+                        .map(|mat| mat.range.start - range_offset..mat.range.end - range_offset),
+                );
+            }
         }
 
         matches
@@ -509,6 +599,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -516,6 +607,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
         }
     }
 
@@ -527,6 +619,9 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -534,6 +629,10 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -577,20 +676,22 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
         }
     }
 
     /// Whether this search should replace only one match per line, instead of
     /// all matches.
-    /// Returns `None` for text searches, as only regex searches support this
-    /// option.
+    /// Returns `None` for text and structural searches, as only regex searches
+    /// support this option.
     pub fn one_match_per_line(&self) -> Option<bool> {
         match self {
             Self::Regex {
                 one_match_per_line, ..
             } => Some(*one_match_per_line),
-            Self::Text { .. } => None,
+            Self::Text { .. } | Self::Structural { .. } => None,
         }
     }
 }
@@ -598,6 +699,8 @@ impl SearchQuery {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use gpui::AppContext as _;
+    use unindent::Unindent as _;
 
     #[test]
     fn path_matcher_creation_for_valid_paths() {
@@ -726,4 +829,95 @@ mod tests {
             "Case sensitivity should not be enabled when \\C pattern item is preceded by a backslash."
         );
     }
+
+    #[gpui::test]
+    async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+        let text = r#"
+            fn main() {
+                let a = foo.unwrap();
+                let b = foo.bar(1, 2).unwrap();
+                let c = foo.unwrap_or(0);
+                // foo.unwrap()
+                let d = compare(a, a) + compare(a, b);
+            }
+        "#
+        .unindent();
+        let buffer = cx.new(|cx| Buffer::local(&text, cx).with_language(language::rust_lang(), cx));
+        cx.run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+
+        let structural_query = |pattern: &str, replacement: &str| {
+            SearchQuery::structural(
+                pattern,
+                false,
+                Default::default(),
+                Default::default(),
+                false,
+                None,
+            )
+            .expect("Should be able to create a structural SearchQuery")
+            .with_replacement(replacement.to_string())
+        };
+        let search = async |query: &SearchQuery| {
+            let matches = query.search(&snapshot, None).await;
+            matches
+                .into_iter()
+                .map(|range| {
+                    let replacement = query
+                        .structural_replacement_for(&snapshot, range.clone())
+                        .unwrap();
+                    format!("{} => {replacement}", &text[range])
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let query = structural_query("$X.unwrap()", r#"$X.expect("TODO")"#);
+        assert_eq!(
+            search(&query).await,
+            [
+                r#"foo.unwrap() => foo.expect("TODO")"#,
+                r#"foo.bar(1, 2).unwrap() => foo.bar(1, 2).expect("TODO")"#,
+            ],
+            "Metavariables should match any expression, but not comments or other methods"
+        );
+
+        let query = structural_query("compare($A, $A)", "same($A)");
+        assert_eq!(
+            search(&query).await,
+            ["compare(a, a) => same(a)"],
+            "A metavariable used twice should match the same text both times"
+        );
+
+        let query = structural_query("foo.bar($$$ARGS)", "foo.baz($$$ARGS, 3)");
+        assert_eq!(
+            search(&query).await,
+            ["foo.bar(1, 2) => foo.baz(1, 2, 3)"],
+            "A sequence metavariable should match all the arguments"
+        );
+
+        let line_range = text.find("let b").unwrap()..text.find("let c").unwrap();
+        let query = structural_query("$X.unwrap()", "");
+        let matches = query.search(&snapshot, Some(line_range.clone())).await;
+        assert_eq!(
+            matches
+                .into_iter()
+                .map(|range| &text[line_range.start + range.start..line_range.start + range.end])
+                .collect::<Vec<_>>(),
+            ["foo.bar(1, 2).unwrap()"],
+            "Matches should be limited to the searched range"
+        );
+
+        assert!(
+            SearchQuery::structural(
+                "$X",
+                false,
+                Default::default(),
+                Default::default(),
+                false,
+                None,
+            )
+            .is_err(),
+            "A pattern can't consist of metavariables only"
+        );
+    }
 }
@@ -0,0 +1,441 @@
+use anyhow::{Result, anyhow};
+use collections::HashMap;
+use language::{BufferSnapshot, Language, LanguageName, Node, Tree, with_parser};
+use parking_lot::Mutex;
+use regex::{Captures, Regex};
+use std::{
+    ops::Range,
+    sync::{Arc, LazyLock},
+};
+
+/// Matches `$NAME`, `$$$NAME` and the `$_` wildcard in patterns and replacements.
+static METAVARIABLE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$(\$\$)?([A-Z_][A-Z0-9_]*)").unwrap());
+
+const SINGLE_PLACEHOLDER_PREFIX: &str = "__zed_var_";
+const MULTIPLE_PLACEHOLDER_PREFIX: &str = "__zed_vars_";
+const WILDCARD: &str = "_";
+
+/// A pattern that matches syntax trees rather than text.
+///
+/// The pattern is written as code in the language of the buffer being
+/// searched, where `$NAME` matches any single syntax node and `$$$NAME`
+/// matches any sequence of nodes. A metavariable used more than once must
+/// match the same text each time, unless it's the `$_` wildcard. The text
+/// each metavariable matched can be used in a replacement.
+///
+/// The pattern is parsed lazily with the grammar of each language searched.
+#[derive(Clone, Debug)]
+pub struct StructuralPattern {
+    source: Arc<str>,
+    /// Words and punctuation that any matching file must contain.
+    literals: Arc<[String]>,
+    compiled: Arc<Mutex<HashMap<LanguageName, Option<Arc<PatternNode>>>>>,
+}
+
+#[derive(Debug)]
+enum PatternNode {
+    Metavariable {
+        name: String,
+        multiple: bool,
+    },
+    Leaf {
+        kind_id: u16,
+        text: String,
+    },
+    Branch {
+        kind_id: u16,
+        children: Vec<PatternNode>,
+    },
+}
+
+/// A match of a [`StructuralPattern`], with the byte ranges each
+/// metavariable matched.
+#[derive(Debug)]
+pub struct StructuralMatch {
+    pub range: Range<usize>,
+    pub captures: HashMap<String, Range<usize>>,
+}
+
+impl StructuralPattern {
+    pub fn new(source: &str) -> Result<Self> {
+        let code = METAVARIABLE_REGEX.replace_all(source, " ");
+        if code.trim().is_empty() {
+            return Err(anyhow!(
+                "a structural pattern must contain code besides metavariables"
+            ));
+        }
+        let mut literals = Vec::new();
+        let mut word = String::new();
+        for c in code.chars() {
+            if c.is_alphanumeric() || c == '_' {
+                word.push(c);
+                continue;
+            }
+            if !word.is_empty() {
+                literals.push(std::mem::take(&mut word));
+            }
+            if !c.is_whitespace() {
+                literals.push(c.to_string());
+            }
+        }
+        if !word.is_empty() {
+            literals.push(word);
+        }
+        literals.sort();
+        literals.dedup();
+
+        Ok(Self {
+            source: source.into(),
+            literals: literals.into(),
+            compiled: Default::default(),
+        })
+    }
+
+    /// Whether the given text may contain a match, which is the case if it
+    /// contains everything in the pattern besides the metavariables.
+    pub fn may_match(&self, text: &str) -> bool {
+        self.literals
+            .iter()
+            .all(|literal| text.contains(literal.as_str()))
+    }
+
+    /// Finds the matches of the pattern that lie within the given range of
+    /// the buffer. Matches don't overlap: nodes inside a match aren't
+    /// searched.
+    pub fn find(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<StructuralMatch> {
+        let mut matches = Vec::new();
+        let mut has_layers = false;
+        for layer in buffer.syntax_layers_for_range(range.clone(), true) {
+            has_layers = true;
+            if let Some(pattern) = self.compile(layer.language) {
+                find_in_tree(&pattern, layer.node(), buffer, &range, &mut matches);
+            }
+        }
+
+        // The buffer may not have been parsed yet, such as when it has just
+        // been opened for a project search.
+        if !has_layers
+            && let Some(language) = buffer.language()
+            && let Some(pattern) = self.compile(language)
+            && let Some(tree) = parse(language, &buffer.text())
+        {
+            find_in_tree(&pattern, tree.root_node(), buffer, &range, &mut matches);
+        }
+
+        matches.sort_by_key(|mat| (mat.range.start, mat.range.end));
+        matches.dedup_by_key(|mat| mat.range.clone());
+        matches
+    }
+
+    /// Returns the replacement for the match at exactly the given range, with
+    /// the metavariables in `replacement` substituted by the text they matched.
+    pub fn replacement_for(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+        replacement: &str,
+    ) -> Option<String> {
+        let mat = self
+            .find(buffer, range.clone())
+            .into_iter()
+            .find(|mat| mat.range == range)?;
+        Some(
+            METAVARIABLE_REGEX
+                .replace_all(replacement, |captures: &Captures| {
+                    match mat.captures.get(&captures[2]) {
+                        Some(range) => buffer.text_for_range(range.clone()).collect::<String>(),
+                        None => captures[0].to_string(),
+                    }
+                })
+                .into_owned(),
+        )
+    }
+
+    fn compile(&self, language: &Arc<Language>) -> Option<Arc<PatternNode>> {
+        self.compiled
+            .lock()
+            .entry(language.name())
+            .or_insert_with(|| {
+                let pattern = compile(&self.source, language);
+                if let Err(error) = &pattern {
+                    log::debug!(
+                        "can't use structural pattern {:?} in {}: {error}",
+                        self.source,
+                        language.name()
+                    );
+                }
+                pattern.ok().map(Arc::new)
+            })
+            .clone()
+    }
+}
+
+fn parse(language: &Language, text: &str) -> Option<Tree> {
+    let grammar = language.grammar()?;
+    with_parser(|parser| {
+        parser.set_language(&grammar.ts_language).ok()?;
+        parser.parse(text, None)
+    })
+}
+
+fn compile(source: &str, language: &Language) -> Result<PatternNode> {
+    let code = METAVARIABLE_REGEX.replace_all(source, |captures: &Captures| {
+        if captures.get(1).is_some() {
+            format!("{MULTIPLE_PLACEHOLDER_PREFIX}{}", &captures[2])
+        } else {
+            format!("{SINGLE_PLACEHOLDER_PREFIX}{}", &captures[2])
+        }
+    });
+    let start = code.len() - code.trim_start().len();
+    let range = start..start + code.trim().len();
+
+    // Languages such as Rust only allow some expressions at the top level as
+    // statements, so a semicolon is added if the pattern doesn't parse alone.
+    for suffix in ["", ";"] {
+        let code = format!("{code}{suffix}");
+        let tree = parse(language, &code).ok_or_else(|| anyhow!("the language has no grammar"))?;
+        if contains_errors(tree.root_node()) {
+            continue;
+        }
+
+        // The pattern is the innermost node spanning all of its code, which
+        // skips the nodes wrapping it, such as the source file.
+        let mut root = tree.root_node();
+        while placeholder(root, &code).is_none()
+            && let Some(child) = significant_children(root)
+                .into_iter()
+                .find(|child| child.start_byte() <= range.start && range.end <= child.end_byte())
+        {
+            root = child;
+        }
+        if root.byte_range() != range {
+            return Err(anyhow!("the pattern must be a single syntax node"));
+        }
+        return match convert(root, &code) {
+            PatternNode::Metavariable { .. } => Err(anyhow!(
+                "the pattern must contain code besides metavariables"
+            )),
+            pattern => Ok(pattern),
+        };
+    }
+    Err(anyhow!("the pattern isn't valid code"))
+}
+
+/// Whether the tree has syntax errors, including nodes inserted by the
+/// parser's error recovery.
+fn contains_errors(root: Node<'_>) -> bool {
+    let mut cursor = root.walk();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.is_error() || node.is_missing() {
+            return true;
+        }
+        stack.extend(node.children(&mut cursor));
+    }
+    false
+}
+
+fn convert(node: Node<'_>, code: &str) -> PatternNode {
+    if let Some((name, multiple)) = placeholder(node, code) {
+        return PatternNode::Metavariable {
+            name: name.to_string(),
+            multiple,
+        };
+    }
+    let children = significant_children(node);
+    if children.is_empty() {
+        PatternNode::Leaf {
+            kind_id: node.kind_id(),
+            text: code[node.byte_range()].to_string(),
+        }
+    } else {
+        PatternNode::Branch {
+            kind_id: node.kind_id(),
+            children: children
+                .into_iter()
+                .map(|child| convert(child, code))
+                .collect(),
+        }
+    }
+}
+
+/// Returns the name of the metavariable the node stands for, and whether it
+/// matches a sequence of nodes.
+fn placeholder<'a>(node: Node<'_>, code: &'a str) -> Option<(&'a str, bool)> {
+    let text = &code[node.byte_range()];
+    let (name, multiple) = match text.strip_prefix(MULTIPLE_PLACEHOLDER_PREFIX) {
+        Some(name) => (name, true),
+        None => (text.strip_prefix(SINGLE_PLACEHOLDER_PREFIX)?, false),
+    };
+    let is_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    is_name.then_some((name, multiple))
+}
+
+/// The children of a node, without comments and nodes inserted by the
+/// parser's error recovery.
+fn significant_children(node: Node<'_>) -> Vec<Node<'_>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra() && !child.is_missing())
+        .collect()
+}
+
+fn find_in_tree(
+    pattern: &PatternNode,
+    root: Node<'_>,
+    buffer: &BufferSnapshot,
+    range: &Range<usize>,
+    matches: &mut Vec<StructuralMatch>,
+) {
+    let kind_id = match pattern {
+        PatternNode::Leaf { kind_id, .. } | PatternNode::Branch { kind_id, .. } => *kind_id,
+        PatternNode::Metavariable { .. } => return,
+    };
+
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.end_byte() <= range.start || node.start_byte() >= range.end {
+            continue;
+        }
+        if node.kind_id() == kind_id
+            && range.start <= node.start_byte()
+            && node.end_byte() <= range.end
+        {
+            let mut captures = HashMap::default();
+            if match_node(pattern, node, buffer, &mut captures) {
+                matches.push(StructuralMatch {
+                    range: node.byte_range(),
+                    captures,
+                });
+                continue;
+            }
+        }
+        let mut cursor = node.walk();
+        let children = node.children(&mut cursor).collect::<Vec<_>>();
+        // Visit the children in order, so that earlier matches win.
+        stack.extend(children.into_iter().rev());
+    }
+}
+
+fn match_node(
+    pattern: &PatternNode,
+    node: Node<'_>,
+    buffer: &BufferSnapshot,
+    captures: &mut HashMap<String, Range<usize>>,
+) -> bool {
+    match pattern {
+        PatternNode::Metavariable { name, .. } => {
+            node.is_named() && capture(name, node.byte_range(), buffer, captures)
+        }
+        PatternNode::Leaf { kind_id, text } => {
+            node.kind_id() == *kind_id && text_equals(buffer, node.byte_range(), text)
+        }
+        PatternNode::Branch { kind_id, children } => {
+            node.kind_id() == *kind_id
+                && match_sequence(
+                    children,
+                    &significant_children(node),
+                    node.start_byte(),
+                    buffer,
+                    captures,
+                )
+        }
+    }
+}
+
+/// Matches a sequence of sibling nodes, backtracking over the number of nodes
+/// each `$$$NAME` metavariable matches.
+fn match_sequence(
+    patterns: &[PatternNode],
+    nodes: &[Node<'_>],
+    position: usize,
+    buffer: &BufferSnapshot,
+    captures: &mut HashMap<String, Range<usize>>,
+) -> bool {
+    let Some((pattern, remaining_patterns)) = patterns.split_first() else {
+        return nodes.is_empty();
+    };
+
+    if let PatternNode::Metavariable {
+        name,
+        multiple: true,
+    } = pattern
+    {
+        for count in 0..=nodes.len() {
+            let range = match count {
+                0 => position..position,
+                count => nodes[0].start_byte()..nodes[count - 1].end_byte(),
+            };
+            let mut new_captures = captures.clone();
+            if capture(name, range.clone(), buffer, &mut new_captures)
+                && match_sequence(
+                    remaining_patterns,
+                    &nodes[count..],
+                    range.end,
+                    buffer,
+                    &mut new_captures,
+                )
+            {
+                *captures = new_captures;
+                return true;
+            }
+        }
+        return false;
+    }
+
+    let Some((node, remaining_nodes)) = nodes.split_first() else {
+        return false;
+    };
+    let mut new_captures = captures.clone();
+    if match_node(pattern, *node, buffer, &mut new_captures)
+        && match_sequence(
+            remaining_patterns,
+            remaining_nodes,
+            node.end_byte(),
+            buffer,
+            &mut new_captures,
+        )
+    {
+        *captures = new_captures;
+        true
+    } else {
+        false
+    }
+}
+
+/// Records what a metavariable matched, checking that it matches the same
+/// text as where it was used before.
+fn capture(
+    name: &str,
+    range: Range<usize>,
+    buffer: &BufferSnapshot,
+    captures: &mut HashMap<String, Range<usize>>,
+) -> bool {
+    if name == WILDCARD {
+        return true;
+    }
+    if let Some(captured_range) = captures.get(name) {
+        let captured_text = buffer
+            .text_for_range(captured_range.clone())
+            .collect::<String>();
+        return text_equals(buffer, range, &captured_text);
+    }
+    captures.insert(name.to_string(), range);
+    true
+}
+
+fn text_equals(buffer: &BufferSnapshot, range: Range<usize>, text: &str) -> bool {
+    if range.len() != text.len() {
+        return false;
+    }
+    let mut offset = 0;
+    buffer.text_for_range(range).all(|chunk| {
+        let equal = text[offset..].starts_with(chunk);
+        offset += chunk.len();
+        equal
+    })
+}
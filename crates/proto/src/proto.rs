@@ -324,6 +324,8 @@ messages!(
     (GetTreeDiffResponse, Background),
     (GetBlobContent, Background),
     (GetBlobContentResponse, Background),
+    (LoadConflictStages, Background),
+    (LoadConflictStagesResponse, Background),
     (GitClone, Background),
     (GitCloneResponse, Background),
     (ToggleLspLogs, Background),
@@ -514,6 +516,7 @@ request_messages!(
     (PullWorkspaceDiagnostics, Ack),
     (GetDefaultBranch, GetDefaultBranchResponse),
     (GetBlobContent, GetBlobContentResponse),
+    (LoadConflictStages, LoadConflictStagesResponse),
     (GetTreeDiff, GetTreeDiffResponse),
     (GitClone, GitCloneResponse),
     (ToggleLspLogs, Ack),
@@ -684,6 +687,7 @@ entity_messages!(
     GetDefaultBranch,
     GetTreeDiff,
     GetBlobContent,
+    LoadConflictStages,
     GitClone,
     GetAgentServerCommand,
     ExternalAgentsUpdated,
@@ -1079,6 +1079,8 @@ impl RulesLibrary {
                                     stop: Vec::new(),
                                     temperature: None,
                                     thinking_allowed: true,
+                                    response_format: None,
+                                    max_tokens: None,
                                 },
                                 cx,
                             )
@@ -3,7 +3,8 @@ mod registrar;
 use crate::{
     FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOption,
     SearchOptions, SearchSource, SelectAllMatches, SelectNextMatch, SelectPreviousMatch,
-    ToggleCaseSensitive, ToggleRegex, ToggleReplace, ToggleSelection, ToggleWholeWord,
+    ToggleCaseSensitive, ToggleRegex, ToggleReplace, ToggleSelection, ToggleStructural,
+    ToggleWholeWord,
     search_bar::{ActionButtonState, input_base_styles, render_action_button, render_text_input},
 };
 use any_vec::AnyVec;
@@ -145,6 +146,7 @@ impl Render for BufferSearchBar {
             case,
             word,
             regex,
+            structural,
             replacement,
             selection,
             find_in_results,
@@ -226,6 +228,13 @@ impl Render for BufferSearchBar {
                                 SearchSource::Buffer,
                                 focus_handle.clone(),
                             ))
+                        })
+                        .when(structural, |div| {
+                            div.child(SearchOption::Structural.as_button(
+                                self.search_options,
+                                SearchSource::Buffer,
+                                focus_handle.clone(),
+                            ))
                         }),
                 )
             });
@@ -540,6 +549,13 @@ impl BufferSearchBar {
                 this.toggle_regex(action, window, cx);
             }
         }));
+        registrar.register_handler(ForDeployed(
+            |this, action: &ToggleStructural, window, cx| {
+                if this.supported_options(cx).structural {
+                    this.toggle_structural(action, window, cx);
+                }
+            },
+        ));
         registrar.register_handler(ForDeployed(|this, action: &ToggleSelection, window, cx| {
             if this.supported_options(cx).selection {
                 this.toggle_selection(action, window, cx);
@@ -1173,6 +1189,15 @@ impl BufferSearchBar {
         self.toggle_search_option(SearchOptions::REGEX, window, cx)
     }
 
+    fn toggle_structural(
+        &mut self,
+        _: &ToggleStructural,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.toggle_search_option(SearchOptions::STRUCTURAL, window, cx)
+    }
+
     fn clear_active_searchable_item_matches(&mut self, window: &mut Window, cx: &mut App) {
         if let Some(active_searchable_item) = self.active_searchable_item.as_ref() {
             self.active_match_index = None;
@@ -1229,7 +1254,24 @@ impl BufferSearchBar {
                 } else {
                     // Value doesn't matter, we only construct empty matchers with it
 
-                    if self.search_options.contains(SearchOptions::REGEX) {
+                    if self.search_options.contains(SearchOptions::STRUCTURAL) {
+                        match SearchQuery::structural(
+                            query,
+                            false,
+                            PathMatcher::default(),
+                            PathMatcher::default(),
+                            false,
+                            None,
+                        ) {
+                            Ok(query) => query.with_replacement(self.replacement(cx)),
+                            Err(e) => {
+                                self.query_error = Some(e.to_string());
+                                self.clear_active_searchable_item_matches(window, cx);
+                                cx.notify();
+                                return done_rx;
+                            }
+                        }
+                    } else if self.search_options.contains(SearchOptions::REGEX) {
                         match SearchQuery::regex(
                             query,
                             self.search_options.contains(SearchOptions::WHOLE_WORD),
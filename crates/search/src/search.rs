@@ -37,6 +37,8 @@ actions!(
         ToggleIncludeIgnored,
         /// Toggles regular expression mode.
         ToggleRegex,
+        /// Toggles structural mode, matching syntax tree patterns with `$NAME` metavariables.
+        ToggleStructural,
         /// Toggles the replace interface.
         ToggleReplace,
         /// Toggles searching within selection only.
@@ -68,6 +70,7 @@ bitflags! {
         const CASE_SENSITIVE = 1 << SearchOption::CaseSensitive as u8;
         const INCLUDE_IGNORED = 1 << SearchOption::IncludeIgnored as u8;
         const REGEX = 1 << SearchOption::Regex as u8;
+        const STRUCTURAL = 1 << SearchOption::Structural as u8;
         const ONE_MATCH_PER_LINE = 1 << SearchOption::OneMatchPerLine as u8;
         /// If set, reverse direction when finding the active match
         const BACKWARDS = 1 << SearchOption::Backwards as u8;
@@ -81,6 +84,7 @@ pub enum SearchOption {
     CaseSensitive,
     IncludeIgnored,
     Regex,
+    Structural,
     OneMatchPerLine,
     Backwards,
 }
@@ -101,6 +105,7 @@ impl SearchOption {
             SearchOption::CaseSensitive => "Match Case Sensitivity",
             SearchOption::IncludeIgnored => "Also search files ignored by configuration",
             SearchOption::Regex => "Use Regular Expressions",
+            SearchOption::Structural => "Match Syntax Patterns",
             SearchOption::OneMatchPerLine => "One Match Per Line",
             SearchOption::Backwards => "Search Backwards",
         }
@@ -112,6 +117,7 @@ impl SearchOption {
             SearchOption::CaseSensitive => ui::IconName::CaseSensitive,
             SearchOption::IncludeIgnored => ui::IconName::Sliders,
             SearchOption::Regex => ui::IconName::Regex,
+            SearchOption::Structural => ui::IconName::ListTree,
             _ => panic!("{self:?} is not a named SearchOption"),
         }
     }
@@ -122,6 +128,7 @@ impl SearchOption {
             SearchOption::CaseSensitive => &ToggleCaseSensitive,
             SearchOption::IncludeIgnored => &ToggleIncludeIgnored,
             SearchOption::Regex => &ToggleRegex,
+            SearchOption::Structural => &ToggleStructural,
             _ => panic!("{self:?} is not a toggle action"),
         }
     }
@@ -173,6 +180,7 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::STRUCTURAL, query.is_structural());
         options
     }
 
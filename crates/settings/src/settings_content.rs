@@ -89,6 +89,9 @@ pub struct SettingsContent {
     /// Configuration for Diagnostics-related features.
     pub diagnostics: Option<DiagnosticsSettingsContent>,
 
+    /// Configuration for the local history of saved files.
+    pub file_history: Option<FileHistorySettingsContent>,
+
     /// Configuration for Git-related features
     pub git: Option<GitSettings>,
 
@@ -98,6 +101,9 @@ pub struct SettingsContent {
     /// The settings for the image viewer.
     pub image_viewer: Option<ImageViewerSettingsContent>,
 
+    /// The settings for the large file viewer.
+    pub large_file_viewer: Option<LargeFileViewerSettingsContent>,
+
     pub repl: Option<ReplSettingsContent>,
 
     /// Whether or not to enable Helix mode.
@@ -113,6 +119,12 @@ pub struct SettingsContent {
     /// Example: {"log": {"client": "warn"}}
     pub log: Option<HashMap<String, String>>,
 
+    /// Named editor macros, each a list of actions to dispatch and text to
+    /// insert, which can be run with `editor_macros::RunMacro`.
+    ///
+    /// Example: {"macros": {"quote_line": ["editor::MoveToBeginningOfLine", {"text": "> "}]}}
+    pub macros: Option<HashMap<String, Vec<MacroStepContent>>>,
+
     pub line_indicator_format: Option<LineIndicatorFormat>,
 
     pub language_models: Option<AllLanguageModelSettingsContent>,
@@ -861,6 +873,53 @@ pub struct ImageViewerSettingsContent {
     pub unit: Option<ImageFileSizeUnit>,
 }
 
+/// The settings for the local history of saved files.
+#[with_fallible_options]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, Default, PartialEq)]
+pub struct FileHistorySettingsContent {
+    /// Whether to keep a snapshot of a file's contents each time it's saved.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// Files larger than this many kilobytes aren't snapshotted.
+    ///
+    /// Default: 1024
+    pub max_file_size_kb: Option<u64>,
+    /// How many snapshots to keep for each file. Older ones are removed first.
+    ///
+    /// Default: 50
+    pub max_snapshots_per_file: Option<usize>,
+    /// Snapshots older than this many days are removed.
+    ///
+    /// Default: 30
+    pub max_age_days: Option<u64>,
+}
+
+/// The settings for the large file viewer.
+#[with_fallible_options]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, Default, PartialEq)]
+pub struct LargeFileViewerSettingsContent {
+    /// Files at least this many megabytes in size are opened in a read-only
+    /// viewer that doesn't load the whole file into memory, instead of in an
+    /// editor. Only applies to local projects.
+    ///
+    /// Default: 256
+    pub threshold_mb: Option<u64>,
+}
+
+/// A step of an editor macro.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema, MergeFrom)]
+#[serde(untagged)]
+pub enum MacroStepContent {
+    /// Inserts text as if it was typed.
+    Text { text: String },
+    /// Dispatches the action with the given name, e.g. "editor::MoveDown".
+    Action(String),
+    /// Dispatches the action with the given name and arguments, as in a
+    /// keymap, e.g. ["editor::MoveToBeginningOfLine", {"stop_at_indent": true}].
+    ActionWithArguments(String, serde_json::Value),
+}
+
 #[with_fallible_options]
 #[derive(
     Clone,
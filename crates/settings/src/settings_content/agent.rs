@@ -106,6 +106,26 @@ pub struct AgentSettingsContent {
     ///
     /// Default: 4
     pub message_editor_min_lines: Option<usize>,
+    /// The most, in US dollars, that a single agent thread may spend on
+    /// model requests before it's paused. Only models with known pricing
+    /// count towards this limit.
+    ///
+    /// Default: null
+    pub thread_budget: Option<f64>,
+    /// The most, in US dollars, that may be spent on model requests per day,
+    /// across agent threads and inline assists, before agent threads are
+    /// paused.
+    ///
+    /// Default: null
+    pub daily_budget: Option<f64>,
+    /// Prices used when tracking spend against `thread_budget` and
+    /// `daily_budget`, for models whose pricing isn't known or differs from
+    /// the built-in defaults. The last entry matching a model's provider and
+    /// name is used.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub model_pricing: Vec<LanguageModelPricingSetting>,
 }
 
 impl AgentSettingsContent {
@@ -177,6 +197,11 @@ pub struct AgentProfileContent {
     pub context_servers: IndexMap<Arc<str>, ContextServerPresetContent>,
     /// The default language model selected when using this profile.
     pub default_model: Option<LanguageModelSelection>,
+    /// Models to switch to, in order, when the thread's model keeps failing
+    /// or its context window is too small for the conversation.
+    ///
+    /// Default: []
+    pub fallback_models: Option<Vec<LanguageModelSelection>>,
 }
 
 #[with_fallible_options]
@@ -239,6 +264,22 @@ pub struct LanguageModelParameters {
     pub temperature: Option<f32>,
 }
 
+/// The price of a model, in US dollars per million tokens.
+#[with_fallible_options]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
+pub struct LanguageModelPricingSetting {
+    pub provider: LanguageModelProviderSetting,
+    pub model: SharedString,
+    /// Price of input tokens.
+    pub input: f64,
+    /// Price of output tokens.
+    pub output: f64,
+    /// Price of tokens read from the prompt cache. Defaults to the input price.
+    pub cache_read: Option<f64>,
+    /// Price of tokens written to the prompt cache. Defaults to the input price.
+    pub cache_write: Option<f64>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, MergeFrom)]
 pub struct LanguageModelProviderSetting(pub String);
 
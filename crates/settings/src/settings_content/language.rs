@@ -74,6 +74,7 @@ pub enum EditPredictionProvider {
     Supermaven,
     Zed,
     Codestral,
+    Local,
     Experimental(&'static str),
 }
 
@@ -92,6 +93,7 @@ impl<'de> Deserialize<'de> for EditPredictionProvider {
             Supermaven,
             Zed,
             Codestral,
+            Local,
             Experimental(String),
         }
 
@@ -101,6 +103,7 @@ impl<'de> Deserialize<'de> for EditPredictionProvider {
             Content::Supermaven => EditPredictionProvider::Supermaven,
             Content::Zed => EditPredictionProvider::Zed,
             Content::Codestral => EditPredictionProvider::Codestral,
+            Content::Local => EditPredictionProvider::Local,
             Content::Experimental(name) => {
                 if name == EXPERIMENTAL_SWEEP_EDIT_PREDICTION_PROVIDER_NAME {
                     EditPredictionProvider::Experimental(
@@ -125,6 +128,7 @@ impl EditPredictionProvider {
             | EditPredictionProvider::Copilot
             | EditPredictionProvider::Supermaven
             | EditPredictionProvider::Codestral
+            | EditPredictionProvider::Local
             | EditPredictionProvider::Experimental(_) => false,
         }
     }
@@ -145,6 +149,9 @@ pub struct EditPredictionSettingsContent {
     pub copilot: Option<CopilotSettingsContent>,
     /// Settings specific to Codestral.
     pub codestral: Option<CodestralSettingsContent>,
+    /// Settings specific to locally hosted models served by Ollama or an
+    /// OpenAI-compatible server.
+    pub local: Option<LocalEditPredictionSettingsContent>,
     /// Whether edit predictions are enabled in the assistant prompt editor.
     /// This has no effect if globally disabled.
     pub enabled_in_text_threads: Option<bool>,
@@ -186,6 +193,95 @@ pub struct CodestralSettingsContent {
     pub api_url: Option<String>,
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
+pub struct LocalEditPredictionSettingsContent {
+    /// The API spoken by the server at `api_url`.
+    ///
+    /// Default: "ollama"
+    #[serde(default)]
+    pub api: Option<LocalEditPredictionApi>,
+    /// Api URL to use for completions. For OpenAI-compatible servers this
+    /// should include the version prefix, e.g. "http://localhost:8080/v1".
+    ///
+    /// Default: "http://localhost:11434" for Ollama, "http://localhost:8080/v1"
+    /// for OpenAI-compatible servers
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// Model to use for completions. This should be a base (non-instruct)
+    /// model trained for fill-in-the-middle.
+    ///
+    /// Default: "qwen2.5-coder:1.5b-base"
+    #[serde(default)]
+    pub model: Option<String>,
+    /// The fill-in-the-middle prompt format understood by the model.
+    /// When unset, it is inferred from the model name.
+    ///
+    /// Default: null
+    #[serde(default)]
+    pub fim_template: Option<FimTemplate>,
+    /// Maximum tokens to generate.
+    ///
+    /// Default: 128
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+}
+
+/// The API used to request completions from a local model server.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    MergeFrom,
+    strum::VariantArray,
+    strum::VariantNames,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalEditPredictionApi {
+    /// Ollama's `/api/generate` endpoint.
+    #[default]
+    Ollama,
+    /// The `/completions` endpoint of an OpenAI-compatible server, such as
+    /// llama.cpp, vLLM or LM Studio.
+    OpenAiCompatible,
+}
+
+/// The special tokens a model uses to mark the prefix, suffix and middle of a
+/// fill-in-the-middle prompt.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Eq,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    MergeFrom,
+    strum::VariantArray,
+    strum::VariantNames,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum FimTemplate {
+    /// `<|fim_prefix|>`, `<|fim_suffix|>`, `<|fim_middle|>` (Qwen2.5-Coder).
+    Qwen,
+    /// `<fim_prefix>`, `<fim_suffix>`, `<fim_middle>` (StarCoder, StarCoder2).
+    StarCoder,
+    /// `<|fim_prefix|>`, `<|fim_suffix|>`, `<|fim_middle|>` (CodeGemma).
+    CodeGemma,
+    /// `<PRE>`, `<SUF>`, `<MID>` (Code Llama).
+    CodeLlama,
+    /// `<｜fim▁begin｜>`, `<｜fim▁hole｜>`, `<｜fim▁end｜>` (DeepSeek Coder).
+    DeepSeek,
+    /// `[SUFFIX]`, `[PREFIX]` (Codestral).
+    Codestral,
+}
+
 /// The mode in which edit predictions should be displayed.
 #[derive(
     Copy,
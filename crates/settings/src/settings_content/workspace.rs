@@ -411,6 +411,11 @@ pub struct StatusBarSettingsContent {
     ///
     /// Default: false
     pub line_endings_button: Option<bool>,
+    /// Whether to show the encoding button in the status bar for files that
+    /// aren't UTF-8.
+    ///
+    /// Default: true
+    pub encoding_button: Option<bool>,
 }
 
 #[derive(
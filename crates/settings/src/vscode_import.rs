@@ -184,6 +184,7 @@ impl VsCodeSettings {
             editor: self.editor_settings_content(),
             extension: ExtensionSettingsContent::default(),
             file_finder: None,
+            file_history: None,
             git: self.git_settings_content(),
             git_panel: self.git_panel_settings_content(),
             global_lsp_settings: None,
@@ -191,8 +192,10 @@ impl VsCodeSettings {
             image_viewer: None,
             journal: None,
             language_models: None,
+            large_file_viewer: None,
             line_indicator_format: None,
             log: None,
+            macros: None,
             message_editor: None,
             node: self.node_binary_settings(),
             notification_panel: None,
@@ -645,6 +648,7 @@ impl VsCodeSettings {
             active_language_button: None,
             cursor_position_button: None,
             line_endings_button: None,
+            encoding_button: None,
         })
     }
 
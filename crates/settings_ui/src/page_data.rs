@@ -2755,6 +2755,28 @@ pub(crate) fn settings_data(cx: &App) -> Vec<SettingsPage> {
                     metadata: None,
                     files: USER,
                 }),
+                SettingsPageItem::SettingItem(SettingItem {
+                    title: "Encoding Button",
+                    description: "Show the encoding button in the status bar for files that aren't UTF-8.",
+                    field: Box::new(SettingField {
+                        json_path: Some("status_bar.encoding_button"),
+                        pick: |settings_content| {
+                            settings_content
+                                .status_bar
+                                .as_ref()?
+                                .encoding_button
+                                .as_ref()
+                        },
+                        write: |settings_content, value| {
+                            settings_content
+                                .status_bar
+                                .get_or_insert_default()
+                                .encoding_button = value;
+                        },
+                    }),
+                    metadata: None,
+                    files: USER,
+                }),
                 SettingsPageItem::SettingItem(SettingItem {
                     title: "Terminal Button",
                     description: "Show the terminal button in the status bar.",
@@ -5935,6 +5957,24 @@ pub(crate) fn settings_data(cx: &App) -> Vec<SettingsPage> {
                             metadata: None,
                             files: USER | PROJECT,
                         }),
+                        SettingsPageItem::SettingItem(SettingItem {
+                            title: "Local Provider",
+                            description: "Use a model hosted on Ollama or an OpenAI-compatible server as your edit prediction provider.",
+                            field: Box::new(
+                                SettingField {
+                                    json_path: Some("edit_prediction.local_provider"),
+                                    pick: |settings_content| {
+                                        settings_content.project.all_languages.edit_predictions.as_ref()?.local.as_ref()
+                                    },
+                                    write: |settings_content, value| {
+                                        settings_content.project.all_languages.edit_predictions.get_or_insert_default().local = value;
+                                    },
+                                }
+                                .unimplemented(),
+                            ),
+                            metadata: None,
+                            files: USER | PROJECT,
+                        }),
                     ]
                 );
                 items
@@ -7050,6 +7090,86 @@ fn language_settings_data() -> Vec<SettingsPageItem> {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Large File Threshold",
+                description: "Files at least this many megabytes in size are opened in a read-only viewer.",
+                field: Box::new(SettingField {
+                    json_path: Some("large_file_viewer.threshold_mb"),
+                    pick: |settings_content| {
+                        settings_content.large_file_viewer.as_ref().and_then(|large_file_viewer| large_file_viewer.threshold_mb.as_ref())
+                    },
+                    write: |settings_content, value| {
+                        settings_content.large_file_viewer.get_or_insert_default().threshold_mb = value;
+
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "File History",
+                description: "Whether to keep a snapshot of a file's contents each time it's saved.",
+                field: Box::new(SettingField {
+                    json_path: Some("file_history.enabled"),
+                    pick: |settings_content| {
+                        settings_content.file_history.as_ref().and_then(|file_history| file_history.enabled.as_ref())
+                    },
+                    write: |settings_content, value| {
+                        settings_content.file_history.get_or_insert_default().enabled = value;
+
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "File History Max File Size",
+                description: "Files larger than this many kilobytes aren't snapshotted.",
+                field: Box::new(SettingField {
+                    json_path: Some("file_history.max_file_size_kb"),
+                    pick: |settings_content| {
+                        settings_content.file_history.as_ref().and_then(|file_history| file_history.max_file_size_kb.as_ref())
+                    },
+                    write: |settings_content, value| {
+                        settings_content.file_history.get_or_insert_default().max_file_size_kb = value;
+
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "File History Max Snapshots",
+                description: "How many snapshots to keep for each file.",
+                field: Box::new(SettingField {
+                    json_path: Some("file_history.max_snapshots_per_file"),
+                    pick: |settings_content| {
+                        settings_content.file_history.as_ref().and_then(|file_history| file_history.max_snapshots_per_file.as_ref())
+                    },
+                    write: |settings_content, value| {
+                        settings_content.file_history.get_or_insert_default().max_snapshots_per_file = value;
+
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "File History Max Age",
+                description: "Snapshots older than this many days are removed.",
+                field: Box::new(SettingField {
+                    json_path: Some("file_history.max_age_days"),
+                    pick: |settings_content| {
+                        settings_content.file_history.as_ref().and_then(|file_history| file_history.max_age_days.as_ref())
+                    },
+                    write: |settings_content, value| {
+                        settings_content.file_history.get_or_insert_default().max_age_days = value;
+
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
             SettingsPageItem::SettingItem(SettingItem {
                 title: "Auto Replace Emoji Shortcode",
                 description: "Whether to automatically replace emoji shortcodes with emoji characters.",
@@ -1414,6 +1414,7 @@ impl SearchableItem for TerminalView {
             case: false,
             word: false,
             regex: true,
+            structural: false,
             replacement: false,
             selection: false,
             find_in_results: false,
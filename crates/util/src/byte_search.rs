@@ -0,0 +1,135 @@
+//! Searching for byte strings in a [`ByteSource`] without reading all of it
+//! into memory at once.
+
+use std::{borrow::Cow, ops::Range};
+
+/// Bytes that can be read a range at a time, so that files don't have to be
+/// loaded into memory to be indexed, shown or searched.
+pub trait ByteSource {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads the bytes in `range`. Fewer bytes are returned if the source ends
+    /// before `range.end`.
+    fn read(&self, range: Range<usize>) -> Cow<'_, [u8]>;
+}
+
+impl<T: AsRef<[u8]> + ?Sized> ByteSource for T {
+    fn len(&self) -> usize {
+        self.as_ref().len()
+    }
+
+    fn read(&self, range: Range<usize>) -> Cow<'_, [u8]> {
+        let bytes = self.as_ref();
+        let end = range.end.min(bytes.len());
+        Cow::Borrowed(&bytes[range.start.min(end)..end])
+    }
+}
+
+/// How many bytes are searched at a time.
+const SEARCH_WINDOW_LEN: usize = 4 * 1024 * 1024;
+
+/// Finds the first occurrence of `query` at or after `offset`, wrapping
+/// around to the start of the file.
+pub fn find_next(
+    contents: &(impl ByteSource + ?Sized),
+    query: &[u8],
+    offset: usize,
+) -> Option<Range<usize>> {
+    if query.is_empty() {
+        return None;
+    }
+    let offset = offset.min(contents.len());
+    let start = find_in(contents, query, offset..contents.len()).or_else(|| {
+        let end = (offset + query.len() - 1).min(contents.len());
+        find_in(contents, query, 0..end)
+    })?;
+    Some(start..start + query.len())
+}
+
+/// Finds the last occurrence of `query` that ends at or before `offset`,
+/// wrapping around to the end of the file.
+pub fn find_previous(
+    contents: &(impl ByteSource + ?Sized),
+    query: &[u8],
+    offset: usize,
+) -> Option<Range<usize>> {
+    if query.is_empty() {
+        return None;
+    }
+    let offset = offset.min(contents.len());
+    let start = rfind_in(contents, query, 0..offset).or_else(|| {
+        let start = offset.saturating_sub(query.len() - 1);
+        rfind_in(contents, query, start..contents.len())
+    })?;
+    Some(start..start + query.len())
+}
+
+/// Finds the first occurrence of `query` within `range`, reading a window at a
+/// time. Windows overlap so that matches spanning two of them are found.
+fn find_in(
+    contents: &(impl ByteSource + ?Sized),
+    query: &[u8],
+    range: Range<usize>,
+) -> Option<usize> {
+    let mut start = range.start;
+    while start < range.end {
+        let end = (start + SEARCH_WINDOW_LEN + query.len() - 1).min(range.end);
+        let window = contents.read(start..end);
+        if let Some(ix) = memchr::memmem::find(&window, query) {
+            return Some(start + ix);
+        }
+        if end == range.end || window.len() < end - start {
+            break;
+        }
+        start += SEARCH_WINDOW_LEN;
+    }
+    None
+}
+
+/// Finds the last occurrence of `query` within `range`, reading a window at a
+/// time from the end.
+fn rfind_in(
+    contents: &(impl ByteSource + ?Sized),
+    query: &[u8],
+    range: Range<usize>,
+) -> Option<usize> {
+    let mut end = range.end;
+    while end > range.start {
+        let start = end
+            .saturating_sub(SEARCH_WINDOW_LEN + query.len() - 1)
+            .max(range.start);
+        let window = contents.read(start..end);
+        if let Some(ix) = memchr::memmem::rfind(&window, query) {
+            return Some(start + ix);
+        }
+        if start == range.start {
+            break;
+        }
+        end -= SEARCH_WINDOW_LEN;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_wraps_around() {
+        let contents = b"abc abc abc";
+        assert_eq!(find_next(contents, b"abc", 0), Some(0..3));
+        assert_eq!(find_next(contents, b"abc", 1), Some(4..7));
+        assert_eq!(find_next(contents, b"abc", 9), Some(0..3));
+        assert_eq!(find_next(contents, b"xyz", 0), None);
+        assert_eq!(find_next(contents, b"", 0), None);
+
+        assert_eq!(find_previous(contents, b"abc", 11), Some(8..11));
+        assert_eq!(find_previous(contents, b"abc", 10), Some(4..7));
+        assert_eq!(find_previous(contents, b"abc", 2), Some(8..11));
+        assert_eq!(find_previous(contents, b"xyz", 11), None);
+    }
+}
@@ -1,5 +1,6 @@
 pub mod arc_cow;
 pub mod archive;
+pub mod byte_search;
 pub mod command;
 pub mod fs;
 pub mod markdown;
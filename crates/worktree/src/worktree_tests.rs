@@ -715,6 +715,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
                 rel_path("tracked-dir/file.txt").into(),
                 "hello".into(),
                 Default::default(),
+                Default::default(),
                 cx,
             )
         })
@@ -726,6 +727,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
                 rel_path("ignored-dir/file.txt").into(),
                 "world".into(),
                 Default::default(),
+                Default::default(),
                 cx,
             )
         })
@@ -743,6 +745,85 @@ async fn test_write_file(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_load_and_save_with_encoding(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree("/root", json!({})).await;
+    // "Déjà vu: café crème et naïveté à la française." in Latin-1.
+    fs.insert_file(
+        "/root/latin1.txt",
+        b"D\xe9j\xe0 vu: caf\xe9 cr\xe8me et na\xefvet\xe9 \xe0 la fran\xe7aise.\n".to_vec(),
+    )
+    .await;
+    fs.insert_file(
+        "/root/utf16.txt",
+        b"\xff\xfeh\x00\xe9\x00l\x00l\x00o\x00\n\x00".to_vec(),
+    )
+    .await;
+
+    let tree = Worktree::local(
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    let loaded = tree
+        .update(cx, |tree, cx| tree.load_file(rel_path("latin1.txt"), cx))
+        .await
+        .unwrap();
+    assert_eq!(loaded.encoding.name(), "windows-1252");
+    assert_eq!(
+        loaded.text,
+        "Déjà vu: café crème et naïveté à la française.\n"
+    );
+    let text = loaded.text.replace("café", "thé");
+    tree.update(cx, |tree, cx| {
+        tree.write_file(
+            rel_path("latin1.txt").into(),
+            text.as_str().into(),
+            Default::default(),
+            loaded.encoding,
+            cx,
+        )
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        fs.load_bytes(Path::new("/root/latin1.txt")).await.unwrap(),
+        b"D\xe9j\xe0 vu: th\xe9 cr\xe8me et na\xefvet\xe9 \xe0 la fran\xe7aise.\n"
+    );
+
+    let loaded = tree
+        .update(cx, |tree, cx| tree.load_file(rel_path("utf16.txt"), cx))
+        .await
+        .unwrap();
+    assert_eq!(loaded.encoding.name(), "UTF-16LE");
+    assert!(loaded.encoding.has_bom());
+    assert_eq!(loaded.text, "héllo\n");
+    tree.update(cx, |tree, cx| {
+        tree.write_file(
+            rel_path("utf16.txt").into(),
+            "héllo wörld\n".into(),
+            Default::default(),
+            loaded.encoding,
+            cx,
+        )
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        fs.load_bytes(Path::new("/root/utf16.txt")).await.unwrap(),
+        b"\xff\xfeh\x00\xe9\x00l\x00l\x00o\x00 \x00w\x00\xf6\x00r\x00l\x00d\x00\n\x00"
+    );
+}
+
 #[gpui::test]
 async fn test_file_scan_inclusions(cx: &mut TestAppContext) {
     init_test(cx);
@@ -1843,8 +1924,13 @@ fn randomly_mutate_worktree(
                 })
             } else {
                 log::info!("overwriting file {:?} ({})", &entry.path, entry.id.0);
-                let task =
-                    worktree.write_file(entry.path.clone(), "".into(), Default::default(), cx);
+                let task = worktree.write_file(
+                    entry.path.clone(),
+                    "".into(),
+                    Default::default(),
+                    Default::default(),
+                    cx,
+                );
                 cx.background_spawn(async move {
                     task.await?;
                     Ok(())
@@ -595,6 +595,10 @@ pub fn main() {
 
         editor::init(cx);
         image_viewer::init(cx);
+        large_file_viewer::init(cx);
+        hex_editor::init(cx);
+        csv_preview::init(cx);
+        editor_macros::init(cx);
         repl::notebook::init(cx);
         diagnostics::init(cx);
 
@@ -603,7 +607,9 @@ pub fn main() {
         ui_prompt::init(cx);
 
         go_to_line::init(cx);
+        bookmarks_ui::init(cx);
         file_finder::init(cx);
+        file_history_ui::init(cx);
         tab_switcher::init(cx);
         outline::init(cx);
         project_symbols::init(cx);
@@ -618,6 +624,7 @@ pub fn main() {
         journal::init(app_state.clone(), cx);
         language_selector::init(cx);
         line_ending_selector::init(cx);
+        encoding_selector::init(cx);
         toolchain_selector::init(cx);
         theme_selector::init(cx);
         settings_profile_selector::init(cx);
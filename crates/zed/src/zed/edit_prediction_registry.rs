@@ -7,6 +7,7 @@ use feature_flags::FeatureFlagAppExt;
 use gpui::{AnyWindowHandle, App, AppContext as _, Context, Entity, WeakEntity};
 use language::language_settings::{EditPredictionProvider, all_language_settings};
 use language_models::MistralLanguageModelProvider;
+use local_edit_prediction::LocalCompletionProvider;
 use settings::{EXPERIMENTAL_SWEEP_EDIT_PREDICTION_PROVIDER_NAME, SettingsStore};
 use std::{cell::RefCell, rc::Rc, sync::Arc};
 use supermaven::{Supermaven, SupermavenCompletionProvider};
@@ -203,6 +204,13 @@ fn assign_edit_prediction_provider(
             let provider = cx.new(|_| CodestralCompletionProvider::new(http_client));
             editor.set_edit_prediction_provider(Some(provider), window, cx);
         }
+        EditPredictionProvider::Local => {
+            let http_client = client.http_client();
+            let project = editor.project().cloned();
+            let provider =
+                cx.new(|cx| LocalCompletionProvider::new(http_client, project.as_ref(), cx));
+            editor.set_edit_prediction_provider(Some(provider), window, cx);
+        }
         value @ (EditPredictionProvider::Experimental(_) | EditPredictionProvider::Zed) => {
             let zeta2 = zeta2::Zeta::global(client, &user_store, cx);
 
@@ -203,6 +203,19 @@ pub mod workspace {
     );
 }
 
+pub mod hex_editor {
+    use gpui::actions;
+
+    actions!(
+        hex_editor,
+        [
+            /// Opens the active file in the hex editor, to view and edit its
+            /// raw bytes.
+            OpenInHexEditor
+        ]
+    );
+}
+
 pub mod git {
     use gpui::actions;
 
@@ -1578,6 +1578,7 @@ impl Zeta {
                     tools: vec![],
                     prompt_cache_key: None,
                     reasoning_effort: None,
+                    response_format: None,
                 };
 
                 log::trace!("Sending edit prediction request");
@@ -2018,6 +2019,7 @@ impl Zeta {
             }],
             prompt_cache_key: None,
             reasoning_effort: None,
+            response_format: None,
         };
 
         #[cfg(feature = "eval-support")]
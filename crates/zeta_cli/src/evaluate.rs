@@ -14,10 +14,10 @@ use zeta2::{Zeta, udiff::DiffLine};
 use crate::{
     EvaluateArguments, PredictionOptions,
     example::{Example, NamedExample},
-    headless::ZetaCliAppState,
     paths::print_run_data_dir,
     predict::{PredictionDetails, perform_predict, setup_zeta},
 };
+use headless::HeadlessAppState;
 
 #[derive(Debug)]
 pub(crate) struct ExecutionData {
@@ -28,7 +28,7 @@ pub(crate) struct ExecutionData {
 
 pub async fn run_evaluate(
     args: EvaluateArguments,
-    app_state: &Arc<ZetaCliAppState>,
+    app_state: &Arc<HeadlessAppState>,
     cx: &mut AsyncApp,
 ) {
     if args.example_paths.is_empty() {
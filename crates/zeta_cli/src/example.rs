@@ -9,7 +9,6 @@ use std::{
     sync::{Arc, OnceLock},
 };
 
-use crate::headless::ZetaCliAppState;
 use anyhow::{Context as _, Result, anyhow};
 use clap::ValueEnum;
 use cloud_zeta2_prompt::CURSOR_MARKER;
@@ -21,6 +20,7 @@ use futures::{
 };
 use futures::{FutureExt as _, future::Shared};
 use gpui::{AsyncApp, Entity, Task, http_client::Url};
+use headless::HeadlessAppState;
 use language::{Anchor, Buffer};
 use project::{Project, ProjectPath};
 use pulldown_cmark::CowStr;
@@ -320,7 +320,7 @@ impl NamedExample {
 
     pub async fn setup_project(
         &self,
-        app_state: &Arc<ZetaCliAppState>,
+        app_state: &Arc<HeadlessAppState>,
         cx: &mut AsyncApp,
     ) -> Result<Entity<Project>> {
         let worktree_path = self.setup_worktree().await?;
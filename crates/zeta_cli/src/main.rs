@@ -1,6 +1,5 @@
 mod evaluate;
 mod example;
-mod headless;
 mod paths;
 mod predict;
 mod source_location;
@@ -10,7 +9,6 @@ mod util;
 use crate::{
     evaluate::run_evaluate,
     example::{ExampleFormat, NamedExample},
-    headless::ZetaCliAppState,
     predict::run_predict,
     source_location::SourceLocation,
     syntax_retrieval_stats::retrieval_stats,
@@ -24,6 +22,7 @@ use edit_prediction_context::{
     EditPredictionContextOptions, EditPredictionExcerptOptions, EditPredictionScoreOptions,
 };
 use gpui::{Application, AsyncApp, Entity, prelude::*};
+use headless::HeadlessAppState;
 use language::{Bias, Buffer, BufferSnapshot, Point};
 use project::{Project, Worktree};
 use reqwest_client::ReqwestClient;
@@ -293,7 +292,7 @@ struct LoadedContext {
 
 async fn load_context(
     args: &ContextArgs,
-    app_state: &Arc<ZetaCliAppState>,
+    app_state: &Arc<HeadlessAppState>,
     cx: &mut AsyncApp,
 ) -> Result<LoadedContext> {
     let ContextArgs {
@@ -376,7 +375,7 @@ async fn load_context(
 
 async fn zeta2_syntax_context(
     args: ContextArgs,
-    app_state: &Arc<ZetaCliAppState>,
+    app_state: &Arc<HeadlessAppState>,
     cx: &mut AsyncApp,
 ) -> Result<String> {
     let LoadedContext {
@@ -433,7 +432,7 @@ async fn zeta2_syntax_context(
 
 async fn zeta1_context(
     args: ContextArgs,
-    app_state: &Arc<ZetaCliAppState>,
+    app_state: &Arc<HeadlessAppState>,
     cx: &mut AsyncApp,
 ) -> Result<zeta::GatherContextOutput> {
     let LoadedContext {
@@ -469,7 +468,7 @@ fn main() {
     let app = Application::headless().with_http_client(http_client);
 
     app.run(move |cx| {
-        let app_state = Arc::new(headless::init(cx));
+        let app_state = Arc::new(headless::init("Zeta CLI", env!("ZED_PKG_VERSION"), cx));
         cx.spawn(async move |cx| {
             match args.command {
                 None => {
@@ -1,5 +1,4 @@
 use crate::example::{ActualExcerpt, ExpectedExcerpt, NamedExample};
-use crate::headless::ZetaCliAppState;
 use crate::paths::{CACHE_DIR, LATEST_EXAMPLE_RUN_DIR, RUN_DIR, print_run_data_dir};
 use crate::{
     CacheMode, PredictArguments, PredictionOptions, PredictionProvider, PredictionsOutputFormat,
@@ -10,6 +9,7 @@ use cloud_zeta2_prompt::{CURSOR_MARKER, write_codeblock};
 use collections::HashMap;
 use futures::StreamExt as _;
 use gpui::{AppContext, AsyncApp, Entity};
+use headless::HeadlessAppState;
 use language::{Anchor, Buffer, Point};
 use project::Project;
 use project::buffer_store::BufferStoreEvent;
@@ -25,7 +25,7 @@ use zeta2::{EvalCache, EvalCacheEntryKind, EvalCacheKey, Zeta};
 
 pub async fn run_predict(
     args: PredictArguments,
-    app_state: &Arc<ZetaCliAppState>,
+    app_state: &Arc<HeadlessAppState>,
     cx: &mut AsyncApp,
 ) {
     let example = NamedExample::load(args.example_path).unwrap();
@@ -43,7 +43,7 @@ pub async fn run_predict(
 pub fn setup_zeta(
     provider: PredictionProvider,
     project: &Entity<Project>,
-    app_state: &Arc<ZetaCliAppState>,
+    app_state: &Arc<HeadlessAppState>,
     cx: &mut AsyncApp,
 ) -> Result<Entity<Zeta>> {
     let zeta =
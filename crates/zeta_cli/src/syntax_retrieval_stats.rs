@@ -34,13 +34,13 @@ use std::{
 use util::paths::PathStyle;
 use zeta2::ContextMode;
 
-use crate::headless::ZetaCliAppState;
 use crate::source_location::SourceLocation;
 use crate::util::{open_buffer, open_buffer_with_language_server};
+use headless::HeadlessAppState;
 
 pub async fn retrieval_stats(
     worktree: PathBuf,
-    app_state: Arc<ZetaCliAppState>,
+    app_state: Arc<HeadlessAppState>,
     only_extension: Option<String>,
     file_limit: Option<usize>,
     skip_files: Option<usize>,